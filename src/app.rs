@@ -2,8 +2,9 @@
 //! Libadwaita `AdwApplication` setup.
 
 use std::{
+    collections::HashMap,
     env::{var, var_os},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{Arc, atomic::AtomicBool},
 };
 
@@ -28,18 +29,43 @@ use {
 };
 
 use crate::{
+    config::shortcuts::ShortcutSettings,
     library::{
         artwork::check_cache_version,
+        cache::set_cache_directory,
+        dr_batch::DrBatchStatus,
+        scan_status::{ScanStatus, track_scan_status},
         scanner::{FsScanner, ScanEvent},
-        watcher::{LibraryWatcher, WatcherEvent},
+        scrobble::spawn_scrobbler,
+        undo::UndoStack,
+        watcher::{LibraryWatcher, WatcherEvent, config::WatcherConfig},
+    },
+    playback::{
+        control::PlaybackController,
+        engine::{
+            PlaybackEngine,
+            PlaybackEvent::{self, Paused, Resumed, Stopped, TrackStarted},
+        },
+        output::startup_device_check,
     },
-    playback::{engine::PlaybackEngine, output::startup_device_check},
     storage::{
+        AlbumSearch, Storage,
+        browse::BrowseFilter,
         database::SqliteStorage,
+        session::{SavedSession, clear_session, restore_session, save_session},
         settings::{ActiveTab, ViewMode},
+        sort_order::{ArtistSortOrder, SortOrder},
+        zoom::ZoomLevel,
+    },
+    threading::{ThreadManager, scheduler::BackgroundScheduler},
+    ui::{
+        CoverArtCache,
+        equalizer::apply_equalizer_settings,
+        errors::{ErrorReporter, ErrorSource::Library},
+        player::track_info::{CurrentTrack, PlaybackSnapshot, TrackInfo},
+        rich_presence::spawn_rich_presence,
+        window::build_window,
     },
-    threading::ThreadManager,
-    ui::{CoverArtCache, window::build_window},
 };
 
 /// Application identifier for D-Bus and resource paths.
@@ -73,8 +99,26 @@ pub struct AppState {
     pub refresh_tx: TokioSender<()>,
     /// Broadcasts view mode changes (grid/column) to library views.
     pub view_mode_tx: TokioSender<ViewMode>,
+    /// Broadcasts album grid zoom level changes to library views.
+    pub zoom_level_tx: TokioSender<ZoomLevel>,
     /// Broadcasts active tab changes (albums/artists) to the UI.
     pub active_tab_tx: TokioSender<ActiveTab>,
+    /// Broadcasts keyboard shortcut changes to the key handler.
+    pub shortcuts_tx: TokioSender<ShortcutSettings>,
+    /// Current album search; album views show only matching albums.
+    pub album_search_tx: TokioSender<AlbumSearch>,
+    /// Order of albums in the library views.
+    pub album_sort_tx: TokioSender<SortOrder>,
+    /// Order of artists in the artist views.
+    pub artist_sort_tx: TokioSender<ArtistSortOrder>,
+    /// Debounce and batch limits of the running library watcher.
+    pub watcher_config_tx: TokioSender<WatcherConfig>,
+    /// Whether a library scan is running and its progress.
+    pub scan_status_tx: TokioSender<ScanStatus>,
+    /// Phase and progress of the library-wide DR analysis.
+    pub dr_batch_tx: TokioSender<DrBatchStatus>,
+    /// Playing track with its details, kept by `follow_current_track`.
+    pub current_track_tx: TokioSender<Option<CurrentTrack>>,
     /// Channel sender for forwarding scan events to the UI (status bar).
     pub scan_event_tx: Sender<ScanEvent>,
     /// Channel receiver for consuming scan events (cloned for each subscriber).
@@ -83,6 +127,8 @@ pub struct AppState {
     pub toast_tx: Sender<String>,
     /// Channel receiver for toast notifications.
     pub toast_rx: Receiver<String>,
+    /// Central sink showing background failures to the user as toasts.
+    pub error_reporter: ErrorReporter,
     /// Flag set while the user is dragging the seek bar. Prevents the polling
     /// timer from fighting the user's drag position and avoids redundant seeks.
     pub is_seeking: Arc<AtomicBool>,
@@ -94,6 +140,10 @@ pub struct AppState {
     pub cover_art_cache: Arc<CoverArtCache>,
     /// Thread lifecycle manager for named OS threads.
     pub thread_manager: Arc<ThreadManager>,
+    /// Shared budget for scanning, analysis, and cover decoding.
+    pub scheduler: Arc<BackgroundScheduler>,
+    /// Undo stack for removing missing files and library directories.
+    pub undo: UndoStack,
 }
 
 impl AppState {
@@ -104,6 +154,66 @@ impl AppState {
         }
     }
 
+    /// Show a toast notification, logging if it cannot be queued.
+    pub async fn send_toast(&self, message: String) {
+        if let Err(e) = self.toast_tx.send(message).await {
+            warn!(error = %e, "Failed to enqueue toast notification");
+        }
+    }
+
+    /// Show a toast notification from a signal handler or other
+    /// synchronous code.
+    ///
+    /// The toast channel is unbounded, so this never waits.
+    pub fn show_toast(&self, message: String) {
+        if let Err(e) = self.toast_tx.try_send(message) {
+            warn!(error = %e, "Failed to enqueue toast notification");
+        }
+    }
+
+    /// What is playing: the track with its details, status, and position.
+    ///
+    /// Status and position are read from the engine, so they are current
+    /// when called. The track is left out while its details are looked up.
+    pub fn current_playback(&self) -> PlaybackSnapshot {
+        let engine = self.playback.state();
+        let track = self
+            .current_track_tx
+            .borrow()
+            .as_ref()
+            .filter(|t| engine.current_track_id == Some(t.track_id))
+            .cloned();
+        PlaybackSnapshot {
+            track,
+            status: engine.status,
+            position: engine.elapsed_seconds,
+            duration: engine.duration_seconds,
+        }
+    }
+
+    /// Wait for the details of `track_id` once it is playing.
+    ///
+    /// # Returns
+    ///
+    /// The details, or `None` if another track starts or playback stops
+    /// before they are known.
+    pub async fn playing_track_info(&self, track_id: i64) -> Option<TrackInfo> {
+        let mut current_rx = self.current_track_tx.subscribe();
+        let Ok(current) = current_rx
+            .wait_for(|current| {
+                current.as_ref().is_some_and(|t| t.track_id == track_id)
+                    || self.playback.state().current_track_id != Some(track_id)
+            })
+            .await
+        else {
+            return None;
+        };
+        current
+            .as_ref()
+            .filter(|t| t.track_id == track_id)
+            .map(|t| t.info.clone())
+    }
+
     /// Construct a new `AppState` with all fields explicitly provided.
     pub fn new(
         playback: Arc<PlaybackEngine>,
@@ -112,6 +222,7 @@ impl AppState {
         channels: AppChannels,
         broadcast: BroadcastChannels,
         thread_manager: Arc<ThreadManager>,
+        scheduler: Arc<BackgroundScheduler>,
     ) -> Self {
         Self {
             playback,
@@ -119,16 +230,28 @@ impl AppState {
             scanner,
             refresh_tx: broadcast.refresh,
             view_mode_tx: broadcast.view_mode,
+            zoom_level_tx: broadcast.zoom_level,
             active_tab_tx: broadcast.active_tab,
+            shortcuts_tx: broadcast.shortcuts,
+            album_search_tx: broadcast.album_search,
+            album_sort_tx: broadcast.album_sort,
+            artist_sort_tx: broadcast.artist_sort,
+            watcher_config_tx: broadcast.watcher_config,
+            scan_status_tx: broadcast.scan_status,
+            dr_batch_tx: channel(DrBatchStatus::default()).0,
+            current_track_tx: channel(None).0,
             scan_event_tx: channels.scan_event_tx,
             scan_event_rx: channels.scan_event_rx,
+            error_reporter: ErrorReporter::new(channels.toast_tx.clone()),
             toast_tx: channels.toast_tx,
             toast_rx: channels.toast_rx,
             is_seeking: Arc::new(AtomicBool::new(false)),
             navigation_tx: channels.navigation_tx,
             navigation_rx: channels.navigation_rx,
-            cover_art_cache: CoverArtCache::new_shared(&thread_manager),
+            cover_art_cache: CoverArtCache::new_shared(&thread_manager, &scheduler),
             thread_manager,
+            scheduler,
+            undo: UndoStack::new(),
         }
     }
 }
@@ -139,17 +262,33 @@ pub struct BroadcastChannels {
     pub refresh: TokioSender<()>,
     /// Broadcasts view mode changes (grid/column) to the UI.
     pub view_mode: TokioSender<ViewMode>,
+    /// Broadcasts album grid zoom level changes to the UI.
+    pub zoom_level: TokioSender<ZoomLevel>,
     /// Broadcasts active tab changes (albums/artists) to the UI.
     pub active_tab: TokioSender<ActiveTab>,
+    /// Broadcasts keyboard shortcut changes to the key handler.
+    pub shortcuts: TokioSender<ShortcutSettings>,
+    /// Holds the current album search.
+    pub album_search: TokioSender<AlbumSearch>,
+    /// Holds the current album sort order.
+    pub album_sort: TokioSender<SortOrder>,
+    /// Holds the current artist sort order.
+    pub artist_sort: TokioSender<ArtistSortOrder>,
+    /// Holds the debounce and batch limits of the library watcher.
+    pub watcher_config: TokioSender<WatcherConfig>,
+    /// Holds the status of the running library scan.
+    pub scan_status: TokioSender<ScanStatus>,
 }
 
 /// Events for navigating between library views and detail pages.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum NavigationEvent {
     /// Navigate to the album detail page.
     AlbumDetail(i64),
     /// Navigate to the artist detail page.
     ArtistDetail(i64),
+    /// Navigate to the albums of a genre or decade.
+    BrowseAlbums(BrowseFilter),
     /// Go back to the library grid view.
     Back,
 }
@@ -211,17 +350,120 @@ fn data_dir() -> PathBuf {
 }
 
 /// Run the filesystem watcher loop in the background.
+///
+/// The library directories of `storage` are watched first. Events are then
+/// debounced and scanned in batches. Watcher errors are also shown to the
+/// user through `reporter`.
 fn spawn_watcher_loop(
-    watcher: LibraryWatcher<SqliteStorage>,
+    mut watcher: LibraryWatcher<SqliteStorage>,
     mut watcher_rx: UnboundedReceiver<WatcherEvent>,
+    storage: Arc<SqliteStorage>,
+    reporter: ErrorReporter,
 ) {
     spawn(async move {
+        watch_library_directories(&mut watcher, &storage, &reporter).await;
         while let Some(event) = watcher_rx.recv().await {
-            watcher.process_event(event).await;
+            let batch = watcher.collect_batch(event, &mut watcher_rx).await;
+            report_watcher_errors(&reporter, &batch);
+            watcher.process_batch(batch).await;
         }
     });
 }
 
+/// Watch the enabled library directories, reporting those that cannot be watched.
+async fn watch_library_directories(
+    watcher: &mut LibraryWatcher<SqliteStorage>,
+    storage: &SqliteStorage,
+    reporter: &ErrorReporter,
+) {
+    let directories: Vec<PathBuf> = match storage.list_library_directories().await {
+        Ok(dirs) => dirs
+            .into_iter()
+            .filter(|d| d.enabled)
+            .map(|d| PathBuf::from(d.path))
+            .collect(),
+        Err(e) => {
+            warn!(error = %e, "Failed to load library directories to watch");
+            return;
+        }
+    };
+    for error in watcher.watch_directories(&directories) {
+        reporter.report(Library, &error.to_string());
+    }
+}
+
+/// Show the watcher errors among `events` to the user.
+fn report_watcher_errors(reporter: &ErrorReporter, events: &[WatcherEvent]) {
+    for event in events {
+        if let WatcherEvent::Error { error } = event {
+            reporter.report(Library, error);
+        }
+    }
+}
+
+/// Keep the background scheduler informed of whether audio is playing.
+///
+/// The scheduler reserves one core for the decode thread while playback
+/// is active so background work never starves the audio path.
+fn spawn_audio_activity_tracker(playback: &PlaybackEngine, scheduler: Arc<BackgroundScheduler>) {
+    spawn(track_audio_activity(playback.subscribe(), scheduler));
+}
+
+/// Forward playback state changes to the scheduler until the engine closes.
+async fn track_audio_activity(rx: Receiver<PlaybackEvent>, scheduler: Arc<BackgroundScheduler>) {
+    while let Ok(event) = rx.recv().await {
+        match event {
+            TrackStarted { .. } | Resumed => scheduler.set_audio_active(true),
+            Paused | Stopped => scheduler.set_audio_active(false),
+            _ => {}
+        }
+    }
+}
+
+/// Restore the queue saved at the last shutdown, without starting playback.
+///
+/// Tracks removed from the library since are dropped from the queue.
+async fn restore_saved_queue(playback: &PlaybackEngine, storage: &SqliteStorage, path: &Path) {
+    let Some(session) = restore_session(path) else {
+        return;
+    };
+    let tracks = match storage.get_tracks_by_ids(&session.track_ids).await {
+        Ok(tracks) => tracks,
+        Err(e) => {
+            warn!(error = %e, "Failed to load the tracks of the saved queue");
+            return;
+        }
+    };
+    let paths: HashMap<i64, PathBuf> = tracks
+        .iter()
+        .map(|t| (t.id, PathBuf::from(&t.audio.file_path)))
+        .collect();
+    let kept_before = |index: usize| {
+        session
+            .track_ids
+            .iter()
+            .take(index)
+            .filter(|id| paths.contains_key(id))
+            .count()
+    };
+    let position = session.current_index.map_or(0, kept_before);
+    let track_ids: Vec<i64> = session
+        .track_ids
+        .iter()
+        .copied()
+        .filter(|id| paths.contains_key(id))
+        .collect();
+    if track_ids.is_empty() {
+        return;
+    }
+
+    let queue_len = track_ids.len();
+    playback.set_track_paths(paths);
+    playback.queue().set_queue(track_ids);
+    let current = playback.queue().jump_to(position.min(queue_len - 1));
+    info!(queue_len, current, "Restored saved queue");
+}
+
 /// Check artwork cache version and test audio device at startup.
 async fn run_startup_checks() {
     if let Err(e) = spawn_blocking(check_cache_version).await {
@@ -262,25 +504,107 @@ pub async fn run_application() -> Result<()> {
             .await
             .context("Failed to initialize storage")?,
     );
+    set_cache_directory(storage.get_cache_directory());
 
     let playback = Arc::new(PlaybackEngine::new());
+    if let Err(e) = playback.set_volume(storage.get_settings_volume()) {
+        warn!(error = %e, "Failed to apply saved volume");
+    }
+    if let Err(e) = playback.set_gapless_enabled(storage.get_gapless_enabled()) {
+        warn!(error = %e, "Failed to apply saved gapless setting");
+    }
+    if let Err(e) = playback.set_crossfade_ms(storage.get_crossfade_ms()) {
+        warn!(error = %e, "Failed to apply saved crossfade setting");
+    }
+    if let Err(e) = playback.set_inter_track_gap_ms(storage.get_inter_track_gap_ms()) {
+        warn!(error = %e, "Failed to apply saved inter-track gap");
+    }
+    let leading_silence_db = storage
+        .get_skip_leading_silence()
+        .then(|| storage.get_leading_silence_db());
+    if let Err(e) = playback.set_leading_silence_db(leading_silence_db) {
+        warn!(error = %e, "Failed to apply saved leading silence skip");
+    }
+    if let Err(e) = playback.set_fade_ms(storage.get_fade_ms()) {
+        warn!(error = %e, "Failed to apply saved fade setting");
+    }
+    if let Err(e) = playback.set_follow_source_rate(storage.get_follow_source_rate()) {
+        warn!(error = %e, "Failed to apply saved sample-rate follow setting");
+    }
+    if let Err(e) = playback.set_resample_quality(storage.get_resample_quality()) {
+        warn!(error = %e, "Failed to apply saved resampler quality");
+    }
+    if let Err(e) = playback.set_buffer_frames(storage.get_output_buffer_frames()) {
+        warn!(error = %e, "Failed to apply saved output buffer size");
+    }
+    if let Err(e) = playback.set_downmix(storage.get_downmix()) {
+        warn!(error = %e, "Failed to apply saved channel mode");
+    }
+    if let Err(e) = playback.set_balance(storage.get_balance()) {
+        warn!(error = %e, "Failed to apply saved balance");
+    }
+    if let Err(e) = playback.set_remember_playback_rate(storage.get_remember_playback_rate()) {
+        warn!(error = %e, "Failed to apply saved playback speed setting");
+    }
+    apply_equalizer_settings(&playback, &storage.get_equalizer());
+    playback.set_level_meter_enabled(storage.get_show_level_meter());
+    let session_path = db_dir.join("session.json");
+    if storage.get_resume_on_startup() {
+        restore_saved_queue(&playback, &storage, &session_path).await;
+    } else {
+        info!("Session restore turned off, starting with an empty queue");
+    }
+    let session_playback = Arc::clone(&playback);
+    let session_storage = Arc::clone(&storage);
+
+    let scheduler = Arc::new(BackgroundScheduler::new(storage.get_work_intensity()));
+    spawn_audio_activity_tracker(&playback, Arc::clone(&scheduler));
+    spawn_scrobbler(
+        &playback,
+        Arc::clone(&storage),
+        db_dir.join("scrobble_queue.json"),
+    );
+    spawn_rich_presence(&playback, Arc::clone(&storage));
 
     let (scan_event_tx, scan_event_rx) = unbounded();
+    let (scanner_event_tx, scanner_event_rx) = unbounded();
     let (toast_tx, toast_rx) = unbounded();
+    let scan_status_tx = channel(ScanStatus::default()).0;
+    spawn(track_scan_status(
+        scanner_event_rx,
+        scan_event_tx.clone(),
+        scan_status_tx.clone(),
+    ));
 
     let scanner = Arc::new(FsScanner::new(
         Arc::clone(&storage),
-        scan_event_tx.clone(),
-        4,
+        scanner_event_tx,
+        Arc::clone(&scheduler),
     ));
+    scanner.set_prefer_sidecar_artwork(storage.get_prefer_sidecar_artwork());
+    scanner.set_follow_symlinks(storage.get_follow_symlinks());
+    scanner.set_metadata_timeout(storage.get_metadata_timeout());
+    scanner.set_scan_threads(storage.get_scan_threads());
 
+    let (watcher_config_tx, watcher_config_rx) = channel(storage.get_watcher_config());
     match LibraryWatcher::new(Arc::clone(&scanner)) {
-        Ok((watcher, watcher_rx)) => spawn_watcher_loop(watcher, watcher_rx),
+        Ok((watcher, watcher_rx)) => spawn_watcher_loop(
+            watcher
+                .with_poll_interval(storage.get_watch_poll_interval())
+                .with_config(watcher_config_rx),
+            watcher_rx,
+            Arc::clone(&storage),
+            ErrorReporter::new(toast_tx.clone()),
+        ),
         Err(e) => warn!(error = %e, "Failed to create filesystem watcher"),
     }
 
     let initial_view_mode = storage.get_view_mode();
+    let initial_zoom_level = storage.get_zoom_level();
     let initial_active_tab = storage.get_active_tab();
+    let initial_shortcuts = storage.get_shortcuts();
+    let initial_album_sort = storage.get_album_sort();
+    let initial_artist_sort = storage.get_artist_sort();
 
     let (navigation_tx, navigation_rx) = unbounded();
 
@@ -298,7 +622,14 @@ pub async fn run_application() -> Result<()> {
     let broadcast = BroadcastChannels {
         refresh: channel(()).0,
         view_mode: channel(initial_view_mode).0,
+        zoom_level: channel(initial_zoom_level).0,
         active_tab: channel(initial_active_tab).0,
+        shortcuts: channel(initial_shortcuts).0,
+        album_search: channel(AlbumSearch::default()).0,
+        album_sort: channel(initial_album_sort).0,
+        artist_sort: channel(initial_artist_sort).0,
+        watcher_config: watcher_config_tx,
+        scan_status: scan_status_tx,
     };
 
     let state = Arc::new(AppState::new(
@@ -308,6 +639,7 @@ pub async fn run_application() -> Result<()> {
         channels,
         broadcast,
         Arc::clone(&thread_manager),
+        scheduler,
     ));
 
     let app = Application::builder().application_id(APP_ID).build();
@@ -319,6 +651,17 @@ pub async fn run_application() -> Result<()> {
 
     info!("Starting application");
     app.run();
+    let saved = if session_storage.get_resume_on_startup() {
+        save_session(
+            &session_path,
+            &SavedSession::capture(session_playback.queue()),
+        )
+    } else {
+        clear_session(&session_path)
+    };
+    if let Err(e) = saved {
+        warn!(error = %e, "Failed to save the playback queue");
+    }
     thread_manager.shutdown();
 
     Ok(())
@@ -339,13 +682,17 @@ mod tests {
 
     use crate::{
         app::{AppChannels, AppState, BroadcastChannels},
-        library::scanner::FsScanner,
+        config::shortcuts::ShortcutSettings,
+        library::{scan_status::ScanStatus, scanner::FsScanner, watcher::config::WatcherConfig},
         playback::engine::PlaybackEngine,
         storage::{
+            AlbumSearch,
             database::SqliteStorage,
             settings::{ActiveTab::Albums, ViewMode::Grid},
+            sort_order::{ArtistSortOrder, SortOrder},
+            zoom::ZoomLevel::Medium,
         },
-        threading::ThreadManager,
+        threading::{ThreadManager, scheduler::BackgroundScheduler},
     };
 
     impl AppState {
@@ -364,6 +711,7 @@ mod tests {
                 .map_err(|e| anyhow!("{e:#}"))?;
 
             let scanner_storage = Arc::clone(&storage);
+            let scheduler = Arc::new(BackgroundScheduler::default());
 
             let (scan_event_tx, scan_event_rx) = unbounded();
             let (toast_tx, toast_rx) = unbounded();
@@ -382,7 +730,14 @@ mod tests {
             let broadcast = BroadcastChannels {
                 refresh: channel(()).0,
                 view_mode: channel(Grid).0,
+                zoom_level: channel(Medium).0,
                 active_tab: channel(Albums).0,
+                shortcuts: channel(ShortcutSettings::default()).0,
+                album_search: channel(AlbumSearch::default()).0,
+                album_sort: channel(SortOrder::Title).0,
+                artist_sort: channel(ArtistSortOrder::Name).0,
+                watcher_config: channel(WatcherConfig::default()).0,
+                scan_status: channel(ScanStatus::default()).0,
             };
 
             Ok(Self::new(
@@ -391,11 +746,12 @@ mod tests {
                 Arc::new(FsScanner::new(
                     scanner_storage,
                     channels.scan_event_tx.clone(),
-                    4,
+                    Arc::clone(&scheduler),
                 )),
                 channels,
                 broadcast,
                 Arc::new(ThreadManager::new()),
+                scheduler,
             ))
         }
     }
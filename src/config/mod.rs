@@ -0,0 +1,3 @@
+//! User-configurable behaviour persisted in the user settings file.
+
+pub mod shortcuts;
@@ -0,0 +1,228 @@
+//! Keyboard shortcut bindings.
+//!
+//! Each [`ShortcutAction`] maps to a GTK accelerator string such as `space`
+//! or `<Control>Right`; an empty string leaves the action unbound. Actions
+//! missing from a saved map fall back to their default accelerator, so new
+//! actions get bound without touching older settings files.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Player action that can be bound to a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ShortcutAction {
+    /// Open the album of the playing track.
+    GoToPlaying,
+    /// Switch between the main window and the mini player.
+    MiniPlayer,
+    /// Skip to the next track.
+    NextTrack,
+    /// Show or leave the fullscreen now playing view.
+    NowPlaying,
+    /// Toggle between playing and paused.
+    PlayPause,
+    /// Return to the previous track.
+    PreviousTrack,
+    /// Seek backward within the current track.
+    SeekBackward,
+    /// Seek backward within the current track by a longer step.
+    SeekBackwardLong,
+    /// Seek forward within the current track.
+    SeekForward,
+    /// Seek forward within the current track by a longer step.
+    SeekForwardLong,
+    /// Lower the volume one step.
+    VolumeDown,
+    /// Raise the volume one step.
+    VolumeUp,
+    /// Show larger album covers.
+    ZoomIn,
+    /// Show smaller album covers.
+    ZoomOut,
+}
+
+impl ShortcutAction {
+    /// Every action, in display order.
+    pub const ALL: [Self; 14] = [
+        Self::PlayPause,
+        Self::NextTrack,
+        Self::PreviousTrack,
+        Self::SeekForward,
+        Self::SeekBackward,
+        Self::SeekForwardLong,
+        Self::SeekBackwardLong,
+        Self::VolumeUp,
+        Self::VolumeDown,
+        Self::ZoomIn,
+        Self::ZoomOut,
+        Self::GoToPlaying,
+        Self::MiniPlayer,
+        Self::NowPlaying,
+    ];
+
+    /// Human-readable action name.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::GoToPlaying => "Go to Playing Album",
+            Self::MiniPlayer => "Mini Player",
+            Self::NextTrack => "Next Track",
+            Self::NowPlaying => "Now Playing",
+            Self::PlayPause => "Play / Pause",
+            Self::PreviousTrack => "Previous Track",
+            Self::SeekBackward => "Seek Backward",
+            Self::SeekBackwardLong => "Seek Backward Further",
+            Self::SeekForward => "Seek Forward",
+            Self::SeekForwardLong => "Seek Forward Further",
+            Self::VolumeDown => "Volume Down",
+            Self::VolumeUp => "Volume Up",
+            Self::ZoomIn => "Zoom In",
+            Self::ZoomOut => "Zoom Out",
+        }
+    }
+
+    /// Accelerator bound to the action out of the box.
+    #[must_use]
+    pub const fn default_accelerator(self) -> &'static str {
+        match self {
+            Self::GoToPlaying => "<Control>j",
+            Self::MiniPlayer => "<Control>m",
+            Self::NextTrack => "<Control>Right",
+            Self::NowPlaying => "F11",
+            Self::PlayPause => "space",
+            Self::PreviousTrack => "<Control>Left",
+            Self::SeekBackward => "Left",
+            Self::SeekBackwardLong => "<Shift>Left",
+            Self::SeekForward => "Right",
+            Self::SeekForwardLong => "<Shift>Right",
+            Self::VolumeDown => "Down",
+            Self::VolumeUp => "Up",
+            Self::ZoomIn => "<Control>equal",
+            Self::ZoomOut => "<Control>minus",
+        }
+    }
+}
+
+/// Accelerators chosen for each shortcut action.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShortcutSettings {
+    /// Accelerator per action; missing actions use their default.
+    pub bindings: BTreeMap<ShortcutAction, String>,
+}
+
+impl ShortcutSettings {
+    /// Accelerator bound to `action`, or `None` if it is unbound.
+    #[must_use]
+    pub fn accelerator(&self, action: ShortcutAction) -> Option<&str> {
+        let accel = self
+            .bindings
+            .get(&action)
+            .map_or(action.default_accelerator(), String::as_str);
+        (!accel.is_empty()).then_some(accel)
+    }
+
+    /// Bind `action` to `accelerator`; an empty string unbinds it.
+    pub fn set(&mut self, action: ShortcutAction, accelerator: &str) {
+        self.bindings.insert(action, accelerator.trim().to_string());
+    }
+
+    /// Other action already bound to the accelerator of `action`, if any.
+    #[must_use]
+    pub fn conflict_for(&self, action: ShortcutAction) -> Option<ShortcutAction> {
+        let accel = self.accelerator(action)?;
+        ShortcutAction::ALL
+            .into_iter()
+            .find(|&other| other != action && self.accelerator(other) == Some(accel))
+    }
+
+    /// Pairs of actions sharing the same accelerator.
+    #[must_use]
+    pub fn conflicts(&self) -> Vec<(ShortcutAction, ShortcutAction)> {
+        ShortcutAction::ALL
+            .into_iter()
+            .filter_map(|action| self.conflict_for(action).map(|other| (action, other)))
+            .filter(|(action, other)| action < other)
+            .collect()
+    }
+}
+
+impl Default for ShortcutSettings {
+    fn default() -> Self {
+        Self {
+            bindings: ShortcutAction::ALL
+                .into_iter()
+                .map(|a| (a, a.default_accelerator().to_string()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        anyhow::{Result, ensure},
+        serde_json::{from_str, to_string},
+    };
+
+    use crate::config::shortcuts::{
+        ShortcutAction::{self, NextTrack, PlayPause, SeekForward},
+        ShortcutSettings,
+    };
+
+    #[test]
+    fn defaults_have_no_conflicts() {
+        let settings = ShortcutSettings::default();
+        assert!(
+            settings.conflicts().is_empty(),
+            "Default bindings must be unique"
+        );
+        for action in ShortcutAction::ALL {
+            assert_eq!(
+                settings.accelerator(action),
+                Some(action.default_accelerator()),
+                "{action:?} must start on its default"
+            );
+        }
+    }
+
+    #[test]
+    fn rebinding_detects_conflicts_and_unbinds() {
+        let mut settings = ShortcutSettings::default();
+        settings.set(NextTrack, "space");
+        assert_eq!(
+            settings.conflict_for(NextTrack),
+            Some(PlayPause),
+            "Rebound key clashes with play/pause"
+        );
+        assert_eq!(
+            settings.conflicts(),
+            vec![(NextTrack, PlayPause)],
+            "Each clash is reported once"
+        );
+
+        settings.set(PlayPause, "");
+        assert_eq!(settings.accelerator(PlayPause), None, "Empty unbinds");
+        assert!(settings.conflicts().is_empty(), "Unbound keys never clash");
+    }
+
+    #[test]
+    fn missing_actions_fall_back_to_defaults() -> Result<()> {
+        let settings: ShortcutSettings = from_str(r#"{"bindings":{"PlayPause":"p"}}"#)?;
+        ensure!(
+            settings.accelerator(PlayPause) == Some("p"),
+            "saved key kept"
+        );
+        ensure!(
+            settings.accelerator(SeekForward) == Some("Right"),
+            "missing action uses its default"
+        );
+        let json = to_string(&settings)?;
+        ensure!(
+            json.contains("\"PlayPause\":\"p\""),
+            "keys use action names"
+        );
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 //! Library crate root — re-exports all public modules for integration testing.
 
 pub mod app;
+pub mod config;
 pub mod library;
 pub mod metrics;
 pub mod playback;
@@ -0,0 +1,89 @@
+//! Splitting artist credits into the collaborating artists.
+//!
+//! Album artist tags often name several artists, as in "Daft Punk feat.
+//! Pharrell Williams" or "Simon & Garfunkel". Each spelling becomes its own
+//! artist when scanning. When collaborations are split, the artist views
+//! list such albums under each credited artist that is in the library.
+
+/// Separators between credited artists, in lowercase.
+///
+/// Longer separators come first so " featuring " is not cut at " feat".
+const SEPARATORS: [&str; 8] = [
+    " featuring ",
+    " feat. ",
+    " feat ",
+    " ft. ",
+    " ft ",
+    " & ",
+    ", ",
+    "; ",
+];
+
+/// Names credited in `credit`, in order, without blanks.
+///
+/// A credit without separators is returned whole.
+#[must_use]
+pub fn split_artist_credit(credit: &str) -> Vec<&str> {
+    // ASCII lowercasing keeps byte offsets, so they index `credit` too.
+    let lower = credit.to_ascii_lowercase();
+    let mut names = Vec::new();
+    let mut start = 0;
+    for (i, _) in lower.char_indices() {
+        if i < start {
+            continue;
+        }
+        let separator = lower
+            .get(i..)
+            .and_then(|rest| SEPARATORS.iter().find(|s| rest.starts_with(**s)));
+        if let Some(separator) = separator {
+            names.push(credit.get(start..i).unwrap_or_default());
+            start = i + separator.len();
+        }
+    }
+    names.push(credit.get(start..).unwrap_or_default());
+    names
+        .into_iter()
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .collect()
+}
+
+/// Key under which artist names are matched, ignoring case.
+#[must_use]
+pub fn artist_key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::library::artist_credit::{artist_key, split_artist_credit};
+
+    #[test]
+    fn credits_split_on_features_and_joins() {
+        assert_eq!(
+            split_artist_credit("Daft Punk feat. Pharrell Williams"),
+            ["Daft Punk", "Pharrell Williams"],
+            "feat. separates"
+        );
+        assert_eq!(
+            split_artist_credit("A, B & C Featuring D"),
+            ["A", "B", "C", "D"],
+            "Every separator counts"
+        );
+    }
+
+    #[test]
+    fn names_containing_separator_letters_stay_whole() {
+        assert_eq!(
+            split_artist_credit("Fleetwood Mac"),
+            ["Fleetwood Mac"],
+            "No separator"
+        );
+        assert_eq!(
+            split_artist_credit("Lift Feature"),
+            ["Lift Feature"],
+            "Not feat."
+        );
+        assert_eq!(artist_key(" Björk "), "björk", "Keys ignore case");
+    }
+}
@@ -1,7 +1,15 @@
 //! Artwork extraction and caching from audio files.
+//!
+//! Album covers come from the pictures embedded in the tags or from a
+//! sidecar image such as `cover.jpg` next to the tracks, whichever the user
+//! prefers when both exist. A cover the user picks for an album is cached
+//! apart from both and takes their place.
 
 use std::{
-    fs::{create_dir_all, read_dir, read_to_string as fs_read_to_string, remove_file, write},
+    fs::{
+        create_dir_all, read as fs_read, read_dir, read_to_string as fs_read_to_string,
+        remove_file, write,
+    },
     io::ErrorKind::NotFound,
     path::{Path, PathBuf},
 };
@@ -14,17 +22,33 @@ use {
         read_from_path,
     },
     thiserror::Error,
-    tracing::warn,
+    tracing::{debug, warn},
 };
 
-use crate::app::dirs_cache_home;
-
-/// Subdirectory for cached artwork files.
-const ARTWORK_CACHE_DIR: &str = "oxhidifi/artwork";
+use crate::library::{
+    cache::{ARTWORK_CACHE_DIR, cache_subdir},
+    cue::split_cue_path,
+};
 
 /// File extensions to try when looking up cached artwork by key.
 const ARTWORK_EXTENSIONS: &[&str] = &["jpg", "png", "webp"];
 
+/// Image files in an artist's directory that picture the artist, in order
+/// of preference.
+const ARTIST_IMAGE_NAMES: &[&str] = &[
+    "artist.jpg",
+    "artist.jpeg",
+    "artist.png",
+    "artist.webp",
+    "folder.jpg",
+    "folder.jpeg",
+    "folder.png",
+];
+
+/// Base names of sidecar images showing an album's front cover, in order of
+/// preference. Matched without regard to case.
+const COVER_IMAGE_STEMS: &[&str] = &["cover", "folder", "front", "album"];
+
 /// Current cache format version.  Bump to force re-extraction of all artwork.
 const CACHE_VERSION: &str = "2";
 
@@ -37,6 +61,9 @@ pub enum ArtworkError {
     /// File not found or inaccessible.
     #[error("File not found or inaccessible: {0}")]
     FileNotFound(String),
+    /// The image is not in a format the artwork cache stores.
+    #[error("Unsupported image format: {0}")]
+    UnsupportedFormat(String),
 }
 
 /// Extract embedded artwork from an audio file.
@@ -81,15 +108,75 @@ pub fn extract_artwork(path: &Path) -> Result<Option<(Vec<u8>, String)>, Artwork
     Ok(Some((picture.data().to_vec(), ext)))
 }
 
+/// Find a sidecar cover image in the directory of a track.
+///
+/// Looks for a JPEG, PNG, or WebP file named `cover`, `folder`, `front`, or
+/// `album`, in that order of preference and ignoring case.
+#[must_use]
+pub fn find_sidecar_cover(track_path: &Path) -> Option<PathBuf> {
+    read_dir(track_path.parent()?)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| image_extension(path).is_some() && path.is_file())
+        .filter_map(|path| {
+            let stem = path.file_stem()?.to_str()?.to_ascii_lowercase();
+            let rank = COVER_IMAGE_STEMS.iter().position(|s| *s == stem)?;
+            Some((rank, path))
+        })
+        .min()
+        .map(|(_, path)| path)
+}
+
+/// Read the sidecar cover image in the directory of a track.
+///
+/// Returns the raw bytes and the cache extension, like [`extract_artwork`],
+/// or `None` if the directory holds no cover image.
+///
+/// # Errors
+///
+/// Returns [`ArtworkError`] if the image exists but cannot be read.
+pub fn read_sidecar_cover(track_path: &Path) -> Result<Option<(Vec<u8>, String)>, ArtworkError> {
+    let Some(path) = find_sidecar_cover(track_path) else {
+        return Ok(None);
+    };
+    let ext = image_extension(&path)
+        .ok_or_else(|| ArtworkError::UnsupportedFormat(path.display().to_string()))?;
+    let data = fs_read(&path)
+        .map_err(|e| ArtworkError::FileNotFound(format!("Cannot read {}: {e}", path.display())))?;
+    Ok(Some((data, ext.to_string())))
+}
+
+/// Find the front cover of the album a track belongs to.
+///
+/// Both the embedded artwork and a sidecar image are tried, with
+/// `prefer_sidecar` deciding which comes first. Unreadable artwork is
+/// logged and skipped. CUE tracks use the artwork of their audio file.
+#[must_use]
+pub fn find_album_artwork(track_path: &Path, prefer_sidecar: bool) -> Option<(Vec<u8>, String)> {
+    let (track_path, _) = split_cue_path(track_path);
+    type ArtworkSource = fn(&Path) -> Result<Option<(Vec<u8>, String)>, ArtworkError>;
+    let sources: [ArtworkSource; 2] = if prefer_sidecar {
+        [read_sidecar_cover, extract_artwork]
+    } else {
+        [extract_artwork, read_sidecar_cover]
+    };
+    sources.iter().find_map(|source| {
+        source(&track_path).unwrap_or_else(|e| {
+            debug!(error = %e, path = %track_path.display(), "Cannot read album artwork");
+            None
+        })
+    })
+}
+
 /// Ensure the artwork cache directory exists.
 ///
 /// # Errors
 ///
 /// Returns an error if the directory cannot be created.
 fn ensure_artwork_cache_dir() -> Result<PathBuf, ArtworkError> {
-    let cache_dir = dirs_cache_home()
-        .map_err(|e| ArtworkError::FileNotFound(format!("Cannot resolve XDG cache home: {e}")))?
-        .join(ARTWORK_CACHE_DIR);
+    let cache_dir = cache_subdir(ARTWORK_CACHE_DIR)
+        .map_err(|e| ArtworkError::FileNotFound(format!("Cannot resolve cache directory: {e}")))?;
 
     create_dir_all(&cache_dir).map_err(|e| {
         ArtworkError::FileNotFound(format!(
@@ -159,6 +246,78 @@ pub fn get_cached_artwork_path(key: &str) -> Option<PathBuf> {
         .find(|p| p.exists())
 }
 
+/// Find an image of the artist next to their albums.
+///
+/// Assumes the common `Artist/Album/track` layout and looks for an
+/// `artist.*` or `folder.*` image in the directory above the track's album
+/// directory. CUE tracks are looked up from their audio file.
+#[must_use]
+pub fn find_artist_image(track_path: &Path) -> Option<PathBuf> {
+    let (track_path, _) = split_cue_path(track_path);
+    let artist_dir = track_path.parent()?.parent()?;
+    ARTIST_IMAGE_NAMES
+        .iter()
+        .map(|name| artist_dir.join(name))
+        .find(|p| p.is_file())
+}
+
+/// Copy a user-chosen artist image into the artwork cache.
+///
+/// The image is stored under `artist-{artist_id}`, replacing any image
+/// cached for the artist before, and stays available if the source file
+/// is moved or deleted.
+///
+/// # Errors
+///
+/// Returns [`ArtworkError`] if the image is not a JPEG, PNG, or WebP file,
+/// or if it cannot be read or cached.
+pub fn cache_artist_image(artist_id: i64, source: &Path) -> Result<PathBuf, ArtworkError> {
+    cache_chosen_image(&format!("artist-{artist_id}"), source)
+}
+
+/// Copy a user-chosen album cover into the artwork cache.
+///
+/// The image is stored under `album-cover-{album_id}`, apart from the
+/// artwork found by the scanner, replacing any cover chosen before.
+///
+/// # Errors
+///
+/// Returns [`ArtworkError`] if the image is not a JPEG, PNG, or WebP file,
+/// or if it cannot be read or cached.
+pub fn cache_album_cover(album_id: i64, source: &Path) -> Result<PathBuf, ArtworkError> {
+    cache_chosen_image(&format!("album-cover-{album_id}"), source)
+}
+
+/// Copy a user-chosen image into the artwork cache under `key`.
+///
+/// # Errors
+///
+/// Returns [`ArtworkError`] if the image is not a JPEG, PNG, or WebP file,
+/// or if it cannot be read or cached.
+fn cache_chosen_image(key: &str, source: &Path) -> Result<PathBuf, ArtworkError> {
+    let ext = image_extension(source)
+        .ok_or_else(|| ArtworkError::UnsupportedFormat(source.display().to_string()))?;
+    let data = fs_read(source).map_err(|e| {
+        ArtworkError::FileNotFound(format!("Cannot read {}: {e}", source.display()))
+    })?;
+    if let Some(previous) = get_cached_artwork_path(key) {
+        remove_cache_file(&previous);
+    }
+    cache_artwork(key, &data, ext)
+}
+
+/// Cache extension for an image file, or `None` if it is not a supported
+/// image.
+fn image_extension(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "jpg" | "jpeg" => Some("jpg"),
+        "png" => Some("png"),
+        "webp" => Some("webp"),
+        _ => None,
+    }
+}
+
 /// Check and update the artwork cache version.
 ///
 /// If the stored version does not match [`CACHE_VERSION`], the artwork
@@ -212,7 +371,7 @@ fn read_to_string(path: &Path) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use std::{
-        fs::{create_dir_all, read},
+        fs::{create_dir_all, read, write},
         io::Write,
         path::Path,
     };
@@ -222,7 +381,10 @@ mod tests {
         tempfile::{NamedTempFile, tempdir},
     };
 
-    use crate::library::artwork::{cache_artwork_in, extract_artwork, get_cached_artwork_path};
+    use crate::library::artwork::{
+        cache_artist_image, cache_artwork_in, extract_artwork, find_album_artwork,
+        find_artist_image, find_sidecar_cover, get_cached_artwork_path, image_extension,
+    };
 
     fn has_cached_artwork_in(cache_dir: &Path, key: &str) -> bool {
         ["jpg", "png", "webp"]
@@ -273,4 +435,63 @@ mod tests {
         let path = get_cached_artwork_path("nonexistent-key");
         assert!(path.is_none());
     }
+
+    #[test]
+    fn find_artist_image_in_artist_directory() -> Result<()> {
+        let dir = tempdir()?;
+        let album_dir = dir.path().join("Artist/Album");
+        create_dir_all(&album_dir)?;
+        let track = album_dir.join("01.flac");
+        ensure!(find_artist_image(&track).is_none(), "no image yet");
+
+        write(dir.path().join("Artist/folder.jpg"), b"folder")?;
+        write(dir.path().join("Artist/artist.png"), b"artist")?;
+        ensure!(
+            find_artist_image(&track) == Some(dir.path().join("Artist/artist.png")),
+            "artist image preferred over folder image"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn sidecar_cover_prefers_cover_over_folder() -> Result<()> {
+        let dir = tempdir()?;
+        let track = dir.path().join("01.flac");
+        ensure!(find_sidecar_cover(&track).is_none(), "no image yet");
+
+        write(dir.path().join("Folder.JPG"), b"folder")?;
+        write(dir.path().join("back.jpg"), b"back")?;
+        ensure!(
+            find_sidecar_cover(&track) == Some(dir.path().join("Folder.JPG")),
+            "names match without regard to case"
+        );
+        write(dir.path().join("cover.png"), b"cover")?;
+        ensure!(
+            find_album_artwork(&track, false) == Some((b"cover".to_vec(), "png".to_string())),
+            "sidecar used when nothing is embedded"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn image_extension_normalizes_supported_formats() {
+        assert_eq!(
+            image_extension(Path::new("a/Photo.JPEG")),
+            Some("jpg"),
+            "jpeg"
+        );
+        assert_eq!(
+            image_extension(Path::new("a/photo.webp")),
+            Some("webp"),
+            "webp"
+        );
+        assert_eq!(image_extension(Path::new("a/photo.gif")), None, "gif");
+        assert_eq!(image_extension(Path::new("a/photo")), None, "no extension");
+    }
+
+    #[test]
+    fn cache_artist_image_rejects_unsupported_format() {
+        let result = cache_artist_image(1, Path::new("/nonexistent/artist.bmp"));
+        assert!(result.is_err(), "bmp is not cached");
+    }
 }
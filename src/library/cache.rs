@@ -0,0 +1,189 @@
+//! Location of the on-disk caches, and clearing them.
+//!
+//! Album artwork, cover thumbnails, and waveform peaks are cached under
+//! `$XDG_CACHE_HOME/oxhidifi` unless the user moved the cache elsewhere,
+//! for example to a faster disk. Each cache recreates its directory before
+//! writing, so clearing or moving the cache never breaks a feature in use.
+//! Clearing removes thumbnails and waveforms, which are rebuilt when next
+//! needed. Extracted artwork stays, as the library refers to those files.
+//! Moving the cache clears the old location the same way, so its
+//! rebuildable files are not left behind.
+
+use std::{
+    fs::{metadata, read_dir, remove_file},
+    io::ErrorKind::NotFound,
+    path::{Path, PathBuf},
+};
+
+use {
+    anyhow::Result,
+    parking_lot::RwLock,
+    tracing::{info, warn},
+};
+
+use crate::app::dirs_cache_home;
+
+/// Subdirectory for cached artwork files.
+pub const ARTWORK_CACHE_DIR: &str = "artwork";
+
+/// Subdirectory for cached thumbnails.
+pub const THUMBNAIL_CACHE_DIR: &str = "thumbnails";
+
+/// Subdirectory for cached waveforms.
+pub const WAVEFORM_CACHE_DIR: &str = "waveforms";
+
+/// Directory of the application in the XDG cache home.
+const APP_CACHE_DIR: &str = "oxhidifi";
+
+/// Caches rebuilt on demand, which [`clear_cache`] empties.
+const REBUILDABLE_CACHES: [&str; 2] = [THUMBNAIL_CACHE_DIR, WAVEFORM_CACHE_DIR];
+
+/// Cache directory chosen by the user in place of the default one.
+static CACHE_DIRECTORY: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Move the caches to `dir`, or back to the default location for `None`.
+///
+/// Files already cached stay where they are; new ones go to `dir`. Callers
+/// clear the old location with [`clear_cache_in`].
+pub fn set_cache_directory(dir: Option<PathBuf>) {
+    info!(path = ?dir, "Cache directory set");
+    *CACHE_DIRECTORY.write() = dir;
+}
+
+/// Directory holding every cache.
+///
+/// # Errors
+///
+/// Returns an error if no directory was chosen and the XDG cache home
+/// cannot be resolved.
+pub fn cache_root() -> Result<PathBuf> {
+    let chosen = CACHE_DIRECTORY.read().clone();
+    chosen.map_or_else(|| Ok(dirs_cache_home()?.join(APP_CACHE_DIR)), Ok)
+}
+
+/// Directory of the `name` cache. It may not exist yet.
+///
+/// # Errors
+///
+/// Returns an error if the cache root cannot be resolved.
+pub fn cache_subdir(name: &str) -> Result<PathBuf> {
+    Ok(cache_root()?.join(name))
+}
+
+/// Remove every cached thumbnail and waveform.
+///
+/// Files that cannot be removed are logged and skipped.
+///
+/// # Returns
+///
+/// The number of bytes freed.
+///
+/// # Errors
+///
+/// Returns an error if the cache root cannot be resolved.
+pub fn clear_cache() -> Result<u64> {
+    Ok(clear_cache_in(&cache_root()?))
+}
+
+/// Remove every cached thumbnail and waveform under `root`.
+///
+/// Files that cannot be removed are logged and skipped.
+///
+/// # Arguments
+///
+/// * `root` - Cache root to clear, such as the one the cache moved from.
+///
+/// # Returns
+///
+/// The number of bytes freed.
+pub fn clear_cache_in(root: &Path) -> u64 {
+    let freed = REBUILDABLE_CACHES
+        .iter()
+        .map(|name| clear_dir(&root.join(name)))
+        .sum();
+    info!(freed, path = %root.display(), "Cache cleared");
+    freed
+}
+
+/// Remove the files in `dir`, returning the bytes freed.
+fn clear_dir(dir: &Path) -> u64 {
+    let entries = match read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == NotFound => return 0,
+        Err(e) => {
+            warn!(error = %e, path = %dir.display(), "Cannot list cache directory");
+            return 0;
+        }
+    };
+    entries
+        .flatten()
+        .map(|entry| remove_cached_file(&entry.path()))
+        .sum()
+}
+
+/// Remove one cached file, returning its size, or 0 if it was kept.
+fn remove_cached_file(path: &Path) -> u64 {
+    let size = match metadata(path) {
+        Ok(meta) if meta.is_file() => meta.len(),
+        Ok(_) => return 0,
+        Err(e) => {
+            warn!(error = %e, path = %path.display(), "Cannot inspect cached file");
+            return 0;
+        }
+    };
+    match remove_file(path) {
+        Ok(()) => size,
+        Err(e) => {
+            warn!(error = %e, path = %path.display(), "Failed to remove cached file");
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, write};
+
+    use {
+        anyhow::{Result, ensure},
+        tempfile::tempdir,
+    };
+
+    use crate::library::cache::{
+        ARTWORK_CACHE_DIR, THUMBNAIL_CACHE_DIR, WAVEFORM_CACHE_DIR, clear_cache_in, clear_dir,
+    };
+
+    #[test]
+    fn clearing_counts_freed_bytes() -> Result<()> {
+        let dir = tempdir()?;
+        let cache = dir.path().join("thumbnails");
+        create_dir_all(cache.join("nested"))?;
+        write(cache.join("a.png"), [0_u8; 100])?;
+        write(cache.join("b.png"), [0_u8; 23])?;
+
+        ensure!(clear_dir(&cache) == 123, "both files freed");
+        ensure!(cache.join("nested").is_dir(), "directories are left alone");
+        ensure!(!cache.join("a.png").exists(), "files are removed");
+        ensure!(clear_dir(&dir.path().join("gone")) == 0, "missing is empty");
+        Ok(())
+    }
+
+    #[test]
+    fn clearing_a_root_keeps_artwork() -> Result<()> {
+        let dir = tempdir()?;
+        for name in [ARTWORK_CACHE_DIR, THUMBNAIL_CACHE_DIR, WAVEFORM_CACHE_DIR] {
+            create_dir_all(dir.path().join(name))?;
+            write(dir.path().join(name).join("x"), [0_u8; 10])?;
+        }
+
+        ensure!(
+            clear_cache_in(dir.path()) == 20,
+            "thumbnails and waveforms freed"
+        );
+        ensure!(
+            dir.path().join(ARTWORK_CACHE_DIR).join("x").exists(),
+            "artwork is kept"
+        );
+        Ok(())
+    }
+}
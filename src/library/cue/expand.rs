@@ -0,0 +1,187 @@
+//! Expansion of scanned audio files into the virtual tracks of their CUE
+//! sheets.
+//!
+//! Sheets are looked up in the directory of each scanned file and parsed
+//! once per directory.
+
+use std::{
+    collections::HashMap,
+    fs::{read, read_dir},
+    path::{Path, PathBuf},
+};
+
+use tracing::{debug, warn};
+
+use crate::library::{
+    cue::{CueRange, CueSheet, CueTrack, cue_track_path, parse_cue},
+    metadata::{AudioMetadata, normalize_genre},
+};
+
+/// Replace audio files described by a CUE sheet with their virtual tracks.
+///
+/// Files without a sheet in their directory are passed through unchanged.
+#[must_use]
+pub fn expand_cue_tracks(
+    items: Vec<(PathBuf, AudioMetadata, Option<String>)>,
+) -> Vec<(PathBuf, AudioMetadata, Option<String>)> {
+    let mut sheets_by_dir: HashMap<PathBuf, Vec<(PathBuf, CueSheet)>> = HashMap::new();
+    items
+        .into_iter()
+        .flat_map(|item| expand_item(item, &mut sheets_by_dir))
+        .collect()
+}
+
+/// Expand one extracted file using the sheets found in its directory.
+fn expand_item(
+    item: (PathBuf, AudioMetadata, Option<String>),
+    sheets_by_dir: &mut HashMap<PathBuf, Vec<(PathBuf, CueSheet)>>,
+) -> Vec<(PathBuf, AudioMetadata, Option<String>)> {
+    let Some(dir) = item.0.parent() else {
+        return vec![item];
+    };
+    let sheets = sheets_by_dir
+        .entry(dir.to_path_buf())
+        .or_insert_with(|| sheets_in_dir(dir));
+    let Some((_, sheet)) = sheets.iter().find(|(audio, _)| references(audio, &item.0)) else {
+        return vec![item];
+    };
+    debug!(path = %item.0.display(), tracks = sheet.tracks.len(), "Splitting file by CUE sheet");
+    let (path, metadata, content_hash) = item;
+    sheet
+        .tracks
+        .iter()
+        .enumerate()
+        .map(|(i, track)| {
+            let end = sheet.tracks.get(i + 1).map(|next| next.start);
+            let range = CueRange {
+                start: track.start,
+                end,
+            };
+            (
+                cue_track_path(&path, range),
+                track_metadata(&metadata, sheet, track, range),
+                content_hash
+                    .as_ref()
+                    .map(|h| format!("{h}#{}", track.number)),
+            )
+        })
+        .collect()
+}
+
+/// Whether a sheet's audio file refers to `path`.
+///
+/// Sheets often name the original `.wav` while the rip was later
+/// compressed, so a matching file stem also counts.
+fn references(sheet_audio: &Path, path: &Path) -> bool {
+    sheet_audio == path || (!sheet_audio.exists() && sheet_audio.file_stem() == path.file_stem())
+}
+
+/// Parse every CUE sheet in a directory, keyed by referenced audio path.
+fn sheets_in_dir(dir: &Path) -> Vec<(PathBuf, CueSheet)> {
+    let entries = match read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!(error = %e, dir = %dir.display(), "Failed to list directory for CUE sheets");
+            return Vec::new();
+        }
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("cue"))
+        })
+        .filter_map(|p| read_sheet(&p))
+        .map(|sheet| (dir.join(&sheet.file), sheet))
+        .collect()
+}
+
+/// Read and parse a CUE sheet, tolerating non-UTF-8 text.
+fn read_sheet(path: &Path) -> Option<CueSheet> {
+    match read(path) {
+        Ok(bytes) => parse_cue(&String::from_utf8_lossy(&bytes)),
+        Err(e) => {
+            warn!(error = %e, path = %path.display(), "Failed to read CUE sheet");
+            None
+        }
+    }
+}
+
+/// Build the metadata of one virtual track from the whole-file metadata.
+fn track_metadata(
+    file: &AudioMetadata,
+    sheet: &CueSheet,
+    track: &CueTrack,
+    range: CueRange,
+) -> AudioMetadata {
+    let performer = track.performer.clone().or_else(|| sheet.performer.clone());
+    AudioMetadata {
+        title: track.title.clone().or_else(|| file.title.clone()),
+        artist: performer.or_else(|| file.artist.clone()),
+        album_artist: sheet
+            .performer
+            .clone()
+            .or_else(|| file.album_artist.clone()),
+        album: sheet.title.clone().or_else(|| file.album.clone()),
+        year: sheet.year.or(file.year),
+        genre: sheet
+            .genre
+            .as_deref()
+            .and_then(normalize_genre)
+            .or_else(|| file.genre.clone()),
+        track_number: Some(track.number),
+        duration: range.end.unwrap_or(file.duration) - range.start,
+        ..file.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::write, path::PathBuf};
+
+    use {
+        anyhow::{Context, Result, ensure},
+        tempfile::tempdir,
+    };
+
+    use crate::library::{
+        cue::{expand::expand_cue_tracks, split_cue_path, tests::SHEET},
+        metadata::{AudioMetadata, tests::test_metadata},
+    };
+
+    #[test]
+    fn expands_file_referenced_by_renamed_sheet() -> Result<()> {
+        let dir = tempdir()?;
+        write(dir.path().join("Live Set.cue"), SHEET)?;
+        let audio = dir.path().join("Live Set.flac");
+        let metadata = AudioMetadata {
+            duration: 600.0,
+            ..test_metadata()
+        };
+        let other = (PathBuf::from("/elsewhere/x.flac"), test_metadata(), None);
+
+        let items = expand_cue_tracks(vec![
+            (audio.clone(), metadata, Some("abc".to_string())),
+            other,
+        ]);
+        ensure!(items.len() == 3, "two cue tracks plus the untouched file");
+        let (path, meta, hash) = items.get(1).context("second cue track")?;
+        ensure!(
+            split_cue_path(path).0 == audio,
+            "virtual path must point at the audio file"
+        );
+        ensure!(meta.title.as_deref() == Some("Finale"), "track title");
+        ensure!(
+            meta.album.as_deref() == Some("Live Set"),
+            "album from sheet"
+        );
+        ensure!(
+            (meta.duration - 299.6).abs() < 1e-9,
+            "last track runs to the end of the file: {}",
+            meta.duration
+        );
+        ensure!(hash.as_deref() == Some("abc#2"), "per-track content hash");
+        Ok(())
+    }
+}
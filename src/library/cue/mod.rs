@@ -0,0 +1,381 @@
+//! CUE sheet support for single-file album rips.
+//!
+//! A `.cue` file next to a single audio file splits it into virtual tracks.
+//! Each virtual track is stored under its audio file with the time range as
+//! one more path component (`album.flac/#t=start,end`), which keeps it a
+//! unique library row and lets the decoder recover the time range when the
+//! track is played. An audio file is not a directory, so no real file has
+//! such a path, and file names that merely contain `#t=` are left alone.
+
+pub mod expand;
+
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+/// Number of CUE frames per second.
+const CUE_FRAMES_PER_SECOND: f64 = 75.0;
+
+/// Prefix of the path component holding the time range of a virtual track.
+const RANGE_PREFIX: &str = "#t=";
+
+/// Time range of a virtual track within its audio file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CueRange {
+    /// Start offset in seconds.
+    pub start: f64,
+    /// End offset in seconds, or `None` to play to the end of the file.
+    pub end: Option<f64>,
+}
+
+impl CueRange {
+    /// Parse a `start[,end]` range in seconds.
+    ///
+    /// # Returns
+    ///
+    /// `None` unless every bound is a number.
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let (start, end) = text
+            .split_once(',')
+            .map_or((text, None), |(s, e)| (s, Some(e)));
+        let start = start.parse::<f64>().ok()?;
+        let end = match end.map(str::parse::<f64>) {
+            None => None,
+            Some(Ok(end)) => Some(end),
+            Some(Err(_)) => return None,
+        };
+        Some(Self { start, end })
+    }
+}
+
+/// Parsed contents of a single-file CUE sheet.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CueSheet {
+    /// Album title.
+    pub title: Option<String>,
+    /// Album performer.
+    pub performer: Option<String>,
+    /// Genre from `REM GENRE`.
+    pub genre: Option<String>,
+    /// Year from `REM DATE`.
+    pub year: Option<i32>,
+    /// Name of the referenced audio file, relative to the sheet.
+    pub file: String,
+    /// Tracks in playback order.
+    pub tracks: Vec<CueTrack>,
+}
+
+/// A track entry of a CUE sheet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CueTrack {
+    /// Track number.
+    pub number: i32,
+    /// Track title.
+    pub title: Option<String>,
+    /// Track performer.
+    pub performer: Option<String>,
+    /// Start offset (`INDEX 01`) in seconds.
+    pub start: f64,
+}
+
+/// Sheet state while parsing.
+#[derive(Debug, Default)]
+struct PendingSheet {
+    /// Album-level fields.
+    sheet: CueSheet,
+    /// Referenced files, in order.
+    files: Vec<String>,
+    /// Tracks seen so far.
+    tracks: Vec<PendingTrack>,
+}
+
+/// A track entry while its `INDEX 01` may still be missing.
+#[derive(Debug, Default)]
+struct PendingTrack {
+    /// Track number.
+    number: i32,
+    /// Track title.
+    title: Option<String>,
+    /// Track performer.
+    performer: Option<String>,
+    /// Start offset in seconds, once seen.
+    start: Option<f64>,
+}
+
+/// Parse a CUE sheet.
+///
+/// Returns `None` for sheets without tracks and for sheets referencing more
+/// than one file, whose tracks already are separate files.
+#[must_use]
+pub fn parse_cue(text: &str) -> Option<CueSheet> {
+    let mut pending = PendingSheet::default();
+    for line in text.lines() {
+        let line = line.trim_start_matches('\u{feff}').trim();
+        let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        apply_command(&mut pending, &command.to_ascii_uppercase(), rest.trim());
+    }
+
+    let [file] = <[String; 1]>::try_from(pending.files).unwrap_or_default();
+    let mut sheet = pending.sheet;
+    sheet.file = file;
+    sheet.tracks = pending
+        .tracks
+        .into_iter()
+        .filter_map(|t| {
+            t.start.map(|start| CueTrack {
+                number: t.number,
+                title: t.title,
+                performer: t.performer,
+                start,
+            })
+        })
+        .collect();
+    sheet.tracks.sort_by(|a, b| a.start.total_cmp(&b.start));
+    (!sheet.file.is_empty() && !sheet.tracks.is_empty()).then_some(sheet)
+}
+
+/// Build the library path of a virtual track.
+#[must_use]
+pub fn cue_track_path(audio: &Path, range: CueRange) -> PathBuf {
+    let component = range.end.map_or_else(
+        || format!("{RANGE_PREFIX}{:.3}", range.start),
+        |end| format!("{RANGE_PREFIX}{:.3},{end:.3}", range.start),
+    );
+    audio.join(component)
+}
+
+/// Split a library path into the audio file path and its cue range.
+///
+/// Paths whose last component is not a valid time range are returned
+/// unchanged.
+#[must_use]
+pub fn split_cue_path(path: &Path) -> (PathBuf, Option<CueRange>) {
+    path.file_name()
+        .and_then(OsStr::to_str)
+        .and_then(|name| name.strip_prefix(RANGE_PREFIX))
+        .and_then(CueRange::parse)
+        .zip(path.parent())
+        .map_or_else(
+            || (path.to_path_buf(), None),
+            |(range, file)| (file.to_path_buf(), Some(range)),
+        )
+}
+
+/// Range of `target` if it is another virtual track of `current`'s file.
+///
+/// Skips between such tracks can seek within the open stream instead of
+/// reopening the file.
+///
+/// # Returns
+///
+/// `None` unless both paths are CUE tracks of the same audio file.
+#[must_use]
+pub fn sibling_cue_range(current: &Path, target: &Path) -> Option<CueRange> {
+    let (current_file, current_range) = split_cue_path(current);
+    let (target_file, target_range) = split_cue_path(target);
+    current_range
+        .and(target_range)
+        .filter(|_| current_file == target_file)
+}
+
+/// Apply one sheet command to the parse state.
+fn apply_command(pending: &mut PendingSheet, command: &str, rest: &str) {
+    match command {
+        "FILE" => pending.files.push(file_name(rest)),
+        "TRACK" => pending.tracks.push(PendingTrack {
+            number: match rest.split_whitespace().next().map(str::parse::<i32>) {
+                Some(Ok(number)) => number,
+                _ => 0,
+            },
+            ..PendingTrack::default()
+        }),
+        "TITLE" => match pending.tracks.last_mut() {
+            Some(track) => track.title = Some(unquote(rest)),
+            None => pending.sheet.title = Some(unquote(rest)),
+        },
+        "PERFORMER" => match pending.tracks.last_mut() {
+            Some(track) => track.performer = Some(unquote(rest)),
+            None => pending.sheet.performer = Some(unquote(rest)),
+        },
+        "INDEX" => {
+            if let (Some(track), Some(start)) = (pending.tracks.last_mut(), index_01(rest)) {
+                track.start = Some(start);
+            }
+        }
+        "REM" => apply_remark(&mut pending.sheet, rest),
+        _ => {}
+    }
+}
+
+/// Apply a `REM GENRE` or `REM DATE` remark.
+fn apply_remark(sheet: &mut CueSheet, rest: &str) {
+    let (key, value) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let value = unquote(value.trim());
+    match key.to_ascii_uppercase().as_str() {
+        "GENRE" => sheet.genre = Some(value),
+        "DATE" => {
+            sheet.year = match value.get(..4).map(str::parse::<i32>) {
+                Some(Ok(year)) => Some(year),
+                _ => None,
+            };
+        }
+        _ => {}
+    }
+}
+
+/// Parse the start time of an `INDEX 01 mm:ss:ff` line.
+fn index_01(rest: &str) -> Option<f64> {
+    let (index, time) = rest.split_once(char::is_whitespace)?;
+    if index.trim().parse::<u32>() != Ok(1) {
+        return None;
+    }
+    let mut parts = time.trim().split(':').map(str::parse::<u32>);
+    let (Some(Ok(minutes)), Some(Ok(seconds)), Some(Ok(frames)), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    Some(
+        f64::from(minutes).mul_add(60.0, f64::from(seconds))
+            + f64::from(frames) / CUE_FRAMES_PER_SECOND,
+    )
+}
+
+/// Extract the file name from a `FILE "name" TYPE` line.
+fn file_name(rest: &str) -> String {
+    match rest.strip_prefix('"').and_then(|r| r.split_once('"')) {
+        Some((name, _)) => name.to_string(),
+        None => rest
+            .rsplit_once(char::is_whitespace)
+            .map_or(rest, |(name, _)| name)
+            .to_string(),
+    }
+}
+
+/// Strip surrounding double quotes.
+fn unquote(value: &str) -> String {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+        .to_string()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::path::Path;
+
+    use anyhow::{Context, Result, ensure};
+
+    use crate::library::cue::{
+        CueRange, cue_track_path, parse_cue, sibling_cue_range, split_cue_path,
+    };
+
+    /// A two-track sheet in EAC style.
+    pub const SHEET: &str = "\u{feff}REM GENRE Classical\r\nREM DATE 1998\r\n\
+        PERFORMER \"Some Orchestra\"\r\nTITLE \"Live Set\"\r\n\
+        FILE \"Live Set.wav\" WAVE\r\n  TRACK 01 AUDIO\r\n    TITLE \"Opening\"\r\n\
+        INDEX 01 00:00:00\r\n  TRACK 02 AUDIO\r\n    TITLE \"Finale\"\r\n\
+        PERFORMER \"Guest\"\r\n    INDEX 00 04:59:00\r\n    INDEX 01 05:00:30\r\n";
+
+    #[test]
+    fn parses_album_and_track_fields() -> Result<()> {
+        let sheet = parse_cue(SHEET).context("sheet must parse")?;
+        ensure!(
+            sheet.file == "Live Set.wav",
+            "unexpected file: {}",
+            sheet.file
+        );
+        ensure!(sheet.title.as_deref() == Some("Live Set"), "album title");
+        ensure!(sheet.year == Some(1998), "year from REM DATE");
+        ensure!(
+            sheet.genre.as_deref() == Some("Classical"),
+            "genre from REM GENRE"
+        );
+        let finale = sheet.tracks.get(1).context("second track")?;
+        ensure!(
+            (finale.start - 300.4).abs() < 1e-9,
+            "INDEX 01 must win: {}",
+            finale.start
+        );
+        ensure!(
+            finale.performer.as_deref() == Some("Guest"),
+            "track performer"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn multi_file_sheets_are_ignored() {
+        let text = "FILE \"a.flac\" WAVE\nTRACK 01 AUDIO\nINDEX 01 00:00:00\n\
+            FILE \"b.flac\" WAVE\nTRACK 02 AUDIO\nINDEX 01 00:00:00\n";
+        assert!(
+            parse_cue(text).is_none(),
+            "Per-track files need no splitting"
+        );
+    }
+
+    #[test]
+    fn cue_paths_round_trip() {
+        let audio = Path::new("/music/Live Set.flac");
+        let range = CueRange {
+            start: 300.4,
+            end: Some(612.0),
+        };
+        let path = cue_track_path(audio, range);
+        assert_eq!(
+            split_cue_path(&path),
+            (audio.to_path_buf(), Some(range)),
+            "Range must survive the round trip"
+        );
+        assert_eq!(
+            split_cue_path(audio),
+            (audio.to_path_buf(), None),
+            "Plain paths have no range"
+        );
+    }
+
+    #[test]
+    fn file_names_containing_the_prefix_are_not_ranges() {
+        for name in ["/music/Live #t=5", "/music/Mix #t=1,2.flac", "/music/#t=x"] {
+            assert_eq!(
+                split_cue_path(Path::new(name)),
+                (Path::new(name).to_path_buf(), None),
+                "{name} is a plain file"
+            );
+        }
+    }
+
+    #[test]
+    fn sibling_ranges_share_the_audio_file() {
+        let audio = Path::new("/music/Live Set.flac");
+        let first = cue_track_path(
+            audio,
+            CueRange {
+                start: 0.0,
+                end: Some(300.4),
+            },
+        );
+        let second_range = CueRange {
+            start: 300.4,
+            end: None,
+        };
+        let second = cue_track_path(audio, second_range);
+        let other = cue_track_path(Path::new("/music/Other.flac"), second_range);
+
+        assert_eq!(sibling_cue_range(&first, &second), Some(second_range));
+        assert_eq!(sibling_cue_range(&first, &other), None, "other file");
+        assert_eq!(
+            sibling_cue_range(audio, &second),
+            None,
+            "not playing a CUE track"
+        );
+        assert_eq!(
+            sibling_cue_range(&first, audio),
+            None,
+            "target is the whole file"
+        );
+    }
+}
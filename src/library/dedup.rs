@@ -83,51 +83,13 @@ pub fn create_fingerprint(metadata: &AudioMetadata) -> String {
     format!("{artist}|{album}|{title}|{track}")
 }
 
-/// Check if a file path indicates a supported audio format.
-///
-/// Supported extensions: `.flac`, `.mp3`, `.aac`, `.m4a`, `.ogg`, `.opus`,
-/// `.wav`, `.aiff`, `.aif`
-#[must_use]
-pub fn is_supported_audio_format(path: &Path) -> bool {
-    path.extension()
-        .and_then(|e| e.to_str())
-        .is_some_and(|ext| {
-            matches!(
-                ext.to_lowercase().as_str(),
-                "flac" | "mp3" | "aac" | "m4a" | "ogg" | "opus" | "wav" | "aiff" | "aif"
-            )
-        })
-}
-
 #[cfg(test)]
 mod tests {
-    use std::path::PathBuf;
-
     use crate::library::{
-        dedup::{create_fingerprint, is_supported_audio_format},
+        dedup::create_fingerprint,
         metadata::tests::{test_metadata, test_metadata_defaults},
     };
 
-    #[test]
-    fn supported_audio_formats() {
-        assert!(is_supported_audio_format(&PathBuf::from("track.flac")));
-        assert!(is_supported_audio_format(&PathBuf::from("track.mp3")));
-        assert!(is_supported_audio_format(&PathBuf::from("track.m4a")));
-        assert!(is_supported_audio_format(&PathBuf::from("track.aac")));
-        assert!(is_supported_audio_format(&PathBuf::from("track.ogg")));
-        assert!(is_supported_audio_format(&PathBuf::from("track.opus")));
-        assert!(is_supported_audio_format(&PathBuf::from("track.wav")));
-        assert!(is_supported_audio_format(&PathBuf::from("track.aiff")));
-        assert!(is_supported_audio_format(&PathBuf::from("track.aif")));
-    }
-
-    #[test]
-    fn unsupported_audio_formats() {
-        assert!(!is_supported_audio_format(&PathBuf::from("track.txt")));
-        assert!(!is_supported_audio_format(&PathBuf::from("track.jpg")));
-        assert!(!is_supported_audio_format(&PathBuf::from("track")));
-    }
-
     #[test]
     fn create_fingerprint_normalizes() {
         let meta = test_metadata();
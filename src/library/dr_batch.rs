@@ -0,0 +1,150 @@
+//! Analyzing the dynamic range of every album that has no value yet.
+//!
+//! The batch is started from the DR Badges preferences. A few albums are
+//! measured at a time, each holding a [`BackgroundScheduler`] permit, so
+//! the batch shares the background budget with scanning and cover decoding
+//! and slows down while audio plays. `AppState` holds the latest
+//! [`DrBatchStatus`] in a watch channel: the preferences row shows it, and
+//! the batch reads it between albums to pause or stop.
+
+use std::path::PathBuf;
+
+use crate::{
+    library::dynamic_range::{AlbumDr, album_dr},
+    playback::DecoderError,
+    threading::scheduler::BackgroundScheduler,
+};
+
+/// Number of albums measured at the same time.
+pub const DR_BATCH_LANES: usize = 2;
+
+/// Phase of the DR batch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DrBatchPhase {
+    /// No batch is running.
+    #[default]
+    Idle,
+    /// Albums are being measured.
+    Running,
+    /// Measuring stops after the albums in progress until resumed.
+    Paused,
+    /// Measuring stops after the albums in progress.
+    Cancelled,
+}
+
+/// Progress of the DR batch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DrBatchStatus {
+    /// Phase of the batch.
+    pub phase: DrBatchPhase,
+    /// Albums the batch set out to measure.
+    pub total: usize,
+    /// Albums finished so far, measured or not.
+    pub done: usize,
+    /// Albums that could not be measured.
+    pub failed: usize,
+}
+
+impl DrBatchStatus {
+    /// Status of a batch starting on `total` albums.
+    #[must_use]
+    pub const fn started(total: usize) -> Self {
+        Self {
+            phase: DrBatchPhase::Running,
+            total,
+            done: 0,
+            failed: 0,
+        }
+    }
+
+    /// Whether a batch is running or paused.
+    #[must_use]
+    pub const fn is_active(&self) -> bool {
+        matches!(self.phase, DrBatchPhase::Running | DrBatchPhase::Paused)
+    }
+
+    /// Ask a running or paused batch to stop; an idle batch stays idle.
+    pub const fn cancel(&mut self) {
+        if self.is_active() {
+            self.phase = DrBatchPhase::Cancelled;
+        }
+    }
+
+    /// Status after one more album finished, `measured` or not.
+    #[must_use]
+    pub const fn after_album(self, measured: bool) -> Self {
+        Self {
+            done: self.done + 1,
+            failed: self.failed + if measured { 0 } else { 1 },
+            ..self
+        }
+    }
+
+    /// Short description such as `12 of 340 albums`.
+    #[must_use]
+    pub fn label(&self) -> String {
+        let progress = format!("{} of {} albums", self.done, self.total);
+        match self.phase {
+            DrBatchPhase::Idle if self.total == 0 => {
+                "Measure every album without a DR value".to_string()
+            }
+            DrBatchPhase::Idle if self.failed > 0 => {
+                format!("Analyzed {progress}, {} failed", self.failed)
+            }
+            DrBatchPhase::Idle => format!("Analyzed {progress}"),
+            DrBatchPhase::Running => progress,
+            DrBatchPhase::Paused => format!("Paused at {progress}"),
+            DrBatchPhase::Cancelled => format!("Stopping at {progress}"),
+        }
+    }
+}
+
+/// Derive the DR value of one album while holding a background permit.
+///
+/// Blocks until a permit is free, so it must only be called from
+/// background threads.
+///
+/// # Errors
+///
+/// Returns [`DecoderError`] if a track cannot be opened or decoded.
+pub fn measure_album(
+    scheduler: &BackgroundScheduler,
+    track_paths: &[PathBuf],
+) -> Result<Option<AlbumDr>, DecoderError> {
+    let permit = scheduler.acquire();
+    let measured = album_dr(track_paths);
+    drop(permit);
+    measured
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::library::dr_batch::{DrBatchPhase, DrBatchStatus};
+
+    #[test]
+    fn finished_albums_count_failures() {
+        let status = DrBatchStatus::started(3)
+            .after_album(true)
+            .after_album(false);
+        assert_eq!(status.done, 2, "Both albums are done");
+        assert_eq!(status.failed, 1, "One album failed");
+        assert_eq!(status.label(), "2 of 3 albums", "Running label");
+    }
+
+    #[test]
+    fn only_running_and_paused_batches_are_active() {
+        let running = DrBatchStatus::started(1);
+        let paused = DrBatchStatus {
+            phase: DrBatchPhase::Paused,
+            ..running
+        };
+        let cancelled = DrBatchStatus {
+            phase: DrBatchPhase::Cancelled,
+            ..running
+        };
+        assert!(running.is_active(), "Running batch is active");
+        assert!(paused.is_active(), "Paused batch is active");
+        assert!(!cancelled.is_active(), "Cancelled batch is not active");
+        assert!(!DrBatchStatus::default().is_active(), "Idle is not active");
+    }
+}
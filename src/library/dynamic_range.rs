@@ -0,0 +1,397 @@
+//! Dynamic range (DR) of albums, read from a DR meter log or measured.
+//!
+//! The measurement follows the DR meter of the loudness war campaign: each
+//! channel is cut into three second blocks, the RMS of the loudest fifth of
+//! the blocks is averaged, and the DR is the gap in dB between the second
+//! highest block peak and that average. A track's DR is the mean of its
+//! channels and an album's the rounded mean of its tracks.
+//!
+//! Rips often ship the meter's log next to the files. Its album value is
+//! preferred, since decoding a whole album takes a while.
+
+use std::{
+    fs::{read_dir, read_to_string},
+    iter::repeat_with,
+    path::{Path, PathBuf},
+};
+
+use {
+    num_traits::cast::AsPrimitive,
+    serde::{Deserialize, Serialize},
+    tracing::{debug, warn},
+};
+
+use crate::{
+    library::cue::split_cue_path,
+    playback::{DecoderError, decoder::Decoder},
+};
+
+/// Length of an analysis block in seconds.
+const BLOCK_SECONDS: u32 = 3;
+
+/// One in this many blocks, the loudest ones, is averaged for the RMS.
+const LOUDEST_BLOCKS_DIVISOR: usize = 5;
+
+/// Line of a DR meter log holding the album value.
+const LOG_ALBUM_PREFIX: &str = "Official DR value:";
+
+/// DR value of an album with its origin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlbumDr {
+    /// Dynamic range in dB, rounded as DR meters show it.
+    pub value: i32,
+    /// Where the value came from.
+    pub source: DrSource,
+}
+
+/// Block statistics of one channel.
+#[derive(Default)]
+struct ChannelBlocks {
+    /// Sum of squared samples of the unfinished block.
+    sum_squares: f64,
+    /// Loudest sample of the unfinished block.
+    peak: f32,
+    /// RMS of each finished block.
+    rms: Vec<f64>,
+    /// Peak of each finished block.
+    peaks: Vec<f32>,
+}
+
+impl ChannelBlocks {
+    /// Close the unfinished block of `frames` frames.
+    fn close(&mut self, frames: usize) {
+        let frames: f64 = frames.as_();
+        self.rms.push((2.0 * self.sum_squares / frames).sqrt());
+        self.peaks.push(self.peak);
+        self.sum_squares = 0.0;
+        self.peak = 0.0;
+    }
+
+    /// DR of the channel in dB, or `None` for silence.
+    fn dr(&self) -> Option<f64> {
+        let mut rms = self.rms.clone();
+        rms.sort_by(|a, b| b.total_cmp(a));
+        let top = (rms.len() / LOUDEST_BLOCKS_DIVISOR).max(1);
+        let count: f64 = top.as_();
+        let loud_rms = (rms.iter().take(top).map(|r| r * r).sum::<f64>() / count).sqrt();
+
+        let mut peaks = self.peaks.clone();
+        peaks.sort_by(|a, b| b.total_cmp(a));
+        let peak = f64::from(*peaks.get(1).or_else(|| peaks.first())?);
+        (loud_rms > 0.0 && peak > 0.0).then(|| 20.0 * (peak / loud_rms).log10())
+    }
+}
+
+/// Which albums show a DR badge on their grid tile.
+///
+/// Badges can be limited to lossless albums, where the DR tells about the
+/// master rather than the encoder, or to albums below a DR value to flag
+/// loudness-war masters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DrBadgeDisplayPolicy {
+    /// Whether badges are shown at all.
+    pub enabled: bool,
+    /// Only badge albums whose tracks are all lossless.
+    pub lossless_only: bool,
+    /// Only badge albums with a DR below this value.
+    pub below: Option<i32>,
+}
+
+impl DrBadgeDisplayPolicy {
+    /// Whether an album with `dr_value` gets a badge.
+    ///
+    /// # Arguments
+    ///
+    /// * `dr_value` - Album DR, `None` until analyzed
+    /// * `lossless` - Whether all tracks of the album are lossless
+    #[must_use]
+    pub fn shows(&self, dr_value: Option<i32>, lossless: bool) -> bool {
+        dr_value.is_some_and(|dr| {
+            self.enabled
+                && (lossless || !self.lossless_only)
+                && self.below.is_none_or(|below| dr < below)
+        })
+    }
+}
+
+impl Default for DrBadgeDisplayPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            lossless_only: false,
+            below: None,
+        }
+    }
+}
+
+/// DR meter over the interleaved samples of one track.
+struct DrMeter {
+    /// Frames per block.
+    block_frames: usize,
+    /// Statistics of each channel.
+    channels: Vec<ChannelBlocks>,
+    /// Frames in the unfinished block.
+    len: usize,
+}
+
+impl DrMeter {
+    /// Meter with blocks of [`BLOCK_SECONDS`] at `sample_rate`.
+    fn new(sample_rate: u32, channels: u16) -> Self {
+        let block_frames = usize::try_from(sample_rate * BLOCK_SECONDS).unwrap_or(1);
+        Self {
+            block_frames: block_frames.max(1),
+            channels: repeat_with(ChannelBlocks::default)
+                .take(usize::from(channels.max(1)))
+                .collect(),
+            len: 0,
+        }
+    }
+
+    /// Fold one batch of interleaved samples into the blocks.
+    fn add(&mut self, samples: &[f32]) {
+        for frame in samples.chunks(self.channels.len()) {
+            self.add_frame(frame);
+        }
+    }
+
+    /// Fold one interleaved frame into the blocks.
+    fn add_frame(&mut self, frame: &[f32]) {
+        for (channel, &sample) in self.channels.iter_mut().zip(frame) {
+            channel.sum_squares += f64::from(sample) * f64::from(sample);
+            channel.peak = channel.peak.max(sample.abs());
+        }
+        self.len += 1;
+        if self.len == self.block_frames {
+            self.close_block();
+        }
+    }
+
+    /// Close the unfinished block in every channel.
+    fn close_block(&mut self) {
+        for channel in &mut self.channels {
+            channel.close(self.len);
+        }
+        self.len = 0;
+    }
+
+    /// Mean DR of the channels, or `None` if the track is silent.
+    fn finish(mut self) -> Option<f64> {
+        if self.len > 0 {
+            self.close_block();
+        }
+        let values: Vec<f64> = self.channels.iter().filter_map(ChannelBlocks::dr).collect();
+        let count: f64 = values.len().as_();
+        (!values.is_empty()).then(|| values.iter().sum::<f64>() / count)
+    }
+}
+
+/// Where an album's DR value came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrSource {
+    /// Read from a DR meter log in the album folder.
+    Log,
+    /// Measured by decoding the album's tracks.
+    Measured,
+}
+
+/// Find the DR value of an album.
+///
+/// A DR meter log in the folder of the first track is used when present,
+/// otherwise every track is decoded, so call this off the main thread.
+///
+/// # Arguments
+///
+/// * `track_paths` - Library paths of the album's tracks, with cue ranges
+///
+/// # Returns
+///
+/// The album DR, or `None` if there are no tracks or all are silent.
+///
+/// # Errors
+///
+/// Returns [`DecoderError`] if a track cannot be opened or decoded.
+pub fn album_dr(track_paths: &[PathBuf]) -> Result<Option<AlbumDr>, DecoderError> {
+    let folder = track_paths
+        .first()
+        .and_then(|path| split_cue_path(path).0.parent().map(Path::to_path_buf));
+    if let Some(value) = folder.as_deref().and_then(read_dr_log) {
+        return Ok(Some(AlbumDr {
+            value,
+            source: DrSource::Log,
+        }));
+    }
+    measure_album_dr(track_paths).map(|value| {
+        value.map(|value| AlbumDr {
+            value,
+            source: DrSource::Measured,
+        })
+    })
+}
+
+/// Decode every track and return the rounded mean of their DR.
+///
+/// # Errors
+///
+/// Returns [`DecoderError`] if a track cannot be opened or decoded.
+pub fn measure_album_dr(track_paths: &[PathBuf]) -> Result<Option<i32>, DecoderError> {
+    let mut values = Vec::with_capacity(track_paths.len());
+    for path in track_paths {
+        if let Some(value) = measure_track_dr(path)? {
+            values.push(value);
+        }
+    }
+    if values.is_empty() {
+        return Ok(None);
+    }
+    let count: f64 = values.len().as_();
+    Ok(Some((values.iter().sum::<f64>() / count).round().as_()))
+}
+
+/// Decode a track and return its unrounded DR.
+///
+/// # Errors
+///
+/// Returns [`DecoderError`] if the track cannot be opened or decoded.
+fn measure_track_dr(track_path: &Path) -> Result<Option<f64>, DecoderError> {
+    let mut decoder = Decoder::open(track_path)?;
+    let params = decoder.params();
+    let mut meter = DrMeter::new(params.sample_rate, params.channels);
+    loop {
+        let decoded = decoder.decode_next()?;
+        if decoded.samples.is_empty() {
+            let value = meter.finish();
+            debug!(path = %track_path.display(), dr = ?value, "Measured track DR");
+            return Ok(value);
+        }
+        meter.add(&decoded.samples);
+    }
+}
+
+/// Read the album value from a DR meter log in `folder`.
+///
+/// Logs are the text files with "dr" in their name, such as `foo_dr.txt`.
+fn read_dr_log(folder: &Path) -> Option<i32> {
+    let entries = match read_dir(folder) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!(error = %e, path = %folder.display(), "Cannot list album folder");
+            return None;
+        }
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_dr_log_name(path))
+        .find_map(|path| match read_to_string(&path) {
+            Ok(text) => parse_dr_log(&text),
+            Err(e) => {
+                warn!(error = %e, path = %path.display(), "Cannot read DR log");
+                None
+            }
+        })
+}
+
+/// Whether `path` is named like a DR meter log.
+fn is_dr_log_name(path: &Path) -> bool {
+    let is_text = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("txt"));
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    is_text && stem.contains("dr")
+}
+
+/// Parse the album value ("Official DR value: DR12") of a DR meter log.
+fn parse_dr_log(text: &str) -> Option<i32> {
+    text.lines().find_map(|line| {
+        let value = line.trim().strip_prefix(LOG_ALBUM_PREFIX)?.trim();
+        value
+            .strip_prefix("DR")
+            .unwrap_or(value)
+            .trim()
+            .parse()
+            .ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::library::dynamic_range::{DrBadgeDisplayPolicy, DrMeter, parse_dr_log};
+
+    #[test]
+    fn square_wave_has_no_dynamic_range() {
+        let mut meter = DrMeter::new(10, 1);
+        let samples: Vec<f32> = (0..300)
+            .map(|i| if i % 2 == 0 { 0.5 } else { -0.5 })
+            .collect();
+        meter.add(&samples);
+        let dr = meter.finish().unwrap_or(f64::NAN);
+        assert!(
+            10.0_f64.mul_add(2.0_f64.log10(), dr).abs() < 1e-9,
+            "the meter is calibrated to sines, putting a square wave at -3 dB, got {dr}"
+        );
+    }
+
+    #[test]
+    fn quiet_passages_do_not_lower_dr() {
+        let mut loud = DrMeter::new(10, 1);
+        let mut mixed = DrMeter::new(10, 1);
+        let block =
+            |level: f32| -> Vec<f32> { [level, -level].into_iter().cycle().take(30).collect() };
+        for _ in 0..5 {
+            loud.add(&block(0.8));
+            mixed.add(&block(0.8));
+            mixed.add(&block(0.1));
+        }
+        assert_eq!(
+            loud.finish().map(f64::round),
+            mixed.finish().map(f64::round),
+            "only the loudest fifth of the blocks counts"
+        );
+        assert_eq!(DrMeter::new(10, 2).finish(), None, "silence has no DR");
+    }
+
+    #[test]
+    fn log_album_value_is_parsed() {
+        let log = "foo_dr_meter\n\nDR         Peak         RMS     Duration Track\n\
+                   DR11      -0.10 dB   -13.52 dB      4:12 01-Intro\n\n\
+                   Number of tracks:  1\nOfficial DR value: DR11\n";
+        assert_eq!(parse_dr_log(log), Some(11));
+        assert_eq!(parse_dr_log("Official DR value: 8"), Some(8));
+        assert_eq!(
+            parse_dr_log("DR11 -0.10 dB"),
+            None,
+            "track lines are ignored"
+        );
+    }
+
+    #[test]
+    fn badge_policy_filters_albums() {
+        let all = DrBadgeDisplayPolicy::default();
+        assert!(all.shows(Some(12), false));
+        assert!(!all.shows(None, true), "unanalyzed albums have no badge");
+
+        let lossless = DrBadgeDisplayPolicy {
+            lossless_only: true,
+            ..all
+        };
+        assert!(lossless.shows(Some(12), true));
+        assert!(!lossless.shows(Some(12), false), "lossy album");
+
+        let loud = DrBadgeDisplayPolicy {
+            below: Some(8),
+            ..all
+        };
+        assert!(loud.shows(Some(5), false));
+        assert!(!loud.shows(Some(8), false), "the threshold is exclusive");
+
+        let off = DrBadgeDisplayPolicy {
+            enabled: false,
+            ..all
+        };
+        assert!(!off.shows(Some(5), true), "badges turned off");
+    }
+}
@@ -0,0 +1,122 @@
+//! Audio file extensions recognised by the library.
+//!
+//! The scanner and the filesystem watcher both decide which files belong to
+//! the library through one [`AudioExtensions`], so a file picked up by a
+//! full rescan is also picked up by live monitoring, and vice versa.
+
+use std::path::Path;
+
+/// Extensions of the audio formats the library reads, in lowercase.
+pub const SUPPORTED_AUDIO_EXTENSIONS: [&str; 9] = [
+    "flac", "mp3", "aac", "m4a", "ogg", "opus", "wav", "aiff", "aif",
+];
+
+/// Set of audio file extensions accepted into the library.
+///
+/// Always contains [`SUPPORTED_AUDIO_EXTENSIONS`]; further extensions, e.g.
+/// for containers the decoder handles but that are not listed, can be added
+/// with [`AudioExtensions::with_extra`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AudioExtensions {
+    /// Lowercase extensions accepted in addition to the supported ones.
+    extra: Vec<String>,
+}
+
+impl AudioExtensions {
+    /// Supported extensions extended by `extra`.
+    ///
+    /// Extensions are matched case-insensitively and may be given with or
+    /// without a leading dot.
+    #[must_use]
+    pub fn with_extra<I, T>(extra: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        let mut extensions = Self::default();
+        let normalized = extra
+            .into_iter()
+            .map(|ext| ext.as_ref().trim().trim_start_matches('.').to_lowercase());
+        for ext in normalized {
+            extensions.add(ext);
+        }
+        extensions
+    }
+
+    /// Whether `path` has one of the accepted extensions.
+    #[must_use]
+    pub fn accepts(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| self.contains(&ext.to_lowercase()))
+    }
+
+    /// Accept the lowercase extension `ext` unless it is empty or already accepted.
+    fn add(&mut self, ext: String) {
+        if !ext.is_empty() && !self.contains(&ext) {
+            self.extra.push(ext);
+        }
+    }
+
+    /// Whether the lowercase extension `ext` is accepted.
+    fn contains(&self, ext: &str) -> bool {
+        SUPPORTED_AUDIO_EXTENSIONS.contains(&ext) || self.extra.iter().any(|e| e == ext)
+    }
+}
+
+/// Check if a file path indicates a supported audio format.
+///
+/// Only [`SUPPORTED_AUDIO_EXTENSIONS`] are accepted.
+#[must_use]
+pub fn is_supported_audio_format(path: &Path) -> bool {
+    AudioExtensions::default().accepts(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::library::formats::{
+        AudioExtensions, SUPPORTED_AUDIO_EXTENSIONS, is_supported_audio_format,
+    };
+
+    #[test]
+    fn supported_audio_formats() {
+        for ext in SUPPORTED_AUDIO_EXTENSIONS {
+            let path = format!("track.{ext}");
+            assert!(is_supported_audio_format(Path::new(&path)), "{ext}");
+            let upper = format!("track.{}", ext.to_uppercase());
+            assert!(is_supported_audio_format(Path::new(&upper)), "{ext}");
+        }
+    }
+
+    #[test]
+    fn unsupported_audio_formats() {
+        assert!(!is_supported_audio_format(Path::new("track.txt")));
+        assert!(!is_supported_audio_format(Path::new("track.jpg")));
+        assert!(!is_supported_audio_format(Path::new("track")));
+    }
+
+    #[test]
+    fn extra_extensions_are_normalized() {
+        let extensions = AudioExtensions::with_extra([".WV", "dsf", "", "flac"]);
+        assert!(extensions.accepts(Path::new("track.wv")), "Dot is stripped");
+        assert!(
+            extensions.accepts(Path::new("track.DSF")),
+            "Case is ignored"
+        );
+        assert!(
+            extensions.accepts(Path::new("track.flac")),
+            "Built-ins stay"
+        );
+        assert!(
+            !extensions.accepts(Path::new("track.ape")),
+            "Others are not added"
+        );
+        assert_eq!(
+            extensions,
+            AudioExtensions::with_extra(["wv", "dsf"]),
+            "Empty and built-in extensions are not stored"
+        );
+    }
+}
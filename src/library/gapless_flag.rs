@@ -0,0 +1,142 @@
+//! Gapless album flag of a track.
+//!
+//! Live recordings, DJ mixes and other albums whose tracks run into each
+//! other are tagged for gapless playback: iTunes sets the MP4 `pgap` atom,
+//! and other taggers write `ITUNESGAPLESS=1` as a Vorbis comment or an
+//! ID3v2 user text frame. lofty's generic tag does not carry these keys, so
+//! they are read from the native tag of each format.
+
+use std::{fs::File, path::Path};
+
+use {
+    lofty::{
+        config::ParseOptions,
+        error::LoftyError,
+        file::{
+            AudioFile,
+            FileType::{self, Flac, Mp4, Mpeg, Opus, Vorbis},
+        },
+        flac::FlacFile,
+        mp4::{Ilst, Mp4File},
+        mpeg::MpegFile,
+        ogg::{OpusFile, VorbisComments, VorbisFile},
+    },
+    tracing::debug,
+};
+
+use crate::library::cue::split_cue_path;
+
+/// Vorbis comment key and ID3v2 user text description of the flag.
+const GAPLESS_KEY: &str = "ITUNESGAPLESS";
+
+/// Returns `true` if `next` continues a gapless album after `current`.
+///
+/// Both tracks must be in the same directory and carry the gapless flag.
+#[must_use]
+pub fn continues_gapless_album(current: &Path, next: &Path) -> bool {
+    is_same_directory(current, next) && has_gapless_flag(current) && has_gapless_flag(next)
+}
+
+/// Returns `true` if the track at `path` is tagged for gapless playback.
+///
+/// CUE tracks read the flag of their audio file. Files whose tags cannot
+/// be read count as not flagged.
+#[must_use]
+pub fn has_gapless_flag(path: &Path) -> bool {
+    let (file_path, _) = split_cue_path(path);
+    match read_gapless_flag(&file_path) {
+        Ok(flagged) => flagged,
+        Err(e) => {
+            debug!(error = %e, path = %file_path.display(), "Cannot read gapless flag");
+            false
+        }
+    }
+}
+
+/// Read the gapless flag from the native tag of the file at `path`.
+///
+/// # Errors
+///
+/// Returns [`LoftyError`] if the file cannot be opened or parsed.
+fn read_gapless_flag(path: &Path) -> Result<bool, LoftyError> {
+    let Some(file_type) = FileType::from_path(path) else {
+        return Ok(false);
+    };
+    let mut file = File::open(path)?;
+    let options = ParseOptions::new()
+        .read_properties(false)
+        .read_cover_art(false);
+    Ok(match file_type {
+        Mp4 => Mp4File::read_from(&mut file, options)?
+            .ilst()
+            .is_some_and(Ilst::is_gapless),
+        Flac => FlacFile::read_from(&mut file, options)?
+            .vorbis_comments()
+            .is_some_and(comment_flag),
+        Vorbis => comment_flag(VorbisFile::read_from(&mut file, options)?.vorbis_comments()),
+        Opus => comment_flag(OpusFile::read_from(&mut file, options)?.vorbis_comments()),
+        Mpeg => MpegFile::read_from(&mut file, options)?
+            .id3v2()
+            .is_some_and(|tag| is_set(tag.get_user_text(GAPLESS_KEY))),
+        _ => false,
+    })
+}
+
+/// Whether Vorbis comments carry the gapless flag.
+fn comment_flag(comments: &VorbisComments) -> bool {
+    is_set(comments.get(GAPLESS_KEY))
+}
+
+/// Whether a flag value is set, written as `1` or `true`.
+fn is_set(value: Option<&str>) -> bool {
+    value
+        .map(str::trim)
+        .is_some_and(|flag| flag == "1" || flag.eq_ignore_ascii_case("true"))
+}
+
+/// Returns `true` if the audio files of both tracks are in the same
+/// directory.
+fn is_same_directory(current: &Path, next: &Path) -> bool {
+    let (current, _) = split_cue_path(current);
+    let (next, _) = split_cue_path(next);
+    current
+        .parent()
+        .is_some_and(|dir| Some(dir) == next.parent())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::library::gapless_flag::{is_same_directory, is_set};
+
+    #[test]
+    fn flag_values_are_read_leniently() {
+        assert!(is_set(Some("1")), "iTunes writes 1");
+        assert!(is_set(Some(" TRUE ")), "true in any case counts");
+        assert!(!is_set(Some("0")), "0 clears the flag");
+        assert!(!is_set(None), "a missing key clears the flag");
+    }
+
+    #[test]
+    fn albums_are_matched_by_directory() {
+        assert!(
+            is_same_directory(
+                Path::new("/music/album/01.flac"),
+                Path::new("/music/album/02.flac")
+            ),
+            "tracks of one folder belong together"
+        );
+        assert!(
+            !is_same_directory(Path::new("/music/a/01.flac"), Path::new("/music/b/01.flac")),
+            "tracks of other folders do not"
+        );
+        assert!(
+            is_same_directory(
+                Path::new("/music/live/image.flac/#t=0.000,60.000"),
+                Path::new("/music/live/02.flac")
+            ),
+            "CUE tracks belong to the folder of their audio file"
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! Lyrics lookup from embedded tags and `.lrc` sidecar files.
+//!
+//! Embedded `LYRICS`/`USLT` tags take precedence; a `.lrc` file with the
+//! same stem as the audio file is the fallback. Text carrying LRC
+//! timestamps becomes timed lines, anything else is kept as plain text.
+
+use std::{fs::read_to_string, path::Path};
+
+use {
+    lofty::{file::TaggedFileExt, read_from_path, tag::ItemKey::Lyrics as LyricsKey},
+    tracing::debug,
+};
+
+/// A single timed lyrics line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LyricLine {
+    /// Start time of the line in seconds.
+    pub time: f64,
+    /// Line text (may be empty for instrumental breaks).
+    pub text: String,
+}
+
+/// Lyrics of one track.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Lyrics {
+    /// Lines with start times, sorted by time.
+    Synced(Vec<LyricLine>),
+    /// Untimed lines.
+    Plain(Vec<String>),
+}
+
+impl Lyrics {
+    /// Index of the line being sung at `elapsed` seconds.
+    ///
+    /// Always `None` for plain lyrics and before the first timed line.
+    #[must_use]
+    pub fn line_at(&self, elapsed: f64) -> Option<usize> {
+        match self {
+            Self::Synced(lines) => lines.partition_point(|l| l.time <= elapsed).checked_sub(1),
+            Self::Plain(_) => None,
+        }
+    }
+
+    /// Text of every line in display order.
+    #[must_use]
+    pub fn texts(&self) -> Vec<&str> {
+        match self {
+            Self::Synced(lines) => lines.iter().map(|l| l.text.as_str()).collect(),
+            Self::Plain(lines) => lines.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// Load lyrics for an audio file.
+///
+/// Returns `None` if neither the tags nor a sidecar `.lrc` file carry lyrics.
+#[must_use]
+pub fn load_lyrics(path: &Path) -> Option<Lyrics> {
+    embedded_lyrics(path)
+        .and_then(|text| parse_lyrics(&text))
+        .or_else(|| parse_lyrics(&sidecar_lyrics(path)?))
+}
+
+/// Parse lyrics text, detecting LRC timestamps.
+///
+/// Metadata tags such as `[ar:...]` are dropped and an `[offset:...]` tag
+/// shifts every timestamp. Returns `None` for blank text.
+#[must_use]
+pub fn parse_lyrics(text: &str) -> Option<Lyrics> {
+    let offset = text.lines().find_map(parse_offset).unwrap_or(0.0);
+    let mut timed = Vec::new();
+    let mut plain = Vec::new();
+    for line in text.lines() {
+        let (times, rest) = split_timestamps(line);
+        timed.extend(times.into_iter().map(|time| LyricLine {
+            time: (time - offset).max(0.0),
+            text: rest.to_string(),
+        }));
+        if !is_tag_line(line) {
+            plain.push(line.trim().to_string());
+        }
+    }
+
+    if !timed.is_empty() {
+        timed.sort_by(|a, b| a.time.total_cmp(&b.time));
+        return Some(Lyrics::Synced(timed));
+    }
+    let first = plain.iter().position(|l| !l.is_empty())?;
+    let last = plain.iter().rposition(|l| !l.is_empty()).unwrap_or(first);
+    Some(Lyrics::Plain(plain.drain(first..=last).collect()))
+}
+
+/// Read the lyrics tag of the file's primary (or first) tag.
+fn embedded_lyrics(path: &Path) -> Option<String> {
+    let tagged_file = match read_from_path(path) {
+        Ok(f) => f,
+        Err(e) => {
+            debug!(error = %e, path = %path.display(), "Cannot read tags for lyrics");
+            return None;
+        }
+    };
+    let tag = tagged_file
+        .primary_tag()
+        .or_else(|| tagged_file.first_tag())?;
+    tag.get_string(LyricsKey).map(String::from)
+}
+
+/// Read the `.lrc` file next to the audio file, if any.
+fn sidecar_lyrics(path: &Path) -> Option<String> {
+    let lrc_path = path.with_extension("lrc");
+    if !lrc_path.is_file() {
+        return None;
+    }
+    match read_to_string(&lrc_path) {
+        Ok(text) => Some(text),
+        Err(e) => {
+            debug!(error = %e, path = %lrc_path.display(), "Cannot read lyrics sidecar");
+            None
+        }
+    }
+}
+
+/// Split leading `[mm:ss.xx]` timestamps from a line.
+fn split_timestamps(line: &str) -> (Vec<f64>, &str) {
+    let mut times = Vec::new();
+    let mut rest = line.trim_start();
+    while let Some((time, after)) = rest
+        .strip_prefix('[')
+        .and_then(|s| s.split_once(']'))
+        .and_then(|(tag, after)| parse_timestamp(tag).map(|t| (t, after)))
+    {
+        times.push(time);
+        rest = after;
+    }
+    (times, rest.trim())
+}
+
+/// Parse an `mm:ss.xx` timestamp into seconds.
+fn parse_timestamp(tag: &str) -> Option<f64> {
+    let (minutes, seconds) = tag.split_once(':')?;
+    let Ok(minutes) = minutes.trim().parse::<u32>() else {
+        return None;
+    };
+    let Ok(seconds) = seconds.trim().parse::<f64>() else {
+        return None;
+    };
+    Some(f64::from(minutes).mul_add(60.0, seconds))
+}
+
+/// Parse an `[offset:+/-ms]` line into seconds.
+fn parse_offset(line: &str) -> Option<f64> {
+    let value = line.trim().strip_prefix("[offset:")?.strip_suffix(']')?;
+    let Ok(ms) = value.trim().parse::<i32>() else {
+        return None;
+    };
+    Some(f64::from(ms) / 1000.0)
+}
+
+/// Whether a line is an LRC tag such as `[ar:Artist]` or a timestamp.
+fn is_tag_line(line: &str) -> bool {
+    let line = line.trim();
+    line.starts_with('[') && line.contains(':') && line.contains(']')
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{Result, bail, ensure};
+
+    use crate::library::lyrics::{Lyrics, parse_lyrics};
+
+    #[test]
+    fn parses_synced_lrc_with_offset_and_repeats() -> Result<()> {
+        let text = "[ar:Someone]\n[offset:500]\n[00:01.50]First\n[00:10.00][00:20.00]Chorus\n";
+        let Some(Lyrics::Synced(lines)) = parse_lyrics(text) else {
+            bail!("timestamps must produce synced lyrics");
+        };
+        let times: Vec<f64> = lines.iter().map(|l| l.time).collect();
+        ensure!(times == [1.0, 9.5, 19.5], "unexpected times: {times:?}");
+        ensure!(
+            lines
+                .iter()
+                .map(|l| l.text.as_str())
+                .eq(["First", "Chorus", "Chorus"]),
+            "unexpected texts"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn plain_lyrics_drop_outer_blank_lines() {
+        let lyrics = parse_lyrics("\n\nLine one\n\nLine two\n\n");
+        assert_eq!(
+            lyrics,
+            Some(Lyrics::Plain(vec![
+                "Line one".to_string(),
+                String::new(),
+                "Line two".to_string(),
+            ])),
+            "Plain lyrics keep inner stanza breaks only"
+        );
+        assert_eq!(parse_lyrics(" \n "), None, "Blank text has no lyrics");
+    }
+
+    #[test]
+    fn line_at_follows_position() -> Result<()> {
+        let Some(lyrics) = parse_lyrics("[00:05.00]a\n[00:10.00]b") else {
+            bail!("lyrics must parse");
+        };
+        ensure!(lyrics.line_at(1.0).is_none(), "no line before the first");
+        ensure!(lyrics.line_at(5.0) == Some(0), "first line at its start");
+        ensure!(lyrics.line_at(60.0) == Some(1), "last line stays current");
+        Ok(())
+    }
+}
@@ -1,22 +1,40 @@
 //! Metadata extraction from audio files using the `lofty` crate.
 
-use std::{fs::metadata, path::Path};
+use std::{
+    fs::metadata,
+    path::Path,
+    sync::mpsc::{RecvTimeoutError, channel},
+    thread::Builder,
+    time::Duration,
+};
 
 use {
     lofty::{
         error::LoftyError,
         file::{
             AudioFile,
-            FileType::{self, Aiff, Flac, Mp4, Mpeg, Opus, Vorbis, Wav},
-            TaggedFile, TaggedFileExt,
+            FileType::{self, Aac, Aiff, Flac, Mp4, Mpeg, Opus, Vorbis, Wav},
+            TaggedFileExt,
         },
-        prelude::Accessor,
         read_from_path,
-        tag::ItemKey::{AlbumArtist, RecordingDate},
     },
+    num_traits::cast::AsPrimitive,
     thiserror::Error,
+    tracing::debug,
+};
+
+use crate::library::{
+    ogg_duration::checked_ogg_duration,
+    ogg_flac::{OggFlac, read_ogg_flac},
+    tag_fields::{TagFields, file_tags},
 };
 
+/// Separator between genres in a stored genre value.
+pub const GENRE_SEPARATOR: &str = "; ";
+
+/// Album artist of compilations that carry no album artist tag.
+pub const VARIOUS_ARTISTS: &str = "Various Artists";
+
 /// Extracted metadata from an audio file.
 #[derive(Debug, Clone)]
 pub struct AudioMetadata {
@@ -26,16 +44,22 @@ pub struct AudioMetadata {
     pub artist: Option<String>,
     /// Album artist name (may differ from track artist for compilations).
     pub album_artist: Option<String>,
+    /// Whether the track is tagged as part of a compilation.
+    pub compilation: bool,
     /// Album title.
     pub album: Option<String>,
-    /// Release year.
+    /// Release year of this edition.
     pub year: Option<i32>,
+    /// Year the recording was first released, when tagged separately.
+    pub original_year: Option<i32>,
     /// Genre tag.
     pub genre: Option<String>,
     /// Track number within album/disc.
     pub track_number: Option<i32>,
     /// Disc number.
     pub disc_number: Option<i32>,
+    /// Number of discs in the release.
+    pub disc_total: Option<i32>,
     /// Duration in seconds.
     pub duration: f64,
     /// Sample rate in Hz.
@@ -69,6 +93,49 @@ pub enum MetadataError {
     /// Failed to parse a tag value.
     #[error("Failed to parse tag value: {0}")]
     ParseError(String),
+    /// Reading the file did not finish in time.
+    #[error("Reading metadata timed out after {0:?}")]
+    Timeout(Duration),
+}
+
+/// Extract metadata, giving up once `timeout` has passed.
+///
+/// The read runs on its own thread so that a file on an unresponsive
+/// network share cannot block the caller. A read that times out keeps its
+/// thread until the filesystem answers; its result is then discarded.
+/// A zero `timeout` reads on the calling thread without a limit.
+///
+/// # Errors
+///
+/// Returns [`MetadataError::Timeout`] if the read does not finish in time,
+/// or any error of [`extract_metadata`].
+pub fn extract_metadata_within(
+    path: &Path,
+    timeout: Duration,
+) -> Result<AudioMetadata, MetadataError> {
+    if timeout.is_zero() {
+        return extract_metadata(path);
+    }
+    let (tx, rx) = channel();
+    let owned = path.to_path_buf();
+    let reader = Builder::new()
+        .name("metadata-read".to_string())
+        .spawn(move || {
+            if tx.send(extract_metadata(&owned)).is_err() {
+                debug!(path = %owned.display(), "Discarding metadata read that timed out");
+            }
+        });
+    if reader.is_err() {
+        return extract_metadata(path);
+    }
+    match rx.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => Err(MetadataError::Timeout(timeout)),
+        Err(RecvTimeoutError::Disconnected) => Err(MetadataError::ParseError(format!(
+            "metadata reader panicked on {}",
+            path.display()
+        ))),
+    }
 }
 
 /// Extract metadata from an audio file at the given path.
@@ -85,151 +152,124 @@ pub enum MetadataError {
 ///
 /// Returns [`MetadataError`] if the file cannot be read, parsed, or has invalid properties.
 pub fn extract_metadata(path: &Path) -> Result<AudioMetadata, MetadataError> {
-    let tagged_file = read_from_path(path)?;
+    let tagged_file = match read_from_path(path) {
+        Ok(tagged_file) => tagged_file,
+        Err(e) if is_ogg(path) => {
+            return read_ogg_flac(path)?.map_or(Err(e.into()), |o| ogg_flac_metadata(path, o));
+        }
+        Err(e) => return Err(e.into()),
+    };
     let props = tagged_file.properties();
     let file_type = tagged_file.file_type();
+    let fields = TagFields::read(&file_tags(&tagged_file));
 
-    let title = extract_title(&tagged_file, path);
-    let artist = extract_artist(&tagged_file);
-    let album_artist = extract_album_artist(&tagged_file);
-    let album = extract_album(&tagged_file);
-    let year = extract_year(&tagged_file);
-    let genre = extract_genre(&tagged_file);
-    let track_number = extract_track_number(&tagged_file);
-    let disc_number = extract_disc_number(&tagged_file);
-
-    let duration = props.duration().as_secs_f64();
+    // lofty reads the length of Ogg streams from the last page only.
+    let duration = match file_type {
+        Opus | Vorbis => checked_ogg_duration(path, props.duration().as_secs_f64()),
+        _ => props.duration().as_secs_f64(),
+    };
     if duration <= 0.0 {
         return Err(MetadataError::InvalidDuration(duration));
     }
 
-    let sample_rate = i32::try_from(props.sample_rate().unwrap_or(0)).unwrap_or(0);
-
-    let bit_depth = props.bit_depth().map(i32::from);
-
-    let channels = i32::from(props.channels().unwrap_or(0));
-
-    let codec = codec_name(file_type).to_string();
-
-    let lossless = matches!(file_type, Flac | Wav | Aiff);
-
-    let bitrate = props.audio_bitrate().map(u32::cast_signed);
-
-    let file_size = metadata(path).map_or(0, |m| m.len().cast_signed());
-
     Ok(AudioMetadata {
-        title,
-        artist,
-        album_artist,
-        album,
-        year,
-        genre,
-        track_number,
-        disc_number,
         duration,
-        sample_rate,
-        bit_depth,
-        channels,
-        codec,
-        lossless,
-        bitrate,
-        file_size,
+        sample_rate: i32::try_from(props.sample_rate().unwrap_or(0)).unwrap_or(0),
+        bit_depth: props.bit_depth().map(i32::from),
+        channels: i32::from(props.channels().unwrap_or(0)),
+        codec: codec_name(file_type).to_string(),
+        lossless: matches!(file_type, Flac | Wav | Aiff),
+        bitrate: props.audio_bitrate().map(u32::cast_signed),
+        ..tagged_metadata(path, fields)
     })
 }
 
-/// Extract the title from tags, falling back to filename stem.
-fn extract_title(tagged_file: &TaggedFile, path: &Path) -> Option<String> {
-    let tag = tagged_file
-        .primary_tag()
-        .or_else(|| tagged_file.first_tag());
-
-    tag.as_ref()
-        .and_then(|t| t.title().map(String::from))
-        .or_else(|| path.file_stem().and_then(|s| s.to_str()).map(String::from))
-}
-
-/// Extract the artist name from tags.
-fn extract_artist(tagged_file: &TaggedFile) -> Option<String> {
-    let tag = tagged_file
-        .primary_tag()
-        .or_else(|| tagged_file.first_tag())?;
-
-    tag.artist().map(String::from)
-}
-
-/// Extract the album artist from tags.
-fn extract_album_artist(tagged_file: &TaggedFile) -> Option<String> {
-    let tag = tagged_file
-        .primary_tag()
-        .or_else(|| tagged_file.first_tag())?;
-
-    tag.get_string(AlbumArtist).map(String::from)
-}
-
-/// Extract the album title from tags.
-fn extract_album(tagged_file: &TaggedFile) -> Option<String> {
-    let tag = tagged_file
-        .primary_tag()
-        .or_else(|| tagged_file.first_tag())?;
-
-    tag.album().map(String::from)
+/// Build the metadata of an Ogg FLAC file lofty could not read.
+///
+/// # Errors
+///
+/// Returns [`MetadataError::InvalidDuration`] if the stream records no length.
+fn ogg_flac_metadata(path: &Path, ogg_flac: OggFlac) -> Result<AudioMetadata, MetadataError> {
+    if ogg_flac.duration <= 0.0 {
+        return Err(MetadataError::InvalidDuration(ogg_flac.duration));
+    }
+    let metadata = tagged_metadata(path, TagFields::read(&[&ogg_flac.tag]));
+    let bits: f64 = (metadata.file_size * 8).as_();
+    Ok(AudioMetadata {
+        duration: ogg_flac.duration,
+        sample_rate: ogg_flac.sample_rate.cast_signed(),
+        bit_depth: Some(i32::from(ogg_flac.bit_depth)),
+        channels: i32::from(ogg_flac.channels),
+        codec: codec_name(Flac).to_string(),
+        lossless: true,
+        bitrate: Some((bits / ogg_flac.duration / 1000.0).round().as_()),
+        ..metadata
+    })
 }
 
-/// Extract the release year from tags.
+/// Metadata holding the tag `fields` and the file size, with empty stream
+/// properties.
 ///
-/// Tries to parse the year as a plain integer first. If that fails,
-/// scans for a 4-digit year substring (handles ranges like "2017–2019"
-/// and full dates like "2017-03-10"). Returns `None` if no year found.
-fn extract_year(tagged_file: &TaggedFile) -> Option<i32> {
-    let tag = tagged_file
-        .primary_tag()
-        .or_else(|| tagged_file.first_tag())?;
-
-    let s = tag.get_string(RecordingDate)?;
-
-    if let Ok(year) = s.parse::<i32>() {
-        return Some(year);
+/// The title falls back to the file name without its extension.
+fn tagged_metadata(path: &Path, fields: TagFields) -> AudioMetadata {
+    AudioMetadata {
+        title: fields
+            .title
+            .or_else(|| path.file_stem().and_then(|s| s.to_str()).map(String::from)),
+        artist: fields.artist,
+        album_artist: fields.album_artist,
+        compilation: fields.compilation,
+        album: fields.album,
+        year: fields.year,
+        original_year: fields.original_year,
+        genre: fields.genre,
+        track_number: fields.track_number,
+        disc_number: fields.disc_number,
+        disc_total: fields.disc_total,
+        duration: 0.0,
+        sample_rate: 0,
+        bit_depth: None,
+        channels: 0,
+        codec: String::new(),
+        lossless: false,
+        bitrate: None,
+        file_size: metadata(path).map_or(0, |m| m.len().cast_signed()),
     }
-
-    s.chars()
-        .collect::<Vec<_>>()
-        .windows(4)
-        .find(|w| {
-            let s: String = w.iter().collect();
-            s.chars().all(|c| c.is_ascii_digit())
-        })
-        .map(|w| {
-            let s: String = w.iter().collect();
-            s.parse::<i32>()
-        })
-        .and_then(Result::ok)
 }
 
-/// Extract the genre from tags.
-fn extract_genre(tagged_file: &TaggedFile) -> Option<String> {
-    let tag = tagged_file
-        .primary_tag()
-        .or_else(|| tagged_file.first_tag())?;
-
-    tag.genre().map(String::from)
+/// Whether `path` has an Ogg extension, which may hold an Ogg FLAC stream.
+fn is_ogg(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ogg") || ext.eq_ignore_ascii_case("oga"))
 }
 
-/// Extract the track number from tags.
-fn extract_track_number(tagged_file: &TaggedFile) -> Option<i32> {
-    let tag = tagged_file
-        .primary_tag()
-        .or_else(|| tagged_file.first_tag())?;
-
-    tag.track().map(u32::cast_signed)
+/// Split a raw genre tag into individual genres.
+///
+/// Values are separated by `;`, `/`, `|` or NUL, trimmed, and deduplicated
+/// case-insensitively while keeping the first spelling seen.
+#[must_use]
+pub fn split_genres(raw: &str) -> Vec<String> {
+    let mut genres: Vec<String> = Vec::new();
+    for genre in raw
+        .split([';', '/', '|', '\0'])
+        .map(str::trim)
+        .filter(|g| !g.is_empty())
+    {
+        if !genres.iter().any(|g| g.eq_ignore_ascii_case(genre)) {
+            genres.push(genre.to_string());
+        }
+    }
+    genres
 }
 
-/// Extract the disc number from tags.
-fn extract_disc_number(tagged_file: &TaggedFile) -> Option<i32> {
-    let tag = tagged_file
-        .primary_tag()
-        .or_else(|| tagged_file.first_tag())?;
-
-    tag.disk().map(u32::cast_signed)
+/// Normalize a raw genre tag to the stored form, e.g. `Rock; Pop`.
+///
+/// Returns `None` if the tag holds no genre.
+#[must_use]
+pub fn normalize_genre(raw: &str) -> Option<String> {
+    let genres = split_genres(raw);
+    (!genres.is_empty()).then(|| genres.join(GENRE_SEPARATOR))
 }
 
 /// Get a human-readable codec name from the file type.
@@ -237,7 +277,7 @@ fn codec_name(file_type: FileType) -> &'static str {
     match file_type {
         Flac => "flac",
         Mpeg => "mp3",
-        Mp4 => "aac",
+        Aac | Mp4 => "aac",
         Vorbis => "ogg",
         Opus => "opus",
         Wav => "wav",
@@ -246,6 +286,20 @@ fn codec_name(file_type: FileType) -> &'static str {
     }
 }
 
+/// Name of the artist the track's album is filed under.
+///
+/// The album artist tag wins. Compilations without one are filed under
+/// [`VARIOUS_ARTISTS`] so their tracks stay on one album; other tracks
+/// fall back to their own artist.
+#[must_use]
+pub fn album_artist_name(meta: &AudioMetadata) -> &str {
+    meta.album_artist
+        .as_deref()
+        .or_else(|| meta.compilation.then_some(VARIOUS_ARTISTS))
+        .or(meta.artist.as_deref())
+        .unwrap_or("Unknown Artist")
+}
+
 /// Compute a metadata fingerprint for duplicate detection.
 ///
 /// Returns a tuple of (artist, album, title, `track_number`) suitable for comparison.
@@ -271,15 +325,17 @@ pub fn metadata_fingerprint(meta: &AudioMetadata) -> (String, String, String, Op
 
 #[cfg(test)]
 pub mod tests {
-    use std::path::Path;
+    use std::{path::Path, time::Duration};
 
     use {
-        anyhow::{Result, bail},
-        lofty::file::FileType::{Aiff, Flac, Mp4, Mpeg, Opus, Vorbis, Wav},
+        anyhow::{Result, bail, ensure},
+        lofty::file::FileType::{Aac, Aiff, Flac, Mp4, Mpeg, Opus, Vorbis, Wav},
     };
 
     use crate::library::metadata::{
-        AudioMetadata, codec_name, extract_metadata, metadata_fingerprint,
+        AudioMetadata, MetadataError, VARIOUS_ARTISTS, album_artist_name, codec_name,
+        extract_metadata, extract_metadata_within, metadata_fingerprint, normalize_genre,
+        split_genres,
     };
 
     #[must_use]
@@ -288,11 +344,14 @@ pub mod tests {
             title: Some("My Track".to_string()),
             artist: Some("Some Artist".to_string()),
             album_artist: Some("Some Artist".to_string()),
+            compilation: false,
             album: Some("Some Album".to_string()),
             year: Some(2024),
+            original_year: None,
             genre: Some("Rock".to_string()),
             track_number: Some(3),
             disc_number: Some(1),
+            disc_total: Some(1),
             duration: 240.0,
             sample_rate: 44100,
             bit_depth: Some(16),
@@ -310,11 +369,14 @@ pub mod tests {
             title: None,
             artist: None,
             album_artist: None,
+            compilation: false,
             album: None,
             year: None,
+            original_year: None,
             genre: None,
             track_number: None,
             disc_number: None,
+            disc_total: None,
             duration: 120.0,
             sample_rate: 44100,
             bit_depth: None,
@@ -335,6 +397,19 @@ pub mod tests {
         Ok(())
     }
 
+    #[test]
+    fn extract_metadata_within_reports_read_errors() -> Result<()> {
+        let path = Path::new("/nonexistent/file.flac");
+        for timeout in [Duration::ZERO, Duration::from_secs(10)] {
+            let result = extract_metadata_within(path, timeout);
+            ensure!(
+                matches!(&result, Err(e) if !matches!(e, MetadataError::Timeout(_))),
+                "missing file must fail with a read error, got {result:?}"
+            );
+        }
+        Ok(())
+    }
+
     #[test]
     fn metadata_fingerprint_normalizes() {
         let meta = test_metadata();
@@ -355,14 +430,57 @@ pub mod tests {
         assert_eq!(track, None);
     }
 
+    #[test]
+    fn compilations_are_filed_under_various_artists() {
+        let untagged = AudioMetadata {
+            album_artist: None,
+            artist: Some("Guest".to_string()),
+            compilation: true,
+            ..test_metadata()
+        };
+        assert_eq!(album_artist_name(&untagged), VARIOUS_ARTISTS);
+
+        let tagged = AudioMetadata {
+            album_artist: Some("Label Sampler".to_string()),
+            ..untagged.clone()
+        };
+        assert_eq!(album_artist_name(&tagged), "Label Sampler");
+
+        let single = AudioMetadata {
+            compilation: false,
+            ..untagged
+        };
+        assert_eq!(album_artist_name(&single), "Guest");
+        assert_eq!(
+            album_artist_name(&test_metadata_defaults()),
+            "Unknown Artist"
+        );
+    }
+
     #[test]
     fn codec_name_variants() {
         assert_eq!(codec_name(Flac), "flac");
         assert_eq!(codec_name(Mpeg), "mp3");
         assert_eq!(codec_name(Mp4), "aac");
+        assert_eq!(codec_name(Aac), "aac", "raw ADTS streams");
         assert_eq!(codec_name(Vorbis), "ogg");
         assert_eq!(codec_name(Opus), "opus");
         assert_eq!(codec_name(Wav), "wav");
         assert_eq!(codec_name(Aiff), "aiff");
     }
+
+    #[test]
+    fn multi_value_genres_are_split_and_normalized() {
+        assert_eq!(
+            split_genres("Rock/Pop; rock |Jazz\0"),
+            vec!["Rock", "Pop", "Jazz"],
+            "Separators split and case-insensitive duplicates are dropped"
+        );
+        assert_eq!(
+            normalize_genre(" Electronic;Ambient ").as_deref(),
+            Some("Electronic; Ambient"),
+            "Stored form joins genres with the separator"
+        );
+        assert_eq!(normalize_genre(" ; / "), None, "Empty tags store no genre");
+    }
 }
@@ -1,7 +1,31 @@
-//! Library scanning, metadata extraction, deduplication, file watching, and artwork.
+//! Library scanning, CUE sheets, metadata extraction and tag writing, lyrics,
+//! deduplication, dynamic range, track numbering fixes, audio formats, file
+//! watching, artwork, thumbnails, waveforms and their cache, play counting,
+//! scrobbling, "now playing" share text, and undo of destructive library
+//! actions.
 
+pub mod artist_credit;
 pub mod artwork;
+pub mod cache;
+pub mod cue;
 pub mod dedup;
+pub mod dr_batch;
+pub mod dynamic_range;
+pub mod formats;
+pub mod gapless_flag;
+pub mod lyrics;
 pub mod metadata;
+pub mod numbering;
+pub mod ogg_duration;
+pub mod ogg_flac;
+pub mod play_threshold;
+pub mod scan_status;
 pub mod scanner;
+pub mod scrobble;
+pub mod share;
+pub mod tag_fields;
+pub mod tag_writer;
+pub mod thumbnail;
+pub mod undo;
 pub mod watcher;
+pub mod waveform;
@@ -0,0 +1,270 @@
+//! Detection and correction of broken track numbering within an album.
+//!
+//! Some rips leave tracks without a number, numbered zero, or with the same
+//! number twice on a disc, so the album lists them in file name order or
+//! worse. Such albums can be renumbered from the file names: a leading
+//! number (`03 - Title.flac`) or disc-track prefix (`1-03 Title.flac`) sets
+//! the order, and the file name itself breaks ties. The corrected numbers
+//! are stored in the library; writing them to the files is optional.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    library::{
+        cue::split_cue_path,
+        tag_writer::{TagChanges, TagWriteError, check_writable, write_metadata},
+    },
+    storage::{FieldUpdate::Set, Storage, StorageResult, Track, TrackUpdate},
+};
+
+/// A new number for one track.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Renumbering {
+    /// Track to renumber.
+    pub track_id: i64,
+    /// Library path of the track.
+    pub file_path: String,
+    /// Number within its disc, starting at 1.
+    pub number: i32,
+}
+
+/// Whether any track of an album is unnumbered, numbered zero, or shares
+/// its number with another track on the same disc.
+#[must_use]
+pub fn needs_renumbering(tracks: &[Track]) -> bool {
+    let mut seen = HashSet::new();
+    tracks.iter().any(|t| match t.number {
+        Some(number) if number > 0 => !seen.insert((t.disc(), number)),
+        _ => true,
+    })
+}
+
+/// Number the tracks of each disc from 1 in file name order.
+///
+/// Returns only the tracks whose number changes.
+#[must_use]
+pub fn renumber_by_file_name(tracks: &[Track]) -> Vec<Renumbering> {
+    let mut ordered: Vec<(i32, Option<u32>, String, &Track)> = tracks
+        .iter()
+        .map(|t| {
+            let name = file_name(&t.audio.file_path);
+            (
+                t.disc(),
+                file_name_track_hint(&name),
+                name.to_lowercase(),
+                t,
+            )
+        })
+        .collect();
+    ordered.sort_by(|a, b| (a.0, a.1.is_none(), a.1, &a.2).cmp(&(b.0, b.1.is_none(), b.1, &b.2)));
+
+    let mut previous_disc = None;
+    let mut number = 0;
+    let mut changes = Vec::new();
+    for (disc, _, _, track) in ordered {
+        number = if previous_disc == Some(disc) {
+            number + 1
+        } else {
+            1
+        };
+        previous_disc = Some(disc);
+        if track.number != Some(number) {
+            changes.push(Renumbering {
+                track_id: track.id,
+                file_path: track.audio.file_path.clone(),
+                number,
+            });
+        }
+    }
+    changes
+}
+
+/// Store the new track numbers in the library.
+///
+/// # Errors
+///
+/// Returns a storage error if a track cannot be updated.
+pub async fn apply_renumbering<S: Storage>(
+    storage: &S,
+    changes: &[Renumbering],
+) -> StorageResult<()> {
+    for change in changes {
+        storage
+            .update_track(
+                change.track_id,
+                TrackUpdate {
+                    track_number: Set(change.number),
+                    ..TrackUpdate::default()
+                },
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// Write the new track numbers to the audio files.
+///
+/// Tracks split from a CUE sheet share one file and are skipped. Every file
+/// is checked for write access before any of them is touched.
+///
+/// # Errors
+///
+/// Returns the first [`TagWriteError`] encountered.
+pub fn write_track_numbers(changes: &[Renumbering]) -> Result<(), TagWriteError> {
+    let files: Vec<(PathBuf, u32)> = changes
+        .iter()
+        .filter_map(|change| {
+            let (path, range) = split_cue_path(Path::new(&change.file_path));
+            let number = u32::try_from(change.number).ok()?;
+            range.is_none().then_some((path, number))
+        })
+        .collect();
+    files
+        .iter()
+        .try_for_each(|(path, _)| check_writable(path))?;
+    files.iter().try_for_each(|(path, number)| {
+        let changes = TagChanges {
+            track_number: Some(*number),
+            ..TagChanges::default()
+        };
+        write_metadata(path, &changes)
+    })
+}
+
+/// File name of a library path, without any cue range.
+fn file_name(path: &str) -> String {
+    split_cue_path(Path::new(path))
+        .0
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Track number a file name starts with.
+///
+/// A one- or two-digit disc prefix followed by `-` or `.` and more digits,
+/// as in `1-03 Title.flac`, yields the number after the prefix.
+fn file_name_track_hint(name: &str) -> Option<u32> {
+    let first = leading_digits(name);
+    if first.is_empty() {
+        return None;
+    }
+    let track = name
+        .get(first.len()..)
+        .and_then(|rest| rest.strip_prefix(['-', '.']))
+        .map(leading_digits)
+        .filter(|digits| !digits.is_empty() && first.len() <= 2)
+        .unwrap_or(first);
+    track.parse().ok()
+}
+
+/// The run of ASCII digits `s` starts with.
+fn leading_digits(s: &str) -> &str {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    s.get(..end).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        library::numbering::{file_name_track_hint, needs_renumbering, renumber_by_file_name},
+        storage::{Track, TrackAudio},
+    };
+
+    fn track(id: i64, path: &str, number: Option<i32>, disc: Option<i32>) -> Track {
+        Track {
+            id,
+            title: format!("Track {id}"),
+            number,
+            disc_number: disc,
+            disc_total: None,
+            duration: 180.0,
+            audio: TrackAudio {
+                file_path: path.to_string(),
+                content_hash: None,
+                format: "FLAC".to_string(),
+                sample_rate: 44_100,
+                bit_depth: Some(16),
+                channels: 2,
+                codec: "flac".to_string(),
+                lossless: true,
+                bitrate: None,
+                album_id: Some(1),
+                artist_id: None,
+                file_size: 1024,
+                last_modified: "2024-01-01T00:00:00Z".to_string(),
+            },
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn detects_missing_zero_and_duplicate_numbers() {
+        let good = [
+            track(1, "/a/01.flac", Some(1), Some(1)),
+            track(2, "/a/02.flac", Some(1), Some(2)),
+        ];
+        assert!(
+            !needs_renumbering(&good),
+            "Same number on two discs is fine"
+        );
+        for broken in [
+            [
+                track(1, "/a/x.flac", None, None),
+                track(2, "/a/y.flac", Some(2), None),
+            ],
+            [
+                track(1, "/a/x.flac", Some(0), None),
+                track(2, "/a/y.flac", Some(2), None),
+            ],
+            [
+                track(1, "/a/x.flac", Some(3), None),
+                track(2, "/a/y.flac", Some(3), None),
+            ],
+        ] {
+            assert!(needs_renumbering(&broken), "Broken numbering not detected");
+        }
+    }
+
+    #[test]
+    fn file_name_hints_read_track_and_disc_prefixes() {
+        assert_eq!(
+            file_name_track_hint("03 - Song.flac"),
+            Some(3),
+            "Plain number"
+        );
+        assert_eq!(
+            file_name_track_hint("1-07 Song.flac"),
+            Some(7),
+            "Disc-track prefix"
+        );
+        assert_eq!(
+            file_name_track_hint("12. Song.flac"),
+            Some(12),
+            "Number and dot"
+        );
+        assert_eq!(file_name_track_hint("Song.flac"), None, "No number");
+    }
+
+    #[test]
+    fn renumbers_each_disc_in_file_name_order() {
+        let tracks = [
+            track(1, "/a/10 Ten.flac", None, Some(1)),
+            track(2, "/a/2 Two.flac", Some(0), Some(1)),
+            track(3, "/a/Bonus.flac", None, Some(1)),
+            track(4, "/a/2-01 Other.flac", Some(1), Some(2)),
+        ];
+        let changes: Vec<(i64, i32)> = renumber_by_file_name(&tracks)
+            .into_iter()
+            .map(|c| (c.track_id, c.number))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![(2, 1), (1, 2), (3, 3)],
+            "Numbered files first, unnumbered last, unchanged tracks left out"
+        );
+    }
+}
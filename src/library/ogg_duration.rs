@@ -0,0 +1,372 @@
+//! Length of Ogg streams from the granule positions of their pages.
+//!
+//! Opus and Vorbis files record no length in their headers; it follows
+//! from the granule position of the last page. Chained files are several
+//! streams played one after another, each starting again from its own
+//! header pages, so reading only the last page undercounts them. This walks
+//! the page headers of the whole file, skipping the audio, and adds up the
+//! length of every link.
+
+use std::{
+    fs::File,
+    io::{BufReader, ErrorKind::UnexpectedEof, Read, Result, Seek},
+    path::Path,
+};
+
+use {
+    num_traits::cast::AsPrimitive,
+    tracing::{debug, warn},
+};
+
+/// Capture pattern starting every Ogg page.
+const OGG_CAPTURE: &[u8; 4] = b"OggS";
+
+/// Length of an Ogg page header before its segment table.
+const PAGE_HEADER_LEN: usize = 27;
+
+/// Header type flag of the first page of a stream.
+const BEGINNING_OF_STREAM: u8 = 0x02;
+
+/// Granule position of pages on which no packet ends.
+const NO_GRANULE: u64 = u64::MAX;
+
+/// Sample rate of Opus granule positions, whatever the input rate.
+const OPUS_GRANULE_RATE: u32 = 48_000;
+
+/// Extensions of files that may hold Ogg streams.
+const OGG_EXTENSIONS: [&str; 3] = ["ogg", "oga", "opus"];
+
+/// Difference in seconds from which a reported length counts as wrong.
+const DURATION_TOLERANCE: f64 = 0.5;
+
+/// Links found while walking the pages of a possibly chained file.
+#[derive(Debug, Default)]
+struct ChainScan {
+    /// Audio streams found so far, in file order.
+    links: Vec<Link>,
+    /// Index in `links` of the audio stream of the current link.
+    current: Option<usize>,
+    /// Whether the last page began a stream, so more may begin in the same
+    /// link.
+    in_headers: bool,
+}
+
+impl ChainScan {
+    /// Note the first page of a stream, whose body is `body`.
+    fn begin_stream(&mut self, serial: u32, body: &[u8]) {
+        if !self.in_headers {
+            // A new group of streams begins: the next link of a chain.
+            self.current = None;
+            self.in_headers = true;
+        }
+        if self.current.is_none()
+            && let Some(link) = parse_id_header(serial, body)
+        {
+            self.links.push(link);
+            self.current = Some(self.links.len() - 1);
+        }
+    }
+
+    /// Note a later page of a stream, keeping its granule position if it
+    /// belongs to the audio stream of the current link.
+    fn continue_stream(&mut self, page: &PageHeader) {
+        self.in_headers = false;
+        let link = self.current.and_then(|i| self.links.get_mut(i));
+        if let Some(link) = link.filter(|l| l.serial == page.serial && page.granule != NO_GRANULE) {
+            link.last_granule = Some(page.granule);
+        }
+    }
+}
+
+/// An audio stream of one link of a chained file.
+#[derive(Debug, Clone, Copy)]
+struct Link {
+    /// Serial number of the stream's pages.
+    serial: u32,
+    /// Granule positions per second.
+    rate: u32,
+    /// Granules at the start that are decoded but not played.
+    pre_skip: u64,
+    /// Granule position of the last page seen with one.
+    last_granule: Option<u64>,
+}
+
+impl Link {
+    /// Played length of the stream in seconds.
+    fn seconds(&self) -> f64 {
+        let granules: f64 = self
+            .last_granule
+            .unwrap_or(0)
+            .saturating_sub(self.pre_skip)
+            .as_();
+        granules / f64::from(self.rate)
+    }
+}
+
+/// Header of one Ogg page.
+struct PageHeader {
+    /// Whether this is the first page of its stream.
+    beginning: bool,
+    /// Granule position after the last packet ending on the page.
+    granule: u64,
+    /// Serial number of the page's stream.
+    serial: u32,
+    /// Length of the page body in bytes.
+    body_len: usize,
+}
+
+/// Whether `path` has the extension of an Ogg file.
+#[must_use]
+pub fn has_ogg_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| OGG_EXTENSIONS.iter().any(|o| ext.eq_ignore_ascii_case(o)))
+}
+
+/// Length of the Ogg file at `path`, checked against a `reported` length.
+///
+/// Headers and quick estimates miss the later links of chained files, so
+/// the length scanned from the pages wins. `reported` is kept if the file
+/// cannot be scanned or yields no length.
+///
+/// # Arguments
+///
+/// * `path` - Path to the Ogg file
+/// * `reported` - Length in seconds reported by the tag or container reader
+///
+/// # Returns
+///
+/// The length in seconds to use for the file.
+#[must_use]
+pub fn checked_ogg_duration(path: &Path, reported: f64) -> f64 {
+    let scanned = File::open(path).and_then(|file| ogg_duration(&mut BufReader::new(file)));
+    match scanned {
+        Ok(Some(seconds)) if seconds > 0.0 => {
+            if (seconds - reported).abs() > DURATION_TOLERANCE {
+                debug!(
+                    path = %path.display(),
+                    reported,
+                    scanned = seconds,
+                    "Using scanned Ogg length over reported length"
+                );
+            }
+            seconds
+        }
+        Ok(_) => reported,
+        Err(e) => {
+            warn!(path = %path.display(), error = %e, "Failed to scan Ogg pages for length");
+            reported
+        }
+    }
+}
+
+/// Length in seconds of the Ogg Opus, Vorbis or FLAC audio in `reader`.
+///
+/// Every link of a chained file counts. A truncated last page ends the
+/// scan rather than failing it.
+///
+/// # Returns
+///
+/// `None` if `reader` is not an Ogg stream or holds no audio stream whose
+/// length is known.
+///
+/// # Errors
+///
+/// Returns an error if reading or seeking fails other than by ending early.
+pub fn ogg_duration<R: Read + Seek>(reader: &mut R) -> Result<Option<f64>> {
+    let mut scan = ChainScan::default();
+    while let Some(page) = read_page_header(reader)? {
+        if !page.beginning {
+            reader.seek_relative(i64::try_from(page.body_len).unwrap_or(i64::MAX))?;
+            scan.continue_stream(&page);
+            continue;
+        }
+        let mut body = vec![0_u8; page.body_len];
+        if !fill(reader, &mut body)? {
+            break;
+        }
+        scan.begin_stream(page.serial, &body);
+    }
+
+    let links = scan.links;
+    if links.iter().all(|l| l.last_granule.is_none()) {
+        return Ok(None);
+    }
+    Ok(Some(links.iter().map(Link::seconds).sum()))
+}
+
+/// Read the next page header and segment table.
+///
+/// # Returns
+///
+/// `None` at the end of the stream, on a truncated header, or where no
+/// Ogg page starts.
+fn read_page_header<R: Read>(reader: &mut R) -> Result<Option<PageHeader>> {
+    let mut header = [0_u8; PAGE_HEADER_LEN];
+    if !fill(reader, &mut header)? || header.get(..4) != Some(OGG_CAPTURE.as_slice()) {
+        return Ok(None);
+    }
+    let mut lacing = vec![0_u8; header.last().copied().map_or(0, usize::from)];
+    if !fill(reader, &mut lacing)? {
+        return Ok(None);
+    }
+    let granule = header
+        .get(6..)
+        .and_then(<[u8]>::first_chunk)
+        .map_or(NO_GRANULE, |b| u64::from_le_bytes(*b));
+    let serial = header
+        .get(14..)
+        .and_then(<[u8]>::first_chunk)
+        .map_or(0, |b| u32::from_le_bytes(*b));
+    Ok(Some(PageHeader {
+        beginning: header.get(5).is_some_and(|t| t & BEGINNING_OF_STREAM != 0),
+        granule,
+        serial,
+        body_len: lacing.iter().map(|l| usize::from(*l)).sum(),
+    }))
+}
+
+/// Recognise the identification header opening an audio stream.
+///
+/// # Returns
+///
+/// `None` for streams other than Opus, Vorbis and FLAC, or a header
+/// without a sample rate.
+fn parse_id_header(serial: u32, packet: &[u8]) -> Option<Link> {
+    let (rate, pre_skip) = if packet.starts_with(b"OpusHead") {
+        let pre_skip = u16::from_le_bytes(*packet.get(10..)?.first_chunk()?);
+        (OPUS_GRANULE_RATE, u64::from(pre_skip))
+    } else if packet.starts_with(b"\x01vorbis") {
+        (u32::from_le_bytes(*packet.get(12..)?.first_chunk()?), 0)
+    } else if packet.starts_with(b"\x7fFLAC") {
+        // STREAMINFO follows the 13-byte mapping header and block header.
+        let packed = u64::from_be_bytes(*packet.get(27..)?.first_chunk()?);
+        ((packed >> 44).as_(), 0)
+    } else {
+        return None;
+    };
+    (rate > 0).then_some(Link {
+        serial,
+        rate,
+        pre_skip,
+        last_granule: None,
+    })
+}
+
+/// Fill `buf` from `reader`.
+///
+/// # Returns
+///
+/// `false` if the stream ends first.
+fn fill<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<bool> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use anyhow::{Result, ensure};
+
+    use std::path::Path;
+
+    use crate::library::ogg_duration::{checked_ogg_duration, has_ogg_extension, ogg_duration};
+
+    /// One Ogg page of `serial` holding `body` as a single packet.
+    fn page(serial: u32, beginning: bool, granule: u64, body: &[u8]) -> Vec<u8> {
+        let mut lacing = vec![255_u8; body.len() / 255];
+        lacing.push(u8::try_from(body.len() % 255).unwrap_or_default());
+        let mut page = b"OggS\0".to_vec();
+        page.push(if beginning { 2 } else { 0 });
+        page.extend_from_slice(&granule.to_le_bytes());
+        page.extend_from_slice(&serial.to_le_bytes());
+        page.extend_from_slice(&[0; 8]);
+        page.push(u8::try_from(lacing.len()).unwrap_or_default());
+        page.extend_from_slice(&lacing);
+        page.extend_from_slice(body);
+        page
+    }
+
+    /// Opus identification header with `pre_skip` granules.
+    fn opus_head(pre_skip: u16) -> Vec<u8> {
+        let mut head = b"OpusHead\x01\x02".to_vec();
+        head.extend_from_slice(&pre_skip.to_le_bytes());
+        head.extend_from_slice(&44_100_u32.to_le_bytes());
+        head.extend_from_slice(&[0, 0, 0]);
+        head
+    }
+
+    /// One link of an Opus chain: headers, then audio ending at `granule`.
+    fn opus_link(serial: u32, granule: u64) -> Vec<u8> {
+        let mut link = page(serial, true, 0, &opus_head(312));
+        link.extend(page(serial, false, 0, b"OpusTags"));
+        link.extend(page(serial, false, granule / 2, &[0; 300]));
+        link.extend(page(serial, false, u64::MAX, &[0; 10]));
+        link.extend(page(serial, false, granule, &[0; 300]));
+        link
+    }
+
+    #[test]
+    fn opus_length_skips_pre_skip() -> Result<()> {
+        let file = opus_link(7, 48_000 * 3 + 312);
+        let duration = ogg_duration(&mut Cursor::new(file))?;
+        ensure!(
+            duration.is_some_and(|d| (d - 3.0).abs() < 1e-9),
+            "3 s after pre-skip, got {duration:?}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn chained_links_add_up() -> Result<()> {
+        let mut file = opus_link(1, 48_000 * 2 + 312);
+        file.extend(opus_link(2, 48_000 * 5 + 312));
+        let mut vorbis = b"\x01vorbis\0\0\0\0\x02".to_vec();
+        vorbis.extend_from_slice(&44_100_u32.to_le_bytes());
+        vorbis.extend_from_slice(&[0; 14]);
+        file.extend(page(3, true, 0, &vorbis));
+        file.extend(page(3, false, 44_100, &[0; 100]));
+        // A truncated last page ends the scan.
+        file.extend_from_slice(b"OggS\0");
+
+        let duration = ogg_duration(&mut Cursor::new(file))?;
+        ensure!(
+            duration.is_some_and(|d| (d - 8.0).abs() < 1e-9),
+            "2 s + 5 s + 1 s, got {duration:?}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reported_length_is_kept_without_scan() -> Result<()> {
+        let missing = Path::new("/nonexistent/chained.opus");
+        ensure!(has_ogg_extension(missing), "opus is Ogg");
+        ensure!(
+            !has_ogg_extension(Path::new("track.flac")),
+            "flac is not Ogg"
+        );
+        ensure!(
+            (checked_ogg_duration(missing, 42.0) - 42.0).abs() < 1e-9,
+            "unreadable file keeps reported length"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn other_streams_have_no_length() -> Result<()> {
+        let speex = page(1, true, 0, b"Speex   ");
+        ensure!(
+            ogg_duration(&mut Cursor::new(speex))?.is_none(),
+            "unknown codec"
+        );
+        ensure!(
+            ogg_duration(&mut Cursor::new(b"ID3 not an ogg".to_vec()))?.is_none(),
+            "not Ogg"
+        );
+        Ok(())
+    }
+}
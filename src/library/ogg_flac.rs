@@ -0,0 +1,379 @@
+//! Reading of FLAC streams in an Ogg container (`.ogg`/`.oga`).
+//!
+//! lofty only recognises Vorbis, Opus and Speex inside Ogg, so Ogg FLAC
+//! files fail to read and never reach the library. This reads just the
+//! header packets: the mapping header carrying STREAMINFO and the FLAC
+//! metadata blocks after it, of which the Vorbis comment block holds the
+//! tags. Page checksums are not verified, as the audio is not read.
+
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufReader, Read},
+    mem::take,
+    path::Path,
+};
+
+use {
+    lofty::{ogg::VorbisComments, tag::Tag},
+    num_traits::cast::AsPrimitive,
+};
+
+use crate::library::metadata::MetadataError::{self, ParseError};
+
+/// Capture pattern starting every Ogg page.
+const OGG_CAPTURE: &[u8; 4] = b"OggS";
+
+/// Start of the first packet of an Ogg FLAC stream.
+const FLAC_MAPPING: &[u8; 5] = b"\x7fFLAC";
+
+/// Length of an Ogg page header before its segment table.
+const PAGE_HEADER_LEN: usize = 27;
+
+/// Length of the STREAMINFO block body.
+const STREAMINFO_LEN: usize = 34;
+
+/// FLAC metadata block type of the Vorbis comment block.
+const VORBIS_COMMENT_BLOCK: u8 = 4;
+
+/// Header packets read at most, guarding against corrupt streams.
+const MAX_HEADER_PACKETS: usize = 64;
+
+/// Bytes of header packets read at most, guarding against corrupt streams.
+const MAX_HEADER_BYTES: usize = 16 * 1024 * 1024;
+
+/// Stream properties and tags of an Ogg FLAC file.
+pub struct OggFlac {
+    /// Sample rate in Hz.
+    pub sample_rate: u32,
+    /// Number of channels.
+    pub channels: u8,
+    /// Bits per sample.
+    pub bit_depth: u8,
+    /// Duration in seconds, zero when the stream does not record it.
+    pub duration: f64,
+    /// Tags of the Vorbis comment block, empty without one.
+    pub tag: Tag,
+}
+
+/// Splits Ogg pages into packets.
+struct OggPackets<R> {
+    /// Source of the pages.
+    reader: R,
+    /// Packets completed but not yet returned.
+    complete: VecDeque<Vec<u8>>,
+    /// Bytes of the packet being assembled.
+    partial: Vec<u8>,
+    /// Total packet bytes read so far.
+    read: usize,
+}
+
+impl<R: Read> OggPackets<R> {
+    /// Wrap `reader`, positioned at the first page.
+    const fn new(reader: R) -> Self {
+        Self {
+            reader,
+            complete: VecDeque::new(),
+            partial: Vec::new(),
+            read: 0,
+        }
+    }
+
+    /// Read pages until the next packet is complete.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MetadataError`] on a malformed page, a read failure or
+    /// headers beyond [`MAX_HEADER_BYTES`].
+    fn next_packet(&mut self) -> Result<Vec<u8>, MetadataError> {
+        loop {
+            match self.complete.pop_front() {
+                Some(packet) => return Ok(packet),
+                None => self.read_page()?,
+            }
+        }
+    }
+
+    /// Read one page, queueing the packets it completes.
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::next_packet`].
+    fn read_page(&mut self) -> Result<(), MetadataError> {
+        let mut header = [0_u8; PAGE_HEADER_LEN];
+        read_exact(&mut self.reader, &mut header)?;
+        let (capture, segments) = (header.get(..4), header.last());
+        if capture != Some(OGG_CAPTURE.as_slice()) {
+            return Err(ParseError("missing Ogg page capture pattern".to_string()));
+        }
+        let mut lacing = vec![0_u8; segments.copied().map_or(0, usize::from)];
+        read_exact(&mut self.reader, &mut lacing)?;
+
+        for segment in lacing {
+            self.read_segment(segment)?;
+        }
+        Ok(())
+    }
+
+    /// Read one lacing segment of `len` bytes into the current packet.
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::next_packet`].
+    fn read_segment(&mut self, len: u8) -> Result<(), MetadataError> {
+        self.read += usize::from(len);
+        if self.read > MAX_HEADER_BYTES {
+            return Err(ParseError("Ogg FLAC headers are too large".to_string()));
+        }
+        let mut bytes = vec![0_u8; usize::from(len)];
+        read_exact(&mut self.reader, &mut bytes)?;
+        self.partial.extend_from_slice(&bytes);
+        if len < 255 {
+            self.complete.push_back(take(&mut self.partial));
+        }
+        Ok(())
+    }
+}
+
+/// Read an Ogg FLAC file.
+///
+/// # Returns
+///
+/// `None` if the file is not an Ogg stream starting with a FLAC packet.
+///
+/// # Errors
+///
+/// Returns [`MetadataError`] if the file cannot be read or its FLAC
+/// headers are malformed.
+pub fn read_ogg_flac(path: &Path) -> Result<Option<OggFlac>, MetadataError> {
+    let file = File::open(path).map_err(|e| ParseError(format!("{}: {e}", path.display())))?;
+    let mut packets = OggPackets::new(BufReader::new(file));
+    let Ok(first) = packets.next_packet() else {
+        return Ok(None);
+    };
+    let Some(mapping) = first.strip_prefix(FLAC_MAPPING) else {
+        return Ok(None);
+    };
+    let mut ogg_flac = parse_mapping_header(mapping)?;
+
+    for _ in 0..MAX_HEADER_PACKETS {
+        let block = packets.next_packet()?;
+        let (&block_header, body) = block
+            .split_first()
+            .ok_or_else(|| ParseError("empty FLAC metadata packet".to_string()))?;
+        if block_header & 0x7f == VORBIS_COMMENT_BLOCK {
+            let body = body.get(3..).unwrap_or_default();
+            ogg_flac.tag = Tag::from(parse_vorbis_comments(body)?);
+            break;
+        }
+        if block_header & 0x80 != 0 {
+            break;
+        }
+    }
+    Ok(Some(ogg_flac))
+}
+
+/// Parse the Ogg FLAC mapping header after its `\x7FFLAC` signature.
+///
+/// # Errors
+///
+/// Returns [`MetadataError`] if the header or its STREAMINFO is malformed.
+fn parse_mapping_header(mapping: &[u8]) -> Result<OggFlac, MetadataError> {
+    // Version (2 bytes), header packet count (2), "fLaC", block header (4).
+    let streaminfo = mapping
+        .get(12..12 + STREAMINFO_LEN)
+        .filter(|_| mapping.get(4..8) == Some(b"fLaC".as_slice()))
+        .ok_or_else(|| ParseError("malformed Ogg FLAC mapping header".to_string()))?;
+    let packed = streaminfo
+        .get(10..18)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_be_bytes)
+        .ok_or_else(|| ParseError("truncated FLAC STREAMINFO".to_string()))?;
+
+    let sample_rate: u32 = (packed >> 44).as_();
+    let channels: u8 = ((packed >> 41) & 0x7).as_();
+    let bit_depth: u8 = ((packed >> 36) & 0x1f).as_();
+    let total_samples = packed & 0xf_ffff_ffff;
+    if sample_rate == 0 {
+        return Err(ParseError("FLAC STREAMINFO has no sample rate".to_string()));
+    }
+    let total: f64 = total_samples.as_();
+    Ok(OggFlac {
+        sample_rate,
+        channels: channels + 1,
+        bit_depth: bit_depth + 1,
+        duration: total / f64::from(sample_rate),
+        tag: Tag::from(VorbisComments::new()),
+    })
+}
+
+/// Parse the body of a FLAC Vorbis comment block.
+///
+/// # Errors
+///
+/// Returns [`MetadataError`] if a length runs past the end of the block.
+fn parse_vorbis_comments(mut body: &[u8]) -> Result<VorbisComments, MetadataError> {
+    let vendor_len = split_u32_le(&mut body)?;
+    split_bytes(&mut body, vendor_len)?;
+    let count = split_u32_le(&mut body)?;
+
+    let mut comments = VorbisComments::new();
+    for _ in 0..count {
+        let len = split_u32_le(&mut body)?;
+        let field = String::from_utf8_lossy(split_bytes(&mut body, len)?);
+        if let Some((key, value)) = field.split_once('=') {
+            comments.push(key.to_string(), value.to_string());
+        }
+    }
+    Ok(comments)
+}
+
+/// Split `len` bytes off the front of `data`.
+fn split_bytes<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8], MetadataError> {
+    let (head, rest) = data
+        .split_at_checked(len)
+        .ok_or_else(|| ParseError("truncated Vorbis comment block".to_string()))?;
+    *data = rest;
+    Ok(head)
+}
+
+/// Split a little-endian `u32` length off the front of `data`.
+fn split_u32_le(data: &mut &[u8]) -> Result<usize, MetadataError> {
+    let bytes: [u8; 4] = split_bytes(data, 4)?
+        .try_into()
+        .map_err(|e| ParseError(format!("truncated Vorbis comment length: {e}")))?;
+    usize::try_from(u32::from_le_bytes(bytes))
+        .map_err(|e| ParseError(format!("Vorbis comment length: {e}")))
+}
+
+/// Fill `buf` from `reader`, mapping failures to [`MetadataError`].
+fn read_exact(reader: &mut impl Read, buf: &mut [u8]) -> Result<(), MetadataError> {
+    reader
+        .read_exact(buf)
+        .map_err(|e| ParseError(format!("truncated Ogg stream: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::write;
+
+    use {
+        anyhow::{Result, bail, ensure},
+        tempfile::tempdir,
+    };
+
+    use crate::library::{ogg_flac::read_ogg_flac, tag_fields::TagFields};
+
+    /// Wrap `packets` into Ogg pages, one page per packet.
+    fn ogg_stream(packets: &[Vec<u8>]) -> Vec<u8> {
+        let mut stream = Vec::new();
+        for (sequence, packet) in (0_u32..).zip(packets) {
+            let mut lacing = vec![255_u8; packet.len() / 255];
+            lacing.push(u8::try_from(packet.len() % 255).unwrap_or_default());
+            stream.extend_from_slice(b"OggS\0");
+            // Only the first page carries the beginning-of-stream flag.
+            stream.push(match sequence {
+                0 => 2,
+                _ => 0,
+            });
+            stream.extend_from_slice(&[0; 8]);
+            stream.extend_from_slice(&1_u32.to_le_bytes());
+            stream.extend_from_slice(&sequence.to_le_bytes());
+            stream.extend_from_slice(&[0; 4]);
+            stream.push(u8::try_from(lacing.len()).unwrap_or_default());
+            stream.extend_from_slice(&lacing);
+            stream.extend_from_slice(packet);
+        }
+        stream
+    }
+
+    /// Mapping header of a 44.1 kHz, 16-bit stereo stream of 88 200 frames.
+    fn mapping_header() -> Vec<u8> {
+        let mut packet = b"\x7fFLAC\x01\x00\x00\x01fLaC".to_vec();
+        packet.extend_from_slice(&[0, 0, 0, 34]);
+        let mut streaminfo = [0_u8; 34];
+        let packed: u64 = (44_100 << 44) | (1 << 41) | (15 << 36) | 88_200;
+        streaminfo[10..18].copy_from_slice(&packed.to_be_bytes());
+        packet.extend_from_slice(&streaminfo);
+        packet
+    }
+
+    /// Last metadata block holding `comments` as Vorbis comments.
+    fn comment_block(comments: &[&str]) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&4_u32.to_le_bytes());
+        body.extend_from_slice(b"test");
+        body.extend_from_slice(
+            &u32::try_from(comments.len())
+                .unwrap_or_default()
+                .to_le_bytes(),
+        );
+        for comment in comments {
+            let len = u32::try_from(comment.len()).unwrap_or_default();
+            body.extend_from_slice(&len.to_le_bytes());
+            body.extend_from_slice(comment.as_bytes());
+        }
+        let len = u32::try_from(body.len()).unwrap_or_default().to_be_bytes();
+        let mut block = vec![0x84, len[1], len[2], len[3]];
+        block.extend_from_slice(&body);
+        block
+    }
+
+    #[test]
+    fn ogg_flac_stream_info_and_tags_are_read() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("track.oga");
+        let long_title = format!("TITLE={}", "x".repeat(300));
+        write(
+            &path,
+            ogg_stream(&[
+                mapping_header(),
+                comment_block(&[
+                    &long_title,
+                    "ARTIST=Track Artist",
+                    "ALBUMARTIST=Album Artist",
+                    "ALBUM=Album",
+                    "DATE=2004-06-01",
+                    "TRACKNUMBER=7/9",
+                    "GENRE=Ambient",
+                ]),
+            ]),
+        )?;
+
+        let ogg_flac = read_ogg_flac(&path)?;
+        let Some(ogg_flac) = ogg_flac else {
+            bail!("an Ogg FLAC stream must be recognised");
+        };
+        ensure!(ogg_flac.sample_rate == 44_100, "sample rate");
+        ensure!(ogg_flac.channels == 2 && ogg_flac.bit_depth == 16, "format");
+        ensure!((ogg_flac.duration - 2.0).abs() < 1e-9, "duration");
+
+        let fields = TagFields::read(&[&ogg_flac.tag]);
+        ensure!(
+            fields.title.as_deref().map(str::len) == Some(300),
+            "a comment spanning several lacing segments"
+        );
+        ensure!(fields.artist.as_deref() == Some("Track Artist"), "artist");
+        ensure!(
+            fields.album_artist.as_deref() == Some("Album Artist"),
+            "album artist"
+        );
+        ensure!(fields.album.as_deref() == Some("Album"), "album");
+        ensure!(fields.year == Some(2004), "year");
+        ensure!(fields.track_number == Some(7), "track number");
+        ensure!(fields.genre.as_deref() == Some("Ambient"), "genre");
+        Ok(())
+    }
+
+    #[test]
+    fn other_ogg_streams_are_not_ogg_flac() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("vorbis.ogg");
+        write(&path, ogg_stream(&[b"\x01vorbis\0\0\0\0".to_vec()]))?;
+        ensure!(read_ogg_flac(&path)?.is_none(), "Vorbis is left to lofty");
+
+        write(&path, b"not an ogg file")?;
+        ensure!(read_ogg_flac(&path)?.is_none(), "garbage is not Ogg FLAC");
+        Ok(())
+    }
+}
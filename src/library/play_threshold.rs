@@ -0,0 +1,211 @@
+//! When a play of a track counts as played.
+//!
+//! A [`PlayTracker`] adds up the time actually listened to the current
+//! track and reports it once the user's [`PlayThreshold`] is reached. Both
+//! scrobbling and anything else that counts plays ask it, so every consumer
+//! agrees on what a play is.
+
+use serde::{Deserialize, Serialize};
+
+/// Listened time after which any track counts with the four-minute rule, in
+/// seconds.
+const FOUR_MINUTES_SECONDS: f64 = 240.0;
+
+/// Largest position jump still counted as continuous listening, in seconds.
+const MAX_TICK_SECONDS: f64 = 2.0;
+
+/// Tracks shorter than this never count as played, in seconds.
+const MIN_TRACK_SECONDS: f64 = 30.0;
+
+/// How much of a track has to be heard before it counts as played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayThreshold {
+    /// Share of the track to hear, in percent.
+    pub percent: u32,
+    /// Also count any track after four minutes, whatever its length.
+    pub four_minute_cap: bool,
+}
+
+impl PlayThreshold {
+    /// Seconds of listening required before a track of `duration_seconds`
+    /// counts as played.
+    ///
+    /// The threshold never exceeds the listenable length of the track, so a
+    /// full play always counts even at 100%.
+    ///
+    /// # Returns
+    ///
+    /// `None` for tracks too short to count.
+    #[must_use]
+    pub fn seconds(&self, duration_seconds: f64) -> Option<f64> {
+        if duration_seconds < MIN_TRACK_SECONDS {
+            return None;
+        }
+        let share = duration_seconds * f64::from(self.percent.clamp(1, 100)) / 100.0;
+        let capped = if self.four_minute_cap {
+            share.min(FOUR_MINUTES_SECONDS)
+        } else {
+            share
+        };
+        Some(capped.min(duration_seconds - MAX_TICK_SECONDS))
+    }
+}
+
+impl Default for PlayThreshold {
+    fn default() -> Self {
+        Self {
+            percent: 50,
+            four_minute_cap: true,
+        }
+    }
+}
+
+/// Accumulates listened time for the current track.
+///
+/// Only forward progress in small steps counts, so seeking ahead does not
+/// make a track eligible early.
+#[derive(Debug, Default)]
+pub struct PlayTracker {
+    /// Track being followed, if any.
+    track_id: Option<i64>,
+    /// Position reported by the last tick, in seconds.
+    last_position: f64,
+    /// Seconds actually listened so far.
+    listened: f64,
+    /// Whether the threshold has already been reported for this play.
+    reported: bool,
+}
+
+impl PlayTracker {
+    /// Start following a new play of `track_id`.
+    pub fn start(&mut self, track_id: i64) {
+        *self = Self {
+            track_id: Some(track_id),
+            ..Self::default()
+        };
+    }
+
+    /// Stop following the current play.
+    pub fn stop(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Record a seek without counting the skipped time as listened.
+    pub fn seek(&mut self, position_seconds: f64) {
+        self.last_position = position_seconds;
+    }
+
+    /// Record a position update.
+    ///
+    /// # Returns
+    ///
+    /// The followed track ID exactly once, when the listened time first
+    /// crosses `threshold` for `duration_seconds`.
+    pub fn tick(
+        &mut self,
+        elapsed_seconds: f64,
+        duration_seconds: f64,
+        threshold: PlayThreshold,
+    ) -> Option<i64> {
+        let delta = elapsed_seconds - self.last_position;
+        self.last_position = elapsed_seconds;
+        if delta > 0.0 && delta <= MAX_TICK_SECONDS {
+            self.listened += delta;
+        }
+        let required = threshold.seconds(duration_seconds)?;
+        if self.reported || self.listened < required {
+            return None;
+        }
+        self.reported = true;
+        self.track_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::library::play_threshold::{PlayThreshold, PlayTracker};
+
+    fn play(tracker: &mut PlayTracker, until: f64, duration: f64) -> Option<i64> {
+        let mut reported = None;
+        let mut position = 0.0;
+        while position < until {
+            position += 0.2;
+            reported = reported.or(tracker.tick(position, duration, PlayThreshold::default()));
+        }
+        reported
+    }
+
+    #[test]
+    fn threshold_is_half_or_four_minutes() {
+        let threshold = PlayThreshold::default();
+        assert_eq!(threshold.seconds(20.0), None, "Short tracks never count");
+        assert_eq!(
+            threshold.seconds(200.0),
+            Some(100.0),
+            "Half of a short track"
+        );
+        assert_eq!(
+            threshold.seconds(1200.0),
+            Some(240.0),
+            "Capped at four minutes"
+        );
+    }
+
+    #[test]
+    fn threshold_follows_the_setting() {
+        let uncapped = PlayThreshold {
+            percent: 75,
+            four_minute_cap: false,
+        };
+        assert_eq!(uncapped.seconds(1200.0), Some(900.0), "No four-minute cap");
+
+        let full = PlayThreshold {
+            percent: 100,
+            four_minute_cap: false,
+        };
+        assert_eq!(
+            full.seconds(200.0),
+            Some(198.0),
+            "A full play still counts at 100%"
+        );
+
+        let zero = PlayThreshold {
+            percent: 0,
+            four_minute_cap: true,
+        };
+        assert_eq!(zero.seconds(200.0), Some(2.0), "At least one percent");
+    }
+
+    #[test]
+    fn tracker_reports_once_past_threshold() {
+        let threshold = PlayThreshold::default();
+        let mut tracker = PlayTracker::default();
+        tracker.start(7);
+        assert_eq!(play(&mut tracker, 90.0, 200.0), None, "Below threshold");
+        assert_eq!(
+            tracker.tick(101.0, 200.0, threshold),
+            None,
+            "Jump over two seconds"
+        );
+        tracker.start(7);
+        assert_eq!(play(&mut tracker, 101.0, 200.0), Some(7), "Past threshold");
+        assert_eq!(
+            tracker.tick(101.2, 200.0, threshold),
+            None,
+            "Reported only once"
+        );
+    }
+
+    #[test]
+    fn seeking_ahead_does_not_count() {
+        let mut tracker = PlayTracker::default();
+        tracker.start(3);
+        tracker.seek(150.0);
+        assert_eq!(
+            tracker.tick(150.2, 200.0, PlayThreshold::default()),
+            None,
+            "Seek skipped listened time"
+        );
+    }
+}
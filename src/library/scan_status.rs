@@ -0,0 +1,143 @@
+//! Whether a library scan is running and how far it has got.
+//!
+//! [`ScanEvent`]s describe single steps of a scan; the UI mostly wants the
+//! current state. `AppState` holds the latest [`ScanStatus`] in a watch
+//! channel fed from the scanner's events, so views read it directly
+//! instead of each following the event stream.
+
+use {
+    async_channel::{Receiver, Sender},
+    tokio::sync::watch::Sender as TokioSender,
+    tracing::warn,
+};
+
+use crate::library::scanner::ScanEvent::{
+    self, ScanCompleted, ScanError, ScanProgress, ScanStarted,
+};
+
+/// Progress of the running library scan.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanStatus {
+    /// Whether a scan is running.
+    pub active: bool,
+    /// Files of the current directory processed so far.
+    pub processed: u32,
+    /// Files found in the current directory, zero until they are counted.
+    pub total: u32,
+}
+
+impl ScanStatus {
+    /// Status after `event`.
+    ///
+    /// # Returns
+    ///
+    /// The status unchanged for events that do not affect progress.
+    #[must_use]
+    pub const fn after(self, event: &ScanEvent) -> Self {
+        match event {
+            ScanStarted { .. } => Self {
+                active: true,
+                processed: 0,
+                total: 0,
+            },
+            ScanProgress {
+                files_found,
+                files_processed,
+                ..
+            } => Self {
+                active: true,
+                processed: *files_processed,
+                total: *files_found,
+            },
+            ScanCompleted { .. } | ScanError { .. } => Self {
+                active: false,
+                ..self
+            },
+            _ => self,
+        }
+    }
+
+    /// Share of the files processed, from `0.0` to `1.0`.
+    #[must_use]
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        (f64::from(self.processed) / f64::from(self.total)).min(1.0)
+    }
+
+    /// Short description such as `Scanning… 120/500 files`.
+    #[must_use]
+    pub fn label(&self) -> String {
+        if self.total == 0 {
+            "Scanning\u{2026}".to_string()
+        } else {
+            format!("Scanning\u{2026} {}/{} files", self.processed, self.total)
+        }
+    }
+}
+
+/// Track the scan status from the scanner's events and pass them on.
+///
+/// Runs until the scanner's channel closes. Events are forwarded to `tx`
+/// unchanged for the consumers of single steps, like the status bar.
+pub async fn track_scan_status(
+    rx: Receiver<ScanEvent>,
+    tx: Sender<ScanEvent>,
+    status: TokioSender<ScanStatus>,
+) {
+    while let Ok(event) = rx.recv().await {
+        status.send_if_modified(|current| {
+            let next = current.after(&event);
+            let changed = next != *current;
+            *current = next;
+            changed
+        });
+        if let Err(e) = tx.send(event).await {
+            warn!(error = %e, "Failed to forward scan event");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, time::Duration};
+
+    use crate::library::{
+        scan_status::ScanStatus,
+        scanner::ScanEvent::{ScanCompleted, ScanError, ScanProgress, ScanStarted},
+    };
+
+    #[test]
+    fn status_follows_a_scan() {
+        let directory = PathBuf::from("/music");
+        let started = ScanStatus::default().after(&ScanStarted {
+            directory: directory.clone(),
+        });
+        assert!(started.active);
+        assert_eq!(started.label(), "Scanning\u{2026}");
+
+        let halfway = started.after(&ScanProgress {
+            directory: directory.clone(),
+            files_found: 200,
+            files_processed: 100,
+        });
+        assert!((halfway.fraction() - 0.5).abs() < f64::EPSILON);
+        assert_eq!(halfway.label(), "Scanning\u{2026} 100/200 files");
+
+        let done = halfway.after(&ScanCompleted {
+            directory: directory.clone(),
+            duration: Duration::from_secs(1),
+            tracks_added: 100,
+            tracks_skipped: 0,
+        });
+        assert!(!done.active);
+        assert_eq!(done.processed, 100, "the last progress is kept");
+
+        let failed = started.after(&ScanError {
+            directory,
+            error: "gone".to_string(),
+        });
+        assert!(!failed.active, "a failed scan is no longer running");
+    }
+}
@@ -4,78 +4,130 @@
 //! extracting metadata, deduplicating tracks, and persisting results to storage.
 
 use std::{
-    cmp::max,
-    collections::HashMap,
-    fs::{DirEntry, read_dir},
+    collections::{HashMap, HashSet},
+    fs::{DirEntry, canonicalize, metadata as fs_metadata, read_dir},
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{
+            AtomicBool, AtomicU64, AtomicUsize,
+            Ordering::{AcqRel, Relaxed, Release},
+        },
+    },
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use {
     async_channel::Sender,
+    parking_lot::Mutex,
     rayon::{
-        iter::IndexedParallelIterator,
+        current_num_threads,
+        iter::ParallelExtend,
         prelude::{IntoParallelRefIterator, ParallelIterator},
+        slice::ParallelSlice,
     },
     tokio::{
         sync::watch::{Receiver, Sender as TokioSender, channel},
-        task::{JoinError, spawn_blocking},
+        task::{JoinError, spawn_blocking, yield_now},
     },
-    tracing::{error, info, warn},
+    tracing::{debug, error, info, warn},
 };
 
 use crate::{
     library::{
-        artwork::{ArtworkError, cache_artwork, extract_artwork},
-        dedup::{compute_content_hash, is_supported_audio_format},
-        metadata::{AudioMetadata, extract_metadata, metadata_fingerprint},
+        artwork::{cache_artwork, find_album_artwork},
+        cue::{expand::expand_cue_tracks, split_cue_path},
+        dedup::compute_content_hash,
+        formats::AudioExtensions,
+        metadata::{
+            AudioMetadata, album_artist_name, extract_metadata_within, metadata_fingerprint,
+        },
         scanner::ScanEvent::{ScanCompleted, ScanProgress, ScanStarted},
     },
-    storage::{NewAlbum, NewArtist, NewTrack, Storage, StorageError, TrackAudio},
+    storage::{
+        NewAlbum, NewArtist, NewTrack, Storage, StorageError, TrackAudio, prune::PruneReport,
+    },
+    threading::{
+        scan_pool::{run_on_scan_pool, scan_thread_count},
+        scheduler::BackgroundScheduler,
+    },
 };
 
+/// Files read per work permit before the permits are taken again, so a
+/// change of the background budget applies during a scan.
+const FILES_PER_PERMIT: usize = 16;
+
+/// Scanned files stored between yields to the other tasks of the runtime.
+const SCAN_YIELD_INTERVAL: usize = 32;
+
 /// Filesystem-based library scanner with storage integration.
 pub struct FsScanner<S: Storage> {
     /// Storage backend for persistence.
     storage: Arc<S>,
-    /// Maximum number of concurrent metadata extractions.
-    max_concurrent: usize,
+    /// Shared background work budget for metadata extraction.
+    scheduler: Arc<BackgroundScheduler>,
+    /// Extensions of the files added to the library.
+    extensions: AudioExtensions,
     /// Cancellation signal sender.
     cancel_tx: TokioSender<bool>,
     /// Cancellation signal receiver (cloned into scan tasks).
     cancel_rx: Receiver<bool>,
     /// Channel sender for forwarding scan events to the UI.
     scan_event_tx: Sender<ScanEvent>,
+    /// Whether a full rescan is running.
+    rescanning: AtomicBool,
+    /// Whether new albums take a sidecar cover over embedded artwork.
+    prefer_sidecar_artwork: AtomicBool,
+    /// Whether the walk descends into symlinked folders and adds symlinked
+    /// files.
+    follow_symlinks: AtomicBool,
+    /// Longest wait for the metadata of one file in milliseconds (`0` waits
+    /// indefinitely).
+    metadata_timeout_ms: AtomicU64,
+    /// Worker threads of the scan pool (`0` follows the background budget).
+    scan_threads: AtomicUsize,
 }
 
 impl<S: Storage> FsScanner<S> {
-    /// Walk a directory and extract metadata from all audio files found.
-    fn walk_and_extract(
-        dir: &Path,
-        max_concurrent: usize,
+    /// Extract metadata from `files`.
+    ///
+    /// Files split by a CUE sheet are returned as one item per cue track.
+    /// Files whose metadata cannot be read within `timeout` are skipped.
+    ///
+    /// Work permits are taken on the calling thread before each batch is
+    /// handed to the pool, and the batch is split into one run of files per
+    /// permit, so pool workers never wait on the budget.
+    fn extract_files(
+        files: &[PathBuf],
+        scheduler: &BackgroundScheduler,
         skip_hashing: bool,
-    ) -> (Vec<(PathBuf, AudioMetadata, Option<String>)>, u32) {
-        let files = Self::walk_directory_parallel(dir);
-        let files_found = u32::try_from(files.len()).unwrap_or(0);
-
-        let chunk_size = max(1, files.len() / max_concurrent);
-
-        let extracted: Vec<_> = files
-            .par_iter()
-            .with_min_len(chunk_size)
-            .filter_map(|path| Self::extract_one(path, skip_hashing))
-            .collect();
+        timeout: Duration,
+    ) -> Vec<(PathBuf, AudioMetadata, Option<String>)> {
+        let mut extracted = Vec::with_capacity(files.len());
+        let mut rest = files;
+        while !rest.is_empty() {
+            let permits = scheduler.acquire_many(current_num_threads());
+            let (batch, next) = rest.split_at(rest.len().min(permits.count() * FILES_PER_PERMIT));
+            extracted.par_extend(
+                batch
+                    .par_chunks(batch.len().div_ceil(permits.count()))
+                    .flat_map_iter(|run| run.iter())
+                    .filter_map(|path| Self::extract_one(path, skip_hashing, timeout)),
+            );
+            drop(permits);
+            rest = next;
+        }
 
-        (extracted, files_found)
+        expand_cue_tracks(extracted)
     }
 
     /// Extract metadata and content hash from a single file path.
     fn extract_one(
         path: &Path,
         skip_hashing: bool,
+        timeout: Duration,
     ) -> Option<(PathBuf, AudioMetadata, Option<String>)> {
-        let metadata = extract_metadata(path).map_or_else(
+        let metadata = extract_metadata_within(path, timeout).map_or_else(
             |e| {
                 warn!(error = %e, path = %path.display(), "Failed to extract metadata");
                 None
@@ -101,46 +153,137 @@ impl<S: Storage> FsScanner<S> {
         )
     }
 
-    /// Log a panic from the walk-and-extract task and return defaults.
-    fn on_walk_panic(e: &JoinError) -> (Vec<(PathBuf, AudioMetadata, Option<String>)>, u32) {
-        error!(error = %e, "Walk and metadata extraction task panicked");
-        Default::default()
+    /// Log a panic from the walk or metadata extraction task and return
+    /// an empty result.
+    fn on_walk_panic<T: Default>(e: &JoinError) -> T {
+        error!(error = %e, "Walk or metadata extraction task panicked");
+        T::default()
     }
 
     /// Create a new filesystem scanner.
-    pub fn new(storage: Arc<S>, scan_event_tx: Sender<ScanEvent>, max_concurrent: usize) -> Self {
+    ///
+    /// Metadata extraction concurrency is bounded by `scheduler`, which
+    /// is shared with the other background subsystems.
+    pub fn new(
+        storage: Arc<S>,
+        scan_event_tx: Sender<ScanEvent>,
+        scheduler: Arc<BackgroundScheduler>,
+    ) -> Self {
         let (cancel_tx, cancel_rx) = channel(false);
         Self {
             storage,
-            max_concurrent,
+            scheduler,
+            extensions: AudioExtensions::default(),
             cancel_tx,
             cancel_rx,
             scan_event_tx,
+            rescanning: AtomicBool::new(false),
+            prefer_sidecar_artwork: AtomicBool::new(false),
+            follow_symlinks: AtomicBool::new(false),
+            metadata_timeout_ms: AtomicU64::new(0),
+            scan_threads: AtomicUsize::new(0),
         }
     }
 
+    /// Choose how many worker threads walk folders and read metadata in
+    /// scans started from now on.
+    ///
+    /// Zero sizes the pool by the shared background work budget.
+    pub fn set_scan_threads(&self, threads: usize) {
+        self.scan_threads.store(threads, Relaxed);
+    }
+
+    /// Skip files whose metadata takes longer than `timeout` to read, such
+    /// as files on an unresponsive network share.
+    ///
+    /// A zero `timeout` waits indefinitely.
+    pub fn set_metadata_timeout(&self, timeout: Duration) {
+        let millis = u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX);
+        self.metadata_timeout_ms.store(millis, Relaxed);
+    }
+
+    /// Choose whether albums scanned from now on take a sidecar image such
+    /// as `cover.jpg` over the artwork embedded in their tracks.
+    ///
+    /// Either source is still used when the other one is missing.
+    pub fn set_prefer_sidecar_artwork(&self, prefer: bool) {
+        self.prefer_sidecar_artwork.store(prefer, Relaxed);
+    }
+
+    /// Choose whether scans follow symbolic links.
+    ///
+    /// Symlinked entries are skipped when disabled. When enabled, every
+    /// folder is walked once by its real path, so circular links end and a
+    /// folder linked into another library folder is not added twice.
+    pub fn set_follow_symlinks(&self, follow: bool) {
+        self.follow_symlinks.store(follow, Relaxed);
+    }
+
+    /// Accept files with `extensions` in addition to the built-in ones.
+    #[must_use]
+    pub fn with_audio_extensions(mut self, extensions: AudioExtensions) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    /// Extensions of the files this scanner adds to the library.
+    ///
+    /// The filesystem watcher filters its events through the same set.
+    #[must_use]
+    pub const fn audio_extensions(&self) -> &AudioExtensions {
+        &self.extensions
+    }
+
     /// Walk a directory recursively in parallel using rayon.
     ///
     /// # Arguments
     ///
     /// * `dir` - Root directory to walk
+    /// * `extensions` - Extensions of the audio files to collect
+    /// * `follow_symlinks` - Whether to follow symlinked folders and files
     ///
     /// # Returns
     ///
     /// A vector of paths to supported audio files.
-    fn walk_directory_parallel(dir: &Path) -> Vec<PathBuf> {
+    fn walk_directory_parallel(
+        dir: &Path,
+        extensions: &AudioExtensions,
+        follow_symlinks: bool,
+    ) -> Vec<PathBuf> {
+        let visited = Mutex::new(HashSet::new());
+        Self::walk_unvisited(dir, extensions, follow_symlinks, &visited)
+    }
+
+    /// Walk `dir` unless following symlinks led back to a walked folder.
+    ///
+    /// `visited` holds the canonical paths of the folders walked so far.
+    fn walk_unvisited(
+        dir: &Path,
+        extensions: &AudioExtensions,
+        follow_symlinks: bool,
+        visited: &Mutex<HashSet<PathBuf>>,
+    ) -> Vec<PathBuf> {
+        if follow_symlinks && !Self::first_visit(dir, visited) {
+            return Vec::new();
+        }
         let entries: Vec<_> = read_dir(dir).into_iter().flatten().flatten().collect();
 
         let mut results: Vec<PathBuf> = Vec::new();
         let mut subdirs: Vec<PathBuf> = Vec::new();
 
         for entry in &entries {
-            Self::classify_entry(entry, &mut subdirs, &mut results);
+            Self::classify_entry(
+                entry,
+                extensions,
+                follow_symlinks,
+                &mut subdirs,
+                &mut results,
+            );
         }
 
         let sub_results: Vec<Vec<PathBuf>> = subdirs
             .par_iter()
-            .map(|path| Self::walk_directory_parallel(path))
+            .map(|path| Self::walk_unvisited(path, extensions, follow_symlinks, visited))
             .collect();
 
         for sub_result in sub_results {
@@ -150,31 +293,118 @@ impl<S: Storage> FsScanner<S> {
         results
     }
 
+    /// Record `dir` by its canonical path, returning whether it is new.
+    fn first_visit(dir: &Path, visited: &Mutex<HashSet<PathBuf>>) -> bool {
+        let canonical = match canonicalize(dir) {
+            Ok(canonical) => canonical,
+            Err(e) => {
+                warn!(error = %e, path = %dir.display(), "Cannot resolve folder");
+                return false;
+            }
+        };
+        let first = visited.lock().insert(canonical);
+        if !first {
+            debug!(path = %dir.display(), "Skipping folder reached again via symlink");
+        }
+        first
+    }
+
     /// Classify a directory entry as a subdirectory or supported audio file.
-    fn classify_entry(entry: &DirEntry, subdirs: &mut Vec<PathBuf>, results: &mut Vec<PathBuf>) {
+    ///
+    /// Symlinked entries are skipped unless `follow_symlinks` is set.
+    fn classify_entry(
+        entry: &DirEntry,
+        extensions: &AudioExtensions,
+        follow_symlinks: bool,
+        subdirs: &mut Vec<PathBuf>,
+        results: &mut Vec<PathBuf>,
+    ) {
         let path = entry.path();
+        if !follow_symlinks && entry.file_type().is_ok_and(|t| t.is_symlink()) {
+            debug!(path = %path.display(), "Skipping symlink");
+            return;
+        }
         if path.is_dir() {
             subdirs.push(path);
             return;
         }
-        if path.is_file() && is_supported_audio_format(&path) {
+        if path.is_file() && extensions.accepts(&path) {
             results.push(path);
         }
     }
 
-    /// Check if a file should be skipped based on path uniqueness.
+    /// Id of the stored track a changed file at `path` replaces, if any.
+    ///
+    /// Cue tracks are only added once: their stamp belongs to the whole
+    /// audio file, so they are skipped rather than rewritten.
     ///
     /// # Errors
     ///
-    /// Returns a storage error if the database lookup fails.
-    async fn check_path_exists(&self, path: &Path) -> Result<bool, StorageError> {
-        match self.storage.find_by_path(path).await {
-            Ok(Some(_)) => Ok(true),
-            Ok(None) => Ok(false),
-            Err(e) => Err(e),
+    /// Returns `SkipReason::DuplicateByPath` for a known cue track, or
+    /// `SkipReason::CorruptFile` if the database lookup fails.
+    async fn existing_track(&self, path: &Path) -> Result<Option<i64>, SkipReason> {
+        let existing = self.storage.find_by_path(path).await.map_err(|e| {
+            warn!(error = %e, path = %path.display(), "Failed to check path existence");
+            SkipReason::CorruptFile
+        })?;
+        match existing {
+            Some(_) if split_cue_path(path).1.is_some() => Err(SkipReason::DuplicateByPath),
+            other => Ok(other.map(|track| track.id)),
         }
     }
 
+    /// Reject a new file whose track is already in the library under
+    /// another path.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SkipReason::DuplicateByFingerprint` or
+    /// `SkipReason::DuplicateByHash` if a duplicate is found.
+    async fn check_new_file_duplicates(
+        &self,
+        metadata: &AudioMetadata,
+        content_hash: Option<&str>,
+    ) -> Result<(), SkipReason> {
+        if self
+            .check_fingerprint_duplicate(metadata)
+            .await
+            .map_err(|e| {
+                warn!(error = %e, title = ?metadata.title, "Failed to check fingerprint duplicate");
+                SkipReason::CorruptFile
+            })?
+        {
+            return Err(SkipReason::DuplicateByFingerprint);
+        }
+        if let Some(hash) = content_hash {
+            self.check_precomputed_hash(hash).await?;
+        }
+        Ok(())
+    }
+
+    /// Update the track of a changed file, or insert `track` as a new one.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SkipReason::CorruptFile` if the database write fails.
+    async fn store_track(&self, existing: Option<i64>, track: NewTrack) -> Result<i64, SkipReason> {
+        let Some(track_id) = existing else {
+            return self
+                .storage
+                .insert_track(track)
+                .await
+                .map_err(|e| Self::map_insert_error(&e, "track"));
+        };
+        self.storage
+            .update_scanned_track(track_id, track)
+            .await
+            .map_err(|e| {
+                warn!(error = %e, track_id, "Failed to update changed track");
+                SkipReason::CorruptFile
+            })?;
+        info!(track_id, "Track re-read after its file changed");
+        Ok(track_id)
+    }
+
     /// Check if a file should be skipped based on content hash.
     ///
     /// # Errors
@@ -208,6 +438,35 @@ impl<S: Storage> FsScanner<S> {
         }
     }
 
+    /// Keep the files that are new or changed since they were last read.
+    ///
+    /// Files whose size and modification time match their stored track are
+    /// left out, so a rescan only reads the tags of what actually changed.
+    async fn changed_files(&self, files: Vec<PathBuf>) -> Vec<PathBuf> {
+        let mut changed = Vec::with_capacity(files.len());
+        for path in files {
+            let read = self.file_changed(&path).await;
+            changed.extend(read.then_some(path));
+        }
+        changed
+    }
+
+    /// Whether `path` has to be read again; files that cannot be checked are.
+    async fn file_changed(&self, path: &Path) -> bool {
+        let Ok(stat) = fs_metadata(path) else {
+            return true;
+        };
+        let file_size = stat.len().cast_signed();
+        let last_modified = modified_rfc3339(stat.modified().ok());
+        self.storage
+            .needs_update(path, file_size, &last_modified)
+            .await
+            .unwrap_or_else(|e| {
+                warn!(error = %e, path = %path.display(), "Failed to check file stamp");
+                true
+            })
+    }
+
     /// Scan a single directory and emit events.
     async fn scan_dir(&self, dir: &Path) {
         info!(
@@ -236,22 +495,32 @@ impl<S: Storage> FsScanner<S> {
         let mut album_cache: HashMap<(i64, String), i64> = HashMap::new();
 
         let dir_buf = dir.to_path_buf();
-        let max_concurrent = self.max_concurrent;
-        let (extracted, files_found) = match spawn_blocking(move || {
-            Self::walk_and_extract(&dir_buf, max_concurrent, skip_hashing)
-        })
-        .await
-        {
-            Ok(v) => v,
-            Err(e) => Self::on_walk_panic(&e),
-        };
+        let extensions = self.extensions.clone();
+        let follow_symlinks = self.follow_symlinks.load(Relaxed);
+        let threads = scan_thread_count(self.scan_threads.load(Relaxed), &self.scheduler);
+        let walk = move || Self::walk_directory_parallel(&dir_buf, &extensions, follow_symlinks);
+        let files = spawn_blocking(move || run_on_scan_pool(threads, walk))
+            .await
+            .unwrap_or_else(|e| Self::on_walk_panic(&e));
+        let files_found = u32::try_from(files.len()).unwrap_or(0);
+
+        let changed = self.changed_files(files).await;
+        let unchanged = usize::try_from(files_found).unwrap_or(0) - changed.len();
 
-        let total = extracted.len();
+        let scheduler = Arc::clone(&self.scheduler);
+        let timeout = Duration::from_millis(self.metadata_timeout_ms.load(Relaxed));
+        let extract = move || Self::extract_files(&changed, &scheduler, skip_hashing, timeout);
+        let extracted = spawn_blocking(move || run_on_scan_pool(threads, extract))
+            .await
+            .unwrap_or_else(|e| Self::on_walk_panic(&e));
+
+        let total = unchanged + extracted.len();
 
         let mut tracks_added: u64 = 0;
-        let mut tracks_skipped: u64 = 0;
+        let mut tracks_skipped: u64 = u64::try_from(unchanged).unwrap_or(0);
 
         for (idx, (path, metadata, content_hash)) in extracted.into_iter().enumerate() {
+            yield_periodically(idx).await;
             let mut ctx = ScanContext {
                 dir,
                 files_found,
@@ -260,8 +529,15 @@ impl<S: Storage> FsScanner<S> {
                 tracks_added: &mut tracks_added,
                 tracks_skipped: &mut tracks_skipped,
             };
-            self.process_scan_item(idx, total, path, metadata, content_hash, &mut ctx)
-                .await;
+            self.process_scan_item(
+                unchanged + idx,
+                total,
+                path,
+                metadata,
+                content_hash,
+                &mut ctx,
+            )
+            .await;
         }
 
         let duration = start.elapsed();
@@ -394,27 +670,23 @@ impl<S: Storage> FsScanner<S> {
         Ok(id)
     }
 
-    /// Try to extract and cache artwork, returning the cached path string on success.
-    fn cache_extracted_artwork(
-        result: Result<Option<(Vec<u8>, String)>, ArtworkError>,
-        key: &str,
-    ) -> Option<String> {
-        match result {
-            Ok(Some((data, ext))) => match cache_artwork(key, &data, &ext) {
-                Ok(p) => Some(p.to_string_lossy().to_string()),
-                Err(e) => {
-                    error!(error = %e, "Failed to cache artwork");
-                    None
-                }
-            },
-            _ => None,
+    /// Try to cache found artwork, returning the cached path string on success.
+    fn cache_found_artwork(artwork: Option<(Vec<u8>, String)>, key: &str) -> Option<String> {
+        let (data, ext) = artwork?;
+        match cache_artwork(key, &data, &ext) {
+            Ok(p) => Some(p.to_string_lossy().to_string()),
+            Err(e) => {
+                error!(error = %e, "Failed to cache artwork");
+                None
+            }
         }
     }
 
     /// Resolve an album ID from cache or by inserting into storage.
     ///
-    /// When a new album is inserted, embedded artwork is extracted from `file_path`,
-    /// cached to disk, and the cached path is stored as `artwork_path`.
+    /// When a new album is inserted, its cover is taken from the artwork
+    /// embedded in `file_path` or a sidecar image next to it, cached to
+    /// disk, and the cached path is stored as `artwork_path`.
     ///
     /// # Errors
     ///
@@ -437,8 +709,8 @@ impl<S: Storage> FsScanner<S> {
             || format!("{codec_upper}/{sr}"),
             |bd| format!("{codec_upper} {bd}/{sr}"),
         );
-        let artwork_path = Self::cache_extracted_artwork(
-            extract_artwork(file_path),
+        let artwork_path = Self::cache_found_artwork(
+            find_album_artwork(file_path, self.prefer_sidecar_artwork.load(Relaxed)),
             &format!("{artist_id}_{}", title.to_lowercase()),
         );
         let id = self
@@ -447,6 +719,7 @@ impl<S: Storage> FsScanner<S> {
                 title: title.to_string(),
                 artist_id,
                 year: metadata.year,
+                original_year: metadata.original_year,
                 genre: metadata.genre.clone(),
                 artwork_path,
                 format_summary,
@@ -482,10 +755,18 @@ impl<S: Storage> FsScanner<S> {
             album_id: Some(album_id),
             artist_id: Some(artist_id),
             file_size: metadata.file_size,
-            last_modified: utc_now_rfc3339(),
+            last_modified: Self::file_modified(path),
         }
     }
 
+    /// Modification time of the file behind `path` as stored with its track.
+    ///
+    /// Cue tracks use the time of the audio file they are cut from.
+    fn file_modified(path: &Path) -> String {
+        let (file, _) = split_cue_path(path);
+        modified_rfc3339(fs_metadata(file).and_then(|m| m.modified()).ok())
+    }
+
     /// Process a file using cached artist/album lookups to avoid repeated DB queries.
     ///
     /// # Errors
@@ -508,37 +789,13 @@ impl<S: Storage> FsScanner<S> {
             return Err(SkipReason::CorruptFile);
         }
 
-        if self.check_path_exists(path).await.map_err(|e| {
-            warn!(error = %e, path = %path.display(), "Failed to check path existence");
-            SkipReason::CorruptFile
-        })? {
-            return Err(SkipReason::DuplicateByPath);
+        let existing = self.existing_track(path).await?;
+        if existing.is_none() {
+            self.check_new_file_duplicates(&metadata, content_hash.as_deref())
+                .await?;
         }
 
-        if self
-            .check_fingerprint_duplicate(&metadata)
-            .await
-            .map_err(|e| {
-                warn!(error = %e, title = ?metadata.title, "Failed to check fingerprint duplicate");
-                SkipReason::CorruptFile
-            })?
-        {
-            return Err(SkipReason::DuplicateByFingerprint);
-        }
-
-        let content_hash = match &content_hash {
-            Some(h) => {
-                self.check_precomputed_hash(h).await?;
-                Some(h.clone())
-            }
-            None => None,
-        };
-
-        let album_artist_name = metadata
-            .album_artist
-            .as_deref()
-            .or(metadata.artist.as_deref())
-            .unwrap_or("Unknown Artist");
+        let album_artist_name = album_artist_name(&metadata);
         let album_artist_id = self.resolve_artist(album_artist_name, artist_cache).await?;
 
         let album_title = metadata.album.as_deref().unwrap_or("Unknown Album");
@@ -560,6 +817,7 @@ impl<S: Storage> FsScanner<S> {
                 .unwrap_or_else(|| "Unknown Track".to_string()),
             track_number: metadata.track_number,
             disc_number: metadata.disc_number,
+            disc_total: metadata.disc_total,
             duration: metadata.duration,
             audio: Self::build_track_audio(
                 path,
@@ -570,10 +828,7 @@ impl<S: Storage> FsScanner<S> {
             ),
         };
 
-        let track_id = self.storage.insert_track(track).await.map_err(|e| {
-            warn!(error = %e, "Failed to insert track");
-            SkipReason::CorruptFile
-        })?;
+        let track_id = self.store_track(existing, track).await?;
 
         let track_info = TrackInfo {
             id: track_id,
@@ -596,6 +851,46 @@ impl<S: Storage> FsScanner<S> {
     }
 }
 
+impl<S: Storage + 'static> FsScanner<S> {
+    /// Remove the tracks under `paths` whose files are gone.
+    ///
+    /// Used for paths the watcher saw disappear; a removed directory drops
+    /// every track below it.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error if the tracks cannot be removed.
+    pub async fn prune_removed(&self, paths: &[PathBuf]) -> Result<PruneReport, StorageError> {
+        self.storage.prune_missing_under(paths).await
+    }
+
+    /// Scan every enabled library directory that can be reached, then
+    /// prune the tracks under them whose files are gone.
+    async fn rescan_reachable(&self) -> Result<RescanReport, StorageError> {
+        let (reachable, unreachable): (Vec<PathBuf>, Vec<PathBuf>) = self
+            .storage
+            .list_library_directories()
+            .await?
+            .into_iter()
+            .filter(|dir| dir.enabled)
+            .map(|dir| PathBuf::from(dir.path))
+            .partition(|path| path.is_dir());
+        for path in &unreachable {
+            warn!(path = %path.display(), "Library directory unreachable, skipping rescan");
+        }
+
+        for path in &reachable {
+            self.scan_dir(path).await;
+        }
+        let pruned = self.storage.prune_missing_under(&reachable).await?;
+        Ok(RescanReport {
+            directories_scanned: reachable.len(),
+            unreachable,
+            pruned,
+        })
+    }
+}
+
 impl<S: Storage + 'static> LibraryScanner for FsScanner<S> {
     async fn scan_all(&self) -> Result<(), StorageError> {
         let dirs = self.storage.list_library_directories().await?;
@@ -613,6 +908,17 @@ impl<S: Storage + 'static> LibraryScanner for FsScanner<S> {
         Ok(())
     }
 
+    async fn rescan_all(&self) -> Result<Option<RescanReport>, StorageError> {
+        if self.rescanning.swap(true, AcqRel) {
+            info!("Rescan already running, ignoring request");
+            return Ok(None);
+        }
+        info!("Rescanning all library directories");
+        let report = self.rescan_reachable().await;
+        self.rescanning.store(false, Release);
+        report.map(Some)
+    }
+
     fn cancel(&self) -> Result<(), StorageError> {
         self.cancel_tx
             .send(true)
@@ -628,6 +934,20 @@ pub trait LibraryScanner: Send + 'static {
     /// Trigger a scan of a specific directory.
     fn scan_directory(&self, path: &Path) -> impl Future<Output = Result<(), StorageError>> + Send;
 
+    /// Re-walk every enabled library directory and reconcile the library.
+    ///
+    /// New files are added, changed files re-read, and tracks whose files
+    /// are gone removed. Directories that cannot be reached are skipped and
+    /// their tracks kept. Returns `None` without scanning while another
+    /// rescan is running.
+    ///
+    /// # Errors
+    ///
+    /// Returns a storage error if the directories cannot be listed or the
+    /// missing tracks cannot be removed.
+    fn rescan_all(&self)
+    -> impl Future<Output = Result<Option<RescanReport>, StorageError>> + Send;
+
     /// Cancel any in-progress scan.
     ///
     /// # Errors
@@ -636,6 +956,17 @@ pub trait LibraryScanner: Send + 'static {
     fn cancel(&self) -> Result<(), StorageError>;
 }
 
+/// Outcome of a full rescan.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RescanReport {
+    /// Library directories that were walked.
+    pub directories_scanned: usize,
+    /// Enabled library directories that could not be reached.
+    pub unreachable: Vec<PathBuf>,
+    /// Rows removed because their files are gone.
+    pub pruned: PruneReport,
+}
+
 /// Mutable state shared across scan item processing.
 struct ScanContext<'a> {
     /// Directory being scanned.
@@ -733,6 +1064,14 @@ pub struct TrackInfo {
     pub album_id: Option<i64>,
 }
 
+/// Yield to the runtime once every [`SCAN_YIELD_INTERVAL`] items, so a
+/// long scan does not hold up other tasks.
+async fn yield_periodically(idx: usize) {
+    if idx.is_multiple_of(SCAN_YIELD_INTERVAL) {
+        yield_now().await;
+    }
+}
+
 /// Format sample rate for display in Hz.
 ///
 /// Converts to kHz-style value: 44100 → "44.1", 48000 → "48".
@@ -744,12 +1083,14 @@ fn format_sample_rate(hz: i32) -> String {
     }
 }
 
-/// Get the current UTC time as an RFC 3339 formatted string.
-fn utc_now_rfc3339() -> String {
-    let now = SystemTime::now()
+/// Format a file modification time as an RFC 3339 UTC string, falling back
+/// to the current time when it is unknown.
+fn modified_rfc3339(modified: Option<SystemTime>) -> String {
+    let secs = modified
+        .unwrap_or_else(SystemTime::now)
         .duration_since(UNIX_EPOCH)
-        .unwrap_or_default();
-    let secs = now.as_secs();
+        .unwrap_or_default()
+        .as_secs();
 
     let days = secs / 86400;
     let remaining = secs % 86400;
@@ -780,27 +1121,131 @@ fn days_to_ymd(days: u64) -> (u64, u64, u64) {
 #[cfg(test)]
 mod tests {
     use std::{
-        fs::{create_dir, write},
-        path::PathBuf,
+        fs::{File, create_dir, create_dir_all, read_dir, write},
+        io::Write,
+        os::unix::fs::symlink,
+        path::{Path, PathBuf},
+        sync::atomic::{
+            AtomicBool,
+            Ordering::{Acquire, Release},
+        },
+        thread::{current, scope, yield_now},
+        time::{Duration, Instant},
     };
 
     use {
-        anyhow::{Result, bail},
+        anyhow::{Result, anyhow, bail, ensure},
+        rayon::ThreadPoolBuilder,
         tempfile::tempdir,
+        tracing::info,
     };
 
     use crate::{
-        library::scanner::{
-            FsScanner,
-            ScanEvent::{ScanStarted, TrackSkipped},
-            SkipReason::{
-                CorruptFile, DuplicateByFingerprint, DuplicateByHash, DuplicateByPath,
-                UnsupportedFormat,
+        library::{
+            formats::AudioExtensions,
+            scanner::{
+                FsScanner,
+                ScanEvent::{ScanStarted, TrackSkipped},
+                SkipReason::{
+                    CorruptFile, DuplicateByFingerprint, DuplicateByHash, DuplicateByPath,
+                    UnsupportedFormat,
+                },
             },
+            watcher::scan_target,
         },
+        playback::write_wav_header,
         storage::database::SqliteStorage,
+        threading::{
+            scan_pool::run_on_scan_pool,
+            scheduler::{BackgroundScheduler, WorkIntensity::High},
+        },
     };
 
+    /// Path, duration, sample rate, and content hash of one extracted file.
+    type Extracted = (PathBuf, f64, i32, Option<String>);
+
+    /// Write a short mono WAV file whose length and samples depend on
+    /// `seed`, so every fixture has its own duration and content hash.
+    fn write_tone(path: &Path, seed: u8) -> Result<()> {
+        let frames = 800 * (usize::from(seed % 8) + 1);
+        let mut data = Vec::with_capacity(frames * 2);
+        for frame in 0..frames {
+            let sample = i16::from(u8::try_from(frame % 251)?) * 100 + i16::from(seed);
+            data.extend_from_slice(&sample.to_le_bytes());
+        }
+        let mut file = File::create(path)?;
+        write_wav_header(&mut file, 1, 8000, 16, u32::try_from(data.len())?)?;
+        file.write_all(&data)?;
+        Ok(())
+    }
+
+    /// Write `count` tone fixtures in album folders under `root`, returning
+    /// the files a scan walk finds.
+    fn write_tone_tree(root: &Path, count: u8) -> Result<Vec<PathBuf>> {
+        for index in 0..count {
+            let album_dir = root.join(format!("album-{}", index / 8));
+            create_dir_all(&album_dir)?;
+            write_tone(&album_dir.join(format!("{index:02}.wav")), index)?;
+        }
+        Ok(FsScanner::<SqliteStorage>::walk_directory_parallel(
+            root,
+            &AudioExtensions::default(),
+            false,
+        ))
+    }
+
+    /// Extract `files` as a scan does, returning whether it ran on a scan
+    /// worker and how many files were read.
+    fn extract_on_scan_worker(files: &[PathBuf], scheduler: &BackgroundScheduler) -> (bool, usize) {
+        let on_scan_worker = current()
+            .name()
+            .is_some_and(|n| n.starts_with("scan-worker-"));
+        let extracted = FsScanner::<SqliteStorage>::extract_files(
+            files,
+            scheduler,
+            false,
+            Duration::from_secs(5),
+        );
+        (on_scan_worker, extracted.len())
+    }
+
+    /// Highest number of permits held on `scheduler` until `done` is set.
+    fn peak_in_flight_until(scheduler: &BackgroundScheduler, done: &AtomicBool) -> usize {
+        let mut peak = scheduler.in_flight();
+        while !done.load(Acquire) {
+            peak = peak.max(scheduler.in_flight());
+            yield_now();
+        }
+        peak
+    }
+
+    /// Extract `files` on a pool of `threads` workers with a permit budget
+    /// of the same size, returning the results sorted by path.
+    fn extract_with_threads(files: &[PathBuf], threads: usize) -> Result<Vec<Extracted>> {
+        let pool = ThreadPoolBuilder::new().num_threads(threads).build()?;
+        let scheduler = BackgroundScheduler::with_cores(High, threads);
+        let started = Instant::now();
+        let extracted = pool.install(|| {
+            FsScanner::<SqliteStorage>::extract_files(
+                files,
+                &scheduler,
+                false,
+                Duration::from_secs(5),
+            )
+        });
+        info!(threads, files = files.len(), elapsed = ?started.elapsed(), "Extracted synthetic tree");
+        ensure!(
+            scheduler.in_flight() == 0,
+            "{threads} threads left permits held"
+        );
+        let mut results: Vec<Extracted> = extracted
+            .into_iter()
+            .map(|(path, metadata, hash)| (path, metadata.duration, metadata.sample_rate, hash))
+            .collect();
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(results)
+    }
+
     #[test]
     fn walk_directory_finds_audio_files() -> Result<()> {
         let dir = tempdir()?;
@@ -816,7 +1261,11 @@ mod tests {
         create_dir(&sub)?;
         write(sub.join("nested.flac"), b"\0")?;
 
-        let files = FsScanner::<SqliteStorage>::walk_directory_parallel(root);
+        let files = FsScanner::<SqliteStorage>::walk_directory_parallel(
+            root,
+            &AudioExtensions::default(),
+            false,
+        );
         if files.len() != 4 {
             bail!("expected 4 audio files, got {}", files.len());
         }
@@ -826,13 +1275,136 @@ mod tests {
     #[test]
     fn walk_directory_handles_empty() -> Result<()> {
         let dir = tempdir()?;
-        let files = FsScanner::<SqliteStorage>::walk_directory_parallel(dir.path());
+        let files = FsScanner::<SqliteStorage>::walk_directory_parallel(
+            dir.path(),
+            &AudioExtensions::default(),
+            false,
+        );
         if !files.is_empty() {
             bail!("expected empty directory, got {} files", files.len());
         }
         Ok(())
     }
 
+    #[test]
+    fn walk_directory_follows_symlinks_only_when_asked() -> Result<()> {
+        let dir = tempdir()?;
+        let album = dir.path().join("album");
+        create_dir(&album)?;
+        write(album.join("01.flac"), b"\0")?;
+        symlink(&album, dir.path().join("linked-album"))?;
+        symlink(dir.path(), album.join("loop"))?;
+
+        let outside = tempdir()?;
+        write(outside.path().join("02.flac"), b"\0")?;
+        symlink(outside.path(), dir.path().join("elsewhere"))?;
+
+        let extensions = AudioExtensions::default();
+        let skipped =
+            FsScanner::<SqliteStorage>::walk_directory_parallel(dir.path(), &extensions, false);
+        ensure!(
+            skipped == vec![album.join("01.flac")],
+            "symlinks must be skipped, got {skipped:?}"
+        );
+
+        let followed =
+            FsScanner::<SqliteStorage>::walk_directory_parallel(dir.path(), &extensions, true);
+        ensure!(
+            followed.len() == 2,
+            "each real folder is walked once despite the loop, got {followed:?}"
+        );
+        ensure!(
+            followed.iter().any(|p| p.ends_with("02.flac")),
+            "linked folders outside the root are walked, got {followed:?}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn watcher_and_scanner_accept_the_same_files() -> Result<()> {
+        let dir = tempdir()?;
+        let extensions = AudioExtensions::with_extra(["wv"]);
+        for name in [
+            "a.flac", "b.MP3", "c.m4a", "d.opus", "e.aif", "f.wv", "g.txt", "h.jpg", "i.cue", "j",
+        ] {
+            write(dir.path().join(name), b"\0")?;
+        }
+
+        let mut scanned =
+            FsScanner::<SqliteStorage>::walk_directory_parallel(dir.path(), &extensions, false);
+        scanned.sort();
+        let mut watched: Vec<PathBuf> = read_dir(dir.path())?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<_, _>>()?;
+        watched.retain(|path| scan_target(path, &extensions).is_some());
+        watched.sort();
+        ensure!(
+            scanned.len() == 6,
+            "expected 6 audio files, got {}",
+            scanned.len()
+        );
+        ensure!(
+            scanned == watched,
+            "scanner found {scanned:?}, watcher {watched:?}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn extraction_matches_across_thread_counts() -> Result<()> {
+        let dir = tempdir()?;
+        let files = write_tone_tree(dir.path(), 64)?;
+        ensure!(files.len() == 64, "expected 64 files, got {}", files.len());
+
+        let mut runs = Vec::new();
+        for threads in [1, 2, 4] {
+            runs.push(extract_with_threads(&files, threads)?);
+        }
+        ensure!(
+            runs.iter().all(|run| run.len() == 64),
+            "every thread count must read every file"
+        );
+        ensure!(
+            runs.iter().flatten().all(|(_, _, _, hash)| hash.is_some()),
+            "every file must be hashed"
+        );
+        ensure!(
+            runs.iter().zip(runs.iter().skip(1)).all(|(a, b)| a == b),
+            "the thread count must not change what a scan reads"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn extraction_on_the_scan_pool_leaves_the_rest_of_the_budget() -> Result<()> {
+        let dir = tempdir()?;
+        let files = write_tone_tree(dir.path(), 32)?;
+        let scheduler = BackgroundScheduler::with_cores(High, 8);
+        let other_work = scheduler.acquire_many(2);
+        let done = AtomicBool::new(false);
+
+        let (peak, (on_scan_worker, extracted)) = scope(|s| {
+            let sampler = s.spawn(|| peak_in_flight_until(&scheduler, &done));
+            let scan = run_on_scan_pool(2, || extract_on_scan_worker(&files, &scheduler));
+            done.store(true, Release);
+            (sampler.join(), scan)
+        });
+        let peak = peak.map_err(|e| anyhow!("the permit sampler panicked: {e:?}"))?;
+
+        ensure!(on_scan_worker, "extraction must run on the scan pool");
+        ensure!(extracted == 32, "expected 32 files, got {extracted}");
+        ensure!(other_work.count() == 2, "other work must get its permits");
+        ensure!(
+            peak <= 4,
+            "two scan workers may hold two permits beside the other work, saw {peak}"
+        );
+        ensure!(
+            scheduler.in_flight() == 2,
+            "the scan must return its permits while other work keeps its own"
+        );
+        Ok(())
+    }
+
     #[test]
     fn scan_event_variants() {
         let started = ScanStarted {
@@ -0,0 +1,291 @@
+//! HTTP submission of listens and "now playing" updates.
+//!
+//! Calls are blocking and must run off the `GLib` main thread and the
+//! tokio reactor (e.g. inside `spawn_blocking`).
+
+use std::time::Duration;
+
+use {
+    md5::{Digest, Md5},
+    serde_json::{Value, from_str, json},
+    thiserror::Error,
+    ureq::{
+        Agent,
+        Error::{self as HttpError, StatusCode},
+    },
+};
+
+use crate::library::scrobble::{
+    Listen,
+    ScrobbleService::{self, LastFm, ListenBrainz},
+    ScrobbleSettings,
+};
+
+/// Last.fm scrobbling API endpoint.
+const LASTFM_API_URL: &str = "https://ws.audioscrobbler.com/2.0/";
+
+/// Maximum listens per Last.fm `track.scrobble` request.
+const LASTFM_BATCH: usize = 50;
+
+/// Maximum listens per `ListenBrainz` import request.
+const LISTENBRAINZ_BATCH: usize = 1000;
+
+/// Name reported to services as the submitting client.
+const CLIENT_NAME: &str = "oxhidifi";
+
+/// Timeout applied to every scrobble request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Errors from scrobble submission.
+#[derive(Debug, Error)]
+pub enum ScrobbleError {
+    /// Required credentials are not configured.
+    #[error("Scrobbling credentials are not configured")]
+    MissingCredentials,
+    /// The request could not be completed, or the service is unavailable;
+    /// retrying later may succeed.
+    #[error("Scrobble request failed: {0}")]
+    Request(String),
+    /// The service refused the credentials.
+    #[error("Scrobbling credentials were refused: {0}")]
+    Unauthorized(String),
+    /// The service rejected the submitted listens.
+    #[error("Scrobble rejected: {0}")]
+    Rejected(String),
+}
+
+impl From<HttpError> for ScrobbleError {
+    fn from(error: HttpError) -> Self {
+        match error {
+            StatusCode(code @ (401 | 403)) => Self::Unauthorized(format!("HTTP {code}")),
+            StatusCode(code @ 400..=499) if code != 429 => Self::Rejected(format!("HTTP {code}")),
+            other => Self::Request(other.to_string()),
+        }
+    }
+}
+
+/// Classify a Last.fm API error code.
+///
+/// Authentication and rate-limit errors are not caused by the listens, so
+/// they are kept for a later retry instead of being rejected.
+fn lastfm_error(code: i64, message: String) -> ScrobbleError {
+    match code {
+        4 | 9 | 10 | 14 | 26 => ScrobbleError::Unauthorized(message),
+        8 | 11 | 16 | 29 => ScrobbleError::Request(message),
+        _ => ScrobbleError::Rejected(message),
+    }
+}
+
+/// Maximum number of listens [`submit_listens`] sends in one request.
+#[must_use]
+pub const fn batch_size(service: ScrobbleService) -> usize {
+    match service {
+        ListenBrainz => LISTENBRAINZ_BATCH,
+        LastFm => LASTFM_BATCH,
+    }
+}
+
+/// Submit the oldest queued listens.
+///
+/// Returns how many listens from the front of `listens` were accepted.
+///
+/// # Errors
+///
+/// Returns [`ScrobbleError`] if credentials are missing or the service
+/// cannot be reached or rejects the batch. Only
+/// [`ScrobbleError::Rejected`] is caused by the listens themselves.
+pub fn submit_listens(
+    settings: &ScrobbleSettings,
+    listens: &[Listen],
+) -> Result<usize, ScrobbleError> {
+    let batch = &listens[..listens.len().min(batch_size(settings.service))];
+    match settings.service {
+        ListenBrainz => {
+            let payload: Vec<Value> = batch
+                .iter()
+                .map(|l| listenbrainz_entry(l, Some(l.listened_at)))
+                .collect();
+            post_listenbrainz(settings, "import", payload)?;
+        }
+        LastFm => post_lastfm(settings, "track.scrobble", lastfm_scrobble_params(batch))?,
+    }
+    Ok(batch.len())
+}
+
+/// Announce the track that just started playing.
+///
+/// # Errors
+///
+/// Returns [`ScrobbleError`] if credentials are missing or the request
+/// fails.
+pub fn submit_now_playing(
+    settings: &ScrobbleSettings,
+    listen: &Listen,
+) -> Result<(), ScrobbleError> {
+    match settings.service {
+        ListenBrainz => post_listenbrainz(
+            settings,
+            "playing_now",
+            vec![listenbrainz_entry(listen, None)],
+        ),
+        LastFm => {
+            let mut params = vec![
+                ("artist".to_string(), listen.artist.clone()),
+                ("track".to_string(), listen.title.clone()),
+                (
+                    "duration".to_string(),
+                    format!("{:.0}", listen.duration_seconds),
+                ),
+            ];
+            if let Some(album) = &listen.album {
+                params.push(("album".to_string(), album.clone()));
+            }
+            post_lastfm(settings, "track.updateNowPlaying", params)
+        }
+    }
+}
+
+/// Build the `ListenBrainz` payload entry for `listen`.
+///
+/// "Now playing" entries carry no `listened_at` timestamp.
+fn listenbrainz_entry(listen: &Listen, listened_at: Option<i64>) -> Value {
+    let duration_ms = (listen.duration_seconds * 1000.0).round();
+    let mut entry = json!({
+        "track_metadata": {
+            "artist_name": listen.artist,
+            "track_name": listen.title,
+            "release_name": listen.album,
+            "additional_info": {
+                "duration_ms": duration_ms,
+                "media_player": CLIENT_NAME,
+                "submission_client": CLIENT_NAME,
+            },
+        },
+    });
+    if let (Some(timestamp), Some(fields)) = (listened_at, entry.as_object_mut()) {
+        fields.insert("listened_at".to_string(), json!(timestamp));
+    }
+    entry
+}
+
+/// Build indexed `track.scrobble` parameters for a batch of listens.
+fn lastfm_scrobble_params(batch: &[Listen]) -> Vec<(String, String)> {
+    let mut params = Vec::new();
+    for (i, l) in batch.iter().enumerate() {
+        params.push((format!("artist[{i}]"), l.artist.clone()));
+        params.push((format!("track[{i}]"), l.title.clone()));
+        params.push((format!("timestamp[{i}]"), l.listened_at.to_string()));
+        params.push((
+            format!("duration[{i}]"),
+            format!("{:.0}", l.duration_seconds),
+        ));
+        if let Some(album) = &l.album {
+            params.push((format!("album[{i}]"), album.clone()));
+        }
+    }
+    params
+}
+
+/// Compute the Last.fm `api_sig` for a set of request parameters.
+///
+/// Parameters are sorted by name and concatenated as `namevalue`, the
+/// shared secret is appended, and the result is MD5-hashed.
+#[must_use]
+pub fn lastfm_signature(params: &[(String, String)], secret: &str) -> String {
+    let mut sorted: Vec<&(String, String)> = params.iter().collect();
+    sorted.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut input = String::new();
+    for (name, value) in sorted {
+        input.push_str(name);
+        input.push_str(value);
+    }
+    input.push_str(secret);
+    hex::encode(Md5::digest(input.as_bytes()))
+}
+
+/// Create an HTTP agent with the scrobble request timeout.
+fn agent() -> Agent {
+    Agent::config_builder()
+        .timeout_global(Some(REQUEST_TIMEOUT))
+        .build()
+        .into()
+}
+
+/// POST a signed Last.fm API call.
+fn post_lastfm(
+    settings: &ScrobbleSettings,
+    method: &str,
+    mut params: Vec<(String, String)>,
+) -> Result<(), ScrobbleError> {
+    if settings.lastfm_api_key.is_empty()
+        || settings.lastfm_api_secret.is_empty()
+        || settings.lastfm_session_key.is_empty()
+    {
+        return Err(ScrobbleError::MissingCredentials);
+    }
+    params.push(("method".to_string(), method.to_string()));
+    params.push(("api_key".to_string(), settings.lastfm_api_key.clone()));
+    params.push(("sk".to_string(), settings.lastfm_session_key.clone()));
+    let signature = lastfm_signature(&params, &settings.lastfm_api_secret);
+    params.push(("api_sig".to_string(), signature));
+    params.push(("format".to_string(), "json".to_string()));
+
+    let mut response = agent().post(LASTFM_API_URL).send_form(params)?;
+    let body = response.body_mut().read_to_string()?;
+    let reply: Value = from_str(&body).map_err(|e| ScrobbleError::Request(e.to_string()))?;
+    let Some(error) = reply.get("error") else {
+        return Ok(());
+    };
+    let message = reply
+        .get("message")
+        .map_or_else(|| format!("error {error}"), Value::to_string);
+    Err(lastfm_error(error.as_i64().unwrap_or_default(), message))
+}
+
+/// POST a `ListenBrainz` `submit-listens` request.
+fn post_listenbrainz(
+    settings: &ScrobbleSettings,
+    listen_type: &str,
+    payload: Vec<Value>,
+) -> Result<(), ScrobbleError> {
+    if settings.listenbrainz_token.is_empty() {
+        return Err(ScrobbleError::MissingCredentials);
+    }
+    let url = format!(
+        "{}/1/submit-listens",
+        settings.listenbrainz_url.trim_end_matches('/')
+    );
+    let body = json!({ "listen_type": listen_type, "payload": payload }).to_string();
+    agent()
+        .post(&url)
+        .header(
+            "Authorization",
+            format!("Token {}", settings.listenbrainz_token),
+        )
+        .content_type("application/json")
+        .send(body.as_str())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::library::scrobble::client::lastfm_signature;
+
+    #[test]
+    fn lastfm_signature_sorts_parameters() {
+        let params = vec![
+            ("track".to_string(), "Song".to_string()),
+            ("artist".to_string(), "Band".to_string()),
+        ];
+        assert_eq!(
+            lastfm_signature(&params, "secret"),
+            lastfm_signature(&params.into_iter().rev().collect::<Vec<_>>(), "secret"),
+            "Parameter order must not affect the signature"
+        );
+        assert_eq!(
+            lastfm_signature(&[], ""),
+            "d41d8cd98f00b204e9800998ecf8427e",
+            "Empty input must hash to the MD5 of the empty string"
+        );
+    }
+}
@@ -0,0 +1,309 @@
+//! Scrobbling listens to `ListenBrainz` or Last.fm.
+//!
+//! A background task follows [`PlaybackEvent`]s, sends a "now playing"
+//! update whenever a track starts, and records a listen once the track has
+//! been heard past the user's [`PlayThreshold`], by default half its length
+//! or four minutes, whichever comes first. Listens go to an on-disk
+//! [`ScrobbleQueue`](queue::ScrobbleQueue) before submission, so plays
+//! made while offline are retried later instead of being lost. Scrobbling
+//! is opt-in and off by default.
+
+pub mod client;
+pub mod queue;
+pub mod submitter;
+
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use {
+    anyhow::{Context, Result},
+    async_channel::Receiver,
+    parking_lot::Mutex,
+    serde::{Deserialize, Serialize},
+    tokio::{spawn, task::spawn_blocking},
+    tracing::warn,
+};
+
+use crate::{
+    library::{
+        play_threshold::{PlayThreshold, PlayTracker},
+        scrobble::{client::submit_now_playing, submitter::Submitter},
+    },
+    playback::{
+        control::PlaybackController,
+        engine::{
+            PlaybackEngine,
+            PlaybackEvent::{self, PositionTick, Seeked, Stopped, TrackStarted},
+        },
+    },
+    storage::{Storage, database::SqliteStorage},
+};
+
+/// Default `ListenBrainz` API root.
+pub const DEFAULT_LISTENBRAINZ_URL: &str = "https://api.listenbrainz.org";
+
+/// A single play ready for submission.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Listen {
+    /// Track artist name.
+    pub artist: String,
+    /// Track title.
+    pub title: String,
+    /// Album title, if known.
+    pub album: Option<String>,
+    /// Track duration in seconds.
+    pub duration_seconds: f64,
+    /// Unix timestamp (seconds) at which playback started.
+    pub listened_at: i64,
+}
+
+/// Which scrobbling service receives listens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrobbleService {
+    /// `ListenBrainz` or a compatible server, authenticated by user token.
+    #[default]
+    ListenBrainz,
+    /// Last.fm, authenticated by API key, secret, and session key.
+    LastFm,
+}
+
+impl ScrobbleService {
+    /// All services in display order.
+    pub const ALL: [Self; 2] = [Self::ListenBrainz, Self::LastFm];
+
+    /// Human-readable label for preference rows.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::ListenBrainz => "ListenBrainz",
+            Self::LastFm => "Last.fm",
+        }
+    }
+}
+
+/// Persisted scrobbling preferences and credentials.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScrobbleSettings {
+    /// Whether scrobbling is enabled.
+    pub enabled: bool,
+    /// Service receiving listens.
+    pub service: ScrobbleService,
+    /// `ListenBrainz` API root URL.
+    pub listenbrainz_url: String,
+    /// `ListenBrainz` user token.
+    pub listenbrainz_token: String,
+    /// Last.fm API key.
+    pub lastfm_api_key: String,
+    /// Last.fm API shared secret.
+    pub lastfm_api_secret: String,
+    /// Last.fm session key for the user.
+    pub lastfm_session_key: String,
+}
+
+impl ScrobbleSettings {
+    /// Copy of these settings without the user's tokens, keys, and secrets.
+    #[must_use]
+    pub fn without_credentials(&self) -> Self {
+        Self {
+            listenbrainz_token: String::new(),
+            lastfm_api_key: String::new(),
+            lastfm_api_secret: String::new(),
+            lastfm_session_key: String::new(),
+            ..self.clone()
+        }
+    }
+
+    /// These settings with the credentials of `local` in place of their own.
+    #[must_use]
+    pub fn with_credentials_of(self, local: &Self) -> Self {
+        Self {
+            listenbrainz_token: local.listenbrainz_token.clone(),
+            lastfm_api_key: local.lastfm_api_key.clone(),
+            lastfm_api_secret: local.lastfm_api_secret.clone(),
+            lastfm_session_key: local.lastfm_session_key.clone(),
+            ..self
+        }
+    }
+}
+
+impl Default for ScrobbleSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            service: ScrobbleService::ListenBrainz,
+            listenbrainz_url: DEFAULT_LISTENBRAINZ_URL.to_string(),
+            listenbrainz_token: String::new(),
+            lastfm_api_key: String::new(),
+            lastfm_api_secret: String::new(),
+            lastfm_session_key: String::new(),
+        }
+    }
+}
+
+/// Current Unix time in whole seconds.
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| i64::try_from(d.as_secs()).unwrap_or(i64::MAX))
+}
+
+/// Send a "now playing" update, then flush any queued listens.
+fn announce_now_playing(
+    settings: &ScrobbleSettings,
+    listen: &Listen,
+    submitter: &Mutex<Submitter>,
+) {
+    if let Err(e) = submit_now_playing(settings, listen) {
+        warn!(error = %e, "Failed to send now playing update");
+    }
+    submitter.lock().flush(settings);
+}
+
+/// Build a listen for `track_id` from library metadata.
+///
+/// Returns `None` if the track is unknown or has no artist, since both
+/// services reject listens without one.
+///
+/// # Errors
+///
+/// Returns an error if the track, its artist, or its album cannot be
+/// loaded from the library.
+async fn resolve_listen(
+    storage: &SqliteStorage,
+    track_id: i64,
+    listened_at: i64,
+) -> Result<Option<Listen>> {
+    let Some(track) = storage
+        .get_track(track_id)
+        .await
+        .with_context(|| format!("Failed to load track {track_id}"))?
+    else {
+        return Ok(None);
+    };
+    let Some(artist_id) = track.audio.artist_id else {
+        return Ok(None);
+    };
+    let Some(artist) = storage
+        .get_artist(artist_id)
+        .await
+        .with_context(|| format!("Failed to load artist {artist_id} of track {track_id}"))?
+    else {
+        return Ok(None);
+    };
+    let album = match track.audio.album_id {
+        Some(album_id) => storage
+            .get_album(album_id)
+            .await
+            .with_context(|| format!("Failed to load album {album_id} of track {track_id}"))?
+            .map(|a| a.title),
+        None => None,
+    };
+    Ok(Some(Listen {
+        artist: artist.name,
+        title: track.title,
+        album,
+        duration_seconds: track.duration,
+        listened_at,
+    }))
+}
+
+/// Resolve the listen for `track_id`, logging a failure to load it.
+async fn listen_for(storage: &SqliteStorage, track_id: i64, listened_at: i64) -> Option<Listen> {
+    resolve_listen(storage, track_id, listened_at)
+        .await
+        .unwrap_or_else(|e| {
+            warn!(error = ?e, track_id, "Cannot scrobble track");
+            None
+        })
+}
+
+/// Follow playback and submit listens while scrobbling is enabled.
+///
+/// # Arguments
+///
+/// * `playback` - Engine whose events are followed
+/// * `storage` - Storage for settings and track metadata
+/// * `queue_path` - File backing the offline listen queue
+pub fn spawn_scrobbler(
+    playback: &PlaybackEngine,
+    storage: Arc<SqliteStorage>,
+    queue_path: PathBuf,
+) {
+    spawn(run_scrobbler(playback.subscribe(), storage, queue_path));
+}
+
+/// Handle playback events until the engine closes its event channel.
+async fn run_scrobbler(
+    rx: Receiver<PlaybackEvent>,
+    storage: Arc<SqliteStorage>,
+    queue_path: PathBuf,
+) {
+    let submitter = Arc::new(Mutex::new(Submitter::load(queue_path)));
+    let mut tracker = PlayTracker::default();
+    let mut started_at = 0;
+
+    while let Ok(event) = rx.recv().await {
+        let settings = storage.get_scrobble_settings();
+        if !settings.enabled {
+            tracker.stop();
+            continue;
+        }
+        let threshold = storage.get_play_threshold();
+        handle_event(
+            &event,
+            &storage,
+            &submitter,
+            (&settings, threshold),
+            &mut tracker,
+            &mut started_at,
+        )
+        .await;
+    }
+}
+
+/// React to one playback event.
+///
+/// A listen is recorded once `threshold` says the track counts as played.
+async fn handle_event(
+    event: &PlaybackEvent,
+    storage: &SqliteStorage,
+    submitter: &Arc<Mutex<Submitter>>,
+    (settings, threshold): (&ScrobbleSettings, PlayThreshold),
+    tracker: &mut PlayTracker,
+    started_at: &mut i64,
+) {
+    match *event {
+        TrackStarted { track_id } => {
+            tracker.start(track_id);
+            *started_at = unix_now();
+            let Some(listen) = listen_for(storage, track_id, *started_at).await else {
+                return;
+            };
+            let settings = settings.clone();
+            let submitter = Arc::clone(submitter);
+            spawn_blocking(move || announce_now_playing(&settings, &listen, &submitter));
+        }
+        PositionTick {
+            elapsed_seconds,
+            duration_seconds,
+        } => {
+            let Some(track_id) = tracker.tick(elapsed_seconds, duration_seconds, threshold) else {
+                return;
+            };
+            let Some(listen) = listen_for(storage, track_id, *started_at).await else {
+                return;
+            };
+            let settings = settings.clone();
+            let submitter = Arc::clone(submitter);
+            spawn_blocking(move || submitter.lock().record(listen, &settings));
+        }
+        Seeked { position_seconds } => tracker.seek(position_seconds),
+        Stopped => tracker.stop(),
+        _ => {}
+    }
+}
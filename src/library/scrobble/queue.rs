@@ -0,0 +1,151 @@
+//! On-disk queue of listens awaiting submission.
+//!
+//! The queue is a JSON array rewritten through a temporary file and an
+//! atomic rename, so a crash mid-write never loses previously queued plays.
+
+use std::{
+    fs::{create_dir_all, read_to_string, rename, write},
+    io::ErrorKind::NotFound,
+    path::PathBuf,
+};
+
+use {
+    anyhow::{Context, Result},
+    serde_json::{from_str, to_string},
+    tracing::warn,
+};
+
+use crate::library::scrobble::Listen;
+
+/// Maximum number of listens kept while offline; the oldest are dropped.
+const MAX_QUEUED: usize = 10_000;
+
+/// Persistent FIFO of listens not yet accepted by the service.
+pub struct ScrobbleQueue {
+    /// File backing the queue.
+    path: PathBuf,
+    /// Listens in submission order.
+    pending: Vec<Listen>,
+}
+
+impl ScrobbleQueue {
+    /// Load the queue from `path`, starting empty if it is missing or
+    /// unreadable.
+    #[must_use]
+    pub fn load(path: PathBuf) -> Self {
+        let pending = match read_to_string(&path) {
+            Ok(json) => from_str(&json).unwrap_or_else(|e| {
+                warn!(error = %e, path = %path.display(), "Discarding corrupt scrobble queue");
+                Vec::new()
+            }),
+            Err(e) if e.kind() == NotFound => Vec::new(),
+            Err(e) => {
+                warn!(error = %e, path = %path.display(), "Failed to read scrobble queue");
+                Vec::new()
+            }
+        };
+        Self { path, pending }
+    }
+
+    /// Append a listen and persist the queue.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the queue file cannot be written.
+    pub fn push(&mut self, listen: Listen) -> Result<()> {
+        self.pending.push(listen);
+        if self.pending.len() > MAX_QUEUED {
+            let excess = self.pending.len() - MAX_QUEUED;
+            self.pending = self.pending.split_off(excess);
+        }
+        self.save()
+    }
+
+    /// Remove the first `count` listens after a successful submission.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the queue file cannot be written.
+    pub fn remove_front(&mut self, count: usize) -> Result<()> {
+        self.pending = self.pending.split_off(count.min(self.pending.len()));
+        self.save()
+    }
+
+    /// Listens awaiting submission, oldest first.
+    #[must_use]
+    pub fn pending(&self) -> &[Listen] {
+        &self.pending
+    }
+
+    /// Number of queued listens.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns `true` if nothing is queued.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Write the queue to disk atomically.
+    fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let json = to_string(&self.pending).context("Failed to serialize scrobble queue")?;
+        let tmp = self.path.with_extension("json.tmp");
+        write(&tmp, json).with_context(|| format!("Failed to write {}", tmp.display()))?;
+        rename(&tmp, &self.path)
+            .with_context(|| format!("Failed to replace {}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{Result, ensure};
+    use tempfile::tempdir;
+
+    use crate::library::scrobble::{Listen, queue::ScrobbleQueue};
+
+    fn listen(title: &str) -> Listen {
+        Listen {
+            artist: "Artist".to_string(),
+            title: title.to_string(),
+            album: None,
+            duration_seconds: 180.0,
+            listened_at: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn queue_survives_reload() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("scrobble_queue.json");
+
+        let mut queue = ScrobbleQueue::load(path.clone());
+        queue.push(listen("One"))?;
+        queue.push(listen("Two"))?;
+        drop(queue);
+
+        let mut reloaded = ScrobbleQueue::load(path.clone());
+        ensure!(reloaded.len() == 2, "both listens must be persisted");
+        reloaded.remove_front(1)?;
+
+        let last = ScrobbleQueue::load(path);
+        ensure!(
+            last.pending() == [listen("Two")],
+            "only the unsubmitted listen must remain"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn missing_file_loads_empty() -> Result<()> {
+        let dir = tempdir()?;
+        let queue = ScrobbleQueue::load(dir.path().join("absent.json"));
+        ensure!(queue.is_empty(), "missing queue file must load empty");
+        Ok(())
+    }
+}
@@ -0,0 +1,243 @@
+//! Submission of queued listens with retry backoff.
+//!
+//! Transport failures, server errors, and refused credentials keep the
+//! queue intact and back off before retrying. A batch rejected by the
+//! service is resubmitted one listen at a time, so only the listens the
+//! service refuses are dropped and the rest of the queue keeps moving.
+
+use std::{
+    path::PathBuf,
+    slice::from_ref,
+    time::{Duration, Instant},
+};
+
+use tracing::{info, warn};
+
+use crate::library::scrobble::{
+    Listen, ScrobbleSettings,
+    client::{
+        ScrobbleError::{self, Rejected},
+        batch_size, submit_listens,
+    },
+    queue::ScrobbleQueue,
+};
+
+/// Minimum delay between attempts to flush the queue after a failure.
+const RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Queue and retry bookkeeping shared by submission tasks.
+pub struct Submitter {
+    /// On-disk queue of listens awaiting submission.
+    queue: ScrobbleQueue,
+    /// When the last failed flush happened, for retry backoff.
+    last_failure: Option<Instant>,
+}
+
+impl Submitter {
+    /// Load the queue of listens awaiting submission from `queue_path`.
+    #[must_use]
+    pub fn load(queue_path: PathBuf) -> Self {
+        Self {
+            queue: ScrobbleQueue::load(queue_path),
+            last_failure: None,
+        }
+    }
+
+    /// Submit queued listens unless a recent failure is still backing off.
+    pub fn flush(&mut self, settings: &ScrobbleSettings) {
+        self.flush_with(batch_size(settings.service), |listens| {
+            submit_listens(settings, listens)
+        });
+    }
+
+    /// Queue a new listen and try to submit right away.
+    ///
+    /// A fresh listen clears the retry backoff so connectivity is retested.
+    pub fn record(&mut self, listen: Listen, settings: &ScrobbleSettings) {
+        if let Err(e) = self.queue.push(listen) {
+            warn!(error = %e, "Failed to queue listen");
+        }
+        self.last_failure = None;
+        self.flush(settings);
+    }
+
+    /// Submit queued listens through `submit`, which sends at most
+    /// `batch` listens per call and returns how many were accepted.
+    fn flush_with(
+        &mut self,
+        batch: usize,
+        mut submit: impl FnMut(&[Listen]) -> Result<usize, ScrobbleError>,
+    ) {
+        if self.queue.is_empty()
+            || self
+                .last_failure
+                .is_some_and(|at| at.elapsed() < RETRY_INTERVAL)
+        {
+            return;
+        }
+        match submit(self.queue.pending()) {
+            Ok(count) => self.accept(count),
+            Err(Rejected(reason)) => {
+                warn!(%reason, "Scrobble batch rejected, submitting listens one by one");
+                self.submit_singly(batch, &mut submit);
+            }
+            Err(e) => self.back_off(&e),
+        }
+    }
+
+    /// Submit the first `count` queued listens one at a time, dropping
+    /// each listen the service rejects.
+    ///
+    /// Stops and backs off on any other failure, keeping the remaining
+    /// listens queued.
+    fn submit_singly(
+        &mut self,
+        count: usize,
+        submit: &mut impl FnMut(&[Listen]) -> Result<usize, ScrobbleError>,
+    ) {
+        let mut remaining = count;
+        while remaining > 0 && self.submit_front(submit) {
+            remaining -= 1;
+        }
+    }
+
+    /// Submit the first queued listen on its own.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the queue is empty or the submission failed for another
+    /// reason than the listen being rejected.
+    fn submit_front(
+        &mut self,
+        submit: &mut impl FnMut(&[Listen]) -> Result<usize, ScrobbleError>,
+    ) -> bool {
+        let Some(listen) = self.queue.pending().first() else {
+            return false;
+        };
+        match submit(from_ref(listen)) {
+            Ok(accepted) => self.accept(accepted),
+            Err(Rejected(reason)) => {
+                warn!(
+                    %reason,
+                    artist = %listen.artist,
+                    title = %listen.title,
+                    listened_at = listen.listened_at,
+                    "Dropping listen rejected by the scrobbling service"
+                );
+                self.remove_front(1);
+            }
+            Err(e) => {
+                self.back_off(&e);
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Drop `count` listens accepted by the service from the queue.
+    fn accept(&mut self, count: usize) {
+        info!(count, "Submitted queued listens");
+        self.last_failure = None;
+        self.remove_front(count);
+    }
+
+    /// Remove the first `count` listens from the queue.
+    fn remove_front(&mut self, count: usize) {
+        if let Err(e) = self.queue.remove_front(count) {
+            warn!(error = %e, "Failed to update scrobble queue");
+        }
+    }
+
+    /// Keep the queue and wait before the next attempt.
+    fn back_off(&mut self, error: &ScrobbleError) {
+        warn!(error = %error, queued = self.queue.len(), "Scrobble submission failed");
+        self.last_failure = Some(Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        anyhow::{Result, ensure},
+        tempfile::{TempDir, tempdir},
+    };
+
+    use crate::library::scrobble::{
+        Listen,
+        client::ScrobbleError::{self, Rejected, Request},
+        submitter::Submitter,
+    };
+
+    fn listen(title: &str) -> Listen {
+        Listen {
+            artist: "Band".to_string(),
+            title: title.to_string(),
+            album: None,
+            duration_seconds: 200.0,
+            listened_at: 1_700_000_000,
+        }
+    }
+
+    fn submitter_with(titles: &[&str]) -> Result<(Submitter, TempDir)> {
+        let dir = tempdir()?;
+        let mut submitter = Submitter::load(dir.path().join("queue.json"));
+        for title in titles {
+            submitter.queue.push(listen(title))?;
+        }
+        Ok((submitter, dir))
+    }
+
+    /// Reject any batch holding the listen titled "bad".
+    fn reject_bad(listens: &[Listen]) -> Result<(), ScrobbleError> {
+        match listens.iter().find(|l| l.title == "bad") {
+            Some(_) => Err(Rejected("HTTP 400".to_string())),
+            None => Ok(()),
+        }
+    }
+
+    fn titles(submitter: &Submitter) -> Vec<&str> {
+        submitter
+            .queue
+            .pending()
+            .iter()
+            .map(|l| l.title.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn rejected_listen_is_dropped_and_the_rest_submitted() -> Result<()> {
+        let (mut submitter, _dir) = submitter_with(&["a", "bad", "c", "d"])?;
+        let mut accepted = Vec::new();
+        submitter.flush_with(3, |listens| {
+            reject_bad(listens)?;
+            accepted.extend(listens.iter().map(|l| l.title.clone()));
+            Ok(listens.len())
+        });
+        ensure!(
+            accepted == ["a", "c"],
+            "valid listens of the batch are sent"
+        );
+        ensure!(titles(&submitter) == ["d"], "the next batch stays queued");
+        ensure!(
+            submitter.last_failure.is_none(),
+            "a rejection does not back off"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn transient_failure_keeps_the_queue() -> Result<()> {
+        let (mut submitter, _dir) = submitter_with(&["a", "b"])?;
+        submitter.flush_with(2, |_| Err(Request("HTTP 503".to_string())));
+        ensure!(titles(&submitter) == ["a", "b"], "nothing is dropped");
+        ensure!(submitter.last_failure.is_some(), "the retry backs off");
+
+        let mut attempts = 0;
+        submitter.flush_with(2, |listens| {
+            attempts += 1;
+            Ok(listens.len())
+        });
+        ensure!(attempts == 0, "no retry before the backoff expires");
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+//! Plain-text "now playing" line for pasting into chats.
+//!
+//! A template holds `{token}` placeholders that are replaced with the
+//! playing track's details. Unknown tokens are kept as written so a typo
+//! shows up in the copied text instead of vanishing.
+
+use crate::storage::format_sample_rate_str;
+
+/// Template used until the user sets their own.
+pub const DEFAULT_SHARE_TEMPLATE: &str =
+    "{artist} \u{2014} {album} \u{2014} {title} ({format} {resolution})";
+
+/// Tokens understood by [`render_share_text`], for the preferences hint.
+pub const SHARE_TOKENS: [&str; 7] = [
+    "artist",
+    "album",
+    "title",
+    "format",
+    "bit_depth",
+    "sample_rate",
+    "resolution",
+];
+
+/// Details of the playing track that a template can refer to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShareFields {
+    /// Track artist name.
+    pub artist: String,
+    /// Album title.
+    pub album: String,
+    /// Track title.
+    pub title: String,
+    /// Container or codec name (e.g. "FLAC").
+    pub format: String,
+    /// Bit depth (`None` for lossy formats).
+    pub bit_depth: Option<i32>,
+    /// Sample rate in Hz.
+    pub sample_rate: i32,
+}
+
+impl ShareFields {
+    /// Value substituted for `token`, or `None` if the token is unknown.
+    fn value(&self, token: &str) -> Option<String> {
+        let rate = format_sample_rate_str(self.sample_rate);
+        let value = match token {
+            "artist" => self.artist.clone(),
+            "album" => self.album.clone(),
+            "title" => self.title.clone(),
+            "format" => self.format.clone(),
+            "bit_depth" => self.bit_depth.map(|d| d.to_string()).unwrap_or_default(),
+            "sample_rate" => rate,
+            "resolution" => self
+                .bit_depth
+                .map_or_else(|| format!("{rate} kHz"), |depth| format!("{depth}/{rate}")),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+/// Fill the `{token}` placeholders of `template` from `fields`.
+///
+/// # Arguments
+///
+/// * `template` - Text with `{token}` placeholders
+/// * `fields` - Details of the playing track
+///
+/// # Returns
+///
+/// The rendered text, with surrounding whitespace trimmed.
+#[must_use]
+pub fn render_share_text(template: &str, fields: &ShareFields) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+        let token = &after[..end];
+        match fields.value(token.trim()) {
+            Some(value) => out.push_str(&value),
+            None => {
+                out.push('{');
+                out.push_str(token);
+                out.push('}');
+            }
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    out.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::library::share::{DEFAULT_SHARE_TEMPLATE, ShareFields, render_share_text};
+
+    fn fields(bit_depth: Option<i32>, sample_rate: i32) -> ShareFields {
+        ShareFields {
+            artist: "Miles Davis".to_string(),
+            album: "Kind of Blue".to_string(),
+            title: "So What".to_string(),
+            format: "FLAC".to_string(),
+            bit_depth,
+            sample_rate,
+        }
+    }
+
+    #[test]
+    fn default_template_names_resolution() {
+        assert_eq!(
+            render_share_text(DEFAULT_SHARE_TEMPLATE, &fields(Some(24), 96_000)),
+            "Miles Davis \u{2014} Kind of Blue \u{2014} So What (FLAC 24/96)"
+        );
+        assert_eq!(
+            render_share_text("{format} {resolution}", &fields(None, 44_100)),
+            "FLAC 44.1 kHz",
+            "lossy tracks show only the rate"
+        );
+    }
+
+    #[test]
+    fn unknown_and_unclosed_tokens_are_kept() {
+        let text = render_share_text("{title} {mood} {artist", &fields(Some(16), 44_100));
+        assert_eq!(text, "So What {mood} {artist");
+    }
+
+    #[test]
+    fn single_tokens_render_bare_values() {
+        let text = render_share_text(
+            "{bit_depth}-bit {sample_rate} kHz",
+            &fields(Some(24), 192_000),
+        );
+        assert_eq!(text, "24-bit 192 kHz");
+    }
+}
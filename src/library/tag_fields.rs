@@ -0,0 +1,280 @@
+//! Mapping of a file's tags to the descriptive fields of
+//! [`AudioMetadata`](crate::library::metadata::AudioMetadata).
+//!
+//! lofty maps the keys of each container, such as the MP4 atoms `©ART`,
+//! `aART`, `©alb` and `©day` or Vorbis comments, to common [`ItemKey`]s.
+//! Taggers still disagree on which key to write, so every field tries a
+//! list of keys, e.g. `DATE` before `YEAR` or `ARTISTS` when `ARTIST` is
+//! missing. All tags of the file are searched, not only the primary one,
+//! and blank values are skipped, so an empty `ARTIST` in one tag does not
+//! hide the name stored in another. Positions written as `3/12` give both
+//! the number and the total.
+
+use lofty::{
+    file::{TaggedFile, TaggedFileExt},
+    tag::{
+        ItemKey::{
+            self, AlbumArtist, AlbumArtists, AlbumTitle, DiscNumber, DiscTotal, FlagCompilation,
+            Genre, OriginalReleaseDate, RecordingDate, ReleaseDate, TrackArtist, TrackArtists,
+            TrackNumber, TrackTitle, Year,
+        },
+        Tag,
+    },
+};
+
+use crate::library::metadata::normalize_genre;
+
+/// Keys holding the track artist, in order of preference.
+const ARTIST_KEYS: &[ItemKey] = &[TrackArtist, TrackArtists];
+
+/// Keys holding the album artist, in order of preference.
+const ALBUM_ARTIST_KEYS: &[ItemKey] = &[AlbumArtist, AlbumArtists];
+
+/// Keys holding the release date of this edition, in order of preference.
+const YEAR_KEYS: &[ItemKey] = &[RecordingDate, Year, ReleaseDate];
+
+/// Descriptive fields read from the tags of one file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagFields {
+    /// Track title.
+    pub title: Option<String>,
+    /// Track artist.
+    pub artist: Option<String>,
+    /// Album artist.
+    pub album_artist: Option<String>,
+    /// Whether the track is tagged as part of a compilation.
+    pub compilation: bool,
+    /// Album title.
+    pub album: Option<String>,
+    /// Release year of this edition.
+    pub year: Option<i32>,
+    /// Year the recording was first released.
+    pub original_year: Option<i32>,
+    /// Normalized genre list.
+    pub genre: Option<String>,
+    /// Track number within the disc.
+    pub track_number: Option<i32>,
+    /// Disc number.
+    pub disc_number: Option<i32>,
+    /// Number of discs in the release.
+    pub disc_total: Option<i32>,
+}
+
+impl TagFields {
+    /// Read the fields from `tags`, earlier tags taking precedence.
+    #[must_use]
+    pub fn read(tags: &[&Tag]) -> Self {
+        let disc = texts(tags, &[DiscNumber]).find_map(parse_position);
+        Self {
+            title: first_text(tags, &[TrackTitle]),
+            artist: first_text(tags, ARTIST_KEYS),
+            album_artist: first_text(tags, ALBUM_ARTIST_KEYS),
+            compilation: texts(tags, &[FlagCompilation])
+                .next()
+                .is_some_and(|flag| flag == "1" || flag.eq_ignore_ascii_case("true")),
+            album: first_text(tags, &[AlbumTitle]),
+            year: texts(tags, YEAR_KEYS).find_map(parse_year),
+            original_year: texts(tags, &[OriginalReleaseDate]).find_map(parse_year),
+            genre: tags.iter().find_map(|tag| {
+                let values: Vec<&str> = tag.get_strings(Genre).collect();
+                normalize_genre(&values.join(";"))
+            }),
+            track_number: texts(tags, &[TrackNumber])
+                .find_map(parse_position)
+                .map(|(number, _)| number),
+            disc_number: disc.map(|(number, _)| number),
+            disc_total: texts(tags, &[DiscTotal])
+                .find_map(|text| parse_position(text).map(|(number, _)| number))
+                .or_else(|| disc?.1),
+        }
+    }
+}
+
+/// Tags of a file, primary tag first.
+#[must_use]
+pub fn file_tags(tagged_file: &TaggedFile) -> Vec<&Tag> {
+    let primary = tagged_file.primary_tag();
+    primary
+        .into_iter()
+        .chain(
+            tagged_file
+                .tags()
+                .iter()
+                .filter(|tag| primary.is_none_or(|p| p.tag_type() != tag.tag_type())),
+        )
+        .collect()
+}
+
+/// Non-blank values stored under `keys`, trimmed, by key preference and
+/// then tag order.
+fn texts<'a>(tags: &'a [&'a Tag], keys: &'a [ItemKey]) -> impl Iterator<Item = &'a str> {
+    keys.iter()
+        .flat_map(move |&key| tags.iter().flat_map(move |tag| tag.get_strings(key)))
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+}
+
+/// First non-blank value stored under `keys`.
+fn first_text(tags: &[&Tag], keys: &[ItemKey]) -> Option<String> {
+    texts(tags, keys).next().map(String::from)
+}
+
+/// Parse a position such as `3`, `03` or `3/12` into number and total.
+fn parse_position(text: &str) -> Option<(i32, Option<i32>)> {
+    let (number, total) = text.split_once('/').unwrap_or((text, ""));
+    let number = number.trim().parse().ok().filter(|n| *n > 0)?;
+    Some((number, total.trim().parse().ok().filter(|t| *t > 0)))
+}
+
+/// Parse the year of a date.
+///
+/// Takes a plain integer, or else the first run of four digits, which
+/// covers full dates like `2017-03-10`, MP4 timestamps like
+/// `2017-03-10T07:00:00Z` and ranges like `2017–2019`.
+fn parse_year(text: &str) -> Option<i32> {
+    if let Ok(year) = text.parse::<i32>() {
+        return Some(year);
+    }
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .windows(4)
+        .find(|w| w.iter().all(char::is_ascii_digit))
+        .and_then(|w| w.iter().collect::<String>().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use lofty::{
+        mp4::{Atom, AtomData, AtomIdent, Ilst},
+        ogg::VorbisComments,
+        prelude::Accessor,
+        tag::{
+            ItemKey::{AlbumArtist, TrackArtist},
+            Tag, TagType,
+        },
+    };
+
+    use crate::library::tag_fields::{TagFields, parse_position, parse_year};
+
+    /// MP4 item list as iTunes writes it, with an MP4 timestamp date.
+    fn itunes_ilst() -> Ilst {
+        let mut ilst = Ilst::new();
+        for (ident, value) in [
+            (*b"\xa9nam", "Intro"),
+            (*b"\xa9ART", "Track Artist"),
+            (*b"aART", "Album Artist"),
+            (*b"\xa9alb", "Album"),
+            (*b"\xa9day", "2019-03-10T07:00:00Z"),
+            (*b"\xa9gen", "Jazz/Soul"),
+        ] {
+            ilst.insert(Atom::new(
+                AtomIdent::Fourcc(ident),
+                AtomData::UTF8(value.to_string()),
+            ));
+        }
+        ilst.set_track(4);
+        ilst.set_disk(2);
+        ilst.set_disk_total(3);
+        ilst
+    }
+
+    /// Vorbis comments with a `YEAR` instead of a `DATE`, a spaced album
+    /// artist key and positions written with their totals.
+    fn vorbis_variants() -> VorbisComments {
+        let mut comments = VorbisComments::new();
+        for (key, value) in [
+            ("TITLE", "Intro"),
+            ("ARTISTS", "Track Artist"),
+            ("ALBUM ARTIST", "Album Artist"),
+            ("ALBUM", "Album"),
+            ("YEAR", "2019"),
+            ("ORIGINALDATE", "1971-05-01"),
+            ("GENRE", "Jazz"),
+            ("GENRE", "Soul"),
+            ("TRACKNUMBER", "04/12"),
+            ("DISCNUMBER", "2/3"),
+            ("COMPILATION", "1"),
+        ] {
+            comments.push(key.to_string(), value.to_string());
+        }
+        comments
+    }
+
+    #[test]
+    fn mp4_atoms_map_to_fields() {
+        let tag = Tag::from(itunes_ilst());
+        let fields = TagFields::read(&[&tag]);
+        assert_eq!(fields.title.as_deref(), Some("Intro"));
+        assert_eq!(fields.artist.as_deref(), Some("Track Artist"), "©ART");
+        assert_eq!(fields.album_artist.as_deref(), Some("Album Artist"), "aART");
+        assert_eq!(fields.album.as_deref(), Some("Album"), "©alb");
+        assert_eq!(fields.year, Some(2019), "©day timestamp");
+        assert_eq!(fields.genre.as_deref(), Some("Jazz; Soul"));
+        assert_eq!(fields.track_number, Some(4));
+        assert_eq!((fields.disc_number, fields.disc_total), (Some(2), Some(3)));
+        assert!(!fields.compilation);
+    }
+
+    #[test]
+    fn vorbis_comment_variants_map_to_fields() {
+        let tag = Tag::from(vorbis_variants());
+        let fields = TagFields::read(&[&tag]);
+        assert_eq!(fields.artist.as_deref(), Some("Track Artist"), "ARTISTS");
+        assert_eq!(
+            fields.album_artist.as_deref(),
+            Some("Album Artist"),
+            "ALBUM ARTIST"
+        );
+        assert_eq!(fields.year, Some(2019), "YEAR without DATE");
+        assert_eq!(fields.original_year, Some(1971));
+        assert_eq!(
+            fields.genre.as_deref(),
+            Some("Jazz; Soul"),
+            "repeated GENRE"
+        );
+        assert_eq!(fields.track_number, Some(4), "TRACKNUMBER=04/12");
+        assert_eq!(
+            (fields.disc_number, fields.disc_total),
+            (Some(2), Some(3)),
+            "disc total taken from DISCNUMBER=2/3"
+        );
+        assert!(fields.compilation);
+    }
+
+    #[test]
+    fn blank_values_fall_back_to_other_tags() {
+        let mut primary = Tag::new(TagType::Id3v2);
+        primary.insert_text(TrackArtist, "  ".to_string());
+        primary.set_album("Primary Album".to_string());
+        let mut secondary = Tag::new(TagType::Ape);
+        secondary.insert_text(TrackArtist, "Fallback Artist".to_string());
+        secondary.insert_text(AlbumArtist, "Fallback Album Artist".to_string());
+        secondary.set_album("Secondary Album".to_string());
+
+        let fields = TagFields::read(&[&primary, &secondary]);
+        assert_eq!(fields.artist.as_deref(), Some("Fallback Artist"));
+        assert_eq!(
+            fields.album_artist.as_deref(),
+            Some("Fallback Album Artist")
+        );
+        assert_eq!(
+            fields.album.as_deref(),
+            Some("Primary Album"),
+            "earlier tags take precedence"
+        );
+        assert_eq!(TagFields::read(&[]), TagFields::default());
+    }
+
+    #[test]
+    fn positions_and_years_are_parsed_leniently() {
+        assert_eq!(parse_position("3"), Some((3, None)));
+        assert_eq!(parse_position(" 03 / 12 "), Some((3, Some(12))));
+        assert_eq!(parse_position("3/"), Some((3, None)));
+        assert_eq!(parse_position("0"), None, "zero is no position");
+        assert_eq!(parse_position("A1"), None, "vinyl sides are not numbers");
+        assert_eq!(parse_year("2017"), Some(2017));
+        assert_eq!(parse_year("2017-03-10"), Some(2017));
+        assert_eq!(parse_year("2017\u{2013}2019"), Some(2017));
+        assert_eq!(parse_year("unknown"), None);
+    }
+}
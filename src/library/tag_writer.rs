@@ -0,0 +1,341 @@
+//! Writing edited tags back to audio files and reflecting them in storage.
+//!
+//! Only FLAC, MP3, Ogg Vorbis and Opus files are written. Every file of a
+//! batch is checked for write access before any of them is touched, so a
+//! read-only album is rejected as a whole instead of being half edited.
+
+use std::{
+    fs::metadata,
+    io::{Error as IoError, ErrorKind::PermissionDenied},
+    path::{Path, PathBuf},
+};
+
+use {
+    lofty::{
+        config::WriteOptions,
+        error::{ErrorKind, LoftyError},
+        file::{
+            FileType::{self, Flac, Mpeg, Opus, Vorbis},
+            TaggedFileExt,
+        },
+        prelude::{Accessor, TagExt},
+        read_from_path,
+        tag::{
+            ItemKey::{AlbumArtist, RecordingDate},
+            Tag,
+        },
+    },
+    thiserror::Error,
+};
+
+use crate::{
+    library::metadata::normalize_genre,
+    storage::{
+        Album, AlbumUpdate,
+        FieldUpdate::{Set, Skip},
+        NewArtist, Storage, StorageResult,
+    },
+};
+
+/// Tag fields to change. `None` leaves the existing value untouched.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagChanges {
+    /// New track title.
+    pub title: Option<String>,
+    /// New track artist.
+    pub artist: Option<String>,
+    /// New album artist.
+    pub album_artist: Option<String>,
+    /// New album title.
+    pub album: Option<String>,
+    /// New release year.
+    pub year: Option<i32>,
+    /// New genre.
+    pub genre: Option<String>,
+    /// New track number.
+    pub track_number: Option<u32>,
+}
+
+impl TagChanges {
+    /// Whether no field would be changed.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.artist.is_none()
+            && self.album_artist.is_none()
+            && self.album.is_none()
+            && self.year.is_none()
+            && self.genre.is_none()
+            && self.track_number.is_none()
+    }
+
+    /// Apply the changes to a tag in memory.
+    fn apply(&self, tag: &mut Tag) {
+        if let Some(title) = &self.title {
+            tag.set_title(title.clone());
+        }
+        if let Some(artist) = &self.artist {
+            tag.set_artist(artist.clone());
+        }
+        if let Some(album_artist) = &self.album_artist {
+            tag.insert_text(AlbumArtist, album_artist.clone());
+        }
+        if let Some(album) = &self.album {
+            tag.set_album(album.clone());
+        }
+        if let Some(year) = self.year {
+            let date = with_year(tag.get_string(RecordingDate), year);
+            tag.insert_text(RecordingDate, date);
+        }
+        if let Some(genre) = &self.genre {
+            tag.set_genre(genre.clone());
+        }
+        if let Some(number) = self.track_number {
+            tag.set_track(number);
+        }
+    }
+}
+
+/// Errors that can occur while writing tags.
+#[derive(Debug, Error)]
+pub enum TagWriteError {
+    /// The file cannot be modified.
+    #[error("File is read-only: {0}")]
+    ReadOnly(PathBuf),
+    /// The file format is not supported for writing.
+    #[error("Tag writing is not supported for {0:?} files")]
+    UnsupportedFormat(FileType),
+    /// The file could not be inspected.
+    #[error("Cannot access file: {0}")]
+    Io(#[from] IoError),
+    /// Reading or saving the tag failed.
+    #[error("Failed to write tags: {0}")]
+    Tag(#[from] LoftyError),
+}
+
+/// Replace the year of the recording date `existing` with `year`.
+///
+/// A full date such as `1977-02-04` keeps its month and day; anything that
+/// does not start with a four-digit year is replaced by the year alone.
+fn with_year(existing: Option<&str>, year: i32) -> String {
+    let rest = existing
+        .and_then(|date| {
+            date.get(..4)
+                .filter(|prefix| prefix.bytes().all(|b| b.is_ascii_digit()))
+                .and(date.get(4..))
+        })
+        .unwrap_or_default();
+    format!("{year:04}{rest}")
+}
+
+/// Check that `path` can be modified.
+///
+/// # Errors
+///
+/// Returns [`TagWriteError::ReadOnly`] for read-only files, or
+/// [`TagWriteError::Io`] if the file cannot be inspected.
+pub fn check_writable(path: &Path) -> Result<(), TagWriteError> {
+    if metadata(path)?.permissions().readonly() {
+        return Err(TagWriteError::ReadOnly(path.to_path_buf()));
+    }
+    Ok(())
+}
+
+/// Write tag changes to a single audio file.
+///
+/// The file's primary tag is edited in place; files without one get a new
+/// tag of the format's preferred type.
+///
+/// # Arguments
+///
+/// * `path` - Audio file to modify
+/// * `changes` - Fields to set
+///
+/// # Errors
+///
+/// Returns [`TagWriteError`] if the file is read-only, in an unsupported
+/// format, or the tag cannot be read or saved.
+pub fn write_metadata(path: &Path, changes: &TagChanges) -> Result<(), TagWriteError> {
+    check_writable(path)?;
+    let mut tagged_file = read_from_path(path)?;
+    let file_type = tagged_file.file_type();
+    if !matches!(file_type, Flac | Mpeg | Opus | Vorbis) {
+        return Err(TagWriteError::UnsupportedFormat(file_type));
+    }
+
+    let tag_type = tagged_file.primary_tag_type();
+    let result = tagged_file.primary_tag_mut().map_or_else(
+        || {
+            let mut tag = Tag::new(tag_type);
+            changes.apply(&mut tag);
+            tag.save_to_path(path, WriteOptions::default())
+        },
+        |tag| {
+            changes.apply(tag);
+            tag.save_to_path(path, WriteOptions::default())
+        },
+    );
+    result.map_err(|e| map_save_error(e, path))
+}
+
+/// Write the same tag changes to every file of a batch.
+///
+/// All files are checked for write access first; nothing is written if any
+/// of them is read-only.
+///
+/// # Errors
+///
+/// Returns the first [`TagWriteError`] encountered.
+pub fn write_metadata_batch(paths: &[PathBuf], changes: &TagChanges) -> Result<(), TagWriteError> {
+    paths.iter().try_for_each(|p| check_writable(p))?;
+    paths.iter().try_for_each(|p| write_metadata(p, changes))
+}
+
+/// Reflect album-level tag changes in storage.
+///
+/// Album title, year and genre are updated on the album row. A new album
+/// artist is matched case-insensitively against existing artists and
+/// created if missing.
+///
+/// # Errors
+///
+/// Returns a storage error if any lookup or update fails.
+pub async fn apply_album_changes<S: Storage>(
+    storage: &S,
+    album: &Album,
+    changes: &TagChanges,
+) -> StorageResult<()> {
+    let artist_id = match &changes.album_artist {
+        Some(name) => Some(resolve_artist_id(storage, name).await?),
+        None => None,
+    };
+    storage
+        .update_album(
+            album.id,
+            AlbumUpdate {
+                title: changes.album.clone(),
+                artist_id,
+                year: changes.year.map_or(Skip, Set),
+                genre: changes
+                    .genre
+                    .as_deref()
+                    .and_then(normalize_genre)
+                    .map_or(Skip, Set),
+            },
+        )
+        .await
+}
+
+/// Find an artist by name, case-insensitively, or insert it.
+///
+/// # Errors
+///
+/// Returns a storage error if the lookup or insert fails.
+async fn resolve_artist_id<S: Storage>(storage: &S, name: &str) -> StorageResult<i64> {
+    let key = name.to_lowercase();
+    let artists = storage.get_all_artists().await?;
+    if let Some(artist) = artists.iter().find(|a| a.name.to_lowercase() == key) {
+        return Ok(artist.id);
+    }
+    storage
+        .insert_artist(NewArtist {
+            name: name.to_string(),
+        })
+        .await
+}
+
+/// Map a save failure caused by missing permissions to [`TagWriteError::ReadOnly`].
+fn map_save_error(e: LoftyError, path: &Path) -> TagWriteError {
+    match e.kind() {
+        ErrorKind::Io(io) if io.kind() == PermissionDenied => {
+            TagWriteError::ReadOnly(path.to_path_buf())
+        }
+        _ => TagWriteError::Tag(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{metadata, read, set_permissions, write};
+
+    use {
+        anyhow::{Result, ensure},
+        tempfile::tempdir,
+    };
+
+    use crate::library::tag_writer::{
+        TagChanges, TagWriteError, check_writable, with_year, write_metadata, write_metadata_batch,
+    };
+
+    #[test]
+    fn empty_changes_are_detected() {
+        assert!(
+            TagChanges::default().is_empty(),
+            "Default changes must be empty"
+        );
+        let changes = TagChanges {
+            year: Some(1999),
+            ..TagChanges::default()
+        };
+        assert!(!changes.is_empty(), "A set year is a change");
+    }
+
+    #[test]
+    fn year_edit_keeps_month_and_day() {
+        assert_eq!(
+            with_year(Some("1977-02-04"), 1987),
+            "1987-02-04",
+            "full date"
+        );
+        assert_eq!(with_year(Some("1977"), 1987), "1987", "year only");
+        assert_eq!(with_year(Some("Feb 1977"), 1987), "1987", "unparsed date");
+        assert_eq!(with_year(None, 1987), "1987", "no date");
+    }
+
+    #[test]
+    fn read_only_batch_is_rejected_before_writing() -> Result<()> {
+        let dir = tempdir()?;
+        let writable = dir.path().join("a.flac");
+        let read_only = dir.path().join("b.flac");
+        write(&writable, b"not audio")?;
+        write(&read_only, b"not audio")?;
+        let mut permissions = metadata(&read_only)?.permissions();
+        permissions.set_readonly(true);
+        set_permissions(&read_only, permissions)?;
+
+        ensure!(
+            matches!(check_writable(&writable), Ok(())),
+            "writable file must pass the check"
+        );
+        let changes = TagChanges {
+            album_artist: Some("Various Artists".to_string()),
+            ..TagChanges::default()
+        };
+        let result = write_metadata_batch(&[writable.clone(), read_only.clone()], &changes);
+        ensure!(
+            matches!(&result, Err(TagWriteError::ReadOnly(p)) if *p == read_only),
+            "batch must fail on the read-only file"
+        );
+        ensure!(
+            read(&writable)? == b"not audio",
+            "no file may be modified when the batch is rejected"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unreadable_file_reports_tag_error() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("broken.flac");
+        write(&path, b"not audio")?;
+
+        ensure!(
+            matches!(
+                write_metadata(&path, &TagChanges::default()),
+                Err(TagWriteError::Tag(_))
+            ),
+            "garbage input must surface the tag library error"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+//! Disk cache locations for pre-scaled cover thumbnails.
+//!
+//! Thumbnails are stored as PNG files named by a SHA-256 hash of the
+//! source image's path, length and modification time plus the target size.
+//! Only the file's metadata is read, so looking up a thumbnail stays cheap.
+//! Editing or replacing the source changes its length or mtime and
+//! therefore the key, so stale thumbnails are never served.
+
+use std::{
+    fs::{create_dir_all, metadata},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use {
+    sha2::{Digest, Sha256},
+    tracing::warn,
+};
+
+use crate::library::cache::{THUMBNAIL_CACHE_DIR, cache_subdir};
+
+/// Compute the cache key for a source image at a given size.
+///
+/// # Arguments
+///
+/// * `source` - Path of the source image
+/// * `len` - Length of the source image in bytes
+/// * `modified` - Modification time of the source image
+/// * `size` - Target width and height in pixels
+#[must_use]
+pub fn thumbnail_key(source: &Path, len: u64, modified: SystemTime, size: i32) -> String {
+    let mtime = modified
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    let mut hasher = Sha256::new();
+    hasher.update(source.to_string_lossy().as_bytes());
+    hasher.update(len.to_le_bytes());
+    hasher.update(mtime.to_le_bytes());
+    hasher.update(size.to_le_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Return the thumbnail path for `source` at `size`.
+///
+/// The file may not exist yet. Returns `None` if the source cannot be
+/// inspected or the cache directory cannot be created.
+#[must_use]
+pub fn thumbnail_path(source: &Path, size: i32) -> Option<PathBuf> {
+    let cache_dir = match cache_subdir(THUMBNAIL_CACHE_DIR) {
+        Ok(dir) => dir,
+        Err(e) => {
+            warn!(error = %e, "Cannot resolve cache directory for thumbnails");
+            return None;
+        }
+    };
+    if let Err(e) = create_dir_all(&cache_dir) {
+        warn!(error = %e, path = %cache_dir.display(), "Cannot create thumbnail cache dir");
+        return None;
+    }
+    thumbnail_path_in(&cache_dir, source, size)
+}
+
+/// Return the thumbnail path for `source` inside `cache_dir`.
+fn thumbnail_path_in(cache_dir: &Path, source: &Path, size: i32) -> Option<PathBuf> {
+    let (len, modified) = match metadata(source).and_then(|m| Ok((m.len(), m.modified()?))) {
+        Ok(identity) => identity,
+        Err(e) => {
+            warn!(error = %e, path = %source.display(), "Cannot read cover metadata");
+            return None;
+        }
+    };
+    let key = thumbnail_key(source, len, modified, size);
+    Some(cache_dir.join(format!("{key}.png")))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::Path,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    use {
+        anyhow::{Result, ensure},
+        tempfile::{NamedTempFile, tempdir},
+    };
+
+    use crate::library::thumbnail::{thumbnail_key, thumbnail_path_in};
+
+    #[test]
+    fn key_changes_with_path_length_mtime_and_size() {
+        let source = Path::new("/music/Album/cover.jpg");
+        let before = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let after = before + Duration::from_secs(1);
+
+        let key = thumbnail_key(source, 4096, before, 256);
+        assert_eq!(
+            key,
+            thumbnail_key(source, 4096, before, 256),
+            "Key must be stable for unchanged input"
+        );
+        assert_ne!(
+            key,
+            thumbnail_key(source, 4096, after, 256),
+            "Key must change with mtime"
+        );
+        assert_ne!(
+            key,
+            thumbnail_key(source, 4096, before, 128),
+            "Key must change with size"
+        );
+        assert_ne!(
+            key,
+            thumbnail_key(source, 4097, before, 256),
+            "Key must change with length"
+        );
+        assert_ne!(
+            key,
+            thumbnail_key(Path::new("/music/Other/cover.jpg"), 4096, before, 256),
+            "Key must change with path"
+        );
+    }
+
+    #[test]
+    fn thumbnail_path_in_requires_source() -> Result<()> {
+        let dir = tempdir()?;
+        let source = NamedTempFile::new()?;
+
+        let path = thumbnail_path_in(dir.path(), source.path(), 256);
+        ensure!(
+            path.is_some_and(|p| p.starts_with(dir.path())),
+            "existing source must map into the cache dir"
+        );
+        ensure!(
+            thumbnail_path_in(dir.path(), Path::new("/nonexistent/cover.jpg"), 256).is_none(),
+            "missing source must have no thumbnail path"
+        );
+        Ok(())
+    }
+}
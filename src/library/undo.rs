@@ -0,0 +1,146 @@
+//! Session undo for destructive library actions.
+//!
+//! Removing missing files and removing a library directory snapshot the
+//! affected rows first and push the snapshot here. Each push is offered to
+//! the user as a toast with an "Undo" button; undoing restores the most
+//! recent snapshot. The stack lives in memory only, so nothing can be
+//! undone after a restart.
+
+use {
+    async_channel::{Receiver, Sender, unbounded},
+    parking_lot::Mutex,
+    tracing::{info, warn},
+};
+
+use crate::storage::{StorageResult, database::SqliteStorage, snapshot::LibrarySnapshot};
+
+/// Number of actions kept for undo; older ones are dropped.
+const UNDO_LIMIT: usize = 10;
+
+/// One undoable action.
+#[derive(Debug, Clone)]
+pub struct UndoEntry {
+    /// What the action did, shown in the toast offering the undo.
+    pub description: String,
+    /// Rows removed by the action.
+    pub snapshot: LibrarySnapshot,
+}
+
+/// Most recent undoable actions of this session.
+pub struct UndoStack {
+    /// Undoable actions, most recent last.
+    entries: Mutex<Vec<UndoEntry>>,
+    /// Announces each pushed action so the window can offer its undo.
+    offered_tx: Sender<String>,
+    /// Receiving end of `offered_tx`.
+    offered_rx: Receiver<String>,
+}
+
+impl UndoStack {
+    /// Create an empty stack.
+    #[must_use]
+    pub fn new() -> Self {
+        let (offered_tx, offered_rx) = unbounded();
+        Self {
+            entries: Mutex::new(Vec::new()),
+            offered_tx,
+            offered_rx,
+        }
+    }
+
+    /// Record an action and offer to undo it.
+    ///
+    /// Actions that removed nothing are not recorded, but their
+    /// description is still announced.
+    ///
+    /// # Arguments
+    ///
+    /// * `description` - What the action did
+    /// * `snapshot` - Rows the action removed
+    pub fn push(&self, description: String, snapshot: LibrarySnapshot) {
+        if !snapshot.is_empty() {
+            let mut entries = self.entries.lock();
+            entries.push(UndoEntry {
+                description: description.clone(),
+                snapshot,
+            });
+            let excess = entries.len().saturating_sub(UNDO_LIMIT);
+            entries.drain(..excess);
+        }
+        if let Err(e) = self.offered_tx.try_send(description) {
+            warn!(error = %e, "Failed to offer undo");
+        }
+    }
+
+    /// Take the most recent action off the stack.
+    pub fn pop(&self) -> Option<UndoEntry> {
+        self.entries.lock().pop()
+    }
+
+    /// Number of actions that can be undone.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    /// Whether there is nothing to undo.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Receiver announcing the description of every pushed action.
+    #[must_use]
+    pub fn offered(&self) -> Receiver<String> {
+        self.offered_rx.clone()
+    }
+
+    /// Restore the rows of the most recent action.
+    ///
+    /// Returns the undone action, or `None` when there is nothing to undo.
+    /// The action stays on the stack when the restore fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rows cannot be restored.
+    pub async fn undo_latest(&self, storage: &SqliteStorage) -> StorageResult<Option<UndoEntry>> {
+        let Some(entry) = self.pop() else {
+            return Ok(None);
+        };
+        match storage.restore_snapshot(&entry.snapshot).await {
+            Ok(restored) => {
+                info!(rows = restored, action = %entry.description, "Undid library action");
+                Ok(Some(entry))
+            }
+            Err(e) => {
+                self.entries.lock().push(entry);
+                Err(e)
+            }
+        }
+    }
+}
+
+impl Default for UndoStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{library::undo::UndoStack, storage::snapshot::LibrarySnapshot};
+
+    #[test]
+    fn empty_snapshots_are_offered_but_not_kept() {
+        let stack = UndoStack::new();
+        let offered = stack.offered();
+        stack.push("Removed 0 tracks".to_string(), LibrarySnapshot::default());
+        assert!(stack.is_empty(), "Nothing was removed, so nothing to undo");
+        assert_eq!(
+            offered.try_recv().ok().as_deref(),
+            Some("Removed 0 tracks"),
+            "The description must still be announced"
+        );
+        assert!(stack.pop().is_none(), "Stack must stay empty");
+    }
+}
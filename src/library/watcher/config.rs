@@ -0,0 +1,89 @@
+//! Debounce and batch limits of the library watcher.
+//!
+//! The limits reach the running watcher through a channel read at the
+//! start of every batch, so a changed configuration applies to the next
+//! batch without recreating the watcher.
+
+use std::{ops::RangeInclusive, time::Duration};
+
+/// Accepted debounce delays in milliseconds.
+pub const DEBOUNCE_MS_RANGE: RangeInclusive<u64> = 50..=10_000;
+
+/// Accepted numbers of events per batch.
+pub const BATCH_SIZE_RANGE: RangeInclusive<usize> = 1..=1000;
+
+/// Debounce and batch limits of the watcher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatcherConfig {
+    /// Quiet period after the last event before a batch is scanned, in milliseconds.
+    pub debounce_ms: u64,
+    /// Most events gathered into one batch.
+    pub max_batch_size: usize,
+}
+
+impl WatcherConfig {
+    /// Limits with each value clamped to its accepted range.
+    #[must_use]
+    pub fn new(debounce_ms: u64, max_batch_size: usize) -> Self {
+        Self {
+            debounce_ms: debounce_ms.clamp(*DEBOUNCE_MS_RANGE.start(), *DEBOUNCE_MS_RANGE.end()),
+            max_batch_size: max_batch_size
+                .clamp(*BATCH_SIZE_RANGE.start(), *BATCH_SIZE_RANGE.end()),
+        }
+    }
+
+    /// Quiet period closing a batch.
+    #[must_use]
+    pub const fn debounce(&self) -> Duration {
+        Duration::from_millis(self.debounce_ms)
+    }
+}
+
+impl Default for WatcherConfig {
+    fn default() -> Self {
+        Self {
+            debounce_ms: 500,
+            max_batch_size: 50,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::library::watcher::config::WatcherConfig;
+
+    #[test]
+    fn debounce_interval_is_reasonable() {
+        let debounce = WatcherConfig::default().debounce();
+        assert!(debounce.as_millis() >= 100, "too short to coalesce a copy");
+        assert!(debounce.as_millis() <= 2000, "too long for live updates");
+    }
+
+    #[test]
+    fn config_is_clamped_to_its_ranges() {
+        assert_eq!(
+            WatcherConfig::new(0, 0),
+            WatcherConfig {
+                debounce_ms: 50,
+                max_batch_size: 1,
+            },
+            "zero raised to the minimum"
+        );
+        assert_eq!(
+            WatcherConfig::new(60_000, 5000),
+            WatcherConfig {
+                debounce_ms: 10_000,
+                max_batch_size: 1000,
+            },
+            "large values capped"
+        );
+        assert_eq!(
+            WatcherConfig::new(2000, 20),
+            WatcherConfig {
+                debounce_ms: 2000,
+                max_batch_size: 20,
+            },
+            "values in range kept"
+        );
+    }
+}
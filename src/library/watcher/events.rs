@@ -0,0 +1,108 @@
+//! Translation of raw notify events into watcher events.
+//!
+//! Backends do not all report changes the same way: `FSEvents` and some
+//! Linux setups deliver coalesced or `Other` events. Events of a kind that
+//! does not say what happened are resolved by checking whether each path
+//! still exists, so edits and deletions are noticed everywhere.
+
+use notify::{
+    Event,
+    EventKind::{Access, Create, Modify, Remove},
+    event::ModifyKind::{Data, Metadata},
+};
+
+use crate::library::watcher::WatcherEvent;
+
+/// Translate a raw notify event into one watcher event per path.
+///
+/// Reads are ignored and removals reported as such. Creations and content
+/// or metadata changes mark the path modified. Every other kind, including
+/// `Any`, `Other` and renames, says too little about what happened, so the
+/// path is checked on disk: it is modified if it still exists and removed
+/// otherwise.
+#[must_use]
+pub fn watcher_events(event: &Event) -> Vec<WatcherEvent> {
+    event
+        .paths
+        .iter()
+        .filter_map(|path| {
+            let path = path.clone();
+            match event.kind {
+                Access(_) => None,
+                Remove(_) => Some(WatcherEvent::FileRemoved { path }),
+                Create(_) | Modify(Data(_) | Metadata(_)) => {
+                    Some(WatcherEvent::DirectoryModified { path })
+                }
+                _ if path.exists() => Some(WatcherEvent::DirectoryModified { path }),
+                _ => Some(WatcherEvent::FileRemoved { path }),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::write;
+
+    use {
+        anyhow::{Result, ensure},
+        notify::{
+            Event,
+            EventKind::{Access, Any, Modify, Other},
+            event::{AccessKind, ModifyKind::Name, RenameMode::Both},
+        },
+        tempfile::tempdir,
+    };
+
+    use crate::library::watcher::{
+        WatcherEvent::{DirectoryModified, FileRemoved},
+        events::watcher_events,
+    };
+
+    #[test]
+    fn unspecific_events_are_resolved_on_disk() -> Result<()> {
+        let dir = tempdir()?;
+        let kept = dir.path().join("kept.flac");
+        write(&kept, b"")?;
+        let gone = dir.path().join("gone.flac");
+
+        for kind in [Other, Any] {
+            let event = Event::new(kind)
+                .add_path(kept.clone())
+                .add_path(gone.clone());
+            ensure!(
+                watcher_events(&event)
+                    == vec![
+                        DirectoryModified { path: kept.clone() },
+                        FileRemoved { path: gone.clone() },
+                    ],
+                "{kind:?} events are checked path by path"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn renames_and_reads_are_classified() -> Result<()> {
+        let dir = tempdir()?;
+        let old = dir.path().join("old.flac");
+        let new = dir.path().join("new.flac");
+        write(&new, b"")?;
+
+        let rename = Event::new(Modify(Name(Both)))
+            .add_path(old.clone())
+            .add_path(new.clone());
+        ensure!(
+            watcher_events(&rename)
+                == vec![
+                    FileRemoved { path: old },
+                    DirectoryModified { path: new.clone() }
+                ],
+            "a rename removes the old path and modifies the new one"
+        );
+
+        let read = Event::new(Access(AccessKind::Any)).add_path(new);
+        ensure!(watcher_events(&read).is_empty(), "reads change nothing");
+        Ok(())
+    }
+}
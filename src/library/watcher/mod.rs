@@ -0,0 +1,349 @@
+//! Filesystem change monitoring using the notify crate.
+//!
+//! Watches configured library directories for changes and triggers incremental
+//! scans when files are added, modified, or removed. File events are filtered
+//! through the scanner's [`AudioExtensions`], so live monitoring and a full
+//! rescan agree on which files belong to the library.
+//!
+//! Events are debounced and scanned in batches: a batch closes once no event
+//! has arrived for the debounce delay or it holds the maximum batch size, and
+//! each affected directory is scanned once. Both limits are set by a
+//! [`WatcherConfig`].
+
+pub mod config;
+pub mod events;
+pub mod watch_limit;
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use {
+    notify::{Config, Error, Event, RecommendedWatcher, RecursiveMode::Recursive, Watcher},
+    tokio::{
+        spawn,
+        sync::{
+            mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel},
+            watch::{Receiver as ConfigReceiver, channel},
+        },
+        time::timeout,
+    },
+    tracing::{error, info, warn},
+};
+
+use crate::{
+    library::{
+        formats::AudioExtensions,
+        scanner::{FsScanner, LibraryScanner},
+        watcher::{
+            config::WatcherConfig,
+            events::watcher_events,
+            watch_limit::{DEFAULT_POLL_INTERVAL, WatcherError, is_watch_limit, poll_directory},
+        },
+    },
+    storage::Storage,
+};
+
+/// Filesystem watcher that monitors library directories for changes.
+pub struct LibraryWatcher<S: Storage> {
+    /// The underlying notify watcher.
+    watcher: RecommendedWatcher,
+    /// Scanner for incremental scans.
+    scanner: Arc<FsScanner<S>>,
+    /// Sender for events, shared with the polling tasks.
+    event_tx: UnboundedSender<WatcherEvent>,
+    /// Interval between rescans of directories that cannot be watched.
+    poll_interval: Duration,
+    /// Debounce and batch limits, read at the start of every batch.
+    config: ConfigReceiver<WatcherConfig>,
+}
+
+impl<S: Storage + 'static> LibraryWatcher<S> {
+    /// Create a new filesystem watcher.
+    ///
+    /// # Arguments
+    ///
+    /// * `scanner` - Scanner to trigger incremental scans
+    ///
+    /// # Returns
+    ///
+    /// A tuple of (watcher, `event_receiver`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the watcher cannot be created.
+    pub fn new(
+        scanner: Arc<FsScanner<S>>,
+    ) -> Result<(Self, UnboundedReceiver<WatcherEvent>), Error> {
+        let (event_tx, event_rx) = unbounded_channel();
+
+        let config = Config::default();
+
+        let cb_tx = event_tx.clone();
+        let watcher = RecommendedWatcher::new(
+            move |result: Result<Event, Error>| {
+                Self::handle_watcher_event(result, &cb_tx);
+            },
+            config,
+        )?;
+
+        Ok((
+            Self {
+                watcher,
+                scanner,
+                event_tx,
+                poll_interval: DEFAULT_POLL_INTERVAL,
+                config: channel(WatcherConfig::default()).1,
+            },
+            event_rx,
+        ))
+    }
+
+    /// Rescan directories that cannot be watched every `poll_interval`.
+    #[must_use]
+    pub const fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Take the debounce and batch limits from `config`.
+    ///
+    /// Values sent on the channel later apply from the next batch.
+    #[must_use]
+    pub fn with_config(mut self, config: ConfigReceiver<WatcherConfig>) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Handle a raw watcher event and forward it through the channel.
+    fn handle_watcher_event(
+        result: Result<Event, Error>,
+        event_tx: &UnboundedSender<WatcherEvent>,
+    ) {
+        let events = match result {
+            Ok(event) => watcher_events(&event),
+            Err(e) => vec![WatcherEvent::Error {
+                error: e.to_string(),
+            }],
+        };
+        for e in events
+            .into_iter()
+            .filter_map(|event| event_tx.send(event).err())
+        {
+            error!(error = %e, "Failed to send watcher event");
+        }
+    }
+
+    /// Start watching the given directories.
+    ///
+    /// A directory failing to be watched does not stop the others from
+    /// being watched.
+    ///
+    /// # Arguments
+    ///
+    /// * `directories` - List of directory paths to watch
+    ///
+    /// # Returns
+    ///
+    /// The errors of the directories that could not be watched.
+    pub fn watch_directories(&mut self, directories: &[PathBuf]) -> Vec<WatcherError> {
+        directories
+            .iter()
+            .filter_map(|dir| self.watch_directory(dir).err())
+            .collect()
+    }
+
+    /// Start watching `dir` recursively.
+    ///
+    /// If the system watch limit is reached, `dir` is rescanned every poll
+    /// interval instead. Must be called from within a Tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WatcherError::WatchLimit`] if the watch limit was reached, or
+    /// [`WatcherError::Watch`] if `dir` cannot be watched otherwise.
+    pub fn watch_directory(&mut self, dir: &Path) -> Result<(), WatcherError> {
+        match self.watcher.watch(dir, Recursive) {
+            Ok(()) => {
+                info!(path = %dir.display(), "Watching directory");
+                Ok(())
+            }
+            Err(e) if is_watch_limit(&e) => {
+                warn!(
+                    error = %e,
+                    path = %dir.display(),
+                    interval_secs = self.poll_interval.as_secs(),
+                    "Watch limit reached, polling directory instead"
+                );
+                spawn(poll_directory(
+                    dir.to_path_buf(),
+                    self.poll_interval,
+                    self.event_tx.clone(),
+                ));
+                Err(WatcherError::WatchLimit {
+                    path: dir.to_path_buf(),
+                    poll_interval_secs: self.poll_interval.as_secs(),
+                })
+            }
+            Err(source) => Err(WatcherError::Watch {
+                path: dir.to_path_buf(),
+                source,
+            }),
+        }
+    }
+
+    /// Stop watching all directories.
+    pub fn stop_watching(&mut self) {
+        if let Err(e) = self.watcher.unwatch(&PathBuf::from("/")) {
+            warn!(error = %e, "Failed to unwatch (expected for non-watched paths)");
+        }
+    }
+
+    /// Gather `first` and the events following it into one batch.
+    ///
+    /// The batch closes once no event has arrived for the debounce delay or
+    /// it holds the maximum batch size. Also closes when `events` does.
+    pub async fn collect_batch(
+        &self,
+        first: WatcherEvent,
+        events: &mut UnboundedReceiver<WatcherEvent>,
+    ) -> Vec<WatcherEvent> {
+        let config = *self.config.borrow();
+        let mut batch = vec![first];
+        while batch.len() < config.max_batch_size {
+            match timeout(config.debounce(), events.recv()).await {
+                Ok(Some(event)) => batch.push(event),
+                Ok(None) | Err(_) => break,
+            }
+        }
+        batch
+    }
+
+    /// Process a batch of watcher events, scanning each affected directory once.
+    ///
+    /// Tracks under removed paths are dropped from the library first. Events
+    /// for files the scanner would not add are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch` - Events gathered by [`Self::collect_batch`]
+    pub async fn process_batch(&self, batch: Vec<WatcherEvent>) {
+        let events = batch.len();
+        let mut removed = Vec::new();
+        let mut targets = Vec::new();
+        for event in batch {
+            match event {
+                WatcherEvent::FileRemoved { path } => removed.push(path),
+                event => targets.extend(self.event_target(event)),
+            }
+        }
+        self.prune_removed(removed).await;
+        targets.sort();
+        targets.dedup();
+        if targets.is_empty() {
+            return;
+        }
+        info!(
+            events,
+            directories = targets.len(),
+            "Directories modified, triggering incremental scan"
+        );
+        for path in &targets {
+            self.scan_modified(path).await;
+        }
+    }
+
+    /// Drop the tracks under `removed` whose files are gone, logging failures.
+    async fn prune_removed(&self, mut removed: Vec<PathBuf>) {
+        if removed.is_empty() {
+            return;
+        }
+        removed.sort();
+        removed.dedup();
+        match self.scanner.prune_removed(&removed).await {
+            Ok(report) if report.tracks_removed > 0 => info!(
+                paths = removed.len(),
+                tracks_removed = report.tracks_removed,
+                "Removed tracks of deleted files"
+            ),
+            Ok(_) => {}
+            Err(e) => error!(error = %e, "Failed to remove tracks of deleted files"),
+        }
+    }
+
+    /// Rescan a modified directory, logging failures.
+    async fn scan_modified(&self, path: &Path) {
+        if let Err(e) = self.scanner.scan_directory(path).await {
+            error!(error = %e, path = %path.display(), "Failed to scan directory");
+        }
+    }
+
+    /// Directory to scan for `event`, logging watcher errors.
+    fn event_target(&self, event: WatcherEvent) -> Option<PathBuf> {
+        match event {
+            WatcherEvent::DirectoryModified { path } => {
+                scan_target(&path, self.scanner.audio_extensions())
+            }
+            WatcherEvent::FileRemoved { .. } => None,
+            WatcherEvent::Error { error } => {
+                error!(error = %error, "Watcher error");
+                None
+            }
+        }
+    }
+}
+
+/// Events emitted by the filesystem watcher.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatcherEvent {
+    /// A directory or a file in it was modified (files added/removed/changed).
+    DirectoryModified {
+        /// Path of the modified directory or file.
+        path: PathBuf,
+    },
+    /// A file or directory was removed (or moved away).
+    FileRemoved {
+        /// Path that no longer exists.
+        path: PathBuf,
+    },
+    /// An error occurred during watching.
+    Error {
+        /// Error message.
+        error: String,
+    },
+}
+
+/// Directory to scan after `path` changed, if any.
+///
+/// A changed directory is scanned itself; a changed audio file with one of
+/// `extensions` has its directory scanned. Other files are ignored.
+#[must_use]
+pub fn scan_target(path: &Path, extensions: &AudioExtensions) -> Option<PathBuf> {
+    if path.is_dir() {
+        return Some(path.to_path_buf());
+    }
+    if !extensions.accepts(path) {
+        return None;
+    }
+    path.parent()
+        .filter(|dir| dir.is_dir())
+        .map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::library::watcher::WatcherEvent::DirectoryModified;
+
+    #[test]
+    fn watcher_event_clone() {
+        let event = DirectoryModified {
+            path: PathBuf::from("/music"),
+        };
+        let cloned = event.clone();
+        assert_eq!(event, cloned);
+    }
+}
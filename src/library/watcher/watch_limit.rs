@@ -0,0 +1,113 @@
+//! Fallback for directories the watcher cannot watch.
+//!
+//! On Linux every watched directory takes one inotify watch. A library larger
+//! than `fs.inotify.max_user_watches` cannot be watched completely; such
+//! directories are reported and rescanned periodically instead.
+
+use std::{path::PathBuf, time::Duration};
+
+use {
+    notify::{Error, ErrorKind},
+    thiserror::Error as ThisError,
+    tokio::{sync::mpsc::UnboundedSender, time::interval},
+    tracing::debug,
+};
+
+use crate::library::watcher::WatcherEvent;
+
+/// Default interval between rescans of directories that cannot be watched.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(300);
+
+/// `ENOSPC`, returned by inotify when the watch limit is exhausted.
+const ENOSPC: i32 = 28;
+
+/// Errors occurring while setting up directory watches.
+#[derive(Debug, ThisError)]
+pub enum WatcherError {
+    /// The system limit on watches was reached; the directory is polled instead.
+    #[error(
+        "Too many folders to watch for changes in {}; raise fs.inotify.max_user_watches. \
+         Checking it every {poll_interval_secs} s instead",
+        path.display()
+    )]
+    WatchLimit {
+        /// Directory that could not be watched completely.
+        path: PathBuf,
+        /// Seconds between rescans of the directory.
+        poll_interval_secs: u64,
+    },
+    /// The directory could not be watched for another reason.
+    #[error("Failed to watch {}: {source}", path.display())]
+    Watch {
+        /// Directory that could not be watched.
+        path: PathBuf,
+        /// Underlying notify error.
+        source: Error,
+    },
+}
+
+/// Whether `error` means the system limit on watches was reached.
+#[must_use]
+pub fn is_watch_limit(error: &Error) -> bool {
+    match &error.kind {
+        ErrorKind::MaxFilesWatch => true,
+        ErrorKind::Io(e) => e.raw_os_error() == Some(ENOSPC),
+        _ => false,
+    }
+}
+
+/// Report `dir` as modified every `period` until the event receiver closes.
+pub async fn poll_directory(
+    dir: PathBuf,
+    period: Duration,
+    event_tx: UnboundedSender<WatcherEvent>,
+) {
+    let mut ticks = interval(period);
+    ticks.tick().await;
+    loop {
+        ticks.tick().await;
+        let event = WatcherEvent::DirectoryModified { path: dir.clone() };
+        if event_tx.send(event).is_err() {
+            debug!(path = %dir.display(), "Watcher closed, stopping directory polling");
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Error as IoError, path::PathBuf};
+
+    use notify::{Error, ErrorKind::MaxFilesWatch};
+
+    use crate::library::watcher::watch_limit::{ENOSPC, WatcherError::WatchLimit, is_watch_limit};
+
+    #[test]
+    fn watch_limit_errors_are_detected() {
+        assert!(
+            is_watch_limit(&Error::new(MaxFilesWatch)),
+            "notify limit kind"
+        );
+        assert!(
+            is_watch_limit(&Error::io(IoError::from_raw_os_error(ENOSPC))),
+            "Raw ENOSPC from inotify"
+        );
+        assert!(
+            !is_watch_limit(&Error::generic("permission denied")),
+            "Other errors are not the limit"
+        );
+    }
+
+    #[test]
+    fn watch_limit_message_names_the_sysctl() {
+        let message = WatchLimit {
+            path: PathBuf::from("/music"),
+            poll_interval_secs: 300,
+        }
+        .to_string();
+        assert!(
+            message.contains("/music") && message.contains("fs.inotify.max_user_watches"),
+            "Message must say what to change: {message}"
+        );
+    }
+}
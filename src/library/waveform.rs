@@ -0,0 +1,286 @@
+//! Peak overviews of tracks for the waveform seek bar, with a disk cache.
+//!
+//! A track is decoded once in full and its loudest sample per short block
+//! is kept, then folded into [`WAVEFORM_BUCKETS`] bars scaled to the
+//! loudest bar. The bars are cached as raw bytes named by the file's
+//! content hash, falling back to its path and modification time for files
+//! scanned without hashing. CUE tracks add their time range to the key.
+
+use std::{
+    fs::{create_dir_all, metadata, read, write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use {
+    num_traits::cast::AsPrimitive,
+    sha2::{Digest, Sha256},
+    tracing::{info, warn},
+};
+
+use crate::{
+    library::{
+        cache::{WAVEFORM_CACHE_DIR, cache_subdir},
+        cue::split_cue_path,
+    },
+    playback::{DecoderError, decoder::Decoder},
+};
+
+/// Number of bars in a waveform overview.
+pub const WAVEFORM_BUCKETS: usize = 480;
+
+/// Peaks are collected per block of this many milliseconds before folding.
+const BLOCK_MS: u32 = 50;
+
+/// Running maximum over blocks of decoded frames.
+struct PeakBlocks {
+    /// Frames per block.
+    block_frames: usize,
+    /// Interleaved channel count of the decoded samples.
+    channels: usize,
+    /// Loudest sample of the unfinished block.
+    current: f32,
+    /// Frames in the unfinished block.
+    len: usize,
+    /// Loudest sample of each finished block.
+    peaks: Vec<f32>,
+}
+
+impl PeakBlocks {
+    /// Blocks of [`BLOCK_MS`] at `sample_rate` over `channels` channels.
+    fn new(sample_rate: u32, channels: u16) -> Self {
+        let block_frames = usize::try_from(sample_rate * BLOCK_MS / 1000).unwrap_or(1);
+        Self {
+            block_frames: block_frames.max(1),
+            channels: usize::from(channels.max(1)),
+            current: 0.0,
+            len: 0,
+            peaks: Vec::new(),
+        }
+    }
+
+    /// Fold one batch of interleaved samples into the blocks.
+    fn add(&mut self, samples: &[f32]) {
+        for frame in samples.chunks(self.channels) {
+            self.add_frame(frame);
+        }
+    }
+
+    /// Fold one frame into the unfinished block, closing it when full.
+    fn add_frame(&mut self, frame: &[f32]) {
+        self.current = frame.iter().fold(self.current, |peak, s| peak.max(s.abs()));
+        self.len += 1;
+        if self.len == self.block_frames {
+            self.peaks.push(self.current);
+            self.current = 0.0;
+            self.len = 0;
+        }
+    }
+
+    /// Block peaks, including the unfinished last block.
+    fn finish(mut self) -> Vec<f32> {
+        if self.len > 0 {
+            self.peaks.push(self.current);
+        }
+        self.peaks
+    }
+}
+
+/// Compute the waveform of a track, reading it from the cache when present.
+///
+/// Decoding a long track takes a while, so call this off the main thread.
+/// Returns `None` if the track cannot be decoded.
+///
+/// # Arguments
+///
+/// * `track_path` - Library path of the track, with a cue range if any
+/// * `content_hash` - Stored content hash of the file, if it was hashed
+#[must_use]
+pub fn load_waveform(track_path: &Path, content_hash: Option<&str>) -> Option<Vec<u8>> {
+    let cached = waveform_path(track_path, content_hash);
+    if let Some(bars) = cached
+        .as_deref()
+        .and_then(|path| read(path).ok())
+        .filter(|bars| bars.len() == WAVEFORM_BUCKETS)
+    {
+        return Some(bars);
+    }
+
+    let bars = match compute_peaks(track_path) {
+        Ok(peaks) => fold_peaks(&peaks, WAVEFORM_BUCKETS),
+        Err(e) => {
+            warn!(error = %e, path = %track_path.display(), "Cannot compute waveform");
+            return None;
+        }
+    };
+    if let Some(path) = cached.filter(|_| !bars.is_empty()) {
+        match write(&path, &bars) {
+            Ok(()) => info!(path = %track_path.display(), "Cached waveform"),
+            Err(e) => warn!(error = %e, path = %path.display(), "Cannot cache waveform"),
+        }
+    }
+    Some(bars)
+}
+
+/// Cache key of a track's waveform.
+///
+/// # Arguments
+///
+/// * `track_path` - Library path of the track, with a cue range if any
+/// * `content_hash` - Stored content hash of the file
+/// * `modified` - Modification time of the file, used without a hash
+#[must_use]
+pub fn waveform_key(
+    track_path: &Path,
+    content_hash: Option<&str>,
+    modified: Option<SystemTime>,
+) -> String {
+    let (file, range) = split_cue_path(track_path);
+    let mut hasher = Sha256::new();
+    if let Some(hash) = content_hash {
+        hasher.update(hash.as_bytes());
+    } else {
+        let mtime = modified
+            .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos());
+        hasher.update(file.to_string_lossy().as_bytes());
+        hasher.update(mtime.to_le_bytes());
+    }
+    if let Some(range) = range {
+        hasher.update(range.start.to_le_bytes());
+        hasher.update(range.end.unwrap_or(-1.0).to_le_bytes());
+    }
+    hasher.update(WAVEFORM_BUCKETS.to_le_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Return the cache file for a track's waveform.
+///
+/// The file may not exist yet. Returns `None` if the cache directory
+/// cannot be created.
+fn waveform_path(track_path: &Path, content_hash: Option<&str>) -> Option<PathBuf> {
+    let cache_dir = match cache_subdir(WAVEFORM_CACHE_DIR) {
+        Ok(dir) => dir,
+        Err(e) => {
+            warn!(error = %e, "Cannot resolve cache directory for waveforms");
+            return None;
+        }
+    };
+    if let Err(e) = create_dir_all(&cache_dir) {
+        warn!(error = %e, path = %cache_dir.display(), "Cannot create waveform cache dir");
+        return None;
+    }
+    let modified = content_hash
+        .is_none()
+        .then(|| {
+            metadata(split_cue_path(track_path).0)
+                .and_then(|m| m.modified())
+                .ok()
+        })
+        .flatten();
+    let key = waveform_key(track_path, content_hash, modified);
+    Some(cache_dir.join(format!("{key}.peaks")))
+}
+
+/// Decode the whole track and return the peak of each block.
+///
+/// # Errors
+///
+/// Returns [`DecoderError`] if the track cannot be opened or decoded.
+fn compute_peaks(track_path: &Path) -> Result<Vec<f32>, DecoderError> {
+    let mut decoder = Decoder::open(track_path)?;
+    let params = decoder.params();
+    let mut blocks = PeakBlocks::new(params.sample_rate, params.channels);
+    loop {
+        let decoded = decoder.decode_next()?;
+        if decoded.samples.is_empty() {
+            return Ok(blocks.finish());
+        }
+        blocks.add(&decoded.samples);
+    }
+}
+
+/// Fold block peaks into `buckets` bars from `0` to `255`.
+///
+/// Each bar is the loudest block it covers, scaled so the loudest bar is
+/// full height; quiet recordings keep a readable shape. Silence and empty
+/// input give flat or no bars.
+fn fold_peaks(peaks: &[f32], buckets: usize) -> Vec<u8> {
+    if peaks.is_empty() || buckets == 0 {
+        return Vec::new();
+    }
+    let bars: Vec<f32> = (0..buckets)
+        .map(|i| {
+            let start = i * peaks.len() / buckets;
+            let end = ((i + 1) * peaks.len() / buckets).max(start + 1);
+            peaks[start..end].iter().fold(0.0_f32, |a, &b| a.max(b))
+        })
+        .collect();
+    let loudest = bars.iter().fold(0.0_f32, |a, &b| a.max(b));
+    if loudest <= 0.0 {
+        return vec![0; buckets];
+    }
+    bars.iter()
+        .map(|bar| (bar / loudest * 255.0).round().as_())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::Path,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    use crate::library::waveform::{PeakBlocks, fold_peaks, waveform_key};
+
+    #[test]
+    fn blocks_keep_the_loudest_sample() {
+        let mut blocks = PeakBlocks::new(40, 2);
+        blocks.add(&[0.1, -0.5, 0.2, 0.3, 0.0, 0.05]);
+        assert_eq!(
+            blocks.finish(),
+            vec![0.5, 0.05],
+            "two frames per block at 40 Hz, last block kept"
+        );
+    }
+
+    #[test]
+    fn peaks_fold_into_scaled_bars() {
+        assert_eq!(
+            fold_peaks(&[0.1, 0.25, 0.5, 0.125], 2),
+            vec![128, 255],
+            "loudest bar is full height"
+        );
+        assert_eq!(
+            fold_peaks(&[0.5], 3),
+            vec![255, 255, 255],
+            "short tracks repeat their peaks"
+        );
+        assert!(fold_peaks(&[], 4).is_empty(), "no audio gives no bars");
+        assert_eq!(fold_peaks(&[0.0, 0.0], 2), vec![0, 0], "silence stays flat");
+    }
+
+    #[test]
+    fn key_follows_hash_or_mtime() {
+        let path = Path::new("/music/album/01.flac");
+        let before = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let after = before + Duration::from_secs(1);
+
+        assert_eq!(
+            waveform_key(path, Some("abc"), Some(before)),
+            waveform_key(Path::new("/moved/01.flac"), Some("abc"), Some(after)),
+            "hashed files keep their waveform when moved or touched"
+        );
+        assert_ne!(
+            waveform_key(path, None, Some(before)),
+            waveform_key(path, None, Some(after)),
+            "unhashed files are keyed by mtime"
+        );
+        assert_ne!(
+            waveform_key(Path::new("/music/image.flac/#t=0,60"), Some("abc"), None),
+            waveform_key(Path::new("/music/image.flac/#t=60,120"), Some("abc"), None),
+            "cue tracks of one image get their own waveform"
+        );
+    }
+}
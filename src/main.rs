@@ -15,9 +15,13 @@ use {
     },
 };
 
-use oxhidifi::app::{dirs_data_home, run_application};
+use oxhidifi::{
+    app::{dirs_data_home, run_application},
+    metrics::diagnostics::LogBuffer,
+};
 
-/// Initialize structured logging to file and stderr.
+/// Initialize structured logging to file, stderr, and the in-memory buffer
+/// included in diagnostics exports.
 ///
 /// Returns a `NonBlocking` guard that must be kept alive for the duration of
 /// the program; dropping it flushes and shuts down the file writer.
@@ -45,12 +49,18 @@ fn init_logging() -> Result<WorkerGuard> {
         .with_target(false)
         .with_thread_ids(false);
 
+    let diagnostics_layer = layer()
+        .with_writer(LogBuffer::session().make_writer())
+        .with_ansi(false)
+        .with_target(true);
+
     let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
 
     registry()
         .with(env_filter)
         .with(file_layer)
         .with(stderr_layer)
+        .with(diagnostics_layer)
         .init();
 
     Ok(guard)
@@ -0,0 +1,285 @@
+//! Diagnostics export for bug reports.
+//!
+//! A bounded in-memory [`LogBuffer`] keeps the most recent `tracing` output
+//! of the session. It is fed by a `fmt` layer installed next to the file and
+//! stderr layers, through the [`MakeWriter`] returned by
+//! [`LogBuffer::make_writer`]. A [`DiagnosticsReport`] puts the log together
+//! with the settings, secrets redacted, and whatever sections the caller
+//! adds, such as library counts and the audio output, into one text file
+//! that can be attached to a bug report.
+
+use std::{
+    collections::VecDeque,
+    env::consts::{ARCH, OS},
+    io::{Result as IoResult, Write},
+    mem::take,
+    sync::LazyLock,
+};
+
+use {
+    parking_lot::Mutex,
+    serde_json::{Value, to_string_pretty, to_value},
+    tracing_subscriber::fmt::MakeWriter,
+};
+
+use crate::storage::{settings::UserSettings, settings_version::SETTINGS_VERSION};
+
+/// Number of log lines kept for the diagnostics export.
+const LOG_BUFFER_LINES: usize = 2000;
+
+/// Replacement for secret setting values.
+const REDACTED: &str = "[redacted]";
+
+/// Setting key fragments marking a value as secret.
+const SECRET_KEY_PARTS: [&str; 4] = ["token", "secret", "key", "password"];
+
+/// Log buffer shared by the logging layer and the diagnostics export.
+static SESSION_LOG: LazyLock<LogBuffer> = LazyLock::new(|| LogBuffer::new(LOG_BUFFER_LINES));
+
+/// Text report assembled from titled sections.
+pub struct DiagnosticsReport {
+    /// Section titles and bodies, in output order.
+    sections: Vec<(String, String)>,
+}
+
+impl DiagnosticsReport {
+    /// Start a report with the application version and platform.
+    #[must_use]
+    pub fn new() -> Self {
+        let mut report = Self {
+            sections: Vec::new(),
+        };
+        report.add_section(
+            "Application",
+            format!(
+                "Version: {}\nPlatform: {OS} ({ARCH})",
+                env!("CARGO_PKG_VERSION")
+            ),
+        );
+        report
+    }
+
+    /// Append a section.
+    pub fn add_section(&mut self, title: &str, body: String) {
+        self.sections.push((title.to_string(), body));
+    }
+
+    /// Append the settings with secret values redacted.
+    pub fn add_settings(&mut self, settings: &UserSettings) {
+        let body = to_value(settings)
+            .and_then(|mut value| {
+                redact_secrets(&mut value);
+                to_string_pretty(&value)
+            })
+            .unwrap_or_else(|e| format!("Settings could not be serialized: {e}"));
+        self.add_section(
+            "Settings",
+            format!(
+                "Layout version: {} (current {SETTINGS_VERSION})\n{body}",
+                settings.version
+            ),
+        );
+    }
+
+    /// Append the buffered log lines.
+    pub fn add_log(&mut self, buffer: &LogBuffer) {
+        let lines = buffer.lines();
+        let body = if lines.is_empty() {
+            "No log output recorded".to_string()
+        } else {
+            lines.join("\n")
+        };
+        self.add_section("Recent Log", body);
+    }
+
+    /// Render the report as plain text.
+    #[must_use]
+    pub fn render(&self) -> String {
+        self.sections
+            .iter()
+            .map(|(title, body)| format!("== {title} ==\n{body}\n\n"))
+            .collect()
+    }
+}
+
+impl Default for DiagnosticsReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Most recent formatted log lines, oldest first.
+pub struct LogBuffer {
+    /// Buffered lines.
+    lines: Mutex<VecDeque<String>>,
+    /// Maximum number of lines kept.
+    capacity: usize,
+}
+
+impl LogBuffer {
+    /// Create a buffer keeping at most `capacity` lines.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Buffer of the running session, fed by the logging layer.
+    #[must_use]
+    pub fn session() -> &'static Self {
+        &SESSION_LOG
+    }
+
+    /// Append `line`, dropping the oldest line when full.
+    pub fn push(&self, line: String) {
+        let mut lines = self.lines.lock();
+        if lines.len() == self.capacity {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    /// Copy of the buffered lines, oldest first.
+    #[must_use]
+    pub fn lines(&self) -> Vec<String> {
+        self.lines.lock().iter().cloned().collect()
+    }
+
+    /// Writer factory for a `tracing_subscriber` `fmt` layer.
+    #[must_use]
+    pub const fn make_writer(&self) -> LogBufferMakeWriter<'_> {
+        LogBufferMakeWriter { buffer: self }
+    }
+}
+
+/// [`MakeWriter`] handing out writers into a [`LogBuffer`].
+#[derive(Clone, Copy)]
+pub struct LogBufferMakeWriter<'a> {
+    /// Buffer receiving the lines.
+    buffer: &'a LogBuffer,
+}
+
+impl<'a> MakeWriter<'a> for LogBufferMakeWriter<'_> {
+    type Writer = LogBufferWriter<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LogBufferWriter {
+            buffer: self.buffer,
+            pending: Vec::new(),
+        }
+    }
+}
+
+/// Writer collecting one formatted event and pushing its lines on drop.
+pub struct LogBufferWriter<'a> {
+    /// Buffer receiving the lines.
+    buffer: &'a LogBuffer,
+    /// Bytes written since the last flush.
+    pending: Vec<u8>,
+}
+
+impl LogBufferWriter<'_> {
+    /// Push the complete lines written so far into the buffer.
+    fn push_pending(&mut self) {
+        let pending = take(&mut self.pending);
+        String::from_utf8_lossy(&pending)
+            .lines()
+            .filter(|line| !line.is_empty())
+            .for_each(|line| self.buffer.push(line.to_string()));
+    }
+}
+
+impl Drop for LogBufferWriter<'_> {
+    fn drop(&mut self) {
+        self.push_pending();
+    }
+}
+
+impl Write for LogBufferWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> IoResult<()> {
+        self.push_pending();
+        Ok(())
+    }
+}
+
+/// Replace non-empty string values under secret-looking keys.
+pub fn redact_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, entry) in map.iter_mut() {
+                redact_entry(key, entry);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_secrets),
+        _ => {}
+    }
+}
+
+/// Redact `entry` if `key` names a secret, or recurse into it.
+fn redact_entry(key: &str, entry: &mut Value) {
+    let key = key.to_lowercase();
+    let secret = SECRET_KEY_PARTS.iter().any(|part| key.contains(part));
+    match entry {
+        Value::String(text) if secret && !text.is_empty() => *text = REDACTED.to_string(),
+        _ => redact_secrets(entry),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use {
+        anyhow::{Result, ensure},
+        serde_json::json,
+        tracing_subscriber::fmt::MakeWriter,
+    };
+
+    use crate::metrics::diagnostics::{LogBuffer, redact_secrets};
+
+    #[test]
+    fn log_buffer_keeps_the_newest_lines() -> Result<()> {
+        let buffer = LogBuffer::new(2);
+        let make_writer = buffer.make_writer();
+        for event in ["first\n", "second\n", "third\nfourth\n"] {
+            make_writer.make_writer().write_all(event.as_bytes())?;
+        }
+        ensure!(
+            buffer.lines() == ["third", "fourth"],
+            "Oldest lines must be dropped"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn secrets_are_redacted() {
+        let mut settings = json!({
+            "volume": 0.5,
+            "scrobble": {
+                "listenbrainz_token": "abc",
+                "lastfm_api_key": "",
+                "lastfm_session_key": "xyz",
+            },
+        });
+        redact_secrets(&mut settings);
+        assert_eq!(
+            settings,
+            json!({
+                "volume": 0.5,
+                "scrobble": {
+                    "listenbrainz_token": "[redacted]",
+                    "lastfm_api_key": "",
+                    "lastfm_session_key": "[redacted]",
+                },
+            }),
+            "Only non-empty secret values must be replaced"
+        );
+    }
+}
@@ -1,3 +1,4 @@
 //! Performance metrics and observability.
 
 pub mod collector;
+pub mod diagnostics;
@@ -0,0 +1,65 @@
+//! A-B repeat of a section of the current track.
+//!
+//! The decode loop cuts each batch at the loop end point and seeks back to
+//! the start point within the open stream, so the jump back is seamless.
+
+use {num_traits::NumCast, tracing::warn};
+
+use crate::playback::{
+    engine::{EngineShared, PlaybackEvent::Seeked},
+    pipeline::LoopCtx,
+};
+
+/// A section of one track played repeatedly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AbLoop {
+    /// Track the loop belongs to; it stops applying once another track plays.
+    pub track_id: i64,
+    /// Loop start (point A) in seconds.
+    pub start_seconds: f64,
+    /// Loop end (point B) in seconds.
+    pub end_seconds: f64,
+}
+
+/// Cut `samples` at the end point of the active A-B loop.
+///
+/// Returns the loop start to continue from once the batch reaching the end
+/// point has been played, or `None` if no loop applies. Loops are skipped
+/// while a crossfade is mixing in the next track.
+pub fn trim_at_loop_end(
+    ctx: &LoopCtx,
+    engine_shared: &EngineShared,
+    samples: &mut Vec<f32>,
+) -> Option<f64> {
+    if ctx.crossfade.is_some() {
+        return None;
+    }
+    let ab_loop = engine_shared.state.lock().active_ab_loop()?;
+    let remaining_seconds = (ab_loop.end_seconds - ctx.elapsed).max(0.0);
+    let keep_frames: usize =
+        NumCast::from(remaining_seconds * ctx.track_sample_rate_f64).unwrap_or(0);
+    if keep_frames >= samples.len() / ctx.src_channels {
+        return None;
+    }
+    samples.truncate(keep_frames * ctx.src_channels);
+    Some(ab_loop.start_seconds)
+}
+
+/// Continue an A-B loop from its start point.
+///
+/// Seeks within the open stream without flushing the output, so the samples
+/// up to the end point still play and the jump back is seamless.
+pub fn restart_loop(ctx: &mut LoopCtx, engine_shared: &EngineShared, start_seconds: f64) {
+    let actual = match ctx.decoder.seek_to(start_seconds) {
+        Ok(actual) => actual,
+        Err(e) => {
+            warn!(error = %e, "Failed to return to A-B loop start");
+            return;
+        }
+    };
+    ctx.elapsed = actual;
+    engine_shared.state.lock().elapsed_seconds = actual;
+    engine_shared.send_event(&Seeked {
+        position_seconds: actual,
+    });
+}
@@ -0,0 +1,147 @@
+//! Biquad filter section shared by the equalizer bands.
+//!
+//! Coefficients can be swapped while a [`BiquadHistory`] is kept, so a
+//! filter changes its response without restarting from silence.
+
+use std::f64::consts::PI;
+
+/// Lowest frequency checked when measuring the peak boost, in Hz.
+const HEADROOM_LOW_HZ: f64 = 20.0;
+
+/// Frequencies checked per octave when measuring the peak boost.
+const HEADROOM_STEPS_PER_OCTAVE: f64 = 12.0;
+
+/// Normalised biquad coefficients (`a0` folded into the others).
+#[derive(Debug, Clone, Copy)]
+pub struct Biquad {
+    /// Feed-forward coefficient for `x[n]`.
+    b0: f64,
+    /// Feed-forward coefficient for `x[n-1]`.
+    b1: f64,
+    /// Feed-forward coefficient for `x[n-2]`.
+    b2: f64,
+    /// Feedback coefficient for `y[n-1]`.
+    a1: f64,
+    /// Feedback coefficient for `y[n-2]`.
+    a2: f64,
+}
+
+impl Biquad {
+    /// Filter passing the signal through unchanged.
+    pub const IDENTITY: Self = Self {
+        b0: 1.0,
+        b1: 0.0,
+        b2: 0.0,
+        a1: 0.0,
+        a2: 0.0,
+    };
+
+    /// RBJ cookbook peaking filter with quality factor `q`, or
+    /// [`Biquad::IDENTITY`] if the band is flat or above the Nyquist
+    /// frequency.
+    #[must_use]
+    pub fn peaking(frequency: f64, gain_db: f64, q: f64, sample_rate: f64) -> Self {
+        if gain_db.abs() < f64::EPSILON || frequency >= sample_rate / 2.0 {
+            return Self::IDENTITY;
+        }
+        let amplitude = 10f64.powf(gain_db / 40.0);
+        let omega = 2.0 * PI * frequency / sample_rate;
+        let alpha = omega.sin() / (2.0 * q);
+        let cos_omega = omega.cos();
+        let a0 = 1.0 + alpha / amplitude;
+        Self {
+            b0: alpha.mul_add(amplitude, 1.0) / a0,
+            b1: -2.0 * cos_omega / a0,
+            b2: (-alpha).mul_add(amplitude, 1.0) / a0,
+            a1: -2.0 * cos_omega / a0,
+            a2: (1.0 - alpha / amplitude) / a0,
+        }
+    }
+
+    /// Magnitude of the frequency response at `omega` radians per sample.
+    #[must_use]
+    pub fn magnitude(&self, omega: f64) -> f64 {
+        let (sin1, cos1) = omega.sin_cos();
+        let (sin2, cos2) = (2.0 * omega).sin_cos();
+        let num_re = self.b2.mul_add(cos2, self.b1.mul_add(cos1, self.b0));
+        let num_im = self.b2.mul_add(sin2, self.b1 * sin1);
+        let den_re = self.a2.mul_add(cos2, self.a1.mul_add(cos1, 1.0));
+        let den_im = self.a2.mul_add(sin2, self.a1 * sin1);
+        (num_re.hypot(num_im) / den_re.hypot(den_im)).abs()
+    }
+
+    /// Filter one sample, updating the per-channel history.
+    pub fn run(&self, x: f64, history: &mut BiquadHistory) -> f64 {
+        let y = self.b0.mul_add(
+            x,
+            self.b1.mul_add(
+                history.x1,
+                self.b2.mul_add(
+                    history.x2,
+                    (-self.a1).mul_add(history.y1, -self.a2 * history.y2),
+                ),
+            ),
+        );
+        history.x2 = history.x1;
+        history.x1 = x;
+        history.y2 = history.y1;
+        history.y1 = y;
+        y
+    }
+}
+
+/// Filter history for one band on one channel.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BiquadHistory {
+    /// Previous input sample.
+    x1: f64,
+    /// Input sample before `x1`.
+    x2: f64,
+    /// Previous output sample.
+    y1: f64,
+    /// Output sample before `y1`.
+    y2: f64,
+}
+
+/// Largest linear gain of the cascade of `filters`, checked
+/// every twelfth of an octave from 20 Hz up to the Nyquist frequency.
+#[must_use]
+pub fn peak_gain(filters: &[Biquad], sample_rate: f64) -> f64 {
+    let nyquist = sample_rate / 2.0;
+    let mut peak: f64 = 1.0;
+    let step = HEADROOM_STEPS_PER_OCTAVE.recip().exp2();
+    let mut frequency = HEADROOM_LOW_HZ;
+    while frequency < nyquist {
+        let omega = 2.0 * PI * frequency / sample_rate;
+        let gain: f64 = filters.iter().map(|f| f.magnitude(omega)).product();
+        peak = peak.max(gain);
+        frequency *= step;
+    }
+    peak
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::playback::biquad::{Biquad, BiquadHistory};
+
+    #[test]
+    fn identity_passes_samples_through() {
+        let mut history = BiquadHistory::default();
+        for x in [0.5, -0.25, 1.0] {
+            let y = Biquad::IDENTITY.run(x, &mut history);
+            assert!((y - x).abs() < f64::EPSILON, "{x} must pass unchanged");
+        }
+    }
+
+    #[test]
+    fn peaking_magnitude_matches_gain_at_centre() {
+        let filter = Biquad::peaking(1000.0, 6.0, 1.41, 48000.0);
+        let gain = filter.magnitude(2.0 * PI * 1000.0 / 48000.0);
+        assert!(
+            gain.log10().mul_add(20.0, -6.0).abs() < 0.01,
+            "The centre frequency must be boosted by the band gain"
+        );
+    }
+}
@@ -0,0 +1,91 @@
+//! Size of the ring buffer between the decode loop and the audio callback.
+//!
+//! The decode loop keeps the buffer full and the callback drains it. A large
+//! buffer rides out a busy system or slow storage without dropouts; a small
+//! one lets equalizer, balance, and speed changes be heard sooner, since
+//! they apply to samples before they enter the buffer. Sizes are powers of
+//! two, counted in stereo frames.
+
+use std::{ops::RangeInclusive, time::Duration};
+
+/// Smallest and largest buffer sizes accepted, in frames.
+pub const BUFFER_FRAMES_RANGE: RangeInclusive<u32> = 2048..=131_072;
+
+/// Buffer size used until the user picks another one, in frames.
+pub const DEFAULT_BUFFER_FRAMES: u32 = 32_768;
+
+/// Samples per frame the ring buffer is sized for.
+const BUFFER_CHANNELS: usize = 2;
+
+/// Buffer sizes the user can choose from, smallest first.
+pub fn buffer_frame_choices() -> impl Iterator<Item = u32> {
+    BUFFER_FRAMES_RANGE.filter(|f| f.is_power_of_two())
+}
+
+/// Whether `frames` is a power of two within [`BUFFER_FRAMES_RANGE`].
+#[must_use]
+pub fn is_valid_buffer_frames(frames: u32) -> bool {
+    frames.is_power_of_two() && BUFFER_FRAMES_RANGE.contains(&frames)
+}
+
+/// `frames` rounded up to a power of two within [`BUFFER_FRAMES_RANGE`].
+///
+/// Sizes edited into the settings file by hand are read through this.
+#[must_use]
+pub fn valid_buffer_frames(frames: u32) -> u32 {
+    frames
+        .clamp(*BUFFER_FRAMES_RANGE.start(), *BUFFER_FRAMES_RANGE.end())
+        .next_power_of_two()
+}
+
+/// Ring buffer capacity in samples for a buffer of `frames`.
+#[must_use]
+pub fn ring_capacity(frames: u32) -> usize {
+    usize::try_from(valid_buffer_frames(frames))
+        .unwrap_or(usize::MAX)
+        .saturating_mul(BUFFER_CHANNELS)
+}
+
+/// Audio held by a full buffer of `frames` at `sample_rate`.
+#[must_use]
+pub fn buffer_latency(frames: u32, sample_rate: u32) -> Duration {
+    Duration::from_secs_f64(f64::from(frames) / f64::from(sample_rate.max(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::playback::buffer::{
+        DEFAULT_BUFFER_FRAMES, buffer_frame_choices, buffer_latency, is_valid_buffer_frames,
+        ring_capacity, valid_buffer_frames,
+    };
+
+    #[test]
+    fn choices_are_powers_of_two() {
+        let choices: Vec<u32> = buffer_frame_choices().collect();
+        assert_eq!(
+            choices,
+            [2048, 4096, 8192, 16_384, 32_768, 65_536, 131_072],
+            "Powers of two within the range"
+        );
+        assert!(
+            is_valid_buffer_frames(DEFAULT_BUFFER_FRAMES),
+            "The default is a choice"
+        );
+        assert!(!is_valid_buffer_frames(3000), "3000 is no power of two");
+    }
+
+    #[test]
+    fn sizes_are_rounded_into_range() {
+        assert_eq!(valid_buffer_frames(3000), 4096, "Rounded up");
+        assert_eq!(valid_buffer_frames(0), 2048, "Raised to the smallest");
+        assert_eq!(valid_buffer_frames(u32::MAX), 131_072, "Capped");
+        assert_eq!(ring_capacity(4096), 8192, "Two samples per frame");
+        assert_eq!(
+            buffer_latency(4800, 48_000),
+            Duration::from_millis(100),
+            "4800 frames at 48 kHz"
+        );
+    }
+}
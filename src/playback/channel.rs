@@ -64,6 +64,7 @@ mod tests {
     use crate::playback::{
         channel::{downmix, maybe_downmix},
         decoder::{AudioParams, DecodedSamples},
+        layout::AudioLayout::Channels,
     };
 
     fn assert_approx_eq(a: f32, b: f32) {
@@ -134,6 +135,7 @@ mod tests {
             params: AudioParams {
                 sample_rate: 44100,
                 channels: 2,
+                layout: Channels(2),
                 duration_seconds: 0.0,
             },
         };
@@ -148,6 +150,7 @@ mod tests {
             params: AudioParams {
                 sample_rate: 44100,
                 channels: 2,
+                layout: Channels(2),
                 duration_seconds: 0.0,
             },
         };
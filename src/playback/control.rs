@@ -6,13 +6,20 @@ use {
 };
 
 use crate::playback::{
-    PlaybackError::{self, QueueEmpty, TrackNotFound},
+    PlaybackError::{
+        self, EqBandOutOfRange, InvalidAbLoop, InvalidBufferSize, InvalidPlaybackRate,
+        NoReplacedQueue, QueueEmpty, QueuePositionOutOfRange, TrackNotFound,
+    },
+    ab_loop::AbLoop,
+    buffer::is_valid_buffer_frames,
     engine::{
         DecodeCommand::{Pause, Resume, Seek},
+        MAX_PLAYBACK_RATE, MIN_PLAYBACK_RATE,
         MuteState::{Muted, Unmuted},
         PlaybackEngine,
         PlaybackEvent::{
-            self, GaplessEnabledChanged, OutputModeChanged, Paused, QueueChanged, Resumed, Seeked,
+            self, AbLoopChanged, GaplessEnabledChanged, OutputModeChanged, Paused,
+            PlaybackRateChanged, QueueChanged, QueueReplaced, RepeatModeChanged, Resumed, Seeked,
             Stopped, VolumeChanged,
         },
         PlaybackState,
@@ -20,6 +27,12 @@ use crate::playback::{
     },
     gapless::GaplessMode::{Disabled, Enabled},
     output::OutputMode::{self, BitPerfect, Resampled},
+    queue::{RepeatMode, shuffle_tracks},
+    queue_undo::ReplacedQueue,
+    resampler::ResampleQuality,
+    signal_path::SignalPathReport,
+    skip,
+    stereo::DownmixMode,
     worker,
 };
 
@@ -39,6 +52,16 @@ pub trait PlaybackController: Send + 'static {
     /// Returns [`PlaybackError`] if playback cannot start.
     fn play_queue(&self, queue: Vec<i64>) -> Result<(), PlaybackError>;
 
+    /// Play a list of track IDs in random order.
+    ///
+    /// The shuffle mode is left as it is, so turning shuffle off later
+    /// keeps the random order. Repeat applies as usual.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError`] if playback cannot start.
+    fn play_queue_shuffled(&self, queue: Vec<i64>) -> Result<(), PlaybackError>;
+
     /// Toggle between play and pause.
     ///
     /// # Errors
@@ -87,6 +110,11 @@ pub trait PlaybackController: Send + 'static {
     /// Get the current playback state.
     fn state(&self) -> PlaybackState;
 
+    /// Report which processing stages currently change the signal.
+    ///
+    /// The bit-perfect indicator is only lit when the report is clean.
+    fn signal_path_report(&self) -> SignalPathReport;
+
     /// Set the output mode (resampled vs bit-perfect).
     ///
     /// # Errors
@@ -101,12 +129,218 @@ pub trait PlaybackController: Send + 'static {
     /// Returns [`PlaybackError`] on failure.
     fn set_gapless_enabled(&self, enabled: bool) -> Result<(), PlaybackError>;
 
+    /// Set the crossfade window between tracks in milliseconds.
+    ///
+    /// A value of `0` disables crossfading. Crossfade is skipped in
+    /// bit-perfect mode and between tracks of a gapless album.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError`] on failure.
+    fn set_crossfade_ms(&self, crossfade_ms: u32) -> Result<(), PlaybackError>;
+
+    /// Set the silence between tracks in milliseconds.
+    ///
+    /// A value of `0` disables the gap. It is only left between tracks
+    /// while gapless playback and crossfade are off.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError`] on failure.
+    fn set_inter_track_gap_ms(&self, gap_ms: u32) -> Result<(), PlaybackError>;
+
+    /// Skip the silence at the start of tracks below `threshold_db` dBFS.
+    ///
+    /// `None` disables the skip. It applies from the next track started
+    /// directly or after a hard cut, and never in bit-perfect mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError`] on failure.
+    fn set_leading_silence_db(&self, threshold_db: Option<i32>) -> Result<(), PlaybackError>;
+
+    /// Set the fade applied when playback starts, pauses, or stops, in
+    /// milliseconds.
+    ///
+    /// A value of `0` disables fading. Fades are skipped in bit-perfect
+    /// mode and never applied between tracks.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError`] on failure.
+    fn set_fade_ms(&self, fade_ms: u32) -> Result<(), PlaybackError>;
+
+    /// Run the output at each track's own sample rate when the device
+    /// supports it, resampling only the tracks it does not.
+    ///
+    /// Takes effect when the next track opens the output. Tracks that need
+    /// a different rate end their gapless or crossfaded transition and
+    /// reopen the output at the new rate instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError`] on failure.
+    fn set_follow_source_rate(&self, enabled: bool) -> Result<(), PlaybackError>;
+
+    /// Choose the quality/CPU trade-off of sample-rate conversion.
+    ///
+    /// Takes effect when the next track opens the output.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError`] on failure.
+    fn set_resample_quality(&self, quality: ResampleQuality) -> Result<(), PlaybackError>;
+
+    /// Set the size of the output ring buffer in frames.
+    ///
+    /// Larger buffers guard against dropouts, smaller ones make changes to
+    /// the sound heard sooner. Takes effect when the next track opens the
+    /// output.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError::InvalidBufferSize`] unless `frames` is a
+    /// power of two within
+    /// [`BUFFER_FRAMES_RANGE`](crate::playback::buffer::BUFFER_FRAMES_RANGE).
+    fn set_buffer_frames(&self, frames: u32) -> Result<(), PlaybackError>;
+
+    /// Choose how the left and right channels reach the device: as they
+    /// are, mixed down to mono, or swapped.
+    ///
+    /// Applied by the audio callback, so the change is heard at once.
+    /// Bypassed in bit-perfect mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError`] on failure.
+    fn set_downmix(&self, mode: DownmixMode) -> Result<(), PlaybackError>;
+
+    /// Set the left/right balance from `-1.0` (left only) through `0.0`
+    /// (centre) to `1.0` (right only).
+    ///
+    /// Bypassed in bit-perfect mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError`] on failure.
+    fn set_balance(&self, balance: f64) -> Result<(), PlaybackError>;
+
+    /// Switch the graphic equalizer on or off.
+    ///
+    /// The equalizer is always bypassed in bit-perfect mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError`] on failure.
+    fn set_eq_enabled(&self, enabled: bool) -> Result<(), PlaybackError>;
+
+    /// Set the gain of equalizer band `index` in decibels.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError::EqBandOutOfRange`] if `index` is not a
+    /// valid band.
+    fn set_eq_band(&self, index: usize, gain_db: f64) -> Result<(), PlaybackError>;
+
     /// Seek to a position in seconds.
     ///
     /// # Errors
     ///
     /// Returns [`PlaybackError`] if no track is playing.
     fn seek_to(&self, position_seconds: f64) -> Result<(), PlaybackError>;
+
+    /// Loop the current track between two points, given in seconds.
+    ///
+    /// When playback reaches the end point it continues from the start
+    /// point without flushing the output. The loop survives pause and
+    /// resume, ends when another track starts, and `None` clears it so
+    /// playback continues normally. Ignored when nothing is playing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError::InvalidAbLoop`] if the end point does not
+    /// come after the start point.
+    fn set_ab_loop(&self, ab_loop: Option<(f64, f64)>) -> Result<(), PlaybackError>;
+
+    /// Set the playback speed of the current track (`1.0` is normal speed).
+    ///
+    /// The speed is applied by resampling, so the pitch shifts with it and
+    /// output is no longer bit-perfect while it differs from `1.0`. Values
+    /// are clamped to [`MIN_PLAYBACK_RATE`]..=[`MAX_PLAYBACK_RATE`]. The
+    /// next track plays at normal speed unless
+    /// [`set_remember_playback_rate`](Self::set_remember_playback_rate)
+    /// is on.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError::InvalidPlaybackRate`] if `rate` is not finite.
+    fn set_playback_rate(&self, rate: f64) -> Result<(), PlaybackError>;
+
+    /// Keep the chosen playback speed when another track starts.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError`] on failure.
+    fn set_remember_playback_rate(&self, remember: bool) -> Result<(), PlaybackError>;
+
+    /// Move the queue entry at `from` to position `to`.
+    ///
+    /// The currently playing track keeps playing; only the order of the
+    /// queue changes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError::QueuePositionOutOfRange`] if either
+    /// position is past the end of the queue.
+    fn move_in_queue(&self, from: usize, to: usize) -> Result<(), PlaybackError>;
+
+    /// Start playing the queue entry at `position`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError`] if the position is out of range or
+    /// playback cannot start.
+    fn jump_to_queue_index(&self, position: usize) -> Result<(), PlaybackError>;
+
+    /// Append tracks to the end of the queue without interrupting playback.
+    ///
+    /// Their paths must already be known to the engine. Emits
+    /// [`PlaybackEvent::QueueChanged`] with the new track order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError`] if the tracks cannot be queued.
+    fn extend_queue(&self, track_ids: Vec<i64>) -> Result<(), PlaybackError>;
+
+    /// Put back the queue replaced by the last [`play_queue`](Self::play_queue).
+    ///
+    /// The track that was current plays again from where it was; if
+    /// playback was stopped, the queue is restored without playing. Emits
+    /// [`PlaybackEvent::QueueChanged`] with the restored track order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError::NoReplacedQueue`] if no replaced queue is
+    /// kept, or [`PlaybackError`] if playback cannot start.
+    fn undo_queue_replace(&self) -> Result<(), PlaybackError>;
+
+    /// Set what happens when a track or the whole queue finishes.
+    ///
+    /// Emits [`PlaybackEvent::RepeatModeChanged`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError`] if the mode cannot be applied.
+    fn set_repeat_mode(&self, mode: RepeatMode) -> Result<(), PlaybackError>;
+
+    /// Turn shuffle on or off.
+    ///
+    /// Emits [`PlaybackEvent::QueueChanged`] with the new track order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlaybackError`] if the queue cannot be reordered.
+    fn set_shuffle(&self, enabled: bool) -> Result<(), PlaybackError>;
 }
 
 impl PlaybackController for PlaybackEngine {
@@ -136,8 +370,14 @@ impl PlaybackController for PlaybackEngine {
         }
         let queue_len = queue.len();
         info!(queue_len, "Play queue command",);
+        let replaced = ReplacedQueue::capture(&self.shared, &queue);
         self.shared.queue.set_queue(queue.clone());
         self.shared.send_event(&QueueChanged { track_ids: queue });
+        if let Some(replaced) = replaced {
+            let previous_len = replaced.queue.tracks().len();
+            *self.shared.replaced_queue.lock() = Some(replaced);
+            self.shared.send_event(&QueueReplaced { previous_len });
+        }
         let first_id = self.shared.queue.current().ok_or(QueueEmpty)?;
         let path = self
             .shared
@@ -150,6 +390,12 @@ impl PlaybackController for PlaybackEngine {
         Ok(())
     }
 
+    fn play_queue_shuffled(&self, mut queue: Vec<i64>) -> Result<(), PlaybackError> {
+        info!(queue_len = queue.len(), "Play shuffled queue command");
+        shuffle_tracks(&mut queue);
+        self.play_queue(queue)
+    }
+
     fn toggle_pause(&self) -> Result<(), PlaybackError> {
         let is_playing = {
             let state = self.shared.state.lock();
@@ -183,20 +429,25 @@ impl PlaybackController for PlaybackEngine {
         drop(cmd_tx);
 
         self.shared.send_event(&event);
+        self.shared.send_position();
         Ok(())
     }
 
     fn stop(&self) -> Result<(), PlaybackError> {
         let current_track = self.shared.state.lock().current_track_id;
         info!(track_id = current_track, "Playback stopped",);
-        worker::stop_decode_task(&self.shared);
         let mut state = self.shared.state.lock();
         state.status = StatusStopped;
         state.current_track_id = None;
         state.current_path = None;
         state.elapsed_seconds = 0.0;
         state.duration_seconds = 0.0;
+        state.channel_layout = None;
+        state.ab_loop = None;
         drop(state);
+        self.shared.skips.cancel();
+        // Mark the state stopped first so the decode thread fades out.
+        worker::stop_decode_task(&self.shared);
         self.shared.send_event(&Stopped);
         Ok(())
     }
@@ -213,7 +464,7 @@ impl PlaybackController for PlaybackEngine {
             .get(&next_id)
             .cloned()
             .ok_or(TrackNotFound(next_id))?;
-        worker::start_playback(&self.shared, next_id, path);
+        skip::queue_skip(&self.shared, next_id, path);
         Ok(())
     }
 
@@ -229,7 +480,7 @@ impl PlaybackController for PlaybackEngine {
             .get(&prev_id)
             .cloned()
             .ok_or(TrackNotFound(prev_id))?;
-        worker::start_playback(&self.shared, prev_id, path);
+        skip::queue_skip(&self.shared, prev_id, path);
         Ok(())
     }
 
@@ -287,10 +538,100 @@ impl PlaybackController for PlaybackEngine {
     fn set_gapless_enabled(&self, enabled: bool) -> Result<(), PlaybackError> {
         info!(enabled, "Gapless playback toggled",);
         self.shared.state.lock().gapless_mode = if enabled { Enabled } else { Disabled };
+        self.shared.sync_prebuffering();
         self.shared.send_event(&GaplessEnabledChanged { enabled });
         Ok(())
     }
 
+    fn set_crossfade_ms(&self, crossfade_ms: u32) -> Result<(), PlaybackError> {
+        info!(crossfade_ms, "Crossfade window changed");
+        self.shared.state.lock().crossfade_ms = crossfade_ms;
+        self.shared.sync_prebuffering();
+        Ok(())
+    }
+
+    fn set_inter_track_gap_ms(&self, gap_ms: u32) -> Result<(), PlaybackError> {
+        info!(gap_ms, "Inter-track gap changed");
+        self.shared.state.lock().inter_track_gap_ms = gap_ms;
+        Ok(())
+    }
+
+    fn set_leading_silence_db(&self, threshold_db: Option<i32>) -> Result<(), PlaybackError> {
+        info!(?threshold_db, "Leading silence skip changed");
+        self.shared.state.lock().leading_silence_db = threshold_db;
+        Ok(())
+    }
+
+    fn set_fade_ms(&self, fade_ms: u32) -> Result<(), PlaybackError> {
+        info!(fade_ms, "Play/pause fade changed");
+        self.shared.state.lock().fade_ms = fade_ms;
+        if let Some(output) = self.shared.output.lock().as_ref() {
+            output.set_fade_ms(fade_ms);
+        }
+        Ok(())
+    }
+
+    fn set_follow_source_rate(&self, enabled: bool) -> Result<(), PlaybackError> {
+        info!(enabled, "Sample-rate follow toggled");
+        self.shared.state.lock().follow_source_rate = enabled;
+        Ok(())
+    }
+
+    fn set_resample_quality(&self, quality: ResampleQuality) -> Result<(), PlaybackError> {
+        info!(quality = ?quality, "Resampler quality changed");
+        self.shared.state.lock().resample_quality = quality;
+        Ok(())
+    }
+
+    fn set_buffer_frames(&self, frames: u32) -> Result<(), PlaybackError> {
+        if !is_valid_buffer_frames(frames) {
+            return Err(InvalidBufferSize(frames));
+        }
+        info!(frames, "Output buffer size changed");
+        self.shared.state.lock().buffer_frames = frames;
+        Ok(())
+    }
+
+    fn set_downmix(&self, mode: DownmixMode) -> Result<(), PlaybackError> {
+        info!(downmix = ?mode, "Channel mode changed");
+        let balance = {
+            let mut state = self.shared.state.lock();
+            state.downmix = mode;
+            state.balance
+        };
+        if let Some(output) = self.shared.output.lock().as_ref() {
+            output.set_stereo_mix(mode, balance);
+        }
+        Ok(())
+    }
+
+    fn set_balance(&self, balance: f64) -> Result<(), PlaybackError> {
+        let clamped = balance.clamp(-1.0, 1.0);
+        info!(balance = clamped, "Balance changed");
+        let mode = {
+            let mut state = self.shared.state.lock();
+            state.balance = clamped;
+            state.downmix
+        };
+        if let Some(output) = self.shared.output.lock().as_ref() {
+            output.set_stereo_mix(mode, clamped);
+        }
+        Ok(())
+    }
+
+    fn set_eq_enabled(&self, enabled: bool) -> Result<(), PlaybackError> {
+        info!(enabled, "Equalizer toggled");
+        self.shared.equalizer.lock().set_enabled(enabled);
+        Ok(())
+    }
+
+    fn set_eq_band(&self, index: usize, gain_db: f64) -> Result<(), PlaybackError> {
+        if !self.shared.equalizer.lock().set_band(index, gain_db) {
+            return Err(EqBandOutOfRange(index));
+        }
+        Ok(())
+    }
+
     fn seek_to(&self, position_seconds: f64) -> Result<(), PlaybackError> {
         let clamped = {
             let state = self.shared.state.lock();
@@ -308,6 +649,156 @@ impl PlaybackController for PlaybackEngine {
         Ok(())
     }
 
+    fn set_ab_loop(&self, ab_loop: Option<(f64, f64)>) -> Result<(), PlaybackError> {
+        let mut state = self.shared.state.lock();
+        let Some(track_id) = state.current_track_id else {
+            info!("A-B loop ignored — not playing");
+            return Ok(());
+        };
+        let ab_loop = match ab_loop {
+            Some((start, end)) if end <= start => return Err(InvalidAbLoop { start, end }),
+            Some((start, end)) => Some(AbLoop {
+                track_id,
+                start_seconds: start.max(0.0),
+                end_seconds: end,
+            }),
+            None => None,
+        };
+        state.ab_loop = ab_loop;
+        drop(state);
+        info!(track_id, ab_loop = ?ab_loop, "A-B loop changed");
+        self.shared.send_event(&AbLoopChanged { ab_loop });
+        Ok(())
+    }
+
+    fn set_playback_rate(&self, rate: f64) -> Result<(), PlaybackError> {
+        if !rate.is_finite() {
+            return Err(InvalidPlaybackRate(rate));
+        }
+        let rate = rate.clamp(MIN_PLAYBACK_RATE, MAX_PLAYBACK_RATE);
+        let mut state = self.shared.state.lock();
+        state.playback_rate = rate;
+        state.playback_rate_track = state.current_track_id;
+        let track_id = state.current_track_id;
+        drop(state);
+        info!(track_id, rate, "Playback rate changed");
+        self.shared.send_event(&PlaybackRateChanged { rate });
+        Ok(())
+    }
+
+    fn set_remember_playback_rate(&self, remember: bool) -> Result<(), PlaybackError> {
+        info!(remember, "Remember playback rate toggled");
+        self.shared.state.lock().remember_playback_rate = remember;
+        Ok(())
+    }
+
+    fn move_in_queue(&self, from: usize, to: usize) -> Result<(), PlaybackError> {
+        let len = self.shared.queue.len();
+        if from >= len {
+            return Err(QueuePositionOutOfRange(from));
+        }
+        if to >= len {
+            return Err(QueuePositionOutOfRange(to));
+        }
+        info!(from, to, "Move queue entry");
+        self.shared.queue.move_track(from, to);
+        self.shared.send_event(&QueueChanged {
+            track_ids: self.shared.queue.tracks(),
+        });
+        Ok(())
+    }
+
+    fn jump_to_queue_index(&self, position: usize) -> Result<(), PlaybackError> {
+        let track_id = self
+            .shared
+            .queue
+            .jump_to(position)
+            .ok_or(QueuePositionOutOfRange(position))?;
+        let path = self
+            .shared
+            .track_paths
+            .lock()
+            .get(&track_id)
+            .cloned()
+            .ok_or(TrackNotFound(track_id))?;
+        info!(position, track_id, "Jump to queue entry");
+        worker::start_playback(&self.shared, track_id, path);
+        Ok(())
+    }
+
+    fn extend_queue(&self, track_ids: Vec<i64>) -> Result<(), PlaybackError> {
+        if track_ids.is_empty() {
+            return Ok(());
+        }
+        info!(added = track_ids.len(), "Extend queue");
+        self.shared.queue.extend(&track_ids);
+        self.shared.send_event(&QueueChanged {
+            track_ids: self.shared.queue.tracks(),
+        });
+        Ok(())
+    }
+
+    fn undo_queue_replace(&self) -> Result<(), PlaybackError> {
+        let replaced = self
+            .shared
+            .replaced_queue
+            .lock()
+            .take()
+            .ok_or(NoReplacedQueue)?;
+        let current = replaced.queue.current();
+        info!(
+            queue_len = replaced.queue.tracks().len(),
+            current, "Undo queue replacement"
+        );
+        self.shared.track_paths.lock().extend(replaced.paths);
+        self.shared.queue.restore(replaced.queue);
+        self.shared.send_event(&QueueChanged {
+            track_ids: self.shared.queue.tracks(),
+        });
+        let (Some(track_id), Some(position_seconds)) = (current, replaced.resume_seconds) else {
+            return self.stop();
+        };
+        let path = self
+            .shared
+            .track_paths
+            .lock()
+            .get(&track_id)
+            .cloned()
+            .ok_or(TrackNotFound(track_id))?;
+        worker::start_playback(&self.shared, track_id, path);
+        if position_seconds <= 0.0 {
+            return Ok(());
+        }
+        // The duration is unknown until the decoder opens, so the seek goes
+        // to the decode task directly instead of through `seek_to`.
+        let cmd_tx = self.shared.decode_tx.lock();
+        if let Some(tx) = cmd_tx.as_ref()
+            && let Err(e) = tx.try_send(Seek(position_seconds))
+        {
+            warn!(error = %e, "Failed to resume the restored track");
+        }
+        drop(cmd_tx);
+        self.shared.state.lock().elapsed_seconds = position_seconds;
+        self.shared.send_event(&Seeked { position_seconds });
+        Ok(())
+    }
+
+    fn set_repeat_mode(&self, mode: RepeatMode) -> Result<(), PlaybackError> {
+        info!(mode = ?mode, "Set repeat mode");
+        self.shared.queue.set_repeat_mode(mode);
+        self.shared.send_event(&RepeatModeChanged { mode });
+        Ok(())
+    }
+
+    fn set_shuffle(&self, enabled: bool) -> Result<(), PlaybackError> {
+        info!(enabled, "Set shuffle");
+        self.shared.queue.set_shuffle(enabled);
+        self.shared.send_event(&QueueChanged {
+            track_ids: self.shared.queue.tracks(),
+        });
+        Ok(())
+    }
+
     fn subscribe(&self) -> Receiver<PlaybackEvent> {
         let (tx, rx) = unbounded();
         self.shared.event_subs.lock().push(tx);
@@ -317,4 +808,17 @@ impl PlaybackController for PlaybackEngine {
     fn state(&self) -> PlaybackState {
         self.shared.state.lock().clone()
     }
+
+    fn signal_path_report(&self) -> SignalPathReport {
+        let equalizer_active = self.shared.equalizer.lock().is_active();
+        let track_sample_rate = *self.shared.track_sample_rate.lock();
+        let device_sample_rate = *self.shared.device_sample_rate.lock();
+        SignalPathReport::new(
+            &self.state(),
+            track_sample_rate,
+            device_sample_rate,
+            equalizer_active,
+        )
+        .with_entry(*self.shared.track_entry.lock())
+    }
 }
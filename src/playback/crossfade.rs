@@ -0,0 +1,373 @@
+//! Crossfade between the outgoing and incoming tracks.
+//!
+//! When a crossfade window is configured, the decode loop opens the
+//! pre-buffered next track shortly before the current one ends and mixes
+//! both streams at the device sample rate with complementary linear gain
+//! ramps. Mixing happens on the decode thread before samples enter the
+//! ring buffer, so the real-time output callback stays lock-free.
+
+use std::{
+    collections::VecDeque,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use {
+    num_traits::cast::cast,
+    rtrb::Producer,
+    tracing::{info, warn},
+};
+
+use crate::playback::{
+    channel::maybe_downmix,
+    decoder::Decoder,
+    engine::{
+        EngineShared,
+        PlaybackEvent::{self, Error},
+    },
+    output::OutputMode::BitPerfect,
+    pipeline::{LoopCtx, OutputConfig, push_output, resample_batch, switches_output_rate},
+    resampler::{AudioResampler, ResampleQuality, create_resampler},
+};
+
+/// Output frames mixed per step once the outgoing track has ended.
+const TAIL_FRAMES: u64 = 4096;
+
+/// Incoming track state while a crossfade is in progress.
+pub struct Crossfade {
+    /// ID of the incoming track.
+    pub track_id: i64,
+    /// Decoder for the incoming track.
+    pub decoder: Decoder,
+    /// Resampler converting the incoming track to the device rate.
+    pub resampler: Option<AudioResampler>,
+    /// Sample rate of the incoming track.
+    pub track_sample_rate: u32,
+    /// Number of source channels in the incoming track.
+    pub src_channels: usize,
+    /// Seconds of the incoming track decoded so far.
+    pub elapsed: f64,
+    /// Incoming samples at the device rate not yet mixed.
+    pending: VecDeque<f32>,
+    /// Number of output channels.
+    dst_channels: usize,
+    /// Length of the fade in output frames.
+    total_frames: u64,
+    /// Output frames mixed so far.
+    frames_done: u64,
+    /// Whether the incoming decoder reached end of stream during the fade.
+    incoming_ended: bool,
+}
+
+impl Crossfade {
+    /// Start a crossfade into `decoder` lasting `window`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a descriptive error string if the incoming resampler cannot
+    /// be created.
+    pub fn new(
+        track_id: i64,
+        decoder: Decoder,
+        window: Duration,
+        device_sample_rate: u32,
+        dst_channels: usize,
+        quality: ResampleQuality,
+    ) -> Result<Self, String> {
+        let params = decoder.params();
+        let resampler = if params.sample_rate == device_sample_rate {
+            None
+        } else {
+            Some(create_resampler(
+                params.sample_rate,
+                device_sample_rate,
+                dst_channels,
+                quality,
+            )?)
+        };
+        let total_frames: u64 =
+            cast((window.as_secs_f64() * f64::from(device_sample_rate)).ceil()).unwrap_or(1);
+        Ok(Self {
+            track_id,
+            decoder,
+            resampler,
+            track_sample_rate: params.sample_rate,
+            src_channels: params.channels as usize,
+            elapsed: 0.0,
+            pending: VecDeque::new(),
+            dst_channels: dst_channels.max(1),
+            total_frames: total_frames.max(1),
+            frames_done: 0,
+            incoming_ended: false,
+        })
+    }
+
+    /// Mix incoming samples into `outgoing` in place.
+    ///
+    /// `outgoing` must be interleaved at the device rate and channel count.
+    /// The outgoing gain ramps from 1 to 0 while the incoming gain ramps
+    /// from 0 to 1 across the fade window.
+    ///
+    /// # Errors
+    ///
+    /// Returns a descriptive error string if decoding or resampling the
+    /// incoming track fails.
+    pub fn mix(&mut self, outgoing: &mut [f32]) -> Result<(), String> {
+        self.fill(outgoing.len())?;
+        for frame in outgoing.chunks_mut(self.dst_channels) {
+            self.mix_frame(frame);
+        }
+        Ok(())
+    }
+
+    /// Returns `true` once the full fade window has been mixed.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.frames_done >= self.total_frames
+    }
+
+    /// Interleaved samples in the next step of the fade after the outgoing
+    /// track ended, at most [`TAIL_FRAMES`] frames.
+    fn tail_len(&self) -> usize {
+        let frames = self
+            .total_frames
+            .saturating_sub(self.frames_done)
+            .min(TAIL_FRAMES);
+        usize::try_from(frames).unwrap_or(0) * self.dst_channels
+    }
+
+    /// Take any incoming samples decoded ahead of the mix position.
+    ///
+    /// These are pushed at full gain when the incoming track takes over.
+    pub fn take_pending(&mut self) -> Vec<f32> {
+        self.pending.drain(..).collect()
+    }
+
+    /// Decode incoming audio until at least `needed` samples are pending.
+    fn fill(&mut self, needed: usize) -> Result<(), String> {
+        while self.pending.len() < needed && !self.incoming_ended {
+            self.fill_batch()?;
+        }
+        Ok(())
+    }
+
+    /// Decode and resample one incoming batch into `pending`.
+    ///
+    /// Marks the incoming track as ended when the decoder is exhausted.
+    fn fill_batch(&mut self) -> Result<(), String> {
+        let batch = self.decoder.decode_next().map_err(|e| e.to_string())?;
+        if batch.samples.is_empty() {
+            self.incoming_ended = true;
+            return Ok(());
+        }
+        let frames: u32 = cast(batch.samples.len() / self.src_channels.max(1)).unwrap_or(u32::MAX);
+        self.elapsed += f64::from(frames) / f64::from(self.track_sample_rate);
+        let samples = maybe_downmix(batch, self.src_channels, self.dst_channels);
+        self.pending
+            .extend(resample_batch(&mut self.resampler, samples)?);
+        Ok(())
+    }
+
+    /// Mix one interleaved output frame at the current fade position.
+    fn mix_frame(&mut self, frame: &mut [f32]) {
+        let (gain_out, gain_in) = fade_gains(self.frames_done, self.total_frames);
+        for sample in frame {
+            let incoming = self.pending.pop_front().unwrap_or(0.0);
+            *sample = sample.mul_add(gain_out, incoming * gain_in);
+        }
+        self.frames_done += 1;
+    }
+}
+
+/// Start a crossfade into the pre-buffered next track once the current
+/// track is within the configured crossfade window of its end.
+///
+/// The next track is pre-buffered whenever crossfade is on, with or
+/// without gapless playback. Does nothing when crossfade is disabled, in
+/// bit-perfect mode, when the next track continues a gapless album, or
+/// when the next track reopens the output at its own sample rate.
+pub fn maybe_start_crossfade(
+    engine_shared: &Arc<EngineShared>,
+    ctx: &mut LoopCtx,
+    output_cfg: OutputConfig,
+) {
+    if ctx.crossfade.is_some() || ctx.continues_album {
+        return;
+    }
+    let state = engine_shared.state.lock();
+    let remaining = state.duration_seconds - ctx.elapsed;
+    let crossfade_ms = state.crossfade_ms;
+    let eligible = crossfade_ms > 0
+        && state.output_mode != BitPerfect
+        && state.duration_seconds > 0.0
+        && remaining > 0.0
+        && remaining * 1000.0 <= f64::from(crossfade_ms);
+    drop(state);
+    if !eligible {
+        return;
+    }
+
+    let transitioner = engine_shared.transitioner.lock();
+    let (next_id, next_rate) = (
+        transitioner.next_track_id(),
+        transitioner.next_sample_rate(),
+    );
+    drop(transitioner);
+    let Some(next_id) = next_id else {
+        return;
+    };
+    if next_rate.is_some_and(|rate| {
+        switches_output_rate(engine_shared, rate, output_cfg.device_sample_rate)
+    }) {
+        return;
+    }
+    if engine_shared.queue.peek_advance() != Some(next_id) {
+        return;
+    }
+    let Some(decoder) = engine_shared.transitioner.lock().transition() else {
+        return;
+    };
+
+    match Crossfade::new(
+        next_id,
+        decoder,
+        Duration::from_millis(u64::from(crossfade_ms)),
+        output_cfg.device_sample_rate,
+        output_cfg.channels as usize,
+        output_cfg.resample_quality,
+    ) {
+        Ok(crossfade) => {
+            info!(
+                next_id,
+                crossfade_ms,
+                remaining_seconds = remaining,
+                "Crossfade started"
+            );
+            ctx.crossfade = Some(crossfade);
+        }
+        Err(e) => warn!(error = %e, "Failed to start crossfade"),
+    }
+}
+
+/// Hand the decode loop over to the incoming track of a finished crossfade.
+///
+/// Returns `Some(track_id)` if the queue advanced to the incoming track, or
+/// `None` if the queue changed during the fade.
+pub fn finish_crossfade(
+    engine_shared: &Arc<EngineShared>,
+    ctx: &mut LoopCtx,
+    mut crossfade: Crossfade,
+    output_cfg: OutputConfig,
+    producer: &mut Producer<f32>,
+) -> Option<i64> {
+    push_output(
+        engine_shared,
+        &mut crossfade.take_pending(),
+        output_cfg,
+        producer,
+    );
+    let next_id = crossfade.track_id;
+    if engine_shared.queue.peek_advance() != Some(next_id)
+        || engine_shared.queue.advance().is_none()
+    {
+        return None;
+    }
+    {
+        let mut state = engine_shared.state.lock();
+        state.current_track_id = Some(next_id);
+        let path = engine_shared.track_paths.lock().get(&next_id).cloned();
+        state.current_path = path;
+        state.elapsed_seconds = crossfade.elapsed;
+        state.duration_seconds = crossfade.decoder.params().duration_seconds;
+        state.channel_layout = Some(crossfade.decoder.params().layout);
+    }
+    *engine_shared.track_sample_rate.lock() = crossfade.track_sample_rate;
+
+    ctx.track_sample_rate_f64 = f64::from(crossfade.track_sample_rate);
+    ctx.track_sample_rate = crossfade.track_sample_rate;
+    ctx.src_channels = crossfade.src_channels;
+    ctx.elapsed = crossfade.elapsed;
+    ctx.last_tick = Instant::now();
+    ctx.resampler = crossfade.resampler;
+    ctx.decoder = crossfade.decoder;
+
+    Some(next_id)
+}
+
+/// Mix a decoded batch of the outgoing track with the active crossfade and
+/// push the result. Returns an error event if decoding or resampling fails.
+pub fn mix_crossfade_batch(
+    ctx: &mut LoopCtx,
+    samples: Vec<f32>,
+    engine_shared: &EngineShared,
+    output_cfg: OutputConfig,
+    producer: &mut Producer<f32>,
+) -> Option<PlaybackEvent> {
+    let mut mixed = match resample_batch(&mut ctx.resampler, samples) {
+        Ok(mixed) => mixed,
+        Err(error) => return Some(Error { error }),
+    };
+    let crossfade = ctx.crossfade.as_mut()?;
+    if let Err(error) = crossfade.mix(&mut mixed) {
+        return Some(Error { error });
+    }
+    push_output(engine_shared, &mut mixed, output_cfg, producer);
+    None
+}
+
+/// Continue the fade against silence after the outgoing track ended before
+/// the fade window did, so the incoming track keeps ramping up instead of
+/// jumping to full gain. Mixes one step per call and returns an error event
+/// if decoding or resampling the incoming track fails.
+pub fn mix_crossfade_tail(
+    ctx: &mut LoopCtx,
+    engine_shared: &EngineShared,
+    output_cfg: OutputConfig,
+    producer: &mut Producer<f32>,
+) -> Option<PlaybackEvent> {
+    let crossfade = ctx.crossfade.as_mut()?;
+    let mut mixed = vec![0.0; crossfade.tail_len()];
+    if let Err(error) = crossfade.mix(&mut mixed) {
+        return Some(Error { error });
+    }
+    push_output(engine_shared, &mut mixed, output_cfg, producer);
+    None
+}
+
+/// Linear `(outgoing, incoming)` gains at `frame` of a `total`-frame fade.
+#[must_use]
+pub fn fade_gains(frame: u64, total: u64) -> (f32, f32) {
+    if total == 0 {
+        return (0.0, 1.0);
+    }
+    let done: f64 = cast(frame.min(total)).unwrap_or(0.0);
+    let len: f64 = cast(total).unwrap_or(1.0);
+    let progress: f32 = cast(done / len).unwrap_or(1.0);
+    (1.0 - progress, progress)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::playback::crossfade::fade_gains;
+
+    #[test]
+    fn fade_gains_are_complementary() {
+        for frame in [0, 25, 50, 100, 150] {
+            let (gain_out, gain_in) = fade_gains(frame, 100);
+            assert!(
+                (gain_out + gain_in - 1.0).abs() < f32::EPSILON,
+                "gains must sum to unity at frame {frame}"
+            );
+        }
+        let (start_out, _) = fade_gains(0, 100);
+        let (_, end_in) = fade_gains(150, 100);
+        assert!(
+            (start_out - 1.0).abs() < f32::EPSILON,
+            "fade must start at full outgoing gain"
+        );
+        assert!(
+            (end_in - 1.0).abs() < f32::EPSILON,
+            "fade must end at full incoming gain"
+        );
+    }
+}
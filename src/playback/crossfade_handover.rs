@@ -0,0 +1,73 @@
+//! Handing playback over to the incoming track when a crossfade completes.
+//!
+//! The decode loop feeds each batch, or the tail after the outgoing track
+//! ended early, into the active crossfade. Once the fade window is done,
+//! the incoming track becomes the current one, as on a gapless transition.
+
+use std::sync::Arc;
+
+use rtrb::Producer;
+
+use crate::playback::{
+    crossfade::{finish_crossfade, mix_crossfade_batch, mix_crossfade_tail},
+    engine::{
+        EngineShared,
+        PlaybackEvent::{self, TrackFinished},
+    },
+    pipeline::{LoopCtx, OutputConfig, announce_transition},
+};
+
+/// Mix a decoded batch into the active crossfade, handing playback over
+/// to the incoming track once the fade window is complete.
+pub fn process_crossfade_batch(
+    ctx: &mut LoopCtx,
+    samples: Vec<f32>,
+    engine_shared: &Arc<EngineShared>,
+    output_cfg: OutputConfig,
+    producer: &mut Producer<f32>,
+    track_id: &mut i64,
+) -> Option<PlaybackEvent> {
+    let event = mix_crossfade_batch(ctx, samples, engine_shared, output_cfg, producer);
+    if event.is_some() {
+        return event;
+    }
+    hand_over_crossfade(ctx, engine_shared, output_cfg, producer, track_id)
+}
+
+/// Continue an active crossfade after the outgoing track ended early,
+/// handing playback over to the incoming track once the fade is complete.
+pub fn process_crossfade_tail(
+    ctx: &mut LoopCtx,
+    engine_shared: &Arc<EngineShared>,
+    output_cfg: OutputConfig,
+    producer: &mut Producer<f32>,
+    track_id: &mut i64,
+) -> Option<PlaybackEvent> {
+    let event = mix_crossfade_tail(ctx, engine_shared, output_cfg, producer);
+    if event.is_some() {
+        return event;
+    }
+    hand_over_crossfade(ctx, engine_shared, output_cfg, producer, track_id)
+}
+
+/// Hand playback over to the incoming track if the crossfade is complete.
+///
+/// Returns `TrackFinished` if the queue changed during the fade.
+fn hand_over_crossfade(
+    ctx: &mut LoopCtx,
+    engine_shared: &Arc<EngineShared>,
+    output_cfg: OutputConfig,
+    producer: &mut Producer<f32>,
+    track_id: &mut i64,
+) -> Option<PlaybackEvent> {
+    let crossfade = ctx.crossfade.take_if(|c| c.is_complete())?;
+    match finish_crossfade(engine_shared, ctx, crossfade, output_cfg, producer) {
+        Some(new_id) => {
+            announce_transition(engine_shared, track_id, new_id);
+            None
+        }
+        None => Some(TrackFinished {
+            track_id: *track_id,
+        }),
+    }
+}
@@ -0,0 +1,116 @@
+//! Next/previous between CUE tracks of the file being played.
+//!
+//! A live set or a classical work ripped as one file plays its CUE tracks
+//! from the same decoder. Skipping to another of them moves the decoder to
+//! the track's range instead of stopping the decode thread and reopening
+//! the file and output, so the skip is instant and the output never stops.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+use tracing::{debug, info, warn};
+
+use crate::{
+    library::cue::{CueRange, sibling_cue_range},
+    playback::{
+        engine::{DecodeCommand::JumpToCueTrack, EngineShared, PlaybackEvent::TrackStarted},
+        gapless::TrackEntry::Started,
+        output::AudioOutput,
+        pipeline::{LoopCtx, preload_next_upcoming},
+        worker::start_playback,
+    },
+};
+
+/// Hand a skip to `track_id` to the decode thread if it plays another CUE
+/// track of the same file.
+///
+/// # Arguments
+///
+/// * `shared` - Engine state of the running decode thread
+/// * `track_id` - Track the queue moved to
+/// * `path` - Library path of the track
+///
+/// # Returns
+///
+/// `true` if the decode thread takes over the skip, `false` if the track
+/// has to be loaded on its own.
+pub fn request_cue_jump(shared: &EngineShared, track_id: i64, path: &Path) -> bool {
+    let current = shared.state.lock().current_path.clone();
+    let Some(range) = current
+        .as_deref()
+        .and_then(|current| sibling_cue_range(current, path))
+    else {
+        return false;
+    };
+    let guard = shared.decode_tx.lock();
+    let Some(tx) = guard.as_ref() else {
+        return false;
+    };
+    let command = JumpToCueTrack {
+        track_id,
+        path: path.to_path_buf(),
+        range,
+    };
+    if let Err(e) = tx.try_send(command) {
+        warn!(error = %e, track_id, "Failed to send CUE track jump");
+        return false;
+    }
+    drop(guard);
+    shared.skips.cancel();
+    debug!(
+        track_id,
+        start = range.start,
+        "Skipping within the open file"
+    );
+    true
+}
+
+/// Move the decode loop to another CUE track of its file.
+///
+/// Audio of the old track still in the output buffer is dropped so the
+/// skip is heard at once. If the decoder cannot reach the new range, the
+/// track is loaded on a fresh decode thread instead.
+///
+/// # Arguments
+///
+/// * `ctx` - Decode loop state holding the open decoder
+/// * `engine_shared` - Engine state to update
+/// * `track_id` - ID of the track the loop is playing, updated on success
+/// * `target` - ID, library path and range of the target track
+pub fn jump_to_cue_track(
+    ctx: &mut LoopCtx,
+    engine_shared: &Arc<EngineShared>,
+    track_id: &mut i64,
+    target: (i64, PathBuf, CueRange),
+) {
+    let (new_id, path, range) = target;
+    engine_shared.output.lock().as_ref().map(AudioOutput::flush);
+    ctx.crossfade = None;
+    engine_shared.equalizer.lock().reset();
+    if let Err(e) = ctx.decoder.restrict_to(range) {
+        warn!(error = %e, track_id = new_id, "Failed to seek to CUE track, reopening file");
+        start_playback(engine_shared, new_id, path);
+        return;
+    }
+
+    ctx.elapsed = 0.0;
+    ctx.last_tick = Instant::now();
+    {
+        let mut state = engine_shared.state.lock();
+        state.current_track_id = Some(new_id);
+        state.current_path = Some(path);
+        state.elapsed_seconds = 0.0;
+        state.duration_seconds = ctx.decoder.params().duration_seconds;
+        state.ab_loop = None;
+    }
+    *engine_shared.track_entry.lock() = Started;
+    engine_shared.transitioner.lock().start_playback(new_id);
+    *track_id = new_id;
+
+    info!(track_id = new_id, "Playback moved to CUE track");
+    engine_shared.send_event(&TrackStarted { track_id: new_id });
+    preload_next_upcoming(engine_shared);
+}
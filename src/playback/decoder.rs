@@ -5,29 +5,56 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use symphonia::{
-    core::{
-        audio::GenericAudioBufferRef,
-        codecs::{
-            CodecParameters,
-            audio::{AudioDecoder, AudioDecoderOptions},
+use {
+    num_traits::cast::cast,
+    symphonia::{
+        core::{
+            audio::{Channels, GenericAudioBufferRef, Position},
+            codecs::{
+                CodecParameters,
+                audio::{AudioDecoder, AudioDecoderOptions},
+            },
+            errors::Error::{DecodeError, IoError, ResetRequired},
+            formats::{
+                FormatOptions, FormatReader, SeekMode::Accurate, SeekTo::Time as SeekTime,
+                TrackType::Audio as TypeAudio, probe::Hint,
+            },
+            io::{MediaSourceStream, MediaSourceStreamOptions},
+            meta::MetadataOptions,
+            units::{Time, Timestamp},
         },
-        errors::Error::{DecodeError, IoError, ResetRequired},
-        formats::{
-            FormatOptions, FormatReader, SeekMode::Accurate, SeekTo::Time as SeekTime,
-            TrackType::Audio as TypeAudio, probe::Hint,
-        },
-        io::{MediaSourceStream, MediaSourceStreamOptions},
-        meta::MetadataOptions,
-        units::{Time, Timestamp},
+        default::{get_codecs, get_probe},
     },
-    default::{get_codecs, get_probe},
 };
 
-use crate::playback::DecoderError::{
-    self, DecodeError as PlaybackDecodeError, EndOfStream, OpenError, SeekError, UnsupportedFormat,
+use crate::{
+    library::{
+        cue::{CueRange, split_cue_path},
+        ogg_duration::{checked_ogg_duration, has_ogg_extension},
+    },
+    playback::{
+        DecoderError::{
+            self, DecodeError as PlaybackDecodeError, EndOfStream, OpenError, SeekError,
+            UnsupportedFormat,
+        },
+        layout::AudioLayout,
+    },
 };
 
+/// Speaker positions carrying low-frequency effects.
+const LFE_POSITIONS: Position = Position::LFE1.union(Position::LFE2);
+
+/// Speaker positions above the listener.
+const HEIGHT_POSITIONS: Position = Position::TOP_CENTER
+    .union(Position::TOP_FRONT_LEFT)
+    .union(Position::TOP_FRONT_CENTER)
+    .union(Position::TOP_FRONT_RIGHT)
+    .union(Position::TOP_REAR_LEFT)
+    .union(Position::TOP_REAR_CENTER)
+    .union(Position::TOP_REAR_RIGHT)
+    .union(Position::TOP_SIDE_LEFT)
+    .union(Position::TOP_SIDE_RIGHT);
+
 /// Audio parameters extracted from the decoded stream.
 #[derive(Debug, Clone, Copy)]
 pub struct AudioParams {
@@ -35,6 +62,8 @@ pub struct AudioParams {
     pub sample_rate: u32,
     /// Number of audio channels.
     pub channels: u16,
+    /// Speaker layout of the channels.
+    pub layout: AudioLayout,
     /// Total duration of the track in seconds (0.0 if unknown).
     pub duration_seconds: f64,
 }
@@ -64,17 +93,43 @@ pub struct Decoder {
     track_id: u32,
     /// Audio parameters of the decoded stream.
     params: AudioParams,
+    /// Duration of the whole file in seconds.
+    file_seconds: f64,
+    /// Time range within the file when decoding a CUE track.
+    range: Option<CueRange>,
+    /// Frames still to discard after a seek to land on the exact position.
+    skip_frames: u64,
+    /// Frames left before the end of the range, if bounded.
+    remaining_frames: Option<u64>,
 }
 
 impl Decoder {
     /// Open an audio file and prepare the decoder.
     ///
+    /// Library paths of CUE tracks (`file/#t=start,end`) open the underlying
+    /// file limited to that time range: decoding starts at the range start,
+    /// ends at the range end, and positions are relative to the range.
+    ///
     /// # Errors
     ///
     /// Returns [`DecoderError`] if the file cannot be opened, probed, or
     /// decoded.
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, DecoderError> {
-        let path = path.as_ref();
+        let (file_path, range) = split_cue_path(path.as_ref());
+        let mut decoder = Self::open_file(&file_path)?;
+        if let Some(range) = range {
+            decoder.restrict_to(range)?;
+        }
+        Ok(decoder)
+    }
+
+    /// Open a whole audio file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecoderError`] if the file cannot be opened, probed, or
+    /// decoded.
+    fn open_file(path: &Path) -> Result<Self, DecoderError> {
         let src = File::open(path).map_err(|e| OpenError(format!("{}: {e}", path.display())))?;
 
         let mss = MediaSourceStream::new(Box::new(src), MediaSourceStreamOptions::default());
@@ -114,7 +169,7 @@ impl Decoder {
             .as_ref()
             .map_or(2, |c| u16::try_from(c.count()).unwrap_or(2));
 
-        let duration_seconds = track
+        let header_seconds = track
             .time_base
             .zip(track.duration)
             .and_then(|(tb, dur)| {
@@ -122,10 +177,17 @@ impl Decoder {
                 tb.calc_time(ts)
             })
             .map_or(0.0, |t| t.as_secs_f64());
+        // The Ogg reader stops at the first link of a chained file.
+        let duration_seconds = if has_ogg_extension(path) {
+            checked_ogg_duration(path, header_seconds)
+        } else {
+            header_seconds
+        };
 
         let params = AudioParams {
             sample_rate,
             channels,
+            layout: channel_layout(audio_params.channels.as_ref(), channels),
             duration_seconds,
         };
 
@@ -140,9 +202,29 @@ impl Decoder {
             codec_params,
             track_id,
             params,
+            file_seconds: duration_seconds,
+            range: None,
+            skip_frames: 0,
+            remaining_frames: None,
         })
     }
 
+    /// Limit decoding to a CUE track range and seek to its start.
+    ///
+    /// Also moves an open CUE track to another track of the same file,
+    /// without reopening it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecoderError::SeekError`] if the range start cannot be reached.
+    pub fn restrict_to(&mut self, range: CueRange) -> Result<(), DecoderError> {
+        let end = range.end.unwrap_or(self.file_seconds);
+        self.params.duration_seconds = (end - range.start).max(0.0);
+        self.range = Some(range);
+        self.seek_to(0.0)?;
+        Ok(())
+    }
+
     /// Decode the next batch of interleaved f32 PCM samples.
     ///
     /// Returns an empty `samples` vec when the stream has ended.
@@ -175,6 +257,10 @@ impl Decoder {
     ///
     /// Returns [`DecoderError::DecodeError`] if the packet cannot be decoded.
     fn try_decode_one(&mut self) -> Result<Option<DecodedSamples>, DecoderError> {
+        if self.remaining_frames == Some(0) {
+            return Err(EndOfStream);
+        }
+
         let packet = match self.format.next_packet() {
             Ok(Some(packet)) => packet,
             Ok(None) => return Err(EndOfStream),
@@ -194,12 +280,37 @@ impl Decoder {
 
         let mut samples = Vec::new();
         copy_interleaved_f32(&decoded, &mut samples);
+        if self.range.is_some() {
+            self.trim_to_range(&mut samples);
+        }
+        if samples.is_empty() {
+            return Ok(None);
+        }
         Ok(Some(DecodedSamples {
             samples,
             params: self.params,
         }))
     }
 
+    /// Drop samples before the seek target and past the range end.
+    fn trim_to_range(&mut self, samples: &mut Vec<f32>) {
+        let channels = usize::from(self.params.channels.max(1));
+        let frames = u64::try_from(samples.len() / channels).unwrap_or(0);
+        let skip = self.skip_frames.min(frames);
+        self.skip_frames -= skip;
+        let keep = self
+            .remaining_frames
+            .map_or(frames - skip, |remaining| remaining.min(frames - skip));
+        if let Some(remaining) = self.remaining_frames.as_mut() {
+            *remaining -= keep;
+        }
+
+        let start = usize::try_from(skip).unwrap_or(0) * channels;
+        let len = usize::try_from(keep).unwrap_or(0) * channels;
+        samples.copy_within(start..start + len, 0);
+        samples.truncate(len);
+    }
+
     /// Returns the audio parameters of the decoded stream.
     #[must_use]
     pub fn params(&self) -> AudioParams {
@@ -209,13 +320,16 @@ impl Decoder {
     /// Seek to a position in seconds.
     ///
     /// Returns the actual position seeked to (may differ slightly from
-    /// the requested position due to codec frame boundaries).
+    /// the requested position due to codec frame boundaries). For CUE
+    /// tracks the position is relative to the track start and is reached
+    /// exactly by discarding the frames before it.
     ///
     /// # Errors
     ///
     /// Returns [`DecoderError::SeekError`] if seeking fails.
     pub fn seek_to(&mut self, seconds: f64) -> Result<f64, DecoderError> {
-        let time = Time::try_from_secs_f64(seconds)
+        let target = self.range.map_or(0.0, |r| r.start) + seconds;
+        let time = Time::try_from_secs_f64(target)
             .ok_or_else(|| DecoderError::SeekError("invalid seek time".into()))?;
 
         let seeked_to = self
@@ -242,9 +356,17 @@ impl Decoder {
             .default_track(TypeAudio)
             .and_then(|t| t.time_base)
             .and_then(|tb| tb.calc_time(seeked_to.actual_ts))
-            .map_or(seconds, |t| t.as_secs_f64());
+            .map_or(target, |t| t.as_secs_f64());
 
-        Ok(actual_seconds)
+        let Some(range) = self.range else {
+            return Ok(actual_seconds);
+        };
+        let sample_rate = f64::from(self.params.sample_rate);
+        self.skip_frames = frames_in((target - actual_seconds).max(0.0), sample_rate);
+        self.remaining_frames = range
+            .end
+            .map(|end| frames_in((end - target).max(0.0), sample_rate));
+        Ok(seconds)
     }
 }
 
@@ -373,6 +495,28 @@ impl Default for DualDecoder {
     }
 }
 
+/// Speaker layout of a stream's channels.
+///
+/// Positioned channels are split into ear-level, LFE and height speakers;
+/// Ambisonics are spatial. Streams without positions fall back to their
+/// channel count.
+fn channel_layout(channels: Option<&Channels>, count: u16) -> AudioLayout {
+    match channels {
+        Some(Channels::Positioned(positions)) => AudioLayout::from_speakers(
+            positions.bits().count_ones(),
+            positions.intersection(LFE_POSITIONS).bits().count_ones(),
+            positions.intersection(HEIGHT_POSITIONS).bits().count_ones(),
+        ),
+        Some(Channels::Ambisonic(_)) => AudioLayout::Spatial,
+        _ => AudioLayout::from_count(u32::from(count)),
+    }
+}
+
+/// Convert a duration in seconds to a whole number of frames.
+fn frames_in(seconds: f64, sample_rate: f64) -> u64 {
+    cast((seconds * sample_rate).round()).unwrap_or(0)
+}
+
 /// Copy decoded audio buffer to interleaved f32 samples.
 fn copy_interleaved_f32(buf: &GenericAudioBufferRef<'_>, out: &mut Vec<f32>) {
     buf.copy_to_vec_interleaved(out);
@@ -391,10 +535,13 @@ mod tests {
         tempfile::NamedTempFile,
     };
 
-    use crate::playback::{
-        DecoderError::OpenError,
-        decoder::{Decoder, DualDecoder},
-        write_wav_header,
+    use crate::{
+        library::cue::{CueRange, cue_track_path},
+        playback::{
+            DecoderError::OpenError,
+            decoder::{Decoder, DualDecoder},
+            write_wav_header,
+        },
     };
 
     fn write_minimal_wav(path: &Path) -> Result<()> {
@@ -405,6 +552,54 @@ mod tests {
         Ok(())
     }
 
+    /// Write one second of 8 kHz mono silence.
+    fn write_second_wav(path: &Path) -> Result<()> {
+        let mut f = File::create(path)?;
+        write_wav_header(&mut f, 1, 8000, 16, 16000)?;
+        f.write_all(&[0u8; 16000])?;
+        Ok(())
+    }
+
+    /// Count the frames decoded until the end of the stream.
+    fn decode_all(decoder: &mut Decoder) -> AnyhowResult<usize> {
+        let mut frames = 0;
+        let mut batch = decoder.decode_next()?;
+        while !batch.samples.is_empty() {
+            frames += batch.samples.len();
+            batch = decoder.decode_next()?;
+        }
+        Ok(frames)
+    }
+
+    #[test]
+    fn cue_track_moves_to_sibling_range() -> AnyhowResult<()> {
+        let tmp = NamedTempFile::new()?;
+        write_second_wav(tmp.path())?;
+        let first = cue_track_path(
+            tmp.path(),
+            CueRange {
+                start: 0.0,
+                end: Some(0.25),
+            },
+        );
+        let mut decoder = Decoder::open(&first)?;
+        if decode_all(&mut decoder)? != 2000 {
+            bail!("the first track must end at its range end");
+        }
+
+        decoder.restrict_to(CueRange {
+            start: 0.5,
+            end: None,
+        })?;
+        if (decoder.params().duration_seconds - 0.5).abs() > f64::EPSILON {
+            bail!("an open-ended track lasts to the end of the file");
+        }
+        if !(3990..=4000).contains(&decode_all(&mut decoder)?) {
+            bail!("the second track must play from its range start");
+        }
+        Ok(())
+    }
+
     #[test]
     fn open_nonexistent_file_returns_error() {
         let result = Decoder::open("/nonexistent/path/audio.flac");
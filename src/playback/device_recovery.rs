@@ -0,0 +1,155 @@
+//! Recovery from losing the audio device during playback.
+//!
+//! When the output stream reports an error, typically because a USB DAC
+//! was unplugged, playback pauses and the decode thread keeps the track
+//! open while it looks for an output device. The output is reopened on the
+//! default device as soon as one is available, still paused, so the track
+//! resumes where it stopped instead of suddenly playing on other speakers.
+
+use std::{
+    sync::{Arc, atomic::Ordering::Relaxed},
+    time::{Duration, Instant},
+};
+
+use {
+    rtrb::Producer,
+    tracing::{info, warn},
+};
+
+use crate::playback::{
+    buffer::ring_capacity,
+    engine::{
+        EngineShared,
+        PlaybackEvent::{DeviceLost, OutputOpened, Paused},
+        PlaybackStatus::{Paused as StatusPaused, Playing},
+    },
+    output::{AudioOutput, is_device_available},
+    pipeline::OutputConfig,
+    worker::configure_output,
+};
+
+/// Delay between attempts to reopen the output while no device works.
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Device recovery state of a decode loop.
+#[derive(Debug, Default)]
+pub struct DeviceRecovery {
+    /// When to try reopening the output next, while it is lost.
+    retry_at: Option<Instant>,
+}
+
+impl DeviceRecovery {
+    /// Whether the output is lost and nothing can be played.
+    #[must_use]
+    pub const fn waiting(&self) -> bool {
+        self.retry_at.is_some()
+    }
+
+    /// Notice a lost device and try to reopen the output.
+    ///
+    /// # Arguments
+    ///
+    /// * `engine_shared` - Engine state holding the output and its loss flag
+    /// * `output` - Configuration of the output the decode loop feeds
+    ///
+    /// # Returns
+    ///
+    /// The producer and configuration of the new output once one is open.
+    pub fn poll(
+        &mut self,
+        engine_shared: &Arc<EngineShared>,
+        output: OutputConfig,
+    ) -> Option<(Producer<f32>, OutputConfig)> {
+        if engine_shared.device_lost.swap(false, Relaxed) {
+            pause_for_lost_device(engine_shared);
+            self.retry_at = Some(Instant::now());
+        }
+        let retry_at = self.retry_at?;
+        let now = Instant::now();
+        if now < retry_at {
+            return None;
+        }
+        self.retry_at = Some(now + RETRY_INTERVAL);
+        if !is_device_available() {
+            return None;
+        }
+        let reopened = reopen_output(engine_shared, output)?;
+        self.retry_at = None;
+        Some(reopened)
+    }
+}
+
+/// Pause playback and drop the output of a lost device.
+fn pause_for_lost_device(engine_shared: &EngineShared) {
+    *engine_shared.output.lock() = None;
+    let was_playing = {
+        let mut state = engine_shared.state.lock();
+        let was_playing = state.status == Playing;
+        if was_playing {
+            state.status = StatusPaused;
+        }
+        was_playing
+    };
+    warn!(
+        was_playing,
+        "Audio device lost, waiting for an output device"
+    );
+    engine_shared.send_event(&DeviceLost {
+        error: "playback paused until an output device is available".to_string(),
+    });
+    if was_playing {
+        engine_shared.send_event(&Paused);
+    }
+}
+
+/// Open a new output on the default device.
+///
+/// The old sample rate is kept when the device supports it, matching the
+/// rate the decode loop resamples to. The output starts paused unless the
+/// user resumed playback while no device was available.
+///
+/// # Returns
+///
+/// `None` if the device cannot be opened yet.
+fn reopen_output(
+    engine_shared: &Arc<EngineShared>,
+    output: OutputConfig,
+) -> Option<(Producer<f32>, OutputConfig)> {
+    let (mut new_output, producer) = match AudioOutput::open(
+        ring_capacity(output.buffer_frames),
+        &engine_shared.device_lost,
+        &engine_shared.levels,
+        Some(output.device_sample_rate),
+    ) {
+        Ok(pair) => pair,
+        Err(e) => {
+            warn!(error = %e, "Failed to reopen audio output, retrying");
+            return None;
+        }
+    };
+
+    let state = engine_shared.state.lock().clone();
+    configure_output(&mut new_output, &state);
+    if state.status == StatusPaused {
+        new_output.pause();
+    }
+    let reopened = OutputConfig {
+        device_sample_rate: new_output.sample_rate(),
+        channels: new_output.channels(),
+        ..output
+    };
+    *engine_shared.device_sample_rate.lock() = reopened.device_sample_rate;
+    *engine_shared.output.lock() = Some(new_output);
+
+    info!(
+        sample_rate = reopened.device_sample_rate,
+        channels = reopened.channels,
+        status = ?state.status,
+        "Audio output reopened after device loss"
+    );
+    engine_shared.send_event(&OutputOpened {
+        previous_device_rate: output.device_sample_rate,
+        device_sample_rate: reopened.device_sample_rate,
+    });
+    Some((producer, reopened))
+}
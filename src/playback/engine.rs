@@ -7,22 +7,53 @@ use std::{
     time::{Duration, Instant},
 };
 
-use {async_channel::Sender, parking_lot::Mutex, tokio::sync::mpsc::Sender as MpscSender};
+use {
+    async_channel::Sender, parking_lot::Mutex, tokio::sync::mpsc::Sender as MpscSender,
+    tracing::info,
+};
 
-use crate::playback::{
-    gapless::{
-        GaplessMode::{self, Enabled},
-        GaplessTransitioner,
-    },
-    output::{
-        AudioOutput,
-        OutputMode::{self, Resampled},
+use crate::{
+    library::cue::CueRange,
+    playback::{
+        ab_loop::AbLoop,
+        buffer::DEFAULT_BUFFER_FRAMES,
+        equalizer::Equalizer,
+        fade::DEFAULT_FADE_MS,
+        gapless::{
+            GaplessMode::{self, Disabled, Enabled},
+            GaplessTransitioner, TrackEntry,
+        },
+        layout::AudioLayout,
+        level::{LevelMeter, Levels},
+        output::{
+            AudioOutput,
+            OutputMode::{self, BitPerfect, Resampled},
+        },
+        queue::{PlaybackQueue, RepeatMode},
+        queue_undo::ReplacedQueue,
+        resampler::ResampleQuality,
+        skip::SkipCoalescer,
+        stereo::DownmixMode,
     },
-    queue::PlaybackQueue,
 };
 
+/// Slowest playback speed accepted by the engine.
+pub const MIN_PLAYBACK_RATE: f64 = 0.5;
+
+/// Fastest playback speed accepted by the engine.
+pub const MAX_PLAYBACK_RATE: f64 = 2.0;
+
 /// Commands sent to the decode task.
 pub enum DecodeCommand {
+    /// Move to another CUE track of the file being decoded.
+    JumpToCueTrack {
+        /// ID of the target track.
+        track_id: i64,
+        /// Library path of the target track.
+        path: PathBuf,
+        /// Time range of the target track within the file.
+        range: CueRange,
+    },
     /// Seek to a position in seconds.
     Seek(f64),
     /// Pause the audio output stream.
@@ -58,10 +89,20 @@ pub struct EngineShared {
     pub device_sample_rate: Mutex<u32>,
     /// Current track sample rate, updated on track start.
     pub track_sample_rate: Mutex<u32>,
+    /// How the current track took over from the previous one.
+    pub track_entry: Mutex<TrackEntry>,
     /// Shared flag set when the audio device is lost.
     pub device_lost: Arc<AtomicBool>,
     /// Gapless transitioner for seamless track transitions.
     pub transitioner: Mutex<GaplessTransitioner>,
+    /// Graphic equalizer applied on the decode thread.
+    pub equalizer: Mutex<Equalizer>,
+    /// Levels published by the audio callback for the level meter.
+    pub levels: Arc<LevelMeter>,
+    /// Next/previous target waiting for a burst of skips to settle.
+    pub skips: SkipCoalescer<(i64, PathBuf)>,
+    /// Queue replaced by the last `play_queue`, kept to undo it.
+    pub replaced_queue: Mutex<Option<ReplacedQueue>>,
 }
 
 impl EngineShared {
@@ -78,6 +119,20 @@ impl EngineShared {
         });
     }
 
+    /// Emit a position tick for the current position right away.
+    ///
+    /// Regular ticks are throttled, so pausing and resuming send one of
+    /// their own to show the exact point playback continues from.
+    pub fn send_position(&self) {
+        let state = self.state.lock();
+        let event = PlaybackEvent::PositionTick {
+            elapsed_seconds: state.elapsed_seconds,
+            duration_seconds: state.duration_seconds,
+        };
+        drop(state);
+        self.send_event(&event);
+    }
+
     /// Update elapsed seconds and optionally emit a position tick.
     pub fn update_elapsed(&self, elapsed: f64, last_tick: &mut Instant) {
         let mut state = self.state.lock();
@@ -92,6 +147,14 @@ impl EngineShared {
             *last_tick = Instant::now();
         }
     }
+
+    /// Pre-buffer the next track while gapless playback or crossfade is on.
+    pub fn sync_prebuffering(&self) {
+        let state = self.state.lock();
+        let prebuffer = state.gapless_mode == Enabled || state.crossfade_ms > 0;
+        drop(state);
+        self.transitioner.lock().set_enabled(prebuffer);
+    }
 }
 
 impl Default for EngineShared {
@@ -106,8 +169,13 @@ impl Default for EngineShared {
             track_paths: Mutex::new(HashMap::new()),
             device_sample_rate: Mutex::new(44100),
             track_sample_rate: Mutex::new(44100),
+            track_entry: Mutex::new(TrackEntry::Started),
             device_lost: Arc::new(AtomicBool::new(false)),
             transitioner: Mutex::new(GaplessTransitioner::new()),
+            equalizer: Mutex::new(Equalizer::new()),
+            levels: Arc::new(LevelMeter::default()),
+            skips: SkipCoalescer::default(),
+            replaced_queue: Mutex::new(None),
         }
     }
 }
@@ -137,8 +205,24 @@ impl PlaybackEngine {
     }
 
     /// Pre-load track ID to file path mappings for queue navigation.
-    pub fn set_track_paths(&self, paths: HashMap<i64, PathBuf>) {
-        *self.shared.track_paths.lock() = paths;
+    ///
+    /// Paths of the tracks in the queue are kept, so a queue about to be
+    /// replaced can still be restored.
+    pub fn set_track_paths(&self, mut paths: HashMap<i64, PathBuf>) {
+        let queued = self.shared.queue.tracks();
+        let mut known = self.shared.track_paths.lock();
+        for (id, path) in queued
+            .into_iter()
+            .filter_map(|id| known.remove(&id).map(|path| (id, path)))
+        {
+            paths.entry(id).or_insert(path);
+        }
+        *known = paths;
+    }
+
+    /// Add file paths for more track IDs, keeping the known ones.
+    pub fn add_track_paths(&self, paths: HashMap<i64, PathBuf>) {
+        self.shared.track_paths.lock().extend(paths);
     }
 
     /// Returns a reference to the playback queue.
@@ -159,6 +243,22 @@ impl PlaybackEngine {
     pub fn reset_album_id(&self) {
         self.shared.state.lock().current_album_id = -1;
     }
+
+    /// Turn level measurement in the audio callback on or off.
+    pub fn set_level_meter_enabled(&self, enabled: bool) {
+        info!(enabled, "Level meter toggled");
+        self.shared.levels.set_enabled(enabled);
+    }
+
+    /// Take the levels played since the last call.
+    ///
+    /// # Returns
+    ///
+    /// `None` while the meter is off or the output is bit-perfect.
+    #[must_use]
+    pub fn take_levels(&self) -> Option<Levels> {
+        self.shared.levels.take()
+    }
 }
 
 /// Events emitted by the playback engine.
@@ -179,6 +279,11 @@ pub enum PlaybackEvent {
         /// New set of track IDs in the queue.
         track_ids: Vec<i64>,
     },
+    /// Playing new tracks replaced a queue that can be restored.
+    QueueReplaced {
+        /// Number of tracks in the replaced queue.
+        previous_len: usize,
+    },
     /// Playback was paused.
     Paused,
     /// Playback was resumed.
@@ -195,7 +300,18 @@ pub enum PlaybackEvent {
         /// New output mode.
         mode: OutputMode,
     },
+    /// An output was opened for the current track, so the rates of the
+    /// signal path are now known.
+    OutputOpened {
+        /// Sample rate of the output used for the previous track, or `0`
+        /// when playback just started.
+        previous_device_rate: u32,
+        /// Sample rate the new output runs at in Hz.
+        device_sample_rate: u32,
+    },
     /// Audio device was lost during playback.
+    ///
+    /// Playback pauses until the output is reopened on another device.
     DeviceLost {
         /// Error description.
         error: String,
@@ -205,11 +321,26 @@ pub enum PlaybackEvent {
         /// Error description.
         error: String,
     },
+    /// The repeat mode changed.
+    RepeatModeChanged {
+        /// New repeat mode.
+        mode: RepeatMode,
+    },
     /// Gapless playback was enabled or disabled.
     GaplessEnabledChanged {
         /// Whether gapless is now enabled.
         enabled: bool,
     },
+    /// An A-B loop was set or cleared.
+    AbLoopChanged {
+        /// New loop, or `None` once cleared.
+        ab_loop: Option<AbLoop>,
+    },
+    /// Playback speed changed.
+    PlaybackRateChanged {
+        /// Speed now applied to the current track (`1.0` is normal speed).
+        rate: f64,
+    },
     /// Seeked to a new position.
     Seeked {
         /// New position in seconds.
@@ -243,10 +374,89 @@ pub struct PlaybackState {
     pub elapsed_seconds: f64,
     /// Total track duration in seconds (0.0 if unknown).
     pub duration_seconds: f64,
+    /// Speaker layout of the playing stream, once its decoder is open.
+    pub channel_layout: Option<AudioLayout>,
     /// Gapless playback mode.
     pub gapless_mode: GaplessMode,
     /// Output mode: resampled (software volume) or bit-perfect (hardware volume).
     pub output_mode: OutputMode,
+    /// Crossfade window between tracks in milliseconds (`0` disables).
+    pub crossfade_ms: u32,
+    /// Silence after a track that ends with a hard cut, in milliseconds
+    /// (`0` disables).
+    pub inter_track_gap_ms: u32,
+    /// Level in dBFS below which leading silence is skipped (`None`
+    /// disables).
+    pub leading_silence_db: Option<i32>,
+    /// Fade applied on play, pause, and stop in milliseconds (`0` disables).
+    pub fade_ms: u32,
+    /// Switch the device to each track's sample rate when it supports it.
+    pub follow_source_rate: bool,
+    /// Quality profile for sample-rate conversion.
+    pub resample_quality: ResampleQuality,
+    /// Size of the output ring buffer in frames, a power of two.
+    pub buffer_frames: u32,
+    /// How the left and right channels reach the device.
+    pub downmix: DownmixMode,
+    /// Left/right balance from `-1.0` (left only) to `1.0` (right only).
+    pub balance: f64,
+    /// A-B loop set on the current or a previous track.
+    pub ab_loop: Option<AbLoop>,
+    /// Playback speed last chosen (`1.0` is normal speed).
+    pub playback_rate: f64,
+    /// Track the chosen speed was set on.
+    pub playback_rate_track: Option<i64>,
+    /// Keep the chosen speed when another track starts.
+    pub remember_playback_rate: bool,
+}
+
+impl PlaybackState {
+    /// The A-B loop, if it belongs to the track playing now.
+    #[must_use]
+    pub fn active_ab_loop(&self) -> Option<AbLoop> {
+        self.ab_loop
+            .filter(|l| Some(l.track_id) == self.current_track_id)
+    }
+
+    /// Silence to leave before the next track after a hard cut.
+    ///
+    /// The gap only applies while gapless playback and crossfade are both
+    /// off, since it would defeat either of them.
+    #[must_use]
+    pub fn inter_track_gap(&self) -> Option<Duration> {
+        (self.inter_track_gap_ms > 0 && self.gapless_mode == Disabled && self.crossfade_ms == 0)
+            .then(|| Duration::from_millis(u64::from(self.inter_track_gap_ms)))
+    }
+
+    /// Threshold for skipping the leading silence of a track entered by
+    /// `entry`.
+    ///
+    /// Bit-perfect output plays every sample, and a track following another
+    /// with gapless playback on keeps its opening, which belongs to the flow
+    /// of the album.
+    #[must_use]
+    pub fn leading_silence_threshold(&self, entry: TrackEntry) -> Option<i32> {
+        let continues = match entry {
+            TrackEntry::Started => false,
+            TrackEntry::Seamless => true,
+            TrackEntry::HardCut => self.gapless_mode != Disabled,
+        };
+        self.leading_silence_db
+            .filter(|_| self.output_mode != BitPerfect && !continues)
+    }
+
+    /// Speed applied to the track playing now.
+    ///
+    /// A chosen speed only carries over to later tracks when
+    /// `remember_playback_rate` is set; otherwise they play at `1.0`.
+    #[must_use]
+    pub fn effective_playback_rate(&self) -> f64 {
+        if self.remember_playback_rate || self.playback_rate_track == self.current_track_id {
+            self.playback_rate
+        } else {
+            1.0
+        }
+    }
 }
 
 impl Default for PlaybackState {
@@ -260,8 +470,22 @@ impl Default for PlaybackState {
             muted: MuteState::Unmuted,
             elapsed_seconds: 0.0,
             duration_seconds: 0.0,
+            channel_layout: None,
             gapless_mode: Enabled,
             output_mode: Resampled,
+            crossfade_ms: 0,
+            inter_track_gap_ms: 0,
+            leading_silence_db: None,
+            fade_ms: DEFAULT_FADE_MS,
+            follow_source_rate: false,
+            resample_quality: ResampleQuality::default(),
+            buffer_frames: DEFAULT_BUFFER_FRAMES,
+            downmix: DownmixMode::Stereo,
+            balance: 0.0,
+            ab_loop: None,
+            playback_rate: 1.0,
+            playback_rate_track: None,
+            remember_playback_rate: false,
         }
     }
 }
@@ -279,14 +503,28 @@ pub enum PlaybackStatus {
 
 #[cfg(test)]
 mod tests {
-    use std::{collections::HashMap, path::PathBuf};
+    use std::{collections::HashMap, path::PathBuf, time::Instant};
 
-    use anyhow::{Result, anyhow, bail};
+    use anyhow::{Result, anyhow, bail, ensure};
 
     use crate::playback::{
-        PlaybackError::{NoDeviceAvailable, Output, QueueEmpty, TrackNotFound},
+        PlaybackError::{
+            EqBandOutOfRange, InvalidAbLoop, InvalidPlaybackRate, NoDeviceAvailable,
+            NoReplacedQueue, Output, QueueEmpty, QueuePositionOutOfRange, TrackNotFound,
+        },
         control::PlaybackController,
-        engine::{PlaybackEngine, PlaybackStatus::Stopped},
+        engine::{
+            PlaybackEngine,
+            PlaybackEvent::{Paused, PositionTick, QueueReplaced, Resumed},
+            PlaybackState,
+            PlaybackStatus::{Paused as StatusPaused, Playing, Stopped},
+        },
+        equalizer::BAND_COUNT,
+        gapless::{
+            GaplessMode::Disabled,
+            TrackEntry::{HardCut, Seamless, Started},
+        },
+        output::OutputMode::BitPerfect,
     };
 
     fn setup_queue(engine: &PlaybackEngine, track_ids: Vec<i64>) {
@@ -306,6 +544,41 @@ mod tests {
         assert!((state.volume - 1.0).abs() < f64::EPSILON);
     }
 
+    #[test]
+    fn leading_silence_skips_only_fresh_starts() {
+        let mut state = PlaybackState {
+            leading_silence_db: Some(-60),
+            ..PlaybackState::default()
+        };
+        assert_eq!(
+            state.leading_silence_threshold(Started),
+            Some(-60),
+            "A fresh start skips silence"
+        );
+        assert_eq!(
+            state.leading_silence_threshold(HardCut),
+            None,
+            "Gapless is on by default"
+        );
+        assert_eq!(
+            state.leading_silence_threshold(Seamless),
+            None,
+            "Seamless transitions keep the opening"
+        );
+        state.gapless_mode = Disabled;
+        assert_eq!(
+            state.leading_silence_threshold(HardCut),
+            Some(-60),
+            "Hard cuts skip silence without gapless"
+        );
+        state.output_mode = BitPerfect;
+        assert_eq!(
+            state.leading_silence_threshold(Started),
+            None,
+            "Bit-perfect plays every sample"
+        );
+    }
+
     #[test]
     fn set_volume_clamps() -> Result<()> {
         let engine = PlaybackEngine::new();
@@ -340,6 +613,57 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn pause_and_resume_keep_position() -> Result<()> {
+        let engine = PlaybackEngine::new();
+        {
+            let mut state = engine.shared.state.lock();
+            state.current_track_id = Some(1);
+            state.status = Playing;
+            state.duration_seconds = 180.0;
+        }
+        let events = engine.subscribe();
+        let mut last_tick = Instant::now();
+        engine.shared.update_elapsed(42.5, &mut last_tick);
+
+        engine.toggle_pause().map_err(|e| anyhow!("{e}"))?;
+        if engine.state().status != StatusPaused {
+            bail!("engine should be paused");
+        }
+        engine.toggle_pause().map_err(|e| anyhow!("{e}"))?;
+        if engine.state().status != Playing {
+            bail!("engine should be playing again");
+        }
+
+        let mut received = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            received.push(event);
+        }
+        let positions: Vec<f64> = received
+            .iter()
+            .filter_map(|event| match event {
+                PositionTick {
+                    elapsed_seconds, ..
+                } => Some(*elapsed_seconds),
+                _ => None,
+            })
+            .collect();
+        ensure!(
+            received.iter().any(|e| matches!(e, Paused))
+                && received.iter().any(|e| matches!(e, Resumed)),
+            "pause and resume are announced"
+        );
+        ensure!(
+            positions == [42.5, 42.5],
+            "position ticks stay at the pause point"
+        );
+        ensure!(
+            (engine.state().elapsed_seconds - 42.5).abs() < f64::EPSILON,
+            "resume continues from the pause point"
+        );
+        Ok(())
+    }
+
     #[test]
     fn play_queue_returns_error_when_empty() {
         let engine = PlaybackEngine::new();
@@ -366,6 +690,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn replaced_queue_is_restored_once() {
+        let engine = PlaybackEngine::new();
+        engine.queue().set_queue(vec![1, 2, 3]);
+        let events = engine.subscribe();
+        assert!(
+            matches!(engine.play_queue(vec![4]), Err(TrackNotFound(4))),
+            "The new queue has no paths"
+        );
+        let mut replaced = false;
+        while let Ok(event) = events.try_recv() {
+            replaced |= matches!(event, QueueReplaced { previous_len: 3 });
+        }
+        assert!(replaced, "The replacement is announced");
+        assert!(
+            engine.undo_queue_replace().is_ok(),
+            "A stopped queue is restored without playing"
+        );
+        assert_eq!(engine.queue().tracks(), [1, 2, 3], "Queue restored");
+        assert!(
+            matches!(engine.undo_queue_replace(), Err(NoReplacedQueue)),
+            "Only the last replacement can be undone"
+        );
+    }
+
     #[test]
     fn play_track_returns_error_when_not_found() {
         let engine = PlaybackEngine::new();
@@ -394,4 +743,97 @@ mod tests {
         let engine = PlaybackEngine::new();
         assert!(matches!(engine.previous_track(), Err(QueueEmpty)));
     }
+
+    #[test]
+    fn move_in_queue_reorders_and_rejects_out_of_range() -> Result<()> {
+        let engine = PlaybackEngine::new();
+        setup_queue(&engine, vec![1, 2, 3]);
+        engine.move_in_queue(2, 0).map_err(|e| anyhow!("{e}"))?;
+        if engine.queue().tracks() != vec![3, 1, 2] {
+            bail!("queue should be reordered to [3, 1, 2]");
+        }
+        if !matches!(engine.move_in_queue(0, 3), Err(QueuePositionOutOfRange(3))) {
+            bail!("moving past the end should be rejected");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn set_ab_loop_validates_and_binds_to_track() -> Result<()> {
+        let engine = PlaybackEngine::new();
+        engine
+            .set_ab_loop(Some((1.0, 2.0)))
+            .map_err(|e| anyhow!("{e}"))?;
+        if engine.state().ab_loop.is_some() {
+            bail!("loop should be ignored while stopped");
+        }
+
+        engine.shared.state.lock().current_track_id = Some(7);
+        if !matches!(
+            engine.set_ab_loop(Some((5.0, 5.0))),
+            Err(InvalidAbLoop { .. })
+        ) {
+            bail!("empty loop should be rejected");
+        }
+        engine
+            .set_ab_loop(Some((5.0, 9.0)))
+            .map_err(|e| anyhow!("{e}"))?;
+        let Some(ab_loop) = engine.state().active_ab_loop() else {
+            bail!("loop should apply to the current track");
+        };
+        if ab_loop.track_id != 7 {
+            bail!("loop should belong to track 7");
+        }
+
+        engine.shared.state.lock().current_track_id = Some(8);
+        if engine.state().active_ab_loop().is_some() {
+            bail!("loop should not apply to another track");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn playback_rate_clamps_and_resets_on_next_track() -> Result<()> {
+        let engine = PlaybackEngine::new();
+        engine.shared.state.lock().current_track_id = Some(7);
+        if !matches!(
+            engine.set_playback_rate(f64::NAN),
+            Err(InvalidPlaybackRate(_))
+        ) {
+            bail!("NaN rate should be rejected");
+        }
+        engine.set_playback_rate(5.0).map_err(|e| anyhow!("{e}"))?;
+        if engine.state().effective_playback_rate() > 2.0 {
+            bail!("rate should be clamped to 2.0");
+        }
+
+        engine.shared.state.lock().current_track_id = Some(8);
+        if engine.state().effective_playback_rate() > 1.0 {
+            bail!("next track should play at normal speed");
+        }
+
+        engine
+            .set_remember_playback_rate(true)
+            .map_err(|e| anyhow!("{e}"))?;
+        if engine.state().effective_playback_rate() < 2.0 {
+            bail!("remembered rate should carry over");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn set_eq_band_rejects_out_of_range() {
+        let engine = PlaybackEngine::new();
+        assert!(
+            matches!(engine.set_eq_band(0, 3.0), Ok(())),
+            "Band 0 must exist"
+        );
+        assert!(
+            matches!(
+                engine.set_eq_band(BAND_COUNT, 3.0),
+                Err(EqBandOutOfRange(_))
+            ),
+            "Index past the last band must be rejected"
+        );
+    }
 }
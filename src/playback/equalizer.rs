@@ -0,0 +1,380 @@
+//! 10-band graphic equalizer built from biquad peaking filters.
+//!
+//! Bands sit at the ISO octave centre frequencies from 31 Hz to 16 kHz.
+//! The equalizer runs on the decode thread after resampling, at the device
+//! sample rate. It is skipped entirely while disabled, while every band is
+//! flat, or in bit-perfect mode, so samples pass through untouched.
+//!
+//! Moving a band updates the filter coefficients in place and keeps the
+//! filter history, so adjusting the equalizer during playback does not
+//! click. The input is attenuated by the peak boost of the combined
+//! response, so boosted bands cannot push full-scale material into
+//! clipping.
+
+use {
+    num_traits::cast::cast,
+    serde::{Deserialize, Serialize},
+};
+
+use crate::playback::biquad::{Biquad, BiquadHistory, peak_gain};
+
+/// Number of equalizer bands.
+pub const BAND_COUNT: usize = 10;
+
+/// Centre frequency of each band in Hz.
+pub const BAND_FREQUENCIES: [f64; BAND_COUNT] = [
+    31.0, 62.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0,
+];
+
+/// Maximum boost or cut per band in decibels.
+pub const MAX_GAIN_DB: f64 = 12.0;
+
+/// Quality factor giving roughly one-octave-wide bands.
+const BAND_Q: f64 = 1.41;
+
+/// Named band-gain presets offered in preferences.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EqPreset {
+    /// All bands at 0 dB.
+    #[default]
+    Flat,
+    /// Lifted low end.
+    BassBoost,
+    /// Presence lift around the vocal range.
+    Vocal,
+    /// User-edited band gains.
+    Custom,
+}
+
+impl EqPreset {
+    /// All presets in display order.
+    pub const ALL: [Self; 4] = [Self::Flat, Self::BassBoost, Self::Vocal, Self::Custom];
+
+    /// Band gains for this preset, or `None` for [`EqPreset::Custom`].
+    #[must_use]
+    pub const fn gains_db(self) -> Option<[f64; BAND_COUNT]> {
+        match self {
+            Self::Flat => Some([0.0; BAND_COUNT]),
+            Self::BassBoost => Some([6.0, 5.0, 4.0, 2.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
+            Self::Vocal => Some([-2.0, -2.0, -1.0, 0.0, 2.0, 4.0, 4.0, 2.0, 0.0, -1.0]),
+            Self::Custom => None,
+        }
+    }
+
+    /// Human-readable label for preference rows.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Flat => "Flat",
+            Self::BassBoost => "Bass Boost",
+            Self::Vocal => "Vocal",
+            Self::Custom => "Custom",
+        }
+    }
+}
+
+/// Stateful equalizer processing interleaved samples.
+#[derive(Debug, Clone)]
+pub struct Equalizer {
+    /// Whether the equalizer is switched on.
+    enabled: bool,
+    /// Gain of each band in decibels.
+    gains_db: [f64; BAND_COUNT],
+    /// Sample rate the coefficients were computed for.
+    sample_rate: u32,
+    /// Number of interleaved channels.
+    channels: usize,
+    /// Coefficients of each band, the identity for flat bands.
+    filters: [Biquad; BAND_COUNT],
+    /// Linear gain applied to the input to leave headroom for boosts.
+    preamp: f64,
+    /// Filter history, indexed by channel then band.
+    history: Vec<[BiquadHistory; BAND_COUNT]>,
+    /// Whether samples bypassed the filters since the history was written.
+    history_stale: bool,
+}
+
+impl Equalizer {
+    /// Create a disabled, flat equalizer.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            gains_db: [0.0; BAND_COUNT],
+            sample_rate: 0,
+            channels: 0,
+            filters: [Biquad::IDENTITY; BAND_COUNT],
+            preamp: 1.0,
+            history: Vec::new(),
+            history_stale: false,
+        }
+    }
+
+    /// Switch the equalizer on or off.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.reset();
+    }
+
+    /// Returns `true` if the equalizer is switched on.
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Set the gain of band `index`, clamped to [`MAX_GAIN_DB`].
+    ///
+    /// Returns `false` if `index` is not a valid band.
+    pub fn set_band(&mut self, index: usize, gain_db: f64) -> bool {
+        let Some(gain) = self.gains_db.get_mut(index) else {
+            return false;
+        };
+        *gain = gain_db.clamp(-MAX_GAIN_DB, MAX_GAIN_DB);
+        self.update_coefficients();
+        true
+    }
+
+    /// Current gain of every band in decibels.
+    #[must_use]
+    pub const fn gains_db(&self) -> [f64; BAND_COUNT] {
+        self.gains_db
+    }
+
+    /// Returns `true` if processing would change the signal.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.enabled && self.gains_db.iter().any(|gain| gain.abs() >= f64::EPSILON)
+    }
+
+    /// Configure for `sample_rate` and `channels`, recomputing coefficients
+    /// only when either changed.
+    pub fn prepare(&mut self, sample_rate: u32, channels: usize) {
+        if self.sample_rate == sample_rate && self.channels == channels {
+            return;
+        }
+        self.sample_rate = sample_rate;
+        self.channels = channels;
+        self.update_coefficients();
+        self.reset();
+    }
+
+    /// Apply the equalizer in place to interleaved samples.
+    pub fn process(&mut self, samples: &mut [f32]) {
+        if !self.is_active() || self.channels == 0 {
+            self.history_stale = true;
+            return;
+        }
+        if self.history_stale {
+            self.reset();
+        }
+        for frame in samples.chunks_mut(self.channels) {
+            filter_frame(&self.filters, self.preamp, &mut self.history, frame);
+        }
+    }
+
+    /// Clear the filter history, e.g. after a seek.
+    pub fn reset(&mut self) {
+        self.history = vec![[BiquadHistory::default(); BAND_COUNT]; self.channels];
+        self.history_stale = false;
+    }
+
+    /// Recompute band coefficients and the headroom, keeping the filter
+    /// history so playback continues without a click.
+    fn update_coefficients(&mut self) {
+        if self.sample_rate == 0 {
+            return;
+        }
+        let sample_rate = f64::from(self.sample_rate);
+        for ((filter, frequency), gain) in self
+            .filters
+            .iter_mut()
+            .zip(BAND_FREQUENCIES)
+            .zip(self.gains_db)
+        {
+            *filter = Biquad::peaking(frequency, gain, BAND_Q, sample_rate);
+        }
+        self.preamp = peak_gain(&self.filters, sample_rate).max(1.0).recip();
+    }
+}
+
+impl Default for Equalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Persisted equalizer preferences.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EqualizerSettings {
+    /// Whether the equalizer is switched on.
+    pub enabled: bool,
+    /// Last selected preset.
+    pub preset: EqPreset,
+    /// Gain of each band in decibels.
+    pub gains_db: [f64; BAND_COUNT],
+}
+
+impl Default for EqualizerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            preset: EqPreset::Flat,
+            gains_db: [0.0; BAND_COUNT],
+        }
+    }
+}
+
+/// Run one interleaved frame through the per-channel filter chains.
+fn filter_frame(
+    filters: &[Biquad; BAND_COUNT],
+    preamp: f64,
+    history: &mut [[BiquadHistory; BAND_COUNT]],
+    frame: &mut [f32],
+) {
+    for (sample, channel_history) in frame.iter_mut().zip(history.iter_mut()) {
+        *sample = filter_sample(filters, preamp, channel_history, *sample);
+    }
+}
+
+/// Run one sample through every band of a channel.
+///
+/// Flat bands run as identity filters, so their history stays current
+/// for when they are moved.
+fn filter_sample(
+    filters: &[Biquad; BAND_COUNT],
+    preamp: f64,
+    history: &mut [BiquadHistory; BAND_COUNT],
+    sample: f32,
+) -> f32 {
+    let value = filters.iter().zip(history.iter_mut()).fold(
+        f64::from(sample) * preamp,
+        |value, (filter, band_history)| filter.run(value, band_history),
+    );
+    cast(value).unwrap_or(sample)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use num_traits::cast::cast;
+
+    use crate::playback::equalizer::{BAND_COUNT, EqPreset, Equalizer, MAX_GAIN_DB};
+
+    fn sine(frequency: f64, sample_rate: f64, frames: u32) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|n| {
+                let t = f64::from(n) / sample_rate;
+                let v: f32 = cast((2.0 * PI * frequency * t).sin() * 0.25).unwrap_or(0.0);
+                [v, v]
+            })
+            .collect()
+    }
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().fold(0.0f32, |acc, s| acc.max(s.abs()))
+    }
+
+    #[test]
+    fn disabled_equalizer_is_bit_exact() {
+        let mut eq = Equalizer::new();
+        eq.prepare(48000, 2);
+        assert!(eq.set_band(5, 6.0), "Band 5 must exist");
+        let original = sine(1000.0, 48000.0, 512);
+        let mut samples = original.clone();
+        eq.process(&mut samples);
+        assert_eq!(samples, original, "Disabled EQ must not touch samples");
+    }
+
+    #[test]
+    fn flat_enabled_equalizer_is_bit_exact() {
+        let mut eq = Equalizer::new();
+        eq.prepare(48000, 2);
+        eq.set_enabled(true);
+        assert!(!eq.is_active(), "Flat EQ must be inactive");
+        let original = sine(1000.0, 48000.0, 512);
+        let mut samples = original.clone();
+        eq.process(&mut samples);
+        assert_eq!(samples, original, "Flat EQ must not touch samples");
+    }
+
+    #[test]
+    fn boost_raises_band_level() {
+        let mut eq = Equalizer::new();
+        eq.prepare(48000, 2);
+        eq.set_enabled(true);
+        assert!(eq.set_band(5, 6.0), "Band 5 must exist");
+        let mut boosted = sine(1000.0, 48000.0, 4800);
+        eq.process(&mut boosted);
+        eq.reset();
+        let mut other = sine(125.0, 48000.0, 4800);
+        eq.process(&mut other);
+        assert!(
+            peak(&boosted[4800..]) > peak(&other[4800..]) * 1.5,
+            "A 6 dB boost at 1 kHz must roughly double a 1 kHz tone against other bands"
+        );
+    }
+
+    #[test]
+    fn headroom_prevents_clipping() {
+        let gains = EqPreset::BassBoost.gains_db().unwrap_or_default();
+        let mut eq = Equalizer::new();
+        eq.prepare(48000, 2);
+        eq.set_enabled(true);
+        for (band, gain) in gains.iter().enumerate() {
+            assert!(eq.set_band(band, *gain), "Band {band} must exist");
+        }
+        for frequency in [31.0, 45.0, 62.0, 90.0, 125.0] {
+            let original = sine(frequency, 48000.0, 48000);
+            let mut samples = original.clone();
+            eq.process(&mut samples);
+            assert!(
+                peak(&samples[48000..]) <= peak(&original) * 1.01,
+                "A {frequency} Hz tone must not be boosted above its input level"
+            );
+        }
+    }
+
+    #[test]
+    fn moving_a_band_keeps_filter_history() {
+        let mut eq = Equalizer::new();
+        eq.prepare(48000, 2);
+        eq.set_enabled(true);
+        assert!(eq.set_band(5, 3.0), "Band 5 must exist");
+        let mut first = sine(1000.0, 48000.0, 4800);
+        eq.process(&mut first);
+        let history = eq.history.clone();
+        assert!(eq.set_band(5, 4.0), "Band 5 must exist");
+        assert!(
+            eq.history == history,
+            "Moving a band must not clear the filter history"
+        );
+    }
+
+    #[test]
+    fn set_band_rejects_out_of_range_and_clamps() {
+        let mut eq = Equalizer::new();
+        assert!(!eq.set_band(BAND_COUNT, 3.0), "Index past the last band");
+        assert!(eq.set_band(0, 40.0), "Band 0 must exist");
+        assert!(
+            (eq.gains_db()[0] - MAX_GAIN_DB).abs() < f64::EPSILON,
+            "Gain must be clamped to the maximum"
+        );
+    }
+
+    #[test]
+    fn presets_stay_within_gain_range() {
+        for (preset, gains) in EqPreset::ALL
+            .iter()
+            .filter_map(|p| Some((p, p.gains_db()?)))
+        {
+            assert!(
+                gains.iter().all(|g| g.abs() <= MAX_GAIN_DB),
+                "{} preset exceeds the gain range",
+                preset.label()
+            );
+        }
+    }
+}
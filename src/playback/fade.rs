@@ -0,0 +1,233 @@
+//! Short gain ramps that soften starting, pausing and stopping playback.
+//!
+//! The audio callback owns a [`GainRamp`] and moves it one frame at a time
+//! towards the target published in the shared [`FadeControl`], so pausing no
+//! longer cuts the waveform mid-cycle and resuming does not start with a click.
+//! Track changes never touch the ramp, leaving gapless transitions untouched.
+
+use std::{
+    sync::atomic::{
+        AtomicU32,
+        Ordering::{AcqRel, Acquire, Relaxed, Release},
+    },
+    thread::sleep,
+    time::Duration,
+};
+
+use num_traits::cast::AsPrimitive;
+
+use crate::playback::{
+    engine::{EngineShared, PlaybackStatus},
+    output::{AudioOutput, OutputMode::BitPerfect},
+};
+
+/// Fade length used until the user picks another one.
+pub const DEFAULT_FADE_MS: u32 = 200;
+
+/// Marks an empty `jump` slot in [`FadeControl`] (a NaN payload no gain uses).
+const NO_JUMP: u32 = u32::MAX;
+
+/// Fade state shared between the output handle and the audio callback.
+///
+/// Gains are stored as `f32::to_bits()` so the callback can read them with
+/// single atomic loads.
+pub struct FadeControl {
+    /// Gain the callback ramps towards.
+    target: AtomicU32,
+    /// Gain change per frame; `1.0` applies a new target at once.
+    step: AtomicU32,
+    /// Gain the callback jumps to before ramping, or [`NO_JUMP`].
+    jump: AtomicU32,
+    /// Fade length in milliseconds (`0` disables fading).
+    length_ms: AtomicU32,
+}
+
+impl FadeControl {
+    /// Set the fade length for a stream running at `sample_rate`.
+    pub fn set_length(&self, fade_ms: u32, sample_rate: u32) {
+        let frames = f64::from(fade_ms) * f64::from(sample_rate) / 1000.0;
+        let step: f32 = if frames < 1.0 {
+            1.0
+        } else {
+            (1.0 / frames).as_()
+        };
+        self.step.store(step.to_bits(), Relaxed);
+        self.length_ms.store(fade_ms, Relaxed);
+    }
+
+    /// How long a full fade takes.
+    #[must_use]
+    pub fn length(&self) -> Duration {
+        Duration::from_millis(u64::from(self.length_ms.load(Relaxed)))
+    }
+
+    /// Restart from silence and ramp up to full gain.
+    pub fn fade_in(&self) {
+        self.target.store(1.0_f32.to_bits(), Relaxed);
+        self.jump.store(0.0_f32.to_bits(), Release);
+    }
+
+    /// Ramp down to silence.
+    ///
+    /// Returns how long the ramp takes, after which the stream can be
+    /// paused without an audible cut.
+    pub fn fade_out(&self) -> Duration {
+        self.target.store(0.0_f32.to_bits(), Relaxed);
+        self.length()
+    }
+
+    /// Jump straight to full gain, abandoning any fade in progress.
+    pub fn reset(&self) {
+        self.target.store(1.0_f32.to_bits(), Relaxed);
+        self.jump.store(1.0_f32.to_bits(), Release);
+    }
+
+    /// Copy the shared target and step into the callback's `ramp`.
+    pub fn sync(&self, ramp: &mut GainRamp) {
+        let jump = self.jump.swap(NO_JUMP, AcqRel);
+        if jump != NO_JUMP {
+            ramp.gain = f32::from_bits(jump);
+        }
+        ramp.target = f32::from_bits(self.target.load(Acquire));
+        ramp.step = f32::from_bits(self.step.load(Relaxed));
+    }
+}
+
+impl Default for FadeControl {
+    fn default() -> Self {
+        Self {
+            target: AtomicU32::new(1.0_f32.to_bits()),
+            step: AtomicU32::new(1.0_f32.to_bits()),
+            jump: AtomicU32::new(NO_JUMP),
+            length_ms: AtomicU32::new(0),
+        }
+    }
+}
+
+/// Per-frame gain owned by the audio callback.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GainRamp {
+    /// Gain applied to the next frame.
+    gain: f32,
+    /// Gain the ramp moves towards.
+    target: f32,
+    /// Largest gain change between two frames.
+    step: f32,
+}
+
+impl GainRamp {
+    /// Gain applied to the next frame, without advancing the ramp.
+    #[must_use]
+    pub const fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Gain for the next frame; moves the ramp one step towards its target.
+    pub fn next_gain(&mut self) -> f32 {
+        let gain = self.gain;
+        self.gain = if self.gain < self.target {
+            (self.gain + self.step).min(self.target)
+        } else {
+            (self.gain - self.step).max(self.target)
+        };
+        gain
+    }
+}
+
+impl Default for GainRamp {
+    fn default() -> Self {
+        Self {
+            gain: 1.0,
+            target: 1.0,
+            step: 1.0,
+        }
+    }
+}
+
+/// Resume the output stream, fading in from silence unless bit-perfect.
+pub fn fade_in_and_play(engine_shared: &EngineShared) {
+    let bit_perfect = engine_shared.state.lock().output_mode == BitPerfect;
+    let output = engine_shared.output.lock();
+    let Some(stream) = output.as_ref() else {
+        return;
+    };
+    if !bit_perfect {
+        stream.fade_in();
+    }
+    stream.play();
+    drop(output);
+}
+
+/// Fade the output out, then pause the stream once the fade has been heard.
+///
+/// Bit-perfect output is paused at once. The pause is skipped if the status
+/// left `expected` during the fade, e.g. because playback was resumed or
+/// restarted on a freshly opened output.
+pub fn fade_out_and_pause(engine_shared: &EngineShared, expected: PlaybackStatus) {
+    if engine_shared.state.lock().output_mode != BitPerfect {
+        let fade = engine_shared
+            .output
+            .lock()
+            .as_ref()
+            .map_or(Duration::ZERO, AudioOutput::fade_out);
+        sleep(fade);
+    }
+    if engine_shared.state.lock().status != expected {
+        return;
+    }
+    engine_shared.output.lock().as_ref().map(AudioOutput::pause);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{iter::repeat_with, time::Duration};
+
+    use crate::playback::fade::{FadeControl, GainRamp};
+
+    #[test]
+    fn fade_out_reaches_silence_after_the_fade_length() {
+        let control = FadeControl::default();
+        control.set_length(10, 1000);
+        let mut ramp = GainRamp::default();
+
+        assert_eq!(control.fade_out(), Duration::from_millis(10), "fade length");
+        control.sync(&mut ramp);
+        let gains: Vec<f32> = repeat_with(|| ramp.next_gain()).take(11).collect();
+
+        assert!((gains[0] - 1.0).abs() < f32::EPSILON, "starts at full gain");
+        assert!(gains.windows(2).all(|w| w[1] < w[0]), "{gains:?}");
+        assert!(ramp.gain().abs() < 1e-6, "silent after ten frames");
+    }
+
+    #[test]
+    fn fade_in_restarts_from_silence() {
+        let control = FadeControl::default();
+        control.set_length(4, 1000);
+        let mut ramp = GainRamp::default();
+
+        control.fade_in();
+        control.sync(&mut ramp);
+        let gains: Vec<f32> = repeat_with(|| ramp.next_gain()).take(6).collect();
+
+        assert_eq!(gains, [0.0, 0.25, 0.5, 0.75, 1.0, 1.0], "linear ramp up");
+    }
+
+    #[test]
+    fn zero_length_switches_gain_at_once() {
+        let control = FadeControl::default();
+        control.set_length(0, 48_000);
+        let mut ramp = GainRamp::default();
+
+        assert_eq!(control.fade_out(), Duration::ZERO, "nothing to wait for");
+        control.sync(&mut ramp);
+        ramp.next_gain();
+        assert!(ramp.gain().abs() < f32::EPSILON, "silent after one frame");
+
+        control.reset();
+        control.sync(&mut ramp);
+        assert!(
+            (ramp.gain() - 1.0).abs() < f32::EPSILON,
+            "reset restores full gain"
+        );
+    }
+}
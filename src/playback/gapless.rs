@@ -48,7 +48,8 @@ pub struct GaplessTransitioner {
     preloaded_decoder: Option<Decoder>,
     /// Path to the pre-loaded next track.
     preloaded_path: Option<PathBuf>,
-    /// Whether gapless transitions are enabled.
+    /// Whether the next track is pre-buffered, for gapless playback or
+    /// crossfade.
     enabled: bool,
 }
 
@@ -64,7 +65,7 @@ impl GaplessTransitioner {
         }
     }
 
-    /// Enable or disable gapless transitions.
+    /// Enable or disable pre-buffering of the next track.
     pub fn set_enabled(&mut self, enabled: bool) {
         if !enabled {
             self.preloaded_decoder = None;
@@ -207,6 +208,18 @@ impl Default for GaplessTransitioner {
     }
 }
 
+/// How the current track took over from the one before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackEntry {
+    /// Started directly, not by a finished track.
+    Started,
+    /// Followed the previous track on the open output, gaplessly or
+    /// crossfaded.
+    Seamless,
+    /// Followed the previous track after the output was closed and reopened.
+    HardCut,
+}
+
 /// Determine whether a resampler reconfiguration is needed based on
 /// sample rate change.
 ///
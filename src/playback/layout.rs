@@ -43,6 +43,29 @@ impl AudioLayout {
         Self::Channels(channels)
     }
 
+    /// Construct from the speaker positions of a decoded stream.
+    ///
+    /// Layouts with height channels become [`Self::Immersive`]; ear-level
+    /// layouts keep only their channel count.
+    ///
+    /// # Arguments
+    ///
+    /// * `total` - Number of channels
+    /// * `lfe` - Low-frequency effect channels among them
+    /// * `height` - Overhead channels among them
+    #[must_use]
+    pub fn from_speakers(total: u32, lfe: u32, height: u32) -> Self {
+        if height == 0 || lfe + height > total {
+            return Self::Channels(total);
+        }
+        let narrow = |n: u32| u8::try_from(n).unwrap_or(u8::MAX);
+        Self::Immersive {
+            surround: narrow(total - lfe - height),
+            lfe: narrow(lfe),
+            height: narrow(height),
+        }
+    }
+
     /// Total physical channel count for this layout.
     #[must_use]
     pub fn total_channels(self) -> u32 {
@@ -102,11 +125,36 @@ pub fn format_channel_label(layout: AudioLayout) -> Cow<'static, str> {
     }
 }
 
+/// Format an [`AudioLayout`] as a short tag for compact format lines.
+///
+/// Yields `Mono`, `Stereo`, `Quad`, `5.1`, `7.1`, `5.1.2` and the like,
+/// as in `FLAC 24/96 · 5.1`.
+#[must_use]
+pub fn compact_channel_label(layout: AudioLayout) -> Cow<'static, str> {
+    match layout {
+        AudioLayout::Spatial => Cow::Borrowed("Spatial"),
+        AudioLayout::Immersive {
+            surround,
+            lfe,
+            height,
+        } => Cow::Owned(format!("{surround}.{lfe}.{height}")),
+        AudioLayout::Channels(count) => match count {
+            1 => Cow::Borrowed("Mono"),
+            2 => Cow::Borrowed("Stereo"),
+            4 => Cow::Borrowed("Quad"),
+            6 => Cow::Borrowed("5.1"),
+            7 => Cow::Borrowed("6.1"),
+            8 => Cow::Borrowed("7.1"),
+            n => Cow::Owned(format!("{n}ch")),
+        },
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::playback::layout::{
         AudioLayout::{self, Channels, Immersive, Spatial},
-        format_channel_label,
+        compact_channel_label, format_channel_label,
     };
 
     #[test]
@@ -180,6 +228,39 @@ mod tests {
         assert_eq!(layout, Channels(6));
     }
 
+    #[test]
+    fn from_speakers_detects_height_channels() {
+        assert_eq!(AudioLayout::from_speakers(6, 1, 0), Channels(6));
+        assert_eq!(
+            AudioLayout::from_speakers(12, 1, 4),
+            Immersive {
+                surround: 7,
+                lfe: 1,
+                height: 4,
+            }
+        );
+        assert_eq!(
+            AudioLayout::from_speakers(2, 1, 4),
+            Channels(2),
+            "inconsistent counts keep the total"
+        );
+    }
+
+    #[test]
+    fn compact_labels() {
+        assert_eq!(compact_channel_label(Channels(2)), "Stereo");
+        assert_eq!(compact_channel_label(Channels(6)), "5.1");
+        assert_eq!(compact_channel_label(Channels(3)), "3ch");
+        assert_eq!(
+            compact_channel_label(Immersive {
+                surround: 5,
+                lfe: 1,
+                height: 2,
+            }),
+            "5.1.2"
+        );
+    }
+
     #[test]
     fn total_channels_standard() {
         assert_eq!(Channels(6).total_channels(), 6);
@@ -0,0 +1,222 @@
+//! Peak and RMS levels of the left/right pair, measured by the audio
+//! callback for the level meter.
+//!
+//! The callback measures each buffer it fills, after the channel mix and
+//! before software volume, and publishes the result through a shared
+//! [`LevelMeter`] the UI samples at its own pace. Peaks are held until the
+//! UI takes them, so none falls between two samples. Measuring only reads
+//! the samples, but bit-perfect output skips it anyway, and the meter is
+//! off unless the user turns it on.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering::Relaxed};
+
+use num_traits::cast::AsPrimitive;
+
+/// Lowest level the meter shows, in dBFS.
+pub const METER_FLOOR_DB: f32 = -60.0;
+
+/// Running peak and sum of squares over one callback buffer.
+#[derive(Debug, Default)]
+pub struct BlockLevels {
+    /// Highest absolute sample of each channel.
+    peak: [f32; 2],
+    /// Sum of squared samples of each channel.
+    sum_squares: [f32; 2],
+    /// Frames measured.
+    frames: usize,
+}
+
+impl BlockLevels {
+    /// Add one left/right frame.
+    pub fn add(&mut self, left: f32, right: f32) {
+        for (channel, sample) in [left, right].into_iter().enumerate() {
+            self.peak[channel] = self.peak[channel].max(sample.abs());
+            self.sum_squares[channel] += sample * sample;
+        }
+        self.frames += 1;
+    }
+
+    /// Levels of the frames added so far.
+    #[must_use]
+    pub fn levels(&self) -> Levels {
+        let frames: f32 = self.frames.max(1).as_();
+        Levels {
+            peak: self.peak,
+            rms: self.sum_squares.map(|sum| (sum / frames).sqrt()),
+        }
+    }
+}
+
+/// Levels shared between the audio callback and the UI.
+///
+/// Values are stored as `f32::to_bits()`. For non-negative floats the bit
+/// patterns order like the values, so peaks are held with `fetch_max`.
+pub struct LevelMeter {
+    /// Set by the user to show the meter.
+    enabled: AtomicBool,
+    /// Set in bit-perfect mode, where the callback does not measure.
+    bypass: AtomicBool,
+    /// Highest peak of each channel since the UI last took it.
+    peak: [AtomicU32; 2],
+    /// RMS of each channel over the latest buffer.
+    rms: [AtomicU32; 2],
+}
+
+impl LevelMeter {
+    /// Turn measuring on or off.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Relaxed);
+        if !enabled {
+            self.reset();
+        }
+    }
+
+    /// Skip measuring while `bypass` is set.
+    pub fn set_bypass(&self, bypass: bool) {
+        self.bypass.store(bypass, Relaxed);
+        if bypass {
+            self.reset();
+        }
+    }
+
+    /// Whether the meter is turned on, regardless of the output mode.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Relaxed)
+    }
+
+    /// Whether the callback measures the buffers it fills.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.is_enabled() && !self.bypass.load(Relaxed)
+    }
+
+    /// Publish the levels of one buffer.
+    pub fn publish(&self, levels: &Levels) {
+        for (held, peak) in self.peak.iter().zip(levels.peak) {
+            held.fetch_max(peak.to_bits(), Relaxed);
+        }
+        for (latest, rms) in self.rms.iter().zip(levels.rms) {
+            latest.store(rms.to_bits(), Relaxed);
+        }
+    }
+
+    /// Take the held peaks and the latest RMS, if the meter is active.
+    #[must_use]
+    pub fn take(&self) -> Option<Levels> {
+        if !self.is_active() {
+            return None;
+        }
+        Some(Levels {
+            peak: self
+                .peak
+                .each_ref()
+                .map(|held| f32::from_bits(held.swap(0, Relaxed))),
+            rms: self
+                .rms
+                .each_ref()
+                .map(|latest| f32::from_bits(latest.load(Relaxed))),
+        })
+    }
+
+    /// Drop the published levels, so the meter restarts from silence.
+    fn reset(&self) {
+        for level in self.peak.iter().chain(&self.rms) {
+            level.store(0, Relaxed);
+        }
+    }
+}
+
+impl Default for LevelMeter {
+    fn default() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            bypass: AtomicBool::new(false),
+            peak: [AtomicU32::new(0), AtomicU32::new(0)],
+            rms: [AtomicU32::new(0), AtomicU32::new(0)],
+        }
+    }
+}
+
+/// Peak and RMS of the left and right channels, as linear amplitudes.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Levels {
+    /// Highest absolute sample of each channel.
+    pub peak: [f32; 2],
+    /// RMS of each channel.
+    pub rms: [f32; 2],
+}
+
+/// Position of `amplitude` on a meter from [`METER_FLOOR_DB`] to 0 dBFS.
+///
+/// # Returns
+///
+/// A fraction from `0.0` at or below the floor to `1.0` at full scale.
+#[must_use]
+pub fn meter_fraction(amplitude: f32) -> f64 {
+    if amplitude <= 0.0 {
+        return 0.0;
+    }
+    let db = 20.0 * amplitude.log10();
+    f64::from(((db - METER_FLOOR_DB) / -METER_FLOOR_DB).clamp(0.0, 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::playback::level::{BlockLevels, LevelMeter, Levels, meter_fraction};
+
+    #[test]
+    fn block_measures_peak_and_rms_per_channel() {
+        let mut block = BlockLevels::default();
+        for sample in [0.5, -0.5].into_iter().cycle().take(100) {
+            block.add(sample, sample / 4.0);
+        }
+        let levels = block.levels();
+        assert_eq!(levels.peak, [0.5, 0.125]);
+        assert!(
+            (levels.rms[0] - 0.5).abs() < 1e-6,
+            "square wave RMS is its peak"
+        );
+        assert!((levels.rms[1] - 0.125).abs() < 1e-6);
+        assert_eq!(BlockLevels::default().levels(), Levels::default());
+    }
+
+    #[test]
+    fn peaks_are_held_until_taken() {
+        let meter = LevelMeter::default();
+        let loud = Levels {
+            peak: [0.9, 0.8],
+            rms: [0.5, 0.4],
+        };
+        meter.publish(&loud);
+        assert_eq!(meter.take(), None, "the meter is off by default");
+
+        meter.set_enabled(true);
+        meter.publish(&loud);
+        meter.publish(&Levels {
+            peak: [0.1, 0.1],
+            rms: [0.05, 0.05],
+        });
+        let taken = meter.take();
+        assert_eq!(taken.map(|l| l.peak), Some([0.9, 0.8]), "earlier peak held");
+        assert_eq!(taken.map(|l| l.rms), Some([0.05, 0.05]), "latest RMS");
+        assert_eq!(meter.take().map(|l| l.peak), Some([0.0, 0.0]));
+
+        meter.set_bypass(true);
+        assert_eq!(meter.take(), None, "bit-perfect output is not metered");
+    }
+
+    #[test]
+    fn fraction_spans_the_meter_range() {
+        assert!(meter_fraction(0.0).abs() < f64::EPSILON);
+        assert!(
+            meter_fraction(0.0001).abs() < f64::EPSILON,
+            "-80 dB is below the floor"
+        );
+        assert!((meter_fraction(1.0) - 1.0).abs() < 1e-9);
+        assert!(
+            (meter_fraction(10.0_f32.powf(-1.5)) - 0.5).abs() < 1e-5,
+            "-30 dB is halfway"
+        );
+    }
+}
@@ -1,15 +1,30 @@
-//! Audio playback pipeline: decoder, resampler, output, queue, gapless transitions.
+//! Audio playback pipeline: decoder, resampler, equalizer, output, queue, gapless transitions.
 
+pub mod ab_loop;
+pub mod biquad;
+pub mod buffer;
 pub mod channel;
 pub mod control;
+pub mod crossfade;
+pub mod crossfade_handover;
+pub mod cue_skip;
 pub mod decoder;
+pub mod device_recovery;
 pub mod engine;
+pub mod equalizer;
+pub mod fade;
 pub mod gapless;
 pub mod layout;
+pub mod level;
 pub mod output;
 pub mod pipeline;
 pub mod queue;
+pub mod queue_undo;
 pub mod resampler;
+pub mod signal_path;
+pub mod silence;
+pub mod skip;
+pub mod stereo;
 pub mod track_transition;
 pub mod worker;
 
@@ -81,6 +96,29 @@ pub enum PlaybackError {
     /// Playback queue is empty.
     #[error("Queue empty")]
     QueueEmpty,
+    /// Queue position is past the end of the queue.
+    #[error("Queue position out of range: {0}")]
+    QueuePositionOutOfRange(usize),
+    /// Equalizer band index is past the last band.
+    #[error("Equalizer band out of range: {0}")]
+    EqBandOutOfRange(usize),
+    /// A-B loop end does not come after its start.
+    #[error("Invalid A-B loop: {start:.2}s to {end:.2}s")]
+    InvalidAbLoop {
+        /// Requested loop start in seconds.
+        start: f64,
+        /// Requested loop end in seconds.
+        end: f64,
+    },
+    /// Playback speed is not a finite number.
+    #[error("Invalid playback rate: {0}")]
+    InvalidPlaybackRate(f64),
+    /// Output buffer size is not an accepted power of two.
+    #[error("Invalid output buffer size: {0} frames")]
+    InvalidBufferSize(u32),
+    /// No replaced queue is kept to restore.
+    #[error("No replaced queue to restore")]
+    NoReplacedQueue,
 }
 
 /// Write a WAV file header (PCM, mono/stereo). Does not write audio data.
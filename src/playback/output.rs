@@ -1,12 +1,15 @@
 //! CPAL audio output: device enumeration, stream configuration, rtrb callback.
 //! Supports both resampled and bit-perfect passthrough output paths.
 
-use std::sync::{
-    Arc,
-    atomic::{
-        AtomicBool, AtomicU32,
-        Ordering::{Acquire, Relaxed, Release},
+use std::{
+    sync::{
+        Arc,
+        atomic::{
+            AtomicBool, AtomicU32,
+            Ordering::{Acquire, Relaxed, Release},
+        },
     },
+    time::Duration,
 };
 
 #[cfg(target_os = "linux")]
@@ -25,7 +28,12 @@ use {
     tracing::{error, info, warn},
 };
 
-use crate::playback::OutputError::{self, NoDeviceAvailable, Output, StreamConfigError};
+use crate::playback::{
+    OutputError::{self, NoDeviceAvailable, Output, StreamConfigError},
+    fade::{FadeControl, GainRamp},
+    level::{BlockLevels, LevelMeter},
+    stereo::{DownmixMode, StereoControl, StereoMix},
+};
 
 /// Controls playback volume via ALSA hardware mixer for bit-perfect mode.
 ///
@@ -144,6 +152,17 @@ pub struct AudioOutput {
     /// Stored as `f32::to_bits()` for lock-free atomic access.
     /// Initialised to 1.0 (no scaling); updated by `set_volume_atomic`.
     pub volume_atomic: Arc<AtomicU32>,
+    /// Gain ramp applied by the audio callback when playback starts,
+    /// pauses, or stops. Bypassed in bit-perfect mode.
+    fade: Arc<FadeControl>,
+    /// Channel mode and balance applied by the audio callback. Bypassed in
+    /// bit-perfect mode.
+    stereo: Arc<StereoControl>,
+    /// Level meter fed by the audio callback. Bypassed in bit-perfect mode.
+    levels: Arc<LevelMeter>,
+    /// Sample-rate ranges the device accepts in the stream's sample format
+    /// and channel count.
+    rate_ranges: Vec<(u32, u32)>,
 }
 
 impl AudioOutput {
@@ -155,6 +174,11 @@ impl AudioOutput {
     /// Returns the opened `AudioOutput` together with the `Producer` end
     /// of the ring buffer for the decode loop to push samples into.
     ///
+    /// With `preferred_rate` set, the stream runs at that rate if the device
+    /// supports it, so tracks at that rate play without resampling. Otherwise
+    /// the device's default rate is used. The audio callback publishes the
+    /// levels it plays to `levels` while the meter is on.
+    ///
     /// # Errors
     ///
     /// Returns [`OutputError`] if no device is available or stream creation
@@ -162,12 +186,21 @@ impl AudioOutput {
     pub fn open(
         ring_capacity: usize,
         device_lost: &Arc<AtomicBool>,
+        levels: &Arc<LevelMeter>,
+        preferred_rate: Option<u32>,
     ) -> Result<(Self, Producer<f32>), OutputError> {
         let volume_atomic = Arc::new(AtomicU32::new(f32::to_bits(1.0)));
         let host = default_host();
 
         if let Some(device) = host.default_output_device() {
-            match Self::try_open_device(&device, ring_capacity, device_lost, &volume_atomic) {
+            match Self::try_open_device(
+                &device,
+                ring_capacity,
+                device_lost,
+                &volume_atomic,
+                levels,
+                preferred_rate,
+            ) {
                 Ok(result) => return Ok(result),
                 Err(e) => warn!(error = %e, "Default audio device failed, trying fallback devices"),
             }
@@ -186,7 +219,14 @@ impl AudioOutput {
 
         let mut last_err = NoDeviceAvailable;
         for device in &devices {
-            match Self::try_open_device(device, ring_capacity, device_lost, &volume_atomic) {
+            match Self::try_open_device(
+                device,
+                ring_capacity,
+                device_lost,
+                &volume_atomic,
+                levels,
+                preferred_rate,
+            ) {
                 Ok(result) => return Ok(result),
                 Err(e) => last_err = e,
             }
@@ -203,6 +243,8 @@ impl AudioOutput {
     /// * `ring_capacity` - Capacity of the ring buffer
     /// * `device_lost` - Shared flag indicating device loss
     /// * `volume_atomic` - Shared atomic volume value
+    /// * `levels` - Level meter fed by the audio callback
+    /// * `preferred_rate` - Sample rate to run at if the device supports it
     ///
     /// # Returns
     ///
@@ -216,6 +258,8 @@ impl AudioOutput {
         ring_capacity: usize,
         device_lost: &Arc<AtomicBool>,
         volume_atomic: &Arc<AtomicU32>,
+        levels: &Arc<LevelMeter>,
+        preferred_rate: Option<u32>,
     ) -> Result<(Self, Producer<f32>), OutputError> {
         let (producer, consumer) = RingBuffer::new(ring_capacity);
         let flush_flag = Arc::new(AtomicBool::new(false));
@@ -225,6 +269,8 @@ impl AudioOutput {
             flush_flag,
             Arc::clone(device_lost),
             Arc::clone(volume_atomic),
+            Arc::clone(levels),
+            preferred_rate,
         )
         .map(|output| (output, producer))
     }
@@ -240,6 +286,8 @@ impl AudioOutput {
         flush_flag: Arc<AtomicBool>,
         device_lost: Arc<AtomicBool>,
         volume_atomic: Arc<AtomicU32>,
+        levels: Arc<LevelMeter>,
+        preferred_rate: Option<u32>,
     ) -> Result<Self, OutputError> {
         let device_id = device
             .id()
@@ -253,32 +301,47 @@ impl AudioOutput {
             .map_err(|e| StreamConfigError(e.to_string()))?;
 
         let sample_format = supported.sample_format();
-        let config = supported.config();
+        let mut config = supported.config();
+        let rate_ranges = supported_rate_ranges(device, sample_format, config.channels);
+        config.sample_rate = negotiate_rate(&rate_ranges, config.sample_rate, preferred_rate);
+        info!(
+            device = %device_name,
+            sample_rate = config.sample_rate,
+            requested_rate = ?preferred_rate,
+            "Output sample rate negotiated"
+        );
 
+        let controls = CallbackControls {
+            flush_flag: Arc::clone(&flush_flag),
+            volume: Arc::clone(&volume_atomic),
+            fade: Arc::new(FadeControl::default()),
+            stereo: Arc::new(StereoControl::default()),
+            levels: Arc::clone(&levels),
+        };
+        let fade = Arc::clone(&controls.fade);
+        let stereo = Arc::clone(&controls.stereo);
+        levels.set_bypass(false);
         let stream = match sample_format {
             F32 => build_stream::<f32>(
                 device,
                 &config,
                 consumer,
-                Arc::clone(&flush_flag),
                 Arc::clone(&device_lost),
-                Arc::clone(&volume_atomic),
+                controls,
             )?,
             I16 => build_stream::<i16>(
                 device,
                 &config,
                 consumer,
-                Arc::clone(&flush_flag),
                 Arc::clone(&device_lost),
-                Arc::clone(&volume_atomic),
+                controls,
             )?,
             U16 => build_stream::<u16>(
                 device,
                 &config,
                 consumer,
-                Arc::clone(&flush_flag),
                 Arc::clone(&device_lost),
-                Arc::clone(&volume_atomic),
+                controls,
             )?,
             fmt => {
                 return Err(StreamConfigError(format!(
@@ -303,6 +366,10 @@ impl AudioOutput {
             flush_flag,
             alsa_volume,
             volume_atomic,
+            fade,
+            stereo,
+            levels,
+            rate_ranges,
         })
     }
 
@@ -356,9 +423,14 @@ impl AudioOutput {
             OutputMode::BitPerfect => {
                 self.alsa_volume = Self::open_alsa_volume(&self.device_id);
                 self.volume_atomic.store(f32::to_bits(1.0), Relaxed);
+                self.fade.reset();
+                self.stereo.set_bypass(true);
+                self.levels.set_bypass(true);
             }
             OutputMode::Resampled => {
                 self.alsa_volume = None;
+                self.stereo.set_bypass(false);
+                self.levels.set_bypass(false);
             }
         }
     }
@@ -413,10 +485,11 @@ impl AudioOutput {
 
     /// Query whether a given sample rate is supported by the current device.
     ///
-    /// Returns `true` if the device supports the given sample rate natively.
+    /// Returns `true` if the device supports the given sample rate natively,
+    /// i.e. a stream could be reopened at that rate without resampling.
     #[must_use]
     pub fn supports_sample_rate(&self, sample_rate: u32) -> bool {
-        sample_rate == self.config.sample_rate
+        sample_rate == self.config.sample_rate || rate_supported(&self.rate_ranges, sample_rate)
     }
 
     /// Whether the device has been detected as lost.
@@ -425,6 +498,44 @@ impl AudioOutput {
         self.device_lost.load(Relaxed)
     }
 
+    /// Set the length of the fades applied on play, pause, and stop.
+    ///
+    /// A length of `0` disables fading.
+    pub fn set_fade_ms(&self, fade_ms: u32) {
+        self.fade.set_length(fade_ms, self.config.sample_rate);
+    }
+
+    /// Set the channel mode and the left/right balance (`-1.0..=1.0`).
+    ///
+    /// Takes effect on the next callback. Ignored in bit-perfect mode, where
+    /// samples must reach the device unchanged.
+    pub fn set_stereo_mix(&self, mode: DownmixMode, balance: f64) {
+        self.stereo.set(mode, balance);
+    }
+
+    /// Ramp the output up from silence.
+    ///
+    /// No-op in bit-perfect mode, where samples must reach the device
+    /// unscaled.
+    pub fn fade_in(&self) {
+        if self.mode == OutputMode::Resampled {
+            self.fade.fade_in();
+        }
+    }
+
+    /// Ramp the output down to silence.
+    ///
+    /// Returns how long to wait before pausing the stream so the fade is
+    /// heard in full; [`Duration::ZERO`] in bit-perfect mode.
+    #[must_use]
+    pub fn fade_out(&self) -> Duration {
+        if self.mode == OutputMode::Resampled {
+            self.fade.fade_out()
+        } else {
+            Duration::ZERO
+        }
+    }
+
     /// Pause the audio output stream instantly.
     pub fn pause(&self) {
         if let Err(e) = self.stream.pause() {
@@ -449,6 +560,20 @@ impl AudioOutput {
     }
 }
 
+/// Shared state the audio callback reads on every invocation.
+struct CallbackControls {
+    /// Set by `flush()`; the callback drains the ring buffer and clears it.
+    flush_flag: Arc<AtomicBool>,
+    /// Software volume stored as `f32::to_bits()`.
+    volume: Arc<AtomicU32>,
+    /// Play/pause/stop gain ramp.
+    fade: Arc<FadeControl>,
+    /// Channel mode and balance.
+    stereo: Arc<StereoControl>,
+    /// Level meter the callback publishes to.
+    levels: Arc<LevelMeter>,
+}
+
 /// Describes an available audio output device.
 pub struct DeviceInfo {
     /// Stable device identifier for persisting selection across restarts.
@@ -494,11 +619,86 @@ fn alsa_card_name(device_id: &str) -> String {
     device_id.to_string()
 }
 
+/// Sample-rate ranges `device` advertises for `sample_format` and `channels`.
+///
+/// Returns an empty list if the device cannot be queried.
+fn supported_rate_ranges(
+    device: &Device,
+    sample_format: SampleFormat,
+    channels: u16,
+) -> Vec<(u32, u32)> {
+    match device.supported_output_configs() {
+        Ok(configs) => configs
+            .filter(|c| c.sample_format() == sample_format && c.channels() == channels)
+            .map(|c| (c.min_sample_rate(), c.max_sample_rate()))
+            .collect(),
+        Err(e) => {
+            warn!(error = %e, "Failed to query supported output sample rates");
+            Vec::new()
+        }
+    }
+}
+
+/// Whether any of `ranges` contains `rate`.
+fn rate_supported(ranges: &[(u32, u32)], rate: u32) -> bool {
+    ranges
+        .iter()
+        .any(|(min, max)| (*min..=*max).contains(&rate))
+}
+
+/// Pick the stream sample rate: `preferred` if the device supports it,
+/// otherwise the device's `default` rate.
+fn negotiate_rate(ranges: &[(u32, u32)], default: u32, preferred: Option<u32>) -> u32 {
+    preferred
+        .filter(|rate| rate_supported(ranges, *rate))
+        .unwrap_or(default)
+}
+
 /// Drain all samples from the ring buffer consumer.
 fn drain_consumer(consumer: &mut Consumer<f32>) {
     while consumer.pop().is_ok() {}
 }
 
+/// Fill one interleaved output frame from the ring buffer.
+///
+/// The fade ramp only advances while audio is available, so a fade-in is
+/// not spent on the silence before the first decoded samples arrive. The
+/// stereo mix applies to the first two channels; any others pass through.
+/// With `meter` given, the mixed pair is measured before volume and fade.
+fn fill_frame<T: SizedSample + FromSample<f32>>(
+    frame: &mut [T],
+    consumer: &mut Consumer<f32>,
+    ramp: &mut GainRamp,
+    volume: f32,
+    mix: StereoMix,
+    meter: Option<&mut BlockLevels>,
+) {
+    let gain = if consumer.is_empty() {
+        ramp.gain()
+    } else {
+        ramp.next_gain()
+    };
+    let scale = volume * gain;
+    let rest = match frame {
+        [left, right, rest @ ..] => {
+            let l = consumer.pop().unwrap_or(0.0);
+            let r = consumer.pop().unwrap_or(0.0);
+            let (l, r) = mix.apply(l, r);
+            if let Some(meter) = meter {
+                meter.add(l, r);
+            }
+            *left = T::from_sample(l * scale);
+            *right = T::from_sample(r * scale);
+            rest
+        }
+        other => other,
+    };
+    for sample in rest {
+        let s: f32 = consumer.pop().unwrap_or(0.0);
+        *sample = T::from_sample(s * scale);
+    }
+}
+
 /// Build a cpal output stream for the given sample type.
 ///
 /// # Errors
@@ -508,21 +708,27 @@ fn build_stream<T: SizedSample + FromSample<f32>>(
     device: &Device,
     config: &StreamConfig,
     mut consumer: Consumer<f32>,
-    flush_flag: Arc<AtomicBool>,
     device_lost: Arc<AtomicBool>,
-    volume: Arc<AtomicU32>,
+    controls: CallbackControls,
 ) -> Result<Stream, OutputError> {
+    let channels = usize::from(config.channels).max(1);
+    let mut ramp = GainRamp::default();
     let stream = device
         .build_output_stream(
             *config,
             move |data: &mut [T], _: &OutputCallbackInfo| {
-                if flush_flag.swap(false, Acquire) {
+                if controls.flush_flag.swap(false, Acquire) {
                     drain_consumer(&mut consumer);
                 }
-                let vol = f32::from_bits(volume.load(Relaxed));
-                for sample in data.iter_mut() {
-                    let s: f32 = consumer.pop().unwrap_or(0.0);
-                    *sample = T::from_sample(s * vol);
+                let vol = f32::from_bits(controls.volume.load(Relaxed));
+                controls.fade.sync(&mut ramp);
+                let mix = controls.stereo.mix();
+                let mut meter = controls.levels.is_active().then(BlockLevels::default);
+                for frame in data.chunks_mut(channels) {
+                    fill_frame(frame, &mut consumer, &mut ramp, vol, mix, meter.as_mut());
+                }
+                if let Some(meter) = meter {
+                    controls.levels.publish(&meter.levels());
                 }
             },
             move |err| {
@@ -630,7 +836,7 @@ pub fn list_output_devices() -> Result<Vec<DeviceInfo>, OutputError> {
 mod tests {
     use crate::playback::output::{
         OutputMode::{BitPerfect, Resampled},
-        list_output_devices,
+        list_output_devices, negotiate_rate,
     };
 
     #[test]
@@ -653,6 +859,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn negotiate_rate_follows_supported_source_rate() {
+        let ranges = [(44_100, 44_100), (48_000, 192_000)];
+        assert_eq!(
+            negotiate_rate(&ranges, 48_000, Some(96_000)),
+            96_000,
+            "rate inside a range"
+        );
+        assert_eq!(
+            negotiate_rate(&ranges, 48_000, Some(44_100)),
+            44_100,
+            "single-rate range"
+        );
+        assert_eq!(
+            negotiate_rate(&ranges, 48_000, Some(22_050)),
+            48_000,
+            "unsupported rates fall back to the default"
+        );
+        assert_eq!(
+            negotiate_rate(&ranges, 48_000, None),
+            48_000,
+            "no preference"
+        );
+        assert_eq!(
+            negotiate_rate(&[], 48_000, Some(96_000)),
+            48_000,
+            "device could not be queried"
+        );
+    }
+
     #[test]
     fn output_mode_default_is_resampled() {
         assert_eq!(Resampled as u8, 1);
@@ -16,16 +16,28 @@ use {
     tracing::warn,
 };
 
-use crate::playback::{
-    channel::maybe_downmix,
-    decoder::Decoder,
-    engine::{
-        DecodeCommand::{self, Pause, PreloadNext, Resume, Seek},
-        EngineShared,
-        PlaybackEvent::{self, Error, TrackFinished, TrackStarted},
+use crate::{
+    library::gapless_flag::continues_gapless_album,
+    playback::{
+        ab_loop::{restart_loop, trim_at_loop_end},
+        channel::maybe_downmix,
+        crossfade::{Crossfade, maybe_start_crossfade},
+        crossfade_handover::{process_crossfade_batch, process_crossfade_tail},
+        cue_skip::jump_to_cue_track,
+        decoder::Decoder,
+        engine::{
+            DecodeCommand::{self, JumpToCueTrack, Pause, PreloadNext, Resume, Seek},
+            EngineShared,
+            PlaybackEvent::{self, Error, TrackFinished, TrackStarted},
+            PlaybackStatus::{Paused, Stopped},
+        },
+        fade::{fade_in_and_play, fade_out_and_pause},
+        gapless::TrackEntry::Seamless,
+        output::{AudioOutput, OutputMode::BitPerfect},
+        resampler::{AudioResampler, ResampleQuality, create_resampler, scaled_input_rate},
+        silence::{LeadingSilence, skip_leading_silence},
+        track_transition::handle_empty_batch,
     },
-    output::AudioOutput,
-    resampler::{AudioResampler, create_resampler},
 };
 
 /// Mutable decode loop state updated by gapless transitions.
@@ -44,6 +56,13 @@ pub struct LoopCtx {
     pub elapsed: f64,
     /// Last tick time for position update throttling.
     pub last_tick: Instant,
+    /// Incoming track being mixed in, while a crossfade is in progress.
+    pub crossfade: Option<Crossfade>,
+    /// Silence still being skipped at the start of the track.
+    pub leading_silence: Option<LeadingSilence>,
+    /// Whether the pre-buffered next track continues a gapless album with
+    /// the current one, so no crossfade is started into it.
+    pub continues_album: bool,
 }
 
 /// Audio output configuration for the decode loop.
@@ -53,6 +72,10 @@ pub struct OutputConfig {
     pub device_sample_rate: u32,
     /// Number of output channels.
     pub channels: u16,
+    /// Quality profile for resamplers created while this output is open.
+    pub resample_quality: ResampleQuality,
+    /// Size of the ring buffer feeding this output, in frames.
+    pub buffer_frames: u32,
 }
 
 /// Loop pushing a single sample, retrying on full buffer.
@@ -90,99 +113,83 @@ fn push_samples(samples: &[f32], producer: &mut Producer<f32>) {
     }
 }
 
-/// Pushes samples through a resampler and writes output to the ring buffer.
+/// Run `samples` through an optional resampler and collect the output.
 ///
-/// Returns `Some(error)` if resampling fails.
-fn process_resampler(
-    r: &mut AudioResampler,
-    samples: &[f32],
-    producer: &mut Producer<f32>,
-) -> Option<String> {
-    r.push_input(samples);
+/// # Errors
+///
+/// Returns a descriptive error string if resampling fails.
+pub fn resample_batch(
+    resampler: &mut Option<AudioResampler>,
+    samples: Vec<f32>,
+) -> Result<Vec<f32>, String> {
+    let Some(r) = resampler.as_mut() else {
+        return Ok(samples);
+    };
+    r.push_input(&samples);
+    let mut out = Vec::new();
     while r.has_pending_output() {
         match r.process() {
-            Ok(Some(output)) => push_samples(output, producer),
+            Ok(Some(chunk)) => out.extend_from_slice(chunk),
             Ok(None) => break,
-            Err(e) => return Some(format!("Resampler error: {e}")),
+            Err(e) => return Err(format!("Resampler error: {e}")),
         }
     }
-    None
+    Ok(out)
+}
+
+/// Apply the equalizer and push device-rate samples into the ring buffer.
+///
+/// The equalizer is bypassed in bit-perfect mode so the output stays
+/// bit-identical to the source.
+pub fn push_output(
+    engine_shared: &EngineShared,
+    samples: &mut [f32],
+    output_cfg: OutputConfig,
+    producer: &mut Producer<f32>,
+) {
+    if engine_shared.state.lock().output_mode != BitPerfect {
+        let mut equalizer = engine_shared.equalizer.lock();
+        equalizer.prepare(output_cfg.device_sample_rate, output_cfg.channels as usize);
+        equalizer.process(samples);
+        drop(equalizer);
+    }
+    push_samples(samples, producer);
 }
 
 /// Processes a decoded batch, optionally resampling, and returns an event if
 /// an error occurred.
 pub fn process_decoded_batch(
-    samples: &[f32],
+    samples: Vec<f32>,
     resampler: &mut Option<AudioResampler>,
+    engine_shared: &EngineShared,
+    output_cfg: OutputConfig,
     producer: &mut Producer<f32>,
 ) -> Option<PlaybackEvent> {
-    let error = if let Some(r) = resampler {
-        process_resampler(r, samples, producer)
-    } else {
-        push_samples(samples, producer);
-        None
-    };
-    error.map(|e| Error { error: e })
-}
-
-/// Handle an empty decode batch (track finished).
-///
-/// Attempts a gapless transition. Returns `Some(track_id)` if a transition was
-/// applied and the decode loop should continue. Returns `None` if no
-/// pre-buffered track is available.
-fn handle_empty_batch(
-    engine_shared: &Arc<EngineShared>,
-    ctx: &mut LoopCtx,
-    dst_channels: usize,
-    device_sample_rate: u32,
-) -> Option<i64> {
-    let mut transitioner = engine_shared.transitioner.lock();
-    let next_id = transitioner.next_track_id();
-    let next_decoder = transitioner.transition();
-    drop(transitioner);
-
-    let (Some(next_id), Some(next_decoder)) = (next_id, next_decoder) else {
-        return None;
-    };
-
-    let params = next_decoder.params();
-    let next_sr = params.sample_rate;
-
-    if engine_shared.queue.peek_next() != Some(next_id) {
-        return None;
-    }
-    debug_assert!(
-        engine_shared.queue.next().is_some(),
-        "peek_next confirmed a track exists for the pre-buffered transition"
-    );
-    {
-        let mut state = engine_shared.state.lock();
-        state.current_track_id = Some(next_id);
-        let path = engine_shared.track_paths.lock().get(&next_id).cloned();
-        state.current_path = path;
-        state.elapsed_seconds = 0.0;
-        state.duration_seconds = params.duration_seconds;
-    }
-    *engine_shared.track_sample_rate.lock() = next_sr;
-
-    if next_sr != ctx.track_sample_rate {
-        match create_resampler(next_sr, device_sample_rate, dst_channels) {
-            Ok(r) => ctx.resampler = Some(r),
-            Err(e) => {
-                warn!("Resampler reconfiguration failed: {e}");
-                return None;
-            }
+    match resample_batch(resampler, samples) {
+        Ok(mut output) => {
+            push_output(engine_shared, &mut output, output_cfg, producer);
+            None
         }
+        Err(error) => Some(Error { error }),
     }
+}
 
-    ctx.decoder = next_decoder;
-    ctx.track_sample_rate = next_sr;
-    ctx.src_channels = params.channels as usize;
-    ctx.elapsed = 0.0;
-    ctx.last_tick = Instant::now();
-    ctx.track_sample_rate_f64 = f64::from(next_sr);
-
-    Some(next_id)
+/// Whether the output should be reopened at `next_rate` before the next track.
+///
+/// True when sample-rate follow is on, the rate differs from the one the
+/// output runs at, and the device supports it natively.
+pub fn switches_output_rate(
+    engine_shared: &EngineShared,
+    next_rate: u32,
+    device_rate: u32,
+) -> bool {
+    next_rate != device_rate
+        && engine_shared.state.lock().follow_source_rate
+        && engine_shared
+            .output
+            .lock()
+            .as_ref()
+            .is_some_and(|o| o.supports_sample_rate(next_rate))
 }
 
 /// Handle a decode command from the control channel.
@@ -193,22 +200,39 @@ pub fn handle_decode_cmd(
     cmd_rx: &mut Receiver<DecodeCommand>,
     engine_shared: &Arc<EngineShared>,
     ctx: &mut LoopCtx,
+    track_id: &mut i64,
 ) -> bool {
     match cmd_rx.try_recv() {
-        Err(Disconnected) => true,
+        Err(Disconnected) => {
+            if engine_shared.state.lock().status == Stopped {
+                fade_out_and_pause(engine_shared, Stopped);
+            }
+            true
+        }
         Ok(Seek(pos)) => {
             engine_shared.output.lock().as_ref().map(AudioOutput::flush);
+            ctx.crossfade = None;
+            ctx.leading_silence = None;
+            engine_shared.equalizer.lock().reset();
             let actual = ctx.decoder.seek_to(pos).unwrap_or(pos);
             ctx.elapsed = actual;
             engine_shared.state.lock().elapsed_seconds = actual;
             false
         }
+        Ok(JumpToCueTrack {
+            track_id: new_id,
+            path,
+            range,
+        }) => {
+            jump_to_cue_track(ctx, engine_shared, track_id, (new_id, path, range));
+            false
+        }
         Ok(Pause) => {
-            engine_shared.output.lock().as_ref().map(AudioOutput::pause);
+            fade_out_and_pause(engine_shared, Paused);
             false
         }
         Ok(Resume) => {
-            engine_shared.output.lock().as_ref().map(AudioOutput::play);
+            fade_in_and_play(engine_shared);
             false
         }
         Ok(PreloadNext {
@@ -216,7 +240,13 @@ pub fn handle_decode_cmd(
             path: next_path,
             ..
         }) => {
-            let current = engine_shared.state.lock().current_track_id;
+            let state = engine_shared.state.lock();
+            let (current, current_path) = (state.current_track_id, state.current_path.clone());
+            let crossfading = state.crossfade_ms > 0;
+            drop(state);
+            // Tags are only read when a crossfade could skip this album.
+            ctx.continues_album = crossfading
+                && current_path.is_some_and(|path| continues_gapless_album(&path, &next_path));
             if let Some(current) = current
                 && let Err(e) = engine_shared
                     .transitioner
@@ -231,10 +261,42 @@ pub fn handle_decode_cmd(
     }
 }
 
+/// Rebuild the resampler when it no longer matches the playback speed.
+///
+/// Skipped while a crossfade is mixing in the next track; the speed then
+/// applies once the crossfade completes.
+fn sync_playback_rate(ctx: &mut LoopCtx, engine_shared: &EngineShared, output_cfg: OutputConfig) {
+    if ctx.crossfade.is_some() {
+        return;
+    }
+    let rate = engine_shared.state.lock().effective_playback_rate();
+    let input_rate = scaled_input_rate(ctx.track_sample_rate, rate);
+    let current = ctx
+        .resampler
+        .as_ref()
+        .map_or(output_cfg.device_sample_rate, AudioResampler::input_rate);
+    if input_rate == current {
+        return;
+    }
+    if input_rate == output_cfg.device_sample_rate {
+        ctx.resampler = None;
+        return;
+    }
+    match create_resampler(
+        input_rate,
+        output_cfg.device_sample_rate,
+        output_cfg.channels as usize,
+        output_cfg.resample_quality,
+    ) {
+        Ok(r) => ctx.resampler = Some(r),
+        Err(e) => warn!(error = %e, rate, "Failed to apply playback rate"),
+    }
+}
+
 /// Send a `PreloadNext` command for the upcoming track after a gapless
 /// transition, if any.
-fn preload_next_upcoming(engine_shared: &Arc<EngineShared>) {
-    let next_id = engine_shared.queue.upcoming().first().copied();
+pub fn preload_next_upcoming(engine_shared: &Arc<EngineShared>) {
+    let next_id = engine_shared.queue.peek_advance();
     let next_path = next_id.and_then(|id| engine_shared.track_paths.lock().get(&id).cloned());
     let Some((next_next_id, next_next_path)) = next_id.zip(next_path) else {
         return;
@@ -251,6 +313,14 @@ fn preload_next_upcoming(engine_shared: &Arc<EngineShared>) {
     }
 }
 
+/// Record a completed track transition and pre-buffer the following track.
+pub fn announce_transition(engine_shared: &Arc<EngineShared>, track_id: &mut i64, new_id: i64) {
+    *track_id = new_id;
+    *engine_shared.track_entry.lock() = Seamless;
+    engine_shared.send_event(&TrackStarted { track_id: new_id });
+    preload_next_upcoming(engine_shared);
+}
+
 /// Process one decoded frame from the decoder.
 ///
 /// Handles empty batches (track finished with possible gapless transition),
@@ -266,12 +336,12 @@ pub fn process_decode_frame(
 ) -> bool {
     match ctx.decoder.decode_next() {
         Ok(batch) if batch.samples.is_empty() => {
-            match handle_empty_batch(
-                engine_shared,
-                ctx,
-                output_cfg.channels as usize,
-                output_cfg.device_sample_rate,
-            ) {
+            if ctx.crossfade.is_some() {
+                *event_to_send =
+                    process_crossfade_tail(ctx, engine_shared, output_cfg, producer, track_id);
+                return event_to_send.is_some() || producer.is_abandoned();
+            }
+            match handle_empty_batch(engine_shared, ctx, output_cfg) {
                 None => {
                     *event_to_send = Some(TrackFinished {
                         track_id: *track_id,
@@ -279,20 +349,42 @@ pub fn process_decode_frame(
                     true
                 }
                 Some(new_id) => {
-                    *track_id = new_id;
-                    engine_shared.send_event(&TrackStarted { track_id: new_id });
-                    preload_next_upcoming(engine_shared);
+                    announce_transition(engine_shared, track_id, new_id);
                     false
                 }
             }
         }
-        Ok(batch) => {
+        Ok(mut batch) => {
+            skip_leading_silence(ctx, &mut batch.samples);
+            sync_playback_rate(ctx, engine_shared, output_cfg);
+            let loop_start = trim_at_loop_end(ctx, engine_shared, &mut batch.samples);
             let frame_count =
                 u32::try_from(batch.samples.len() / ctx.src_channels).unwrap_or(u32::MAX);
             ctx.elapsed += f64::from(frame_count) / ctx.track_sample_rate_f64;
             engine_shared.update_elapsed(ctx.elapsed, &mut ctx.last_tick);
+            if let Some(start_seconds) = loop_start {
+                restart_loop(ctx, engine_shared, start_seconds);
+            }
+            maybe_start_crossfade(engine_shared, ctx, output_cfg);
             let samples = maybe_downmix(batch, ctx.src_channels, output_cfg.channels as usize);
-            *event_to_send = process_decoded_batch(&samples, &mut ctx.resampler, producer);
+            if ctx.crossfade.is_none() {
+                *event_to_send = process_decoded_batch(
+                    samples,
+                    &mut ctx.resampler,
+                    engine_shared,
+                    output_cfg,
+                    producer,
+                );
+                return event_to_send.is_some() || producer.is_abandoned();
+            }
+            *event_to_send = process_crossfade_batch(
+                ctx,
+                samples,
+                engine_shared,
+                output_cfg,
+                producer,
+                track_id,
+            );
             event_to_send.is_some() || producer.is_abandoned()
         }
         Err(e) => {
@@ -1,6 +1,17 @@
 //! Playback queue with current, next, and previous track navigation.
-
-use std::sync::Arc;
+//!
+//! The queue also holds the repeat and shuffle modes. Shuffling reorders
+//! the tracks after the current one and remembers the original order so
+//! that turning it off restores it. Automatic advancing at the end of a
+//! track goes through [`PlaybackQueue::advance`], which repeats the current
+//! track in [`RepeatMode::One`]; skipping with [`PlaybackQueue::next`] always
+//! moves on.
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::Arc,
+};
 
 use parking_lot::Mutex;
 
@@ -19,11 +30,15 @@ impl PlaybackQueue {
             inner: Arc::new(Mutex::new(PlaybackQueueInner {
                 tracks: Vec::new(),
                 current_index: None,
+                repeat: RepeatMode::Off,
+                unshuffled: None,
             })),
         }
     }
 
     /// Replace the entire queue and start from the beginning.
+    ///
+    /// While shuffle is on, the tracks after the first are shuffled.
     pub fn set_queue(&self, track_ids: Vec<i64>) {
         let mut inner = self.inner.lock();
         inner.tracks = track_ids;
@@ -32,17 +47,38 @@ impl PlaybackQueue {
         } else {
             Some(0)
         };
+        if inner.unshuffled.is_some() {
+            inner.unshuffled = Some(inner.tracks.clone());
+            inner.shuffle_upcoming();
+        }
     }
 
     /// Append a track to the end of the queue.
     pub fn append(&self, track_id: i64) {
         let mut inner = self.inner.lock();
         inner.tracks.push(track_id);
+        if let Some(unshuffled) = inner.unshuffled.as_mut() {
+            unshuffled.push(track_id);
+        }
         if inner.current_index.is_none() {
             inner.current_index = Some(0);
         }
     }
 
+    /// Append several tracks to the end of the queue, in order.
+    ///
+    /// Like [`PlaybackQueue::append`], the new tracks are not shuffled.
+    pub fn extend(&self, track_ids: &[i64]) {
+        let mut inner = self.inner.lock();
+        inner.tracks.extend_from_slice(track_ids);
+        if let Some(unshuffled) = inner.unshuffled.as_mut() {
+            unshuffled.extend_from_slice(track_ids);
+        }
+        if inner.current_index.is_none() && !inner.tracks.is_empty() {
+            inner.current_index = Some(0);
+        }
+    }
+
     /// Remove a track by its position in the queue.
     ///
     /// Adjusts the current index if necessary.
@@ -53,6 +89,9 @@ impl PlaybackQueue {
             return None;
         }
         let removed = inner.tracks.remove(position);
+        if let Some(unshuffled) = inner.unshuffled.as_mut() {
+            remove_first(unshuffled, removed);
+        }
         inner.current_index = inner
             .current_index
             .and_then(|idx| adjust_index_after_remove(idx, position, inner.tracks.len()));
@@ -73,43 +112,112 @@ impl PlaybackQueue {
             .map(|idx| adjust_index_after_move(idx, from, to));
     }
 
+    /// Make the track at `position` current, returning its ID.
+    ///
+    /// Returns `None` if `position` is out of bounds.
+    #[must_use]
+    pub fn jump_to(&self, position: usize) -> Option<i64> {
+        let mut inner = self.inner.lock();
+        let track_id = *inner.tracks.get(position)?;
+        inner.current_index = Some(position);
+        drop(inner);
+        Some(track_id)
+    }
+
     /// Get the next track ID without advancing.
+    ///
+    /// With [`RepeatMode::All`], the first track follows the last.
     #[must_use]
     pub fn peek_next(&self) -> Option<i64> {
         let inner = self.inner.lock();
-        let idx = inner.current_index?;
-        let next = idx + 1;
-        (next < inner.tracks.len()).then(|| inner.tracks[next])
+        let next = inner.next_index()?;
+        Some(inner.tracks[next])
     }
 
     /// Advance to the next track, returning its ID.
     ///
-    /// Returns `None` if there is no next track.
+    /// Returns `None` if there is no next track. With [`RepeatMode::All`],
+    /// the first track follows the last.
     #[must_use]
     pub fn next(&self) -> Option<i64> {
         let mut inner = self.inner.lock();
-        let idx = inner.current_index?;
-        let next = idx + 1;
-        (next < inner.tracks.len()).then(|| {
-            inner.current_index = Some(next);
-            inner.tracks[next]
-        })
+        let next = inner.next_index()?;
+        inner.current_index = Some(next);
+        Some(inner.tracks[next])
+    }
+
+    /// Get the track that plays after the current one finishes, without advancing.
+    ///
+    /// Like [`PlaybackQueue::peek_next`], except that [`RepeatMode::One`]
+    /// returns the current track.
+    #[must_use]
+    pub fn peek_advance(&self) -> Option<i64> {
+        let inner = self.inner.lock();
+        let next = inner.advance_index()?;
+        Some(inner.tracks[next])
+    }
+
+    /// Advance after the current track finished, returning the track to play.
+    ///
+    /// Like [`PlaybackQueue::next`], except that [`RepeatMode::One`] stays
+    /// on the current track.
+    #[must_use]
+    pub fn advance(&self) -> Option<i64> {
+        let mut inner = self.inner.lock();
+        let next = inner.advance_index()?;
+        inner.current_index = Some(next);
+        Some(inner.tracks[next])
     }
 
     /// Move to the previous track, returning its ID.
     ///
-    /// Returns `None` if there is no previous track.
+    /// Returns `None` if there is no previous track. With
+    /// [`RepeatMode::All`], the last track precedes the first.
     #[must_use]
     pub fn previous(&self) -> Option<i64> {
         let mut inner = self.inner.lock();
         let idx = inner.current_index?;
-        let result = (idx > 0).then(|| {
-            let prev = idx - 1;
-            inner.current_index = Some(prev);
-            inner.tracks[prev]
-        });
-        drop(inner);
-        result
+        let prev = match (idx.checked_sub(1), inner.repeat) {
+            (Some(prev), _) => prev,
+            (None, RepeatMode::All) => inner.tracks.len() - 1,
+            (None, _) => return None,
+        };
+        inner.current_index = Some(prev);
+        Some(inner.tracks[prev])
+    }
+
+    /// Get the repeat mode.
+    #[must_use]
+    pub fn repeat_mode(&self) -> RepeatMode {
+        self.inner.lock().repeat
+    }
+
+    /// Set the repeat mode.
+    pub fn set_repeat_mode(&self, mode: RepeatMode) {
+        self.inner.lock().repeat = mode;
+    }
+
+    /// Returns `true` if shuffle is on.
+    #[must_use]
+    pub fn is_shuffled(&self) -> bool {
+        self.inner.lock().unshuffled.is_some()
+    }
+
+    /// Turn shuffle on or off.
+    ///
+    /// Turning it on shuffles the tracks after the current one; turning it
+    /// off restores the original order. The current track stays current.
+    pub fn set_shuffle(&self, enabled: bool) {
+        let mut inner = self.inner.lock();
+        match (enabled, inner.unshuffled.take()) {
+            (true, None) => {
+                inner.unshuffled = Some(inner.tracks.clone());
+                inner.shuffle_upcoming();
+            }
+            (true, Some(unshuffled)) => inner.unshuffled = Some(unshuffled),
+            (false, Some(unshuffled)) => inner.restore_order(unshuffled),
+            (false, None) => {}
+        }
     }
 
     /// Get the ID of the currently playing track.
@@ -119,6 +227,12 @@ impl PlaybackQueue {
         inner.current_index.map(|idx| inner.tracks[idx])
     }
 
+    /// Get the position of the current track in the queue.
+    #[must_use]
+    pub fn current_position(&self) -> Option<usize> {
+        self.inner.lock().current_index
+    }
+
     /// Get the track IDs of upcoming tracks (after the current one).
     #[must_use]
     pub fn upcoming(&self) -> Vec<i64> {
@@ -156,6 +270,27 @@ impl PlaybackQueue {
         inner.tracks.clear();
         inner.current_index = None;
     }
+
+    /// Capture the tracks, current position, and shuffle order.
+    #[must_use]
+    pub fn snapshot(&self) -> QueueSnapshot {
+        let inner = self.inner.lock();
+        QueueSnapshot {
+            tracks: inner.tracks.clone(),
+            current_index: inner.current_index,
+            unshuffled: inner.unshuffled.clone(),
+        }
+    }
+
+    /// Put back a queue captured by [`Self::snapshot`].
+    ///
+    /// The repeat mode is left as it is now.
+    pub fn restore(&self, snapshot: QueueSnapshot) {
+        let mut inner = self.inner.lock();
+        inner.tracks = snapshot.tracks;
+        inner.current_index = snapshot.current_index;
+        inner.unshuffled = snapshot.unshuffled;
+    }
 }
 
 impl Default for PlaybackQueue {
@@ -171,6 +306,128 @@ struct PlaybackQueueInner {
     tracks: Vec<i64>,
     /// Index of the currently playing track (None if empty).
     current_index: Option<usize>,
+    /// What happens at the end of a track or of the queue.
+    repeat: RepeatMode,
+    /// Track order before shuffling, while shuffle is on.
+    unshuffled: Option<Vec<i64>>,
+}
+
+impl PlaybackQueueInner {
+    /// Index of the track after the current one, wrapping in [`RepeatMode::All`].
+    fn next_index(&self) -> Option<usize> {
+        let next = self.current_index? + 1;
+        if next < self.tracks.len() {
+            Some(next)
+        } else {
+            (self.repeat == RepeatMode::All).then_some(0)
+        }
+    }
+
+    /// Index of the track to play once the current one finishes.
+    fn advance_index(&self) -> Option<usize> {
+        if self.repeat == RepeatMode::One {
+            self.current_index
+        } else {
+            self.next_index()
+        }
+    }
+
+    /// Shuffle the tracks after the current one in place.
+    fn shuffle_upcoming(&mut self) {
+        let start = self.current_index.map_or(0, |idx| idx + 1);
+        if let Some(upcoming) = self.tracks.get_mut(start..) {
+            shuffle_tracks(upcoming);
+        }
+    }
+
+    /// Restore the `unshuffled` order, keeping the current track current.
+    fn restore_order(&mut self, unshuffled: Vec<i64>) {
+        let current = self
+            .current_index
+            .and_then(|idx| self.tracks.get(idx).copied());
+        self.tracks = unshuffled;
+        self.current_index = current
+            .and_then(|id| self.tracks.iter().position(|&t| t == id))
+            .or_else(|| (!self.tracks.is_empty()).then_some(0));
+    }
+}
+
+/// Tracks, position, and shuffle order of a queue at one moment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueueSnapshot {
+    /// Ordered list of track IDs.
+    tracks: Vec<i64>,
+    /// Index of the current track (None if empty).
+    current_index: Option<usize>,
+    /// Track order before shuffling, while shuffle was on.
+    unshuffled: Option<Vec<i64>>,
+}
+
+impl QueueSnapshot {
+    /// Track IDs in queue order.
+    #[must_use]
+    pub fn tracks(&self) -> &[i64] {
+        &self.tracks
+    }
+
+    /// The current track, if any.
+    #[must_use]
+    pub fn current(&self) -> Option<i64> {
+        self.tracks.get(self.current_index?).copied()
+    }
+}
+
+/// What happens when a track or the whole queue finishes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RepeatMode {
+    /// Stop after the last track.
+    #[default]
+    Off,
+    /// Start over from the first track after the last.
+    All,
+    /// Play the current track again.
+    One,
+}
+
+impl RepeatMode {
+    /// The mode after this one when cycling through them with one button.
+    #[must_use]
+    pub const fn cycle(self) -> Self {
+        match self {
+            Self::Off => Self::All,
+            Self::All => Self::One,
+            Self::One => Self::Off,
+        }
+    }
+
+    /// Icon name for the repeat button.
+    #[must_use]
+    pub const fn icon_name(self) -> &'static str {
+        match self {
+            Self::Off | Self::All => "media-playlist-repeat-symbolic",
+            Self::One => "media-playlist-repeat-song-symbolic",
+        }
+    }
+
+    /// Human-readable description for tooltips.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Off => "Repeat off",
+            Self::All => "Repeat queue",
+            Self::One => "Repeat track",
+        }
+    }
+}
+
+/// Shuffle `tracks` in place (Fisher-Yates).
+pub fn shuffle_tracks(tracks: &mut [i64]) {
+    let mut hasher = RandomState::new().build_hasher();
+    for i in (1..tracks.len()).rev() {
+        hasher.write_usize(i);
+        let j = usize::try_from(hasher.finish() % (i as u64 + 1)).unwrap_or(0);
+        tracks.swap(i, j);
+    }
 }
 
 /// Adjust current index after removing a track at `position`.
@@ -186,6 +443,13 @@ fn adjust_index_after_remove(idx: usize, position: usize, len: usize) -> Option<
     }
 }
 
+/// Remove the first occurrence of `track_id` from `tracks`.
+fn remove_first(tracks: &mut Vec<i64>, track_id: i64) {
+    if let Some(position) = tracks.iter().position(|&t| t == track_id) {
+        tracks.remove(position);
+    }
+}
+
 /// Adjust current index after moving a track from `from` to `to`.
 fn adjust_index_after_move(idx: usize, from: usize, to: usize) -> usize {
     if idx == from {
@@ -201,7 +465,11 @@ fn adjust_index_after_move(idx: usize, from: usize, to: usize) -> usize {
 
 #[cfg(test)]
 mod tests {
-    use crate::playback::queue::PlaybackQueue;
+    use crate::playback::queue::{
+        PlaybackQueue,
+        RepeatMode::{All, One},
+        shuffle_tracks,
+    };
 
     fn three_track_queue() -> PlaybackQueue {
         let q = PlaybackQueue::new();
@@ -224,6 +492,25 @@ mod tests {
         assert_eq!(q.len(), 3);
     }
 
+    #[test]
+    fn jump_to_sets_current() {
+        let q = three_track_queue();
+        assert_eq!(q.jump_to(2), Some(30));
+        assert_eq!(q.current(), Some(30));
+        assert_eq!(q.jump_to(3), None);
+        assert_eq!(q.current(), Some(30));
+    }
+
+    #[test]
+    fn current_position_follows_jumps() {
+        let q = three_track_queue();
+        assert_eq!(q.current_position(), Some(0));
+        assert_eq!(q.jump_to(2), Some(30));
+        assert_eq!(q.current_position(), Some(2));
+        q.clear();
+        assert_eq!(q.current_position(), None);
+    }
+
     #[test]
     fn next_advances_index() {
         let q = three_track_queue();
@@ -251,6 +538,17 @@ mod tests {
         assert_eq!(q.upcoming(), vec![20, 30]);
     }
 
+    #[test]
+    fn extend_appends_in_order() {
+        let q = PlaybackQueue::new();
+        q.extend(&[]);
+        assert_eq!(q.current(), None);
+        q.extend(&[10, 20]);
+        q.extend(&[30, 40]);
+        assert_eq!(q.current(), Some(10));
+        assert_eq!(q.upcoming(), vec![20, 30, 40]);
+    }
+
     #[test]
     fn remove_adjusts_current() {
         let q = PlaybackQueue::new();
@@ -270,4 +568,66 @@ mod tests {
         assert!(q.is_empty());
         assert!(q.current().is_none());
     }
+
+    #[test]
+    fn repeat_all_wraps_around() {
+        let q = three_track_queue();
+        q.set_repeat_mode(All);
+        assert_eq!(q.previous(), Some(30), "Last precedes first");
+        assert_eq!(q.peek_next(), Some(10), "First follows last");
+        assert_eq!(q.advance(), Some(10));
+    }
+
+    #[test]
+    fn repeat_one_only_affects_advance() {
+        let q = three_track_queue();
+        q.set_repeat_mode(One);
+        assert_eq!(q.peek_advance(), Some(10), "Finished track repeats");
+        assert_eq!(q.advance(), Some(10));
+        assert_eq!(q.next(), Some(20), "Skipping still moves on");
+    }
+
+    #[test]
+    fn shuffle_keeps_current_and_restores_order() {
+        let q = PlaybackQueue::new();
+        q.set_queue((1..=50).collect());
+        assert_eq!(q.next(), Some(2));
+        q.set_shuffle(true);
+        assert!(q.is_shuffled());
+        assert_eq!(q.current(), Some(2), "Current track stays current");
+        let mut upcoming = q.upcoming();
+        upcoming.sort_unstable();
+        assert_eq!(upcoming, (3..=50).collect::<Vec<_>>(), "Same tracks");
+
+        q.set_shuffle(false);
+        assert_eq!(q.tracks(), (1..=50).collect::<Vec<_>>(), "Order restored");
+        assert_eq!(q.current(), Some(2));
+    }
+
+    #[test]
+    fn shuffled_tracks_keep_every_track() {
+        let mut tracks: Vec<i64> = (1..=50).collect();
+        shuffle_tracks(&mut tracks);
+        assert_ne!(tracks, (1..=50).collect::<Vec<_>>(), "Order changed");
+        tracks.sort_unstable();
+        assert_eq!(tracks, (1..=50).collect::<Vec<_>>(), "Same tracks");
+    }
+
+    #[test]
+    fn snapshot_restores_tracks_position_and_shuffle() {
+        let q = three_track_queue();
+        assert_eq!(q.jump_to(1), Some(20), "Jumped to the second track");
+        q.set_shuffle(true);
+        let snapshot = q.snapshot();
+        q.set_queue(vec![40, 50]);
+        q.restore(snapshot.clone());
+        assert_eq!(q.snapshot(), snapshot, "Queue restored as captured");
+        assert_eq!(q.current(), Some(20), "Current track restored");
+        assert_eq!(
+            snapshot.current(),
+            Some(20),
+            "Snapshot knows the current track"
+        );
+        assert!(q.is_shuffled(), "Shuffle restored");
+    }
 }
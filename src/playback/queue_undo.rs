@@ -0,0 +1,82 @@
+//! Undoing the replacement of the queue.
+//!
+//! Playing an album or a list of tracks replaces the queue. The engine keeps
+//! the queue it replaced, with the paths of its tracks and the position in
+//! the track playing then, until the next replacement, so the replacement
+//! can be undone.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
+
+use crate::playback::{
+    engine::{EngineShared, PlaybackStatus::Stopped},
+    queue::QueueSnapshot,
+};
+
+/// A queue replaced by another, kept to undo the replacement.
+#[derive(Debug, Clone)]
+pub struct ReplacedQueue {
+    /// The queue as it was.
+    pub queue: QueueSnapshot,
+    /// File paths of its tracks.
+    pub paths: HashMap<i64, PathBuf>,
+    /// Position in the current track, unless playback was stopped.
+    pub resume_seconds: Option<f64>,
+}
+
+impl ReplacedQueue {
+    /// Capture the queue of `shared` before it is replaced by `next`.
+    ///
+    /// # Returns
+    ///
+    /// `None` if every queued track is also in `next`, since replacing the
+    /// queue loses nothing then.
+    #[must_use]
+    pub fn capture(shared: &EngineShared, next: &[i64]) -> Option<Self> {
+        let queue = shared.queue.snapshot();
+        if loses_nothing(queue.tracks(), next) {
+            return None;
+        }
+        let paths = {
+            let known = shared.track_paths.lock();
+            queue
+                .tracks()
+                .iter()
+                .filter_map(|id| Some((*id, known.get(id)?.clone())))
+                .collect()
+        };
+        let resume_seconds = {
+            let state = shared.state.lock();
+            (state.status != Stopped).then_some(state.elapsed_seconds)
+        };
+        Some(Self {
+            queue,
+            paths,
+            resume_seconds,
+        })
+    }
+}
+
+/// Whether every track of `queued` is also in `next`.
+fn loses_nothing(queued: &[i64], next: &[i64]) -> bool {
+    let next: HashSet<i64> = next.iter().copied().collect();
+    queued.iter().all(|id| next.contains(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::playback::queue_undo::loses_nothing;
+
+    #[test]
+    fn only_queues_losing_tracks_are_kept() {
+        assert!(loses_nothing(&[], &[1, 2]), "Empty queue");
+        assert!(
+            loses_nothing(&[1, 2, 3], &[3, 1, 2]),
+            "Same tracks reordered"
+        );
+        assert!(!loses_nothing(&[1, 2, 3], &[4, 5]), "Other tracks");
+        assert!(!loses_nothing(&[1, 2, 3], &[1, 2]), "A track dropped");
+    }
+}
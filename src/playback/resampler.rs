@@ -9,9 +9,10 @@ use {
         FixedSync::Input,
         Indexing,
         ResampleError::{self, InsufficientInputBufferSize, InsufficientOutputBufferSize},
-        Resampler, ResamplerConstructionError,
+        Resampler, ResamplerConstructionError, WindowFunction,
         audioadapter_buffers::direct::InterleavedSlice,
     },
+    serde::{Deserialize, Serialize},
 };
 
 /// Sample rate converter wrapping the rubato FFT resampler.
@@ -36,10 +37,12 @@ pub struct AudioResampler {
     output_buf: Vec<f32>,
     /// Indexing state for streaming process calls.
     indexing: Indexing,
+    /// Quality profile the resampler was built with.
+    quality: ResampleQuality,
 }
 
 impl AudioResampler {
-    /// Create a new audio resampler.
+    /// Create a new audio resampler with the default quality profile.
     ///
     /// # Arguments
     ///
@@ -58,13 +61,37 @@ impl AudioResampler {
         chunk_size: usize,
         channels: usize,
     ) -> Result<Self, ResamplerConstructionError> {
-        let resampler = Fft::<f32>::new(
-            input_rate as usize,
-            output_rate as usize,
+        Self::with_quality(
+            input_rate,
+            output_rate,
             chunk_size,
             channels,
-            Input,
-        )?;
+            ResampleQuality::default(),
+        )
+    }
+
+    /// Create a new audio resampler using the given quality profile.
+    ///
+    /// # Arguments
+    ///
+    /// * `input_rate` - Input sample rate in Hz.
+    /// * `output_rate` - Output sample rate in Hz.
+    /// * `chunk_size` - Fixed input chunk size in frames per process call.
+    /// * `channels` - Number of audio channels.
+    /// * `quality` - Quality/CPU trade-off of the conversion.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ResamplerConstructionError`] if the resampler cannot be
+    /// constructed (e.g., invalid sample rate pair).
+    pub fn with_quality(
+        input_rate: u32,
+        output_rate: u32,
+        chunk_size: usize,
+        channels: usize,
+        quality: ResampleQuality,
+    ) -> Result<Self, ResamplerConstructionError> {
+        let resampler = build_fft(input_rate, output_rate, chunk_size, channels, quality)?;
 
         let output_frames_max = resampler.output_frames_max();
         let output_buf = vec![0.0_f32; output_frames_max * channels];
@@ -85,6 +112,7 @@ impl AudioResampler {
             input_accum: Vec::new(),
             output_buf,
             indexing,
+            quality,
         })
     }
 
@@ -167,12 +195,12 @@ impl AudioResampler {
         input_rate: u32,
         output_rate: u32,
     ) -> Result<(), ResamplerConstructionError> {
-        let new_resampler = Fft::<f32>::new(
-            input_rate as usize,
-            output_rate as usize,
+        let new_resampler = build_fft(
+            input_rate,
+            output_rate,
             self.chunk_size,
             self.channels,
-            Input,
+            self.quality,
         )?;
 
         let output_frames_max = new_resampler.output_frames_max();
@@ -222,6 +250,12 @@ impl AudioResampler {
         self.chunk_size
     }
 
+    /// Quality profile the resampler was built with.
+    #[must_use]
+    pub fn quality(&self) -> ResampleQuality {
+        self.quality
+    }
+
     /// Resample ratio (`output_rate` / `input_rate`).
     #[must_use]
     pub fn ratio(&self) -> f64 {
@@ -260,6 +294,79 @@ pub enum ResampleAlgorithm {
     Fft,
 }
 
+/// Trade-off between conversion quality and CPU use.
+///
+/// Each profile picks the anti-aliasing window and how many FFT sub-chunks
+/// a chunk is split into. Fewer sub-chunks mean longer filters with a
+/// steeper cutoff, at the cost of more work per chunk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResampleQuality {
+    /// Short filters for weak hardware.
+    Fast,
+    /// The resampler's stock settings.
+    Balanced,
+    /// Longest filters with the strongest stopband attenuation.
+    #[default]
+    High,
+}
+
+impl ResampleQuality {
+    /// All profiles, in the order the preferences list shows them.
+    pub const ALL: [Self; 3] = [Self::Fast, Self::Balanced, Self::High];
+
+    /// Human-readable name for the preferences list.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Fast => "Fast",
+            Self::Balanced => "Balanced",
+            Self::High => "High",
+        }
+    }
+
+    /// Anti-aliasing window used by the FFT resampler.
+    const fn window(self) -> WindowFunction {
+        match self {
+            Self::Fast => WindowFunction::Hann2,
+            Self::Balanced | Self::High => WindowFunction::BlackmanHarris2,
+        }
+    }
+
+    /// Number of FFT sub-chunks a chunk of `chunk_size` frames is split into.
+    ///
+    /// Sub-chunks stay well under the 1024-frame chunk the engine uses: a
+    /// single sub-chunk delays the output by a whole chunk, so the first
+    /// chunk of a track would come back empty.
+    fn sub_chunks(self, chunk_size: usize) -> usize {
+        let sub_chunk_frames = match self {
+            Self::Fast => 64,
+            Self::Balanced => 256,
+            Self::High => 512,
+        };
+        (chunk_size / sub_chunk_frames.max(1)).max(1)
+    }
+}
+
+/// Build the rubato FFT resampler for a quality profile.
+fn build_fft(
+    input_rate: u32,
+    output_rate: u32,
+    chunk_size: usize,
+    channels: usize,
+    quality: ResampleQuality,
+) -> Result<Fft<f32>, ResamplerConstructionError> {
+    Fft::<f32>::new_custom(
+        input_rate as usize,
+        output_rate as usize,
+        chunk_size,
+        quality.sub_chunks(chunk_size),
+        channels,
+        quality.window(),
+        Input,
+    )
+}
+
 /// Compute the RMS (Root Mean Square) of a slice of samples.
 #[must_use]
 pub fn rms(samples: &[f32]) -> f64 {
@@ -398,11 +505,28 @@ pub fn create_resampler(
     input_rate: u32,
     output_rate: u32,
     channels: usize,
+    quality: ResampleQuality,
 ) -> Result<AudioResampler, String> {
-    AudioResampler::new(input_rate, output_rate, 1024, channels)
+    AudioResampler::with_quality(input_rate, output_rate, 1024, channels, quality)
         .map_err(|e| format!("Failed to create resampler: {e}"))
 }
 
+/// Input rate that plays a track of `sample_rate` Hz at `playback_rate` speed.
+///
+/// Feeding the resampler more (or fewer) source frames per output second
+/// than the track holds changes speed and pitch together. Changed rates are
+/// rounded to 100 Hz so the FFT resampler keeps small chunk sizes.
+#[must_use]
+pub fn scaled_input_rate(sample_rate: u32, playback_rate: f64) -> u32 {
+    if (playback_rate - 1.0).abs() < f64::EPSILON {
+        return sample_rate;
+    }
+    let hundreds: u32 =
+        num_traits::NumCast::from((f64::from(sample_rate) * playback_rate / 100.0).round())
+            .unwrap_or(0);
+    hundreds.max(1) * 100
+}
+
 #[cfg(test)]
 mod tests {
     use std::f64::consts::SQRT_2;
@@ -410,9 +534,34 @@ mod tests {
     use anyhow::{Result, anyhow, ensure};
 
     use crate::playback::resampler::{
-        AudioResampler, compute_snr_db, generate_silence, generate_sine, rms,
+        AudioResampler, ResampleQuality, compute_snr_db, generate_silence, generate_sine, rms,
+        scaled_input_rate,
     };
 
+    #[test]
+    fn scaled_input_rate_follows_speed() {
+        assert_eq!(
+            scaled_input_rate(44100, 1.0),
+            44100,
+            "Normal speed is unchanged"
+        );
+        assert_eq!(
+            scaled_input_rate(22050, 1.0),
+            22050,
+            "Normal speed is not rounded"
+        );
+        assert_eq!(
+            scaled_input_rate(44100, 1.5),
+            66200,
+            "Faster speed reads more frames"
+        );
+        assert_eq!(
+            scaled_input_rate(48000, 0.75),
+            36000,
+            "Slower speed reads fewer frames"
+        );
+    }
+
     #[test]
     fn resampler_creates_with_valid_params() -> Result<()> {
         let r = AudioResampler::new(44100, 48000, 1024, 2)?;
@@ -467,6 +616,18 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn every_quality_profile_converts_and_survives_reconfigure() -> Result<()> {
+        for quality in ResampleQuality::ALL {
+            let mut r = AudioResampler::with_quality(44100, 48000, 1024, 2, quality)?;
+            r.push_input(&vec![0.0_f32; 1024 * 2 * 2]);
+            ensure!(r.process()?.is_some(), "{quality:?} produced no output");
+            r.reconfigure(96000, 48000)?;
+            ensure!(r.quality() == quality, "{quality:?} lost on reconfigure");
+        }
+        Ok(())
+    }
+
     #[test]
     fn resampler_reset_clears_accumulator() -> Result<()> {
         let mut r = AudioResampler::new(44100, 48000, 1024, 2)?;
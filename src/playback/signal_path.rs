@@ -0,0 +1,334 @@
+//! Report of the processing stages between the decoder and the device.
+//!
+//! Bit-perfect mode skips the equalizer, the channel mix and software
+//! volume, but a track whose rate or speed differs from the device is still
+//! resampled. The report lists what actually touches the samples so the UI
+//! only claims bit-perfect output when nothing does. It also tells whether
+//! the current track joined its predecessor gaplessly, so a gapless
+//! indicator can admit a hard cut.
+//!
+//! The level meter is no stage: it only reads the samples, after processing
+//! and before the device, and bit-perfect output skips it.
+
+use crate::playback::{
+    engine::{MuteState::Muted, PlaybackState},
+    gapless::{
+        GaplessMode::Enabled,
+        TrackEntry::{self, HardCut, Started},
+    },
+    output::OutputMode::{self, BitPerfect},
+    resampler::ResampleQuality,
+    stereo::DownmixMode::Stereo,
+};
+
+/// Snapshot of the signal path of the current track.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalPathReport {
+    /// Output mode chosen by the user.
+    pub output_mode: OutputMode,
+    /// Sample rate of the current track in Hz (`0` before the first track).
+    pub track_sample_rate: u32,
+    /// Sample rate the device runs at in Hz (`0` before an output opens).
+    pub device_sample_rate: u32,
+    /// Speed applied to the current track (`1.0` is normal speed).
+    pub playback_rate: f64,
+    /// Quality profile used when the track is resampled.
+    pub resample_quality: ResampleQuality,
+    /// Stages currently changing the samples, in signal order.
+    pub stages: Vec<SignalStage>,
+    /// Whether gapless playback is enabled.
+    pub gapless: bool,
+    /// How the current track took over from the previous one.
+    pub entry: TrackEntry,
+}
+
+impl SignalPathReport {
+    /// Work out the active stages from the engine state.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - Current playback state
+    /// * `track_sample_rate` - Sample rate of the current track in Hz
+    /// * `device_sample_rate` - Sample rate of the open output in Hz
+    /// * `equalizer_active` - Whether the equalizer would change the signal
+    #[must_use]
+    pub fn new(
+        state: &PlaybackState,
+        track_sample_rate: u32,
+        device_sample_rate: u32,
+        equalizer_active: bool,
+    ) -> Self {
+        let processed = state.output_mode != BitPerfect;
+        let playback_rate = state.effective_playback_rate();
+        let mixed = state.downmix != Stereo || state.balance.abs() >= f64::EPSILON;
+        let attenuated = state.muted == Muted || (state.volume - 1.0).abs() >= f64::EPSILON;
+        let mut report = Self {
+            output_mode: state.output_mode,
+            track_sample_rate,
+            device_sample_rate,
+            playback_rate,
+            resample_quality: state.resample_quality,
+            stages: Vec::new(),
+            gapless: state.gapless_mode == Enabled,
+            entry: Started,
+        };
+        let resampled = report.rate_differs() || (playback_rate - 1.0).abs() >= f64::EPSILON;
+        report.stages = [
+            (SignalStage::Resampler, resampled),
+            (SignalStage::Equalizer, processed && equalizer_active),
+            (SignalStage::ChannelMix, processed && mixed),
+            (SignalStage::SoftwareVolume, processed && attenuated),
+        ]
+        .into_iter()
+        .filter_map(|(stage, active)| active.then_some(stage))
+        .collect();
+        report
+    }
+
+    /// Record how the current track took over from the previous one.
+    #[must_use]
+    pub const fn with_entry(mut self, entry: TrackEntry) -> Self {
+        self.entry = entry;
+        self
+    }
+
+    /// Whether gapless playback is on but the current track still followed
+    /// its predecessor with a hard cut, for example because the output had
+    /// to reopen at a new sample rate.
+    #[must_use]
+    pub const fn gapless_fell_back(&self) -> bool {
+        self.gapless && matches!(self.entry, HardCut)
+    }
+
+    /// Whether decoded samples reach the device unchanged.
+    ///
+    /// Requires bit-perfect mode, since resampled mode may start scaling
+    /// the signal at any moment.
+    #[must_use]
+    pub fn is_bit_perfect(&self) -> bool {
+        self.output_mode == BitPerfect && self.stages.is_empty()
+    }
+
+    /// One-line description for tooltips and the preferences.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        if self.is_bit_perfect() {
+            return "Bit-perfect \u{2014} samples reach the device unchanged".to_string();
+        }
+        if self.stages.is_empty() {
+            return "Resampled mode \u{2014} no processing active right now, but volume and \
+                    effects may change the signal"
+                .to_string();
+        }
+        let stages: Vec<String> = self.stages.iter().map(|s| self.describe(*s)).collect();
+        format!("Not bit-perfect \u{2014} {}", stages.join(", "))
+    }
+
+    /// One-line bit-perfect and gapless status.
+    ///
+    /// A track that followed the previous one with a hard cut although
+    /// gapless playback is on is reported as such.
+    #[must_use]
+    pub fn status_line(&self) -> String {
+        let output = if self.is_bit_perfect() {
+            "Bit-perfect"
+        } else {
+            "Not bit-perfect"
+        };
+        let transitions = if !self.gapless {
+            "Gapless off"
+        } else if self.gapless_fell_back() {
+            "Hard cut"
+        } else {
+            "Gapless"
+        };
+        format!("{output} \u{2022} {transitions}")
+    }
+
+    /// Whether the track and device rates are both known and differ.
+    const fn rate_differs(&self) -> bool {
+        self.track_sample_rate != 0
+            && self.device_sample_rate != 0
+            && self.track_sample_rate != self.device_sample_rate
+    }
+
+    /// Stage name, with the rate conversion or speed and the quality
+    /// profile for the resampler.
+    fn describe(&self, stage: SignalStage) -> String {
+        if stage != SignalStage::Resampler {
+            return stage.label().to_string();
+        }
+        let quality = self.resample_quality.label();
+        if self.rate_differs() {
+            let from = f64::from(self.track_sample_rate) / 1000.0;
+            let to = f64::from(self.device_sample_rate) / 1000.0;
+            return format!(
+                "{} ({from:.1} \u{2192} {to:.1} kHz, {quality} quality)",
+                stage.label()
+            );
+        }
+        format!(
+            "{} ({}\u{d7} speed, {quality} quality)",
+            stage.label(),
+            self.playback_rate
+        )
+    }
+}
+
+/// Processing stage that changes samples on their way to the device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalStage {
+    /// Sample-rate conversion to the device rate or for a changed speed.
+    Resampler,
+    /// Graphic equalizer.
+    Equalizer,
+    /// Mono, swapped channels, or an off-centre balance.
+    ChannelMix,
+    /// Software volume below full scale, or muted.
+    SoftwareVolume,
+}
+
+impl SignalStage {
+    /// Human-readable stage name.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Resampler => "resampler",
+            Self::Equalizer => "equalizer",
+            Self::ChannelMix => "channel mix",
+            Self::SoftwareVolume => "software volume",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::playback::{
+        engine::PlaybackState,
+        gapless::{
+            GaplessMode::Disabled,
+            TrackEntry::{HardCut, Seamless},
+        },
+        output::OutputMode::{BitPerfect, Resampled},
+        resampler::ResampleQuality::Fast,
+        signal_path::{
+            SignalPathReport,
+            SignalStage::{Equalizer, Resampler, SoftwareVolume},
+        },
+    };
+
+    #[test]
+    fn bit_perfect_needs_native_rate() {
+        let state = PlaybackState {
+            output_mode: BitPerfect,
+            volume: 0.5,
+            ..PlaybackState::default()
+        };
+        let report = SignalPathReport::new(&state, 44_100, 44_100, true);
+        assert!(
+            report.is_bit_perfect(),
+            "equalizer and volume are skipped in bit-perfect mode"
+        );
+
+        let report = SignalPathReport::new(&state, 44_100, 48_000, false);
+        assert_eq!(report.stages, vec![Resampler], "rate mismatch resamples");
+        assert!(!report.is_bit_perfect(), "resampled output is not lit");
+        assert!(
+            report.summary().contains("44.1 \u{2192} 48.0 kHz"),
+            "summary names the conversion"
+        );
+    }
+
+    #[test]
+    fn resampler_stage_names_quality_profile() {
+        let state = PlaybackState {
+            resample_quality: Fast,
+            ..PlaybackState::default()
+        };
+        let report = SignalPathReport::new(&state, 44_100, 48_000, false);
+        assert!(
+            report.summary().contains("Fast quality"),
+            "summary names the active profile"
+        );
+    }
+
+    #[test]
+    fn resampled_mode_lists_processing() {
+        let state = PlaybackState {
+            output_mode: Resampled,
+            volume: 0.8,
+            ..PlaybackState::default()
+        };
+        let report = SignalPathReport::new(&state, 96_000, 96_000, true);
+        assert_eq!(
+            report.stages,
+            vec![Equalizer, SoftwareVolume],
+            "stages listed in signal order"
+        );
+
+        let unity = PlaybackState {
+            volume: 1.0,
+            ..state
+        };
+        let report = SignalPathReport::new(&unity, 96_000, 96_000, false);
+        assert!(report.stages.is_empty(), "nothing touches the samples");
+        assert!(!report.is_bit_perfect(), "resampled mode is never lit");
+    }
+
+    #[test]
+    fn hard_cut_counts_as_fallback_only_with_gapless_on() {
+        let state = PlaybackState::default();
+        let report = SignalPathReport::new(&state, 44_100, 44_100, false);
+        assert!(
+            !report.gapless_fell_back(),
+            "a started track is no fallback"
+        );
+        assert!(
+            !report.clone().with_entry(Seamless).gapless_fell_back(),
+            "a seamless transition is no fallback"
+        );
+        assert!(
+            report.with_entry(HardCut).gapless_fell_back(),
+            "a hard cut with gapless on is a fallback"
+        );
+
+        let off = PlaybackState {
+            gapless_mode: Disabled,
+            ..PlaybackState::default()
+        };
+        let report = SignalPathReport::new(&off, 44_100, 44_100, false).with_entry(HardCut);
+        assert!(
+            !report.gapless_fell_back(),
+            "hard cuts are expected with gapless off"
+        );
+    }
+
+    #[test]
+    fn status_names_output_and_gapless() {
+        let state = PlaybackState {
+            output_mode: BitPerfect,
+            ..PlaybackState::default()
+        };
+        let native = SignalPathReport::new(&state, 44_100, 44_100, false);
+        assert_eq!(
+            native.status_line(),
+            "Bit-perfect \u{2022} Gapless",
+            "untouched output with gapless on"
+        );
+        assert_eq!(
+            native.with_entry(HardCut).status_line(),
+            "Bit-perfect \u{2022} Hard cut",
+            "gapless on but the output reopened"
+        );
+        let gapless_off = PlaybackState {
+            gapless_mode: Disabled,
+            ..state
+        };
+        let resampled = SignalPathReport::new(&gapless_off, 44_100, 48_000, false);
+        assert_eq!(
+            resampled.status_line(),
+            "Not bit-perfect \u{2022} Gapless off",
+            "resampled output with gapless off"
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! Skipping the silence at the start of a track.
+//!
+//! Some tracks open with seconds of near-silence, which is tiresome on
+//! shuffle. When enabled, the decode loop drops the first frames of a track
+//! while every sample stays below a threshold, and moves the reported
+//! position past them, so the elapsed time still matches the file. The skip
+//! ends at the first audible frame, after [`MAX_SKIP_SECONDS`], or when
+//! the user seeks.
+
+use std::ops::RangeInclusive;
+
+use {num_traits::cast::cast, tracing::debug};
+
+use crate::playback::pipeline::LoopCtx;
+
+/// Threshold used until the user picks another one, in dBFS.
+pub const DEFAULT_SILENCE_THRESHOLD_DB: i32 = -60;
+
+/// Longest stretch of leading silence skipped, in seconds.
+pub const MAX_SKIP_SECONDS: f64 = 30.0;
+
+/// Thresholds the user can choose from, in dBFS.
+pub const SILENCE_THRESHOLD_DB_RANGE: RangeInclusive<i32> = -90..=-30;
+
+/// Leading silence still being skipped in the track playing now.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LeadingSilence {
+    /// Linear amplitude below which a sample counts as silent.
+    threshold: f32,
+    /// Frames that may still be skipped before playback starts anyway.
+    remaining_frames: u64,
+    /// Whether an audible frame has been reached.
+    audible: bool,
+}
+
+impl LeadingSilence {
+    /// Start skipping silence below `threshold_db` in a track at `sample_rate`.
+    #[must_use]
+    pub fn new(threshold_db: i32, sample_rate: f64) -> Self {
+        Self {
+            threshold: cast(10f64.powf(f64::from(threshold_db) / 20.0)).unwrap_or(0.0),
+            remaining_frames: cast(MAX_SKIP_SECONDS * sample_rate).unwrap_or(0),
+            audible: false,
+        }
+    }
+
+    /// Whether the skip is over and the rest of the track plays unchanged.
+    #[must_use]
+    pub const fn is_finished(&self) -> bool {
+        self.audible || self.remaining_frames == 0
+    }
+
+    /// Drop the silent frames at the start of interleaved `samples`.
+    ///
+    /// # Returns
+    ///
+    /// The number of frames dropped.
+    pub fn trim(&mut self, samples: &mut Vec<f32>, channels: usize) -> usize {
+        let channels = channels.max(1);
+        let frames = samples.len() / channels;
+        let limit = usize::try_from(self.remaining_frames)
+            .unwrap_or(usize::MAX)
+            .min(frames);
+        let silent = samples
+            .chunks_exact(channels)
+            .take(limit)
+            .take_while(|frame| frame.iter().all(|s| s.abs() < self.threshold))
+            .count();
+        samples.drain(..silent * channels);
+        self.remaining_frames = self
+            .remaining_frames
+            .saturating_sub(u64::try_from(silent).unwrap_or(u64::MAX));
+        self.audible = silent < limit;
+        silent
+    }
+}
+
+/// Drop leading silence from a decoded batch of the loop's track.
+///
+/// The skipped time is added to the elapsed time, so the position reported
+/// once playback starts is the position within the file.
+pub fn skip_leading_silence(ctx: &mut LoopCtx, samples: &mut Vec<f32>) {
+    let Some(silence) = ctx.leading_silence.as_mut() else {
+        return;
+    };
+    let skipped = silence.trim(samples, ctx.src_channels);
+    let skipped = u32::try_from(skipped).unwrap_or(u32::MAX);
+    ctx.elapsed += f64::from(skipped) / ctx.track_sample_rate_f64;
+    if silence.is_finished() {
+        debug!(skipped_seconds = ctx.elapsed, "Skipped leading silence");
+        ctx.leading_silence = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::playback::silence::LeadingSilence;
+
+    #[test]
+    fn silent_frames_are_dropped_up_to_the_first_audible_one() {
+        let mut silence = LeadingSilence::new(-60, 10.0);
+        let mut samples = vec![0.0, 0.0001, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0];
+        assert_eq!(silence.trim(&mut samples, 2), 2, "Two silent frames");
+        assert_eq!(
+            samples,
+            [0.5, 0.0, 0.0, 0.0],
+            "Audio from the first loud frame"
+        );
+        assert!(silence.is_finished(), "An audible frame ends the skip");
+    }
+
+    #[test]
+    fn silence_spanning_batches_keeps_skipping() {
+        let mut silence = LeadingSilence::new(-60, 10.0);
+        let mut samples = vec![0.0; 4];
+        assert_eq!(silence.trim(&mut samples, 2), 2, "Whole batch is silent");
+        assert!(samples.is_empty(), "Nothing left to play");
+        assert!(!silence.is_finished(), "Skip continues into the next batch");
+    }
+
+    #[test]
+    fn skip_stops_at_the_limit() {
+        let mut silence = LeadingSilence::new(-60, 0.1);
+        let mut samples = vec![0.0; 10];
+        assert_eq!(
+            silence.trim(&mut samples, 1),
+            3,
+            "At most 30 seconds at 0.1 Hz"
+        );
+        assert_eq!(samples.len(), 7, "The rest is played");
+        assert!(silence.is_finished(), "The limit ends the skip");
+    }
+}
@@ -0,0 +1,199 @@
+//! Coalescing of rapid next/previous presses.
+//!
+//! Every press moves the queue at once, but the track is only loaded once
+//! the presses stop for [`SKIP_SETTLE`]. A burst of presses thus loads the
+//! track it ends on, instead of stopping a decode thread and opening a
+//! decoder, and possibly the output device, for every track on the way.
+
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    thread::{Builder, sleep},
+    time::{Duration, Instant},
+};
+
+use {
+    parking_lot::Mutex,
+    tracing::{debug, error},
+};
+
+use crate::playback::{cue_skip::request_cue_jump, engine::EngineShared, worker::start_playback};
+
+/// Quiet time after the last skip before its track is loaded.
+pub const SKIP_SETTLE: Duration = Duration::from_millis(150);
+
+/// Skip waiting for the burst it belongs to to end.
+struct PendingSkip<T> {
+    /// Track the burst currently ends on.
+    target: T,
+    /// When the target is loaded unless another skip arrives.
+    due: Instant,
+}
+
+/// Keeps the latest skip target of a burst until the burst ends.
+pub struct SkipCoalescer<T> {
+    /// Skip waiting to be loaded, if a burst is in progress.
+    pending: Mutex<Option<PendingSkip<T>>>,
+    /// Quiet time that ends a burst.
+    settle: Duration,
+}
+
+impl<T> SkipCoalescer<T> {
+    /// Coalescer ending bursts after `settle` without skips.
+    #[must_use]
+    pub const fn new(settle: Duration) -> Self {
+        Self {
+            pending: Mutex::new(None),
+            settle,
+        }
+    }
+
+    /// Make `target` the track the current burst ends on.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - Track the skip moved to
+    /// * `now` - Time of the skip
+    ///
+    /// # Returns
+    ///
+    /// `true` if the skip starts a new burst, in which case the caller
+    /// must start a waiter polling [`Self::poll`].
+    pub fn request(&self, target: T, now: Instant) -> bool {
+        let mut pending = self.pending.lock();
+        let starts_burst = pending.is_none();
+        *pending = Some(PendingSkip {
+            target,
+            due: now + self.settle,
+        });
+        starts_burst
+    }
+
+    /// Take the target once the burst has settled.
+    pub fn poll(&self, now: Instant) -> SkipPoll<T> {
+        let mut pending = self.pending.lock();
+        match pending.as_ref() {
+            None => SkipPoll::Idle,
+            Some(skip) if skip.due > now => SkipPoll::Wait(skip.due - now),
+            Some(_) => pending
+                .take()
+                .map_or(SkipPoll::Idle, |skip| SkipPoll::Load(skip.target)),
+        }
+    }
+
+    /// Drop the pending skip, when another track is played directly or
+    /// playback stops.
+    pub fn cancel(&self) -> Option<T> {
+        self.pending.lock().take().map(|skip| skip.target)
+    }
+}
+
+impl<T> Default for SkipCoalescer<T> {
+    fn default() -> Self {
+        Self::new(SKIP_SETTLE)
+    }
+}
+
+/// What the waiter of a burst does next.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SkipPoll<T> {
+    /// The burst is over; load this track.
+    Load(T),
+    /// Check again after this long.
+    Wait(Duration),
+    /// Nothing is pending, as the burst was superseded.
+    Idle,
+}
+
+/// Load `track_id` once the current burst of skips settles.
+///
+/// The first skip of a burst starts a thread waiting for it to settle;
+/// later ones only move its target. Skips to another CUE track of the
+/// file being played need no loading and are handed to the decode thread
+/// at once.
+///
+/// # Arguments
+///
+/// * `shared` - Engine state holding the pending skip
+/// * `track_id` - Track the queue moved to
+/// * `path` - File path of the track
+pub fn queue_skip(shared: &Arc<EngineShared>, track_id: i64, path: PathBuf) {
+    if request_cue_jump(shared, track_id, &path) {
+        return;
+    }
+    if !shared.skips.request((track_id, path), Instant::now()) {
+        debug!(track_id, "Skip joined pending burst");
+        return;
+    }
+    let engine_shared = Arc::clone(shared);
+    if let Err(e) = Builder::new()
+        .name("skip-settle".to_string())
+        .spawn(move || settle_skips(&engine_shared))
+    {
+        error!(error = %e, "Failed to spawn skip settle thread");
+        if let Some((track_id, path)) = shared.skips.cancel() {
+            start_playback(shared, track_id, path);
+        }
+    }
+}
+
+/// Wait for the pending burst to settle and load the track it ended on.
+fn settle_skips(shared: &Arc<EngineShared>) {
+    loop {
+        match shared.skips.poll(Instant::now()) {
+            SkipPoll::Wait(remaining) => sleep(remaining),
+            SkipPoll::Load((track_id, path)) => {
+                start_playback(shared, track_id, path);
+                return;
+            }
+            SkipPoll::Idle => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::playback::skip::{SkipCoalescer, SkipPoll};
+
+    #[test]
+    fn burst_of_skips_loads_only_the_final_track() {
+        let skips = SkipCoalescer::new(Duration::from_millis(100));
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        assert!(skips.request(2, at(0)), "first skip starts a burst");
+        assert!(!skips.request(3, at(40)), "later skips join it");
+        assert_eq!(
+            skips.poll(at(100)),
+            SkipPoll::Wait(Duration::from_millis(40))
+        );
+        assert!(!skips.request(4, at(120)));
+
+        assert_eq!(
+            skips.poll(at(219)),
+            SkipPoll::Wait(Duration::from_millis(1)),
+            "every skip restarts the quiet time"
+        );
+        assert_eq!(
+            skips.poll(at(220)),
+            SkipPoll::Load(4),
+            "only the last target loads"
+        );
+        assert_eq!(skips.poll(at(400)), SkipPoll::Idle, "a burst loads once");
+        assert!(
+            skips.request(5, at(500)),
+            "the next skip starts a new burst"
+        );
+    }
+
+    #[test]
+    fn cancelled_burst_loads_nothing() {
+        let skips = SkipCoalescer::new(Duration::from_millis(100));
+        let now = Instant::now();
+        skips.request(7, now);
+        assert_eq!(skips.cancel(), Some(7));
+        assert_eq!(skips.poll(now + Duration::from_secs(1)), SkipPoll::Idle);
+    }
+}
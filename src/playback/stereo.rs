@@ -0,0 +1,219 @@
+//! Channel mode and balance applied to the left/right pair by the audio
+//! callback.
+//!
+//! Both settings are published through a shared [`StereoControl`] and read
+//! once per callback, so a change is heard within one device buffer rather
+//! than after the ring buffer drains. Bit-perfect output bypasses the mix.
+
+use std::sync::atomic::{
+    AtomicBool, AtomicU8, AtomicU32,
+    Ordering::{Acquire, Relaxed, Release},
+};
+
+use {
+    num_traits::cast::AsPrimitive,
+    serde::{Deserialize, Serialize},
+};
+
+/// How the left and right channels reach the device.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownmixMode {
+    /// Channels pass through unchanged.
+    #[default]
+    Stereo,
+    /// Both channels carry the average of left and right.
+    Mono,
+    /// Left and right are exchanged.
+    #[serde(rename = "swap_lr")]
+    SwapLR,
+}
+
+impl DownmixMode {
+    /// All modes, in the order the preferences list shows them.
+    pub const ALL: [Self; 3] = [Self::Stereo, Self::Mono, Self::SwapLR];
+
+    /// Human-readable name for the preferences list.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Stereo => "Stereo",
+            Self::Mono => "Mono",
+            Self::SwapLR => "Swap Left and Right",
+        }
+    }
+
+    /// Encoding stored in [`StereoControl`].
+    const fn to_u8(self) -> u8 {
+        match self {
+            Self::Stereo => 0,
+            Self::Mono => 1,
+            Self::SwapLR => 2,
+        }
+    }
+
+    /// Decode a value written by [`Self::to_u8`].
+    const fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Mono,
+            2 => Self::SwapLR,
+            _ => Self::Stereo,
+        }
+    }
+}
+
+/// Channel mode and balance shared between the output handle and the audio
+/// callback.
+///
+/// The balance is stored as `f32::to_bits()` so the callback can read it
+/// with a single atomic load.
+pub struct StereoControl {
+    /// [`DownmixMode`] encoded with `DownmixMode::to_u8`.
+    mode: AtomicU8,
+    /// Balance from `-1.0` (left only) to `1.0` (right only).
+    balance: AtomicU32,
+    /// Set in bit-perfect mode, where samples must reach the device unchanged.
+    bypass: AtomicBool,
+}
+
+impl StereoControl {
+    /// Publish a new channel mode and balance.
+    ///
+    /// The balance is clamped to `-1.0..=1.0`.
+    pub fn set(&self, mode: DownmixMode, balance: f64) {
+        let balance: f32 = balance.clamp(-1.0, 1.0).as_();
+        self.balance.store(balance.to_bits(), Relaxed);
+        self.mode.store(mode.to_u8(), Release);
+    }
+
+    /// Skip the mix entirely while `bypass` is set.
+    pub fn set_bypass(&self, bypass: bool) {
+        self.bypass.store(bypass, Relaxed);
+    }
+
+    /// The mix the callback applies to the next buffer.
+    #[must_use]
+    pub fn mix(&self) -> StereoMix {
+        if self.bypass.load(Relaxed) {
+            return StereoMix::default();
+        }
+        let mode = DownmixMode::from_u8(self.mode.load(Acquire));
+        StereoMix::new(mode, f32::from_bits(self.balance.load(Relaxed)))
+    }
+}
+
+impl Default for StereoControl {
+    fn default() -> Self {
+        Self {
+            mode: AtomicU8::new(DownmixMode::Stereo.to_u8()),
+            balance: AtomicU32::new(0.0_f32.to_bits()),
+            bypass: AtomicBool::new(false),
+        }
+    }
+}
+
+/// Channel mode and per-side gains for one callback invocation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StereoMix {
+    /// How left and right are combined.
+    mode: DownmixMode,
+    /// Gain applied to the left output channel.
+    left_gain: f32,
+    /// Gain applied to the right output channel.
+    right_gain: f32,
+}
+
+impl StereoMix {
+    /// Mix for `mode` with `balance` in `-1.0..=1.0`.
+    ///
+    /// Balance only attenuates the side it moves away from, so the centre
+    /// position leaves both channels at full level.
+    #[must_use]
+    pub fn new(mode: DownmixMode, balance: f32) -> Self {
+        let balance = balance.clamp(-1.0, 1.0);
+        Self {
+            mode,
+            left_gain: (1.0 - balance).min(1.0),
+            right_gain: (1.0 + balance).min(1.0),
+        }
+    }
+
+    /// Mix one left/right sample pair.
+    ///
+    /// Mono averages the two channels: each contributes at half level, so
+    /// full-scale material on both sides cannot clip, and uncorrelated
+    /// material comes out about 3 dB below its stereo loudness.
+    #[must_use]
+    pub fn apply(self, left: f32, right: f32) -> (f32, f32) {
+        let (left, right) = match self.mode {
+            DownmixMode::Stereo => (left, right),
+            DownmixMode::Mono => {
+                let mid = (left + right) * 0.5;
+                (mid, mid)
+            }
+            DownmixMode::SwapLR => (right, left),
+        };
+        (left * self.left_gain, right * self.right_gain)
+    }
+}
+
+impl Default for StereoMix {
+    fn default() -> Self {
+        Self::new(DownmixMode::Stereo, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::playback::stereo::{
+        DownmixMode::{Mono, Stereo, SwapLR},
+        StereoControl, StereoMix,
+    };
+
+    #[test]
+    fn mono_averages_without_clipping() {
+        let (left, right) = StereoMix::new(Mono, 0.0).apply(1.0, 1.0);
+        assert!((left - 1.0).abs() < f32::EPSILON, "full scale stays at 1.0");
+        assert!((right - left).abs() < f32::EPSILON, "both sides match");
+
+        let (left, _) = StereoMix::new(Mono, 0.0).apply(1.0, -1.0);
+        assert!(left.abs() < f32::EPSILON, "opposite phases cancel");
+    }
+
+    #[test]
+    fn swap_exchanges_channels() {
+        assert_eq!(
+            StereoMix::new(SwapLR, 0.0).apply(0.25, -0.5),
+            (-0.5, 0.25),
+            "left and right swapped"
+        );
+    }
+
+    #[test]
+    fn balance_attenuates_the_opposite_side() {
+        assert_eq!(
+            StereoMix::new(Stereo, 0.5).apply(1.0, 1.0),
+            (0.5, 1.0),
+            "right of centre halves the left channel"
+        );
+        assert_eq!(
+            StereoMix::new(Stereo, -1.0).apply(1.0, 1.0),
+            (1.0, 0.0),
+            "hard left mutes the right channel"
+        );
+    }
+
+    #[test]
+    fn bypass_leaves_samples_untouched() {
+        let control = StereoControl::default();
+        control.set(SwapLR, 0.75);
+        assert_ne!(control.mix(), StereoMix::default(), "mix published");
+
+        control.set_bypass(true);
+        assert_eq!(
+            control.mix().apply(0.1, 0.2),
+            (0.1, 0.2),
+            "bit-perfect output is not mixed"
+        );
+    }
+}
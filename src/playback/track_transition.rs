@@ -1,15 +1,115 @@
 //! Handling track boundaries: gapless transitions, auto-advance, and finalisation.
 
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use tracing::info;
+use tracing::{info, warn};
 
-use crate::playback::engine::{
-    EngineShared,
-    PlaybackEvent::{self, Stopped, TrackFinished},
-    PlaybackStatus::{Playing, Stopped as StatusStopped},
+use crate::playback::{
+    engine::{
+        EngineShared,
+        PlaybackEvent::{self, Stopped, TrackFinished},
+        PlaybackStatus::{Playing, Stopped as StatusStopped},
+    },
+    gapless::GaplessMode::Enabled,
+    pipeline::{LoopCtx, OutputConfig, switches_output_rate},
+    resampler::create_resampler,
 };
 
+/// Silence to leave after the current track before the next one starts.
+///
+/// Returns `None` when no gap applies or no track follows, so stopping at
+/// the end of the queue is not delayed.
+pub fn gap_before_next(engine_shared: &EngineShared) -> Option<Duration> {
+    let gap = engine_shared.state.lock().inter_track_gap()?;
+    engine_shared.queue.peek_advance().is_some().then_some(gap)
+}
+
+/// Handle an empty decode batch (track finished).
+///
+/// Attempts a gapless transition. Returns `Some(track_id)` if a transition was
+/// applied and the decode loop should continue. Returns `None` if no
+/// pre-buffered track is available, if gapless playback is off, or if the
+/// next track should reopen the output at its own sample rate.
+pub fn handle_empty_batch(
+    engine_shared: &Arc<EngineShared>,
+    ctx: &mut LoopCtx,
+    output_cfg: OutputConfig,
+) -> Option<i64> {
+    let device_sample_rate = output_cfg.device_sample_rate;
+    let mut transitioner = engine_shared.transitioner.lock();
+    let next_id = transitioner.next_track_id();
+    let next_decoder = transitioner.transition();
+    drop(transitioner);
+    // Crossfade pre-buffers the next track even with gapless playback off.
+    if engine_shared.state.lock().gapless_mode != Enabled {
+        return None;
+    }
+
+    let (Some(next_id), Some(next_decoder)) = (next_id, next_decoder) else {
+        return None;
+    };
+
+    let params = next_decoder.params();
+    let next_sr = params.sample_rate;
+    if switches_output_rate(engine_shared, next_sr, device_sample_rate) {
+        info!(
+            track_id = next_id,
+            sample_rate = next_sr,
+            "Reopening output at the next track's sample rate"
+        );
+        return None;
+    }
+
+    if engine_shared.queue.peek_advance() != Some(next_id) {
+        return None;
+    }
+    if engine_shared.queue.advance() != Some(next_id) {
+        warn!(
+            track_id = next_id,
+            "Queue changed during the gapless transition; reopening the next track"
+        );
+        return None;
+    }
+    {
+        let mut state = engine_shared.state.lock();
+        state.current_track_id = Some(next_id);
+        let path = engine_shared.track_paths.lock().get(&next_id).cloned();
+        state.current_path = path;
+        state.elapsed_seconds = 0.0;
+        state.duration_seconds = params.duration_seconds;
+        state.channel_layout = Some(params.layout);
+    }
+    *engine_shared.track_sample_rate.lock() = next_sr;
+
+    if next_sr != ctx.track_sample_rate {
+        match create_resampler(
+            next_sr,
+            device_sample_rate,
+            output_cfg.channels as usize,
+            output_cfg.resample_quality,
+        ) {
+            Ok(r) => ctx.resampler = Some(r),
+            Err(e) => {
+                warn!("Resampler reconfiguration failed: {e}");
+                return None;
+            }
+        }
+    }
+
+    ctx.decoder = next_decoder;
+    ctx.track_sample_rate = next_sr;
+    ctx.src_channels = params.channels as usize;
+    ctx.elapsed = 0.0;
+    ctx.last_tick = Instant::now();
+    ctx.track_sample_rate_f64 = f64::from(next_sr);
+
+    Some(next_id)
+}
+
 /// Try to advance to the next track in the queue after a track finishes.
 ///
 /// Advances the queue and updates playback state. Returns `Some((track_id, path))`
@@ -20,7 +120,7 @@ pub fn try_auto_advance(
 ) -> Option<(i64, PathBuf)> {
     let next_track = match &event_to_send {
         Some(TrackFinished { .. }) => {
-            let next_id = engine_shared.queue.next();
+            let next_id = engine_shared.queue.advance();
             next_id.and_then(|next_id| {
                 let path = engine_shared.track_paths.lock().get(&next_id).cloned()?;
                 Some((next_id, path))
@@ -40,6 +140,7 @@ pub fn try_auto_advance(
         state.status = Playing;
         state.elapsed_seconds = 0.0;
         state.duration_seconds = 0.0;
+        state.channel_layout = None;
     }
 
     info!(next_id, "Auto-advancing to next track",);
@@ -71,6 +172,7 @@ pub fn finalize_track(
         state.current_path = None;
         state.elapsed_seconds = 0.0;
         state.duration_seconds = 0.0;
+        state.channel_layout = None;
         drop(state);
         if had_track
             && engine_shared.queue.upcoming().is_empty()
@@ -91,20 +193,98 @@ pub fn finalize_track(
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
+    use std::{
+        fs::File,
+        io::Write,
+        path::{Path, PathBuf},
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+
+    use {
+        anyhow::{Result, ensure},
+        tempfile::tempdir,
+    };
 
     use crate::playback::{
+        decoder::Decoder,
         engine::{
             EngineShared,
             PlaybackEvent::{Paused, TrackFinished},
         },
-        track_transition::try_auto_advance,
+        gapless::GaplessMode::Disabled,
+        pipeline::{LoopCtx, OutputConfig},
+        queue::RepeatMode::One,
+        resampler::ResampleQuality,
+        track_transition::{gap_before_next, handle_empty_batch, try_auto_advance},
+        write_wav_header,
     };
 
     fn make_shared_engine() -> Arc<EngineShared> {
         Arc::new(EngineShared::default())
     }
 
+    /// Write a tenth of a second of 8 kHz mono silence.
+    fn write_short_wav(path: &Path) -> Result<()> {
+        let mut f = File::create(path)?;
+        write_wav_header(&mut f, 1, 8000, 16, 1600)?;
+        f.write_all(&[0_u8; 1600])?;
+        Ok(())
+    }
+
+    #[test]
+    fn gapless_transition_advances_queue() -> Result<()> {
+        let dir = tempdir()?;
+        let (first, second) = (dir.path().join("1.wav"), dir.path().join("2.wav"));
+        write_short_wav(&first)?;
+        write_short_wav(&second)?;
+
+        let shared = make_shared_engine();
+        shared.queue.set_queue(vec![1, 2]);
+        shared.track_paths.lock().insert(2, second.clone());
+        let mut transitioner = shared.transitioner.lock();
+        transitioner.start_playback(1);
+        ensure!(
+            transitioner.prebuffer_next(1, 2, second)?,
+            "the next track is pre-buffered"
+        );
+        drop(transitioner);
+
+        let mut ctx = LoopCtx {
+            decoder: Decoder::open(&first)?,
+            resampler: None,
+            track_sample_rate: 8000,
+            src_channels: 1,
+            track_sample_rate_f64: 8000.0,
+            elapsed: 0.1,
+            last_tick: Instant::now(),
+            crossfade: None,
+            leading_silence: None,
+            continues_album: false,
+        };
+        let output_cfg = OutputConfig {
+            device_sample_rate: 8000,
+            channels: 1,
+            resample_quality: ResampleQuality::default(),
+            buffer_frames: 4096,
+        };
+
+        ensure!(
+            handle_empty_batch(&shared, &mut ctx, output_cfg) == Some(2),
+            "the pre-buffered track takes over"
+        );
+        ensure!(
+            shared.queue.current_position() == Some(1),
+            "the queue moves on to the second track"
+        );
+        ensure!(
+            shared.state.lock().current_track_id == Some(2),
+            "the second track is reported as playing"
+        );
+        ensure!(ctx.elapsed.abs() < f64::EPSILON, "elapsed time restarts");
+        Ok(())
+    }
+
     #[test]
     fn try_auto_advance_returns_none_for_non_track_finished() {
         let shared = make_shared_engine();
@@ -139,4 +319,56 @@ mod tests {
             "should return None when path not found for upcoming track"
         );
     }
+
+    #[test]
+    fn try_auto_advance_repeats_track_in_repeat_one() {
+        let shared = make_shared_engine();
+        shared.queue.set_queue(vec![1, 2]);
+        shared.queue.set_repeat_mode(One);
+        shared
+            .track_paths
+            .lock()
+            .insert(1, PathBuf::from("/music/1.flac"));
+        let mut event = Some(TrackFinished { track_id: 1 });
+        let result = try_auto_advance(&shared, &mut event);
+        assert_eq!(
+            result.map(|(id, _)| id),
+            Some(1),
+            "should replay the finished track in repeat-one mode"
+        );
+    }
+
+    #[test]
+    fn gap_only_applies_between_hard_cut_tracks() {
+        let shared = make_shared_engine();
+        shared.queue.set_queue(vec![1, 2]);
+        shared.state.lock().inter_track_gap_ms = 2000;
+        assert_eq!(
+            gap_before_next(&shared),
+            None,
+            "gapless playback must not be interrupted"
+        );
+
+        shared.state.lock().gapless_mode = Disabled;
+        assert_eq!(
+            gap_before_next(&shared),
+            Some(Duration::from_secs(2)),
+            "the gap must apply with gapless playback off"
+        );
+
+        shared.state.lock().crossfade_ms = 3000;
+        assert_eq!(
+            gap_before_next(&shared),
+            None,
+            "crossfades must not be interrupted"
+        );
+
+        shared.state.lock().crossfade_ms = 0;
+        shared.queue.set_queue(vec![1]);
+        assert_eq!(
+            gap_before_next(&shared),
+            None,
+            "stopping after the last track must not wait"
+        );
+    }
 }
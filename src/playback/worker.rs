@@ -2,7 +2,7 @@
 
 use std::{
     path::{Path, PathBuf},
-    sync::{Arc, atomic::Ordering::Relaxed},
+    sync::Arc,
     thread::{Builder, sleep},
     time::{Duration, Instant},
 };
@@ -14,20 +14,22 @@ use {
 };
 
 use crate::playback::{
+    buffer::ring_capacity,
     decoder::Decoder,
+    device_recovery::DeviceRecovery,
     engine::{
         DecodeCommand::{self, PreloadNext},
         EngineShared,
-        PlaybackEvent::{DeviceLost, Resumed, TrackStarted},
+        PlaybackEvent::{OutputOpened, TrackFinished, TrackStarted},
+        PlaybackState,
         PlaybackStatus::{Paused, Playing},
     },
-    output::{
-        AudioOutput,
-        OutputMode::{BitPerfect, Resampled},
-    },
+    gapless::TrackEntry::{HardCut, Started},
+    output::{AudioOutput, OutputMode::BitPerfect},
     pipeline::{LoopCtx, OutputConfig, handle_decode_cmd, process_decode_frame},
     resampler::{AudioResampler, create_resampler},
-    track_transition::finalize_track,
+    silence::LeadingSilence,
+    track_transition::{finalize_track, gap_before_next},
 };
 
 /// Initialised decoder and resampler context for a decode loop.
@@ -42,21 +44,27 @@ struct DecoderCtx {
     resampler: Option<AudioResampler>,
 }
 
-/// Open a decoder for `path` and create a resampler if needed.
+/// Open a decoder for `path`.
+///
+/// Returns `None` on failure (error event sent via `engine_shared`).
+fn open_decoder(path: &Path, engine_shared: &EngineShared) -> Option<Decoder> {
+    match Decoder::open(path) {
+        Ok(d) => Some(d),
+        Err(e) => {
+            engine_shared.send_error_event(&format!("Failed to open decoder: {e}"));
+            None
+        }
+    }
+}
+
+/// Set up playback of `decoder`'s track and create a resampler if needed.
 ///
 /// Returns `None` on failure (error event sent via `engine_shared`).
 fn init_decoder(
-    path: &Path,
+    decoder: Decoder,
     engine_shared: &Arc<EngineShared>,
     output: OutputConfig,
 ) -> Option<DecoderCtx> {
-    let decoder = match Decoder::open(path) {
-        Ok(d) => d,
-        Err(e) => {
-            engine_shared.send_error_event(&format!("Failed to open decoder: {e}"));
-            return None;
-        }
-    };
     let track_sample_rate = decoder.params().sample_rate;
     let src_channels = decoder.params().channels as usize;
     let out_channels = output.channels as usize;
@@ -72,7 +80,12 @@ fn init_decoder(
     let resampler = if track_sample_rate == output.device_sample_rate {
         None
     } else {
-        match create_resampler(track_sample_rate, output.device_sample_rate, out_channels) {
+        match create_resampler(
+            track_sample_rate,
+            output.device_sample_rate,
+            out_channels,
+            output.resample_quality,
+        ) {
             Ok(r) => Some(r),
             Err(e) => {
                 engine_shared.send_error_event(&e);
@@ -89,26 +102,44 @@ fn init_decoder(
     })
 }
 
-/// Open audio output and run the decode loop for one track.
+/// Whether the output has played everything pushed into the ring buffer.
+fn output_drained(producer: &Producer<f32>) -> bool {
+    producer.slots() == producer.buffer().capacity() || producer.is_abandoned()
+}
+
+/// Run the decode loop for one track on an opened audio output.
+///
+/// Once the track has been decoded, the loop keeps handling commands until
+/// the output has played the rest of the ring buffer, since the output is
+/// dropped before the next track opens a new one. With an inter-track gap
+/// configured it then waits out the gap before advancing.
 ///
 /// Returns `Some((next_track_id, next_path))` if the track finished and the next
 /// one should start playing (auto-advance). Returns `None` if playback should stop.
 fn run_decode_loop(
-    path: &Path,
+    decoder: Decoder,
     mut producer: Producer<f32>,
     mut cmd_rx: MpscReceiver<DecodeCommand>,
     engine_shared: &Arc<EngineShared>,
     mut track_id: i64,
-    output: OutputConfig,
+    mut output: OutputConfig,
 ) -> Option<(i64, PathBuf)> {
     let DecoderCtx {
         decoder,
         track_sample_rate,
         src_channels,
         resampler,
-    } = init_decoder(path, engine_shared, output)?;
+    } = init_decoder(decoder, engine_shared, output)?;
 
     let mut event_to_send = None;
+    let mut finished = false;
+    let mut gap_end: Option<Instant> = None;
+    let entry = *engine_shared.track_entry.lock();
+    let leading_silence = engine_shared
+        .state
+        .lock()
+        .leading_silence_threshold(entry)
+        .map(|threshold_db| LeadingSilence::new(threshold_db, f64::from(track_sample_rate)));
     let mut ctx = LoopCtx {
         decoder,
         resampler,
@@ -117,34 +148,61 @@ fn run_decode_loop(
         track_sample_rate_f64: f64::from(track_sample_rate),
         elapsed: 0.0,
         last_tick: Instant::now(),
+        crossfade: None,
+        leading_silence,
+        continues_album: false,
     };
 
+    let mut recovery = DeviceRecovery::default();
     loop {
-        if engine_shared.device_lost.load(Relaxed) {
-            engine_shared.device_lost.store(false, Relaxed);
-            match reconnect_device(engine_shared) {
-                Some(new_producer) => producer = new_producer,
-                None => break,
-            }
+        if let Some((new_producer, reopened)) = recovery.poll(engine_shared, output) {
+            producer = new_producer;
+            output = reopened;
+            // Resamplers are rebuilt for the rate of the new device.
+            ctx.resampler = None;
+            ctx.crossfade = None;
         }
 
-        if handle_decode_cmd(&mut cmd_rx, engine_shared, &mut ctx) {
+        let playing = track_id;
+        if handle_decode_cmd(&mut cmd_rx, engine_shared, &mut ctx, &mut track_id) {
             break;
         }
+        if track_id != playing {
+            // A CUE track jump restarts a track that had finished decoding.
+            finished = false;
+            event_to_send = None;
+            gap_end = None;
+        }
 
-        if engine_shared.state.lock().status == Paused {
+        if recovery.waiting() || engine_shared.state.lock().status == Paused {
             sleep(Duration::from_millis(1));
             continue;
         }
 
-        if process_decode_frame(
+        // Commands stay live during the gap, so skipping ends it early.
+        if finished
+            && output_drained(&producer)
+            && gap_before_next(engine_shared).is_none_or(|gap| {
+                Instant::now() >= *gap_end.get_or_insert_with(|| Instant::now() + gap)
+            })
+        {
+            break;
+        }
+        if finished {
+            sleep(Duration::from_millis(5));
+            continue;
+        }
+
+        let done = process_decode_frame(
             &mut ctx,
             engine_shared,
             &mut event_to_send,
             &mut producer,
             output,
             &mut track_id,
-        ) {
+        );
+        finished = done && matches!(event_to_send, Some(TrackFinished { .. }));
+        if done && !finished {
             break;
         }
     }
@@ -158,18 +216,37 @@ fn run_decode_loop(
 /// Run a decode cycle for one or more tracks, dropping and re-opening the audio
 /// output between non-gapless auto-advances. Keeps potentially-blocking ALSA
 /// stream operations off the main thread.
+///
+/// Only the first output fades in; auto-advances start at full gain. With
+/// sample-rate follow on, each output is opened at its track's sample rate
+/// if the device supports it.
 fn init_decode_thread_loop(
     mut path: PathBuf,
     mut cmd_rx: MpscReceiver<DecodeCommand>,
     engine_shared: &Arc<EngineShared>,
     mut track_id: i64,
 ) {
+    let mut fade_in = true;
+    let mut previous_device_rate = 0;
     loop {
         *engine_shared.output.lock() = None;
 
-        let ring_capacity = 48000 * 2;
+        let Some(decoder) = open_decoder(&path, engine_shared) else {
+            return;
+        };
+        let (follow_source_rate, buffer_frames) = {
+            let state = engine_shared.state.lock();
+            (state.follow_source_rate, state.buffer_frames)
+        };
+        let preferred_rate = follow_source_rate.then(|| decoder.params().sample_rate);
+
         let device_lost = Arc::clone(&engine_shared.device_lost);
-        let (output, producer) = match AudioOutput::open(ring_capacity, &device_lost) {
+        let (mut output, producer) = match AudioOutput::open(
+            ring_capacity(buffer_frames),
+            &device_lost,
+            &engine_shared.levels,
+            preferred_rate,
+        ) {
             Ok(pair) => pair,
             Err(e) => {
                 engine_shared.send_error_event(&format!("Audio device unavailable: {e}"));
@@ -180,16 +257,27 @@ fn init_decode_thread_loop(
         let output_config = OutputConfig {
             device_sample_rate: output.sample_rate(),
             channels: output.channels(),
+            resample_quality: engine_shared.state.lock().resample_quality,
+            buffer_frames,
         };
         *engine_shared.device_sample_rate.lock() = output_config.device_sample_rate;
-        let current_volume = engine_shared.state.lock().volume;
-        if output.mode() == Resampled {
-            output.set_volume_atomic(current_volume);
+        let state = engine_shared.state.lock().clone();
+        configure_output(&mut output, &state);
+        if fade_in && state.output_mode != BitPerfect {
+            output.fade_in();
         }
+        fade_in = false;
         *engine_shared.output.lock() = Some(output);
+        *engine_shared.track_sample_rate.lock() = decoder.params().sample_rate;
+        engine_shared.state.lock().channel_layout = Some(decoder.params().layout);
+        engine_shared.send_event(&OutputOpened {
+            previous_device_rate,
+            device_sample_rate: output_config.device_sample_rate,
+        });
+        previous_device_rate = output_config.device_sample_rate;
 
         match run_decode_loop(
-            &path,
+            decoder,
             producer,
             cmd_rx,
             engine_shared,
@@ -199,6 +287,7 @@ fn init_decode_thread_loop(
             Some((next_id, next_path)) => {
                 let (cmd_tx, new_cmd_rx) = MpscChannel(4);
 
+                *engine_shared.track_entry.lock() = HardCut;
                 engine_shared.send_event(&TrackStarted { track_id: next_id });
 
                 send_preload_next(engine_shared, &cmd_tx);
@@ -214,9 +303,22 @@ fn init_decode_thread_loop(
     }
 }
 
+/// Carry the engine's volume, output mode, fade length, and channel mix
+/// over to a newly opened output.
+pub fn configure_output(output: &mut AudioOutput, state: &PlaybackState) {
+    output.set_fade_ms(state.fade_ms);
+    output.set_stereo_mix(state.downmix, state.balance);
+    if state.output_mode == BitPerfect {
+        output.set_mode(BitPerfect);
+        output.set_hardware_volume(state.volume);
+    } else {
+        output.set_volume_atomic(state.volume);
+    }
+}
+
 /// Send a `PreloadNext` command for the upcoming track, if any.
 fn send_preload_next(engine_shared: &Arc<EngineShared>, cmd_tx: &Sender<DecodeCommand>) {
-    let next_id = engine_shared.queue.upcoming().first().copied();
+    let next_id = engine_shared.queue.peek_advance();
     let next_path = next_id.and_then(|id| engine_shared.track_paths.lock().get(&id).cloned());
     if let (Some(track_id), Some(path)) = (next_id, next_path)
         && let Err(e) = cmd_tx.try_send(PreloadNext { track_id, path })
@@ -225,48 +327,6 @@ fn send_preload_next(engine_shared: &Arc<EngineShared>, cmd_tx: &Sender<DecodeCo
     }
 }
 
-/// Attempt to reconnect the audio output after a device loss.
-///
-/// Drops the old output before opening a new one to avoid ALSA device
-/// contention.
-fn reconnect_device(engine_shared: &Arc<EngineShared>) -> Option<Producer<f32>> {
-    let err_msg = "Audio device disconnected during playback".to_string();
-    warn!(error = %err_msg, "Audio device lost, attempting reconnection");
-    engine_shared.send_event(&DeviceLost { error: err_msg });
-
-    *engine_shared.output.lock() = None;
-
-    let ring_capacity = 48000 * 2;
-    match AudioOutput::open(ring_capacity, &engine_shared.device_lost) {
-        Ok((mut new_output, new_producer)) => {
-            let state = engine_shared.state.lock();
-            let current_vol = state.volume;
-            let mode = state.output_mode;
-            drop(state);
-            if mode == BitPerfect {
-                new_output.set_mode(mode);
-                new_output.set_hardware_volume(current_vol);
-            } else {
-                new_output.set_volume_atomic(current_vol);
-            }
-            let sr = new_output.sample_rate();
-            *engine_shared.device_sample_rate.lock() = sr;
-            *engine_shared.output.lock() = Some(new_output);
-            info!(
-                sample_rate = sr,
-                "Audio device reconnected, resuming playback"
-            );
-            engine_shared.send_event(&Resumed);
-            Some(new_producer)
-        }
-        Err(e) => {
-            error!(error = %e, "Audio device reconnection failed");
-            engine_shared.send_error_event(&format!("Audio device reconnection failed: {e}"));
-            None
-        }
-    }
-}
-
 /// Stop the currently running decode task.
 ///
 /// Drops the command sender so the old decode thread sees `Disconnected`
@@ -283,7 +343,9 @@ pub fn stop_decode_task(shared: &EngineShared) {
 ///
 /// Spawns a decode thread that handles its own `AudioOutput` lifecycle,
 /// keeping potentially-blocking device operations off the main thread.
+/// A pending next/previous skip is dropped, as this track supersedes it.
 pub fn start_playback(shared: &Arc<EngineShared>, track_id: i64, path: PathBuf) {
+    shared.skips.cancel();
     stop_decode_task(shared);
 
     {
@@ -294,7 +356,10 @@ pub fn start_playback(shared: &Arc<EngineShared>, track_id: i64, path: PathBuf)
         state.status = Playing;
         state.elapsed_seconds = 0.0;
         state.duration_seconds = 0.0;
+        state.channel_layout = None;
+        state.ab_loop = None;
     }
+    *shared.track_entry.lock() = Started;
 
     let engine_state = Arc::clone(shared);
     let (cmd_tx, cmd_rx) = MpscChannel::<DecodeCommand>(4);
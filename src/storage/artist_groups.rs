@@ -0,0 +1,181 @@
+//! Album artists grouped by the artists credited on them.
+//!
+//! With collaborations split, the artist of an album credited to "A feat.
+//! B" or "A & B" counts for each credited name that is also an artist of
+//! the library, matched ignoring case. Credits none of whose names are
+//! library artists keep their own entry. The artist grid and the artist
+//! detail page both use these groups, so an artist lists the same albums
+//! in both.
+
+use std::{cmp::Ordering::Equal, collections::HashMap};
+
+use sqlx::{SqlitePool, query_as};
+
+use crate::{
+    library::artist_credit::{artist_key, split_artist_credit},
+    storage::{
+        Artist,
+        StorageError::Database,
+        StorageResult,
+        collation::TitleCollator,
+        sort_order::ArtistSortOrder::{self, AlbumCount, Name, TrackCount},
+    },
+};
+
+/// Artists credited on albums, with their album and track counts.
+///
+/// # Errors
+///
+/// Returns an error if a query fails.
+pub async fn get_credited_artists(
+    pool: &SqlitePool,
+    sort: ArtistSortOrder,
+    collator: &TitleCollator,
+) -> StorageResult<Vec<Artist>> {
+    let album_artists: Vec<Artist> = query_as(
+        "SELECT ar.id, ar.name, COUNT(DISTINCT al.id) AS album_count, COUNT(t.id) AS \
+         track_count FROM artists ar JOIN albums al ON al.artist_id = ar.id LEFT JOIN tracks t \
+         ON t.album_id = al.id GROUP BY ar.id",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| Database(format!("Get album artists failed: {e}")))?;
+    let names = artist_names(pool).await?;
+    let names_by_id: HashMap<i64, &str> = names.iter().map(|(id, n)| (*id, n.as_str())).collect();
+
+    let credits: Vec<(i64, String)> = album_artists
+        .iter()
+        .map(|a| (a.id, a.name.clone()))
+        .collect();
+    let counts: HashMap<i64, (i32, i32)> = album_artists
+        .iter()
+        .map(|a| (a.id, (a.album_count, a.track_count)))
+        .collect();
+    let mut artists: Vec<Artist> = group_by_credit(&credits, &first_ids(&names))
+        .into_iter()
+        .filter_map(|(id, members)| {
+            let name = (*names_by_id.get(&id)?).to_string();
+            let (album_count, track_count) = members
+                .iter()
+                .filter_map(|m| counts.get(m))
+                .fold((0, 0), |(albums, tracks), (a, t)| (albums + a, tracks + t));
+            Some(Artist {
+                id,
+                name,
+                album_count,
+                track_count,
+                image_path: None,
+                bio: None,
+            })
+        })
+        .collect();
+    sort_artists(&mut artists, sort, collator);
+    Ok(artists)
+}
+
+/// IDs of the album artists whose albums are listed under `artist_id`.
+///
+/// # Returns
+///
+/// Just `artist_id` if it groups no other album artists.
+///
+/// # Errors
+///
+/// Returns an error if a query fails.
+pub async fn get_credited_album_artist_ids(
+    pool: &SqlitePool,
+    artist_id: i64,
+) -> StorageResult<Vec<i64>> {
+    let credits: Vec<(i64, String)> = query_as(
+        "SELECT DISTINCT ar.id, ar.name FROM artists ar JOIN albums al ON al.artist_id = ar.id",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| Database(format!("Get album artists failed: {e}")))?;
+    let names = artist_names(pool).await?;
+    Ok(group_by_credit(&credits, &first_ids(&names))
+        .remove(&artist_id)
+        .unwrap_or_else(|| vec![artist_id]))
+}
+
+/// ID and name of every artist, oldest first.
+async fn artist_names(pool: &SqlitePool) -> StorageResult<Vec<(i64, String)>> {
+    query_as("SELECT id, name FROM artists ORDER BY id")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Database(format!("Get artist names failed: {e}")))
+}
+
+/// Oldest artist ID for each [`artist_key`].
+fn first_ids(names: &[(i64, String)]) -> HashMap<String, i64> {
+    let mut ids = HashMap::new();
+    for (id, name) in names {
+        ids.entry(artist_key(name)).or_insert(*id);
+    }
+    ids
+}
+
+/// Group album artists under the artists credited in their names.
+///
+/// # Arguments
+///
+/// * `credits` - ID and name of every album artist
+/// * `artist_ids` - Artist ID for each [`artist_key`] in the library
+///
+/// # Returns
+///
+/// For each listed artist, the album artists whose albums it lists.
+fn group_by_credit(
+    credits: &[(i64, String)],
+    artist_ids: &HashMap<String, i64>,
+) -> HashMap<i64, Vec<i64>> {
+    let mut groups: HashMap<i64, Vec<i64>> = HashMap::new();
+    for (id, credit) in credits {
+        let mut credited: Vec<i64> = split_artist_credit(credit)
+            .into_iter()
+            .filter_map(|name| artist_ids.get(&artist_key(name)).copied())
+            .collect();
+        credited.sort_unstable();
+        credited.dedup();
+        if credited.is_empty() {
+            credited.push(*id);
+        }
+        for artist in credited {
+            groups.entry(artist).or_default().push(*id);
+        }
+    }
+    groups
+}
+
+/// Sort `artists` by `sort`, with ties and names in `collator` order.
+fn sort_artists(artists: &mut [Artist], sort: ArtistSortOrder, collator: &TitleCollator) {
+    artists.sort_by(|a, b| {
+        let by_count = match sort {
+            Name => Equal,
+            AlbumCount => b.album_count.cmp(&a.album_count),
+            TrackCount => b.track_count.cmp(&a.track_count),
+        };
+        by_count.then_with(|| collator.compare(&a.name, &b.name))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::storage::artist_groups::{first_ids, group_by_credit};
+
+    #[test]
+    fn collaborations_are_listed_under_each_library_artist() {
+        let names = [
+            (1, "Daft Punk".to_string()),
+            (2, "Pharrell Williams".to_string()),
+            (3, "Daft Punk feat. Pharrell Williams".to_string()),
+            (4, "Nobody & Someone".to_string()),
+        ];
+        let credits = [names[0].clone(), names[2].clone(), names[3].clone()];
+        let groups = group_by_credit(&credits, &first_ids(&names));
+        let expected = HashMap::from([(1, vec![1, 3]), (2, vec![3]), (4, vec![4])]);
+        assert_eq!(groups, expected, "Unexpected groups");
+    }
+}
@@ -0,0 +1,151 @@
+//! Genre and decade groupings for browsing the library.
+//!
+//! Genres are counted per album from the normalized genre column, so an
+//! album tagged `Rock; Pop` counts towards both genres. Decades are
+//! computed from the album release year. A [`BrowseFilter`] turns a chosen
+//! genre or decade into an [`AlbumSearch`] for the album grid.
+
+use std::collections::HashMap;
+
+use sqlx::{FromRow, SqlitePool, query_as};
+
+use crate::{
+    library::metadata::split_genres,
+    storage::{AlbumSearch, StorageError::Database, StorageResult},
+};
+
+/// A genre or decade chosen in the browse view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BrowseFilter {
+    /// Albums tagged with a genre.
+    Genre(String),
+    /// Albums released in the decade starting at the given year.
+    Decade(i32),
+}
+
+impl BrowseFilter {
+    /// Search matching the albums of this genre or decade.
+    #[must_use]
+    pub fn search(&self) -> AlbumSearch {
+        match self {
+            Self::Genre(genre) => AlbumSearch {
+                genre: Some(genre.clone()),
+                ..AlbumSearch::default()
+            },
+            Self::Decade(decade) => AlbumSearch {
+                min_year: Some(*decade),
+                max_year: Some(decade + 9),
+                ..AlbumSearch::default()
+            },
+        }
+    }
+
+    /// Page title, e.g. `Jazz` or `1990s`.
+    #[must_use]
+    pub fn title(&self) -> String {
+        match self {
+            Self::Genre(genre) => genre.clone(),
+            Self::Decade(decade) => format!("{decade}s"),
+        }
+    }
+}
+
+/// A release decade and the number of albums released in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromRow)]
+pub struct DecadeSummary {
+    /// First year of the decade, e.g. `1990`.
+    pub decade: i32,
+    /// Number of albums released in the decade.
+    pub album_count: i64,
+}
+
+/// A genre and the number of albums tagged with it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenreSummary {
+    /// Genre name as first spelled in the library.
+    pub name: String,
+    /// Number of albums tagged with the genre.
+    pub album_count: i64,
+}
+
+/// Get every genre with its album count, sorted by name.
+///
+/// # Errors
+///
+/// Returns [`Database`] if the album genres cannot be read.
+pub async fn get_genres(pool: &SqlitePool) -> StorageResult<Vec<GenreSummary>> {
+    let rows: Vec<(String,)> = query_as("SELECT genre FROM albums WHERE genre IS NOT NULL")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Database(format!("Get genres failed: {e}")))?;
+    Ok(count_genres(rows.iter().map(|(genre,)| genre.as_str())))
+}
+
+/// Get every decade holding albums with its album count, oldest first.
+///
+/// Albums are counted under the year they are shown with, as chosen by
+/// `use_original_year`.
+///
+/// # Errors
+///
+/// Returns [`Database`] if the album years cannot be read.
+pub async fn get_decades(
+    pool: &SqlitePool,
+    use_original_year: bool,
+) -> StorageResult<Vec<DecadeSummary>> {
+    let sql = if use_original_year {
+        "SELECT (COALESCE(original_year, year) / 10) * 10 AS decade, COUNT(*) AS album_count \
+         FROM albums WHERE COALESCE(original_year, year) > 0 GROUP BY decade ORDER BY decade"
+    } else {
+        "SELECT (COALESCE(year, original_year) / 10) * 10 AS decade, COUNT(*) AS album_count \
+         FROM albums WHERE COALESCE(year, original_year) > 0 GROUP BY decade ORDER BY decade"
+    };
+    query_as(sql)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Database(format!("Get decades failed: {e}")))
+}
+
+/// Count albums per genre, merging spellings that differ only in case.
+fn count_genres<'a>(genres: impl Iterator<Item = &'a str>) -> Vec<GenreSummary> {
+    let mut counts: HashMap<String, GenreSummary> = HashMap::new();
+    for genre in genres.flat_map(split_genres) {
+        counts
+            .entry(genre.to_lowercase())
+            .or_insert_with(|| GenreSummary {
+                name: genre,
+                album_count: 0,
+            })
+            .album_count += 1;
+    }
+    let mut summaries: Vec<GenreSummary> = counts.into_values().collect();
+    summaries.sort_by_cached_key(|g| g.name.to_lowercase());
+    summaries
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::browse::{BrowseFilter, count_genres};
+
+    #[test]
+    fn genres_are_counted_per_album() {
+        let genres = count_genres(["Rock; Pop", "rock", "Jazz"].into_iter());
+        let counts: Vec<(&str, i64)> = genres
+            .iter()
+            .map(|g| (g.name.as_str(), g.album_count))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![("Jazz", 1), ("Pop", 1), ("Rock", 2)],
+            "Case variants must merge and genres sort by name"
+        );
+    }
+
+    #[test]
+    fn decade_filter_covers_ten_years() {
+        let search = BrowseFilter::Decade(1990).search();
+        assert_eq!(search.min_year, Some(1990), "Decade starts at its year");
+        assert_eq!(search.max_year, Some(1999), "Decade ends nine years later");
+        assert_eq!(BrowseFilter::Decade(1990).title(), "1990s", "Decade title");
+    }
+}
@@ -0,0 +1,358 @@
+//! Export of the library catalog to CSV or JSON.
+//!
+//! The catalog lists every track with its album, artist, and audio format,
+//! e.g. for keeping a spreadsheet of the collection or moving to another
+//! player. Tracks are read a page at a time in ID order and written as they
+//! arrive, so a large library never has to fit in memory at once.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Error as IoError, Write},
+    path::Path,
+};
+
+use {
+    serde::Serialize,
+    serde_json::to_string,
+    sqlx::{FromRow, SqlitePool, query_as},
+    tokio::task::spawn_blocking,
+};
+
+use crate::storage::{
+    StorageError::{Database, Serialization},
+    StorageResult,
+};
+
+/// Number of tracks read and written per page.
+const CATALOG_PAGE_SIZE: i64 = 500;
+
+/// Column names of the CSV header, matching the [`CatalogRow`] fields.
+const CSV_HEADER: [&str; 14] = [
+    "id",
+    "artist",
+    "album",
+    "title",
+    "disc",
+    "track",
+    "year",
+    "format",
+    "bit_depth",
+    "sample_rate",
+    "channels",
+    "duration",
+    "dr",
+    "path",
+];
+
+/// File format of an exported catalog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatalogFormat {
+    /// Comma-separated values with a header row.
+    Csv,
+    /// A JSON array of track objects.
+    Json,
+}
+
+impl CatalogFormat {
+    /// Every format, in display order.
+    pub const ALL: [Self; 2] = [Self::Csv, Self::Json];
+
+    /// File name extension, without the dot.
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+
+    /// Human-readable format name.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Csv => "CSV",
+            Self::Json => "JSON",
+        }
+    }
+}
+
+/// One track of the catalog.
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize)]
+pub struct CatalogRow {
+    /// Track ID.
+    pub id: i64,
+    /// Track artist, or the album artist for tracks without one.
+    pub artist: String,
+    /// Album title, empty for tracks without an album.
+    pub album: String,
+    /// Track title.
+    pub title: String,
+    /// Disc number.
+    pub disc: Option<i32>,
+    /// Track number within the disc.
+    pub track: Option<i32>,
+    /// Album release year.
+    pub year: Option<i32>,
+    /// File format, e.g. `"FLAC"`.
+    pub format: String,
+    /// Bit depth, none for lossy formats.
+    pub bit_depth: Option<i32>,
+    /// Sample rate in Hz.
+    pub sample_rate: i32,
+    /// Number of audio channels.
+    pub channels: i32,
+    /// Duration in seconds.
+    pub duration: f64,
+    /// Dynamic range of the album.
+    pub dr: Option<i32>,
+    /// Absolute path of the audio file.
+    pub path: String,
+}
+
+/// Writes catalog rows in one format.
+pub struct CatalogWriter<W: Write> {
+    /// Destination of the catalog.
+    out: W,
+    /// Format being written.
+    format: CatalogFormat,
+    /// Number of rows written so far.
+    rows: usize,
+}
+
+impl<W: Write> CatalogWriter<W> {
+    /// Start a catalog in `format`, writing its header.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the header cannot be written.
+    pub fn new(mut out: W, format: CatalogFormat) -> Result<Self, IoError> {
+        match format {
+            CatalogFormat::Csv => writeln!(out, "{}", CSV_HEADER.join(","))?,
+            CatalogFormat::Json => write!(out, "[")?,
+        }
+        Ok(Self {
+            out,
+            format,
+            rows: 0,
+        })
+    }
+
+    /// Close the catalog.
+    ///
+    /// # Returns
+    ///
+    /// The number of rows written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the end of the catalog cannot be written.
+    pub fn finish(mut self) -> Result<usize, IoError> {
+        if self.format == CatalogFormat::Json {
+            let close = match self.rows {
+                0 => "]\n",
+                _ => "\n]\n",
+            };
+            write!(self.out, "{close}")?;
+        }
+        self.out.flush()?;
+        Ok(self.rows)
+    }
+
+    /// Append `rows` to the catalog.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a row cannot be serialized or written.
+    pub fn write_rows(&mut self, rows: &[CatalogRow]) -> Result<(), IoError> {
+        for row in rows {
+            match self.format {
+                CatalogFormat::Csv => writeln!(self.out, "{}", csv_line(row))?,
+                CatalogFormat::Json => write!(
+                    self.out,
+                    "{}\n  {}",
+                    match self.rows {
+                        0 => "",
+                        _ => ",",
+                    },
+                    to_string(row)?
+                )?,
+            }
+            self.rows += 1;
+        }
+        Ok(())
+    }
+}
+
+/// CSV line of `row`, without the line break.
+fn csv_line(row: &CatalogRow) -> String {
+    let optional = |value: Option<i32>| value.map(|v| v.to_string()).unwrap_or_default();
+    [
+        row.id.to_string(),
+        csv_field(&row.artist),
+        csv_field(&row.album),
+        csv_field(&row.title),
+        optional(row.disc),
+        optional(row.track),
+        optional(row.year),
+        csv_field(&row.format),
+        optional(row.bit_depth),
+        row.sample_rate.to_string(),
+        row.channels.to_string(),
+        format!("{:.3}", row.duration),
+        optional(row.dr),
+        csv_field(&row.path),
+    ]
+    .join(",")
+}
+
+/// `value` as a CSV field, quoted when it contains a separator, quote, or
+/// line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Read the page of tracks following `after_id`.
+async fn catalog_page(pool: &SqlitePool, after_id: i64) -> StorageResult<Vec<CatalogRow>> {
+    query_as(
+        "SELECT t.id, COALESCE(tar.name, aar.name, '') AS artist, \
+         COALESCE(al.title, '') AS album, t.title, t.disc_number AS disc, t.number AS track, \
+         al.year, t.format, t.bit_depth, t.sample_rate, t.channels, t.duration, \
+         al.dr_value AS dr, t.file_path AS path \
+         FROM tracks t \
+         LEFT JOIN albums al ON al.id = t.album_id \
+         LEFT JOIN artists tar ON tar.id = t.artist_id \
+         LEFT JOIN artists aar ON aar.id = al.artist_id \
+         WHERE t.id > ? ORDER BY t.id LIMIT ?",
+    )
+    .bind(after_id)
+    .bind(CATALOG_PAGE_SIZE)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| Database(format!("Read catalog page failed: {e}")))
+}
+
+/// Write the catalog of every track to `path`.
+///
+/// # Returns
+///
+/// The number of tracks written.
+///
+/// # Errors
+///
+/// Returns [`Database`] if the tracks cannot be read and [`Serialization`]
+/// if the file cannot be written.
+pub async fn export_catalog(
+    pool: &SqlitePool,
+    format: CatalogFormat,
+    path: &Path,
+) -> StorageResult<usize> {
+    let write_error =
+        |e: IoError| Serialization(format!("Write catalog to {} failed: {e}", path.display()));
+    let file = File::create(path).map_err(write_error)?;
+    let mut writer = CatalogWriter::new(BufWriter::new(file), format).map_err(write_error)?;
+    let mut after_id = 0;
+    loop {
+        let rows = catalog_page(pool, after_id).await?;
+        let Some(last) = rows.last() else {
+            break;
+        };
+        after_id = last.id;
+        writer = spawn_blocking(move || writer.write_rows(&rows).map(|()| writer))
+            .await
+            .map_err(|e| Database(format!("Failed to spawn blocking write: {e}")))?
+            .map_err(write_error)?;
+    }
+    writer.finish().map_err(write_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        anyhow::{Result, ensure},
+        serde_json::{Value, from_slice},
+    };
+
+    use crate::storage::catalog::{
+        CatalogFormat::{Csv, Json},
+        CatalogRow, CatalogWriter,
+    };
+
+    /// Catalog row with the given artist and no optional fields.
+    fn row(id: i64, artist: &str) -> CatalogRow {
+        CatalogRow {
+            id,
+            artist: artist.to_string(),
+            album: "Kind of Blue".to_string(),
+            title: "So What".to_string(),
+            disc: None,
+            track: Some(1),
+            year: Some(1959),
+            format: "FLAC".to_string(),
+            bit_depth: Some(24),
+            sample_rate: 96000,
+            channels: 2,
+            duration: 562.5,
+            dr: None,
+            path: "/music/So What.flac".to_string(),
+        }
+    }
+
+    #[test]
+    fn csv_quotes_fields_with_separators() -> Result<()> {
+        let mut writer = CatalogWriter::new(Vec::new(), Csv)?;
+        writer.write_rows(&[row(1, "Miles Davis, \"Quintet\"")])?;
+        ensure!(writer.rows == 1, "One row must be counted");
+        let text = String::from_utf8(writer.out)?;
+        let mut lines = text.lines();
+        ensure!(
+            lines
+                .next()
+                .is_some_and(|header| header.starts_with("id,artist,album")),
+            "The header must come first"
+        );
+        ensure!(
+            lines.next()
+                == Some(
+                    "1,\"Miles Davis, \"\"Quintet\"\"\",Kind of Blue,So What,,1,1959,FLAC,24,\
+                     96000,2,562.500,,/music/So What.flac"
+                ),
+            "Fields must be quoted and escaped"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn json_is_an_array_of_rows() -> Result<()> {
+        let mut out = Vec::new();
+        let mut writer = CatalogWriter::new(&mut out, Json)?;
+        writer.write_rows(&[row(1, "Miles Davis")])?;
+        writer.write_rows(&[row(2, "John Coltrane")])?;
+        ensure!(writer.finish()? == 2, "Both rows must be counted");
+
+        let parsed: Value = from_slice(&out)?;
+        let artists: Vec<&str> = parsed
+            .as_array()
+            .map(|rows| rows.iter().filter_map(|r| r["artist"].as_str()).collect())
+            .unwrap_or_default();
+        ensure!(
+            artists == ["Miles Davis", "John Coltrane"],
+            "Got {artists:?}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn empty_json_catalog_is_valid() -> Result<()> {
+        let mut out = Vec::new();
+        ensure!(
+            CatalogWriter::new(&mut out, Json)?.finish()? == 0,
+            "No rows"
+        );
+        ensure!(out == b"[]\n", "Empty catalog must be an empty array");
+        Ok(())
+    }
+}
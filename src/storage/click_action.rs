@@ -0,0 +1,57 @@
+//! What clicking albums and list rows in the library views does.
+
+use serde::{Deserialize, Serialize};
+
+/// What clicking an album in the library views does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AlbumClickAction {
+    /// Open the album detail page.
+    #[default]
+    Open,
+    /// Play the album from its first track, replacing the queue.
+    Play,
+    /// Play the album in random order, replacing the queue.
+    PlayShuffle,
+    /// Add the album to the end of the queue.
+    Enqueue,
+}
+
+impl AlbumClickAction {
+    /// Every action, in display order.
+    pub const ALL: [Self; 4] = [Self::Open, Self::Play, Self::PlayShuffle, Self::Enqueue];
+
+    /// Human-readable action name.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Open => "Open Album",
+            Self::Play => "Play Album",
+            Self::PlayShuffle => "Shuffle Album",
+            Self::Enqueue => "Add Album to Queue",
+        }
+    }
+}
+
+/// How rows of the library list view are activated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ListActivation {
+    /// A single click opens or plays the row.
+    SingleClick,
+    /// A single click selects the row and a double click opens or plays it.
+    #[default]
+    DoubleClick,
+}
+
+impl ListActivation {
+    /// Every activation mode, in display order.
+    pub const ALL: [Self; 2] = [Self::DoubleClick, Self::SingleClick];
+
+    /// Human-readable mode name.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::SingleClick => "Single Click",
+            Self::DoubleClick => "Double Click",
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! Ordering of album titles and artist names.
+//!
+//! Titles are compared by a sort key: lowercased, with accented Latin
+//! letters folded to their base letters and an optional leading article
+//! ("The Beatles" sorts as "Beatles") removed. Keys compare naturally, so
+//! runs of digits compare by value and "Volume 2" sorts before "Volume 10".
+//!
+//! The same [`TitleCollator`] orders the list views and, registered as the
+//! [`LIBRARY_COLLATION`] `SQLite` collation, the queries of the library
+//! views.
+
+use std::{
+    cmp::Ordering::{self, Equal},
+    iter::Peekable,
+    str::Chars,
+};
+
+/// Name of the `SQLite` collation ordering titles and names.
+pub const LIBRARY_COLLATION: &str = "LIBRARY";
+
+/// Leading articles ignored when sorting, unless configured otherwise.
+pub const DEFAULT_SORT_ARTICLES: [&str; 3] = ["The", "A", "An"];
+
+/// Compares titles and names for sorting.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TitleCollator {
+    /// Folded articles skipped at the start of a title.
+    articles: Vec<String>,
+}
+
+impl TitleCollator {
+    /// Collator skipping the given leading `articles`, none if empty.
+    #[must_use]
+    pub fn new(articles: &[String]) -> Self {
+        Self {
+            articles: articles
+                .iter()
+                .map(|a| fold(a.trim()))
+                .filter(|a| !a.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Compare `a` and `b` by their sort keys.
+    ///
+    /// Titles with equal keys, such as "Café" and "Cafe", fall back to
+    /// plain string order so the result is stable.
+    #[must_use]
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        natural_cmp(&self.sort_key(a), &self.sort_key(b)).then_with(|| a.cmp(b))
+    }
+
+    /// Folded `text` without its leading article.
+    ///
+    /// A title that is only an article, such as "A", is kept.
+    fn sort_key(&self, text: &str) -> String {
+        let key = fold(text.trim());
+        let stripped = self.articles.iter().find_map(|article| {
+            key.strip_prefix(article.as_str())
+                .and_then(|rest| rest.strip_prefix(' '))
+                .map(str::trim_start)
+                .filter(|rest| !rest.is_empty())
+        });
+        stripped.map_or_else(|| key.clone(), str::to_string)
+    }
+}
+
+/// Lowercase `text` and fold accented Latin letters to their base letters.
+fn fold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars().flat_map(char::to_lowercase) {
+        match fold_char(c) {
+            Some(base) => folded.push_str(base),
+            None => folded.push(c),
+        }
+    }
+    folded
+}
+
+/// Base letters of a lowercase accented Latin letter.
+const fn fold_char(c: char) -> Option<&'static str> {
+    Some(match c {
+        'à'..='å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'ð' | 'ď' | 'đ' => "d",
+        'è'..='ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'ì'..='ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'ł' | 'ľ' | 'ĺ' | 'ļ' => "l",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'ò'..='ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'œ' => "oe",
+        'ŕ' | 'ř' => "r",
+        'ś' | 'ŝ' | 'ş' | 'š' => "s",
+        'ß' => "ss",
+        'ţ' | 'ť' => "t",
+        'þ' => "th",
+        'ù'..='ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'ý' | 'ÿ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    })
+}
+
+/// Compare `a` and `b` character by character, with runs of digits
+/// compared by their value.
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        let (Some(&x), Some(&y)) = (a.peek(), b.peek()) else {
+            return a.peek().is_some().cmp(&b.peek().is_some());
+        };
+        let ordering = if x.is_ascii_digit() && y.is_ascii_digit() {
+            compare_numbers(&take_digits(&mut a), &take_digits(&mut b))
+        } else {
+            a.next();
+            b.next();
+            x.cmp(&y)
+        };
+        if ordering != Equal {
+            return ordering;
+        }
+    }
+}
+
+/// Take the run of ASCII digits at the start of `chars`.
+fn take_digits(chars: &mut Peekable<Chars<'_>>) -> String {
+    let mut digits = String::new();
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        digits.push(digit);
+    }
+    digits
+}
+
+/// Compare two runs of digits by value, however long they are.
+fn compare_numbers(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.trim_start_matches('0'), b.trim_start_matches('0'));
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering::{Equal, Greater, Less};
+
+    use crate::storage::collation::{DEFAULT_SORT_ARTICLES, TitleCollator};
+
+    /// Collator skipping the default articles.
+    fn collator() -> TitleCollator {
+        let articles: Vec<String> = DEFAULT_SORT_ARTICLES.map(String::from).to_vec();
+        TitleCollator::new(&articles)
+    }
+
+    #[test]
+    fn numbers_compare_by_value() {
+        let collator = collator();
+        assert_eq!(collator.compare("Track 2", "Track 10"), Less, "2 < 10");
+        assert_eq!(collator.compare("Opus 007", "Opus 7"), Less, "Tie on zeros");
+        assert_eq!(collator.compare("1999", "200"), Greater, "1999 > 200");
+    }
+
+    #[test]
+    fn accents_and_case_are_folded() {
+        let collator = collator();
+        assert_eq!(collator.compare("Érik", "eric"), Greater, "k after c");
+        assert_eq!(collator.compare("Édith", "Eve"), Less, "É sorts as e");
+        assert_eq!(collator.compare("abba", "ABBA"), Greater, "Stable tie");
+        assert_eq!(collator.compare("Björk", "Björk"), Equal, "Same title");
+    }
+
+    #[test]
+    fn leading_articles_are_skipped_when_configured() {
+        let collator = collator();
+        assert_eq!(
+            collator.compare("The Beatles", "Blur"),
+            Less,
+            "Beatles < Blur"
+        );
+        assert_eq!(
+            collator.compare("Theory", "Blur"),
+            Greater,
+            "Not an article"
+        );
+        assert_eq!(collator.compare("A", "B"), Less, "A lone article is kept");
+        let plain = TitleCollator::new(&[]);
+        assert_eq!(plain.compare("The Beatles", "Blur"), Greater, "No articles");
+    }
+}
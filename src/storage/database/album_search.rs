@@ -0,0 +1,132 @@
+//! SQL fragments of album queries: the conditions of an [`AlbumSearch`] and
+//! the order of a [`SortOrder`].
+
+use sqlx::{QueryBuilder, Sqlite};
+
+use crate::storage::{
+    AlbumSearch, album_year_sql,
+    sort_order::SortOrder::{
+        self, Artist as ByArtist, DateAdded, DrValue, FolderPath, Title, Year,
+    },
+};
+
+/// SQL `ORDER BY` terms for an album sort order.
+///
+/// Titles and names are compared with the
+/// [`LIBRARY_COLLATION`](crate::storage::collation::LIBRARY_COLLATION) and years
+/// follow [`album_year_sql`]. Ties fall back to the title, and for
+/// [`DateAdded`] to the insert order, so albums added in the same second
+/// still list newest first. [`FolderPath`] sorts by the first file path of
+/// the album, with albums that have no tracks last. [`DrValue`] lists the
+/// highest dynamic range first, with unmeasured albums last.
+pub const fn album_order_clause(sort: SortOrder, use_original_year: bool) -> &'static str {
+    match (sort, use_original_year) {
+        (Title, _) => "al.title COLLATE LIBRARY",
+        (ByArtist, false) => {
+            "(SELECT ar.name FROM artists ar WHERE ar.id = al.artist_id) COLLATE LIBRARY, \
+             COALESCE(al.year, al.original_year), al.title COLLATE LIBRARY"
+        }
+        (ByArtist, true) => {
+            "(SELECT ar.name FROM artists ar WHERE ar.id = al.artist_id) COLLATE LIBRARY, \
+             COALESCE(al.original_year, al.year), al.title COLLATE LIBRARY"
+        }
+        (Year, false) => {
+            "COALESCE(al.year, al.original_year) IS NULL, COALESCE(al.year, al.original_year), \
+             al.title COLLATE LIBRARY"
+        }
+        (Year, true) => {
+            "COALESCE(al.original_year, al.year) IS NULL, COALESCE(al.original_year, al.year), \
+             al.title COLLATE LIBRARY"
+        }
+        (DateAdded, _) => "al.date_added DESC, al.id DESC",
+        (FolderPath, _) => {
+            "(SELECT MIN(t.file_path) FROM tracks t WHERE t.album_id = al.id) NULLS LAST, \
+             al.title COLLATE LIBRARY"
+        }
+        (DrValue, _) => "al.dr_value DESC NULLS LAST, al.title COLLATE LIBRARY",
+    }
+}
+
+/// Append the text, year, genre, dynamic range, format and resolution
+/// conditions of `search` to an album query.
+///
+/// Years follow [`album_year_sql`] for `use_original_year`.
+pub fn push_album_filters(
+    builder: &mut QueryBuilder<Sqlite>,
+    search: &AlbumSearch,
+    use_original_year: bool,
+) {
+    let text = search.query.trim();
+    if !text.is_empty() {
+        let pattern = format!("%{text}%");
+        builder
+            .push(" AND (al.title LIKE ")
+            .push_bind(pattern.clone())
+            .push(
+                " OR EXISTS (SELECT 1 FROM artists ar WHERE ar.id = al.artist_id AND \
+                 ar.name LIKE ",
+            )
+            .push_bind(pattern.clone())
+            .push(
+                ") OR EXISTS (SELECT 1 FROM tracks t WHERE t.album_id = al.id AND t.title \
+                 LIKE ",
+            )
+            .push_bind(pattern)
+            .push("))");
+    }
+    let year = album_year_sql(use_original_year);
+    if let Some(min_year) = search.min_year {
+        builder
+            .push(" AND ")
+            .push(year)
+            .push(" >= ")
+            .push_bind(min_year);
+    }
+    if let Some(max_year) = search.max_year {
+        builder
+            .push(" AND ")
+            .push(year)
+            .push(" <= ")
+            .push_bind(max_year);
+    }
+    if let Some(genre) = &search.genre {
+        builder
+            .push(" AND INSTR('; ' || LOWER(al.genre) || '; ', '; ' || LOWER(")
+            .push_bind(genre.clone())
+            .push(") || '; ') > 0");
+    }
+    if let Some(min_dr) = search.min_dr {
+        builder.push(" AND al.dr_value >= ").push_bind(min_dr);
+    }
+    if let Some(max_dr) = search.max_dr {
+        builder.push(" AND al.dr_value <= ").push_bind(max_dr);
+    }
+    if search.has_track_facets() {
+        push_track_facets(builder, search);
+    }
+}
+
+/// Append an `EXISTS` clause requiring one track that satisfies every
+/// format and resolution facet of `search`.
+fn push_track_facets(builder: &mut QueryBuilder<Sqlite>, search: &AlbumSearch) {
+    builder.push(" AND EXISTS (SELECT 1 FROM tracks t WHERE t.album_id = al.id");
+    if !search.formats.is_empty() {
+        builder.push(" AND UPPER(t.format) IN (");
+        let mut separated = builder.separated(", ");
+        for format in &search.formats {
+            separated.push_bind(format.to_uppercase());
+        }
+        builder.push(")");
+    }
+    if let Some(min_bit_depth) = search.min_bit_depth {
+        builder
+            .push(" AND t.bit_depth >= ")
+            .push_bind(min_bit_depth);
+    }
+    if let Some(min_sample_rate) = search.min_sample_rate {
+        builder
+            .push(" AND t.sample_rate >= ")
+            .push_bind(min_sample_rate);
+    }
+    builder.push(")");
+}
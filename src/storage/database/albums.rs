@@ -0,0 +1,339 @@
+//! Album rows of [`SqliteStorage`](super::SqliteStorage): inserting,
+//! updating and searching albums, and the formats of their tracks.
+
+use std::collections::HashMap;
+
+use {
+    sqlx::{FromRow, QueryBuilder, SqlitePool, query, query_as},
+    tracing::warn,
+};
+
+use crate::storage::{
+    Album, AlbumSearch, AlbumUpdate,
+    FieldUpdate::{Set, SetNull, Skip},
+    FormatInfo, NewAlbum,
+    StorageError::Database,
+    StorageResult, album_year_sql,
+    artist_groups::get_credited_album_artist_ids,
+    database::album_search::{album_order_clause, push_album_filters},
+    prune::push_id_list,
+    sort_order::SortOrder,
+};
+
+/// Select fragment for the id, title, artist, year, genre and artwork columns.
+///
+/// A user-chosen cover takes the place of the scanned artwork.
+macro_rules! album_head_cols {
+    () => {
+        "SELECT al.id, al.title, al.artist_id, al.year, al.genre, \
+         COALESCE(al.cover_override, al.artwork_path) AS artwork_path, al.cover_override, "
+    };
+}
+
+/// Column fragment for the original year, album count, duration, format and DR columns.
+macro_rules! album_meta_cols {
+    () => {
+        "al.original_year, (SELECT COUNT(*) FROM tracks WHERE album_id = al.id) AS track_count, \
+         (SELECT COALESCE(SUM(duration), 0.0) FROM tracks WHERE album_id = al.id) AS \
+         total_duration, al.format_summary, al.lossless, al.format, al.bit_depth, \
+         al.sample_rate, al.date_added, al.dr_value FROM albums al"
+    };
+}
+
+impl From<FormatInfoRow> for FormatInfo {
+    fn from(row: FormatInfoRow) -> Self {
+        raw_info_to_format_info(
+            row.formats,
+            row.sample_rates.as_deref(),
+            row.bit_depths.as_deref(),
+            row.channels.as_deref(),
+        )
+    }
+}
+
+/// Raw row from the `GROUP_CONCAT` format info query.
+#[derive(Debug, Clone, FromRow)]
+struct FormatInfoRow {
+    /// Album identifier.
+    album_id: i64,
+    /// Comma-separated distinct format/codec names.
+    formats: Option<String>,
+    /// Comma-separated distinct sample rates.
+    sample_rates: Option<String>,
+    /// Comma-separated distinct bit depths.
+    bit_depths: Option<String>,
+    /// Comma-separated distinct channel counts.
+    channels: Option<String>,
+}
+
+/// Insert a new album, added now, and return its ID.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn insert_album(pool: &SqlitePool, album: NewAlbum) -> StorageResult<i64> {
+    let row_id: (i64,) = query_as(
+        "INSERT INTO albums (title, artist_id, year, original_year, genre, artwork_path, \
+         format_summary, lossless, format, bit_depth, sample_rate, date_added) VALUES (?, ?, \
+         ?, ?, ?, ?, ?, ?, ?, ?, ?, datetime('now')) RETURNING id",
+    )
+    .bind(&album.title)
+    .bind(album.artist_id)
+    .bind(album.year)
+    .bind(album.original_year)
+    .bind(&album.genre)
+    .bind(&album.artwork_path)
+    .bind(&album.format_summary)
+    .bind(album.lossless)
+    .bind(&album.format)
+    .bind(album.bit_depth)
+    .bind(album.sample_rate)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| Database(format!("Insert album failed: {e}")))?;
+
+    Ok(row_id.0)
+}
+
+/// Apply the set fields of `album` to the album `id`.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn update_album(
+    pool: &SqlitePool,
+    id: i64,
+    album: AlbumUpdate,
+) -> StorageResult<()> {
+    if let Some(title) = album.title {
+        query("UPDATE albums SET title = ? WHERE id = ?")
+            .bind(&title)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map_err(|e| Database(format!("Update album failed: {e}")))?;
+    }
+    if let Some(artist_id) = album.artist_id {
+        query("UPDATE albums SET artist_id = ? WHERE id = ?")
+            .bind(artist_id)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map_err(|e| Database(format!("Update album failed: {e}")))?;
+    }
+    apply_field!("albums", album, year, pool, id);
+    apply_field!("albums", album, genre, pool, id);
+    Ok(())
+}
+
+/// Get the album `id`, if it exists.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn get_album(pool: &SqlitePool, id: i64) -> StorageResult<Option<Album>> {
+    query_as::<_, Album>(concat!(
+        album_head_cols!(),
+        album_meta_cols!(),
+        " WHERE al.id = ?",
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| Database(format!("Get album failed: {e}")))
+}
+
+/// Get every album in title order.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn get_all_albums(pool: &SqlitePool) -> StorageResult<Vec<Album>> {
+    query_as::<_, Album>(concat!(
+        album_head_cols!(),
+        album_meta_cols!(),
+        " ORDER BY al.title COLLATE LIBRARY",
+    ))
+    .fetch_all(pool)
+    .await
+    .map_err(|e| Database(format!("Get all albums failed: {e}")))
+}
+
+/// Get the albums matching `search` in the `sort` order.
+///
+/// `use_original_year` filters and sorts on the original release year.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn search_albums(
+    pool: &SqlitePool,
+    search: &AlbumSearch,
+    sort: SortOrder,
+    use_original_year: bool,
+) -> StorageResult<Vec<Album>> {
+    let mut builder = QueryBuilder::new(concat!(
+        album_head_cols!(),
+        album_meta_cols!(),
+        " WHERE 1 = 1",
+    ));
+
+    push_album_filters(&mut builder, search, use_original_year);
+    builder
+        .push(" ORDER BY ")
+        .push(album_order_clause(sort, use_original_year));
+
+    builder
+        .build_query_as::<Album>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Database(format!("Search albums failed: {e}")))
+}
+
+/// Get the distinct formats, sample rates, bit depths and channel counts
+/// of an album's tracks.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn get_album_format_info(
+    pool: &SqlitePool,
+    album_id: i64,
+) -> StorageResult<FormatInfo> {
+    #[derive(Debug, Clone, FromRow)]
+    struct RawInfo {
+        formats: Option<String>,
+        sample_rates: Option<String>,
+        bit_depths: Option<String>,
+        channels: Option<String>,
+    }
+
+    let row: Option<RawInfo> = query_as(
+        "SELECT GROUP_CONCAT(DISTINCT UPPER(codec)) AS formats, GROUP_CONCAT(DISTINCT \
+         sample_rate) AS sample_rates, GROUP_CONCAT(DISTINCT bit_depth) AS bit_depths, \
+         GROUP_CONCAT(DISTINCT channels) AS channels FROM tracks WHERE album_id = ?",
+    )
+    .bind(album_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| Database(format!("Get album format info failed: {e}")))?;
+
+    Ok(row.map_or_else(FormatInfo::default, |r| {
+        raw_info_to_format_info(
+            r.formats,
+            r.sample_rates.as_deref(),
+            r.bit_depths.as_deref(),
+            r.channels.as_deref(),
+        )
+    }))
+}
+
+/// Get the format info of several albums, keyed by album ID.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn get_albums_format_info(
+    pool: &SqlitePool,
+    album_ids: &[i64],
+) -> StorageResult<HashMap<i64, FormatInfo>> {
+    if album_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut builder = QueryBuilder::new(
+        "SELECT album_id, GROUP_CONCAT(DISTINCT UPPER(codec)) AS formats, \
+         GROUP_CONCAT(DISTINCT sample_rate) AS sample_rates, GROUP_CONCAT(DISTINCT bit_depth) \
+         AS bit_depths, GROUP_CONCAT(DISTINCT channels) AS channels FROM tracks WHERE \
+         album_id IN (",
+    );
+
+    let mut separated = builder.separated(", ");
+    for id in album_ids {
+        separated.push_bind(id);
+    }
+    builder.push(") GROUP BY album_id");
+
+    let rows: Vec<FormatInfoRow> = builder
+        .build_query_as()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Database(format!("Get albums format info failed: {e}")))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| (r.album_id, FormatInfo::from(r)))
+        .collect())
+}
+
+/// Get an artist's albums in year order.
+///
+/// With `split_collaborations`, albums credited to the artist together
+/// with others are included.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn get_albums_by_artist(
+    pool: &SqlitePool,
+    artist_id: i64,
+    split_collaborations: bool,
+    use_original_year: bool,
+) -> StorageResult<Vec<Album>> {
+    let artist_ids = if split_collaborations {
+        get_credited_album_artist_ids(pool, artist_id).await?
+    } else {
+        vec![artist_id]
+    };
+    let mut builder = QueryBuilder::new(concat!(
+        album_head_cols!(),
+        album_meta_cols!(),
+        " WHERE al.artist_id IN (",
+    ));
+    push_id_list(&mut builder, &artist_ids);
+    builder
+        .push(" ORDER BY ")
+        .push(album_year_sql(use_original_year));
+    builder
+        .build_query_as::<Album>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Database(format!("Get albums by artist failed: {e}")))
+}
+
+/// Parse a comma-separated string of integers, logging parse failures.
+fn parse_int_list(s: &str) -> Vec<i32> {
+    s.split(',')
+        .filter_map(|v| {
+            let trimmed = v.trim();
+            match trimmed.parse::<i32>() {
+                Ok(n) => Some(n),
+                Err(e) => {
+                    warn!(
+                        error = %e,
+                        value = trimmed,
+                        "Skipping unparseable integer in format info",
+                    );
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Parse comma-separated format info strings into a `FormatInfo`.
+fn raw_info_to_format_info(
+    formats: Option<String>,
+    sample_rates: Option<&str>,
+    bit_depths: Option<&str>,
+    channels: Option<&str>,
+) -> FormatInfo {
+    FormatInfo {
+        formats: formats.map_or_else(Vec::new, |s| {
+            s.split(',').map(str::trim).map(str::to_string).collect()
+        }),
+        sample_rates: sample_rates.map_or_else(Vec::new, parse_int_list),
+        bit_depths: bit_depths.map_or_else(Vec::new, parse_int_list),
+        channels: channels.map_or_else(Vec::new, parse_int_list),
+    }
+}
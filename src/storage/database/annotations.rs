@@ -0,0 +1,89 @@
+//! User annotations on [`SqliteStorage`]: artist images and biographies,
+//! chosen album covers, and measured dynamic range values.
+
+use sqlx::{query, query_as};
+
+use crate::storage::{StorageError::Database, StorageResult, database::SqliteStorage};
+
+impl SqliteStorage {
+    /// Set or clear the user-chosen image of an artist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the update fails.
+    pub async fn set_artist_image(&self, id: i64, path: Option<&str>) -> StorageResult<()> {
+        query("UPDATE artists SET image_path = ? WHERE id = ?")
+            .bind(path)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Database(format!("Set artist image failed: {e}")))?;
+        Ok(())
+    }
+
+    /// Set or clear the biography of an artist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the update fails.
+    pub async fn set_artist_bio(&self, id: i64, bio: Option<&str>) -> StorageResult<()> {
+        query("UPDATE artists SET bio = ? WHERE id = ?")
+            .bind(bio)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Database(format!("Set artist bio failed: {e}")))?;
+        Ok(())
+    }
+
+    /// Store or clear the dynamic range value of an album.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the update fails.
+    pub async fn set_album_dr(&self, id: i64, dr_value: Option<i32>) -> StorageResult<()> {
+        query("UPDATE albums SET dr_value = ? WHERE id = ?")
+            .bind(dr_value)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Database(format!("Set album DR failed: {e}")))?;
+        Ok(())
+    }
+
+    /// IDs of the albums that have tracks but no dynamic range value yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub async fn get_album_ids_without_dr(&self) -> StorageResult<Vec<i64>> {
+        let rows: Vec<(i64,)> = query_as(
+            "SELECT al.id FROM albums al \
+             WHERE al.dr_value IS NULL \
+             AND EXISTS (SELECT 1 FROM tracks t WHERE t.album_id = al.id) \
+             ORDER BY al.id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Database(format!("Get albums without DR failed: {e}")))?;
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+
+    /// Set or clear the user-chosen cover of an album.
+    ///
+    /// The cover is shown instead of the artwork found by the scanner until
+    /// it is cleared.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the update fails.
+    pub async fn set_album_cover(&self, id: i64, path: Option<&str>) -> StorageResult<()> {
+        query("UPDATE albums SET cover_override = ? WHERE id = ?")
+            .bind(path)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Database(format!("Set album cover failed: {e}")))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+//! Browsing on [`SqliteStorage`]: album artists, genres and decades, and
+//! the whole library in play order.
+
+use sqlx::{QueryBuilder, query_as};
+
+use crate::storage::{
+    Album, Artist, Storage,
+    StorageError::Database,
+    StorageResult,
+    artist_groups::get_credited_artists,
+    browse::{BrowseFilter, DecadeSummary, GenreSummary, get_decades, get_genres},
+    database::SqliteStorage,
+    sort_order::{
+        ArtistSortOrder::{self, AlbumCount, Name, TrackCount},
+        SortOrder,
+    },
+};
+
+impl SqliteStorage {
+    /// Get the artists that own at least one album, with their album and
+    /// track counts, in the given order.
+    ///
+    /// Artists only credited on tracks, such as the contributors to a
+    /// compilation filed under its album artist, are left out. Tracks are
+    /// counted on the artist's albums. With collaborations split, albums
+    /// count for every credited artist, see [`get_credited_artists`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub async fn get_album_artists(&self, sort: ArtistSortOrder) -> StorageResult<Vec<Artist>> {
+        if self.get_split_collaborations() {
+            return get_credited_artists(&self.pool, sort, &self.title_collator()).await;
+        }
+        let mut builder = QueryBuilder::new(
+            "SELECT ar.id, ar.name, COUNT(DISTINCT al.id) AS album_count, COUNT(t.id) AS \
+             track_count FROM artists ar JOIN albums al ON al.artist_id = ar.id LEFT JOIN \
+             tracks t ON t.album_id = al.id GROUP BY ar.id ORDER BY ",
+        );
+        builder.push(artist_order_clause(sort));
+        builder
+            .build_query_as::<Artist>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| Database(format!("Get album artists failed: {e}")))
+    }
+
+    /// Get every genre with its album count, sorted by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the album genres cannot be read.
+    pub async fn get_genres(&self) -> StorageResult<Vec<GenreSummary>> {
+        get_genres(&self.pool).await
+    }
+
+    /// Get every release decade with its album count, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the album years cannot be read.
+    pub async fn get_decades(&self) -> StorageResult<Vec<DecadeSummary>> {
+        get_decades(&self.pool, self.get_use_original_year()).await
+    }
+
+    /// Get the albums tagged with `genre` in the given order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub async fn get_albums_by_genre(
+        &self,
+        genre: &str,
+        sort: SortOrder,
+    ) -> StorageResult<Vec<Album>> {
+        self.search_albums(&BrowseFilter::Genre(genre.to_string()).search(), sort)
+            .await
+    }
+
+    /// IDs of every track in library order: by album artist, album, disc,
+    /// and track number.
+    ///
+    /// Only the IDs are read, so even a large library stays small in
+    /// memory; the tracks themselves can be fetched a chunk at a time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub async fn get_library_track_ids(&self) -> StorageResult<Vec<i64>> {
+        let rows: Vec<(i64,)> = query_as(
+            "SELECT t.id FROM tracks t \
+             LEFT JOIN albums al ON al.id = t.album_id \
+             LEFT JOIN artists ar ON ar.id = COALESCE(al.artist_id, t.artist_id) \
+             ORDER BY ar.name IS NULL, ar.name COLLATE LIBRARY, \
+             COALESCE(al.original_year, al.year), al.title COLLATE LIBRARY, t.album_id, \
+             COALESCE(t.disc_number, 1), t.number, t.id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Database(format!("Get library track IDs failed: {e}")))?;
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+}
+
+/// SQL `ORDER BY` terms for an artist sort order.
+///
+/// Artists with equal counts fall back to name order. Names are compared with
+/// the [`LIBRARY_COLLATION`](crate::storage::collation::LIBRARY_COLLATION).
+const fn artist_order_clause(sort: ArtistSortOrder) -> &'static str {
+    match sort {
+        Name => "ar.name COLLATE LIBRARY",
+        AlbumCount => "album_count DESC, ar.name COLLATE LIBRARY",
+        TrackCount => "track_count DESC, ar.name COLLATE LIBRARY",
+    }
+}
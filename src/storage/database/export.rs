@@ -0,0 +1,96 @@
+//! Export and import on [`SqliteStorage`]: settings moved between
+//! machines with their library folders, and the track catalog written as
+//! CSV or JSON.
+
+use std::{fs::write, path::Path};
+
+use {tokio::task::spawn_blocking, tracing::warn};
+
+use crate::storage::{
+    Storage,
+    StorageError::{self, Database},
+    StorageResult,
+    catalog::{CatalogFormat, export_catalog},
+    database::SqliteStorage,
+    transfer::{ImportReport, export_json, merge_imported, partition_directories, read_export},
+};
+
+impl SqliteStorage {
+    /// Write the settings and library directories to a portable file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directories cannot be read or the file
+    /// cannot be written.
+    pub async fn export_settings(&self, path: &Path) -> StorageResult<()> {
+        let directories = self
+            .list_library_directories()
+            .await?
+            .into_iter()
+            .map(|d| d.path)
+            .collect();
+        let json = export_json(self.settings.read().get(), directories)?;
+        let path = path.to_path_buf();
+        spawn_blocking(move || write(&path, json).map_err(|e| (path, e)))
+            .await
+            .map_err(|e| Database(format!("Failed to spawn blocking write: {e}")))?
+            .map_err(|(path, e)| {
+                Database(format!(
+                    "Failed to write settings to {}: {e}",
+                    path.display()
+                ))
+            })
+    }
+
+    /// Export the catalog of every track to `path`.
+    ///
+    /// # Returns
+    ///
+    /// The number of tracks written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tracks cannot be read or the file cannot be
+    /// written.
+    pub async fn export_catalog(&self, format: CatalogFormat, path: &Path) -> StorageResult<usize> {
+        export_catalog(&self.pool, format, path).await
+    }
+
+    /// Replace the settings with those of an exported file.
+    ///
+    /// The local window geometry is kept. Library directories of the file
+    /// that exist on this machine are added; missing ones are reported.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed, or the
+    /// settings cannot be saved.
+    pub async fn import_settings(&self, path: &Path) -> StorageResult<ImportReport> {
+        let path = path.to_path_buf();
+        let (imported, existing, missing) = spawn_blocking(move || {
+            let imported = read_export(&path)?;
+            let (existing, missing) = partition_directories(&imported.library_directories);
+            Ok::<_, StorageError>((imported, existing, missing))
+        })
+        .await
+        .map_err(|e| Database(format!("Failed to spawn blocking read: {e}")))??;
+
+        for dir in &existing {
+            self.add_library_directory(dir).await?;
+        }
+        for dir in &missing {
+            warn!(path = %dir, "Imported library directory does not exist on this machine");
+        }
+        {
+            let mut settings = self.settings.write();
+            let merged = merge_imported(settings.get(), imported);
+            settings.update_memory(|s| *s = merged);
+        }
+        self.update_collator();
+        self.save_settings_async().await?;
+        Ok(ImportReport {
+            directories_added: existing,
+            missing_directories: missing,
+        })
+    }
+}
@@ -0,0 +1,37 @@
+//! The `apply_field!` macro shared by the album and track updates.
+
+/// Apply a `FieldUpdate` to a column in the given table.
+macro_rules! apply_field {
+    ($table:literal, $update:expr, $field:ident, $pool:expr, $id:expr) => {
+        match &$update.$field {
+            Set(v) => {
+                query(concat!(
+                    "UPDATE ",
+                    $table,
+                    " SET ",
+                    stringify!($field),
+                    " = ? WHERE id = ?"
+                ))
+                .bind(v)
+                .bind($id)
+                .execute($pool)
+                .await
+                .map_err(|e| Database(format!("Update {} failed: {e}", $table)))?;
+            }
+            SetNull => {
+                query(concat!(
+                    "UPDATE ",
+                    $table,
+                    " SET ",
+                    stringify!($field),
+                    " = NULL WHERE id = ?"
+                ))
+                .bind($id)
+                .execute($pool)
+                .await
+                .map_err(|e| Database(format!("Update {} failed: {e}", $table)))?;
+            }
+            Skip => {}
+        }
+    };
+}
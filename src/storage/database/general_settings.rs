@@ -0,0 +1,184 @@
+//! General settings on [`SqliteStorage`]: view mode, active tab, audio
+//! device, volume and output mode, and saving the settings file.
+
+use std::fs::write;
+
+use {serde_json::to_string_pretty, tokio::task::spawn_blocking};
+
+use crate::{
+    playback::output::OutputMode,
+    storage::{
+        StorageError::{self, Database},
+        collation::TitleCollator,
+        database::SqliteStorage,
+        settings::{ActiveTab, UserSettings, ViewMode},
+    },
+};
+
+impl SqliteStorage {
+    /// Get a copy of all current settings.
+    pub fn get_settings(&self) -> UserSettings {
+        self.settings.read().get().clone()
+    }
+
+    /// Get the current view mode.
+    pub fn get_view_mode(&self) -> ViewMode {
+        self.settings.read().get().view_mode
+    }
+
+    /// Set the view mode in memory and persist to disk asynchronously.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_view_mode(&self, mode: ViewMode) -> Result<(), StorageError> {
+        self.settings.write().update_memory(|s| s.view_mode = mode);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save view mode: {e}")))?;
+        Ok(())
+    }
+
+    /// Get whether gapless playback is enabled.
+    pub fn get_gapless_enabled(&self) -> bool {
+        self.settings.read().get_gapless_enabled()
+    }
+
+    /// Set whether gapless playback is enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if settings cannot be saved.
+    pub async fn set_gapless_enabled(&self, enabled: bool) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.gapless_enabled = enabled);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save gapless setting: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the preferred audio device name.
+    pub fn get_audio_device(&self) -> Option<String> {
+        self.settings.read().get_audio_device().map(String::from)
+    }
+
+    /// Set the preferred audio device name.
+    ///
+    /// The volume switches to the one last used with that device, if any;
+    /// read it back with [`Self::get_settings_volume`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if settings cannot be saved.
+    pub async fn set_audio_device(&self, device: Option<String>) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.select_audio_device(device));
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save audio device: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the active tab preference.
+    pub fn get_active_tab(&self) -> ActiveTab {
+        self.settings.read().get_active_tab()
+    }
+
+    /// Set the active tab preference.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if settings cannot be saved.
+    pub async fn set_active_tab(&self, tab: ActiveTab) -> Result<(), StorageError> {
+        self.settings.write().update_memory(|s| s.active_tab = tab);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save active tab: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the volume level from settings.
+    pub fn get_settings_volume(&self) -> f64 {
+        self.settings.read().get_volume()
+    }
+
+    /// Set the volume level in memory and persist to disk asynchronously.
+    ///
+    /// The volume is also remembered for the preferred audio device.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_volume(&self, volume: f64) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.remember_volume(volume));
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save volume: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the output mode from settings.
+    pub fn get_output_mode(&self) -> OutputMode {
+        self.settings.read().get_output_mode()
+    }
+
+    /// Set the output mode in memory and persist to disk asynchronously.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_output_mode(&self, mode: OutputMode) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.output_mode = mode);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save output mode: {e}")))?;
+        Ok(())
+    }
+
+    /// Rebuild the title order after the sort settings changed.
+    pub(super) fn update_collator(&self) {
+        let collator = sort_collator(self.settings.read().get());
+        *self.collator.write() = collator;
+    }
+
+    /// Serialize settings to JSON and persist to disk via `spawn_blocking`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `StorageError::Database` if JSON serialization or file writing fails.
+    pub(super) async fn save_settings_async(&self) -> Result<(), StorageError> {
+        let (json, path) = {
+            let settings = self.settings.read();
+            let json = to_string_pretty(settings.get())
+                .map_err(|e| Database(format!("Failed to serialize settings: {e}")))?;
+            (json, settings.path().to_path_buf())
+        };
+        let path_for_error = path.clone();
+        spawn_blocking(move || write(&path, &json))
+            .await
+            .map_err(|e| Database(format!("Failed to spawn blocking write: {e}")))?
+            .map_err(|e| {
+                Database(format!(
+                    "Failed to write settings to {}: {e}",
+                    path_for_error.display()
+                ))
+            })?;
+        Ok(())
+    }
+}
+
+/// Title order of the sort settings in `settings`.
+pub(super) fn sort_collator(settings: &UserSettings) -> TitleCollator {
+    if settings.ignore_sort_articles {
+        TitleCollator::new(&settings.sort_articles)
+    } else {
+        TitleCollator::new(&[])
+    }
+}
@@ -0,0 +1,116 @@
+//! Integration settings of [`SqliteStorage`]: scrobbling and when a track
+//! counts as played, Discord Rich Presence, and the now playing text
+//! template.
+
+use crate::{
+    library::{play_threshold::PlayThreshold, scrobble::ScrobbleSettings},
+    storage::{
+        StorageError::{self, Database},
+        database::SqliteStorage,
+    },
+};
+
+impl SqliteStorage {
+    /// Get the scrobbling preferences from settings.
+    pub fn get_scrobble_settings(&self) -> ScrobbleSettings {
+        self.settings.read().get().scrobble.clone()
+    }
+
+    /// Set the scrobbling preferences in memory and persist to disk asynchronously.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_scrobble_settings(
+        &self,
+        scrobble: ScrobbleSettings,
+    ) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.scrobble = scrobble);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save scrobble settings: {e}")))?;
+        Ok(())
+    }
+
+    /// Get how much of a track has to be heard before it counts as played.
+    pub fn get_play_threshold(&self) -> PlayThreshold {
+        self.settings.read().get().play_threshold
+    }
+
+    /// Set how much of a track has to be heard before it counts as played.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_play_threshold(&self, threshold: PlayThreshold) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.play_threshold = threshold);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save play threshold: {e}")))?;
+        Ok(())
+    }
+
+    /// Get whether Discord Rich Presence is enabled from settings.
+    pub fn get_rich_presence_enabled(&self) -> bool {
+        self.settings.read().get().rich_presence_enabled
+    }
+
+    /// Set whether Discord Rich Presence is enabled and persist to disk asynchronously.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_rich_presence_enabled(&self, enabled: bool) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.rich_presence_enabled = enabled);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save Rich Presence setting: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the Discord application ID used for Rich Presence from settings.
+    pub fn get_discord_client_id(&self) -> String {
+        self.settings.read().get().discord_client_id.clone()
+    }
+
+    /// Set the Discord application ID and persist to disk asynchronously.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_discord_client_id(&self, client_id: String) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.discord_client_id = client_id);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save Discord application ID: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the template of the text copied by "Copy Now Playing".
+    pub fn get_share_template(&self) -> String {
+        self.settings.read().get().share_template.clone()
+    }
+
+    /// Set the template of the text copied by "Copy Now Playing".
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_share_template(&self, template: String) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.share_template = template);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save share template: {e}")))?;
+        Ok(())
+    }
+}
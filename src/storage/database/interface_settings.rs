@@ -0,0 +1,293 @@
+//! Interface settings of [`SqliteStorage`]: how the library is laid out and
+//! sorted, what the album and track lists do on click, and which badges,
+//! meters and notifications are shown.
+
+use crate::{
+    config::shortcuts::ShortcutSettings,
+    library::dynamic_range::DrBadgeDisplayPolicy,
+    storage::{
+        StorageError::{self, Database},
+        click_action::{AlbumClickAction, ListActivation},
+        database::SqliteStorage,
+        sort_order::{ArtistSortOrder, SortOrder},
+        zoom::ZoomLevel,
+    },
+};
+
+impl SqliteStorage {
+    /// Get the album grid zoom level.
+    pub fn get_zoom_level(&self) -> ZoomLevel {
+        self.settings.read().get().zoom_level
+    }
+
+    /// Set the album grid zoom level in memory and persist to disk asynchronously.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_zoom_level(&self, level: ZoomLevel) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.zoom_level = level);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save zoom level: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the album sort order.
+    pub fn get_album_sort(&self) -> SortOrder {
+        self.settings.read().get().album_sort
+    }
+
+    /// Set the album sort order in memory and persist to disk asynchronously.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_album_sort(&self, sort: SortOrder) -> Result<(), StorageError> {
+        self.settings.write().update_memory(|s| s.album_sort = sort);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save album sort order: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the artist sort order.
+    pub fn get_artist_sort(&self) -> ArtistSortOrder {
+        self.settings.read().get().artist_sort
+    }
+
+    /// Set the artist sort order in memory and persist to disk asynchronously.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_artist_sort(&self, sort: ArtistSortOrder) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.artist_sort = sort);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save artist sort order: {e}")))?;
+        Ok(())
+    }
+
+    /// Get whether the waveform overview is shown above the seek bar.
+    pub fn get_show_waveform(&self) -> bool {
+        self.settings.read().get().show_waveform
+    }
+
+    /// Set whether the waveform overview is shown above the seek bar.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_show_waveform(&self, enabled: bool) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.show_waveform = enabled);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save waveform preference: {e}")))?;
+        Ok(())
+    }
+
+    /// Get what clicking an album in the library views does.
+    pub fn get_album_click_action(&self) -> AlbumClickAction {
+        self.settings.read().get().album_click_action
+    }
+
+    /// Set what clicking an album in the library views does.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_album_click_action(
+        &self,
+        action: AlbumClickAction,
+    ) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.album_click_action = action);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save album click action: {e}")))?;
+        Ok(())
+    }
+
+    /// Get whether list view rows open on a single or a double click.
+    pub fn get_list_activation(&self) -> ListActivation {
+        self.settings.read().get().list_activation
+    }
+
+    /// Set whether list view rows open on a single or a double click.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_list_activation(
+        &self,
+        activation: ListActivation,
+    ) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.list_activation = activation);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save list activation mode: {e}")))?;
+        Ok(())
+    }
+
+    /// Get which albums show a DR badge in the album grid.
+    pub fn get_dr_badge_policy(&self) -> DrBadgeDisplayPolicy {
+        self.settings.read().get().dr_badges
+    }
+
+    /// Set which albums show a DR badge in the album grid.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_dr_badge_policy(
+        &self,
+        policy: DrBadgeDisplayPolicy,
+    ) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.dr_badges = policy);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save DR badge policy: {e}")))?;
+        Ok(())
+    }
+
+    /// Get whether quality badges are shown in the album grid.
+    pub fn get_show_quality_badges(&self) -> bool {
+        self.settings.read().get().show_quality_badges
+    }
+
+    /// Set whether quality badges are shown in the album grid.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_show_quality_badges(&self, enabled: bool) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.show_quality_badges = enabled);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save quality badge setting: {e}")))?;
+        Ok(())
+    }
+
+    /// Get whether level meters are shown in the player panel.
+    pub fn get_show_level_meter(&self) -> bool {
+        self.settings.read().get().show_level_meter
+    }
+
+    /// Set whether level meters are shown in the player panel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_show_level_meter(&self, enabled: bool) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.show_level_meter = enabled);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save level meter preference: {e}")))?;
+        Ok(())
+    }
+
+    /// Get whether a desktop notification is shown when a track starts.
+    pub fn get_track_notifications(&self) -> bool {
+        self.settings.read().get().track_notifications
+    }
+
+    /// Set whether a desktop notification is shown when a track starts.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_track_notifications(&self, enabled: bool) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.track_notifications = enabled);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save track notification preference: {e}")))?;
+        Ok(())
+    }
+
+    /// Get whether the time remaining is shown instead of the track
+    /// duration.
+    pub fn get_show_remaining_time(&self) -> bool {
+        self.settings.read().get().show_remaining_time
+    }
+
+    /// Set whether the time remaining is shown instead of the track
+    /// duration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_show_remaining_time(&self, enabled: bool) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.show_remaining_time = enabled);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save time display preference: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the remembered mini player size as `(width, height)`.
+    pub fn get_mini_player_size(&self) -> (i32, i32) {
+        let settings = self.settings.read();
+        let size = (
+            settings.get().mini_player_width,
+            settings.get().mini_player_height,
+        );
+        drop(settings);
+        size
+    }
+
+    /// Remember the mini player size for the next time it opens.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_mini_player_size(&self, width: i32, height: i32) -> Result<(), StorageError> {
+        self.settings.write().update_memory(|s| {
+            s.mini_player_width = width;
+            s.mini_player_height = height;
+        });
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save mini player size: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the keyboard shortcut bindings from settings.
+    pub fn get_shortcuts(&self) -> ShortcutSettings {
+        self.settings.read().get().shortcuts.clone()
+    }
+
+    /// Set the keyboard shortcut bindings in memory and persist to disk asynchronously.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_shortcuts(&self, shortcuts: ShortcutSettings) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.shortcuts = shortcuts);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save shortcuts: {e}")))?;
+        Ok(())
+    }
+}
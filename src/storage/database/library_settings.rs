@@ -0,0 +1,258 @@
+//! Library settings of [`SqliteStorage`]: how hard background work and
+//! scans run, how the watcher batches changes, how titles and artists are
+//! sorted and credited, and where artwork and caches come from.
+
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    library::watcher::config::WatcherConfig,
+    storage::{
+        StorageError::{self, Database},
+        collation::TitleCollator,
+        database::SqliteStorage,
+    },
+    threading::scheduler::WorkIntensity,
+};
+
+impl SqliteStorage {
+    /// Get the background work intensity from settings.
+    pub fn get_work_intensity(&self) -> WorkIntensity {
+        self.settings.read().get().work_intensity
+    }
+
+    /// Set the background work intensity in memory and persist to disk asynchronously.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_work_intensity(&self, intensity: WorkIntensity) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.work_intensity = intensity);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save work intensity: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the number of library scan worker threads (`0` follows the work
+    /// intensity).
+    pub fn get_scan_threads(&self) -> usize {
+        self.settings.read().get().scan_threads
+    }
+
+    /// Set the number of library scan worker threads and persist to disk
+    /// asynchronously.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_scan_threads(&self, threads: usize) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.scan_threads = threads);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save scan threads: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the rescan interval of unwatchable library directories from settings.
+    pub fn get_watch_poll_interval(&self) -> Duration {
+        Duration::from_secs(self.settings.read().get().watch_poll_interval_secs.max(1))
+    }
+
+    /// Get how long the scanner waits for the metadata of one file.
+    ///
+    /// A zero duration waits indefinitely.
+    pub fn get_metadata_timeout(&self) -> Duration {
+        Duration::from_secs(self.settings.read().get().metadata_timeout_secs)
+    }
+
+    /// Get the debounce and batch limits of the library watcher.
+    ///
+    /// Values edited out of range in the settings file are clamped.
+    pub fn get_watcher_config(&self) -> WatcherConfig {
+        let settings = self.settings.read();
+        let (debounce_ms, batch_size) = (
+            settings.get().watch_debounce_ms,
+            settings.get().watch_batch_size,
+        );
+        drop(settings);
+        WatcherConfig::new(debounce_ms, batch_size)
+    }
+
+    /// Set the debounce and batch limits of the library watcher.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_watcher_config(&self, config: WatcherConfig) -> Result<(), StorageError> {
+        self.settings.write().update_memory(|s| {
+            s.watch_debounce_ms = config.debounce_ms;
+            s.watch_batch_size = config.max_batch_size;
+        });
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save watcher settings: {e}")))?;
+        Ok(())
+    }
+
+    /// Get whether albums are shown with their original release year.
+    pub fn get_use_original_year(&self) -> bool {
+        self.settings.read().get().use_original_year
+    }
+
+    /// Set whether albums are shown with their original release year.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_use_original_year(&self, enabled: bool) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.use_original_year = enabled);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save year preference: {e}")))?;
+        Ok(())
+    }
+
+    /// Get whether titles and names are sorted without their leading article.
+    pub fn get_ignore_sort_articles(&self) -> bool {
+        self.settings.read().get().ignore_sort_articles
+    }
+
+    /// Set whether titles and names are sorted without their leading article.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_ignore_sort_articles(&self, enabled: bool) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.ignore_sort_articles = enabled);
+        self.update_collator();
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save article setting: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the leading articles skipped when sorting.
+    pub fn get_sort_articles(&self) -> Vec<String> {
+        self.settings.read().get().sort_articles.clone()
+    }
+
+    /// Set the leading articles skipped when sorting.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_sort_articles(&self, articles: Vec<String>) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.sort_articles = articles);
+        self.update_collator();
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save sort articles: {e}")))?;
+        Ok(())
+    }
+
+    /// Get whether albums of collaborations are listed under each artist.
+    pub fn get_split_collaborations(&self) -> bool {
+        self.settings.read().get().split_collaborations
+    }
+
+    /// Set whether albums of collaborations are listed under each artist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_split_collaborations(&self, enabled: bool) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.split_collaborations = enabled);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save collaboration setting: {e}")))?;
+        Ok(())
+    }
+
+    /// Title order of the current sort settings.
+    pub fn title_collator(&self) -> TitleCollator {
+        self.collator.read().clone()
+    }
+
+    /// Get whether album covers come from sidecar images before embedded
+    /// artwork.
+    pub fn get_prefer_sidecar_artwork(&self) -> bool {
+        self.settings.read().get().prefer_sidecar_artwork
+    }
+
+    /// Set whether album covers come from sidecar images before embedded
+    /// artwork.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_prefer_sidecar_artwork(&self, prefer: bool) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.prefer_sidecar_artwork = prefer);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save artwork preference: {e}")))?;
+        Ok(())
+    }
+
+    /// Get whether scans follow symbolic links.
+    pub fn get_follow_symlinks(&self) -> bool {
+        self.settings.read().get().follow_symlinks
+    }
+
+    /// Set whether scans follow symbolic links.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_follow_symlinks(&self, follow: bool) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.follow_symlinks = follow);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save symlink preference: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the directory chosen for the caches, if any.
+    pub fn get_cache_directory(&self) -> Option<PathBuf> {
+        self.settings
+            .read()
+            .get()
+            .cache_directory
+            .as_ref()
+            .map(PathBuf::from)
+    }
+
+    /// Set the directory for the caches, or `None` for the default one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_cache_directory(&self, dir: Option<&Path>) -> Result<(), StorageError> {
+        let dir = dir.map(|d| d.to_string_lossy().into_owned());
+        self.settings
+            .write()
+            .update_memory(|s| s.cache_directory = dir);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save cache directory: {e}")))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+//! Library maintenance on [`SqliteStorage`]: removing tracks whose files
+//! are gone, with snapshots to undo it, moving library folders, finding
+//! duplicates, and cached library statistics.
+
+use std::path::Path;
+
+use crate::storage::{
+    StorageResult,
+    database::SqliteStorage,
+    duplicates::{DuplicateGroup, find_duplicates},
+    prune::{PruneReport, find_missing_tracks, prune_tracks},
+    relocate::{RemapReport, remap_directory},
+    snapshot::{LibrarySnapshot, restore_snapshot, snapshot_directory, snapshot_tracks},
+    stats::{LibraryStats, compute_stats, library_revision},
+};
+
+impl SqliteStorage {
+    /// Find the tracks whose audio file no longer exists on disk.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the track paths cannot be read.
+    pub async fn find_missing_tracks(&self) -> StorageResult<Vec<i64>> {
+        find_missing_tracks(&self.pool, None).await
+    }
+
+    /// Remove the given tracks along with albums and artists left without tracks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the removal fails; the library is then unchanged.
+    pub async fn remove_tracks(&self, ids: &[i64]) -> StorageResult<PruneReport> {
+        prune_tracks(&self.pool, ids).await
+    }
+
+    /// Copy the given tracks and their albums and artists before removing them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rows cannot be read.
+    pub async fn snapshot_tracks(&self, ids: &[i64]) -> StorageResult<LibrarySnapshot> {
+        snapshot_tracks(&self.pool, ids).await
+    }
+
+    /// Copy a library directory row before removing it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the row cannot be read.
+    pub async fn snapshot_directory(&self, id: i64) -> StorageResult<LibrarySnapshot> {
+        snapshot_directory(&self.pool, id).await
+    }
+
+    /// Insert the rows of a snapshot again, returning how many were restored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the restore fails; the library is then unchanged.
+    pub async fn restore_snapshot(&self, snapshot: &LibrarySnapshot) -> StorageResult<u64> {
+        restore_snapshot(&self.pool, snapshot).await
+    }
+
+    /// Move every stored path under `old_prefix` to `new_prefix`, keeping
+    /// the tracks and everything attached to them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `new_prefix` is not a directory, a moved track
+    /// collides with an existing one, or the update fails; the library is
+    /// then unchanged.
+    pub async fn remap_directory(
+        &self,
+        old_prefix: &Path,
+        new_prefix: &Path,
+    ) -> StorageResult<RemapReport> {
+        remap_directory(&self.pool, old_prefix, new_prefix).await
+    }
+
+    /// Remove every track whose file is missing, along with orphaned albums
+    /// and artists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the paths cannot be read or the removal fails.
+    pub async fn prune_missing(&self) -> StorageResult<PruneReport> {
+        let missing = self.find_missing_tracks().await?;
+        self.remove_tracks(&missing).await
+    }
+
+    /// Find groups of tracks holding the same recording, best copy first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tracks cannot be read.
+    pub async fn find_duplicates(&self) -> StorageResult<Vec<DuplicateGroup>> {
+        find_duplicates(&self.pool).await
+    }
+
+    /// Get library-wide statistics.
+    ///
+    /// The aggregation runs only when the library changed since the last
+    /// call; otherwise the cached result is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the revision or the aggregates cannot be read.
+    pub async fn stats(&self) -> StorageResult<LibraryStats> {
+        let revision = library_revision(&self.pool).await?;
+        if let Some((cached_revision, stats)) = self.stats_cache.lock().as_ref()
+            && *cached_revision == revision
+        {
+            return Ok(stats.clone());
+        }
+        let stats = compute_stats(&self.pool).await?;
+        *self.stats_cache.lock() = Some((revision, stats.clone()));
+        Ok(stats)
+    }
+}
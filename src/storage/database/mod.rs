@@ -0,0 +1,319 @@
+//! `SQLite` database implementation using `sqlx` for library catalog persistence.
+
+#[macro_use]
+mod field_update;
+pub mod album_search;
+mod albums;
+pub mod annotations;
+pub mod browsing;
+pub mod export;
+mod general_settings;
+pub mod integration_settings;
+pub mod interface_settings;
+pub mod library_settings;
+pub mod maintenance;
+pub mod playback_settings;
+mod queue;
+mod tracks;
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use {
+    parking_lot::{Mutex, RwLock},
+    sqlx::{
+        SqlitePool, query, query_as,
+        sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    },
+};
+
+use crate::storage::{
+    Album, AlbumSearch, AlbumUpdate, Artist, FormatInfo, LibraryDirectory, NewAlbum, NewArtist,
+    NewQueueEntry, NewTrack, QueueContext, QueueEntry, Storage,
+    StorageError::{Database, InvalidPath},
+    StorageResult, Track, TrackUpdate,
+    collation::{LIBRARY_COLLATION, TitleCollator},
+    database::general_settings::sort_collator,
+    migrations::run,
+    prune::{PruneReport, find_missing_tracks},
+    settings::SettingsStore,
+    sort_order::SortOrder,
+    stats::LibraryStats,
+};
+
+/// SQLite-backed storage implementation.
+pub struct SqliteStorage {
+    /// `SQLite` connection pool.
+    pool: SqlitePool,
+    /// User settings store.
+    settings: RwLock<SettingsStore>,
+    /// Last computed library statistics and the revision they describe.
+    stats_cache: Mutex<Option<(i64, LibraryStats)>>,
+    /// Title order of the sort settings, shared with the `SQLite` collation.
+    collator: Arc<RwLock<TitleCollator>>,
+}
+
+impl SqliteStorage {
+    /// Create a new `SqliteStorage` with a connection pool to the given database path.
+    ///
+    /// Runs migrations on connect and registers the [`LIBRARY_COLLATION`]
+    /// ordering titles by the sort settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be loaded, or the pool cannot
+    /// be created or migrations fail.
+    pub async fn connect(database_path: &Path) -> StorageResult<Self> {
+        let settings = SettingsStore::load_async()
+            .await
+            .map_err(|e| Database(format!("Failed to load settings: {e}")))?;
+        let collator = Arc::new(RwLock::new(sort_collator(settings.get())));
+
+        let collation = Arc::clone(&collator);
+        let opts = SqliteConnectOptions::new()
+            .filename(database_path)
+            .create_if_missing(true)
+            .collation(LIBRARY_COLLATION, move |a, b| {
+                collation.read().compare(a, b)
+            });
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(opts)
+            .await
+            .map_err(|e| Database(format!("Failed to connect: {e}")))?;
+
+        run(&pool).await?;
+
+        Ok(Self {
+            pool,
+            settings: RwLock::new(settings),
+            stats_cache: Mutex::new(None),
+            collator,
+        })
+    }
+}
+
+impl Storage for SqliteStorage {
+    async fn insert_track(&self, track: NewTrack) -> StorageResult<i64> {
+        tracks::insert_track(&self.pool, &track).await
+    }
+
+    async fn update_track(&self, id: i64, track: TrackUpdate) -> StorageResult<()> {
+        tracks::update_track(&self.pool, id, track).await
+    }
+
+    async fn delete_track(&self, id: i64) -> StorageResult<()> {
+        tracks::delete_track(&self.pool, id).await
+    }
+
+    async fn get_track(&self, id: i64) -> StorageResult<Option<Track>> {
+        tracks::get_track(&self.pool, id).await
+    }
+
+    async fn get_tracks_by_album(&self, album_id: i64) -> StorageResult<Vec<Track>> {
+        tracks::get_tracks_by_album(&self.pool, album_id).await
+    }
+
+    async fn get_tracks_by_artist(&self, artist_id: i64) -> StorageResult<Vec<Track>> {
+        tracks::get_tracks_by_artist(&self.pool, artist_id).await
+    }
+
+    async fn search_tracks(&self, query: &str) -> StorageResult<Vec<Track>> {
+        tracks::search_tracks(&self.pool, query).await
+    }
+
+    async fn insert_album(&self, album: NewAlbum) -> StorageResult<i64> {
+        albums::insert_album(&self.pool, album).await
+    }
+
+    async fn update_album(&self, id: i64, album: AlbumUpdate) -> StorageResult<()> {
+        albums::update_album(&self.pool, id, album).await
+    }
+
+    async fn get_album(&self, id: i64) -> StorageResult<Option<Album>> {
+        albums::get_album(&self.pool, id).await
+    }
+
+    async fn get_all_albums(&self) -> StorageResult<Vec<Album>> {
+        albums::get_all_albums(&self.pool).await
+    }
+
+    async fn search_albums(
+        &self,
+        search: &AlbumSearch,
+        sort: SortOrder,
+    ) -> StorageResult<Vec<Album>> {
+        albums::search_albums(&self.pool, search, sort, self.get_use_original_year()).await
+    }
+
+    async fn get_album_format_info(&self, album_id: i64) -> StorageResult<FormatInfo> {
+        albums::get_album_format_info(&self.pool, album_id).await
+    }
+
+    async fn get_albums_format_info(
+        &self,
+        album_ids: &[i64],
+    ) -> StorageResult<HashMap<i64, FormatInfo>> {
+        albums::get_albums_format_info(&self.pool, album_ids).await
+    }
+
+    async fn get_albums_by_artist(&self, artist_id: i64) -> StorageResult<Vec<Album>> {
+        albums::get_albums_by_artist(
+            &self.pool,
+            artist_id,
+            self.get_split_collaborations(),
+            self.get_use_original_year(),
+        )
+        .await
+    }
+
+    async fn insert_artist(&self, artist: NewArtist) -> StorageResult<i64> {
+        let row_id: (i64,) = query_as("INSERT INTO artists (name) VALUES (?) RETURNING id")
+            .bind(&artist.name)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| Database(format!("Insert artist failed: {e}")))?;
+
+        Ok(row_id.0)
+    }
+
+    async fn get_artist(&self, id: i64) -> StorageResult<Option<Artist>> {
+        query_as::<_, Artist>(
+            "SELECT ar.id, ar.name, (SELECT COUNT(*) FROM albums WHERE artist_id = ar.id) AS \
+             album_count, ar.image_path, ar.bio FROM artists ar WHERE ar.id = ?",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| Database(format!("Get artist failed: {e}")))
+    }
+
+    async fn get_all_artists(&self) -> StorageResult<Vec<Artist>> {
+        query_as::<_, Artist>(
+            "SELECT ar.id, ar.name, (SELECT COUNT(*) FROM albums WHERE artist_id = ar.id) AS \
+             album_count FROM artists ar ORDER BY ar.name COLLATE LIBRARY",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Database(format!("Get all artists failed: {e}")))
+    }
+
+    async fn list_library_directories(&self) -> StorageResult<Vec<LibraryDirectory>> {
+        query_as::<_, LibraryDirectory>("SELECT * FROM library_directories ORDER BY path")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| Database(format!("List directories failed: {e}")))
+    }
+
+    async fn add_library_directory(&self, path: &Path) -> StorageResult<()> {
+        let path_str = path
+            .to_str()
+            .ok_or_else(|| InvalidPath(path.display().to_string()))?;
+
+        query("INSERT OR IGNORE INTO library_directories (path) VALUES (?)")
+            .bind(path_str)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Database(format!("Add directory failed: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn remove_library_directory(&self, id: i64) -> StorageResult<()> {
+        query("DELETE FROM library_directories WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Database(format!("Remove directory failed: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn get_queue(&self) -> StorageResult<Vec<QueueEntry>> {
+        queue::get_queue(&self.pool).await
+    }
+
+    async fn set_queue(&self, entries: &[NewQueueEntry]) -> StorageResult<()> {
+        queue::set_queue(&self.pool, entries).await
+    }
+
+    async fn append_queue(
+        &self,
+        track_id: i64,
+        context: Option<QueueContext>,
+    ) -> StorageResult<()> {
+        queue::append_queue(&self.pool, track_id, context).await
+    }
+
+    async fn remove_queue_entry(&self, id: i64) -> StorageResult<()> {
+        queue::remove_queue_entry(&self.pool, id).await
+    }
+
+    async fn reorder_queue(&self, entry_id: i64, new_position: u32) -> StorageResult<()> {
+        queue::reorder_queue(&self.pool, entry_id, new_position).await
+    }
+
+    async fn clear_queue(&self) -> StorageResult<()> {
+        queue::clear_queue(&self.pool).await
+    }
+
+    async fn find_by_path(&self, path: &Path) -> StorageResult<Option<Track>> {
+        tracks::find_by_path(&self.pool, path).await
+    }
+
+    async fn needs_update(
+        &self,
+        path: &Path,
+        file_size: i64,
+        last_modified: &str,
+    ) -> StorageResult<bool> {
+        tracks::needs_update(&self.pool, path, file_size, last_modified).await
+    }
+
+    async fn update_scanned_track(&self, id: i64, track: NewTrack) -> StorageResult<()> {
+        tracks::update_scanned_track(&self.pool, id, track).await
+    }
+
+    async fn prune_missing_under(&self, roots: &[PathBuf]) -> StorageResult<PruneReport> {
+        let missing = find_missing_tracks(&self.pool, Some(roots.to_vec())).await?;
+        self.remove_tracks(&missing).await
+    }
+
+    async fn find_by_hash(&self, hash: &str) -> StorageResult<Vec<Track>> {
+        tracks::find_by_hash(&self.pool, hash).await
+    }
+
+    async fn find_by_metadata_fingerprint(
+        &self,
+        artist: &str,
+        album: &str,
+        title: &str,
+        track: Option<u32>,
+    ) -> StorageResult<Vec<Track>> {
+        tracks::find_by_metadata_fingerprint(&self.pool, artist, album, title, track).await
+    }
+
+    async fn insert_tracks_batch(&self, tracks: Vec<NewTrack>) -> StorageResult<Vec<i64>> {
+        tracks::insert_tracks_batch(&self.pool, tracks).await
+    }
+
+    async fn find_by_paths_batch(&self, paths: &[&Path]) -> StorageResult<Vec<Option<Track>>> {
+        tracks::find_by_paths_batch(&self.pool, paths).await
+    }
+
+    async fn find_by_hashes_batch(&self, hashes: &[&str]) -> StorageResult<Vec<Vec<Track>>> {
+        tracks::find_by_hashes_batch(&self.pool, hashes).await
+    }
+
+    async fn get_tracks_by_albums(&self, album_ids: &[i64]) -> StorageResult<Vec<Track>> {
+        tracks::get_tracks_by_albums(&self.pool, album_ids).await
+    }
+
+    async fn get_tracks_by_ids(&self, ids: &[i64]) -> StorageResult<Vec<Track>> {
+        tracks::get_tracks_by_ids(&self.pool, ids).await
+    }
+}
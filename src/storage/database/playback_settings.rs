@@ -0,0 +1,293 @@
+//! Playback settings of [`SqliteStorage`]: transitions between tracks,
+//! fades, sample rate handling, channel mixing, the equalizer, and what
+//! is restored on the next start.
+
+use crate::{
+    playback::{
+        buffer::valid_buffer_frames, equalizer::EqualizerSettings, resampler::ResampleQuality,
+        stereo::DownmixMode,
+    },
+    storage::{
+        StorageError::{self, Database},
+        database::SqliteStorage,
+    },
+};
+
+impl SqliteStorage {
+    /// Get the crossfade window in milliseconds from settings.
+    pub fn get_crossfade_ms(&self) -> u32 {
+        self.settings.read().get().crossfade_ms
+    }
+
+    /// Set the crossfade window in memory and persist to disk asynchronously.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_crossfade_ms(&self, crossfade_ms: u32) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.crossfade_ms = crossfade_ms);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save crossfade: {e}")))?;
+        Ok(())
+    }
+
+    /// Get whether the silence at the start of tracks is skipped.
+    pub fn get_skip_leading_silence(&self) -> bool {
+        self.settings.read().get().skip_leading_silence
+    }
+
+    /// Set whether the silence at the start of tracks is skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_skip_leading_silence(&self, enabled: bool) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.skip_leading_silence = enabled);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save leading silence skip: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the level in dBFS below which leading audio counts as silence.
+    pub fn get_leading_silence_db(&self) -> i32 {
+        self.settings.read().get().leading_silence_db
+    }
+
+    /// Set the level in dBFS below which leading audio counts as silence.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_leading_silence_db(&self, threshold_db: i32) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.leading_silence_db = threshold_db);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save silence threshold: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the silence between non-gapless tracks in milliseconds.
+    pub fn get_inter_track_gap_ms(&self) -> u32 {
+        self.settings.read().get().inter_track_gap_ms
+    }
+
+    /// Set the silence between non-gapless tracks in milliseconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_inter_track_gap_ms(&self, gap_ms: u32) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.inter_track_gap_ms = gap_ms);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save inter-track gap: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the play/pause fade length in milliseconds from settings.
+    pub fn get_fade_ms(&self) -> u32 {
+        self.settings.read().get().fade_ms
+    }
+
+    /// Set the play/pause fade length in memory and persist to disk asynchronously.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_fade_ms(&self, fade_ms: u32) -> Result<(), StorageError> {
+        self.settings.write().update_memory(|s| s.fade_ms = fade_ms);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save fade length: {e}")))?;
+        Ok(())
+    }
+
+    /// Get whether the output follows each track's sample rate.
+    pub fn get_follow_source_rate(&self) -> bool {
+        self.settings.read().get().follow_source_rate
+    }
+
+    /// Set whether the output follows each track's sample rate.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_follow_source_rate(&self, enabled: bool) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.follow_source_rate = enabled);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save sample-rate follow: {e}")))?;
+        Ok(())
+    }
+
+    /// Get whether a toast announces output sample rate changes.
+    pub fn get_notify_rate_changes(&self) -> bool {
+        self.settings.read().get().notify_rate_changes
+    }
+
+    /// Set whether a toast announces output sample rate changes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_notify_rate_changes(&self, enabled: bool) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.notify_rate_changes = enabled);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save rate change notice: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the quality profile for sample-rate conversion.
+    pub fn get_resample_quality(&self) -> ResampleQuality {
+        self.settings.read().get().resample_quality
+    }
+
+    /// Set the quality profile for sample-rate conversion.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_resample_quality(&self, quality: ResampleQuality) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.resample_quality = quality);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save resampler quality: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the size of the output ring buffer in frames.
+    ///
+    /// Sizes edited out of range in the settings file are rounded up to a
+    /// power of two and clamped.
+    pub fn get_output_buffer_frames(&self) -> u32 {
+        valid_buffer_frames(self.settings.read().get().output_buffer_frames)
+    }
+
+    /// Set the size of the output ring buffer in frames.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_output_buffer_frames(&self, frames: u32) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.output_buffer_frames = frames);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save output buffer size: {e}")))?;
+        Ok(())
+    }
+
+    /// Get how the left and right channels reach the device.
+    pub fn get_downmix(&self) -> DownmixMode {
+        self.settings.read().get().downmix
+    }
+
+    /// Set how the left and right channels reach the device.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_downmix(&self, mode: DownmixMode) -> Result<(), StorageError> {
+        self.settings.write().update_memory(|s| s.downmix = mode);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save channel mode: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the left/right balance.
+    pub fn get_balance(&self) -> f64 {
+        self.settings.read().get().balance
+    }
+
+    /// Set the left/right balance.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_balance(&self, balance: f64) -> Result<(), StorageError> {
+        self.settings.write().update_memory(|s| s.balance = balance);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save balance: {e}")))?;
+        Ok(())
+    }
+
+    /// Get whether a changed playback speed carries over to the next track.
+    pub fn get_remember_playback_rate(&self) -> bool {
+        self.settings.read().get().remember_playback_rate
+    }
+
+    /// Set whether a changed playback speed carries over to the next track.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_remember_playback_rate(&self, remember: bool) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.remember_playback_rate = remember);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save playback speed preference: {e}")))?;
+        Ok(())
+    }
+
+    /// Get whether the playback queue is restored at launch.
+    pub fn get_resume_on_startup(&self) -> bool {
+        self.settings.read().get().resume_on_startup
+    }
+
+    /// Set whether the playback queue is restored at launch.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_resume_on_startup(&self, resume: bool) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.resume_on_startup = resume);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save session restore preference: {e}")))?;
+        Ok(())
+    }
+
+    /// Get the equalizer preferences from settings.
+    pub fn get_equalizer(&self) -> EqualizerSettings {
+        self.settings.read().get().equalizer
+    }
+
+    /// Set the equalizer preferences in memory and persist to disk asynchronously.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be saved.
+    pub async fn set_equalizer(&self, equalizer: EqualizerSettings) -> Result<(), StorageError> {
+        self.settings
+            .write()
+            .update_memory(|s| s.equalizer = equalizer);
+        self.save_settings_async()
+            .await
+            .map_err(|e| Database(format!("Failed to save equalizer: {e}")))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+//! Playback queue rows of [`SqliteStorage`](super::SqliteStorage).
+
+use sqlx::{SqlitePool, query, query_as};
+
+use crate::storage::{
+    NewQueueEntry,
+    QueueContext::{self, Album as QueueAlbum, Artist as QueueArtist, Manual},
+    QueueEntry,
+    StorageError::Database,
+    StorageResult,
+};
+
+/// Get the playback queue in order.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn get_queue(pool: &SqlitePool) -> StorageResult<Vec<QueueEntry>> {
+    query_as::<_, QueueEntry>("SELECT * FROM playback_queue ORDER BY position")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Database(format!("Get queue failed: {e}")))
+}
+
+/// Replace the playback queue with `entries`.
+///
+/// # Errors
+///
+/// Returns an error if clearing the queue or inserting an entry fails.
+pub(super) async fn set_queue(pool: &SqlitePool, entries: &[NewQueueEntry]) -> StorageResult<()> {
+    query("DELETE FROM playback_queue")
+        .execute(pool)
+        .await
+        .map_err(|e| Database(format!("Clear queue failed: {e}")))?;
+
+    for entry in entries {
+        query(
+            "INSERT INTO playback_queue (track_id, position, context_type, context_id) VALUES \
+             (?, ?, ?, ?)",
+        )
+        .bind(entry.track_id)
+        .bind(entry.position)
+        .bind(&entry.context_type)
+        .bind(entry.context_id)
+        .execute(pool)
+        .await
+        .map_err(|e| Database(format!("Set queue entry failed: {e}")))?;
+    }
+
+    Ok(())
+}
+
+/// Add a track to the end of the playback queue.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn append_queue(
+    pool: &SqlitePool,
+    track_id: i64,
+    context: Option<QueueContext>,
+) -> StorageResult<()> {
+    let max_pos: Option<(i32,)> =
+        query_as("SELECT COALESCE(MAX(position), -1) FROM playback_queue")
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| Database(format!("Queue max failed: {e}")))?;
+
+    let next_pos = max_pos.map_or(0, |(p,)| p + 1);
+
+    let (context_type, context_id) = match context {
+        Some(QueueAlbum(id)) => (Some("album".to_string()), Some(id)),
+        Some(QueueArtist(id)) => (Some("artist".to_string()), Some(id)),
+        Some(Manual) | None => (None, None),
+    };
+
+    query(
+        "INSERT INTO playback_queue (track_id, position, context_type, context_id) VALUES (?, \
+         ?, ?, ?)",
+    )
+    .bind(track_id)
+    .bind(next_pos)
+    .bind(context_type)
+    .bind(context_id)
+    .execute(pool)
+    .await
+    .map_err(|e| Database(format!("Append queue failed: {e}")))?;
+
+    Ok(())
+}
+
+/// Remove the queue entry `id`.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn remove_queue_entry(pool: &SqlitePool, id: i64) -> StorageResult<()> {
+    query("DELETE FROM playback_queue WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(|e| Database(format!("Remove queue entry failed: {e}")))?;
+
+    Ok(())
+}
+
+/// Move the queue entry `entry_id` to `new_position`.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn reorder_queue(
+    pool: &SqlitePool,
+    entry_id: i64,
+    new_position: u32,
+) -> StorageResult<()> {
+    query("UPDATE playback_queue SET position = ? WHERE id = ?")
+        .bind(new_position.cast_signed())
+        .bind(entry_id)
+        .execute(pool)
+        .await
+        .map_err(|e| Database(format!("Reorder queue failed: {e}")))?;
+
+    Ok(())
+}
+
+/// Remove every entry from the playback queue.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn clear_queue(pool: &SqlitePool) -> StorageResult<()> {
+    query("DELETE FROM playback_queue")
+        .execute(pool)
+        .await
+        .map_err(|e| Database(format!("Clear queue failed: {e}")))?;
+
+    Ok(())
+}
@@ -0,0 +1,395 @@
+//! Track rows of [`SqliteStorage`](super::SqliteStorage): inserting and
+//! updating scanned tracks, and finding them by ID, path, hash or album.
+
+use std::path::Path;
+
+use sqlx::{QueryBuilder, SqlitePool, query, query_as};
+
+use crate::storage::{
+    FieldUpdate::{Set, SetNull, Skip},
+    NewTrack,
+    StorageError::{Database, InvalidPath},
+    StorageResult, Track, TrackUpdate,
+};
+
+/// Insert a new track row and return its ID.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn insert_track(pool: &SqlitePool, track: &NewTrack) -> StorageResult<i64> {
+    let row_id: (i64,) = query_as(
+        "INSERT INTO tracks (title, number, disc_number, disc_total, duration, file_path, \
+         content_hash, format, sample_rate, bit_depth, channels, codec, lossless, bitrate, \
+         album_id, artist_id, file_size, last_modified) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, \
+         ?, ?, ?, ?, ?, ?, ?, ?, ?) RETURNING id",
+    )
+    .bind(&track.title)
+    .bind(track.track_number)
+    .bind(track.disc_number)
+    .bind(track.disc_total)
+    .bind(track.duration)
+    .bind(&track.audio.file_path)
+    .bind(&track.audio.content_hash)
+    .bind(&track.audio.format)
+    .bind(track.audio.sample_rate)
+    .bind(track.audio.bit_depth)
+    .bind(track.audio.channels)
+    .bind(&track.audio.codec)
+    .bind(track.audio.lossless)
+    .bind(track.audio.bitrate)
+    .bind(track.audio.album_id)
+    .bind(track.audio.artist_id)
+    .bind(track.audio.file_size)
+    .bind(&track.audio.last_modified)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| Database(format!("Insert track failed: {e}")))?;
+
+    Ok(row_id.0)
+}
+
+/// Apply the set fields of `track` to the track `id`.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn update_track(
+    pool: &SqlitePool,
+    id: i64,
+    track: TrackUpdate,
+) -> StorageResult<()> {
+    if let Some(title) = track.title {
+        query("UPDATE tracks SET title = ? WHERE id = ?")
+            .bind(&title)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map_err(|e| Database(format!("Update track failed: {e}")))?;
+    }
+    apply_field!("tracks", track, track_number, pool, id);
+    apply_field!("tracks", track, disc_number, pool, id);
+    if let Some(duration) = track.duration {
+        query("UPDATE tracks SET duration = ? WHERE id = ?")
+            .bind(duration)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map_err(|e| Database(format!("Update track failed: {e}")))?;
+    }
+    apply_field!("tracks", track, content_hash, pool, id);
+    apply_field!("tracks", track, album_id, pool, id);
+    apply_field!("tracks", track, artist_id, pool, id);
+    Ok(())
+}
+
+/// Delete the track `id`.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn delete_track(pool: &SqlitePool, id: i64) -> StorageResult<()> {
+    query("DELETE FROM tracks WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(|e| Database(format!("Delete track failed: {e}")))?;
+    Ok(())
+}
+
+/// Get the track `id`, if it exists.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn get_track(pool: &SqlitePool, id: i64) -> StorageResult<Option<Track>> {
+    query_as::<_, Track>("SELECT * FROM tracks WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| Database(format!("Get track failed: {e}")))
+}
+
+/// Get the tracks of an album in disc and track order.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn get_tracks_by_album(
+    pool: &SqlitePool,
+    album_id: i64,
+) -> StorageResult<Vec<Track>> {
+    query_as::<_, Track>(
+        "SELECT * FROM tracks WHERE album_id = ? ORDER BY COALESCE(disc_number, 1), number",
+    )
+    .bind(album_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| Database(format!("Get tracks by album failed: {e}")))
+}
+
+/// Get the tracks credited to an artist.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn get_tracks_by_artist(
+    pool: &SqlitePool,
+    artist_id: i64,
+) -> StorageResult<Vec<Track>> {
+    query_as::<_, Track>("SELECT * FROM tracks WHERE artist_id = ?")
+        .bind(artist_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Database(format!("Get tracks by artist failed: {e}")))
+}
+
+/// Get the tracks whose title or path contains `query`.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn search_tracks(pool: &SqlitePool, query: &str) -> StorageResult<Vec<Track>> {
+    let pattern = format!("%{query}%");
+    query_as::<_, Track>("SELECT * FROM tracks WHERE title LIKE ? OR file_path LIKE ?")
+        .bind(&pattern)
+        .bind(&pattern)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Database(format!("Search tracks failed: {e}")))
+}
+
+/// Find the track stored at `path`.
+///
+/// # Errors
+///
+/// Returns an error if the path is not UTF-8 or the query fails.
+pub(super) async fn find_by_path(pool: &SqlitePool, path: &Path) -> StorageResult<Option<Track>> {
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| InvalidPath(path.display().to_string()))?;
+
+    query_as::<_, Track>("SELECT * FROM tracks WHERE file_path = ?")
+        .bind(path_str)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| Database(format!("Find by path failed: {e}")))
+}
+
+/// Whether the file at `path` changed since it was scanned.
+///
+/// # Errors
+///
+/// Returns an error if the path is not UTF-8 or the query fails.
+pub(super) async fn needs_update(
+    pool: &SqlitePool,
+    path: &Path,
+    file_size: i64,
+    last_modified: &str,
+) -> StorageResult<bool> {
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| InvalidPath(path.display().to_string()))?;
+
+    let unchanged: Option<(i64,)> = query_as(
+        "SELECT id FROM tracks WHERE file_path = ? AND file_size = ? AND last_modified = ?",
+    )
+    .bind(path_str)
+    .bind(file_size)
+    .bind(last_modified)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| Database(format!("Check file stamp failed: {e}")))?;
+    Ok(unchanged.is_none())
+}
+
+/// Overwrite the track `id` with freshly scanned metadata.
+///
+/// A missing content hash keeps the stored one.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn update_scanned_track(
+    pool: &SqlitePool,
+    id: i64,
+    track: NewTrack,
+) -> StorageResult<()> {
+    query(
+        "UPDATE tracks SET title = ?, number = ?, disc_number = ?, disc_total = ?, \
+         duration = ?, content_hash = COALESCE(?, content_hash), format = ?, \
+         sample_rate = ?, bit_depth = ?, channels = ?, codec = ?, lossless = ?, bitrate = ?, \
+         album_id = ?, artist_id = ?, file_size = ?, last_modified = ? WHERE id = ?",
+    )
+    .bind(&track.title)
+    .bind(track.track_number)
+    .bind(track.disc_number)
+    .bind(track.disc_total)
+    .bind(track.duration)
+    .bind(&track.audio.content_hash)
+    .bind(&track.audio.format)
+    .bind(track.audio.sample_rate)
+    .bind(track.audio.bit_depth)
+    .bind(track.audio.channels)
+    .bind(&track.audio.codec)
+    .bind(track.audio.lossless)
+    .bind(track.audio.bitrate)
+    .bind(track.audio.album_id)
+    .bind(track.audio.artist_id)
+    .bind(track.audio.file_size)
+    .bind(&track.audio.last_modified)
+    .bind(id)
+    .execute(pool)
+    .await
+    .map_err(|e| Database(format!("Update scanned track failed: {e}")))?;
+    Ok(())
+}
+
+/// Find the tracks with the given content hash.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn find_by_hash(pool: &SqlitePool, hash: &str) -> StorageResult<Vec<Track>> {
+    query_as::<_, Track>("SELECT * FROM tracks WHERE content_hash = ?")
+        .bind(hash)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Database(format!("Find by hash failed: {e}")))
+}
+
+/// Find the tracks matching an artist, album, title and track number.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn find_by_metadata_fingerprint(
+    pool: &SqlitePool,
+    artist: &str,
+    album: &str,
+    title: &str,
+    track: Option<u32>,
+) -> StorageResult<Vec<Track>> {
+    query_as::<_, Track>(
+        "SELECT t.* FROM tracks t JOIN albums a ON t.album_id = a.id JOIN artists ar ON \
+         t.artist_id = ar.id WHERE ar.name = ? AND a.title = ? AND t.title = ? AND (? IS NULL \
+         OR t.number = ?)",
+    )
+    .bind(artist)
+    .bind(album)
+    .bind(title)
+    .bind(track.map(u32::cast_signed))
+    .bind(track.map(u32::cast_signed))
+    .fetch_all(pool)
+    .await
+    .map_err(|e| Database(format!("Find by fingerprint failed: {e}")))
+}
+
+/// Insert the tracks in order and return their IDs.
+///
+/// # Errors
+///
+/// Returns an error on the first insert that fails.
+pub(super) async fn insert_tracks_batch(
+    pool: &SqlitePool,
+    tracks: Vec<NewTrack>,
+) -> StorageResult<Vec<i64>> {
+    let mut ids = Vec::with_capacity(tracks.len());
+    for track in &tracks {
+        ids.push(insert_track(pool, track).await?);
+    }
+    Ok(ids)
+}
+
+/// Find the track stored at each of `paths`.
+///
+/// # Errors
+///
+/// Returns an error if a path is not UTF-8 or a query fails.
+pub(super) async fn find_by_paths_batch(
+    pool: &SqlitePool,
+    paths: &[&Path],
+) -> StorageResult<Vec<Option<Track>>> {
+    let mut results = Vec::with_capacity(paths.len());
+    for path in paths {
+        let path_str = path
+            .to_str()
+            .ok_or_else(|| InvalidPath(path.display().to_string()))?;
+
+        let track = query_as::<_, Track>("SELECT * FROM tracks WHERE file_path = ?")
+            .bind(path_str)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| Database(format!("Find by path failed: {e}")))?;
+        results.push(track);
+    }
+    Ok(results)
+}
+
+/// Find the tracks with each of the content `hashes`.
+///
+/// # Errors
+///
+/// Returns an error if a query fails.
+pub(super) async fn find_by_hashes_batch(
+    pool: &SqlitePool,
+    hashes: &[&str],
+) -> StorageResult<Vec<Vec<Track>>> {
+    let mut results = Vec::with_capacity(hashes.len());
+    for hash in hashes {
+        let tracks = query_as::<_, Track>("SELECT * FROM tracks WHERE content_hash = ?")
+            .bind(hash)
+            .fetch_all(pool)
+            .await
+            .map_err(|e| Database(format!("Find by hash failed: {e}")))?;
+        results.push(tracks);
+    }
+    Ok(results)
+}
+
+/// Get the tracks of several albums, grouped by album in play order.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn get_tracks_by_albums(
+    pool: &SqlitePool,
+    album_ids: &[i64],
+) -> StorageResult<Vec<Track>> {
+    if album_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut builder = QueryBuilder::new("SELECT * FROM tracks WHERE album_id IN (");
+    let mut separated = builder.separated(", ");
+    for id in album_ids {
+        separated.push_bind(id);
+    }
+    builder.push(") ORDER BY album_id, COALESCE(disc_number, 1), number");
+    builder
+        .build_query_as::<Track>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Database(format!("Get tracks by albums failed: {e}")))
+}
+
+/// Get the tracks with the given IDs.
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub(super) async fn get_tracks_by_ids(pool: &SqlitePool, ids: &[i64]) -> StorageResult<Vec<Track>> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut builder = QueryBuilder::new("SELECT * FROM tracks WHERE id IN (");
+    let mut separated = builder.separated(", ");
+    for id in ids {
+        separated.push_bind(id);
+    }
+    builder.push(")");
+    builder
+        .build_query_as::<Track>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Database(format!("Get tracks by ids failed: {e}")))
+}
@@ -0,0 +1,70 @@
+//! Volume remembered for each output device.
+//!
+//! Switching between headphones, speakers and a DAC restores the volume
+//! last used with each of them. The system default output is remembered
+//! under its own key.
+
+use crate::storage::settings::UserSettings;
+
+/// Key under which the volume of the system default output is remembered.
+pub const DEFAULT_DEVICE_KEY: &str = "default";
+
+impl UserSettings {
+    /// Volume remembered for the preferred output device, or the last
+    /// volume when none has been remembered for it yet.
+    #[must_use]
+    pub fn device_volume(&self) -> f64 {
+        self.device_volumes
+            .get(self.device_key())
+            .copied()
+            .unwrap_or(self.volume)
+    }
+
+    /// Set the volume and remember it for the preferred output device.
+    pub fn remember_volume(&mut self, volume: f64) {
+        self.volume = volume;
+        let key = self.device_key().to_string();
+        self.device_volumes.insert(key, volume);
+    }
+
+    /// Prefer `device`, switching to the volume remembered for it.
+    ///
+    /// A device without a remembered volume keeps the current one.
+    pub fn select_audio_device(&mut self, device: Option<String>) {
+        self.audio_device = device;
+        self.volume = self.device_volume();
+    }
+
+    /// Key of the preferred output device in `device_volumes`.
+    fn device_key(&self) -> &str {
+        self.audio_device.as_deref().unwrap_or(DEFAULT_DEVICE_KEY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::settings::UserSettings;
+
+    #[test]
+    fn volume_is_remembered_per_device() {
+        let mut settings = UserSettings::default();
+        settings.remember_volume(0.3);
+        settings.select_audio_device(Some("USB DAC".to_string()));
+        assert!(
+            (settings.volume - 0.3).abs() < f64::EPSILON,
+            "A new device keeps the current volume"
+        );
+        settings.remember_volume(0.9);
+
+        settings.select_audio_device(None);
+        assert!(
+            (settings.volume - 0.3).abs() < f64::EPSILON,
+            "The default device gets its own volume back"
+        );
+        settings.select_audio_device(Some("USB DAC".to_string()));
+        assert!(
+            (settings.device_volume() - 0.9).abs() < f64::EPSILON,
+            "The DAC gets its own volume back"
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! Detection of the same recording stored more than once.
+//!
+//! Tracks are fingerprinted by normalized title and artist. Tracks sharing a
+//! fingerprint whose durations lie within a small tolerance of each other
+//! form a group. Each group lists its copies best quality first.
+
+use std::{cmp::Reverse, mem::take};
+
+use sqlx::{FromRow, SqlitePool, query_as};
+
+use crate::storage::{StorageError::Database, StorageResult, format_sample_rate_str};
+
+/// Largest duration difference, in seconds, between copies of one recording.
+const DURATION_TOLERANCE_SECONDS: f64 = 2.0;
+
+/// A track considered when looking for duplicates.
+#[derive(Debug, Clone, FromRow)]
+pub struct DuplicateCandidate {
+    /// Track identifier.
+    pub id: i64,
+    /// Track title.
+    pub title: String,
+    /// Track artist, or the album artist when the track has none.
+    pub artist: String,
+    /// Duration in seconds.
+    pub duration: f64,
+    /// Absolute path to the audio file.
+    pub file_path: String,
+    /// File format (FLAC, MP3, etc.).
+    pub format: String,
+    /// Whether the format is lossless.
+    pub lossless: bool,
+    /// Bit depth (none for lossy formats).
+    pub bit_depth: Option<i32>,
+    /// Native sample rate in Hz.
+    pub sample_rate: i32,
+    /// Average bitrate in kbps.
+    pub bitrate: Option<i32>,
+    /// File size in bytes.
+    pub file_size: i64,
+}
+
+impl DuplicateCandidate {
+    /// Short quality description, e.g. `FLAC 24-bit/96 kHz`.
+    #[must_use]
+    pub fn quality_label(&self) -> String {
+        let depth = self
+            .bit_depth
+            .map_or_else(String::new, |bits| format!("{bits}-bit/"));
+        format!(
+            "{} {depth}{} kHz",
+            self.format,
+            format_sample_rate_str(self.sample_rate)
+        )
+    }
+
+    /// Ordering key where higher means better quality.
+    ///
+    /// Lossless beats lossy, then higher bit depth, sample rate, bitrate,
+    /// and finally the larger file.
+    fn quality_rank(&self) -> (bool, i32, i32, i32, i64) {
+        (
+            self.lossless,
+            self.bit_depth.unwrap_or(0),
+            self.sample_rate,
+            self.bitrate.unwrap_or(0),
+            self.file_size,
+        )
+    }
+}
+
+/// Copies of one recording, best quality first.
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    /// Copies sorted by descending quality; always at least two.
+    pub copies: Vec<DuplicateCandidate>,
+}
+
+impl DuplicateGroup {
+    /// The copy to keep.
+    #[must_use]
+    pub fn best(&self) -> Option<&DuplicateCandidate> {
+        self.copies.first()
+    }
+
+    /// The copies other than the best one.
+    #[must_use]
+    pub fn extras(&self) -> &[DuplicateCandidate] {
+        self.copies.get(1..).unwrap_or_default()
+    }
+}
+
+/// Find groups of tracks that hold the same recording.
+///
+/// Tracks without a known artist are skipped, as their titles alone are
+/// too unreliable to match on.
+///
+/// # Errors
+///
+/// Returns [`Database`] if the tracks cannot be read.
+pub async fn find_duplicates(pool: &SqlitePool) -> StorageResult<Vec<DuplicateGroup>> {
+    let candidates: Vec<DuplicateCandidate> = query_as(
+        "SELECT t.id, t.title, COALESCE(ar.name, al_ar.name) AS artist, t.duration, \
+         t.file_path, t.format, t.lossless, t.bit_depth, t.sample_rate, t.bitrate, t.file_size \
+         FROM tracks t \
+         LEFT JOIN artists ar ON ar.id = t.artist_id \
+         LEFT JOIN albums al ON al.id = t.album_id \
+         LEFT JOIN artists al_ar ON al_ar.id = al.artist_id \
+         WHERE COALESCE(ar.name, al_ar.name) IS NOT NULL",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| Database(format!("Find duplicates failed: {e}")))?;
+    Ok(group_duplicates(candidates))
+}
+
+/// Group `candidates` by fingerprint and duration.
+///
+/// Tracks sharing one file, such as the tracks of a CUE sheet, are never
+/// duplicates of each other.
+#[must_use]
+pub fn group_duplicates(candidates: Vec<DuplicateCandidate>) -> Vec<DuplicateGroup> {
+    let mut keyed: Vec<(String, DuplicateCandidate)> = candidates
+        .into_iter()
+        .map(|c| (fingerprint(&c), c))
+        .collect();
+    keyed.sort_by(|(ka, a), (kb, b)| ka.cmp(kb).then(a.duration.total_cmp(&b.duration)));
+
+    let mut groups = Vec::new();
+    let mut current_key = String::new();
+    let mut current: Vec<DuplicateCandidate> = Vec::new();
+    for (key, candidate) in keyed {
+        let same_recording = key == current_key
+            && current.first().is_some_and(|first| {
+                candidate.duration - first.duration <= DURATION_TOLERANCE_SECONDS
+            });
+        if !same_recording {
+            push_group(&mut groups, take(&mut current));
+            current_key = key;
+        }
+        if !current.iter().any(|c| c.file_path == candidate.file_path) {
+            current.push(candidate);
+        }
+    }
+    push_group(&mut groups, current);
+    groups
+}
+
+/// Keep `copies` as a group if there is more than one, best first.
+fn push_group(groups: &mut Vec<DuplicateGroup>, mut copies: Vec<DuplicateCandidate>) {
+    if copies.len() < 2 {
+        return;
+    }
+    copies.sort_by_key(|c| Reverse(c.quality_rank()));
+    groups.push(DuplicateGroup { copies });
+}
+
+/// Normalized artist and title of a track.
+fn fingerprint(candidate: &DuplicateCandidate) -> String {
+    format!(
+        "{}\u{1f}{}",
+        normalize(&candidate.artist),
+        normalize(&candidate.title)
+    )
+}
+
+/// Lowercase `text` and reduce it to words of letters and digits.
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::duplicates::{DuplicateCandidate, group_duplicates, normalize};
+
+    fn candidate(id: i64, title: &str, duration: f64, path: &str) -> DuplicateCandidate {
+        DuplicateCandidate {
+            id,
+            title: title.to_string(),
+            artist: "Artist".to_string(),
+            duration,
+            file_path: path.to_string(),
+            format: "MP3".to_string(),
+            lossless: false,
+            bit_depth: None,
+            sample_rate: 44100,
+            bitrate: Some(320),
+            file_size: 1000,
+        }
+    }
+
+    #[test]
+    fn normalize_ignores_case_and_punctuation() {
+        assert_eq!(
+            normalize("  Hey, Jude! "),
+            "hey jude",
+            "Case and punctuation must not matter"
+        );
+    }
+
+    #[test]
+    fn groups_copies_and_ranks_lossless_first() {
+        let mut flac = candidate(2, "hey jude", 431.5, "/b/hey.flac");
+        flac.format = "FLAC".to_string();
+        flac.lossless = true;
+        flac.bit_depth = Some(24);
+        let groups = group_duplicates(vec![
+            candidate(1, "Hey Jude", 430.0, "/a/hey.mp3"),
+            flac,
+            candidate(3, "Hey Jude", 500.0, "/c/hey-live.mp3"),
+            candidate(4, "Let It Be", 243.0, "/a/let.mp3"),
+        ]);
+        assert_eq!(groups.len(), 1, "Only the two studio copies match");
+        let ids: Vec<i64> = groups[0].copies.iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec![2, 1], "Lossless copy must come first");
+        assert_eq!(
+            groups[0].best().map(|c| c.quality_label()).as_deref(),
+            Some("FLAC 24-bit/44.1 kHz"),
+            "Best copy must describe its quality"
+        );
+    }
+
+    #[test]
+    fn tracks_sharing_a_file_are_not_duplicates() {
+        let groups = group_duplicates(vec![
+            candidate(1, "Intro", 60.0, "/a/album.flac"),
+            candidate(2, "Intro", 60.5, "/a/album.flac"),
+        ]);
+        assert!(groups.is_empty(), "CUE tracks of one file must not match");
+    }
+}
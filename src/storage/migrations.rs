@@ -2,7 +2,10 @@
 
 use sqlx::{SqlitePool, query, query_as};
 
-use crate::storage::{StorageError::Database, StorageResult};
+use crate::{
+    library::metadata::normalize_genre,
+    storage::{StorageError::Database, StorageResult, stats::create_revision_tracking},
+};
 
 /// Run all database migrations to create tables.
 ///
@@ -94,6 +97,14 @@ pub async fn run(pool: &SqlitePool) -> StorageResult<()> {
     .map_err(|e| Database(format!("Migration failed: {e}")))?;
 
     add_album_format_columns(pool).await?;
+    add_album_date_added_column(pool).await?;
+    add_track_disc_total_column(pool).await?;
+    add_artist_profile_columns(pool).await?;
+    add_album_original_year_column(pool).await?;
+    add_album_dr_column(pool).await?;
+    add_album_cover_override_column(pool).await?;
+    normalize_album_genres(pool).await?;
+    create_revision_tracking(pool).await?;
     create_indexes(pool).await
 }
 
@@ -105,21 +116,21 @@ pub async fn run(pool: &SqlitePool) -> StorageResult<()> {
 ///
 /// Returns a storage error if any ALTER TABLE or UPDATE fails.
 async fn add_album_format_columns(pool: &SqlitePool) -> StorageResult<()> {
-    if !column_exists(pool, "format").await {
+    if !column_exists(pool, "albums", "format").await {
         query("ALTER TABLE albums ADD COLUMN format TEXT NOT NULL DEFAULT ''")
             .execute(pool)
             .await
             .map_err(|e| Database(format!("Migration failed: {e}")))?;
     }
 
-    if !column_exists(pool, "bit_depth").await {
+    if !column_exists(pool, "albums", "bit_depth").await {
         query("ALTER TABLE albums ADD COLUMN bit_depth INTEGER")
             .execute(pool)
             .await
             .map_err(|e| Database(format!("Migration failed: {e}")))?;
     }
 
-    if !column_exists(pool, "sample_rate").await {
+    if !column_exists(pool, "albums", "sample_rate").await {
         query("ALTER TABLE albums ADD COLUMN sample_rate INTEGER")
             .execute(pool)
             .await
@@ -139,9 +150,158 @@ async fn add_album_format_columns(pool: &SqlitePool) -> StorageResult<()> {
     Ok(())
 }
 
-/// Check if a column exists in the `albums` table.
-async fn column_exists(pool: &SqlitePool, name: &str) -> bool {
-    query_as::<_, (String,)>("SELECT name FROM pragma_table_info('albums') WHERE name = ?1")
+/// Add the `date_added` column to the albums table.
+///
+/// New albums get the insert time. Existing albums are backfilled with the
+/// time their earliest track was inserted, so "Recently Added" is useful
+/// right after upgrading.
+///
+/// # Errors
+///
+/// Returns a storage error if the ALTER TABLE or UPDATE fails.
+async fn add_album_date_added_column(pool: &SqlitePool) -> StorageResult<()> {
+    if !column_exists(pool, "albums", "date_added").await {
+        query("ALTER TABLE albums ADD COLUMN date_added TEXT")
+            .execute(pool)
+            .await
+            .map_err(|e| Database(format!("Migration failed: {e}")))?;
+    }
+
+    query(
+        "UPDATE albums SET date_added = COALESCE((SELECT MIN(created_at) FROM tracks WHERE \
+         tracks.album_id = albums.id), datetime('now')) WHERE date_added IS NULL",
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| Database(format!("Migration backfill failed: {e}")))?;
+
+    Ok(())
+}
+
+/// Add the `disc_total` column to the tracks table.
+///
+/// Existing tracks keep `NULL` until they are rescanned.
+///
+/// # Errors
+///
+/// Returns a storage error if the ALTER TABLE fails.
+async fn add_track_disc_total_column(pool: &SqlitePool) -> StorageResult<()> {
+    if !column_exists(pool, "tracks", "disc_total").await {
+        query("ALTER TABLE tracks ADD COLUMN disc_total INTEGER")
+            .execute(pool)
+            .await
+            .map_err(|e| Database(format!("Migration failed: {e}")))?;
+    }
+    Ok(())
+}
+
+/// Add the `image_path` and `bio` columns to the artists table.
+///
+/// Both stay `NULL` until the user sets an image or writes a biography.
+///
+/// # Errors
+///
+/// Returns a storage error if an ALTER TABLE fails.
+async fn add_artist_profile_columns(pool: &SqlitePool) -> StorageResult<()> {
+    if !column_exists(pool, "artists", "image_path").await {
+        query("ALTER TABLE artists ADD COLUMN image_path TEXT")
+            .execute(pool)
+            .await
+            .map_err(|e| Database(format!("Migration failed: {e}")))?;
+    }
+    if !column_exists(pool, "artists", "bio").await {
+        query("ALTER TABLE artists ADD COLUMN bio TEXT")
+            .execute(pool)
+            .await
+            .map_err(|e| Database(format!("Migration failed: {e}")))?;
+    }
+    Ok(())
+}
+
+/// Add the original release year to the albums table.
+///
+/// Albums scanned before this column existed keep `NULL` until rescanned.
+///
+/// # Errors
+///
+/// Returns a storage error if the ALTER TABLE fails.
+async fn add_album_original_year_column(pool: &SqlitePool) -> StorageResult<()> {
+    if !column_exists(pool, "albums", "original_year").await {
+        query("ALTER TABLE albums ADD COLUMN original_year INTEGER")
+            .execute(pool)
+            .await
+            .map_err(|e| Database(format!("Migration failed: {e}")))?;
+    }
+    Ok(())
+}
+
+/// Add the dynamic range value to the albums table.
+///
+/// The value stays `NULL` until the album is analyzed.
+///
+/// # Errors
+///
+/// Returns a storage error if the ALTER TABLE fails.
+async fn add_album_dr_column(pool: &SqlitePool) -> StorageResult<()> {
+    if !column_exists(pool, "albums", "dr_value").await {
+        query("ALTER TABLE albums ADD COLUMN dr_value INTEGER")
+            .execute(pool)
+            .await
+            .map_err(|e| Database(format!("Migration failed: {e}")))?;
+    }
+    Ok(())
+}
+
+/// Add the user-chosen cover to the albums table.
+///
+/// Kept apart from `artwork_path` so rescans never replace it and clearing
+/// it brings back the scanned artwork.
+///
+/// # Errors
+///
+/// Returns a storage error if the ALTER TABLE fails.
+async fn add_album_cover_override_column(pool: &SqlitePool) -> StorageResult<()> {
+    if !column_exists(pool, "albums", "cover_override").await {
+        query("ALTER TABLE albums ADD COLUMN cover_override TEXT")
+            .execute(pool)
+            .await
+            .map_err(|e| Database(format!("Migration failed: {e}")))?;
+    }
+    Ok(())
+}
+
+/// Rewrite album genres stored before multi-value tags were split.
+///
+/// Only rows whose genre differs from its normalized form are updated, so
+/// this is cheap once the library has been normalized.
+///
+/// # Errors
+///
+/// Returns a storage error if the genres cannot be read or updated.
+async fn normalize_album_genres(pool: &SqlitePool) -> StorageResult<()> {
+    let rows: Vec<(i64, String)> = query_as("SELECT id, genre FROM albums WHERE genre IS NOT NULL")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Database(format!("Migration failed: {e}")))?;
+    for (id, genre) in rows {
+        let normalized = normalize_genre(&genre);
+        if normalized.as_deref() == Some(genre.as_str()) {
+            continue;
+        }
+        query("UPDATE albums SET genre = ? WHERE id = ?")
+            .bind(normalized)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map_err(|e| Database(format!("Migration backfill failed: {e}")))?;
+    }
+    Ok(())
+}
+
+/// Check if a column exists in `table`.
+async fn column_exists(pool: &SqlitePool, table: &str, name: &str) -> bool {
+    query_as::<_, (String,)>("SELECT name FROM pragma_table_info(?1) WHERE name = ?2")
+        .bind(table)
         .bind(name)
         .fetch_optional(pool)
         .await
@@ -179,6 +339,11 @@ async fn create_indexes(pool: &SqlitePool) -> StorageResult<()> {
         .await
         .map_err(|e| Database(format!("Index creation failed: {e}")))?;
 
+    query("CREATE INDEX IF NOT EXISTS idx_album_date_added ON albums(date_added)")
+        .execute(pool)
+        .await
+        .map_err(|e| Database(format!("Index creation failed: {e}")))?;
+
     query("CREATE INDEX IF NOT EXISTS idx_queue_position ON playback_queue(position)")
         .execute(pool)
         .await
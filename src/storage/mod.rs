@@ -1,14 +1,39 @@
 //! Persistence layer: domain types, storage trait, and error types.
 
+pub mod artist_groups;
+pub mod browse;
+pub mod catalog;
+pub mod click_action;
+pub mod collation;
 pub mod database;
+pub mod device_volume;
+pub mod duplicates;
 pub mod migrations;
+pub mod prune;
+pub mod relocate;
+pub mod session;
 pub mod settings;
-
-use std::{borrow::Cow, collections::HashMap, future::Future, path::Path, result::Result};
+pub mod settings_version;
+pub mod snapshot;
+pub mod sort_order;
+pub mod stats;
+pub mod transfer;
+pub mod zoom;
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    future::Future,
+    path::{Path, PathBuf},
+    result::Result,
+};
 
 use {sqlx::FromRow, thiserror::Error};
 
-use crate::playback::layout::{AudioLayout, format_channel_label};
+use crate::{
+    playback::layout::{AudioLayout, compact_channel_label, format_channel_label},
+    storage::{prune::PruneReport, sort_order::SortOrder},
+};
 
 /// Full album record from the database.
 #[derive(Debug, Clone, FromRow)]
@@ -19,12 +44,16 @@ pub struct Album {
     pub title: String,
     /// Foreign key to artist.
     pub artist_id: i64,
-    /// Release year.
+    /// Release year of this edition.
     pub year: Option<i32>,
+    /// Year the recording was first released, when tagged separately.
+    pub original_year: Option<i32>,
     /// Genre tag.
     pub genre: Option<String>,
-    /// Path to cached album artwork.
+    /// Path to cached album artwork, the user-chosen cover when set.
     pub artwork_path: Option<String>,
+    /// Path to the user-chosen cover, if any.
+    pub cover_override: Option<String>,
     /// Number of tracks.
     pub track_count: i32,
     /// Total duration in seconds.
@@ -39,6 +68,90 @@ pub struct Album {
     pub bit_depth: Option<i32>,
     /// Sample rate in Hz.
     pub sample_rate: Option<i32>,
+    /// When the album was first added to the library.
+    pub date_added: Option<String>,
+    /// Dynamic range in dB, once analyzed.
+    pub dr_value: Option<i32>,
+}
+
+impl Album {
+    /// Year the album is shown and sorted with.
+    ///
+    /// Prefers the original release year when `use_original_year` is set and
+    /// the edition year otherwise, falling back to whichever one is tagged.
+    #[must_use]
+    pub fn display_year(&self, use_original_year: bool) -> Option<i32> {
+        if use_original_year {
+            self.original_year.or(self.year)
+        } else {
+            self.year.or(self.original_year)
+        }
+    }
+}
+
+/// Structured album search: free text plus format, year, resolution, genre
+/// and dynamic range facets.
+///
+/// Every facet left at its default accepts all albums.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AlbumSearch {
+    /// Text matched against album titles, artist names and track titles.
+    pub query: String,
+    /// Accepted formats (e.g. `"FLAC"`); empty accepts every format.
+    pub formats: Vec<String>,
+    /// Earliest release year.
+    pub min_year: Option<i32>,
+    /// Latest release year.
+    pub max_year: Option<i32>,
+    /// Minimum bit depth of at least one track.
+    pub min_bit_depth: Option<i32>,
+    /// Minimum sample rate in Hz of at least one track.
+    pub min_sample_rate: Option<i32>,
+    /// One genre of the album, matched case-insensitively.
+    pub genre: Option<String>,
+    /// Lowest measured DR value; unmeasured albums never match.
+    pub min_dr: Option<i32>,
+    /// Highest measured DR value; unmeasured albums never match.
+    pub max_dr: Option<i32>,
+}
+
+impl AlbumSearch {
+    /// Whether the search matches every album.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.query.trim().is_empty() && !self.has_facets()
+    }
+
+    /// Whether any facet besides the text query is set.
+    #[must_use]
+    pub fn has_facets(&self) -> bool {
+        !self.formats.is_empty()
+            || self.min_year.is_some()
+            || self.max_year.is_some()
+            || self.genre.is_some()
+            || self.min_dr.is_some()
+            || self.max_dr.is_some()
+            || self.has_track_facets()
+    }
+
+    /// Whether a facet is set that is checked against individual tracks.
+    #[must_use]
+    pub fn has_track_facets(&self) -> bool {
+        !self.formats.is_empty() || self.min_bit_depth.is_some() || self.min_sample_rate.is_some()
+    }
+}
+
+/// Partial update fields for an album.
+#[derive(Debug, Clone, Default)]
+pub struct AlbumUpdate {
+    /// New album title.
+    pub title: Option<String>,
+    /// New album artist id.
+    pub artist_id: Option<i64>,
+    /// New release year.
+    pub year: FieldUpdate<i32>,
+    /// New genre tag.
+    pub genre: FieldUpdate<String>,
 }
 
 /// Full artist record from the database.
@@ -50,6 +163,15 @@ pub struct Artist {
     pub name: String,
     /// Number of albums by this artist.
     pub album_count: i32,
+    /// Number of tracks on this artist's albums, where the query counts them.
+    #[sqlx(default)]
+    pub track_count: i32,
+    /// Artist image chosen by the user, copied into the artwork cache.
+    #[sqlx(default)]
+    pub image_path: Option<String>,
+    /// Biography text written by the user.
+    #[sqlx(default)]
+    pub bio: Option<String>,
 }
 
 /// Represents the intent for a nullable database field in an update operation.
@@ -88,9 +210,11 @@ impl FormatInfo {
     ///
     /// Order is always: format(s) → bit-depth(s) → sample-rate(s).
     /// Bit depth and sample rate are joined with `/` when both present.
+    /// Channel layouts follow a `·` unless every track is stereo.
     ///
     /// Uniform lossless: `"FLAC 24/96"`
     /// Uniform lossy:    `"MP3 44.1"`
+    /// Surround:         `"FLAC 24/96 · 5.1"`
     /// Mixed:           `"FLAC, MP3 16, 24/44.1, 96"`
     #[must_use]
     pub fn summary(&self) -> String {
@@ -118,7 +242,16 @@ impl FormatInfo {
             (true, false) => parts.push(bd),
             (false, false) => {}
         }
-        parts.join(" ")
+        let summary = parts.join(" ");
+        if self.channels.iter().all(|&c| c == 2) {
+            return summary;
+        }
+        let layouts: Vec<Cow<'static, str>> = self
+            .channels
+            .iter()
+            .map(|&c| compact_channel_label(AudioLayout::from_count(u32::try_from(c).unwrap_or(0))))
+            .collect();
+        format!("{summary} \u{b7} {}", layouts.join(", "))
     }
 
     /// Full summary for **detail pages** (with units and channels, matches side panel).
@@ -213,8 +346,10 @@ pub struct NewAlbum {
     pub title: String,
     /// Foreign key to artist.
     pub artist_id: i64,
-    /// Release year.
+    /// Release year of this edition.
     pub year: Option<i32>,
+    /// Year the recording was first released, when tagged separately.
+    pub original_year: Option<i32>,
     /// Genre tag.
     pub genre: Option<String>,
     /// Path to cached album artwork.
@@ -260,6 +395,8 @@ pub struct NewTrack {
     pub track_number: Option<i32>,
     /// Disc number.
     pub disc_number: Option<i32>,
+    /// Number of discs in the release.
+    pub disc_total: Option<i32>,
     /// Duration in seconds.
     pub duration: f64,
     /// Audio file metadata.
@@ -330,12 +467,30 @@ pub trait Storage: Send + Sync + 'static {
     /// Insert a new album, returning its id.
     fn insert_album(&self, album: NewAlbum) -> impl Future<Output = StorageResult<i64>> + Send;
 
+    /// Update an existing album.
+    fn update_album(
+        &self,
+        id: i64,
+        album: AlbumUpdate,
+    ) -> impl Future<Output = StorageResult<()>> + Send;
+
     /// Get an album by id.
     fn get_album(&self, id: i64) -> impl Future<Output = StorageResult<Option<Album>>> + Send;
 
     /// Get all albums.
     fn get_all_albums(&self) -> impl Future<Output = StorageResult<Vec<Album>>> + Send;
 
+    /// Get the albums matching a text query and facets in the given order.
+    ///
+    /// Facets are evaluated in SQL; format and resolution facets match
+    /// albums with at least one track satisfying all of them. An empty
+    /// search returns every album.
+    fn search_albums(
+        &self,
+        search: &AlbumSearch,
+        sort: SortOrder,
+    ) -> impl Future<Output = StorageResult<Vec<Album>>> + Send;
+
     /// Get distinct format info for a single album.
     fn get_album_format_info(
         &self,
@@ -409,6 +564,36 @@ pub trait Storage: Send + Sync + 'static {
         path: &Path,
     ) -> impl Future<Output = StorageResult<Option<Track>>> + Send;
 
+    /// Whether the file at `path` is new or changed since it was scanned.
+    ///
+    /// A file is unchanged when a track with its path was stored with the
+    /// same size and modification time; anything else needs re-reading.
+    fn needs_update(
+        &self,
+        path: &Path,
+        file_size: i64,
+        last_modified: &str,
+    ) -> impl Future<Output = StorageResult<bool>> + Send;
+
+    /// Replace the scanned tags and audio properties of a track whose file
+    /// changed, keeping its id so play history and playlists stay attached.
+    ///
+    /// A missing content hash keeps the stored one.
+    fn update_scanned_track(
+        &self,
+        id: i64,
+        track: NewTrack,
+    ) -> impl Future<Output = StorageResult<()>> + Send;
+
+    /// Remove the tracks under `roots` whose files no longer exist, along
+    /// with the albums and artists left without tracks.
+    ///
+    /// Tracks outside `roots` are kept even when their files are missing.
+    fn prune_missing_under(
+        &self,
+        roots: &[PathBuf],
+    ) -> impl Future<Output = StorageResult<PruneReport>> + Send;
+
     /// Find tracks by content hash.
     fn find_by_hash(&self, hash: &str) -> impl Future<Output = StorageResult<Vec<Track>>> + Send;
 
@@ -486,6 +671,8 @@ pub struct Track {
     pub number: Option<i32>,
     /// Disc number.
     pub disc_number: Option<i32>,
+    /// Number of discs in the release.
+    pub disc_total: Option<i32>,
     /// Duration in seconds.
     pub duration: f64,
     /// Audio file metadata.
@@ -495,6 +682,14 @@ pub struct Track {
     pub created_at: String,
 }
 
+impl Track {
+    /// Disc the track belongs to; untagged tracks count as disc 1.
+    #[must_use]
+    pub fn disc(&self) -> i32 {
+        self.disc_number.unwrap_or(1)
+    }
+}
+
 /// Audio metadata shared between insert and retrieval.
 #[derive(Debug, Clone, FromRow)]
 pub struct TrackAudio {
@@ -522,7 +717,7 @@ pub struct TrackAudio {
     pub artist_id: Option<i64>,
     /// File size in bytes.
     pub file_size: i64,
-    /// Filesystem mtime at scan time.
+    /// Filesystem mtime of the file when it was last read, as RFC 3339.
     pub last_modified: String,
 }
 
@@ -545,6 +740,17 @@ pub struct TrackUpdate {
     pub artist_id: FieldUpdate<i64>,
 }
 
+/// SQL expression over the `albums` table aliased `al` matching
+/// [`Album::display_year`].
+#[must_use]
+pub const fn album_year_sql(use_original_year: bool) -> &'static str {
+    if use_original_year {
+        "COALESCE(al.original_year, al.year)"
+    } else {
+        "COALESCE(al.year, al.original_year)"
+    }
+}
+
 /// Format a channel count to a human-readable label.
 #[must_use]
 fn fmt_channel(c: i32) -> Cow<'static, str> {
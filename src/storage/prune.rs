@@ -0,0 +1,189 @@
+//! Removal of tracks whose audio files no longer exist on disk.
+//!
+//! Files deleted or moved outside the application are not always noticed by
+//! the watcher, especially on network shares that emit no events. Pruning
+//! checks every track path, deletes the dead tracks, and then deletes the
+//! albums and artists left without tracks. A prune can be limited to tracks
+//! under given roots, so that files on an unreachable share are kept.
+
+use std::path::{Path, PathBuf};
+
+use {
+    sqlx::{QueryBuilder, Sqlite, SqlitePool, Transaction, query, query_as},
+    tokio::task::spawn_blocking,
+    tracing::info,
+};
+
+use crate::{
+    library::cue::split_cue_path,
+    storage::{StorageError::Database, StorageResult},
+};
+
+/// Number of track IDs deleted per statement.
+const PRUNE_BATCH_SIZE: usize = 500;
+
+/// Rows removed by a prune.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    /// Tracks whose files were missing.
+    pub tracks_removed: u64,
+    /// Albums left without tracks.
+    pub albums_removed: u64,
+    /// Artists left without tracks or albums.
+    pub artists_removed: u64,
+}
+
+/// Find the tracks whose audio file no longer exists.
+///
+/// The existence checks run on a blocking thread so that slow network
+/// shares do not stall the async runtime. Tracks split from a CUE sheet
+/// are checked against the sheet's audio file.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool
+/// * `roots` - Only check tracks under these directories, or all with `None`
+///
+/// # Errors
+///
+/// Returns [`Database`] if the track paths cannot be read.
+pub async fn find_missing_tracks(
+    pool: &SqlitePool,
+    roots: Option<Vec<PathBuf>>,
+) -> StorageResult<Vec<i64>> {
+    let rows: Vec<(i64, String)> = query_as("SELECT id, file_path FROM tracks")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Database(format!("Read track paths failed: {e}")))?;
+    spawn_blocking(move || missing_ids(rows, roots.as_deref()))
+        .await
+        .map_err(|e| Database(format!("Missing file check failed: {e}")))
+}
+
+/// Delete the tracks in `ids` and the albums and artists left without tracks.
+///
+/// Tracks are deleted in batches inside a single transaction, so a failure
+/// part-way leaves the library unchanged. Queue entries for the deleted
+/// tracks are removed with them.
+///
+/// # Errors
+///
+/// Returns [`Database`] if any statement or the commit fails.
+pub async fn prune_tracks(pool: &SqlitePool, ids: &[i64]) -> StorageResult<PruneReport> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| Database(format!("Begin prune failed: {e}")))?;
+
+    let mut report = PruneReport::default();
+    for batch in ids.chunks(PRUNE_BATCH_SIZE) {
+        report.tracks_removed += delete_track_batch(&mut tx, batch).await?;
+    }
+    report.albums_removed = query(
+        "DELETE FROM albums WHERE NOT EXISTS \
+         (SELECT 1 FROM tracks t WHERE t.album_id = albums.id)",
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| Database(format!("Delete orphaned albums failed: {e}")))?
+    .rows_affected();
+    report.artists_removed = query(
+        "DELETE FROM artists WHERE NOT EXISTS \
+         (SELECT 1 FROM tracks t WHERE t.artist_id = artists.id) \
+         AND NOT EXISTS (SELECT 1 FROM albums al WHERE al.artist_id = artists.id)",
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| Database(format!("Delete orphaned artists failed: {e}")))?
+    .rows_affected();
+
+    tx.commit()
+        .await
+        .map_err(|e| Database(format!("Commit prune failed: {e}")))?;
+    info!(
+        tracks = report.tracks_removed,
+        albums = report.albums_removed,
+        artists = report.artists_removed,
+        "Pruned missing files from library"
+    );
+    Ok(report)
+}
+
+/// IDs of the rows under `roots` whose audio file does not exist.
+fn missing_ids(rows: Vec<(i64, String)>, roots: Option<&[PathBuf]>) -> Vec<i64> {
+    rows.into_iter()
+        .filter(|(_, path)| {
+            let (file, _) = split_cue_path(Path::new(path));
+            roots.is_none_or(|roots| roots.iter().any(|root| file.starts_with(root)))
+                && !file.exists()
+        })
+        .map(|(id, _)| id)
+        .collect()
+}
+
+/// Delete one batch of tracks and their queue entries.
+///
+/// Returns the number of tracks deleted.
+async fn delete_track_batch(tx: &mut Transaction<'_, Sqlite>, ids: &[i64]) -> StorageResult<u64> {
+    let mut queue = QueryBuilder::new("DELETE FROM playback_queue WHERE track_id IN (");
+    push_id_list(&mut queue, ids);
+    queue
+        .build()
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| Database(format!("Delete queue entries failed: {e}")))?;
+
+    let mut tracks = QueryBuilder::new("DELETE FROM tracks WHERE id IN (");
+    push_id_list(&mut tracks, ids);
+    Ok(tracks
+        .build()
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| Database(format!("Delete tracks failed: {e}")))?
+        .rows_affected())
+}
+
+/// Append `ids` as a bound, comma-separated list and close the parenthesis.
+pub fn push_id_list(builder: &mut QueryBuilder<Sqlite>, ids: &[i64]) {
+    let mut separated = builder.separated(", ");
+    for id in ids {
+        separated.push_bind(*id);
+    }
+    builder.push(")");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::write, path::PathBuf};
+
+    use {
+        anyhow::{Result, ensure},
+        tempfile::tempdir,
+    };
+
+    use crate::storage::prune::missing_ids;
+
+    #[test]
+    fn missing_ids_checks_cue_files_under_roots() -> Result<()> {
+        let dir = tempdir()?;
+        let image = dir.path().join("image.flac");
+        write(&image, b"")?;
+        let path = |name: &str| dir.path().join(name).to_string_lossy().into_owned();
+        let rows = vec![
+            (1, format!("{}/#t=0,60", path("image.flac"))),
+            (2, path("gone.flac")),
+            (3, "/unmounted/share/song.flac".to_string()),
+        ];
+
+        ensure!(
+            missing_ids(rows.clone(), None) == vec![2, 3],
+            "cue tracks of an existing image are kept"
+        );
+        ensure!(
+            missing_ids(rows, Some(&[PathBuf::from(dir.path())])) == vec![2],
+            "tracks outside the roots are left alone"
+        );
+        drop(dir);
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+//! Relocation of library files after a move to another directory.
+//!
+//! When the music moves to another mount point, every stored path goes
+//! stale and a rescan would import the files as new tracks, dropping their
+//! edited tags, chosen covers, and queue entries. Remapping rewrites the path prefix of the
+//! tracks, covers, artist images, and library directories in place instead,
+//! so every track and album keeps its ID and everything attached to it.
+
+use std::path::Path;
+
+use {
+    sqlx::{Sqlite, SqlitePool, Transaction, query, query_as},
+    tokio::task::spawn_blocking,
+    tracing::info,
+};
+
+use crate::storage::{
+    StorageError::{Database, Duplicate, InvalidPath},
+    StorageResult,
+};
+
+/// Statements moving the album cover and artist image paths.
+const REMAP_ARTWORK: [&str; 3] = [
+    "UPDATE albums SET artwork_path = ?2 || substr(artwork_path, length(?1) + 1) \
+     WHERE artwork_path = ?1 OR substr(artwork_path, 1, length(?1) + 1) = ?1 || '/'",
+    "UPDATE albums SET cover_override = ?2 || substr(cover_override, length(?1) + 1) \
+     WHERE cover_override = ?1 OR substr(cover_override, 1, length(?1) + 1) = ?1 || '/'",
+    "UPDATE artists SET image_path = ?2 || substr(image_path, length(?1) + 1) \
+     WHERE image_path = ?1 OR substr(image_path, 1, length(?1) + 1) = ?1 || '/'",
+];
+
+/// Statement moving the library directory paths.
+const REMAP_DIRECTORIES: &str = "UPDATE library_directories \
+     SET path = ?2 || substr(path, length(?1) + 1) \
+     WHERE path = ?1 OR substr(path, 1, length(?1) + 1) = ?1 || '/'";
+
+/// Statement moving the track file paths.
+const REMAP_TRACKS: &str = "UPDATE tracks SET file_path = ?2 || substr(file_path, length(?1) + 1) \
+     WHERE file_path = ?1 OR substr(file_path, 1, length(?1) + 1) = ?1 || '/'";
+
+/// Rows rewritten by a remap.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RemapReport {
+    /// Tracks whose file path was moved.
+    pub tracks: u64,
+    /// Album covers and artist images whose path was moved.
+    pub artwork: u64,
+    /// Library directories whose path was moved.
+    pub directories: u64,
+}
+
+/// Move every stored path under `old_prefix` to `new_prefix`.
+///
+/// Only whole path components match, so `/mnt/music` does not move
+/// `/mnt/music2`. Everything runs in one transaction: if any track already
+/// exists at its new path, nothing is changed. A library directory that is
+/// already configured at its new path is merged into it.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool
+/// * `old_prefix` - Directory the files were stored under
+/// * `new_prefix` - Existing directory the files now live under
+///
+/// # Errors
+///
+/// Returns [`InvalidPath`] if `new_prefix` is not an existing directory,
+/// [`Duplicate`] if a moved track collides with one already in the library,
+/// and [`Database`] if a statement or the commit fails. A failure leaves
+/// the library unchanged.
+pub async fn remap_directory(
+    pool: &SqlitePool,
+    old_prefix: &Path,
+    new_prefix: &Path,
+) -> StorageResult<RemapReport> {
+    let target = new_prefix.to_path_buf();
+    let exists = spawn_blocking(move || target.is_dir())
+        .await
+        .map_err(|e| Database(format!("Directory check failed: {e}")))?;
+    if !exists {
+        return Err(InvalidPath(format!(
+            "{} is not an existing directory",
+            new_prefix.display()
+        )));
+    }
+    let old = prefix_str(old_prefix)?;
+    let new = prefix_str(new_prefix)?;
+    if old == new {
+        return Ok(RemapReport::default());
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| Database(format!("Begin remap failed: {e}")))?;
+
+    let collision: Option<(String,)> = query_as(
+        "SELECT o.file_path FROM tracks t JOIN tracks o \
+         ON o.file_path = ?2 || substr(t.file_path, length(?1) + 1) \
+         WHERE t.file_path = ?1 OR substr(t.file_path, 1, length(?1) + 1) = ?1 || '/' LIMIT 1",
+    )
+    .bind(old)
+    .bind(new)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| Database(format!("Check remap collisions failed: {e}")))?;
+    if let Some((path,)) = collision {
+        return Err(Duplicate(format!("{path} is already in the library")));
+    }
+
+    let mut report = RemapReport {
+        tracks: remap_paths(&mut tx, REMAP_TRACKS, old, new).await?,
+        ..RemapReport::default()
+    };
+    for statement in REMAP_ARTWORK {
+        report.artwork += remap_paths(&mut tx, statement, old, new).await?;
+    }
+    query(
+        "DELETE FROM library_directories \
+         WHERE (path = ?1 OR substr(path, 1, length(?1) + 1) = ?1 || '/') \
+         AND ?2 || substr(path, length(?1) + 1) IN (SELECT path FROM library_directories)",
+    )
+    .bind(old)
+    .bind(new)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| Database(format!("Merge library directories failed: {e}")))?;
+    report.directories = remap_paths(&mut tx, REMAP_DIRECTORIES, old, new).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| Database(format!("Commit remap failed: {e}")))?;
+    info!(
+        old_prefix = old,
+        new_prefix = new,
+        tracks = report.tracks,
+        artwork = report.artwork,
+        directories = report.directories,
+        "Remapped library directory"
+    );
+    Ok(report)
+}
+
+/// `prefix` as stored in the database, without a trailing separator.
+fn prefix_str(prefix: &Path) -> StorageResult<&str> {
+    prefix
+        .to_str()
+        .map(|s| s.trim_end_matches('/'))
+        .ok_or_else(|| InvalidPath(prefix.display().to_string()))
+}
+
+/// Run the path rewrite `statement` with `old` and `new` bound.
+async fn remap_paths(
+    tx: &mut Transaction<'_, Sqlite>,
+    statement: &'static str,
+    old: &str,
+    new: &str,
+) -> StorageResult<u64> {
+    query(statement)
+        .bind(old)
+        .bind(new)
+        .execute(&mut **tx)
+        .await
+        .map(|done| done.rows_affected())
+        .map_err(|e| Database(format!("Remap paths failed: {e}")))
+}
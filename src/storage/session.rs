@@ -0,0 +1,278 @@
+//! Saved playback queue, restored at the next launch.
+//!
+//! The session file starts with a header line holding a magic word, the
+//! layout version and a SHA-256 checksum of the JSON body that follows:
+//!
+//! ```text
+//! oxhidifi-session 1 <sha256 of body>
+//! {"track_ids":[...],"current_index":2}
+//! ```
+//!
+//! It is written to a temporary file, flushed and renamed over the old
+//! one, so a crash mid-write leaves the previous session in place. A file
+//! that is truncated, garbled or written by a newer version is moved aside
+//! and the application starts with an empty queue instead of failing.
+//!
+//! Restoring can be turned off, in which case the file is deleted on exit
+//! instead of written, so no stale queue reappears once it is turned back
+//! on.
+
+use std::{
+    fs::{File, create_dir_all, read_to_string, remove_file, rename},
+    io::{ErrorKind::NotFound, Write},
+    path::Path,
+};
+
+use {
+    anyhow::{Context, Result, bail, ensure},
+    hex::encode,
+    serde::{Deserialize, Serialize},
+    serde_json::{from_str, to_string},
+    sha2::{Digest, Sha256},
+    tracing::{info, warn},
+};
+
+use crate::playback::queue::PlaybackQueue;
+
+/// First word of every session file.
+const SESSION_MAGIC: &str = "oxhidifi-session";
+
+/// Current session file layout version.
+pub const SESSION_VERSION: u32 = 1;
+
+/// Playback queue saved between launches.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedSession {
+    /// Track IDs in queue order.
+    pub track_ids: Vec<i64>,
+    /// Position of the current track in `track_ids`.
+    pub current_index: Option<usize>,
+}
+
+impl SavedSession {
+    /// Capture the tracks and current position of `queue`.
+    #[must_use]
+    pub fn capture(queue: &PlaybackQueue) -> Self {
+        Self {
+            track_ids: queue.tracks(),
+            current_index: queue.current_position(),
+        }
+    }
+}
+
+/// Write `session` to `path` atomically.
+///
+/// # Errors
+///
+/// Returns an error if the directory, the temporary file or the rename
+/// fails.
+pub fn save_session(path: &Path, session: &SavedSession) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    let mut file =
+        File::create(&tmp).with_context(|| format!("Failed to create {}", tmp.display()))?;
+    file.write_all(encode_session(session)?.as_bytes())
+        .and_then(|()| file.sync_all())
+        .with_context(|| format!("Failed to write {}", tmp.display()))?;
+    rename(&tmp, path).with_context(|| format!("Failed to replace {}", path.display()))
+}
+
+/// Delete the session saved at `path`, if any.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be removed.
+pub fn clear_session(path: &Path) -> Result<()> {
+    match remove_file(path) {
+        Ok(()) => {
+            info!(path = %path.display(), "Cleared saved session");
+            Ok(())
+        }
+        Err(e) if e.kind() == NotFound => Ok(()),
+        Err(e) => Err(e).with_context(|| format!("Failed to remove {}", path.display())),
+    }
+}
+
+/// Read the session saved at `path`.
+///
+/// A corrupt file is moved aside to `<path>.corrupt`, so it is reported
+/// once and can still be inspected.
+///
+/// # Returns
+///
+/// `None` if there is no session or it cannot be read.
+#[must_use]
+pub fn restore_session(path: &Path) -> Option<SavedSession> {
+    let content = match read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == NotFound => return None,
+        Err(e) => {
+            warn!(error = %e, path = %path.display(), "Failed to read saved session");
+            return None;
+        }
+    };
+    match decode_session(&content) {
+        Ok(session) => {
+            info!(tracks = session.track_ids.len(), "Restored saved session");
+            Some(session)
+        }
+        Err(e) => {
+            warn!(error = %e, path = %path.display(), "Discarding corrupt saved session");
+            let aside = path.with_extension("json.corrupt");
+            if let Err(e) = rename(path, &aside) {
+                warn!(error = %e, path = %path.display(), "Failed to move corrupt session aside");
+            }
+            None
+        }
+    }
+}
+
+/// Serialize `session` with its header line.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails.
+fn encode_session(session: &SavedSession) -> Result<String> {
+    let body = to_string(session).context("Failed to serialize session")?;
+    let checksum = encode(Sha256::digest(body.as_bytes()));
+    Ok(format!(
+        "{SESSION_MAGIC} {SESSION_VERSION} {checksum}\n{body}"
+    ))
+}
+
+/// Parse and verify a session file.
+///
+/// # Errors
+///
+/// Returns an error if the header, the checksum or the body is invalid, or
+/// the file was written by a newer version.
+fn decode_session(content: &str) -> Result<SavedSession> {
+    let Some((header, body)) = content.split_once('\n') else {
+        bail!("Session file has no header line");
+    };
+    let mut fields = header.split(' ');
+    let (Some(SESSION_MAGIC), Some(version), Some(checksum), None) =
+        (fields.next(), fields.next(), fields.next(), fields.next())
+    else {
+        bail!("Session file header is malformed");
+    };
+    let version: u32 = version
+        .parse()
+        .with_context(|| format!("Invalid session version: {version}"))?;
+    ensure!(
+        version <= SESSION_VERSION,
+        "Session file version {version} is newer than {SESSION_VERSION}"
+    );
+    ensure!(
+        encode(Sha256::digest(body.as_bytes())) == checksum,
+        "Session file checksum does not match"
+    );
+
+    let session: SavedSession = from_str(body).context("Failed to parse session")?;
+    ensure!(
+        session
+            .current_index
+            .is_none_or(|index| index < session.track_ids.len()),
+        "Current track index is past the end of the queue"
+    );
+    Ok(session)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{read_to_string, write};
+
+    use {
+        anyhow::{Result, ensure},
+        tempfile::tempdir,
+    };
+
+    use crate::storage::session::{
+        SavedSession, clear_session, decode_session, encode_session, restore_session, save_session,
+    };
+
+    fn session() -> SavedSession {
+        SavedSession {
+            track_ids: vec![7, 3, 9],
+            current_index: Some(1),
+        }
+    }
+
+    #[test]
+    fn session_survives_reload() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("session.json");
+        ensure!(restore_session(&path).is_none(), "no session yet");
+
+        save_session(&path, &session())?;
+        save_session(&path, &session())?;
+        ensure!(
+            restore_session(&path) == Some(session()),
+            "the saved queue must be restored"
+        );
+        ensure!(
+            !path.with_extension("json.tmp").exists(),
+            "the temporary file must be renamed"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cleared_session_is_not_restored() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("session.json");
+        clear_session(&path)?;
+
+        save_session(&path, &session())?;
+        clear_session(&path)?;
+        ensure!(!path.exists(), "the session file must be removed");
+        ensure!(restore_session(&path).is_none(), "nothing to restore");
+        Ok(())
+    }
+
+    #[test]
+    fn truncated_and_garbage_files_start_fresh() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("session.json");
+        let full = encode_session(&session())?;
+        let truncated = full.get(..full.len() - 5).unwrap_or_default();
+
+        for content in [truncated, "", "\0\u{1}garbage", "{\"track_ids\":[1]}"] {
+            write(&path, content)?;
+            ensure!(
+                restore_session(&path).is_none(),
+                "{content:?} must be discarded"
+            );
+            ensure!(!path.exists(), "the corrupt file must be moved aside");
+            ensure!(
+                read_to_string(path.with_extension("json.corrupt"))? == content,
+                "the corrupt file is kept for inspection"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn header_is_verified() -> Result<()> {
+        let full = encode_session(&session())?;
+        ensure!(decode_session(&full)? == session(), "round trip");
+
+        let tampered = full.replace("[7,3,9]", "[7,3,8]");
+        ensure!(decode_session(&tampered).is_err(), "checksum mismatch");
+
+        let newer = full.replacen(" 1 ", " 99 ", 1);
+        ensure!(decode_session(&newer).is_err(), "newer layout");
+
+        let past_end = SavedSession {
+            current_index: Some(3),
+            ..session()
+        };
+        ensure!(
+            decode_session(&encode_session(&past_end)?).is_err(),
+            "index past the end"
+        );
+        Ok(())
+    }
+}
@@ -1,20 +1,45 @@
 //! XDG-based user settings persistence using `serde_json`.
 
-use std::path::{Path, PathBuf};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
 
 use {
-    anyhow::{Context, Result},
+    anyhow::{Context, Error, Result},
     serde::{Deserialize, Serialize},
     serde_json::{from_str, to_string_pretty},
     tokio::{
-        fs::{create_dir_all, read_to_string, try_exists},
+        fs::{create_dir_all, read_to_string, try_exists, write},
         task::spawn_blocking,
     },
+    tracing::info,
 };
 
 use crate::{
     app::dirs_config_home,
-    playback::output::OutputMode::{self, Resampled},
+    config::shortcuts::ShortcutSettings,
+    library::{
+        dynamic_range::DrBadgeDisplayPolicy, play_threshold::PlayThreshold,
+        scrobble::ScrobbleSettings, share::DEFAULT_SHARE_TEMPLATE,
+    },
+    playback::{
+        buffer::DEFAULT_BUFFER_FRAMES,
+        equalizer::EqualizerSettings,
+        fade::DEFAULT_FADE_MS,
+        output::OutputMode::{self, Resampled},
+        resampler::ResampleQuality,
+        silence::DEFAULT_SILENCE_THRESHOLD_DB,
+        stereo::DownmixMode,
+    },
+    storage::{
+        click_action::{AlbumClickAction, ListActivation},
+        collation::DEFAULT_SORT_ARTICLES,
+        settings_version::{SETTINGS_VERSION, upgrade_settings},
+        sort_order::{ArtistSortOrder, SortOrder},
+        zoom::ZoomLevel,
+    },
+    threading::scheduler::WorkIntensity,
 };
 
 /// Active tab in the library view.
@@ -24,6 +49,8 @@ pub enum ActiveTab {
     Albums,
     /// Artists tab.
     Artists,
+    /// Genre and decade browse tab.
+    Browse,
 }
 
 /// Manages persistent user settings stored as JSON.
@@ -52,20 +79,50 @@ impl SettingsStore {
         })?;
 
         let settings_path = config_dir.join("settings.json");
-        let settings = if try_exists(&settings_path).await.unwrap_or(false) {
-            let content = read_to_string(&settings_path)
-                .await
-                .with_context(|| format!("Failed to read settings: {}", settings_path.display()))?;
-            from_str(&content)
-                .with_context(|| format!("Failed to parse settings: {}", settings_path.display()))?
-        } else {
-            UserSettings::default()
-        };
+        if !try_exists(&settings_path).await.unwrap_or(false) {
+            return Ok(Self {
+                settings_path,
+                settings: UserSettings::default(),
+            });
+        }
 
-        Ok(Self {
+        let content = read_to_string(&settings_path)
+            .await
+            .with_context(|| format!("Failed to read settings: {}", settings_path.display()))?;
+        let upgraded = from_str(&content)
+            .map_err(Error::from)
+            .and_then(upgrade_settings)
+            .with_context(|| format!("Failed to parse settings: {}", settings_path.display()))?;
+        let needs_save = upgraded.needs_save();
+        let store = Self {
             settings_path,
-            settings,
-        })
+            settings: upgraded.settings,
+        };
+        if needs_save {
+            store.save_upgraded(&content, upgraded.from_version).await?;
+        }
+        Ok(store)
+    }
+
+    /// Back up the original file and write the upgraded settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backup or the upgraded file cannot be written.
+    async fn save_upgraded(&self, original: &str, from_version: u32) -> Result<()> {
+        let backup = self
+            .settings_path
+            .with_extension(format!("v{from_version}.json.bak"));
+        write(&backup, original)
+            .await
+            .with_context(|| format!("Failed to back up settings: {}", backup.display()))?;
+        info!(
+            from_version,
+            to_version = self.settings.version,
+            backup = %backup.display(),
+            "Upgraded settings file"
+        );
+        self.save_async().await
     }
 
     /// Synchronously update in-memory state only (no I/O).
@@ -196,14 +253,56 @@ impl SettingsStore {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct UserSettings {
+    /// Layout version of the settings file.
+    pub version: u32,
     /// Configured library directory paths.
     pub library_directories: Vec<String>,
     /// Preferred audio output device name (None = default).
     pub audio_device: Option<String>,
+    /// Last volume used with each output device, by device name.
+    pub device_volumes: BTreeMap<String, f64>,
     /// Playback volume (0.0–1.0).
     pub volume: f64,
     /// Current view mode preference.
     pub view_mode: ViewMode,
+    /// Album grid zoom level.
+    pub zoom_level: ZoomLevel,
+    /// Order of albums in the library views.
+    pub album_sort: SortOrder,
+    /// Order of artists in the artist views.
+    pub artist_sort: ArtistSortOrder,
+    /// What clicking an album in the library views does.
+    pub album_click_action: AlbumClickAction,
+    /// Whether list view rows open on a single or a double click.
+    pub list_activation: ListActivation,
+    /// Which albums show a DR badge in the album grid.
+    pub dr_badges: DrBadgeDisplayPolicy,
+    /// Show Hi-Res, Lossless, or Lossy badges on album grid covers.
+    pub show_quality_badges: bool,
+    /// Show and sort albums by their original release year instead of
+    /// the edition year.
+    pub use_original_year: bool,
+    /// Sort titles and names without their leading article.
+    pub ignore_sort_articles: bool,
+    /// Leading articles skipped when sorting, such as "The".
+    pub sort_articles: Vec<String>,
+    /// List albums credited to "A feat. B" or "A & B" under each artist.
+    pub split_collaborations: bool,
+    /// Show a waveform overview of the playing track above the seek bar.
+    pub show_waveform: bool,
+    /// Show peak and RMS level meters in the player panel.
+    pub show_level_meter: bool,
+    /// Show the time remaining instead of the track duration next to the
+    /// seek bar.
+    pub show_remaining_time: bool,
+    /// Take album covers from sidecar images such as `cover.jpg` before
+    /// the artwork embedded in the tracks.
+    pub prefer_sidecar_artwork: bool,
+    /// Follow symbolic links to folders and files while scanning.
+    pub follow_symlinks: bool,
+    /// Directory holding the artwork, thumbnail, and waveform caches, in
+    /// place of the XDG cache home.
+    pub cache_directory: Option<String>,
     /// Last active tab.
     pub active_tab: ActiveTab,
     /// Stored window width.
@@ -212,25 +311,134 @@ pub struct UserSettings {
     pub window_height: i32,
     /// Whether window is maximized.
     pub window_maximized: bool,
+    /// Stored mini player width.
+    pub mini_player_width: i32,
+    /// Stored mini player height.
+    pub mini_player_height: i32,
     /// Whether gapless playback is enabled.
     pub gapless_enabled: bool,
     /// Output mode: resampled (software volume) or bit-perfect (hardware volume).
     pub output_mode: OutputMode,
+    /// Switch the device to each track's sample rate when it supports it.
+    pub follow_source_rate: bool,
+    /// Show a toast when the output changes its sample rate between tracks.
+    pub notify_rate_changes: bool,
+    /// Quality/CPU trade-off of sample-rate conversion.
+    pub resample_quality: ResampleQuality,
+    /// Size of the output ring buffer in frames, a power of two.
+    pub output_buffer_frames: u32,
+    /// How the left and right channels reach the device.
+    pub downmix: DownmixMode,
+    /// Left/right balance from `-1.0` (left only) to `1.0` (right only).
+    pub balance: f64,
+    /// Shared concurrency budget for scanning, analysis, and cover decoding.
+    pub work_intensity: WorkIntensity,
+    /// Worker threads of the library scan pool (`0` follows `work_intensity`).
+    pub scan_threads: usize,
+    /// Seconds between rescans of library directories that cannot be watched.
+    pub watch_poll_interval_secs: u64,
+    /// Quiet period after a filesystem change before the change is scanned, in milliseconds.
+    pub watch_debounce_ms: u64,
+    /// Most filesystem changes gathered into one scan batch.
+    pub watch_batch_size: usize,
+    /// Seconds the scanner waits for the metadata of one file before skipping
+    /// it (`0` waits indefinitely).
+    pub metadata_timeout_secs: u64,
+    /// Crossfade window between tracks in milliseconds (`0` disables).
+    pub crossfade_ms: u32,
+    /// Silence between tracks in milliseconds when gapless playback and
+    /// crossfade are off (`0` disables).
+    pub inter_track_gap_ms: u32,
+    /// Skip the near-silence at the start of tracks.
+    pub skip_leading_silence: bool,
+    /// Level in dBFS below which leading audio counts as silence.
+    pub leading_silence_db: i32,
+    /// Fade applied on play, pause, and stop in milliseconds (`0` disables).
+    pub fade_ms: u32,
+    /// Keep a changed playback speed when the next track starts.
+    pub remember_playback_rate: bool,
+    /// Save the playback queue on exit and restore it at the next launch.
+    pub resume_on_startup: bool,
+    /// Graphic equalizer state and selected preset.
+    pub equalizer: EqualizerSettings,
+    /// Opt-in scrobbling service and credentials.
+    pub scrobble: ScrobbleSettings,
+    /// How much of a track has to be heard before it counts as played.
+    pub play_threshold: PlayThreshold,
+    /// Show a desktop notification when a track starts while the window
+    /// is in the background.
+    pub track_notifications: bool,
+    /// Whether the playing track is shown as Discord Rich Presence.
+    pub rich_presence_enabled: bool,
+    /// Application ID of the Discord application publishing the presence.
+    pub discord_client_id: String,
+    /// Keyboard shortcut bindings.
+    pub shortcuts: ShortcutSettings,
+    /// Template of the text copied by "Copy Now Playing".
+    pub share_template: String,
 }
 
 impl Default for UserSettings {
     fn default() -> Self {
         Self {
+            version: SETTINGS_VERSION,
             library_directories: Vec::new(),
             audio_device: None,
+            device_volumes: BTreeMap::new(),
             volume: 0.8,
             view_mode: ViewMode::Grid,
+            zoom_level: ZoomLevel::Medium,
+            album_sort: SortOrder::Title,
+            artist_sort: ArtistSortOrder::Name,
+            album_click_action: AlbumClickAction::Open,
+            list_activation: ListActivation::DoubleClick,
+            dr_badges: DrBadgeDisplayPolicy::default(),
+            show_quality_badges: true,
+            use_original_year: false,
+            ignore_sort_articles: true,
+            sort_articles: DEFAULT_SORT_ARTICLES.map(String::from).to_vec(),
+            split_collaborations: false,
+            show_waveform: true,
+            show_level_meter: false,
+            show_remaining_time: false,
+            prefer_sidecar_artwork: false,
+            follow_symlinks: false,
+            cache_directory: None,
             active_tab: ActiveTab::Albums,
             window_width: 1200,
             window_height: 800,
             window_maximized: false,
+            mini_player_width: 360,
+            mini_player_height: 140,
             gapless_enabled: true,
             output_mode: Resampled,
+            follow_source_rate: false,
+            notify_rate_changes: false,
+            resample_quality: ResampleQuality::High,
+            output_buffer_frames: DEFAULT_BUFFER_FRAMES,
+            downmix: DownmixMode::Stereo,
+            balance: 0.0,
+            work_intensity: WorkIntensity::Balanced,
+            scan_threads: 0,
+            watch_poll_interval_secs: 300,
+            watch_debounce_ms: 500,
+            watch_batch_size: 50,
+            metadata_timeout_secs: 30,
+            crossfade_ms: 0,
+            inter_track_gap_ms: 0,
+            skip_leading_silence: false,
+            leading_silence_db: DEFAULT_SILENCE_THRESHOLD_DB,
+            fade_ms: DEFAULT_FADE_MS,
+            remember_playback_rate: false,
+            resume_on_startup: true,
+            equalizer: EqualizerSettings::default(),
+            scrobble: ScrobbleSettings::default(),
+            play_threshold: PlayThreshold::default(),
+            track_notifications: false,
+            rich_presence_enabled: false,
+            discord_client_id: String::new(),
+            shortcuts: ShortcutSettings::default(),
+            share_template: DEFAULT_SHARE_TEMPLATE.to_string(),
         }
     }
 }
@@ -277,12 +485,22 @@ mod tests {
     };
 
     use crate::{
-        playback::output::OutputMode::Resampled,
-        storage::settings::{
-            ActiveTab::Albums,
-            UserSettings,
-            ViewMode::{Column, Grid},
+        playback::{
+            buffer::DEFAULT_BUFFER_FRAMES, equalizer::EqPreset::Flat, fade::DEFAULT_FADE_MS,
+            output::OutputMode::Resampled, resampler::ResampleQuality,
+            silence::DEFAULT_SILENCE_THRESHOLD_DB, stereo::DownmixMode::Stereo,
+        },
+        storage::{
+            click_action::{AlbumClickAction, ListActivation},
+            settings::{
+                ActiveTab::Albums,
+                UserSettings,
+                ViewMode::{Column, Grid},
+            },
+            sort_order::{ArtistSortOrder, SortOrder},
+            zoom::ZoomLevel::{Large, Medium},
         },
+        threading::scheduler::WorkIntensity::Balanced,
     };
 
     #[test]
@@ -291,10 +509,54 @@ mod tests {
         assert!(settings.library_directories.is_empty());
         assert!((settings.volume - 0.8).abs() < f64::EPSILON);
         assert_eq!(settings.view_mode, Grid);
+        assert_eq!(settings.zoom_level, Medium);
+        assert_eq!(settings.album_sort, SortOrder::Title);
+        assert_eq!(settings.artist_sort, ArtistSortOrder::Name);
+        assert_eq!(settings.album_click_action, AlbumClickAction::Open);
+        assert_eq!(settings.list_activation, ListActivation::DoubleClick);
+        assert!(settings.dr_badges.enabled);
+        assert!(!settings.dr_badges.lossless_only);
+        assert_eq!(settings.dr_badges.below, None);
+        assert!(settings.show_quality_badges);
+        assert!(!settings.use_original_year);
+        assert!(settings.ignore_sort_articles);
+        assert_eq!(settings.sort_articles, ["The", "A", "An"]);
+        assert!(!settings.split_collaborations);
+        assert!(settings.show_waveform);
+        assert!(!settings.show_level_meter);
+        assert!(!settings.show_remaining_time);
+        assert!(!settings.prefer_sidecar_artwork);
+        assert!(!settings.follow_symlinks);
+        assert_eq!(settings.cache_directory, None);
+        assert!(settings.resume_on_startup);
         assert_eq!(settings.active_tab, Albums);
         assert_eq!(settings.window_width, 1200);
         assert!(!settings.window_maximized);
+        assert_eq!(settings.mini_player_width, 360);
         assert_eq!(settings.output_mode, Resampled);
+        assert!(!settings.follow_source_rate);
+        assert!(!settings.notify_rate_changes);
+        assert_eq!(settings.resample_quality, ResampleQuality::High);
+        assert_eq!(settings.output_buffer_frames, DEFAULT_BUFFER_FRAMES);
+        assert_eq!(settings.downmix, Stereo);
+        assert!(settings.balance.abs() < f64::EPSILON);
+        assert_eq!(settings.work_intensity, Balanced);
+        assert_eq!(settings.scan_threads, 0);
+        assert_eq!(settings.watch_debounce_ms, 500);
+        assert_eq!(settings.metadata_timeout_secs, 30);
+        assert_eq!(settings.watch_batch_size, 50);
+        assert_eq!(settings.crossfade_ms, 0);
+        assert_eq!(settings.inter_track_gap_ms, 0);
+        assert!(!settings.skip_leading_silence);
+        assert_eq!(settings.leading_silence_db, DEFAULT_SILENCE_THRESHOLD_DB);
+        assert_eq!(settings.fade_ms, DEFAULT_FADE_MS);
+        assert!(!settings.equalizer.enabled);
+        assert_eq!(settings.equalizer.preset, Flat);
+        assert!(!settings.scrobble.enabled);
+        assert_eq!(settings.play_threshold.percent, 50);
+        assert!(settings.play_threshold.four_minute_cap);
+        assert!(!settings.track_notifications);
+        assert!(!settings.rich_presence_enabled);
     }
 
     #[test]
@@ -306,6 +568,7 @@ mod tests {
             library_directories: vec!["/music".to_string()],
             volume: 0.5,
             view_mode: Column,
+            zoom_level: Large,
             ..UserSettings::default()
         };
 
@@ -327,5 +590,6 @@ mod tests {
         assert_eq!(restored.library_directories, original.library_directories);
         assert!((restored.volume - 0.5).abs() < f64::EPSILON);
         assert_eq!(restored.view_mode, Column);
+        assert_eq!(restored.zoom_level, Large);
     }
 }
@@ -0,0 +1,181 @@
+//! Versioning and upgrade of the settings file layout.
+//!
+//! Settings files carry a `version` key. Files written before versioning
+//! have none and are treated as version 1. On load, each upgrade step from
+//! the file's version to [`SETTINGS_VERSION`] rewrites the raw JSON, e.g.
+//! renaming keys, before it is deserialized. A key whose value no longer
+//! fits its field is dropped so that it alone falls back to its default
+//! instead of the whole file being rejected.
+
+use std::cmp::Ordering::{Equal, Greater, Less};
+
+use {
+    anyhow::{Result, bail},
+    serde_json::{Map, Value, from_value},
+    tracing::warn,
+};
+
+use crate::storage::settings::UserSettings;
+
+/// Current settings layout version.
+pub const SETTINGS_VERSION: u32 = 2;
+
+/// Version of files written before settings were versioned.
+const UNVERSIONED: u32 = 1;
+
+/// Upgrade steps; entry `i` upgrades version `i + 1` to version `i + 2`.
+const UPGRADES: [fn(&mut Map<String, Value>); 1] = [upgrade_v1];
+
+/// Settings read from a file, upgraded to the current layout.
+#[derive(Debug, Clone)]
+pub struct UpgradedSettings {
+    /// Settings in the current layout.
+    pub settings: UserSettings,
+    /// Layout version the file was written with.
+    pub from_version: u32,
+    /// Keys whose values could not be read and were reset to defaults.
+    pub dropped_keys: Vec<String>,
+}
+
+impl UpgradedSettings {
+    /// Whether the file should be rewritten in the current layout.
+    ///
+    /// Files from a newer version are never rewritten, so that switching
+    /// back to the newer application keeps the settings it added.
+    #[must_use]
+    pub fn needs_save(&self) -> bool {
+        match self.from_version.cmp(&SETTINGS_VERSION) {
+            Less => true,
+            Equal => !self.dropped_keys.is_empty(),
+            Greater => false,
+        }
+    }
+}
+
+/// Upgrade raw settings JSON to the current layout and deserialize it.
+///
+/// # Errors
+///
+/// Returns an error if `value` is not a JSON object.
+pub fn upgrade_settings(value: Value) -> Result<UpgradedSettings> {
+    let Value::Object(mut map) = value else {
+        bail!("Settings must be a JSON object");
+    };
+    let from_version = map
+        .get("version")
+        .and_then(Value::as_u64)
+        .map_or(UNVERSIONED, |v| u32::try_from(v).unwrap_or(u32::MAX));
+    for upgrade in UPGRADES
+        .iter()
+        .skip(usize::try_from(from_version.saturating_sub(UNVERSIONED)).unwrap_or(usize::MAX))
+    {
+        upgrade(&mut map);
+    }
+
+    let dropped_keys = drop_invalid_keys(&mut map);
+    for key in &dropped_keys {
+        warn!(key, "Resetting unreadable setting to its default");
+    }
+    let mut settings: UserSettings = from_value(Value::Object(map))?;
+    settings.version = settings.version.max(SETTINGS_VERSION);
+    Ok(UpgradedSettings {
+        settings,
+        from_version,
+        dropped_keys,
+    })
+}
+
+/// Upgrade an unversioned file to version 2, which added the `version` key.
+fn upgrade_v1(map: &mut Map<String, Value>) {
+    map.insert("version".to_string(), Value::from(2));
+}
+
+/// Remove the keys whose values do not deserialize into their field.
+///
+/// Returns the removed keys. Unknown keys are kept; they are ignored when
+/// deserializing.
+fn drop_invalid_keys(map: &mut Map<String, Value>) -> Vec<String> {
+    let invalid: Vec<String> = map
+        .iter()
+        .filter(|(key, value)| {
+            let single = Map::from_iter([((*key).clone(), (*value).clone())]);
+            from_value::<UserSettings>(Value::Object(single)).is_err()
+        })
+        .map(|(key, _)| key.clone())
+        .collect();
+    for key in &invalid {
+        map.remove(key);
+    }
+    invalid
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        anyhow::{Result, ensure},
+        serde_json::{from_str, json},
+    };
+
+    use crate::{
+        playback::output::OutputMode::BitPerfect,
+        storage::{
+            settings::{ActiveTab::Artists, ViewMode::Column},
+            settings_version::{SETTINGS_VERSION, upgrade_settings},
+        },
+    };
+
+    /// Settings file as written before settings were versioned.
+    const V1_FIXTURE: &str = r#"{
+        "library_directories": ["/music"],
+        "audio_device": "USB DAC",
+        "volume": 0.35,
+        "view_mode": "Column",
+        "active_tab": "Artists",
+        "window_width": 1600,
+        "window_height": 900,
+        "window_maximized": true,
+        "gapless_enabled": false,
+        "output_mode": "bit_perfect"
+    }"#;
+
+    #[test]
+    fn v1_fixture_upgrades_without_data_loss() -> Result<()> {
+        let upgraded = upgrade_settings(from_str(V1_FIXTURE)?)?;
+        let s = &upgraded.settings;
+        ensure!(upgraded.from_version == 1, "unversioned file is version 1");
+        ensure!(upgraded.needs_save(), "upgraded file must be written back");
+        ensure!(upgraded.dropped_keys.is_empty(), "no key may be dropped");
+        ensure!(s.version == SETTINGS_VERSION, "version must be current");
+        ensure!(s.library_directories == ["/music"]);
+        ensure!(s.audio_device.as_deref() == Some("USB DAC"));
+        ensure!((s.volume - 0.35).abs() < f64::EPSILON);
+        ensure!(s.view_mode == Column && s.active_tab == Artists);
+        ensure!(s.window_width == 1600 && s.window_height == 900 && s.window_maximized);
+        ensure!(!s.gapless_enabled && s.output_mode == BitPerfect);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_value_resets_only_its_key() -> Result<()> {
+        let upgraded = upgrade_settings(json!({
+            "version": SETTINGS_VERSION,
+            "volume": 0.5,
+            "view_mode": "Carousel"
+        }))?;
+        ensure!(
+            upgraded.dropped_keys == ["view_mode"],
+            "bad key must be dropped"
+        );
+        ensure!((upgraded.settings.volume - 0.5).abs() < f64::EPSILON);
+        ensure!(upgraded.needs_save(), "cleaned file must be written back");
+        Ok(())
+    }
+
+    #[test]
+    fn newer_files_are_not_rewritten() -> Result<()> {
+        let upgraded = upgrade_settings(json!({ "version": SETTINGS_VERSION + 1 }))?;
+        ensure!(!upgraded.needs_save(), "newer layouts must be left alone");
+        ensure!(upgrade_settings(json!([1, 2])).is_err(), "non-objects fail");
+        Ok(())
+    }
+}
@@ -0,0 +1,195 @@
+//! Snapshots of library rows taken before destructive operations.
+//!
+//! A snapshot copies whole rows out of the database as JSON, so that a
+//! prune or a directory removal can be undone by inserting the rows again
+//! with their original IDs. Columns are read from the table schema when the
+//! snapshot is taken, which keeps snapshots in step with columns added by
+//! later migrations.
+
+use {
+    sqlx::{QueryBuilder, Sqlite, SqlitePool, query_as},
+    tracing::info,
+};
+
+use crate::storage::{StorageError::Database, StorageResult, prune::push_id_list};
+
+/// Number of row IDs captured per statement.
+const SNAPSHOT_BATCH_SIZE: usize = 500;
+
+/// Rows copied out of the library before they were deleted.
+#[derive(Debug, Clone, Default)]
+pub struct LibrarySnapshot {
+    /// Captured rows, in the order they are restored.
+    tables: Vec<TableRows>,
+}
+
+impl LibrarySnapshot {
+    /// Number of rows held by the snapshot.
+    #[must_use]
+    pub fn row_count(&self) -> u64 {
+        self.tables.iter().map(|t| t.count).sum()
+    }
+
+    /// Whether the snapshot holds no rows.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.row_count() == 0
+    }
+}
+
+/// Rows of one table captured in a single statement.
+#[derive(Debug, Clone)]
+struct TableRows {
+    /// Table the rows came from.
+    table: &'static str,
+    /// Column names of the table when the rows were captured.
+    columns: Vec<String>,
+    /// JSON array holding one object per row.
+    rows: String,
+    /// Number of rows in `rows`.
+    count: u64,
+}
+
+/// Capture the tracks in `ids` with their albums and artists.
+///
+/// Albums and artists are captured whether or not the deletion that
+/// follows removes them; restoring skips rows that still exist.
+///
+/// # Errors
+///
+/// Returns [`Database`] if the rows cannot be read.
+pub async fn snapshot_tracks(pool: &SqlitePool, ids: &[i64]) -> StorageResult<LibrarySnapshot> {
+    let mut album_ids = Vec::new();
+    let mut artist_ids = Vec::new();
+    for batch in ids.chunks(SNAPSHOT_BATCH_SIZE) {
+        let mut builder = QueryBuilder::new(
+            "SELECT t.album_id, t.artist_id, al.artist_id \
+             FROM tracks t LEFT JOIN albums al ON al.id = t.album_id WHERE t.id IN (",
+        );
+        push_id_list(&mut builder, batch);
+        let rows: Vec<(Option<i64>, Option<i64>, Option<i64>)> = builder
+            .build_query_as()
+            .fetch_all(pool)
+            .await
+            .map_err(|e| Database(format!("Read track owners failed: {e}")))?;
+        for (album_id, artist_id, album_artist_id) in rows {
+            album_ids.extend(album_id);
+            artist_ids.extend(artist_id.into_iter().chain(album_artist_id));
+        }
+    }
+    for list in [&mut album_ids, &mut artist_ids] {
+        list.sort_unstable();
+        list.dedup();
+    }
+
+    let mut snapshot = LibrarySnapshot::default();
+    capture_rows(pool, "artists", &artist_ids, &mut snapshot).await?;
+    capture_rows(pool, "albums", &album_ids, &mut snapshot).await?;
+    capture_rows(pool, "tracks", ids, &mut snapshot).await?;
+    Ok(snapshot)
+}
+
+/// Capture the library directory with the given ID.
+///
+/// # Errors
+///
+/// Returns [`Database`] if the row cannot be read.
+pub async fn snapshot_directory(pool: &SqlitePool, id: i64) -> StorageResult<LibrarySnapshot> {
+    let mut snapshot = LibrarySnapshot::default();
+    capture_rows(pool, "library_directories", &[id], &mut snapshot).await?;
+    Ok(snapshot)
+}
+
+/// Insert the rows of `snapshot` again with their original IDs.
+///
+/// Rows whose ID or unique path is taken again, for example by a rescan
+/// that found a moved file, are skipped. All rows are restored in one
+/// transaction. Queue entries removed with the tracks are not restored.
+///
+/// Returns the number of rows restored.
+///
+/// # Errors
+///
+/// Returns [`Database`] if an insert or the commit fails; the library is
+/// then unchanged.
+pub async fn restore_snapshot(pool: &SqlitePool, snapshot: &LibrarySnapshot) -> StorageResult<u64> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| Database(format!("Begin restore failed: {e}")))?;
+
+    let mut restored = 0;
+    for rows in &snapshot.tables {
+        let columns = rows.columns.join(", ");
+        let values = rows
+            .columns
+            .iter()
+            .map(|c| format!("json_extract(value, '$.{c}')"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(format!(
+            "INSERT OR IGNORE INTO {} ({columns}) SELECT ",
+            rows.table
+        ));
+        builder.push(values).push(" FROM json_each(");
+        builder.push_bind(rows.rows.as_str()).push(")");
+        restored += builder
+            .build()
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| Database(format!("Restore {} failed: {e}", rows.table)))?
+            .rows_affected();
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| Database(format!("Commit restore failed: {e}")))?;
+    info!(rows = restored, "Restored library snapshot");
+    Ok(restored)
+}
+
+/// Append the rows of `table` with IDs in `ids` to `snapshot`.
+async fn capture_rows(
+    pool: &SqlitePool,
+    table: &'static str,
+    ids: &[i64],
+    snapshot: &mut LibrarySnapshot,
+) -> StorageResult<()> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+    let columns: Vec<String> = query_as::<_, (String,)>("SELECT name FROM pragma_table_info(?1)")
+        .bind(table)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Database(format!("Read {table} columns failed: {e}")))?
+        .into_iter()
+        .map(|(name,)| name)
+        .collect();
+    let fields = columns
+        .iter()
+        .map(|c| format!("'{c}', {c}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    for batch in ids.chunks(SNAPSHOT_BATCH_SIZE) {
+        let mut builder = QueryBuilder::new(format!(
+            "SELECT json_group_array(json_object({fields})), COUNT(*) FROM {table} WHERE id IN ("
+        ));
+        push_id_list(&mut builder, batch);
+        let (rows, count): (String, i64) = builder
+            .build_query_as()
+            .fetch_one(pool)
+            .await
+            .map_err(|e| Database(format!("Snapshot {table} failed: {e}")))?;
+        if count > 0 {
+            snapshot.tables.push(TableRows {
+                table,
+                columns: columns.clone(),
+                rows,
+                count: u64::try_from(count).unwrap_or(0),
+            });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,73 @@
+//! Orders of albums and artists in the library views.
+
+use serde::{Deserialize, Serialize};
+
+/// Order of artists in the artist views.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ArtistSortOrder {
+    /// Alphabetical by artist name.
+    #[default]
+    Name,
+    /// Artists with the most albums first.
+    AlbumCount,
+    /// Artists with the most tracks first.
+    TrackCount,
+}
+
+impl ArtistSortOrder {
+    /// Every order, in display order.
+    pub const ALL: [Self; 3] = [Self::Name, Self::AlbumCount, Self::TrackCount];
+
+    /// Human-readable order name.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Name => "Name",
+            Self::AlbumCount => "Most Albums",
+            Self::TrackCount => "Most Tracks",
+        }
+    }
+}
+
+/// Order of albums in the library views.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SortOrder {
+    /// Alphabetical by album title.
+    #[default]
+    Title,
+    /// Alphabetical by album artist, then by year.
+    Artist,
+    /// By release year, oldest first.
+    Year,
+    /// Most recently added albums first.
+    DateAdded,
+    /// By on-disk location of the album's files.
+    FolderPath,
+    /// Highest dynamic range first, unmeasured albums last.
+    DrValue,
+}
+
+impl SortOrder {
+    /// Every order, in display order.
+    pub const ALL: [Self; 6] = [
+        Self::Title,
+        Self::Artist,
+        Self::Year,
+        Self::DateAdded,
+        Self::FolderPath,
+        Self::DrValue,
+    ];
+
+    /// Human-readable order name.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Title => "Title",
+            Self::Artist => "Artist",
+            Self::Year => "Year",
+            Self::DateAdded => "Recently Added",
+            Self::FolderPath => "Folder",
+            Self::DrValue => "Dynamic Range",
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! Library-wide statistics: counts, playtime, size on disk, and formats.
+//!
+//! Aggregating a large library scans every track, so results are cached by
+//! [`SqliteStorage`](crate::storage::database::SqliteStorage) against a
+//! revision counter. Triggers created with the schema bump the counter on
+//! every change to tracks, albums, or artists, which makes checking whether
+//! the cache is still current a single-row lookup.
+
+use sqlx::{FromRow, SqlitePool, query, query_as};
+
+use crate::storage::{StorageError::Database, StorageResult};
+
+/// Triggers bumping the library revision on every library change.
+const REVISION_TRIGGERS: [&str; 9] = [
+    "CREATE TRIGGER IF NOT EXISTS bump_revision_tracks_insert AFTER INSERT ON tracks BEGIN \
+     UPDATE library_revision SET revision = revision + 1 WHERE id = 1; END",
+    "CREATE TRIGGER IF NOT EXISTS bump_revision_tracks_update AFTER UPDATE ON tracks BEGIN \
+     UPDATE library_revision SET revision = revision + 1 WHERE id = 1; END",
+    "CREATE TRIGGER IF NOT EXISTS bump_revision_tracks_delete AFTER DELETE ON tracks BEGIN \
+     UPDATE library_revision SET revision = revision + 1 WHERE id = 1; END",
+    "CREATE TRIGGER IF NOT EXISTS bump_revision_albums_insert AFTER INSERT ON albums BEGIN \
+     UPDATE library_revision SET revision = revision + 1 WHERE id = 1; END",
+    "CREATE TRIGGER IF NOT EXISTS bump_revision_albums_update AFTER UPDATE ON albums BEGIN \
+     UPDATE library_revision SET revision = revision + 1 WHERE id = 1; END",
+    "CREATE TRIGGER IF NOT EXISTS bump_revision_albums_delete AFTER DELETE ON albums BEGIN \
+     UPDATE library_revision SET revision = revision + 1 WHERE id = 1; END",
+    "CREATE TRIGGER IF NOT EXISTS bump_revision_artists_insert AFTER INSERT ON artists BEGIN \
+     UPDATE library_revision SET revision = revision + 1 WHERE id = 1; END",
+    "CREATE TRIGGER IF NOT EXISTS bump_revision_artists_update AFTER UPDATE ON artists BEGIN \
+     UPDATE library_revision SET revision = revision + 1 WHERE id = 1; END",
+    "CREATE TRIGGER IF NOT EXISTS bump_revision_artists_delete AFTER DELETE ON artists BEGIN \
+     UPDATE library_revision SET revision = revision + 1 WHERE id = 1; END",
+];
+
+/// Tracks sharing one audio format.
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct FormatStats {
+    /// Format name, e.g. `"FLAC"`.
+    pub format: String,
+    /// Number of tracks in the format.
+    pub track_count: i64,
+    /// Total duration of those tracks in seconds.
+    pub total_duration: f64,
+    /// Total size of those tracks in bytes.
+    pub total_size: i64,
+}
+
+/// Aggregated statistics over the whole library.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LibraryStats {
+    /// Number of albums.
+    pub album_count: i64,
+    /// Number of artists.
+    pub artist_count: i64,
+    /// Number of tracks.
+    pub track_count: i64,
+    /// Number of lossless tracks.
+    pub lossless_count: i64,
+    /// Total duration of all tracks in seconds.
+    pub total_duration: f64,
+    /// Total size of all tracks in bytes.
+    pub total_size: i64,
+    /// Tracks per format, most common first.
+    pub formats: Vec<FormatStats>,
+}
+
+/// Track totals read in one pass over the tracks table.
+#[derive(FromRow)]
+struct TrackTotals {
+    /// Number of tracks.
+    track_count: i64,
+    /// Number of lossless tracks.
+    lossless_count: i64,
+    /// Total duration in seconds.
+    total_duration: f64,
+    /// Total size in bytes.
+    total_size: i64,
+}
+
+/// Current library revision.
+///
+/// Changes whenever a track, album, or artist is inserted, updated, or
+/// deleted.
+///
+/// # Errors
+///
+/// Returns [`Database`] if the revision cannot be read.
+pub async fn library_revision(pool: &SqlitePool) -> StorageResult<i64> {
+    let (revision,): (i64,) = query_as("SELECT revision FROM library_revision WHERE id = 1")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| Database(format!("Read library revision failed: {e}")))?;
+    Ok(revision)
+}
+
+/// Aggregate statistics over the whole library.
+///
+/// # Errors
+///
+/// Returns [`Database`] if any aggregate query fails.
+pub async fn compute_stats(pool: &SqlitePool) -> StorageResult<LibraryStats> {
+    let totals: TrackTotals = query_as(
+        "SELECT COUNT(*) AS track_count, COALESCE(SUM(lossless), 0) AS lossless_count, \
+         COALESCE(SUM(duration), 0.0) AS total_duration, COALESCE(SUM(file_size), 0) AS \
+         total_size FROM tracks",
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| Database(format!("Track totals failed: {e}")))?;
+    let (album_count, artist_count): (i64, i64) =
+        query_as("SELECT (SELECT COUNT(*) FROM albums), (SELECT COUNT(*) FROM artists)")
+            .fetch_one(pool)
+            .await
+            .map_err(|e| Database(format!("Album and artist counts failed: {e}")))?;
+    let formats = query_as(
+        "SELECT format, COUNT(*) AS track_count, SUM(duration) AS total_duration, \
+         SUM(file_size) AS total_size FROM tracks GROUP BY format \
+         ORDER BY track_count DESC, format",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| Database(format!("Format breakdown failed: {e}")))?;
+
+    Ok(LibraryStats {
+        album_count,
+        artist_count,
+        track_count: totals.track_count,
+        lossless_count: totals.lossless_count,
+        total_duration: totals.total_duration,
+        total_size: totals.total_size,
+        formats,
+    })
+}
+
+/// Create the library revision counter and the triggers that bump it.
+///
+/// The counter lets cached library statistics detect changes without
+/// re-aggregating every track.
+///
+/// # Errors
+///
+/// Returns a storage error if the table or a trigger cannot be created.
+pub async fn create_revision_tracking(pool: &SqlitePool) -> StorageResult<()> {
+    query(
+        "CREATE TABLE IF NOT EXISTS library_revision (
+            id INTEGER PRIMARY KEY CHECK(id = 1),
+            revision INTEGER NOT NULL DEFAULT 0
+        )",
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| Database(format!("Migration failed: {e}")))?;
+
+    query("INSERT OR IGNORE INTO library_revision (id, revision) VALUES (1, 0)")
+        .execute(pool)
+        .await
+        .map_err(|e| Database(format!("Migration failed: {e}")))?;
+
+    for trigger in REVISION_TRIGGERS {
+        query(trigger)
+            .execute(pool)
+            .await
+            .map_err(|e| Database(format!("Migration failed: {e}")))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,302 @@
+//! Portable settings files for copying a configuration between machines.
+//!
+//! An export holds the full [`UserSettings`] together with the library
+//! directories, which live in the database, under a format version.
+//! Scrobbling credentials are left out, as settings files get shared.
+//! Importing replaces the current settings except for the window geometry,
+//! which belongs to the local screen, and the scrobbling credentials, which
+//! belong to the local user. Library directories that do not exist
+//! on this machine are reported instead of failing the import, since paths
+//! often differ between machines.
+
+use std::{
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+
+use {
+    serde::{Deserialize, Serialize},
+    serde_json::{Value, from_str, to_string_pretty},
+};
+
+use crate::storage::{
+    StorageError::{InvalidPath, Serialization},
+    StorageResult,
+    settings::UserSettings,
+    settings_version::upgrade_settings,
+};
+
+/// Format version written to exported settings files.
+pub const SETTINGS_EXPORT_VERSION: u32 = 1;
+
+/// Outcome of a settings import.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Library directories added to this machine's library.
+    pub directories_added: Vec<PathBuf>,
+    /// Library directories of the file that do not exist on this machine.
+    pub missing_directories: Vec<String>,
+}
+
+/// Exported settings file before its settings are upgraded.
+#[derive(Debug, Deserialize)]
+struct RawSettingsExport {
+    /// Format version of the file.
+    version: u32,
+    /// Settings in the layout they were exported with.
+    settings: Value,
+}
+
+/// Contents of an exported settings file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsExport {
+    /// Format version of the file.
+    pub version: u32,
+    /// Exported settings, including the library directories.
+    pub settings: UserSettings,
+}
+
+/// Serialize `settings` with the library `directories` for export.
+///
+/// Scrobbling credentials are cleared in the exported copy.
+///
+/// # Errors
+///
+/// Returns [`Serialization`] if the settings cannot be serialized.
+pub fn export_json(settings: &UserSettings, directories: Vec<String>) -> StorageResult<String> {
+    let export = SettingsExport {
+        version: SETTINGS_EXPORT_VERSION,
+        settings: UserSettings {
+            library_directories: directories,
+            scrobble: settings.scrobble.without_credentials(),
+            ..settings.clone()
+        },
+    };
+    to_string_pretty(&export).map_err(|e| Serialization(format!("Export settings failed: {e}")))
+}
+
+/// Parse an exported settings file.
+///
+/// The settings are upgraded from the layout they were exported with, and
+/// fields missing from older files take their defaults.
+///
+/// # Errors
+///
+/// Returns [`Serialization`] if the file is not a settings export or was
+/// written by a newer version of the application.
+pub fn parse_export(json: &str) -> StorageResult<UserSettings> {
+    let export: RawSettingsExport =
+        from_str(json).map_err(|e| Serialization(format!("Invalid settings file: {e}")))?;
+    if export.version > SETTINGS_EXPORT_VERSION {
+        return Err(Serialization(format!(
+            "Settings file version {} is newer than the supported version {}",
+            export.version, SETTINGS_EXPORT_VERSION
+        )));
+    }
+    upgrade_settings(export.settings)
+        .map(|upgraded| upgraded.settings)
+        .map_err(|e| Serialization(format!("Invalid settings file: {e}")))
+}
+
+/// Read and parse the exported settings file at `path`.
+///
+/// # Errors
+///
+/// Returns [`InvalidPath`] if the file cannot be read, or [`Serialization`]
+/// if it is not a supported settings export.
+pub fn read_export(path: &Path) -> StorageResult<UserSettings> {
+    let json = read_to_string(path)
+        .map_err(|e| InvalidPath(format!("Failed to read {}: {e}", path.display())))?;
+    parse_export(&json)
+}
+
+/// Settings after importing `imported` over `current`.
+///
+/// The window geometry of `current`, including the mini player's, its
+/// scrobbling credentials, and the settings tied to this machine (cache
+/// folder, output device and per-device volumes) are kept.
+#[must_use]
+pub fn merge_imported(current: &UserSettings, imported: UserSettings) -> UserSettings {
+    UserSettings {
+        scrobble: imported.scrobble.with_credentials_of(&current.scrobble),
+        window_width: current.window_width,
+        window_height: current.window_height,
+        window_maximized: current.window_maximized,
+        mini_player_width: current.mini_player_width,
+        mini_player_height: current.mini_player_height,
+        cache_directory: current.cache_directory.clone(),
+        audio_device: current.audio_device.clone(),
+        device_volumes: current.device_volumes.clone(),
+        ..imported
+    }
+}
+
+/// Split `directories` into those that exist on this machine and those that do not.
+#[must_use]
+pub fn partition_directories(directories: &[String]) -> (Vec<PathBuf>, Vec<String>) {
+    let mut existing = Vec::new();
+    let mut missing = Vec::new();
+    for dir in directories {
+        let path = Path::new(dir);
+        if path.is_dir() {
+            existing.push(path.to_path_buf());
+        } else {
+            missing.push(dir.clone());
+        }
+    }
+    (existing, missing)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use {
+        anyhow::{Result, ensure},
+        tempfile::tempdir,
+    };
+
+    use crate::{
+        library::scrobble::ScrobbleSettings,
+        storage::{
+            settings::UserSettings,
+            sort_order::SortOrder::Year,
+            transfer::{
+                SETTINGS_EXPORT_VERSION, export_json, merge_imported, parse_export,
+                partition_directories,
+            },
+        },
+    };
+
+    #[test]
+    fn export_round_trips_with_directories() -> Result<()> {
+        let settings = UserSettings {
+            album_sort: Year,
+            crossfade_ms: 3000,
+            ..UserSettings::default()
+        };
+        let json = export_json(&settings, vec!["/music".to_string()])?;
+        ensure!(json.contains(&format!("\"version\": {SETTINGS_EXPORT_VERSION}")));
+
+        let imported = parse_export(&json)?;
+        ensure!(imported.album_sort == Year, "sort order must survive");
+        ensure!(imported.crossfade_ms == 3000, "audio settings must survive");
+        ensure!(imported.library_directories == ["/music"]);
+        Ok(())
+    }
+
+    #[test]
+    fn newer_versions_and_garbage_are_rejected() {
+        assert!(
+            parse_export(r#"{"version": 99, "settings": {}}"#).is_err(),
+            "Files from newer versions must be rejected"
+        );
+        assert!(
+            parse_export("not json").is_err(),
+            "Garbage must be rejected"
+        );
+        assert!(
+            parse_export(r#"{"version": 1, "settings": {}}"#).is_ok(),
+            "Missing fields take their defaults"
+        );
+    }
+
+    #[test]
+    fn import_keeps_local_window_geometry() {
+        let current = UserSettings {
+            window_width: 1920,
+            ..UserSettings::default()
+        };
+        let imported = UserSettings {
+            window_width: 800,
+            volume: 0.3,
+            ..UserSettings::default()
+        };
+        let merged = merge_imported(&current, imported);
+        assert_eq!(merged.window_width, 1920, "Window size stays local");
+        assert!(
+            (merged.volume - 0.3).abs() < f64::EPSILON,
+            "Volume is imported"
+        );
+    }
+
+    #[test]
+    fn import_keeps_local_cache_folder_and_devices() {
+        let current = UserSettings {
+            cache_directory: Some("/home/me/.cache/oxhidifi".to_string()),
+            audio_device: Some("hw:DAC".to_string()),
+            device_volumes: BTreeMap::from([("hw:DAC".to_string(), 0.8)]),
+            ..UserSettings::default()
+        };
+        let imported = UserSettings {
+            cache_directory: Some("/Users/other/Library/Caches".to_string()),
+            audio_device: Some("hw:Other".to_string()),
+            device_volumes: BTreeMap::from([("hw:Other".to_string(), 0.2)]),
+            ..UserSettings::default()
+        };
+        let merged = merge_imported(&current, imported);
+        assert_eq!(
+            merged.cache_directory, current.cache_directory,
+            "Cache folder stays local"
+        );
+        assert_eq!(
+            merged.audio_device, current.audio_device,
+            "Output device stays local"
+        );
+        assert_eq!(
+            merged.device_volumes, current.device_volumes,
+            "Device volumes stay local"
+        );
+    }
+
+    #[test]
+    fn credentials_are_neither_exported_nor_imported() -> Result<()> {
+        let settings = UserSettings {
+            scrobble: ScrobbleSettings {
+                enabled: true,
+                listenbrainz_token: "exported-token".to_string(),
+                lastfm_session_key: "exported-session".to_string(),
+                ..ScrobbleSettings::default()
+            },
+            ..UserSettings::default()
+        };
+        let json = export_json(&settings, Vec::new())?;
+        ensure!(!json.contains("exported-token"), "the token stays local");
+        ensure!(
+            !json.contains("exported-session"),
+            "the session key stays local"
+        );
+
+        let current = UserSettings {
+            scrobble: ScrobbleSettings {
+                listenbrainz_token: "local-token".to_string(),
+                ..ScrobbleSettings::default()
+            },
+            ..UserSettings::default()
+        };
+        let merged = merge_imported(&current, parse_export(&json)?);
+        ensure!(
+            merged.scrobble.enabled,
+            "scrobbling preferences are imported"
+        );
+        ensure!(
+            merged.scrobble.listenbrainz_token == "local-token",
+            "local credentials are kept"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn missing_directories_are_reported() -> Result<()> {
+        let dir = tempdir()?;
+        let present = dir.path().to_string_lossy().to_string();
+        let (existing, missing) =
+            partition_directories(&[present, "/no/such/oxhidifi/dir".to_string()]);
+        ensure!(existing == [dir.path()], "existing directory must be kept");
+        ensure!(
+            missing == ["/no/such/oxhidifi/dir"],
+            "missing one must be reported"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+//! Zoom levels of the album grid.
+
+use serde::{Deserialize, Serialize};
+
+/// Album grid zoom level, controlling the cover size of each tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ZoomLevel {
+    /// Smallest covers, most albums per row.
+    Small,
+    /// Default cover size.
+    #[default]
+    Medium,
+    /// Larger covers.
+    Large,
+    /// Largest covers, fewest albums per row.
+    ExtraLarge,
+}
+
+impl ZoomLevel {
+    /// All levels, from the smallest covers to the largest.
+    pub const ALL: [Self; 4] = [Self::Small, Self::Medium, Self::Large, Self::ExtraLarge];
+
+    /// Human-readable name for the preferences list.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Small => "Small",
+            Self::Medium => "Medium",
+            Self::Large => "Large",
+            Self::ExtraLarge => "Extra Large",
+        }
+    }
+
+    /// Cover edge length in pixels at this zoom level.
+    #[must_use]
+    pub const fn cover_size(self) -> i32 {
+        match self {
+            Self::Small => 140,
+            Self::Medium => 180,
+            Self::Large => 230,
+            Self::ExtraLarge => 290,
+        }
+    }
+
+    /// Next larger level, saturating at [`ZoomLevel::ExtraLarge`].
+    #[must_use]
+    pub const fn zoom_in(self) -> Self {
+        match self {
+            Self::Small => Self::Medium,
+            Self::Medium => Self::Large,
+            Self::Large | Self::ExtraLarge => Self::ExtraLarge,
+        }
+    }
+
+    /// Next smaller level, saturating at [`ZoomLevel::Small`].
+    #[must_use]
+    pub const fn zoom_out(self) -> Self {
+        match self {
+            Self::Small | Self::Medium => Self::Small,
+            Self::Large => Self::Medium,
+            Self::ExtraLarge => Self::Large,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::zoom::ZoomLevel::{self, ExtraLarge, Large, Medium, Small};
+
+    #[test]
+    fn zoom_steps_saturate() {
+        assert_eq!(Medium.zoom_in(), Large, "Zooming in grows covers");
+        assert_eq!(ExtraLarge.zoom_in(), ExtraLarge, "Largest level is kept");
+        assert_eq!(Medium.zoom_out(), Small, "Zooming out shrinks covers");
+        assert_eq!(Small.zoom_out(), Small, "Smallest level is kept");
+        assert!(
+            Small.cover_size() < Medium.cover_size()
+                && Large.cover_size() < ExtraLarge.cover_size(),
+            "Cover size grows with the zoom level"
+        );
+        assert!(
+            ZoomLevel::ALL
+                .windows(2)
+                .all(|pair| matches!(pair, [a, b] if a.cover_size() < b.cover_size())),
+            "Preferences list levels from small to large"
+        );
+    }
+}
@@ -39,9 +39,10 @@
 //! │      • AtomicBool for flush/drain signal                         │
 //! │      • NEVER holds a Mutex — real-time safety invariant          │
 //! │                                                                  │
-//! │  [5] RAYON THREAD POOL (n_cores workers)                         │
+//! │  [5] RAYON SCAN POOL (per scan, "scan-worker-{n}")               │
 //! │      • Parallel directory walk and metadata extraction           │
 //! │      • Runs inside spawn_blocking — intentional isolation        │
+//! │      • Sized by the scan thread setting or the work budget       │
 //! │                                                                  │
 //! │  [6] NOTIFY WATCHER (OS thread, from notify crate)               │
 //! │      • Callback → tokio::sync::mpsc::unbounded                   │
@@ -77,7 +78,7 @@
 //!    is detached — its `JoinHandle` is stored in `EngineShared::decode_thread` but never
 //!    explicitly joined to avoid blocking the `GLib` main loop.
 //! 5. Tokio runtime drops → all tokio tasks cancelled
-//! 6. Rayon pool drains
+//! 6. A running scan's pool drains
 //! 7. Process exits
 
 //! # Exceptions
@@ -85,6 +86,17 @@
 //! - The **decode thread** (`engine.rs`) is intentionally NOT managed via `ThreadManager`. Its
 //!   `AudioOutput::drop()` is blocking and must not be joined from the `GLib` main thread. Its
 //!   `JoinHandle` is stored directly in `EngineShared::decode_thread`.
+//!
+//! # Background Work Budget
+//!
+//! Rayon metadata extraction and the cover decoder draw permits from a
+//! single [`scheduler::BackgroundScheduler`] so their combined
+//! concurrency follows the user's "background work intensity" setting.
+//! Scans additionally run on their own [`scan_pool`], whose size can be
+//! tuned apart from that setting.
+
+pub mod scan_pool;
+pub mod scheduler;
 
 use std::{
     mem::take,
@@ -0,0 +1,105 @@
+//! Dedicated worker pool for library scans.
+//!
+//! The directory walk and metadata extraction of a scan run on a pool of
+//! their own instead of rayon's global pool, which has a thread for every
+//! core and would let a large import compete with playback and the UI for
+//! all of them. The pool is sized by the user's scan thread setting, or by
+//! the background work budget when that is left automatic. Work permits
+//! from the [`BackgroundScheduler`] still apply: they are taken before
+//! work is handed to the pool, so its threads never wait on them.
+
+use {rayon::ThreadPoolBuilder, tracing::warn};
+
+use crate::threading::scheduler::BackgroundScheduler;
+
+/// Largest number of scan worker threads a user can choose.
+pub const MAX_SCAN_THREADS: usize = 16;
+
+/// Number of worker threads for the next scan.
+///
+/// # Arguments
+///
+/// * `configured` - User-chosen thread count, `0` to follow the background budget
+/// * `scheduler` - Shared background work budget
+#[must_use]
+pub fn scan_thread_count(configured: usize, scheduler: &BackgroundScheduler) -> usize {
+    if configured == 0 {
+        scheduler.budget()
+    } else {
+        configured.min(MAX_SCAN_THREADS)
+    }
+}
+
+/// Run `work` on a pool of `threads` named scan workers.
+///
+/// Parallel iterators inside `work` use the pool. It is dropped once
+/// `work` returns, so no scan threads are left idle between scans.
+///
+/// # Returns
+///
+/// The result of `work`, run on the calling thread's pool if the scan pool
+/// cannot be created.
+pub fn run_on_scan_pool<T: Send>(threads: usize, work: impl FnOnce() -> T + Send) -> T {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(threads.max(1))
+        .thread_name(|index| format!("scan-worker-{index}"))
+        .build();
+    match pool {
+        Ok(pool) => pool.install(work),
+        Err(e) => {
+            warn!(error = %e, threads, "Failed to create scan pool, using the shared pool");
+            work()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread::current;
+
+    use rayon::{
+        current_num_threads,
+        iter::{IntoParallelIterator, ParallelIterator},
+    };
+
+    use crate::threading::{
+        scan_pool::{MAX_SCAN_THREADS, run_on_scan_pool, scan_thread_count},
+        scheduler::{BackgroundScheduler, WorkIntensity::Balanced},
+    };
+
+    /// Whether the calling thread is one of the scan pool's workers.
+    fn on_scan_worker() -> bool {
+        current()
+            .name()
+            .is_some_and(|n| n.starts_with("scan-worker-"))
+    }
+
+    #[test]
+    fn thread_count_follows_the_budget_unless_set() {
+        let scheduler = BackgroundScheduler::with_cores(Balanced, 8);
+        assert_eq!(
+            scan_thread_count(0, &scheduler),
+            4,
+            "Automatic uses the budget"
+        );
+        assert_eq!(scan_thread_count(2, &scheduler), 2, "A chosen count wins");
+        assert_eq!(
+            scan_thread_count(64, &scheduler),
+            MAX_SCAN_THREADS,
+            "Chosen counts are capped"
+        );
+    }
+
+    #[test]
+    fn work_runs_on_named_scan_workers() {
+        let (threads, names) = run_on_scan_pool(2, || {
+            let names: Vec<bool> = (0..8).into_par_iter().map(|_| on_scan_worker()).collect();
+            (current_num_threads(), names)
+        });
+        assert_eq!(threads, 2, "The pool must have the requested size");
+        assert!(
+            names.iter().all(|named| *named),
+            "Parallel work must run on scan workers"
+        );
+    }
+}
@@ -0,0 +1,356 @@
+//! Global scheduler for background CPU/IO work.
+//!
+//! Library scanning, audio analysis, and cover decoding all draw work
+//! permits from one shared [`BackgroundScheduler`] instead of each
+//! keeping an independent concurrency knob. The permit budget is derived
+//! from the user's [`WorkIntensity`] preference and shrinks by one slot
+//! while audio is playing, so background work always yields a core to
+//! the decode thread.
+
+use std::thread::available_parallelism;
+
+use {
+    parking_lot::{Condvar, Mutex},
+    serde::{Deserialize, Serialize},
+};
+
+/// Shared permit budget for all background work.
+///
+/// Workers call [`acquire`](Self::acquire) before each unit of work
+/// (one file's metadata extraction, one cover decode, one analysis
+/// pass) and hold the returned [`WorkPermit`] until the unit finishes.
+/// `acquire` blocks while the budget is exhausted, so it must only be
+/// called from background threads — never from the `GLib` main thread
+/// or the audio callback. Parallel batches take their permits up front
+/// with [`acquire_many`](Self::acquire_many) on the dispatching thread, so
+/// rayon workers never block on the budget.
+pub struct BackgroundScheduler {
+    /// Mutable scheduling state guarded by a single lock.
+    state: Mutex<SchedulerState>,
+    /// Signalled whenever a permit is released or the budget grows.
+    released: Condvar,
+    /// Number of logical CPUs the budget is scaled against.
+    cores: usize,
+}
+
+impl BackgroundScheduler {
+    /// Create a scheduler sized to the host's available parallelism.
+    #[must_use]
+    pub fn new(intensity: WorkIntensity) -> Self {
+        let cores = available_parallelism().map_or(1, usize::from);
+        Self::with_cores(intensity, cores)
+    }
+
+    /// Create a scheduler for an explicit core count.
+    ///
+    /// # Arguments
+    ///
+    /// * `intensity` - Initial background work intensity
+    /// * `cores` - Number of logical CPUs to scale the budget against
+    #[must_use]
+    pub fn with_cores(intensity: WorkIntensity, cores: usize) -> Self {
+        Self {
+            state: Mutex::new(SchedulerState {
+                intensity,
+                audio_active: false,
+                in_flight: 0,
+            }),
+            released: Condvar::new(),
+            cores: cores.max(1),
+        }
+    }
+
+    /// Block until a work permit is available and take it.
+    ///
+    /// The permit is returned to the pool when the [`WorkPermit`] drops.
+    pub fn acquire(&self) -> WorkPermit<'_> {
+        let mut state = self.state.lock();
+        while state.in_flight >= state.budget(self.cores) {
+            self.released.wait(&mut state);
+        }
+        state.in_flight += 1;
+        drop(state);
+        WorkPermit { scheduler: self }
+    }
+
+    /// Block until at least one work permit is available, then take as
+    /// many as are free, up to `wanted`.
+    ///
+    /// # Arguments
+    ///
+    /// * `wanted` - Largest number of permits to take, at least one
+    ///
+    /// # Returns
+    ///
+    /// The permits, which return to the pool when the [`WorkPermits`]
+    /// drops.
+    pub fn acquire_many(&self, wanted: usize) -> WorkPermits<'_> {
+        let mut state = self.state.lock();
+        while state.in_flight >= state.budget(self.cores) {
+            self.released.wait(&mut state);
+        }
+        let count = wanted
+            .max(1)
+            .min(state.budget(self.cores) - state.in_flight);
+        state.in_flight += count;
+        drop(state);
+        WorkPermits {
+            scheduler: self,
+            count,
+        }
+    }
+
+    /// Current number of concurrent background work units allowed.
+    #[must_use]
+    pub fn budget(&self) -> usize {
+        self.state.lock().budget(self.cores)
+    }
+
+    /// Number of work permits currently held.
+    #[must_use]
+    pub fn in_flight(&self) -> usize {
+        self.state.lock().in_flight
+    }
+
+    /// Current background work intensity.
+    #[must_use]
+    pub fn intensity(&self) -> WorkIntensity {
+        self.state.lock().intensity
+    }
+
+    /// Change the background work intensity.
+    ///
+    /// Takes effect for the next [`acquire`](Self::acquire) call; permits
+    /// already held are not revoked.
+    pub fn set_intensity(&self, intensity: WorkIntensity) {
+        self.state.lock().intensity = intensity;
+        self.released.notify_all();
+    }
+
+    /// Record whether the audio path is currently playing.
+    ///
+    /// While active, the budget is reduced by one slot (never below one).
+    pub fn set_audio_active(&self, active: bool) {
+        self.state.lock().audio_active = active;
+        self.released.notify_all();
+    }
+
+    /// Return a permit to the pool and wake one waiter.
+    fn release(&self) {
+        let mut state = self.state.lock();
+        state.in_flight = state.in_flight.saturating_sub(1);
+        drop(state);
+        self.released.notify_one();
+    }
+
+    /// Return `count` permits to the pool and wake every waiter.
+    fn release_many(&self, count: usize) {
+        let mut state = self.state.lock();
+        state.in_flight = state.in_flight.saturating_sub(count);
+        drop(state);
+        self.released.notify_all();
+    }
+}
+
+impl Default for BackgroundScheduler {
+    fn default() -> Self {
+        Self::new(WorkIntensity::default())
+    }
+}
+
+/// Mutable state behind the scheduler lock.
+struct SchedulerState {
+    /// User-selected background work intensity.
+    intensity: WorkIntensity,
+    /// Whether audio is currently playing.
+    audio_active: bool,
+    /// Number of permits currently held.
+    in_flight: usize,
+}
+
+impl SchedulerState {
+    /// Compute the permit budget for `cores` logical CPUs.
+    fn budget(&self, cores: usize) -> usize {
+        let base = self.intensity.max_workers(cores);
+        if self.audio_active {
+            base.saturating_sub(1).max(1)
+        } else {
+            base
+        }
+    }
+}
+
+/// User-facing background work intensity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkIntensity {
+    /// One background task at a time.
+    Low,
+    /// Half of the available cores.
+    #[default]
+    Balanced,
+    /// All available cores.
+    High,
+}
+
+impl WorkIntensity {
+    /// Maximum number of concurrent background work units for `cores` CPUs.
+    #[must_use]
+    pub fn max_workers(self, cores: usize) -> usize {
+        match self {
+            Self::Low => 1,
+            Self::Balanced => (cores / 2).max(1),
+            Self::High => cores.max(1),
+        }
+    }
+
+    /// Human-readable label for preference rows.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Low => "Low",
+            Self::Balanced => "Balanced",
+            Self::High => "High",
+        }
+    }
+}
+
+/// RAII guard for one unit of background work.
+///
+/// Dropping the guard returns the permit to its [`BackgroundScheduler`].
+pub struct WorkPermit<'a> {
+    /// Scheduler the permit was drawn from.
+    scheduler: &'a BackgroundScheduler,
+}
+
+impl Drop for WorkPermit<'_> {
+    fn drop(&mut self) {
+        self.scheduler.release();
+    }
+}
+
+/// RAII guard for a batch of background work run in parallel.
+///
+/// Dropping the guard returns all of its permits to its
+/// [`BackgroundScheduler`].
+pub struct WorkPermits<'a> {
+    /// Scheduler the permits were drawn from.
+    scheduler: &'a BackgroundScheduler,
+    /// Number of permits held.
+    count: usize,
+}
+
+impl WorkPermits<'_> {
+    /// Number of permits held, the most work units the batch may run at
+    /// once.
+    #[must_use]
+    pub const fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl Drop for WorkPermits<'_> {
+    fn drop(&mut self) {
+        self.scheduler.release_many(self.count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        iter::repeat_with,
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering::SeqCst},
+        },
+        thread::{JoinHandle, sleep, spawn},
+        time::Duration,
+    };
+
+    use crate::threading::scheduler::{
+        BackgroundScheduler,
+        WorkIntensity::{Balanced, High, Low},
+    };
+
+    fn run_unit(scheduler: &BackgroundScheduler, active: &AtomicUsize, peak: &AtomicUsize) {
+        let permit = scheduler.acquire();
+        let now = active.fetch_add(1, SeqCst) + 1;
+        peak.fetch_max(now, SeqCst);
+        sleep(Duration::from_millis(5));
+        active.fetch_sub(1, SeqCst);
+        drop(permit);
+    }
+
+    fn spawn_worker(
+        scheduler: Arc<BackgroundScheduler>,
+        active: Arc<AtomicUsize>,
+        peak: Arc<AtomicUsize>,
+    ) -> JoinHandle<()> {
+        spawn(move || (0..3).for_each(|_| run_unit(&scheduler, &active, &peak)))
+    }
+
+    #[test]
+    fn intensity_budgets_scale_with_cores() {
+        assert_eq!(Low.max_workers(8), 1, "Low must run one task at a time");
+        assert_eq!(
+            Balanced.max_workers(8),
+            4,
+            "Balanced must use half the cores"
+        );
+        assert_eq!(High.max_workers(8), 8, "High must use every core");
+        assert_eq!(Balanced.max_workers(1), 1, "Budget must never reach zero");
+    }
+
+    #[test]
+    fn audio_playback_reserves_a_slot() {
+        let scheduler = BackgroundScheduler::with_cores(High, 8);
+        scheduler.set_audio_active(true);
+        assert_eq!(scheduler.budget(), 7, "Playback must reserve one core");
+        scheduler.set_intensity(Low);
+        assert_eq!(scheduler.budget(), 1, "Budget must never drop below one");
+    }
+
+    #[test]
+    fn batches_take_only_the_free_permits() {
+        let scheduler = BackgroundScheduler::with_cores(High, 4);
+        let single = scheduler.acquire();
+        let batch = scheduler.acquire_many(8);
+        assert_eq!(batch.count(), 3, "A batch must take only the free permits");
+        assert_eq!(scheduler.in_flight(), 4, "The budget must be fully held");
+        drop(batch);
+        assert_eq!(scheduler.in_flight(), 1, "A batch must return its permits");
+        drop(single);
+        assert_eq!(
+            scheduler.acquire_many(0).count(),
+            1,
+            "A batch takes one permit"
+        );
+    }
+
+    #[test]
+    fn low_intensity_bounds_combined_concurrency() {
+        let scheduler = Arc::new(BackgroundScheduler::with_cores(Low, 8));
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        // Mix of scanner-, analysis- and cover-decoder-style workers.
+        let workers: Vec<_> = repeat_with(|| {
+            spawn_worker(
+                Arc::clone(&scheduler),
+                Arc::clone(&active),
+                Arc::clone(&peak),
+            )
+        })
+        .take(12)
+        .collect();
+        for worker in workers {
+            assert!(matches!(worker.join(), Ok(())), "Worker thread panicked");
+        }
+
+        assert!(
+            peak.load(SeqCst) <= scheduler.budget(),
+            "Combined background concurrency exceeded the Low budget"
+        );
+        assert_eq!(scheduler.in_flight(), 0, "All permits must be returned");
+    }
+}
@@ -0,0 +1,104 @@
+//! Library > Background Work group of the preferences dialog.
+//!
+//! The intensity sets how much of the machine scanning, analysis, and
+//! cover loading may use. Library scans read files on their own bounded
+//! pool; a scan thread count of zero lets the intensity pick it.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        ComboRow, PreferencesGroup, PreferencesPage, SpinRow,
+        glib::spawn_future_local,
+        gtk::{Adjustment, StringList},
+        prelude::{ComboRowExt, ObjectExt, PreferencesGroupExt, PreferencesPageExt},
+    },
+    num_traits::NumCast,
+    tracing::{error, info},
+};
+
+use crate::{
+    app::AppState,
+    storage::database::SqliteStorage,
+    threading::{
+        scan_pool::MAX_SCAN_THREADS,
+        scheduler::WorkIntensity::{self, Balanced, High, Low},
+    },
+};
+
+/// Build the Library > Background Work group.
+pub fn build_background_group(page: &PreferencesPage, state: &Arc<AppState>) {
+    let group = PreferencesGroup::new();
+    group.set_title("Background Work");
+    group.set_description(Some(
+        "Scanning, analysis, and cover loading share one budget and always yield to playback; \
+         scans run on their own threads",
+    ));
+
+    let intensities = [Low, Balanced, High];
+    let labels: Vec<&str> = intensities.iter().map(|i| i.label()).collect();
+    let model = StringList::new(&labels);
+    let combo = ComboRow::builder()
+        .title("Intensity")
+        .subtitle("Low keeps the system responsive; High finishes scans sooner")
+        .model(&model)
+        .build();
+    let current = state.scheduler.intensity();
+    let selected = intensities.iter().position(|i| *i == current).unwrap_or(1);
+    combo.set_selected(u32::try_from(selected).unwrap_or(1));
+
+    let state_intensity = Arc::clone(state);
+    combo.connect_selected_notify(move |combo| {
+        let Some(&intensity) = intensities.get(combo.selected() as usize) else {
+            return;
+        };
+        info!(intensity = ?intensity, "Background work intensity changed");
+        state_intensity.scheduler.set_intensity(intensity);
+        spawn_future_local(save_work_intensity(
+            Arc::clone(&state_intensity.storage),
+            intensity,
+        ));
+    });
+
+    group.add(&combo);
+    group.add(&build_scan_threads_row(state));
+    page.add(&group);
+}
+
+/// Persist background work intensity, logging on failure.
+async fn save_work_intensity(storage: Arc<SqliteStorage>, intensity: WorkIntensity) {
+    if let Err(e) = storage.set_work_intensity(intensity).await {
+        error!(error = %e, "Failed to save background work intensity");
+    }
+}
+
+/// Build the row choosing how many threads library scans use.
+fn build_scan_threads_row(state: &Arc<AppState>) -> SpinRow {
+    let threads = NumCast::from(state.storage.get_scan_threads()).unwrap_or(0.0);
+    let max = NumCast::from(MAX_SCAN_THREADS).unwrap_or(1.0);
+    let row = SpinRow::builder()
+        .title("Scan Threads")
+        .subtitle("Threads reading files during scans; 0 follows the intensity")
+        .adjustment(&Adjustment::new(threads, 0.0, max, 1.0, 4.0, 0.0))
+        .digits(0)
+        .build();
+
+    let state_threads = Arc::clone(state);
+    row.connect_notify_local(Some("value"), move |row, _| {
+        let threads: usize = NumCast::from(row.value()).unwrap_or(0);
+        info!(threads, "Scan thread count changed");
+        state_threads.scanner.set_scan_threads(threads);
+        spawn_future_local(save_scan_threads(
+            Arc::clone(&state_threads.storage),
+            threads,
+        ));
+    });
+    row
+}
+
+/// Persist the scan worker thread count, logging on failure.
+async fn save_scan_threads(storage: Arc<SqliteStorage>, threads: usize) {
+    if let Err(e) = storage.set_scan_threads(threads).await {
+        error!(error = %e, "Failed to save scan threads");
+    }
+}
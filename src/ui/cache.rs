@@ -0,0 +1,203 @@
+//! Library > Cache group of the preferences dialog.
+//!
+//! "Cache Location" moves the artwork, thumbnail, and waveform caches to
+//! another folder, such as one on a faster disk, or back to the default.
+//! Thumbnails and waveforms in the old folder are removed on the move;
+//! extracted artwork stays there, as the library refers to those files.
+//! "Clear Cache" removes the thumbnails and waveforms, which are rebuilt
+//! when next shown, and reports the space freed.
+
+use std::{path::PathBuf, sync::Arc};
+
+use {
+    libadwaita::{
+        ActionRow, PreferencesGroup, PreferencesPage,
+        gio::spawn_blocking,
+        glib::{format_size, object::Cast, spawn_future_local},
+        gtk::{Align::Center, Button, FileDialog, Window},
+        prelude::{
+            ActionRowExt, ButtonExt, FileExt, PreferencesGroupExt, PreferencesPageExt, WidgetExt,
+        },
+    },
+    tracing::{info, warn},
+};
+
+use crate::{
+    app::AppState,
+    library::cache::{cache_root, clear_cache, clear_cache_in, set_cache_directory},
+};
+
+/// Build the Library > Cache group.
+pub fn build_cache_group(page: &PreferencesPage, state: &Arc<AppState>) {
+    let group = PreferencesGroup::new();
+    group.set_title("Cache");
+    group.set_description(Some(
+        "Album artwork, cover thumbnails, and waveforms are kept here",
+    ));
+    group.add(&build_location_row(state));
+    group.add(&build_clear_row(state));
+    page.add(&group);
+}
+
+/// Build the "Cache Location" row with its "Change…" and "Reset" buttons.
+fn build_location_row(state: &Arc<AppState>) -> ActionRow {
+    let row = ActionRow::builder().title("Cache Location").build();
+    row.add_css_class("property");
+    show_location(&row);
+
+    let change_btn = Button::builder()
+        .label("Change…")
+        .valign(Center)
+        .tooltip_text(
+            "Keep new cache files in another folder; thumbnails and waveforms in the old one are removed",
+        )
+        .build();
+    let reset_btn = Button::builder()
+        .label("Reset")
+        .valign(Center)
+        .tooltip_text(
+            "Keep new cache files in the default folder; thumbnails and waveforms in the old one are removed",
+        )
+        .css_classes(["flat"])
+        .build();
+    reset_btn.set_sensitive(state.storage.get_cache_directory().is_some());
+    row.add_suffix(&reset_btn);
+    row.add_suffix(&change_btn);
+
+    let state_change = Arc::clone(state);
+    let (row_change, reset_change) = (row.clone(), reset_btn.clone());
+    change_btn.connect_clicked(move |btn| {
+        let parent = btn.root().and_then(|r| r.downcast::<Window>().ok());
+        spawn_future_local(change_location(
+            Arc::clone(&state_change),
+            parent,
+            row_change.clone(),
+            reset_change.clone(),
+        ));
+    });
+
+    let state_reset = Arc::clone(state);
+    let row_reset = row.clone();
+    reset_btn.connect_clicked(move |btn| {
+        btn.set_sensitive(false);
+        let (state, row) = (Arc::clone(&state_reset), row_reset.clone());
+        spawn_future_local(async move {
+            move_cache(&state, None).await;
+            show_location(&row);
+        });
+    });
+    row
+}
+
+/// Build the "Clear Cache" row with its "Clear" button.
+fn build_clear_row(state: &Arc<AppState>) -> ActionRow {
+    let clear_btn = Button::builder().label("Clear").valign(Center).build();
+    let row = ActionRow::builder()
+        .title("Clear Cache")
+        .subtitle("Remove cover thumbnails and waveforms; they are rebuilt when next shown")
+        .build();
+    row.add_suffix(&clear_btn);
+    row.set_activatable_widget(Some(&clear_btn));
+
+    let state = Arc::clone(state);
+    clear_btn.connect_clicked(move |btn| {
+        btn.set_sensitive(false);
+        spawn_future_local(clear_and_report(Arc::clone(&state), btn.clone()));
+    });
+    row
+}
+
+/// Clear the cache off the main thread and report the space freed.
+async fn clear_and_report(state: Arc<AppState>, btn: Button) {
+    let message = match spawn_blocking(clear_cache).await {
+        Ok(Ok(freed)) => format!("Cleared {} of cache", format_size(freed)),
+        Ok(Err(e)) => {
+            warn!(error = %e, "Failed to clear cache");
+            format!("Could not clear the cache: {e}")
+        }
+        Err(e) => {
+            warn!(error = ?e, "Cache clearing panicked");
+            "Could not clear the cache".to_string()
+        }
+    };
+    state.send_toast(message).await;
+    btn.set_sensitive(true);
+}
+
+/// Show the current cache location as the subtitle of `row`.
+fn show_location(row: &ActionRow) {
+    match cache_root() {
+        Ok(root) => row.set_subtitle(&root.display().to_string()),
+        Err(e) => {
+            warn!(error = %e, "Cannot resolve cache directory");
+            row.set_subtitle("Unavailable");
+        }
+    }
+}
+
+/// Ask for the folder to keep the cache in.
+///
+/// # Returns
+///
+/// `None` if the dialog was cancelled or the folder has no local path.
+async fn pick_cache_folder(parent: Option<&Window>) -> Option<PathBuf> {
+    let dialog = FileDialog::builder()
+        .title("Select Cache Folder")
+        .accept_label("Select")
+        .build();
+    match dialog.select_folder_future(parent).await {
+        Ok(folder) => folder.path(),
+        Err(e) => {
+            info!(error = %e, "Cache folder selection cancelled");
+            None
+        }
+    }
+}
+
+/// Ask for a new cache folder and move the cache there.
+async fn change_location(
+    state: Arc<AppState>,
+    parent: Option<Window>,
+    row: ActionRow,
+    reset: Button,
+) {
+    let Some(dir) = pick_cache_folder(parent.as_ref()).await else {
+        return;
+    };
+    move_cache(&state, Some(dir)).await;
+    show_location(&row);
+    reset.set_sensitive(true);
+}
+
+/// Keep new cache files in `dir`, or in the default folder for `None`,
+/// and remember the choice.
+///
+/// The thumbnails and waveforms left in the previous folder are removed.
+async fn move_cache(state: &AppState, dir: Option<PathBuf>) {
+    let previous = cache_root();
+    if let Err(e) = state.storage.set_cache_directory(dir.as_deref()).await {
+        warn!(error = %e, "Failed to save cache directory");
+        state
+            .send_toast(format!("Could not move the cache: {e}"))
+            .await;
+        return;
+    }
+    set_cache_directory(dir);
+    match (previous, cache_root()) {
+        (Ok(old), Ok(new)) if old != new => clear_old_location(state, old).await,
+        (Err(e), _) | (_, Err(e)) => warn!(error = %e, "Cannot resolve cache directory"),
+        _ => {}
+    }
+}
+
+/// Remove the thumbnails and waveforms left in `old` after the cache moved,
+/// and report the space freed.
+async fn clear_old_location(state: &AppState, old: PathBuf) {
+    match spawn_blocking(move || clear_cache_in(&old)).await {
+        Ok(freed) => {
+            let message = format!("Freed {} in the old cache folder", format_size(freed));
+            state.send_toast(message).await;
+        }
+        Err(e) => warn!(error = ?e, "Clearing the old cache folder panicked"),
+    }
+}
@@ -0,0 +1,103 @@
+//! Library > Catalog group of the preferences dialog.
+//!
+//! Exports every track with its album, artist, and audio format to a CSV
+//! or JSON file chosen by the user, for spreadsheets or other players. A
+//! toast reports how many tracks were written.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        ActionRow, PreferencesGroup, PreferencesPage,
+        gio::ListStore,
+        glib::{object::Cast, spawn_future_local},
+        gtk::{
+            Align::Center, Box as GtkBox, Button, FileDialog, FileFilter, Orientation::Horizontal,
+            Window,
+        },
+        prelude::{
+            ActionRowExt, BoxExt, ButtonExt, FileExt, PreferencesGroupExt, PreferencesPageExt,
+            WidgetExt,
+        },
+    },
+    tracing::{info, warn},
+};
+
+use crate::{
+    app::AppState,
+    storage::catalog::CatalogFormat::{self, Csv, Json},
+};
+
+/// Build the Library > Catalog group.
+pub fn build_catalog_group(page: &PreferencesPage, state: &Arc<AppState>) {
+    let group = PreferencesGroup::new();
+    group.set_title("Catalog");
+
+    let buttons = GtkBox::new(Horizontal, 0);
+    buttons.add_css_class("linked");
+    buttons.set_valign(Center);
+    for format in CatalogFormat::ALL {
+        let button = Button::with_label(&format!("{}…", format.label()));
+        let state_export = Arc::clone(state);
+        button.connect_clicked(move |btn| {
+            let parent = btn.root().and_then(|r| r.downcast::<Window>().ok());
+            spawn_future_local(export_catalog(Arc::clone(&state_export), format, parent));
+        });
+        buttons.append(&button);
+    }
+
+    let row = ActionRow::builder()
+        .title("Export Catalog")
+        .subtitle("Save every track with its album, artist, and audio format")
+        .build();
+    row.add_suffix(&buttons);
+    group.add(&row);
+    page.add(&group);
+}
+
+/// Ask for a destination and export the catalog there in `format`.
+async fn export_catalog(state: Arc<AppState>, format: CatalogFormat, parent: Option<Window>) {
+    let dialog = catalog_file_dialog(format);
+    let file = match dialog.save_future(parent.as_ref()).await {
+        Ok(file) => file,
+        Err(e) => {
+            info!(error = %e, "Catalog export cancelled");
+            return;
+        }
+    };
+    let Some(path) = file.path() else {
+        warn!("Selected catalog file has no local path");
+        return;
+    };
+    let message = match state.storage.export_catalog(format, &path).await {
+        Ok(tracks) => {
+            info!(tracks, path = %path.display(), "Catalog exported");
+            format!("Exported {tracks} tracks to {}", path.display())
+        }
+        Err(e) => {
+            warn!(error = %e, path = %path.display(), "Failed to export catalog");
+            format!("Could not export catalog: {e}")
+        }
+    };
+    state.send_toast(message).await;
+}
+
+/// Save dialog filtered to catalog files in `format`.
+fn catalog_file_dialog(format: CatalogFormat) -> FileDialog {
+    let filter = FileFilter::new();
+    filter.set_name(Some(&format!("{} files", format.label())));
+    filter.add_mime_type(match format {
+        Csv => "text/csv",
+        Json => "application/json",
+    });
+    filter.add_suffix(format.extension());
+    let filters = ListStore::new::<FileFilter>();
+    filters.append(&filter);
+    FileDialog::builder()
+        .title("Export Catalog")
+        .accept_label("Export")
+        .initial_name(format!("oxhidifi-catalog.{}", format.extension()))
+        .filters(&filters)
+        .default_filter(&filter)
+        .build()
+}
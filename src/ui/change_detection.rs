@@ -0,0 +1,102 @@
+//! Library > Change Detection group of the preferences dialog.
+//!
+//! The debounce delay and batch size decide how file changes are gathered
+//! before the affected folders are rescanned. Changes reach the running
+//! watcher through its config channel and apply from its next batch.
+
+use std::{ops::RangeInclusive, sync::Arc};
+
+use {
+    libadwaita::{
+        PreferencesGroup, PreferencesPage, SpinRow,
+        glib::spawn_future_local,
+        gtk::Adjustment,
+        prelude::{ObjectExt, PreferencesGroupExt, PreferencesPageExt},
+    },
+    num_traits::NumCast,
+    tracing::{error, info},
+};
+
+use crate::{
+    app::AppState,
+    library::watcher::config::{BATCH_SIZE_RANGE, DEBOUNCE_MS_RANGE, WatcherConfig},
+    storage::database::SqliteStorage,
+};
+
+/// Build the Library > Change Detection group.
+///
+/// Changes apply to the running watcher from its next batch.
+pub fn build_change_detection_group(page: &PreferencesPage, state: &Arc<AppState>) {
+    let group = PreferencesGroup::new();
+    group.set_title("Change Detection");
+    group.set_description(Some(
+        "How file changes are gathered before the affected folders are rescanned",
+    ));
+
+    let config = *state.watcher_config_tx.borrow();
+    let (min, max) = range_limits(&DEBOUNCE_MS_RANGE);
+    let debounce = NumCast::from(config.debounce_ms).unwrap_or(min);
+    let debounce_row = SpinRow::builder()
+        .title("Debounce Delay")
+        .subtitle("Milliseconds without changes before scanning; raise for slow network mounts")
+        .adjustment(&Adjustment::new(debounce, min, max, 50.0, 500.0, 0.0))
+        .digits(0)
+        .build();
+    let state_debounce = Arc::clone(state);
+    debounce_row.connect_notify_local(Some("value"), move |row, _| {
+        let current = *state_debounce.watcher_config_tx.borrow();
+        let debounce_ms = NumCast::from(row.value()).unwrap_or(current.debounce_ms);
+        apply_watcher_config(
+            &state_debounce,
+            WatcherConfig::new(debounce_ms, current.max_batch_size),
+        );
+    });
+    group.add(&debounce_row);
+
+    let (min, max) = range_limits(&BATCH_SIZE_RANGE);
+    let batch_size = NumCast::from(config.max_batch_size).unwrap_or(min);
+    let batch_row = SpinRow::builder()
+        .title("Batch Size")
+        .subtitle("Most changes gathered before a rescan starts")
+        .adjustment(&Adjustment::new(batch_size, min, max, 10.0, 100.0, 0.0))
+        .digits(0)
+        .build();
+    let state_batch = Arc::clone(state);
+    batch_row.connect_notify_local(Some("value"), move |row, _| {
+        let current = *state_batch.watcher_config_tx.borrow();
+        let max_batch_size = NumCast::from(row.value()).unwrap_or(current.max_batch_size);
+        apply_watcher_config(
+            &state_batch,
+            WatcherConfig::new(current.debounce_ms, max_batch_size),
+        );
+    });
+    group.add(&batch_row);
+
+    page.add(&group);
+}
+
+/// Hand `config` to the running watcher and persist it.
+fn apply_watcher_config(state: &Arc<AppState>, config: WatcherConfig) {
+    if state.watcher_config_tx.send_replace(config) == config {
+        return;
+    }
+    info!(
+        debounce_ms = config.debounce_ms,
+        max_batch_size = config.max_batch_size,
+        "Watcher settings changed"
+    );
+    spawn_future_local(save_watcher_config(Arc::clone(&state.storage), config));
+}
+
+/// Lower and upper bound of `range` as spin row limits.
+fn range_limits<T: NumCast + Copy>(range: &RangeInclusive<T>) -> (f64, f64) {
+    let start = NumCast::from(*range.start()).unwrap_or(0.0);
+    (start, NumCast::from(*range.end()).unwrap_or(start))
+}
+
+/// Persist the watcher settings, logging on failure.
+async fn save_watcher_config(storage: Arc<SqliteStorage>, config: WatcherConfig) {
+    if let Err(e) = storage.set_watcher_config(config).await {
+        error!(error = %e, "Failed to save watcher settings");
+    }
+}
@@ -0,0 +1,106 @@
+//! Audio > Channels group of the preferences dialog.
+//!
+//! The channel mode plays stereo as is, as mono, or with the channels
+//! swapped, and the balance favours one side. Both are skipped in
+//! bit-perfect mode.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        ComboRow, PreferencesGroup, PreferencesPage, SpinRow,
+        glib::spawn_future_local,
+        gtk::{Adjustment, StringList},
+        prelude::{ComboRowExt, ObjectExt, PreferencesGroupExt, PreferencesPageExt},
+    },
+    tracing::{error, warn},
+};
+
+use crate::{
+    app::AppState,
+    playback::{control::PlaybackController, stereo::DownmixMode},
+    storage::database::SqliteStorage,
+};
+
+/// Build the Channels preferences group: downmix mode and balance.
+pub fn build_channels_group(page: &PreferencesPage, state: &Arc<AppState>) {
+    let channels_group = PreferencesGroup::new();
+    channels_group.set_title("Channels");
+    channels_group.set_description(Some("Skipped in bit-perfect mode"));
+    channels_group.add(&build_downmix_row(state));
+    channels_group.add(&build_balance_row(state));
+    page.add(&channels_group);
+}
+
+/// Build the row choosing stereo, mono, or swapped channels.
+fn build_downmix_row(state: &Arc<AppState>) -> ComboRow {
+    let labels: Vec<&str> = DownmixMode::ALL.iter().map(|m| m.label()).collect();
+    let model = StringList::new(&labels);
+    let downmix_row = ComboRow::builder()
+        .title("Channel Mode")
+        .subtitle("Mono averages both channels")
+        .model(&model)
+        .build();
+    let current = state.storage.get_downmix();
+    let position = DownmixMode::ALL.iter().position(|m| *m == current);
+    downmix_row.set_selected(position.and_then(|p| u32::try_from(p).ok()).unwrap_or(0));
+
+    let state_downmix = Arc::clone(state);
+    downmix_row.connect_selected_notify(move |row| {
+        let Some(mode) = usize::try_from(row.selected())
+            .ok()
+            .and_then(|i| DownmixMode::ALL.get(i).copied())
+        else {
+            return;
+        };
+        if let Err(e) = state_downmix.playback.set_downmix(mode) {
+            warn!(error = %e, "Failed to set channel mode");
+        }
+        spawn_future_local(save_downmix_setting(
+            Arc::clone(&state_downmix.storage),
+            mode,
+        ));
+    });
+
+    downmix_row
+}
+
+/// Build the balance row (percent, negative values favour the left).
+fn build_balance_row(state: &Arc<AppState>) -> SpinRow {
+    let initial = (state.storage.get_balance() * 100.0).round();
+    let adjustment = Adjustment::new(initial, -100.0, 100.0, 5.0, 25.0, 0.0);
+    let balance_row = SpinRow::builder()
+        .title("Balance")
+        .subtitle("From \u{2212}100 (left only) to 100 (right only), 0 is centred")
+        .adjustment(&adjustment)
+        .digits(0)
+        .build();
+
+    let state_balance = Arc::clone(state);
+    balance_row.connect_notify_local(Some("value"), move |row, _| {
+        let balance = row.value() / 100.0;
+        if let Err(e) = state_balance.playback.set_balance(balance) {
+            warn!(error = %e, "Failed to set balance from preferences");
+        }
+        spawn_future_local(save_balance_setting(
+            Arc::clone(&state_balance.storage),
+            balance,
+        ));
+    });
+
+    balance_row
+}
+
+/// Persist channel mode, logging on failure.
+async fn save_downmix_setting(storage: Arc<SqliteStorage>, mode: DownmixMode) {
+    if let Err(e) = storage.set_downmix(mode).await {
+        error!(error = %e, "Failed to save channel mode");
+    }
+}
+
+/// Persist left/right balance, logging on failure.
+async fn save_balance_setting(storage: Arc<SqliteStorage>, balance: f64) {
+    if let Err(e) = storage.set_balance(balance).await {
+        error!(error = %e, "Failed to save balance");
+    }
+}
@@ -0,0 +1,283 @@
+//! Library > Maintenance group of the preferences dialog.
+//!
+//! "Rescan Library" walks every library directory again, picking up new and
+//! changed files and dropping tracks whose files are gone; scan progress
+//! shows in the status bar while the row shows a spinner.
+//! "Remove Missing Files" checks every track path, asks for confirmation
+//! with the number of missing files, and then removes those tracks along
+//! with the albums and artists left without tracks; the toast reporting the
+//! removal offers to undo it. "Duplicate Tracks" reviews copies of the same
+//! recording.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        ActionRow, AlertDialog, PreferencesGroup, PreferencesPage,
+        ResponseAppearance::Destructive,
+        Spinner,
+        glib::spawn_future_local,
+        gtk::{Align::Center, Button},
+        prelude::{
+            ActionRowExt, AdwDialogExt, AlertDialogExt, ButtonExt, PreferencesGroupExt,
+            PreferencesPageExt, WidgetExt,
+        },
+    },
+    tokio::spawn,
+    tracing::{info, warn},
+};
+
+use crate::{
+    app::AppState,
+    library::scanner::{LibraryScanner, RescanReport},
+    storage::{StorageError, prune::PruneReport},
+    ui::duplicates::build_duplicates_row,
+};
+
+/// Subtitle of the rescan row while idle.
+const RESCAN_SUBTITLE: &str = "Find new, changed, and removed files in every library folder";
+
+/// Build the Library > Maintenance group.
+pub fn build_cleanup_group(page: &PreferencesPage, state: &Arc<AppState>) {
+    let group = PreferencesGroup::new();
+    group.set_title("Maintenance");
+    group.add(&build_rescan_row(state));
+
+    let check_btn = Button::builder().label("Check").valign(Center).build();
+    let row = ActionRow::builder()
+        .title("Remove Missing Files")
+        .subtitle("Remove tracks whose files were deleted or moved outside the app")
+        .build();
+    row.add_suffix(&check_btn);
+    row.set_activatable_widget(Some(&check_btn));
+
+    let state_check = Arc::clone(state);
+    check_btn.connect_clicked(move |btn| {
+        btn.set_sensitive(false);
+        spawn_future_local(check_missing_files(Arc::clone(&state_check), btn.clone()));
+    });
+
+    group.add(&row);
+    group.add(&build_duplicates_row(state));
+    page.add(&group);
+}
+
+/// Build the "Rescan Library" row with its "Scan Now" button.
+fn build_rescan_row(state: &Arc<AppState>) -> ActionRow {
+    let scan_btn = Button::builder().label("Scan Now").valign(Center).build();
+    let spinner = Spinner::builder().visible(false).build();
+    let row = ActionRow::builder()
+        .title("Rescan Library")
+        .subtitle(RESCAN_SUBTITLE)
+        .build();
+    row.add_suffix(&spinner);
+    row.add_suffix(&scan_btn);
+    row.set_activatable_widget(Some(&scan_btn));
+
+    let state = Arc::clone(state);
+    let row_scan = row.clone();
+    scan_btn.connect_clicked(move |btn| {
+        btn.set_sensitive(false);
+        spinner.set_visible(true);
+        row_scan.set_subtitle("Rescanning\u{2026} progress is shown in the status bar");
+        let (btn, spinner, row) = (btn.clone(), spinner.clone(), row_scan.clone());
+        let state = Arc::clone(&state);
+        spawn_future_local(async move {
+            rescan_library(&state).await;
+            spinner.set_visible(false);
+            row.set_subtitle(RESCAN_SUBTITLE);
+            btn.set_sensitive(true);
+        });
+    });
+    row
+}
+
+/// Rescan every library directory and report the outcome.
+async fn rescan_library(state: &AppState) {
+    let scanner = Arc::clone(&state.scanner);
+    let message = match spawn(async move { scanner.rescan_all().await }).await {
+        Ok(Ok(Some(report))) => {
+            if let Err(e) = state.refresh_tx.send(()) {
+                warn!(error = %e, "Failed to send refresh signal");
+            }
+            rescan_summary(&report)
+        }
+        Ok(Ok(None)) => "A rescan is already running".to_string(),
+        Ok(Err(e)) => {
+            warn!(error = %e, "Library rescan failed");
+            format!("Could not rescan the library: {e}")
+        }
+        Err(e) => {
+            warn!(error = %e, "Library rescan task panicked");
+            "Could not rescan the library".to_string()
+        }
+    };
+    state.send_toast(message).await;
+}
+
+/// Look for missing files and ask before removing them.
+async fn check_missing_files(state: Arc<AppState>, button: Button) {
+    let missing = match state.storage.find_missing_tracks().await {
+        Ok(missing) => missing,
+        Err(e) => {
+            warn!(error = %e, "Failed to check for missing files");
+            state
+                .send_toast(format!("Could not check for missing files: {e}"))
+                .await;
+            button.set_sensitive(true);
+            return;
+        }
+    };
+    info!(count = missing.len(), "Missing files found");
+    if missing.is_empty() {
+        state.send_toast("No missing files found".to_string()).await;
+        button.set_sensitive(true);
+        return;
+    }
+    confirm_removal(&state, &button, missing);
+}
+
+/// Ask whether the `missing` tracks should be removed from the library.
+fn confirm_removal(state: &Arc<AppState>, button: &Button, missing: Vec<i64>) {
+    let count = u64::try_from(missing.len()).unwrap_or(u64::MAX);
+    let dialog = AlertDialog::new(
+        Some(&format!("Remove {}?", count_label(count, "Missing Track"))),
+        Some(
+            "Their files no longer exist. Albums and artists left without tracks are removed \
+             too. The files themselves are not touched.",
+        ),
+    );
+    dialog.add_response("cancel", "Cancel");
+    dialog.add_response("remove", "Remove");
+    dialog.set_response_appearance("remove", Destructive);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+
+    let state = Arc::clone(state);
+    let button_response = button.clone();
+    dialog.connect_response(None, move |_, response| {
+        if response == "remove" {
+            spawn_future_local(remove_missing(Arc::clone(&state), missing.clone()));
+        }
+        button_response.set_sensitive(true);
+    });
+    dialog.present(Some(button));
+}
+
+/// Remove the `missing` tracks, refresh the library views, and offer to
+/// undo the removal.
+///
+/// Nothing is removed when the tracks cannot be copied for undo first.
+async fn remove_missing(state: Arc<AppState>, missing: Vec<i64>) {
+    let removal = async {
+        let snapshot = state.storage.snapshot_tracks(&missing).await?;
+        let report = state.storage.remove_tracks(&missing).await?;
+        Ok::<_, StorageError>((snapshot, report))
+    };
+    match removal.await {
+        Ok((snapshot, report)) => {
+            if let Err(e) = state.refresh_tx.send(()) {
+                warn!(error = %e, "Failed to send refresh signal");
+            }
+            state.undo.push(prune_summary(report), snapshot);
+        }
+        Err(e) => {
+            warn!(error = %e, "Failed to remove missing files");
+            state
+                .send_toast(format!("Could not remove missing files: {e}"))
+                .await;
+        }
+    }
+}
+
+/// Toast text describing what a prune removed.
+fn prune_summary(report: PruneReport) -> String {
+    format!(
+        "Removed {}, {}, and {}",
+        count_label(report.tracks_removed, "track"),
+        count_label(report.albums_removed, "album"),
+        count_label(report.artists_removed, "artist")
+    )
+}
+
+/// Toast text describing what a rescan did.
+fn rescan_summary(report: &RescanReport) -> String {
+    let count = |len: usize| u64::try_from(len).unwrap_or(u64::MAX);
+    let mut parts = vec![format!(
+        "Rescanned {}",
+        count_label(count(report.directories_scanned), "folder")
+    )];
+    if report.pruned != PruneReport::default() {
+        parts.push(prune_summary(report.pruned));
+    }
+    if !report.unreachable.is_empty() {
+        parts.push(format!(
+            "Skipped {} that could not be reached",
+            count_label(count(report.unreachable.len()), "folder")
+        ));
+    }
+    parts.join(". ")
+}
+
+/// `count` followed by `noun`, pluralized unless `count` is one.
+fn count_label(count: u64, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::{
+        library::scanner::RescanReport,
+        storage::prune::PruneReport,
+        ui::cleanup::{prune_summary, rescan_summary},
+    };
+
+    #[test]
+    fn summary_lists_removed_rows() {
+        let report = PruneReport {
+            tracks_removed: 12,
+            albums_removed: 2,
+            artists_removed: 1,
+        };
+        assert_eq!(
+            prune_summary(report),
+            "Removed 12 tracks, 2 albums, and 1 artist",
+            "Summary must list every count"
+        );
+    }
+
+    #[test]
+    fn rescan_summary_mentions_removals_and_skips() {
+        let quiet = RescanReport {
+            directories_scanned: 1,
+            ..RescanReport::default()
+        };
+        assert_eq!(
+            rescan_summary(&quiet),
+            "Rescanned 1 folder",
+            "Nothing else to report"
+        );
+
+        let report = RescanReport {
+            directories_scanned: 2,
+            unreachable: vec![PathBuf::from("/mnt/share")],
+            pruned: PruneReport {
+                tracks_removed: 3,
+                albums_removed: 1,
+                artists_removed: 0,
+            },
+        };
+        assert_eq!(
+            rescan_summary(&report),
+            "Rescanned 2 folders. Removed 3 tracks, 1 album, and 0 artists. \
+             Skipped 1 folder that could not be reached",
+            "Summary must list removals and unreachable folders"
+        );
+    }
+}
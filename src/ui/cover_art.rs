@@ -0,0 +1,56 @@
+//! Library > Cover Art group of the preferences dialog.
+//!
+//! Albums without embedded artwork always use an image from their folder.
+//! The switch lets folder images win over embedded artwork for albums
+//! scanned from then on.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        PreferencesGroup, PreferencesPage, SwitchRow,
+        glib::spawn_future_local,
+        prelude::{ActionRowExt, PreferencesGroupExt, PreferencesPageExt, PreferencesRowExt},
+    },
+    tracing::{error, info},
+};
+
+use crate::{app::AppState, storage::database::SqliteStorage};
+
+/// Build the Library > Cover Art group.
+pub fn build_artwork_group(page: &PreferencesPage, state: &Arc<AppState>) {
+    let group = PreferencesGroup::new();
+    group.set_title("Cover Art");
+    group.set_description(Some(
+        "Albums without embedded artwork always use an image from their folder",
+    ));
+
+    let sidecar_row = SwitchRow::new();
+    sidecar_row.set_title("Prefer Folder Images");
+    sidecar_row.set_subtitle(
+        "Use cover.jpg, folder.jpg, or front.png over embedded artwork for albums scanned \
+         from now on",
+    );
+    sidecar_row.set_active(state.storage.get_prefer_sidecar_artwork());
+
+    let state_sidecar = Arc::clone(state);
+    sidecar_row.connect_active_notify(move |row| {
+        let prefer = row.is_active();
+        info!(prefer, "Artwork source preference changed");
+        state_sidecar.scanner.set_prefer_sidecar_artwork(prefer);
+        spawn_future_local(save_sidecar_artwork_setting(
+            Arc::clone(&state_sidecar.storage),
+            prefer,
+        ));
+    });
+
+    group.add(&sidecar_row);
+    page.add(&group);
+}
+
+/// Persist the album cover source preference, logging on failure.
+async fn save_sidecar_artwork_setting(storage: Arc<SqliteStorage>, prefer: bool) {
+    if let Err(e) = storage.set_prefer_sidecar_artwork(prefer).await {
+        error!(error = %e, "Failed to save artwork preference");
+    }
+}
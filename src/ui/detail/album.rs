@@ -1,10 +1,11 @@
 //! Album detail page with artwork, metadata, and track listing.
 
-use std::{boxed::Box, sync::Arc};
+use std::{boxed::Box, rc::Rc, sync::Arc};
 
 use {
     async_channel::{Receiver, Sender, unbounded},
     libadwaita::{
+        gdk::MemoryTexture,
         glib::{
             ControlFlow::{self, Break, Continue},
             MainContext, idle_add_local,
@@ -28,12 +29,25 @@ use {
 
 use crate::{
     app::{AppState, NavigationEvent},
-    playback::control::PlaybackController,
+    library::{numbering::needs_renumbering, tag_writer::TagChanges},
+    playback::{
+        control::PlaybackController,
+        engine::PlaybackEvent::{self, Stopped, TrackStarted},
+    },
     storage::{Storage, Track},
     ui::{
         ArtworkDecodeRequest, DecodedCover, build_album_play_button,
-        detail::common::{build_detail_wrapper, build_scroll_content, fill_track_list_batch},
-        library::albums::{album_play_icon, toggle_or_play_album},
+        detail::{
+            album_cover::build_cover_controls,
+            common::{
+                build_detail_wrapper, build_scroll_content, fill_track_list_batch,
+                highlight_playing_track, is_multi_disc, numbers_within_disc, set_disc_headers,
+            },
+            dynamic_range::build_dr_controls,
+            edit_info::open_edit_info,
+            track_order::open_fix_track_order,
+        },
+        library::albums::{album_play_icon, play_album, toggle_or_play_album},
         raw_to_texture,
     },
 };
@@ -59,6 +73,14 @@ struct AlbumDetailContent {
     genre_label: Label,
     /// Format summary label.
     format_label: Label,
+    /// Row of metadata labels and album actions.
+    meta_box: GtkBox,
+    /// Button opening the "Edit Info" dialog.
+    edit_button: Button,
+    /// Button playing the album in shuffled order.
+    shuffle_button: Button,
+    /// Button opening "Fix Track Order", shown when numbering is broken.
+    fix_order_button: Button,
     /// Track listing container.
     track_list: ListBox,
 }
@@ -77,10 +99,25 @@ struct AlbumDetailWidgets<'a> {
     genre_label: &'a Label,
     /// Format summary label (sample rate, bit depth, etc.).
     format_label: &'a Label,
+    /// Button opening "Fix Track Order".
+    fix_order_button: &'a Button,
     /// Track listing container.
     track_list: &'a ListBox,
 }
 
+/// Labels refreshed in place after an "Edit Info" save.
+#[derive(Clone)]
+struct EditableLabels {
+    /// Album title label.
+    title: Label,
+    /// Artist name label.
+    artist: Label,
+    /// Release year label.
+    year: Label,
+    /// Genre label.
+    genre: Label,
+}
+
 /// Build the scrollable content area with album widgets.
 fn build_album_content() -> AlbumDetailContent {
     let (scroll, content) = build_scroll_content();
@@ -152,6 +189,31 @@ fn build_album_content() -> AlbumDetailContent {
     format_label.update_property(&[PropertyLabel("Audio format")]);
     meta_box.append(&format_label);
 
+    let edit_button = Button::builder()
+        .icon_name("document-edit-symbolic")
+        .tooltip_text("Edit Info")
+        .css_classes(["flat", "circular"])
+        .build();
+    edit_button.update_property(&[PropertyLabel("Edit album info")]);
+    meta_box.append(&edit_button);
+
+    let shuffle_button = Button::builder()
+        .icon_name("media-playlist-shuffle-symbolic")
+        .tooltip_text("Shuffle")
+        .css_classes(["flat", "circular"])
+        .build();
+    shuffle_button.update_property(&[PropertyLabel("Shuffle album")]);
+    meta_box.append(&shuffle_button);
+
+    let fix_order_button = Button::builder()
+        .icon_name("view-sort-ascending-symbolic")
+        .tooltip_text("Fix Track Order")
+        .css_classes(["flat", "circular"])
+        .visible(false)
+        .build();
+    fix_order_button.update_property(&[PropertyLabel("Fix track order")]);
+    meta_box.append(&fix_order_button);
+
     content.append(&meta_box);
 
     let tracks_header = Label::builder()
@@ -176,6 +238,10 @@ fn build_album_content() -> AlbumDetailContent {
         year_label,
         genre_label,
         format_label,
+        meta_box,
+        edit_button,
+        shuffle_button,
+        fix_order_button,
         track_list,
     }
 }
@@ -191,6 +257,15 @@ pub fn build_album_detail(
 
     let content = build_album_content();
     wrapper.append(&content.scroll);
+    content.meta_box.append(&build_dr_controls(state, album_id));
+    let (cover_state, cover_artwork) = (Arc::clone(state), content.artwork.clone());
+    let on_cover_changed = Rc::new(move |path: Option<&str>| match path {
+        Some(path) => show_artwork(&cover_state, album_id, path, &cover_artwork),
+        None => cover_artwork.set_paintable(None::<&MemoryTexture>),
+    });
+    content
+        .meta_box
+        .append(&build_cover_controls(state, album_id, on_cover_changed));
 
     content
         .play_button
@@ -213,18 +288,61 @@ pub fn build_album_detail(
         });
     });
 
+    let shuffle_state = Arc::clone(state);
+    content.shuffle_button.connect_clicked(move |_| {
+        let s = Arc::clone(&shuffle_state);
+        spawn_future_local(async move {
+            play_album(&s, album_id, true).await;
+        });
+    });
+
     let ev_rx = state.playback.subscribe();
     let ev_btn = content.play_button.clone();
     let ev_state = Arc::clone(state);
     let ev_aid = album_id;
+    let ev_list = content.track_list.clone();
     MainContext::default().spawn_local(async move {
-        while let Ok(_event) = ev_rx.recv().await {
+        while let Ok(event) = ev_rx.recv().await {
+            highlight_on_event(&ev_list, &event);
             let icon = album_play_icon(&ev_state, ev_aid);
             let btn = ev_btn.clone();
             idle_add_local(move || update_detail_play_button(&btn, icon));
         }
     });
 
+    let labels = EditableLabels {
+        title: content.title_label.clone(),
+        artist: content.artist_label.clone(),
+        year: content.year_label.clone(),
+        genre: content.genre_label.clone(),
+    };
+    let edit_state = Arc::clone(state);
+    content.edit_button.connect_clicked(move |button| {
+        let labels = labels.clone();
+        let saved_state = Arc::clone(&edit_state);
+        let on_saved = Rc::new(move |changes: &TagChanges| {
+            apply_saved_changes(&saved_state, album_id, &labels, changes);
+        });
+        open_edit_info(button.upcast_ref(), &edit_state, album_id, on_saved);
+    });
+
+    let order_state = Arc::clone(state);
+    let order_list = content.track_list.clone();
+    content.fix_order_button.connect_clicked(move |button| {
+        let (state, list, button_ref) =
+            (Arc::clone(&order_state), order_list.clone(), button.clone());
+        let on_fixed = Rc::new(move || {
+            list.remove_all();
+            spawn_future_local(reload_tracks(
+                Arc::clone(&state),
+                album_id,
+                list.clone(),
+                button_ref.clone(),
+            ));
+        });
+        open_fix_track_order(button.upcast_ref(), &order_state, album_id, on_fixed);
+    });
+
     let sc = Arc::clone(state);
     spawn_future_local(async move {
         populate_album_detail(
@@ -237,6 +355,7 @@ pub fn build_album_detail(
                 year_label: &content.year_label,
                 genre_label: &content.genre_label,
                 format_label: &content.format_label,
+                fix_order_button: &content.fix_order_button,
                 track_list: &content.track_list,
             },
         )
@@ -246,6 +365,46 @@ pub fn build_album_detail(
     wrapper.upcast()
 }
 
+/// Move the playing-track highlight when another track starts or playback
+/// stops.
+fn highlight_on_event(list: &ListBox, event: &PlaybackEvent) {
+    match event {
+        TrackStarted { track_id } => highlight_playing_track(list, Some(*track_id)),
+        Stopped => highlight_playing_track(list, None),
+        _ => {}
+    }
+}
+
+/// Show saved tag edits on the detail page without reloading it.
+///
+/// The edited year is the edition year, which is not the one shown when
+/// the original release year is preferred, so the year label is refreshed
+/// from storage.
+fn apply_saved_changes(
+    state: &Arc<AppState>,
+    album_id: i64,
+    labels: &EditableLabels,
+    changes: &TagChanges,
+) {
+    if let Some(title) = &changes.album {
+        labels.title.set_label(title);
+    }
+    if let Some(artist) = &changes.album_artist {
+        labels.artist.set_label(artist);
+    }
+    if changes.year.is_some() {
+        spawn_future_local(refresh_year_label(
+            Arc::clone(state),
+            album_id,
+            labels.year.clone(),
+        ));
+    }
+    if let Some(genre) = &changes.genre {
+        labels.genre.set_label(genre);
+        labels.genre.set_visible(true);
+    }
+}
+
 /// Update the detail page play button icon via idle callback.
 fn update_detail_play_button(btn: &Button, icon: &'static str) -> ControlFlow {
     btn.set_icon_name(icon);
@@ -260,6 +419,26 @@ fn try_send_cover(tx: &Sender<DecodedCover>, decoded: Option<DecodedCover>) {
     }
 }
 
+/// Show `year` in the year label, hiding it when unknown.
+fn show_year(label: &Label, year: Option<i32>) {
+    if let Some(year) = year {
+        label.set_label(&year.to_string());
+    }
+    label.set_visible(year.is_some());
+}
+
+/// Reload the album and show the year it is displayed with.
+async fn refresh_year_label(state: Arc<AppState>, album_id: i64, label: Label) {
+    match state.storage.get_album(album_id).await {
+        Ok(Some(album)) => show_year(
+            &label,
+            album.display_year(state.storage.get_use_original_year()),
+        ),
+        Ok(None) => info!(album_id, "Album not found"),
+        Err(e) => warn!(error = %e, album_id, "Failed to reload album year"),
+    }
+}
+
 /// Poll for decoded artwork and apply it to the picture widget.
 fn poll_artwork(rx: &Receiver<DecodedCover>, artwork: &Picture) -> ControlFlow {
     rx.try_recv().map_or(Continue, |decoded| {
@@ -269,6 +448,22 @@ fn poll_artwork(rx: &Receiver<DecodedCover>, artwork: &Picture) -> ControlFlow {
     })
 }
 
+/// Decode the cover at `path` in the background and show it in `artwork`.
+fn show_artwork(state: &AppState, album_id: i64, path: &str, artwork: &Picture) {
+    let (tx, rx) = unbounded::<DecodedCover>();
+
+    state.cover_art_cache.request_decode(ArtworkDecodeRequest {
+        album_id,
+        path: path.to_string(),
+        size: DETAIL_COVER_SIZE,
+        on_complete: Box::new(move |_, decoded| try_send_cover(&tx, decoded)),
+        wanted: None,
+    });
+
+    let artwork = artwork.clone();
+    idle_add_local(move || poll_artwork(&rx, &artwork));
+}
+
 /// Load album data from storage and populate the detail UI elements.
 async fn populate_album_detail(
     state: &Arc<AppState>,
@@ -288,18 +483,7 @@ async fn populate_album_detail(
     };
 
     if let Some(path) = &album.artwork_path {
-        let path = path.clone();
-        let (tx, rx) = unbounded::<DecodedCover>();
-
-        state.cover_art_cache.request_decode(ArtworkDecodeRequest {
-            album_id,
-            path,
-            size: DETAIL_COVER_SIZE,
-            on_complete: Box::new(move |_, decoded| try_send_cover(&tx, decoded)),
-        });
-
-        let artwork = widgets.artwork.clone();
-        idle_add_local(move || poll_artwork(&rx, &artwork));
+        show_artwork(state, album_id, path, widgets.artwork);
     }
 
     widgets.title_label.set_label(&album.title);
@@ -310,12 +494,10 @@ async fn populate_album_detail(
     };
     widgets.artist_label.set_label(&artist_name);
 
-    if let Some(year) = album.year {
-        widgets.year_label.set_label(&year.to_string());
-        widgets.year_label.set_visible(true);
-    } else {
-        widgets.year_label.set_visible(false);
-    }
+    show_year(
+        widgets.year_label,
+        album.display_year(state.storage.get_use_original_year()),
+    );
 
     if let Some(genre) = &album.genre {
         widgets.genre_label.set_label(genre);
@@ -340,6 +522,22 @@ async fn populate_album_detail(
         format_info.summary_detailed(),
     ));
 
+    show_tracks(
+        state,
+        album_id,
+        widgets.track_list,
+        widgets.fix_order_button,
+    )
+    .await;
+}
+
+/// Reload the track list after the tracks were renumbered.
+async fn reload_tracks(state: Arc<AppState>, album_id: i64, list: ListBox, button: Button) {
+    show_tracks(&state, album_id, &list, &button).await;
+}
+
+/// Fill the track list and show "Fix Track Order" if numbering is broken.
+async fn show_tracks(state: &Arc<AppState>, album_id: i64, list: &ListBox, fix_order: &Button) {
     let tracks = match state.storage.get_tracks_by_album(album_id).await {
         Ok(t) => t,
         Err(e) => {
@@ -347,13 +545,15 @@ async fn populate_album_detail(
             return;
         }
     };
+    fix_order.set_visible(needs_renumbering(&tracks));
 
-    let track_list = widgets.track_list.clone();
-    let mut remaining: Vec<(Track, usize)> = tracks
-        .into_iter()
-        .enumerate()
-        .map(|(i, t)| (t, i + 1))
-        .collect::<Vec<_>>();
+    let track_list = list.clone();
+    let discs: Vec<i32> = tracks.iter().map(Track::disc).collect();
+    let numbers = numbers_within_disc(&discs);
+    if is_multi_disc(&tracks) {
+        set_disc_headers(&track_list, discs);
+    }
+    let mut remaining: Vec<(Track, usize)> = tracks.into_iter().zip(numbers).collect();
     remaining.reverse();
 
     let state = Arc::clone(state);
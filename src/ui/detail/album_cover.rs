@@ -0,0 +1,187 @@
+//! "Change Cover" and "Reset Cover" actions of the album detail page.
+//!
+//! A chosen image is copied into the artwork cache and shown instead of the
+//! artwork found by the scanner, in every view, until it is reset. Covers
+//! only ever come from local files.
+
+use std::{path::PathBuf, rc::Rc, sync::Arc};
+
+use {
+    libadwaita::{
+        gio::spawn_blocking,
+        glib::{object::Cast, spawn_future_local},
+        gtk::{
+            Box as GtkBox, Button, Orientation::Horizontal, Window,
+            accessible::Property::Label as PropertyLabel,
+        },
+        prelude::{AccessibleExtManual, BoxExt, ButtonExt, FileExt, WidgetExt},
+    },
+    tracing::{info, warn},
+};
+
+use crate::{
+    app::AppState, library::artwork::cache_album_cover, storage::Storage,
+    ui::detail::artist_profile::image_file_dialog,
+};
+
+/// Called with the artwork path the album shows after a change.
+type OnCoverChanged = Rc<dyn Fn(Option<&str>)>;
+
+/// Build the buttons choosing and resetting an album's cover.
+///
+/// # Arguments
+///
+/// * `state` - Application state
+/// * `album_id` - Album whose cover is changed
+/// * `on_changed` - Called with the artwork path shown after a change
+#[must_use]
+pub fn build_cover_controls(
+    state: &Arc<AppState>,
+    album_id: i64,
+    on_changed: OnCoverChanged,
+) -> GtkBox {
+    let container = GtkBox::builder().orientation(Horizontal).spacing(6).build();
+
+    let change = Button::builder()
+        .icon_name("image-x-generic-symbolic")
+        .tooltip_text("Change Cover")
+        .css_classes(["flat", "circular"])
+        .build();
+    change.update_property(&[PropertyLabel("Change album cover")]);
+    container.append(&change);
+
+    let reset = Button::builder()
+        .icon_name("edit-undo-symbolic")
+        .tooltip_text("Reset Cover")
+        .css_classes(["flat", "circular"])
+        .visible(false)
+        .build();
+    reset.update_property(&[PropertyLabel("Reset album cover")]);
+    container.append(&reset);
+
+    let (change_state, change_reset, change_done) =
+        (Arc::clone(state), reset.clone(), Rc::clone(&on_changed));
+    change.connect_clicked(move |button| {
+        let parent = button.root().and_then(|r| r.downcast::<Window>().ok());
+        spawn_future_local(pick_album_cover(
+            Arc::clone(&change_state),
+            parent,
+            album_id,
+            change_reset.clone(),
+            Rc::clone(&change_done),
+        ));
+    });
+
+    let reset_state = Arc::clone(state);
+    reset.connect_clicked(move |button| {
+        spawn_future_local(reset_album_cover(
+            Arc::clone(&reset_state),
+            album_id,
+            button.clone(),
+            Rc::clone(&on_changed),
+        ));
+    });
+
+    spawn_future_local(show_reset_if_overridden(Arc::clone(state), album_id, reset));
+    container
+}
+
+/// Show "Reset Cover" when the album has a user-chosen cover.
+async fn show_reset_if_overridden(state: Arc<AppState>, album_id: i64, reset: Button) {
+    match state.storage.get_album(album_id).await {
+        Ok(Some(album)) => reset.set_visible(album.cover_override.is_some()),
+        Ok(None) => info!(album_id, "Album not found"),
+        Err(e) => warn!(error = %e, album_id, "Failed to load album cover"),
+    }
+}
+
+/// Ask for an image file and set it as the album cover.
+async fn pick_album_cover(
+    state: Arc<AppState>,
+    parent: Option<Window>,
+    album_id: i64,
+    reset: Button,
+    on_changed: OnCoverChanged,
+) {
+    let file = match image_file_dialog("Choose Album Cover")
+        .open_future(parent.as_ref())
+        .await
+    {
+        Ok(file) => file,
+        Err(e) => {
+            info!(error = %e, "Album cover selection cancelled");
+            return;
+        }
+    };
+    let Some(source) = file.path() else {
+        warn!("Selected album cover has no local path");
+        return;
+    };
+    save_album_cover(&state, album_id, source, &reset, &on_changed).await;
+}
+
+/// Copy the chosen image into the artwork cache and record it for the album.
+async fn save_album_cover(
+    state: &AppState,
+    album_id: i64,
+    source: PathBuf,
+    reset: &Button,
+    on_changed: &OnCoverChanged,
+) {
+    let cached = match spawn_blocking(move || cache_album_cover(album_id, &source)).await {
+        Ok(Ok(path)) => path.to_string_lossy().into_owned(),
+        Ok(Err(e)) => {
+            warn!(error = %e, album_id, "Failed to cache album cover");
+            state
+                .send_toast(format!("Could not set album cover: {e}"))
+                .await;
+            return;
+        }
+        Err(e) => {
+            warn!(error = ?e, album_id, "Album cover copy panicked");
+            return;
+        }
+    };
+    if let Err(e) = state.storage.set_album_cover(album_id, Some(&cached)).await {
+        warn!(error = %e, album_id, "Failed to save album cover");
+        state
+            .send_toast("Could not save the album cover".to_string())
+            .await;
+        return;
+    }
+    info!(album_id, path = %cached, "Album cover changed");
+    reset.set_visible(true);
+    refresh_cover(state, album_id);
+    on_changed(Some(&cached));
+}
+
+/// Drop the user-chosen cover and show the scanned artwork again.
+async fn reset_album_cover(
+    state: Arc<AppState>,
+    album_id: i64,
+    reset: Button,
+    on_changed: OnCoverChanged,
+) {
+    if let Err(e) = state.storage.set_album_cover(album_id, None).await {
+        warn!(error = %e, album_id, "Failed to reset album cover");
+        state
+            .send_toast("Could not reset the album cover".to_string())
+            .await;
+        return;
+    }
+    info!(album_id, "Album cover reset");
+    reset.set_visible(false);
+    refresh_cover(&state, album_id);
+    match state.storage.get_album(album_id).await {
+        Ok(album) => on_changed(album.and_then(|a| a.artwork_path).as_deref()),
+        Err(e) => warn!(error = %e, album_id, "Failed to reload album artwork"),
+    }
+}
+
+/// Forget the decoded cover and refresh the library views to show the new one.
+fn refresh_cover(state: &AppState, album_id: i64) {
+    state.cover_art_cache.remove(album_id);
+    if let Err(e) = state.refresh_tx.send(()) {
+        warn!(error = %e, "Failed to send refresh signal");
+    }
+}
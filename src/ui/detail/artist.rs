@@ -1,10 +1,12 @@
-//! Artist detail page with album groupings and track listings.
+//! Artist detail page with the artist image, biography, and albums with
+//! their track listings.
 
-use std::{boxed::Box, collections::HashMap, sync::Arc};
+use std::{boxed::Box, collections::HashMap, path::Path, rc::Rc, sync::Arc};
 
 use {
     async_channel::{Receiver, Sender, unbounded},
     libadwaita::{
+        Avatar,
         glib::{
             ControlFlow::{self, Break, Continue},
             idle_add_local,
@@ -12,15 +14,15 @@ use {
             spawn_future_local,
         },
         gtk::{
-            Align::Start,
-            Box as GtkBox,
+            Align::{Center, Start},
+            Box as GtkBox, Button,
             ContentFit::Cover,
             Label, ListBox, ListBoxRow,
             Orientation::{Horizontal, Vertical},
             Picture, Widget,
             accessible::Property::Label as PropertyLabel,
             pango::EllipsizeMode::End,
-            prelude::{AccessibleExtManual, BoxExt},
+            prelude::{AccessibleExtManual, BoxExt, ButtonExt, WidgetExt},
         },
     },
     tokio::join,
@@ -29,14 +31,146 @@ use {
 
 use crate::{
     app::{AppState, NavigationEvent},
-    storage::{Album, FormatInfo, Storage, Track},
+    library::artwork::find_artist_image,
+    storage::{Album, Artist, FormatInfo, Storage, Track},
     ui::{
         ArtworkDecodeRequest, DecodedCover,
-        detail::common::{build_detail_wrapper, build_scroll_content, fill_track_list_batch},
+        detail::{
+            artist_profile::{choose_artist_image, open_bio_editor},
+            common::{build_detail_wrapper, build_scroll_content, fill_track_list_batch},
+        },
+        library::artists::play_artist,
         raw_to_texture,
     },
 };
 
+/// Size of the artist image in the detail header in pixels.
+const ARTIST_IMAGE_SIZE: i32 = 128;
+
+/// Header of the artist detail page.
+struct ArtistHeader {
+    /// Artist image, showing the initials until an image has loaded.
+    avatar: Avatar,
+    /// Artist name label.
+    name_label: Label,
+    /// Album count label.
+    album_count_label: Label,
+    /// Biography label, hidden while the artist has no biography.
+    bio_label: Label,
+    /// Button playing every album of the artist.
+    play_button: Button,
+    /// Button playing all of the artist's tracks shuffled.
+    shuffle_button: Button,
+    /// Button choosing the artist image.
+    image_button: Button,
+    /// Button opening the biography editor.
+    bio_button: Button,
+}
+
+impl ArtistHeader {
+    /// Build the header and append it to `content`.
+    fn new(content: &GtkBox) -> Self {
+        let header_box = GtkBox::builder()
+            .orientation(Horizontal)
+            .spacing(18)
+            .build();
+
+        let avatar = Avatar::new(ARTIST_IMAGE_SIZE, None, true);
+        avatar.set_valign(Start);
+        avatar.update_property(&[PropertyLabel("Artist image")]);
+        header_box.append(&avatar);
+
+        let info_box = GtkBox::builder()
+            .orientation(Vertical)
+            .spacing(6)
+            .hexpand(true)
+            .valign(Center)
+            .build();
+
+        let name_label = Label::builder()
+            .css_classes(["title-2", "heading"])
+            .ellipsize(End)
+            .halign(Start)
+            .build();
+        name_label.update_property(&[PropertyLabel("Artist name")]);
+        info_box.append(&name_label);
+
+        let album_count_label = Label::builder()
+            .css_classes(["dim-label", "body"])
+            .halign(Start)
+            .build();
+        album_count_label.update_property(&[PropertyLabel("Album count")]);
+        info_box.append(&album_count_label);
+
+        let bio_label = Label::builder()
+            .css_classes(["body"])
+            .wrap(true)
+            .xalign(0.0)
+            .selectable(true)
+            .visible(false)
+            .build();
+        bio_label.update_property(&[PropertyLabel("Artist biography")]);
+        info_box.append(&bio_label);
+
+        let button_box = GtkBox::builder().orientation(Horizontal).spacing(6).build();
+        let play_button = Button::builder()
+            .label("Play All")
+            .css_classes(["pill", "suggested-action"])
+            .build();
+        play_button.update_property(&[PropertyLabel("Play all albums")]);
+        button_box.append(&play_button);
+
+        let shuffle_button = Button::builder()
+            .icon_name("media-playlist-shuffle-symbolic")
+            .tooltip_text("Shuffle")
+            .css_classes(["flat", "circular"])
+            .valign(Center)
+            .build();
+        shuffle_button.update_property(&[PropertyLabel("Shuffle all tracks")]);
+        button_box.append(&shuffle_button);
+
+        let image_button = Button::builder()
+            .icon_name("image-x-generic-symbolic")
+            .tooltip_text("Set Image")
+            .css_classes(["flat", "circular"])
+            .build();
+        image_button.update_property(&[PropertyLabel("Set artist image")]);
+        button_box.append(&image_button);
+
+        let bio_button = Button::builder()
+            .icon_name("document-edit-symbolic")
+            .tooltip_text("Edit Biography")
+            .css_classes(["flat", "circular"])
+            .build();
+        bio_button.update_property(&[PropertyLabel("Edit artist biography")]);
+        button_box.append(&bio_button);
+        info_box.append(&button_box);
+
+        header_box.append(&info_box);
+        content.append(&header_box);
+
+        Self {
+            avatar,
+            name_label,
+            album_count_label,
+            bio_label,
+            play_button,
+            shuffle_button,
+            image_button,
+            bio_button,
+        }
+    }
+
+    /// Show the artist's name, album count, and biography.
+    fn show(&self, artist: &Artist) {
+        self.avatar.set_text(Some(artist.name.as_str()));
+        self.name_label.set_label(&artist.name);
+        self.album_count_label
+            .set_label(&format!("{} albums", artist.album_count));
+        show_bio(&self.bio_label, artist.bio.as_deref());
+    }
+}
+
 /// Build the artist detail page widget.
 #[must_use]
 pub fn build_artist_detail(
@@ -48,20 +182,8 @@ pub fn build_artist_detail(
 
     let (scroll, content) = build_scroll_content();
 
-    let name_label = Label::builder()
-        .css_classes(["title-2", "heading"])
-        .ellipsize(End)
-        .halign(Start)
-        .build();
-    name_label.update_property(&[PropertyLabel("Artist name")]);
-    content.append(&name_label);
-
-    let album_count_label = Label::builder()
-        .css_classes(["dim-label", "body"])
-        .halign(Start)
-        .build();
-    album_count_label.update_property(&[PropertyLabel("Album count")]);
-    content.append(&album_count_label);
+    let header = ArtistHeader::new(&content);
+    connect_header_buttons(state, artist_id, &header);
 
     let albums_container = GtkBox::builder().orientation(Vertical).spacing(18).build();
     content.append(&albums_container);
@@ -71,14 +193,7 @@ pub fn build_artist_detail(
 
     let sc = Arc::clone(state);
     spawn_future_local(async move {
-        populate_artist_detail(
-            &sc,
-            artist_id,
-            &name_label,
-            &album_count_label,
-            &albums_container,
-        )
-        .await;
+        populate_artist_detail(&sc, artist_id, &header, &albums_container).await;
     });
 
     wrapper.upcast()
@@ -88,11 +203,10 @@ pub fn build_artist_detail(
 async fn populate_artist_detail(
     state: &Arc<AppState>,
     artist_id: i64,
-    name_label: &Label,
-    album_count_label: &Label,
+    header: &ArtistHeader,
     albums_container: &GtkBox,
 ) {
-    let artist = match state.storage.get_artist(artist_id).await {
+    let mut artist = match state.storage.get_artist(artist_id).await {
         Ok(Some(a)) => a,
         Ok(None) => {
             info!(artist_id, "Artist not found");
@@ -104,16 +218,17 @@ async fn populate_artist_detail(
         }
     };
 
-    name_label.set_label(&artist.name);
-    album_count_label.set_label(&format!("{} albums", artist.album_count));
-
     let albums = match state.storage.get_albums_by_artist(artist_id).await {
         Ok(a) => a,
         Err(e) => {
             warn!(error = %e, artist_id, "Failed to load artist albums");
+            header.show(&artist);
             return;
         }
     };
+    // Albums of collaborations count too when they are split.
+    artist.album_count = i32::try_from(albums.len()).unwrap_or(i32::MAX);
+    header.show(&artist);
 
     let album_ids: Vec<i64> = albums.iter().map(|a| a.id).collect();
     let (format_info_map, all_tracks) = join!(
@@ -123,6 +238,14 @@ async fn populate_artist_detail(
     let format_info_map = format_info_map.unwrap_or_default();
     let all_tracks = all_tracks.unwrap_or_default();
 
+    let image = artist
+        .image_path
+        .filter(|p| Path::new(p).is_file())
+        .or_else(|| local_artist_image(&all_tracks));
+    if let Some(path) = image {
+        load_artist_image(state, artist_id, path, &header.avatar);
+    }
+
     let mut tracks_by_album: HashMap<i64, Vec<Track>> = HashMap::new();
     for track in &all_tracks {
         tracks_by_album
@@ -151,6 +274,83 @@ async fn populate_artist_detail(
     }
 }
 
+/// Play the artist's albums, in order or shuffled, when `button` is clicked.
+fn connect_play_button(state: &Arc<AppState>, artist_id: i64, button: &Button, shuffled: bool) {
+    let play_state = Arc::clone(state);
+    button.connect_clicked(move |_| {
+        let state = Arc::clone(&play_state);
+        spawn_future_local(async move { play_artist(&state, artist_id, shuffled).await });
+    });
+}
+
+/// Start playback, open the image chooser, and open the biography editor
+/// from the header buttons.
+fn connect_header_buttons(state: &Arc<AppState>, artist_id: i64, header: &ArtistHeader) {
+    connect_play_button(state, artist_id, &header.play_button, false);
+    connect_play_button(state, artist_id, &header.shuffle_button, true);
+
+    let image_state = Arc::clone(state);
+    let avatar = header.avatar.clone();
+    header.image_button.connect_clicked(move |button| {
+        let decode_state = Arc::clone(&image_state);
+        let avatar = avatar.clone();
+        let on_saved = Rc::new(move |path: &str| {
+            load_artist_image(&decode_state, artist_id, path.to_string(), &avatar);
+        });
+        choose_artist_image(button.upcast_ref(), &image_state, artist_id, on_saved);
+    });
+
+    let bio_state = Arc::clone(state);
+    let bio_label = header.bio_label.clone();
+    header.bio_button.connect_clicked(move |button| {
+        let label = bio_label.clone();
+        let on_saved = Rc::new(move |bio: Option<&str>| show_bio(&label, bio));
+        open_bio_editor(
+            button.upcast_ref(),
+            &bio_state,
+            artist_id,
+            &bio_label.label(),
+            on_saved,
+        );
+    });
+}
+
+/// Show `bio` in `label`, hiding the label when there is no biography.
+fn show_bio(label: &Label, bio: Option<&str>) {
+    label.set_label(bio.unwrap_or_default());
+    label.set_visible(bio.is_some());
+}
+
+/// Find an image of the artist next to their first track.
+fn local_artist_image(tracks: &[Track]) -> Option<String> {
+    let track = tracks.first()?;
+    find_artist_image(Path::new(&track.audio.file_path)).map(|p| p.to_string_lossy().into_owned())
+}
+
+/// Decode the artist image at `path` off the main thread and show it in
+/// `avatar`.
+fn load_artist_image(state: &AppState, artist_id: i64, path: String, avatar: &Avatar) {
+    let (tx, rx) = unbounded::<DecodedCover>();
+    state.cover_art_cache.request_decode(ArtworkDecodeRequest {
+        album_id: artist_id,
+        path,
+        size: ARTIST_IMAGE_SIZE,
+        on_complete: Box::new(move |_, decoded| try_send_artist_cover(&tx, decoded)),
+        wanted: None,
+    });
+    let avatar = avatar.clone();
+    idle_add_local(move || poll_artist_image(&rx, &avatar));
+}
+
+/// Poll for the decoded artist image and apply it to the avatar.
+fn poll_artist_image(rx: &Receiver<DecodedCover>, avatar: &Avatar) -> ControlFlow {
+    rx.try_recv().map_or(Continue, |decoded| {
+        let texture = raw_to_texture(&decoded);
+        avatar.set_custom_image(Some(&texture));
+        Break
+    })
+}
+
 /// When a row is selected in one album's track list, unselect all rows
 /// in the other albums' track lists to keep a single active highlight.
 fn clear_other_lists(row: Option<&ListBoxRow>, others: &[ListBox]) {
@@ -221,6 +421,7 @@ fn build_album_section(
             path,
             size: 60,
             on_complete: Box::new(move |_, decoded| try_send_artist_cover(&tx, decoded)),
+            wanted: None,
         });
 
         idle_add_local(move || poll_artist_artwork(&rx, &thumb));
@@ -0,0 +1,213 @@
+//! Dialogs for the artist image and biography on the artist detail page.
+//!
+//! A chosen image is copied into the artwork cache, so the artist keeps it
+//! when the original file is moved or deleted. Artists without one fall back
+//! to an `artist.*` or `folder.*` image found in their directory.
+
+use std::{path::PathBuf, rc::Rc, sync::Arc};
+
+use {
+    libadwaita::{
+        AlertDialog,
+        ResponseAppearance::Suggested,
+        gio::{ListStore, spawn_blocking},
+        glib::{object::Cast, spawn_future_local},
+        gtk::{
+            FileDialog, FileFilter, ScrolledWindow, TextView, Widget, Window, WrapMode::WordChar,
+        },
+        prelude::{
+            AdwDialogExt, AlertDialogExt, AlertDialogExtManual, FileExt, TextBufferExt,
+            TextViewExt, WidgetExt,
+        },
+    },
+    tracing::{info, warn},
+};
+
+use crate::{app::AppState, library::artwork::cache_artist_image};
+
+/// Response id of the cancel button.
+const RESPONSE_CANCEL: &str = "cancel";
+
+/// Response id of the save button.
+const RESPONSE_SAVE: &str = "save";
+
+/// Called with the new biography, `None` when it was cleared.
+type OnBioSaved = Rc<dyn Fn(Option<&str>)>;
+
+/// Let the user pick an image for an artist.
+///
+/// # Arguments
+///
+/// * `parent` - Widget whose window the file chooser is attached to
+/// * `state` - Application state
+/// * `artist_id` - Artist to set the image for
+/// * `on_saved` - Called with the cached image path after a successful save
+pub fn choose_artist_image(
+    parent: &Widget,
+    state: &Arc<AppState>,
+    artist_id: i64,
+    on_saved: Rc<dyn Fn(&str)>,
+) {
+    let parent = parent.root().and_then(|r| r.downcast::<Window>().ok());
+    spawn_future_local(pick_artist_image(
+        Arc::clone(state),
+        parent,
+        artist_id,
+        on_saved,
+    ));
+}
+
+/// Open a dialog for editing an artist's biography.
+///
+/// # Arguments
+///
+/// * `parent` - Widget the dialog is presented over
+/// * `state` - Application state
+/// * `artist_id` - Artist to edit
+/// * `bio` - Current biography text
+/// * `on_saved` - Called with the new biography (`None` when cleared)
+pub fn open_bio_editor(
+    parent: &Widget,
+    state: &Arc<AppState>,
+    artist_id: i64,
+    bio: &str,
+    on_saved: OnBioSaved,
+) {
+    let text_view = TextView::builder()
+        .wrap_mode(WordChar)
+        .top_margin(6)
+        .bottom_margin(6)
+        .left_margin(6)
+        .right_margin(6)
+        .build();
+    text_view.buffer().set_text(bio);
+    let scroll = ScrolledWindow::builder()
+        .child(&text_view)
+        .min_content_height(160)
+        .min_content_width(360)
+        .css_classes(["card"])
+        .build();
+
+    let dialog = AlertDialog::new(Some("Edit Biography"), None);
+    dialog.add_responses(&[(RESPONSE_CANCEL, "Cancel"), (RESPONSE_SAVE, "Save")]);
+    dialog.set_response_appearance(RESPONSE_SAVE, Suggested);
+    dialog.set_close_response(RESPONSE_CANCEL);
+    dialog.set_extra_child(Some(&scroll));
+
+    let state = Arc::clone(state);
+    dialog.connect_response(Some(RESPONSE_SAVE), move |_, _| {
+        let buffer = text_view.buffer();
+        let text = buffer
+            .text(&buffer.start_iter(), &buffer.end_iter(), false)
+            .trim()
+            .to_string();
+        spawn_future_local(save_artist_bio(
+            Arc::clone(&state),
+            artist_id,
+            (!text.is_empty()).then_some(text),
+            Rc::clone(&on_saved),
+        ));
+    });
+
+    dialog.present(Some(parent));
+}
+
+/// Ask for an image file and set it as the artist image.
+async fn pick_artist_image(
+    state: Arc<AppState>,
+    parent: Option<Window>,
+    artist_id: i64,
+    on_saved: Rc<dyn Fn(&str)>,
+) {
+    let file = match image_file_dialog("Choose Artist Image")
+        .open_future(parent.as_ref())
+        .await
+    {
+        Ok(file) => file,
+        Err(e) => {
+            info!(error = %e, "Artist image selection cancelled");
+            return;
+        }
+    };
+    let Some(path) = file.path() else {
+        warn!("Selected artist image has no local path");
+        return;
+    };
+    save_artist_image(state, artist_id, path, on_saved).await;
+}
+
+/// Copy the chosen image into the artwork cache and record it for the artist.
+async fn save_artist_image(
+    state: Arc<AppState>,
+    artist_id: i64,
+    source: PathBuf,
+    on_saved: Rc<dyn Fn(&str)>,
+) {
+    let cached = match spawn_blocking(move || cache_artist_image(artist_id, &source)).await {
+        Ok(Ok(path)) => path.to_string_lossy().into_owned(),
+        Ok(Err(e)) => {
+            warn!(error = %e, artist_id, "Failed to cache artist image");
+            state
+                .send_toast(format!("Could not set artist image: {e}"))
+                .await;
+            return;
+        }
+        Err(e) => {
+            warn!(error = ?e, artist_id, "Artist image copy panicked");
+            return;
+        }
+    };
+    if let Err(e) = state
+        .storage
+        .set_artist_image(artist_id, Some(&cached))
+        .await
+    {
+        warn!(error = %e, artist_id, "Failed to save artist image");
+        return;
+    }
+    info!(artist_id, path = %cached, "Artist image updated");
+    on_saved(&cached);
+}
+
+/// Store the edited biography for the artist.
+async fn save_artist_bio(
+    state: Arc<AppState>,
+    artist_id: i64,
+    bio: Option<String>,
+    on_saved: OnBioSaved,
+) {
+    if let Err(e) = state
+        .storage
+        .set_artist_bio(artist_id, bio.as_deref())
+        .await
+    {
+        warn!(error = %e, artist_id, "Failed to save artist biography");
+        state
+            .send_toast("Could not save the biography".to_string())
+            .await;
+        return;
+    }
+    on_saved(bio.as_deref());
+}
+
+/// File dialog filtered to the image formats the artwork cache stores.
+///
+/// # Arguments
+///
+/// * `title` - Title of the dialog
+#[must_use]
+pub fn image_file_dialog(title: &str) -> FileDialog {
+    let filter = FileFilter::new();
+    filter.set_name(Some("Images"));
+    for mime in ["image/jpeg", "image/png", "image/webp"] {
+        filter.add_mime_type(mime);
+    }
+    let filters = ListStore::new::<FileFilter>();
+    filters.append(&filter);
+    FileDialog::builder()
+        .title(title)
+        .accept_label("Set Image")
+        .filters(&filters)
+        .default_filter(&filter)
+        .build()
+}
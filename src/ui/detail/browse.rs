@@ -0,0 +1,100 @@
+//! Albums of one genre or decade, opened from the browse tab.
+//!
+//! The page reuses the virtualized album grid, filtered in SQL through the
+//! [`AlbumSearch`](crate::storage::AlbumSearch) of the chosen
+//! [`BrowseFilter`], in the current album sort order.
+
+use std::{collections::HashMap, sync::Arc};
+
+use {
+    async_channel::Sender,
+    libadwaita::{
+        StatusPage,
+        glib::{object::Cast, spawn_future_local},
+        gtk::{ScrolledWindow, Widget},
+        prelude::BoxExt,
+    },
+    tokio::join,
+    tracing::{info, warn},
+};
+
+use crate::{
+    app::{AppState, NavigationEvent},
+    storage::{Storage, browse::BrowseFilter},
+    ui::{detail::common::build_detail_wrapper, library::album_tiles::build_album_grid_view},
+};
+
+/// Build the page listing the albums matching `filter`.
+#[must_use]
+pub fn build_browse_detail(
+    state: &Arc<AppState>,
+    filter: &BrowseFilter,
+    nav_tx: &Sender<NavigationEvent>,
+) -> Widget {
+    let wrapper = build_detail_wrapper(nav_tx, &filter.title());
+    let scroll = ScrolledWindow::builder()
+        .vexpand(true)
+        .hexpand(true)
+        .build();
+    wrapper.append(&scroll);
+
+    spawn_future_local(populate_browse_detail(
+        Arc::clone(state),
+        filter.clone(),
+        scroll,
+    ));
+
+    wrapper.upcast()
+}
+
+/// Load the matching albums and show them in the album grid.
+async fn populate_browse_detail(
+    state: Arc<AppState>,
+    filter: BrowseFilter,
+    scroll: ScrolledWindow,
+) {
+    let sort = *state.album_sort_tx.borrow();
+    let search = filter.search();
+    let (albums, artists) = join!(
+        state.storage.search_albums(&search, sort),
+        state.storage.get_all_artists(),
+    );
+    let albums = match albums {
+        Ok(albums) => albums,
+        Err(e) => {
+            warn!(error = %e, filter = %filter.title(), "Failed to load browsed albums");
+            return;
+        }
+    };
+    info!(filter = %filter.title(), albums = albums.len(), "Browsing albums");
+    if albums.is_empty() {
+        scroll.set_child(Some(&build_no_albums()));
+        return;
+    }
+
+    let album_ids: Vec<i64> = albums.iter().map(|a| a.id).collect();
+    let format_info = state
+        .storage
+        .get_albums_format_info(&album_ids)
+        .await
+        .unwrap_or_default();
+    let artist_names: HashMap<i64, String> = match artists {
+        Ok(artists) => artists.into_iter().map(|a| (a.id, a.name)).collect(),
+        Err(e) => {
+            warn!(error = %e, "Failed to load artists for browsed albums");
+            HashMap::new()
+        }
+    };
+    let grid = build_album_grid_view(&state, &albums, &artist_names, &format_info);
+    scroll.set_child(Some(&grid));
+}
+
+/// Placeholder shown when the albums were removed since the tile was built.
+fn build_no_albums() -> StatusPage {
+    StatusPage::builder()
+        .icon_name("folder-music-symbolic")
+        .title("No Albums")
+        .description("No albums in the library match anymore.")
+        .vexpand(true)
+        .build()
+}
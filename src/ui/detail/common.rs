@@ -13,7 +13,7 @@ use {
         },
         gtk::{
             Align::{End, Start},
-            Box, Button, EventControllerKey, GestureClick, Label, ListBox, ListBoxRow,
+            Box, Button, EventControllerKey, GestureClick, Image, Label, ListBox, ListBoxRow,
             Orientation::{Horizontal, Vertical},
             ScrolledWindow,
             accessible::Property::Label as PropertyLabel,
@@ -38,6 +38,9 @@ use crate::{
 /// Number of tracks to add per batch in the detail page track list.
 const BATCH_SIZE: usize = 10;
 
+/// Prefix of the widget name identifying the track of a track row.
+const TRACK_ROW_PREFIX: &str = "track-";
+
 /// Append up to `BATCH_SIZE` track rows from `remaining` to the list.
 /// Call from an idle callback; returns `Continue` if more remain, `Break` when done.
 pub fn fill_track_list_batch(
@@ -157,6 +160,7 @@ pub fn build_detail_header(back_button: &Button, title: &str) -> Box {
 pub fn build_track_row(state: &Arc<AppState>, track: &Track, display_number: usize) -> ListBoxRow {
     let row = ListBoxRow::builder()
         .activatable(true)
+        .name(format!("{TRACK_ROW_PREFIX}{}", track.id))
         .tooltip_text("Click to play, right-click to add to queue")
         .build();
 
@@ -169,6 +173,15 @@ pub fn build_track_row(state: &Arc<AppState>, track: &Track, display_number: usi
         .margin_end(12)
         .build();
 
+    let playing_icon = Image::builder()
+        .icon_name("audio-volume-high-symbolic")
+        .width_request(30)
+        .halign(Start)
+        .visible(false)
+        .build();
+    playing_icon.update_property(&[PropertyLabel("Now playing")]);
+    hbox.append(&playing_icon);
+
     let number_label = Label::builder()
         .label(display_number.to_string())
         .width_request(30)
@@ -219,6 +232,9 @@ pub fn build_track_row(state: &Arc<AppState>, track: &Track, display_number: usi
     hbox.append(&duration_label);
 
     row.set_child(Some(&hbox));
+    if state.playback.state().current_track_id == Some(track.id) {
+        set_row_playing(&row, true);
+    }
 
     let sc = Arc::clone(state);
     let tid = track.id;
@@ -253,6 +269,46 @@ pub fn build_track_row(state: &Arc<AppState>, track: &Track, display_number: usi
     row
 }
 
+/// Highlight the row of the playing track in a list built from
+/// [`build_track_row`], clearing the highlight of every other row.
+///
+/// Pass `None` when playback stopped.
+pub fn highlight_playing_track(list: &ListBox, playing: Option<i64>) {
+    let mut index = 0;
+    while let Some(row) = list.row_at_index(index) {
+        set_row_playing(&row, playing.is_some() && row_track_id(&row) == playing);
+        index += 1;
+    }
+}
+
+/// Track ID of a row built by [`build_track_row`].
+fn row_track_id(row: &ListBoxRow) -> Option<i64> {
+    row.widget_name()
+        .strip_prefix(TRACK_ROW_PREFIX)
+        .and_then(|id| id.parse().ok())
+}
+
+/// Show or hide the playing indicator of a track row.
+///
+/// The indicator replaces the track number, and the title is drawn in the
+/// accent color.
+fn set_row_playing(row: &ListBoxRow, playing: bool) {
+    let Some(indicator) = row.child().and_then(|hbox| hbox.first_child()) else {
+        return;
+    };
+    let number = indicator.next_sibling();
+    let title = number.as_ref().and_then(WidgetExt::next_sibling);
+    indicator.set_visible(playing);
+    if let Some(number) = number {
+        number.set_visible(!playing);
+    }
+    match (title, playing) {
+        (Some(title), true) => title.add_css_class("accent"),
+        (Some(title), false) => title.remove_css_class("accent"),
+        (None, _) => {}
+    }
+}
+
 /// Spawns playback of the track with the given ID.
 fn spawn_playback(state: &Arc<AppState>, track_id: i64) {
     let state = Arc::clone(state);
@@ -302,6 +358,71 @@ async fn play_single_track(state: &Arc<AppState>, track_id: i64) {
     }
 }
 
+/// Whether `tracks` span more than one disc.
+///
+/// An album tagged as part of a larger set counts even when only one of
+/// its discs is in the library.
+#[must_use]
+pub fn is_multi_disc(tracks: &[Track]) -> bool {
+    let mut discs = tracks.iter().map(Track::disc);
+    let first = discs.next();
+    discs.any(|disc| Some(disc) != first)
+        || tracks.iter().any(|t| t.disc_total.is_some_and(|n| n > 1))
+}
+
+/// Position of each track within its disc, starting at 1 on every disc.
+///
+/// `discs` holds the disc of each track in list order.
+#[must_use]
+pub fn numbers_within_disc(discs: &[i32]) -> Vec<usize> {
+    let mut previous = None;
+    let mut number = 0;
+    discs
+        .iter()
+        .map(|disc| {
+            number = if previous == Some(disc) {
+                number + 1
+            } else {
+                1
+            };
+            previous = Some(disc);
+            number
+        })
+        .collect()
+}
+
+/// Label the first track of each disc in `track_list` with a "Disc N" header.
+///
+/// `discs` holds the disc of each row in list order.
+pub fn set_disc_headers(track_list: &ListBox, discs: Vec<i32>) {
+    track_list.set_header_func(move |row, _| {
+        let header = disc_header(&discs, row.index()).map(build_disc_header);
+        row.set_header(header.as_ref());
+    });
+}
+
+/// Disc starting at row `index`, or `None` if the row continues a disc.
+fn disc_header(discs: &[i32], index: i32) -> Option<i32> {
+    let Ok(index) = usize::try_from(index) else {
+        return None;
+    };
+    let disc = discs.get(index)?;
+    let previous = index.checked_sub(1).and_then(|i| discs.get(i));
+    (previous != Some(disc)).then_some(*disc)
+}
+
+/// Build the header label shown above the first track of a disc.
+fn build_disc_header(disc: i32) -> Label {
+    Label::builder()
+        .label(format!("Disc {disc}"))
+        .css_classes(["heading"])
+        .halign(Start)
+        .margin_top(12)
+        .margin_bottom(6)
+        .margin_start(12)
+        .build()
+}
+
 /// Format seconds as `M:SS` or `MM:SS`.
 #[must_use]
 pub fn format_duration(seconds: f64) -> String {
@@ -313,7 +434,7 @@ pub fn format_duration(seconds: f64) -> String {
 
 #[cfg(test)]
 mod tests {
-    use crate::ui::detail::common::format_duration;
+    use crate::ui::detail::common::{disc_header, format_duration, numbers_within_disc};
 
     #[test]
     fn format_duration_zero() {
@@ -334,4 +455,19 @@ mod tests {
     fn format_duration_negative_treated_as_zero() {
         assert_eq!(format_duration(-5.0), "0:00");
     }
+
+    #[test]
+    fn numbering_restarts_on_each_disc() {
+        assert_eq!(numbers_within_disc(&[1, 1, 1, 2, 2]), [1, 2, 3, 1, 2]);
+        assert_eq!(numbers_within_disc(&[1, 1, 1]), [1, 2, 3]);
+        assert!(numbers_within_disc(&[]).is_empty());
+    }
+
+    #[test]
+    fn disc_header_only_on_first_track_of_disc() {
+        let discs = [1, 1, 2, 2, 3];
+        let headers: Vec<Option<i32>> = (0..6).map(|i| disc_header(&discs, i)).collect();
+        assert_eq!(headers, [Some(1), None, Some(2), None, Some(3), None]);
+        assert_eq!(disc_header(&discs, -1), None);
+    }
 }
@@ -0,0 +1,201 @@
+//! Dynamic range value of the album detail page with its "Recompute DR"
+//! and "Clear DR" actions.
+//!
+//! Recomputing reads the album's DR meter log or decodes every track, so
+//! it runs off the main thread and the buttons stay disabled meanwhile.
+
+use std::{path::PathBuf, sync::Arc};
+
+use {
+    libadwaita::{
+        gio::spawn_blocking,
+        glib::spawn_future_local,
+        gtk::{
+            Box as GtkBox, Button, Label, Orientation::Horizontal,
+            accessible::Property::Label as PropertyLabel,
+        },
+        prelude::{AccessibleExtManual, BoxExt, ButtonExt, WidgetExt},
+    },
+    tracing::{info, warn},
+};
+
+use crate::{
+    app::AppState,
+    library::dynamic_range::{AlbumDr, DrSource, album_dr},
+    storage::Storage,
+};
+
+/// Widgets showing and changing an album's DR value.
+#[derive(Clone)]
+struct DrControls {
+    /// "DR12" label, hidden while the value is unknown.
+    label: Label,
+    /// Button recomputing the value.
+    recompute: Button,
+    /// Button clearing the stored value.
+    clear: Button,
+}
+
+impl DrControls {
+    /// Show `value`, hiding the label and "Clear DR" when it is unknown.
+    fn show(&self, value: Option<i32>) {
+        if let Some(value) = value {
+            self.label.set_label(&format!("DR{value}"));
+        }
+        self.label.set_visible(value.is_some());
+        self.clear.set_visible(value.is_some());
+    }
+
+    /// Enable or disable both buttons.
+    fn set_busy(&self, busy: bool) {
+        self.recompute.set_sensitive(!busy);
+        self.clear.set_sensitive(!busy);
+    }
+}
+
+/// Build the DR label and actions of an album.
+///
+/// # Arguments
+///
+/// * `state` - Application state
+/// * `album_id` - Album whose DR value is shown
+#[must_use]
+pub fn build_dr_controls(state: &Arc<AppState>, album_id: i64) -> GtkBox {
+    let container = GtkBox::builder().orientation(Horizontal).spacing(6).build();
+
+    let label = Label::builder()
+        .css_classes(["dim-label", "caption", "numeric"])
+        .visible(false)
+        .build();
+    label.update_property(&[PropertyLabel("Dynamic range")]);
+    container.append(&label);
+
+    let recompute = Button::builder()
+        .icon_name("view-refresh-symbolic")
+        .tooltip_text("Recompute DR")
+        .css_classes(["flat", "circular"])
+        .build();
+    recompute.update_property(&[PropertyLabel("Recompute dynamic range")]);
+    container.append(&recompute);
+
+    let clear = Button::builder()
+        .icon_name("edit-clear-symbolic")
+        .tooltip_text("Clear DR")
+        .css_classes(["flat", "circular"])
+        .visible(false)
+        .build();
+    clear.update_property(&[PropertyLabel("Clear dynamic range")]);
+    container.append(&clear);
+
+    let controls = DrControls {
+        label,
+        recompute,
+        clear,
+    };
+
+    let (recompute_state, recompute_controls) = (Arc::clone(state), controls.clone());
+    controls.recompute.connect_clicked(move |_| {
+        spawn_future_local(recompute_dr(
+            Arc::clone(&recompute_state),
+            album_id,
+            recompute_controls.clone(),
+        ));
+    });
+
+    let (clear_state, clear_controls) = (Arc::clone(state), controls.clone());
+    controls.clear.connect_clicked(move |_| {
+        spawn_future_local(clear_dr(
+            Arc::clone(&clear_state),
+            album_id,
+            clear_controls.clone(),
+        ));
+    });
+
+    spawn_future_local(show_stored_dr(Arc::clone(state), album_id, controls));
+    container
+}
+
+/// Show the stored DR value of the album.
+async fn show_stored_dr(state: Arc<AppState>, album_id: i64, controls: DrControls) {
+    match state.storage.get_album(album_id).await {
+        Ok(Some(album)) => controls.show(album.dr_value),
+        Ok(None) => info!(album_id, "Album not found"),
+        Err(e) => warn!(error = %e, album_id, "Failed to load album DR"),
+    }
+}
+
+/// Derive the album's DR value again and store it.
+async fn recompute_dr(state: Arc<AppState>, album_id: i64, controls: DrControls) {
+    let paths: Vec<PathBuf> = match state.storage.get_tracks_by_album(album_id).await {
+        Ok(tracks) => tracks
+            .into_iter()
+            .map(|t| PathBuf::from(t.audio.file_path))
+            .collect(),
+        Err(e) => {
+            warn!(error = %e, album_id, "Failed to load album tracks");
+            return;
+        }
+    };
+
+    controls.set_busy(true);
+    let measured = spawn_blocking(move || album_dr(&paths)).await;
+    controls.set_busy(false);
+    let dr = match measured {
+        Ok(Ok(Some(dr))) => dr,
+        Ok(Ok(None)) => {
+            state.send_toast("No audio to measure".to_string()).await;
+            return;
+        }
+        Ok(Err(e)) => {
+            warn!(error = %e, album_id, "Failed to measure album DR");
+            state.send_toast(format!("Could not measure DR: {e}")).await;
+            return;
+        }
+        Err(e) => {
+            warn!(error = ?e, "DR analysis panicked");
+            return;
+        }
+    };
+
+    if store_dr(&state, album_id, Some(dr.value), &controls).await {
+        info!(album_id, dr = dr.value, source = ?dr.source, "Album DR recomputed");
+        state.send_toast(recomputed_message(dr)).await;
+    }
+}
+
+/// Remove the stored DR value of the album.
+async fn clear_dr(state: Arc<AppState>, album_id: i64, controls: DrControls) {
+    if store_dr(&state, album_id, None, &controls).await {
+        info!(album_id, "Album DR cleared");
+        state.send_toast("DR value cleared".to_string()).await;
+    }
+}
+
+/// Store `value`, show it and refresh the library views.
+///
+/// Returns whether the value was stored.
+async fn store_dr(
+    state: &AppState,
+    album_id: i64,
+    value: Option<i32>,
+    controls: &DrControls,
+) -> bool {
+    if let Err(e) = state.storage.set_album_dr(album_id, value).await {
+        warn!(error = %e, album_id, "Failed to store album DR");
+        state.send_toast(format!("Could not save DR: {e}")).await;
+        return false;
+    }
+    controls.show(value);
+    if let Err(e) = state.refresh_tx.send(()) {
+        warn!(error = %e, "Failed to send refresh signal");
+    }
+    true
+}
+
+/// Toast text after recomputing, naming where the value came from.
+fn recomputed_message(dr: AlbumDr) -> String {
+    match dr.source {
+        DrSource::Log => format!("DR{} read from the DR log", dr.value),
+        DrSource::Measured => format!("DR{} measured", dr.value),
+    }
+}
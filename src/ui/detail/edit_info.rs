@@ -0,0 +1,246 @@
+//! "Edit Info" dialog for correcting album tags.
+//!
+//! Edits apply to every track of the album. Files are rewritten first and
+//! the library catalog is only updated once all of them succeeded, so the
+//! library never shows values that are not on disk.
+
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+};
+
+use {
+    libadwaita::{
+        AlertDialog, EntryRow, PreferencesGroup,
+        ResponseAppearance::Suggested,
+        gio::spawn_blocking,
+        glib::spawn_future_local,
+        gtk::Widget,
+        prelude::{
+            AdwDialogExt, AlertDialogExt, AlertDialogExtManual, EditableExt, PreferencesGroupExt,
+            PreferencesRowExt,
+        },
+    },
+    tracing::{info, warn},
+};
+
+use crate::{
+    app::AppState,
+    library::{
+        cue::split_cue_path,
+        tag_writer::{TagChanges, apply_album_changes, write_metadata_batch},
+    },
+    storage::{Album, Storage},
+};
+
+/// Response id of the cancel button.
+const RESPONSE_CANCEL: &str = "cancel";
+
+/// Response id of the save button.
+const RESPONSE_SAVE: &str = "save";
+
+/// Entry rows of the dialog together with the values they started with.
+struct EditRows {
+    /// Album title row.
+    album: EntryRow,
+    /// Album artist row.
+    album_artist: EntryRow,
+    /// Release year row.
+    year: EntryRow,
+    /// Genre row.
+    genre: EntryRow,
+    /// Album as loaded when the dialog opened.
+    original: Album,
+    /// Album artist name as loaded when the dialog opened.
+    original_artist: String,
+}
+
+impl EditRows {
+    /// Build the rows pre-filled with the current album values.
+    fn new(album: Album, artist_name: String) -> Self {
+        let year = album.year.map(|y| y.to_string()).unwrap_or_default();
+        let genre = album.genre.clone().unwrap_or_default();
+        Self {
+            album: entry_row("Album", &album.title),
+            album_artist: entry_row("Album Artist", &artist_name),
+            year: entry_row("Year", &year),
+            genre: entry_row("Genre", &genre),
+            original: album,
+            original_artist: artist_name,
+        }
+    }
+
+    /// Collect the fields that differ from the original values.
+    ///
+    /// Empty fields are left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns a user-facing message if the year is not a number.
+    fn changes(&self) -> Result<TagChanges, &'static str> {
+        let year = match self.year.text().trim() {
+            "" => None,
+            text => match text.parse::<i32>() {
+                Ok(year) => Some(year),
+                Err(_) => return Err("Year must be a number"),
+            },
+        };
+        Ok(TagChanges {
+            album: changed_text(&self.album, &self.original.title),
+            album_artist: changed_text(&self.album_artist, &self.original_artist),
+            year: year.filter(|y| Some(*y) != self.original.year),
+            genre: changed_text(
+                &self.genre,
+                self.original.genre.as_deref().unwrap_or_default(),
+            ),
+            ..TagChanges::default()
+        })
+    }
+}
+
+/// Open the "Edit Info" dialog for an album.
+///
+/// # Arguments
+///
+/// * `parent` - Widget the dialog is presented over
+/// * `state` - Application state
+/// * `album_id` - Album to edit
+/// * `on_saved` - Called with the applied changes after a successful save
+pub fn open_edit_info(
+    parent: &Widget,
+    state: &Arc<AppState>,
+    album_id: i64,
+    on_saved: Rc<dyn Fn(&TagChanges)>,
+) {
+    let parent = parent.clone();
+    let state = Arc::clone(state);
+    spawn_future_local(async move {
+        let album = match state.storage.get_album(album_id).await {
+            Ok(Some(a)) => a,
+            Ok(None) => {
+                info!(album_id, "Album not found");
+                return;
+            }
+            Err(e) => {
+                warn!(error = %e, album_id, "Failed to load album");
+                return;
+            }
+        };
+        let artist_name = match state.storage.get_artist(album.artist_id).await {
+            Ok(Some(a)) => a.name,
+            _ => "Unknown Artist".to_string(),
+        };
+        present_dialog(&parent, state, EditRows::new(album, artist_name), on_saved);
+    });
+}
+
+/// Build and present the dialog for the loaded album.
+fn present_dialog(
+    parent: &Widget,
+    state: Arc<AppState>,
+    rows: EditRows,
+    on_saved: Rc<dyn Fn(&TagChanges)>,
+) {
+    let group = PreferencesGroup::new();
+    group.set_description(Some(&format!(
+        "Changes are written to all {} tracks of the album",
+        rows.original.track_count
+    )));
+    group.add(&rows.album);
+    group.add(&rows.album_artist);
+    group.add(&rows.year);
+    group.add(&rows.genre);
+
+    let dialog = AlertDialog::new(Some("Edit Info"), None);
+    dialog.add_responses(&[(RESPONSE_CANCEL, "Cancel"), (RESPONSE_SAVE, "Save")]);
+    dialog.set_response_appearance(RESPONSE_SAVE, Suggested);
+    dialog.set_default_response(Some(RESPONSE_SAVE));
+    dialog.set_close_response(RESPONSE_CANCEL);
+    dialog.set_extra_child(Some(&group));
+
+    dialog.connect_response(Some(RESPONSE_SAVE), move |_, _| {
+        let state = Arc::clone(&state);
+        match rows.changes() {
+            Ok(changes) if changes.is_empty() => {}
+            Ok(changes) => {
+                spawn_future_local(save_album_changes(
+                    state,
+                    rows.original.clone(),
+                    changes,
+                    Rc::clone(&on_saved),
+                ));
+            }
+            Err(msg) => state.show_toast(msg.to_string()),
+        }
+    });
+
+    dialog.present(Some(parent));
+}
+
+/// Write the changes to every track of the album and update the library.
+async fn save_album_changes(
+    state: Arc<AppState>,
+    album: Album,
+    changes: TagChanges,
+    on_saved: Rc<dyn Fn(&TagChanges)>,
+) {
+    let tracks = match state.storage.get_tracks_by_album(album.id).await {
+        Ok(t) => t,
+        Err(e) => {
+            warn!(error = %e, album_id = album.id, "Failed to load album tracks");
+            return;
+        }
+    };
+    let mut paths: Vec<PathBuf> = tracks
+        .iter()
+        .map(|t| split_cue_path(Path::new(&t.audio.file_path)).0)
+        .collect();
+    paths.dedup();
+
+    let write_changes = changes.clone();
+    match spawn_blocking(move || write_metadata_batch(&paths, &write_changes)).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => {
+            warn!(error = %e, album_id = album.id, "Failed to write album tags");
+            state.send_toast(format!("Could not edit tags: {e}")).await;
+            return;
+        }
+        Err(e) => {
+            warn!(error = ?e, album_id = album.id, "Tag writer panicked");
+            return;
+        }
+    }
+
+    if let Err(e) = apply_album_changes(state.storage.as_ref(), &album, &changes).await {
+        warn!(error = %e, album_id = album.id, "Failed to update library after tag edit");
+        state
+            .send_toast("Tags were written but the library could not be updated".to_string())
+            .await;
+        return;
+    }
+
+    info!(
+        album_id = album.id,
+        tracks = tracks.len(),
+        "Album tags updated"
+    );
+    on_saved(&changes);
+    if let Err(e) = state.refresh_tx.send(()) {
+        warn!(error = %e, "Failed to send refresh signal");
+    }
+}
+
+/// Build an entry row with a title and initial text.
+fn entry_row(title: &str, text: &str) -> EntryRow {
+    let row = EntryRow::new();
+    row.set_title(title);
+    row.set_text(text);
+    row
+}
+
+/// Return the row's trimmed text if it is non-empty and differs from `original`.
+fn changed_text(row: &EntryRow, original: &str) -> Option<String> {
+    let text = row.text().trim().to_string();
+    (!text.is_empty() && text != original).then_some(text)
+}
@@ -1,5 +1,11 @@
-//! Detail pages for albums and artists.
+//! Detail pages for albums, artists, and browsed genres or decades.
 
 pub mod album;
+pub mod album_cover;
 pub mod artist;
+pub mod artist_profile;
+pub mod browse;
 pub mod common;
+pub mod dynamic_range;
+pub mod edit_info;
+pub mod track_order;
@@ -0,0 +1,155 @@
+//! "Fix Track Order" dialog for albums with broken track numbering.
+//!
+//! Tracks are renumbered from their file names. The new numbers are stored
+//! in the library, where they last until the files change and are scanned
+//! again; they are written to the files only when the user asks for it.
+//! Files are then rewritten first, so the library never shows numbers that
+//! could not be saved.
+
+use std::{rc::Rc, sync::Arc};
+
+use {
+    libadwaita::{
+        AlertDialog, PreferencesGroup,
+        ResponseAppearance::Suggested,
+        SwitchRow,
+        gio::spawn_blocking,
+        glib::spawn_future_local,
+        gtk::Widget,
+        prelude::{AdwDialogExt, AlertDialogExt, AlertDialogExtManual, PreferencesGroupExt},
+    },
+    tracing::{info, warn},
+};
+
+use crate::{
+    app::AppState,
+    library::numbering::{
+        Renumbering, apply_renumbering, renumber_by_file_name, write_track_numbers,
+    },
+    storage::Storage,
+};
+
+/// Response id of the cancel button.
+const RESPONSE_CANCEL: &str = "cancel";
+
+/// Response id of the renumber button.
+const RESPONSE_RENUMBER: &str = "renumber";
+
+/// Open the "Fix Track Order" dialog for an album.
+///
+/// # Arguments
+///
+/// * `parent` - Widget the dialog is presented over
+/// * `state` - Application state
+/// * `album_id` - Album to renumber
+/// * `on_fixed` - Called after the new numbers are stored
+pub fn open_fix_track_order(
+    parent: &Widget,
+    state: &Arc<AppState>,
+    album_id: i64,
+    on_fixed: Rc<dyn Fn()>,
+) {
+    let parent = parent.clone();
+    let state = Arc::clone(state);
+    spawn_future_local(async move {
+        let tracks = match state.storage.get_tracks_by_album(album_id).await {
+            Ok(t) => t,
+            Err(e) => {
+                warn!(error = %e, album_id, "Failed to load album tracks");
+                return;
+            }
+        };
+        let changes = renumber_by_file_name(&tracks);
+        if changes.is_empty() {
+            state
+                .send_toast("Track numbers already follow the file names".to_string())
+                .await;
+            return;
+        }
+        present_dialog(&parent, state, changes, on_fixed);
+    });
+}
+
+/// Build and present the dialog for the proposed numbers.
+fn present_dialog(
+    parent: &Widget,
+    state: Arc<AppState>,
+    changes: Vec<Renumbering>,
+    on_fixed: Rc<dyn Fn()>,
+) {
+    let write_files = SwitchRow::builder()
+        .title("Write to Files")
+        .subtitle("Also save the new numbers in the track tags")
+        .build();
+    let group = PreferencesGroup::new();
+    group.add(&write_files);
+
+    let count = changes.len();
+    let tracks = if count == 1 { "track" } else { "tracks" };
+    let dialog = AlertDialog::new(
+        Some("Fix Track Order"),
+        Some(&format!(
+            "Renumber {count} {tracks} by file name? Tracks without a number in their file \
+             name are placed last."
+        )),
+    );
+    dialog.add_responses(&[(RESPONSE_CANCEL, "Cancel"), (RESPONSE_RENUMBER, "Renumber")]);
+    dialog.set_response_appearance(RESPONSE_RENUMBER, Suggested);
+    dialog.set_default_response(Some(RESPONSE_RENUMBER));
+    dialog.set_close_response(RESPONSE_CANCEL);
+    dialog.set_extra_child(Some(&group));
+
+    dialog.connect_response(Some(RESPONSE_RENUMBER), move |_, _| {
+        spawn_future_local(save_renumbering(
+            Arc::clone(&state),
+            changes.clone(),
+            write_files.is_active(),
+            Rc::clone(&on_fixed),
+        ));
+    });
+
+    dialog.present(Some(parent));
+}
+
+/// Optionally write the new numbers to the files, then store them.
+async fn save_renumbering(
+    state: Arc<AppState>,
+    changes: Vec<Renumbering>,
+    write_files: bool,
+    on_fixed: Rc<dyn Fn()>,
+) {
+    if write_files {
+        let write_changes = changes.clone();
+        match spawn_blocking(move || write_track_numbers(&write_changes)).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => {
+                warn!(error = %e, "Failed to write track numbers");
+                state
+                    .send_toast(format!("Could not write track numbers: {e}"))
+                    .await;
+                return;
+            }
+            Err(e) => {
+                warn!(error = ?e, "Tag writer panicked");
+                return;
+            }
+        }
+    }
+
+    if let Err(e) = apply_renumbering(state.storage.as_ref(), &changes).await {
+        warn!(error = %e, "Failed to store track numbers");
+        state
+            .send_toast(format!("Could not renumber tracks: {e}"))
+            .await;
+        return;
+    }
+
+    info!(
+        tracks = changes.len(),
+        write_files, "Album tracks renumbered"
+    );
+    on_fixed();
+    if let Err(e) = state.refresh_tx.send(()) {
+        warn!(error = %e, "Failed to send refresh signal");
+    }
+}
@@ -0,0 +1,138 @@
+//! General > Troubleshooting group of the preferences dialog.
+//!
+//! Exports a diagnostics file to attach to bug reports: the recent log of
+//! this session, the settings with secrets redacted, library counts, and a
+//! summary of the audio output.
+
+use std::{fs::write, path::PathBuf, sync::Arc};
+
+use {
+    libadwaita::{
+        ActionRow, PreferencesGroup, PreferencesPage,
+        gio::spawn_blocking,
+        glib::{object::Cast, spawn_future_local},
+        gtk::{Align::Center, Button, FileDialog, Window},
+        prelude::{
+            ActionRowExt, ButtonExt, FileExt, PreferencesGroupExt, PreferencesPageExt, WidgetExt,
+        },
+    },
+    tracing::{info, warn},
+};
+
+use crate::{
+    app::AppState,
+    metrics::diagnostics::{DiagnosticsReport, LogBuffer},
+    playback::{control::PlaybackController, output::list_output_devices},
+};
+
+/// Suggested name of an exported diagnostics file.
+const EXPORT_FILE_NAME: &str = "oxhidifi-diagnostics.txt";
+
+/// Build the General > Troubleshooting group.
+pub fn build_diagnostics_group(page: &PreferencesPage, state: &Arc<AppState>) {
+    let group = PreferencesGroup::new();
+    group.set_title("Troubleshooting");
+
+    let export_btn = Button::builder().label("Export…").valign(Center).build();
+    let export_row = ActionRow::builder()
+        .title("Export Diagnostics")
+        .subtitle("Save the recent log, settings, and audio setup for a bug report")
+        .build();
+    export_row.add_suffix(&export_btn);
+    export_row.set_activatable_widget(Some(&export_btn));
+    let state = Arc::clone(state);
+    export_btn.connect_clicked(move |btn| {
+        let parent = btn.root().and_then(|r| r.downcast::<Window>().ok());
+        spawn_future_local(export_diagnostics(Arc::clone(&state), parent));
+    });
+
+    group.add(&export_row);
+    page.add(&group);
+}
+
+/// Ask for a destination and write the diagnostics report there.
+async fn export_diagnostics(state: Arc<AppState>, parent: Option<Window>) {
+    let dialog = FileDialog::builder()
+        .title("Export Diagnostics")
+        .accept_label("Export")
+        .initial_name(EXPORT_FILE_NAME)
+        .build();
+    let file = match dialog.save_future(parent.as_ref()).await {
+        Ok(file) => file,
+        Err(e) => {
+            info!(error = %e, "Diagnostics export cancelled");
+            return;
+        }
+    };
+    let Some(path) = file.path() else {
+        warn!("Selected diagnostics file has no local path");
+        return;
+    };
+    let text = build_report(&state).await.render();
+    let message = match write_report(path.clone(), text).await {
+        Ok(()) => {
+            info!(path = %path.display(), "Diagnostics exported");
+            format!("Diagnostics exported to {}", path.display())
+        }
+        Err(e) => {
+            warn!(error = %e, path = %path.display(), "Failed to export diagnostics");
+            format!("Could not export diagnostics: {e}")
+        }
+    };
+    state.send_toast(message).await;
+}
+
+/// Gather the report sections.
+async fn build_report(state: &AppState) -> DiagnosticsReport {
+    let mut report = DiagnosticsReport::new();
+    report.add_settings(&state.storage.get_settings());
+    report.add_section("Library", library_section(state).await);
+    report.add_section("Audio Output", audio_section(state).await);
+    report.add_log(LogBuffer::session());
+    report
+}
+
+/// Library counts, or the error reading them.
+async fn library_section(state: &AppState) -> String {
+    match state.storage.stats().await {
+        Ok(stats) => format!(
+            "Artists: {}\nAlbums: {}\nTracks: {} ({} lossless)",
+            stats.artist_count, stats.album_count, stats.track_count, stats.lossless_count
+        ),
+        Err(e) => format!("Library statistics unavailable: {e}"),
+    }
+}
+
+/// Signal path, preferred device, and the devices currently available.
+async fn audio_section(state: &AppState) -> String {
+    let report = state.playback.signal_path_report();
+    let preferred = state
+        .storage
+        .get_audio_device()
+        .unwrap_or_else(|| "System default".to_string());
+    let devices = match spawn_blocking(list_output_devices).await {
+        Ok(Ok(devices)) => devices
+            .into_iter()
+            .map(|d| format!("  {} ({})", d.name, d.id))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Ok(Err(e)) => format!("  Could not list devices: {e}"),
+        Err(e) => format!("  Device enumeration panicked: {e:?}"),
+    };
+    format!(
+        "Signal path: {}\nOutput mode: {:?}\nTrack rate: {} Hz\nDevice rate: {} Hz\n\
+         Preferred device: {preferred}\nAvailable devices:\n{devices}",
+        report.summary(),
+        report.output_mode,
+        report.track_sample_rate,
+        report.device_sample_rate,
+    )
+}
+
+/// Write the report text off the main thread.
+async fn write_report(path: PathBuf, text: String) -> Result<(), String> {
+    match spawn_blocking(move || write(path, text)).await {
+        Ok(result) => result.map_err(|e| e.to_string()),
+        Err(e) => Err(format!("writer panicked: {e:?}")),
+    }
+}
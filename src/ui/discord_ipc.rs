@@ -0,0 +1,225 @@
+//! Client side of the local Discord IPC protocol.
+//!
+//! Discord listens on a Unix socket in the runtime directory. Each message
+//! is a frame of a little-endian opcode, a little-endian payload length,
+//! and a JSON payload. A handshake frame identifies the application, after
+//! which command frames are answered one reply at a time.
+
+use std::{
+    env::var_os,
+    io::{
+        Error as IoError,
+        ErrorKind::{InvalidData, NotFound},
+        Read, Result as IoResult, Write,
+    },
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    process::id,
+    time::Duration,
+};
+
+use serde_json::{Value, from_slice, json, to_vec};
+
+/// Timeout for reads and writes on the IPC socket.
+const IO_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Largest accepted reply payload, guarding against a corrupt length.
+const MAX_PAYLOAD_BYTES: usize = 64 * 1024;
+
+/// Opcode of command frames and their replies.
+const OP_FRAME: u32 = 1;
+
+/// Opcode of the handshake frame.
+const OP_HANDSHAKE: u32 = 0;
+
+/// Number of numbered IPC sockets Discord may listen on.
+const SOCKET_COUNT: u32 = 10;
+
+/// Subdirectories of the runtime directory used by sandboxed Discord installs.
+const SOCKET_SUBDIRS: [&str; 3] = ["", "app/com.discordapp.Discord", "snap.discord"];
+
+/// Connection to the local Discord client.
+pub struct DiscordIpc {
+    /// IPC socket of the Discord client.
+    stream: UnixStream,
+    /// Nonce of the last command sent.
+    nonce: u64,
+}
+
+impl DiscordIpc {
+    /// Connect to the first Discord socket accepting `client_id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no Discord client accepts the connection.
+    pub fn connect(client_id: &str) -> IoResult<Self> {
+        let mut last_error = IoError::new(NotFound, "Discord is not running");
+        for path in socket_paths() {
+            match Self::handshake(&path, client_id) {
+                Ok(ipc) => return Ok(ipc),
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error)
+    }
+
+    /// Open the socket at `path` and identify as `client_id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket cannot be opened or Discord rejects
+    /// the handshake, e.g. for an unknown application ID.
+    fn handshake(path: &Path, client_id: &str) -> IoResult<Self> {
+        let stream = UnixStream::connect(path)?;
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+        let mut ipc = Self { stream, nonce: 0 };
+        write_frame(
+            &mut ipc.stream,
+            OP_HANDSHAKE,
+            &json!({ "v": 1, "client_id": client_id }),
+        )?;
+        let (_, reply) = read_frame(&mut ipc.stream)?;
+        if event_name(&reply) != Some("READY") {
+            return Err(IoError::other(format!(
+                "Discord rejected the handshake: {}",
+                reply_message(&reply)
+            )));
+        }
+        Ok(ipc)
+    }
+
+    /// Show `activity`, or clear the presence when `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket fails or Discord rejects the activity.
+    pub fn set_activity(&mut self, activity: Option<&Value>) -> IoResult<()> {
+        self.nonce += 1;
+        let command = json!({
+            "cmd": "SET_ACTIVITY",
+            "args": { "pid": id(), "activity": activity },
+            "nonce": self.nonce.to_string(),
+        });
+        write_frame(&mut self.stream, OP_FRAME, &command)?;
+        let (_, reply) = read_frame(&mut self.stream)?;
+        if event_name(&reply) == Some("ERROR") {
+            return Err(IoError::other(format!(
+                "Discord rejected the activity: {}",
+                reply_message(&reply)
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Candidate paths of the Discord IPC socket, in order of preference.
+fn socket_paths() -> Vec<PathBuf> {
+    let base = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"]
+        .into_iter()
+        .find_map(var_os)
+        .map_or_else(|| PathBuf::from("/tmp"), PathBuf::from);
+    SOCKET_SUBDIRS
+        .iter()
+        .flat_map(|subdir| {
+            let dir = base.join(subdir);
+            (0..SOCKET_COUNT).map(move |n| dir.join(format!("discord-ipc-{n}")))
+        })
+        .collect()
+}
+
+/// Write one IPC frame: opcode, payload length, and JSON payload.
+///
+/// # Errors
+///
+/// Returns an error if the payload cannot be serialized or written.
+fn write_frame(writer: &mut impl Write, op: u32, payload: &Value) -> IoResult<()> {
+    let body = to_vec(payload)?;
+    let len = u32::try_from(body.len()).map_err(|e| IoError::new(InvalidData, e))?;
+    let mut frame = Vec::with_capacity(body.len() + 8);
+    frame.extend_from_slice(&op.to_le_bytes());
+    frame.extend_from_slice(&len.to_le_bytes());
+    frame.extend_from_slice(&body);
+    writer.write_all(&frame)
+}
+
+/// Read one IPC frame, returning its opcode and JSON payload.
+///
+/// # Errors
+///
+/// Returns an error if the frame cannot be read or is not valid JSON.
+fn read_frame(reader: &mut impl Read) -> IoResult<(u32, Value)> {
+    let op = read_u32(reader)?;
+    let len = usize::try_from(read_u32(reader)?).unwrap_or(usize::MAX);
+    if len > MAX_PAYLOAD_BYTES {
+        return Err(IoError::new(
+            InvalidData,
+            format!("IPC payload of {len} bytes is too large"),
+        ));
+    }
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body)?;
+    Ok((op, from_slice(&body)?))
+}
+
+/// Read a little-endian `u32`.
+///
+/// # Errors
+///
+/// Returns an error if four bytes cannot be read.
+fn read_u32(reader: &mut impl Read) -> IoResult<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// Event name of an IPC reply, e.g. `READY` or `ERROR`.
+fn event_name(reply: &Value) -> Option<&str> {
+    reply.get("evt").and_then(Value::as_str)
+}
+
+/// Error message of an IPC reply, or the whole reply if it has none.
+fn reply_message(reply: &Value) -> String {
+    reply
+        .get("data")
+        .and_then(|d| d.get("message"))
+        .and_then(Value::as_str)
+        .map_or_else(|| reply.to_string(), str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use {
+        anyhow::{Result, ensure},
+        serde_json::json,
+    };
+
+    use crate::ui::discord_ipc::{OP_FRAME, read_frame, write_frame};
+
+    #[test]
+    fn frames_round_trip() -> Result<()> {
+        let payload = json!({ "cmd": "SET_ACTIVITY", "nonce": "1" });
+        let mut buffer = Vec::new();
+        write_frame(&mut buffer, OP_FRAME, &payload)?;
+        ensure!(
+            buffer.get(..4) == Some(OP_FRAME.to_le_bytes().as_slice()),
+            "opcode first"
+        );
+
+        let (op, read) = read_frame(&mut Cursor::new(buffer))?;
+        ensure!(op == OP_FRAME && read == payload, "frame must round-trip");
+        Ok(())
+    }
+
+    #[test]
+    fn oversized_frames_are_rejected() {
+        let mut frame = OP_FRAME.to_le_bytes().to_vec();
+        frame.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(
+            read_frame(&mut Cursor::new(frame)).is_err(),
+            "A corrupt length must not allocate"
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! View > DR Badges group of the preferences dialog.
+//!
+//! Chooses which analyzed albums show their dynamic range on the grid
+//! cover: all of them, only lossless ones, or only those below a value, to
+//! flag loud masters. The library views reload with each change.
+
+use std::{cell::Cell, rc::Rc, sync::Arc};
+
+use {
+    libadwaita::{
+        PreferencesGroup, PreferencesPage, SpinRow, SwitchRow,
+        glib::spawn_future_local,
+        gtk::Adjustment,
+        prelude::{
+            ActionRowExt, ObjectExt, PreferencesGroupExt, PreferencesPageExt, PreferencesRowExt,
+            WidgetExt,
+        },
+    },
+    num_traits::cast::cast,
+    tracing::{error, info, warn},
+};
+
+use crate::{
+    app::AppState, library::dynamic_range::DrBadgeDisplayPolicy, ui::dr_batch::build_dr_batch_row,
+};
+
+/// Build the group choosing which albums show a DR badge in the grid.
+///
+/// The rows share one policy, so changes made in quick succession build
+/// on each other before they are saved.
+pub fn build_dr_badge_group(page: &PreferencesPage, state: &Arc<AppState>) {
+    let group = PreferencesGroup::new();
+    group.set_title("DR Badges");
+    group.set_description(Some(
+        "Dynamic range values on album covers, once an album has been analyzed",
+    ));
+    let policy = Rc::new(Cell::new(state.storage.get_dr_badge_policy()));
+
+    let lossless_row = SwitchRow::new();
+    lossless_row.set_title("Lossless Albums Only");
+    lossless_row.set_subtitle("Lossy encoding changes the measured value");
+    lossless_row.set_active(policy.get().lossless_only);
+    let (state_lossless, policy_lossless) = (Arc::clone(state), Rc::clone(&policy));
+    lossless_row.connect_active_notify(move |row| {
+        let lossless_only = row.is_active();
+        update_dr_badge_policy(&state_lossless, &policy_lossless, |p| {
+            p.lossless_only = lossless_only;
+        });
+    });
+
+    let below = f64::from(policy.get().below.unwrap_or(0));
+    let adjustment = Adjustment::new(below, 0.0, 20.0, 1.0, 5.0, 0.0);
+    let below_row = SpinRow::builder()
+        .title("Only Below DR")
+        .subtitle("Flag loud masters by badging only albums below this value (0 badges all)")
+        .adjustment(&adjustment)
+        .digits(0)
+        .build();
+    let (state_below, policy_below) = (Arc::clone(state), Rc::clone(&policy));
+    below_row.connect_notify_local(Some("value"), move |row, _| {
+        let below: i32 = cast(row.value()).unwrap_or(0);
+        update_dr_badge_policy(&state_below, &policy_below, |p| {
+            p.below = (below > 0).then_some(below);
+        });
+    });
+
+    let show_row = SwitchRow::new();
+    show_row.set_title("Show DR Badges");
+    show_row.set_active(policy.get().enabled);
+    lossless_row.set_sensitive(policy.get().enabled);
+    below_row.set_sensitive(policy.get().enabled);
+    let (state_show, policy_show) = (Arc::clone(state), Rc::clone(&policy));
+    let (lossless_show, below_show) = (lossless_row.clone(), below_row.clone());
+    show_row.connect_active_notify(move |row| {
+        let enabled = row.is_active();
+        lossless_show.set_sensitive(enabled);
+        below_show.set_sensitive(enabled);
+        update_dr_badge_policy(&state_show, &policy_show, |p| p.enabled = enabled);
+    });
+
+    group.add(&show_row);
+    group.add(&lossless_row);
+    group.add(&below_row);
+    group.add(&build_dr_batch_row(state));
+    page.add(&group);
+}
+
+/// Apply `change` to the shared DR badge policy and save it.
+fn update_dr_badge_policy(
+    state: &Arc<AppState>,
+    policy: &Cell<DrBadgeDisplayPolicy>,
+    change: impl FnOnce(&mut DrBadgeDisplayPolicy),
+) {
+    let mut updated = policy.get();
+    change(&mut updated);
+    policy.set(updated);
+    info!(policy = ?updated, "DR badge policy changed");
+    spawn_future_local(save_dr_badge_setting(Arc::clone(state), updated));
+}
+
+/// Persist the DR badge policy and reload the library views.
+async fn save_dr_badge_setting(state: Arc<AppState>, policy: DrBadgeDisplayPolicy) {
+    if let Err(e) = state.storage.set_dr_badge_policy(policy).await {
+        error!(error = %e, "Failed to save DR badge policy");
+    }
+    if let Err(e) = state.refresh_tx.send(()) {
+        warn!(error = %e, "Failed to send refresh signal");
+    }
+}
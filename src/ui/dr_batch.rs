@@ -0,0 +1,276 @@
+//! DR Badges preferences row analyzing the dynamic range of the whole
+//! library.
+//!
+//! "Analyze" measures every album without a DR value in the background,
+//! [`DR_BATCH_LANES`] albums at a time. The row shows how many albums are
+//! done and can pause, resume, or cancel the batch. Cancelling and pausing
+//! take effect once the albums being measured are finished. The batch keeps
+//! running when the preferences are closed.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    path::PathBuf,
+    rc::Rc,
+    sync::Arc,
+};
+
+use {
+    libadwaita::{
+        ActionRow,
+        gio::spawn_blocking,
+        glib::{WeakRef, spawn_future_local},
+        gtk::{Align::Center, Button, accessible::Property::Label as PropertyLabel},
+        prelude::{AccessibleExtManual, ActionRowExt, ButtonExt, ObjectExt, WidgetExt},
+    },
+    tokio::sync::watch::Receiver,
+    tracing::{debug, info, warn},
+};
+
+use crate::{
+    app::AppState,
+    library::dr_batch::{
+        DR_BATCH_LANES,
+        DrBatchPhase::{Cancelled, Idle, Paused, Running},
+        DrBatchStatus, measure_album,
+    },
+    storage::Storage,
+};
+
+/// Buttons of the analysis row.
+#[derive(Clone)]
+struct BatchButtons {
+    /// Starts the batch.
+    start: Button,
+    /// Pauses or resumes the running batch.
+    pause: Button,
+    /// Stops the running batch.
+    cancel: Button,
+}
+
+impl BatchButtons {
+    /// Show the buttons that apply to `status`.
+    fn show(&self, status: &DrBatchStatus) {
+        self.start.set_visible(status.phase == Idle);
+        self.pause.set_visible(status.is_active());
+        self.cancel.set_visible(status.is_active());
+        let (icon_name, label) = if status.phase == Paused {
+            ("media-playback-start-symbolic", "Resume")
+        } else {
+            ("media-playback-pause-symbolic", "Pause")
+        };
+        self.pause.set_icon_name(icon_name);
+        self.pause.set_tooltip_text(Some(label));
+        self.pause.update_property(&[PropertyLabel(label)]);
+    }
+}
+
+/// Build the row starting and following the library-wide DR analysis.
+#[must_use]
+pub fn build_dr_batch_row(state: &Arc<AppState>) -> ActionRow {
+    let row = ActionRow::builder().title("Analyze Dynamic Range").build();
+    let buttons = BatchButtons {
+        start: Button::builder().label("Analyze").valign(Center).build(),
+        pause: Button::builder()
+            .valign(Center)
+            .css_classes(["flat", "circular"])
+            .build(),
+        cancel: Button::builder()
+            .icon_name("process-stop-symbolic")
+            .tooltip_text("Cancel")
+            .valign(Center)
+            .css_classes(["flat", "circular"])
+            .build(),
+    };
+    buttons.cancel.update_property(&[PropertyLabel("Cancel")]);
+    row.add_suffix(&buttons.pause);
+    row.add_suffix(&buttons.cancel);
+    row.add_suffix(&buttons.start);
+
+    let status = *state.dr_batch_tx.borrow();
+    row.set_subtitle(&status.label());
+    buttons.show(&status);
+
+    let state_start = Arc::clone(state);
+    buttons.start.connect_clicked(move |_| {
+        spawn_future_local(run_dr_batch(Arc::clone(&state_start)));
+    });
+    let state_pause = Arc::clone(state);
+    buttons.pause.connect_clicked(move |_| {
+        state_pause.dr_batch_tx.send_modify(|s| {
+            s.phase = match s.phase {
+                Running => Paused,
+                Paused => Running,
+                phase => phase,
+            };
+        });
+    });
+    let state_cancel = Arc::clone(state);
+    buttons.cancel.connect_clicked(move |_| {
+        state_cancel.dr_batch_tx.send_modify(DrBatchStatus::cancel);
+    });
+
+    let status_rx = state.dr_batch_tx.subscribe();
+    spawn_future_local(follow_batch_status(status_rx, row.downgrade(), buttons));
+
+    row
+}
+
+/// Show every change of the batch status on the row until it is dropped.
+async fn follow_batch_status(
+    mut status_rx: Receiver<DrBatchStatus>,
+    weak_row: WeakRef<ActionRow>,
+    buttons: BatchButtons,
+) {
+    while status_rx.changed().await.is_ok() {
+        let status = *status_rx.borrow_and_update();
+        let Some(row) = weak_row.upgrade() else {
+            break;
+        };
+        row.set_subtitle(&status.label());
+        buttons.show(&status);
+    }
+}
+
+/// Measure every album without a DR value, unless a batch is running.
+async fn run_dr_batch(state: Arc<AppState>) {
+    if state.dr_batch_tx.borrow().phase != Idle {
+        return;
+    }
+    state.dr_batch_tx.send_replace(DrBatchStatus::started(0));
+    let album_ids = match state.storage.get_album_ids_without_dr().await {
+        Ok(ids) => ids,
+        Err(e) => {
+            warn!(error = %e, "Failed to find albums without DR");
+            state.dr_batch_tx.send_replace(DrBatchStatus::default());
+            state
+                .send_toast(format!("Could not start DR analysis: {e}"))
+                .await;
+            return;
+        }
+    };
+    if album_ids.is_empty() {
+        state.dr_batch_tx.send_replace(DrBatchStatus::default());
+        state
+            .send_toast("Every album already has a DR value".to_string())
+            .await;
+        return;
+    }
+
+    info!(albums = album_ids.len(), "DR analysis started");
+    state
+        .dr_batch_tx
+        .send_replace(DrBatchStatus::started(album_ids.len()));
+    let pending = Rc::new(RefCell::new(VecDeque::from(album_ids)));
+    let lanes_left = Rc::new(Cell::new(DR_BATCH_LANES));
+    for _ in 0..DR_BATCH_LANES {
+        spawn_future_local(run_lane(
+            Arc::clone(&state),
+            Rc::clone(&pending),
+            Rc::clone(&lanes_left),
+        ));
+    }
+}
+
+/// Measure pending albums one after another until none are left or the
+/// batch is cancelled. The last lane to stop finishes the batch.
+async fn run_lane(
+    state: Arc<AppState>,
+    pending: Rc<RefCell<VecDeque<i64>>>,
+    lanes_left: Rc<Cell<usize>>,
+) {
+    while wait_until_running(&state).await {
+        let Some(album_id) = pending.borrow_mut().pop_front() else {
+            break;
+        };
+        let measured = measure(&state, album_id).await;
+        state
+            .dr_batch_tx
+            .send_modify(|s| *s = s.after_album(measured));
+    }
+    lanes_left.set(lanes_left.get() - 1);
+    if lanes_left.get() == 0 {
+        finish_batch(&state).await;
+    }
+}
+
+/// Wait while the batch is paused.
+///
+/// # Returns
+///
+/// Whether measuring goes on, `false` once the batch is cancelled.
+async fn wait_until_running(state: &AppState) -> bool {
+    let mut status_rx = state.dr_batch_tx.subscribe();
+    loop {
+        let phase = status_rx.borrow_and_update().phase;
+        match phase {
+            Running => return true,
+            Paused => {}
+            Idle | Cancelled => return false,
+        }
+        if status_rx.changed().await.is_err() {
+            return false;
+        }
+    }
+}
+
+/// Derive and store the DR value of one album.
+///
+/// # Returns
+///
+/// Whether a value was stored.
+async fn measure(state: &AppState, album_id: i64) -> bool {
+    let paths: Vec<PathBuf> = match state.storage.get_tracks_by_album(album_id).await {
+        Ok(tracks) => tracks
+            .into_iter()
+            .map(|t| PathBuf::from(t.audio.file_path))
+            .collect(),
+        Err(e) => {
+            warn!(error = %e, album_id, "Failed to load album tracks");
+            return false;
+        }
+    };
+
+    let scheduler = Arc::clone(&state.scheduler);
+    let value = match spawn_blocking(move || measure_album(&scheduler, &paths)).await {
+        Ok(Ok(Some(dr))) => dr.value,
+        Ok(Ok(None)) => {
+            info!(album_id, "Album has no audio to measure");
+            return false;
+        }
+        Ok(Err(e)) => {
+            warn!(error = %e, album_id, "Failed to measure album DR");
+            return false;
+        }
+        Err(e) => {
+            warn!(error = ?e, album_id, "DR analysis panicked");
+            return false;
+        }
+    };
+
+    if let Err(e) = state.storage.set_album_dr(album_id, Some(value)).await {
+        warn!(error = %e, album_id, "Failed to store album DR");
+        return false;
+    }
+    debug!(album_id, dr = value, "Album DR analyzed");
+    true
+}
+
+/// Mark the batch finished, reload the library views, and report the result.
+async fn finish_batch(state: &AppState) {
+    let status = DrBatchStatus {
+        phase: Idle,
+        ..*state.dr_batch_tx.borrow()
+    };
+    state.dr_batch_tx.send_replace(status);
+    info!(
+        done = status.done,
+        total = status.total,
+        failed = status.failed,
+        "DR analysis finished"
+    );
+    if let Err(e) = state.refresh_tx.send(()) {
+        warn!(error = %e, "Failed to send refresh signal");
+    }
+    state.send_toast(status.label()).await;
+}
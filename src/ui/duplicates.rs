@@ -0,0 +1,203 @@
+//! Duplicate track review for the Library > Maintenance group.
+//!
+//! Lists every group of tracks holding the same recording. The best copy of
+//! each group is kept; the other copies start checked for removal and can
+//! be unchecked to ignore them. Checked copies are moved to the trash and
+//! removed from the library so the next scan does not add them again.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        ActionRow, AlertDialog,
+        ResponseAppearance::Destructive,
+        gio::File,
+        glib::{Priority, spawn_future_local},
+        gtk::{
+            Align::Center, Box, Button, CheckButton, ListBox, Orientation::Vertical,
+            PolicyType::Never, ScrolledWindow, SelectionMode::None as SelectNone,
+        },
+        prelude::{
+            ActionRowExt, AdwDialogExt, AlertDialogExt, BoxExt, ButtonExt, CheckButtonExt, FileExt,
+            PreferencesRowExt, WidgetExt,
+        },
+    },
+    tracing::{info, warn},
+};
+
+use crate::{
+    app::AppState,
+    storage::duplicates::{DuplicateCandidate, DuplicateGroup},
+};
+
+/// A copy offered for removal and the check button selecting it.
+struct RemovalChoice {
+    /// Checked while the copy should be removed.
+    check: CheckButton,
+    /// Track identifier of the copy.
+    track_id: i64,
+    /// Audio file of the copy.
+    path: String,
+}
+
+/// Build the row that searches the library for duplicate tracks.
+#[must_use]
+pub fn build_duplicates_row(state: &Arc<AppState>) -> ActionRow {
+    let find_btn = Button::builder().label("Find").valign(Center).build();
+    let row = ActionRow::builder()
+        .title("Duplicate Tracks")
+        .subtitle("Keep the best copy of tracks ripped or downloaded more than once")
+        .build();
+    row.add_suffix(&find_btn);
+    row.set_activatable_widget(Some(&find_btn));
+
+    let state_find = Arc::clone(state);
+    find_btn.connect_clicked(move |btn| {
+        btn.set_sensitive(false);
+        spawn_future_local(find_duplicates(Arc::clone(&state_find), btn.clone()));
+    });
+    row
+}
+
+/// Search for duplicates and present them for review.
+async fn find_duplicates(state: Arc<AppState>, button: Button) {
+    let groups = match state.storage.find_duplicates().await {
+        Ok(groups) => groups,
+        Err(e) => {
+            warn!(error = %e, "Failed to find duplicate tracks");
+            state
+                .send_toast(format!("Could not search for duplicates: {e}"))
+                .await;
+            button.set_sensitive(true);
+            return;
+        }
+    };
+    info!(groups = groups.len(), "Duplicate track groups found");
+    if groups.is_empty() {
+        state
+            .send_toast("No duplicate tracks found".to_string())
+            .await;
+        button.set_sensitive(true);
+        return;
+    }
+    present_duplicates_dialog(&state, &button, &groups);
+}
+
+/// Show the duplicate groups and remove the copies the user leaves checked.
+fn present_duplicates_dialog(state: &Arc<AppState>, button: &Button, groups: &[DuplicateGroup]) {
+    let dialog = AlertDialog::new(
+        Some("Duplicate Tracks"),
+        Some(
+            "The best copy of each track is kept. Checked copies are moved to the trash and \
+             removed from the library; uncheck a copy to keep it.",
+        ),
+    );
+
+    let list = Box::builder().orientation(Vertical).spacing(12).build();
+    let mut choices = Vec::new();
+    for group in groups {
+        list.append(&build_group_list(group, &mut choices));
+    }
+    let scroll = ScrolledWindow::builder()
+        .hscrollbar_policy(Never)
+        .max_content_height(420)
+        .propagate_natural_height(true)
+        .child(&list)
+        .build();
+    dialog.set_extra_child(Some(&scroll));
+
+    dialog.add_response("cancel", "Cancel");
+    dialog.add_response("remove", "Remove Checked Copies");
+    dialog.set_response_appearance("remove", Destructive);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+
+    let state = Arc::clone(state);
+    let button_response = button.clone();
+    dialog.connect_response(None, move |_, response| {
+        if response == "remove" {
+            let selected = checked_copies(&choices);
+            spawn_future_local(remove_copies(Arc::clone(&state), selected));
+        }
+        button_response.set_sensitive(true);
+    });
+    dialog.present(Some(button));
+}
+
+/// Build the rows of one group: the kept copy, then one checkable row per extra.
+fn build_group_list(group: &DuplicateGroup, choices: &mut Vec<RemovalChoice>) -> ListBox {
+    let list = ListBox::builder()
+        .selection_mode(SelectNone)
+        .css_classes(["boxed-list"])
+        .build();
+    if let Some(best) = group.best() {
+        let row = copy_row(best);
+        row.set_title(&format!("{} \u{2014} {}", best.title, best.artist));
+        row.set_subtitle(&format!(
+            "Keep \u{b7} {} \u{b7} {}",
+            best.quality_label(),
+            best.file_path
+        ));
+        list.append(&row);
+    }
+    for extra in group.extras() {
+        let row = copy_row(extra);
+        let check = CheckButton::builder().active(true).valign(Center).build();
+        row.add_prefix(&check);
+        row.set_activatable_widget(Some(&check));
+        list.append(&row);
+        choices.push(RemovalChoice {
+            check,
+            track_id: extra.id,
+            path: extra.file_path.clone(),
+        });
+    }
+    list
+}
+
+/// Row describing the quality and location of one copy.
+fn copy_row(copy: &DuplicateCandidate) -> ActionRow {
+    ActionRow::builder()
+        .title(copy.quality_label())
+        .subtitle(&copy.file_path)
+        .subtitle_lines(2)
+        .build()
+}
+
+/// Track IDs and paths of the copies still checked for removal.
+fn checked_copies(choices: &[RemovalChoice]) -> Vec<(i64, String)> {
+    choices
+        .iter()
+        .filter(|choice| choice.check.is_active())
+        .map(|choice| (choice.track_id, choice.path.clone()))
+        .collect()
+}
+
+/// Move the `copies` to the trash and remove them from the library.
+///
+/// A copy whose file cannot be trashed stays in the library.
+async fn remove_copies(state: Arc<AppState>, copies: Vec<(i64, String)>) {
+    let mut removed = Vec::new();
+    for (track_id, path) in copies {
+        match File::for_path(&path).trash_future(Priority::DEFAULT).await {
+            Ok(()) => removed.push(track_id),
+            Err(e) => warn!(error = %e, path, "Failed to move duplicate to trash"),
+        }
+    }
+    let message = match state.storage.remove_tracks(&removed).await {
+        Ok(report) => {
+            if let Err(e) = state.refresh_tx.send(()) {
+                warn!(error = %e, "Failed to send refresh signal");
+            }
+            format!(
+                "Moved {} duplicate copies to the trash",
+                report.tracks_removed
+            )
+        }
+        Err(e) => {
+            warn!(error = %e, "Failed to remove duplicate tracks");
+            format!("Could not remove duplicates from the library: {e}")
+        }
+    };
+    state.send_toast(message).await;
+}
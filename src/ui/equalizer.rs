@@ -0,0 +1,242 @@
+//! Equalizer page of the preferences dialog.
+//!
+//! Offers an enable switch, a preset selector, and one gain row per band.
+//! Editing a band switches the preset to Custom; choosing a preset rewrites
+//! every band. Changes apply to the playback engine immediately and are
+//! persisted to [`EqualizerSettings`].
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering::Relaxed},
+};
+
+use {
+    libadwaita::{
+        ComboRow, PreferencesDialog, PreferencesGroup, PreferencesPage, SpinRow, SwitchRow,
+        glib::{WeakRef, spawn_future_local},
+        gtk::{Adjustment, StringList},
+        prelude::{
+            ComboRowExt, ObjectExt, PreferencesDialogExt, PreferencesGroupExt, PreferencesPageExt,
+            PreferencesRowExt,
+        },
+    },
+    parking_lot::Mutex,
+    tracing::{error, warn},
+};
+
+use crate::{
+    app::AppState,
+    playback::{
+        control::PlaybackController,
+        engine::PlaybackEngine,
+        equalizer::{
+            BAND_FREQUENCIES,
+            EqPreset::{self, Custom},
+            EqualizerSettings, MAX_GAIN_DB,
+        },
+    },
+    storage::database::SqliteStorage,
+};
+
+/// State shared between the preset selector and the band rows.
+struct EqualizerPageState {
+    /// Application state for playback and persistence.
+    app: Arc<AppState>,
+    /// Settings as currently shown on the page.
+    current: Mutex<EqualizerSettings>,
+    /// Set while the page updates its own rows, to suppress feedback.
+    updating: AtomicBool,
+}
+
+impl EqualizerPageState {
+    /// Update the page settings and persist them in the background.
+    fn commit(&self, update: impl FnOnce(&mut EqualizerSettings)) {
+        let mut current = self.current.lock();
+        update(&mut current);
+        let settings = *current;
+        drop(current);
+        spawn_future_local(save_equalizer(Arc::clone(&self.app.storage), settings));
+    }
+}
+
+/// Persist equalizer settings, logging on failure.
+async fn save_equalizer(storage: Arc<SqliteStorage>, settings: EqualizerSettings) {
+    if let Err(e) = storage.set_equalizer(settings).await {
+        error!(error = %e, "Failed to save equalizer settings");
+    }
+}
+
+/// Apply per-band gains to the playback engine, logging failures.
+fn apply_equalizer_gains(playback: &PlaybackEngine, gains_db: &[f64]) {
+    for (index, gain_db) in gains_db.iter().enumerate() {
+        if let Err(e) = playback.set_eq_band(index, *gain_db) {
+            warn!(error = %e, band = index, "Failed to apply equalizer band");
+        }
+    }
+}
+
+/// Apply saved equalizer settings to the playback engine.
+pub fn apply_equalizer_settings(playback: &PlaybackEngine, settings: &EqualizerSettings) {
+    apply_equalizer_gains(playback, &settings.gains_db);
+    if let Err(e) = playback.set_eq_enabled(settings.enabled) {
+        warn!(error = %e, "Failed to apply equalizer state");
+    }
+}
+
+/// Format a band centre frequency for display.
+fn band_label(frequency: f64) -> String {
+    if frequency >= 1000.0 {
+        format!("{} kHz", frequency / 1000.0)
+    } else {
+        format!("{frequency} Hz")
+    }
+}
+
+/// Position of `preset` in the preset selector.
+fn preset_position(preset: EqPreset) -> u32 {
+    EqPreset::ALL
+        .iter()
+        .position(|p| *p == preset)
+        .map_or(0, |p| u32::try_from(p).unwrap_or(0))
+}
+
+/// Build the Equalizer preferences page.
+pub fn build_equalizer_page(dialog: &PreferencesDialog, state: &Arc<AppState>) {
+    let page = PreferencesPage::new();
+    page.set_title("Equalizer");
+    page.set_icon_name(Some("multimedia-equalizer-symbolic"));
+
+    let settings = state.storage.get_equalizer();
+    let shared = Arc::new(EqualizerPageState {
+        app: Arc::clone(state),
+        current: Mutex::new(settings),
+        updating: AtomicBool::new(false),
+    });
+
+    let band_rows: Vec<SpinRow> = BAND_FREQUENCIES
+        .iter()
+        .zip(settings.gains_db)
+        .map(|(frequency, gain_db)| {
+            let adjustment = Adjustment::new(gain_db, -MAX_GAIN_DB, MAX_GAIN_DB, 0.5, 3.0, 0.0);
+            SpinRow::builder()
+                .title(band_label(*frequency))
+                .subtitle("Gain in dB")
+                .adjustment(&adjustment)
+                .digits(1)
+                .build()
+        })
+        .collect();
+
+    let general_group = PreferencesGroup::new();
+    general_group.set_title("Equalizer");
+    general_group.set_description(Some("Bypassed in bit-perfect mode"));
+    general_group.add(&build_enable_row(&shared, settings.enabled));
+    let preset_combo = build_preset_combo(&shared, settings.preset, &band_rows);
+    general_group.add(&preset_combo);
+    page.add(&general_group);
+
+    let bands_group = PreferencesGroup::new();
+    bands_group.set_title("Bands");
+    for (index, row) in band_rows.iter().enumerate() {
+        connect_band_row(&shared, row, index, &preset_combo);
+        bands_group.add(row);
+    }
+    page.add(&bands_group);
+
+    dialog.add(&page);
+}
+
+/// Build the switch turning the equalizer on or off.
+fn build_enable_row(shared: &Arc<EqualizerPageState>, enabled: bool) -> SwitchRow {
+    let enable_row = SwitchRow::new();
+    enable_row.set_title("Enable Equalizer");
+    enable_row.set_active(enabled);
+
+    let shared = Arc::clone(shared);
+    enable_row.connect_active_notify(move |row| {
+        let enabled = row.is_active();
+        if let Err(e) = shared.app.playback.set_eq_enabled(enabled) {
+            warn!(error = %e, "Failed to toggle equalizer");
+        }
+        shared.commit(|s| s.enabled = enabled);
+    });
+
+    enable_row
+}
+
+/// Build the preset selector, rewriting every band row on selection.
+fn build_preset_combo(
+    shared: &Arc<EqualizerPageState>,
+    selected: EqPreset,
+    band_rows: &[SpinRow],
+) -> ComboRow {
+    let labels: Vec<&str> = EqPreset::ALL.iter().map(|p| p.label()).collect();
+    let preset_combo = ComboRow::builder()
+        .title("Preset")
+        .model(&StringList::new(&labels))
+        .build();
+    preset_combo.set_selected(preset_position(selected));
+
+    let shared = Arc::clone(shared);
+    let weak_rows: Vec<WeakRef<SpinRow>> = band_rows.iter().map(ObjectExt::downgrade).collect();
+    preset_combo.connect_selected_notify(move |combo| {
+        if shared.updating.load(Relaxed) {
+            return;
+        }
+        let Some(preset) = EqPreset::ALL.get(combo.selected() as usize).copied() else {
+            return;
+        };
+        let Some(gains_db) = preset.gains_db() else {
+            shared.commit(|s| s.preset = preset);
+            return;
+        };
+
+        shared.updating.store(true, Relaxed);
+        weak_rows
+            .iter()
+            .zip(gains_db)
+            .filter_map(|(row, gain_db)| Some((row.upgrade()?, gain_db)))
+            .for_each(|(row, gain_db)| row.set_value(gain_db));
+        shared.updating.store(false, Relaxed);
+
+        apply_equalizer_gains(&shared.app.playback, &gains_db);
+        shared.commit(|s| {
+            s.preset = preset;
+            s.gains_db = gains_db;
+        });
+    });
+
+    preset_combo
+}
+
+/// Apply band edits to the engine and mark the preset as Custom.
+fn connect_band_row(
+    shared: &Arc<EqualizerPageState>,
+    row: &SpinRow,
+    index: usize,
+    preset_combo: &ComboRow,
+) {
+    let shared = Arc::clone(shared);
+    let preset_combo = preset_combo.clone();
+    row.connect_notify_local(Some("value"), move |row, _| {
+        if shared.updating.load(Relaxed) {
+            return;
+        }
+        let gain_db = row.value();
+        if let Err(e) = shared.app.playback.set_eq_band(index, gain_db) {
+            warn!(error = %e, band = index, "Failed to set equalizer band");
+        }
+
+        shared.updating.store(true, Relaxed);
+        preset_combo.set_selected(preset_position(Custom));
+        shared.updating.store(false, Relaxed);
+
+        shared.commit(|s| {
+            s.preset = Custom;
+            s.gains_db
+                .get_mut(index)
+                .into_iter()
+                .for_each(|g| *g = gain_db);
+        });
+    });
+}
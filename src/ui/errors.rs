@@ -0,0 +1,144 @@
+//! Central sink for errors shown to the user.
+//!
+//! Background failures such as decode errors, a lost audio device, or
+//! library scan and watcher errors otherwise only reach the log.
+//! [`ErrorReporter`] logs them and queues a toast on the main window so
+//! the user can act on them.
+
+use {async_channel::Sender, libadwaita::glib::spawn_future_local, tracing::warn};
+
+use crate::{
+    app::AppState,
+    playback::{
+        control::PlaybackController,
+        engine::PlaybackEvent::{self, DeviceLost, Error},
+    },
+};
+
+/// Queues user-facing error toasts.
+#[derive(Clone)]
+pub struct ErrorReporter {
+    /// Toast channel drained by the main window.
+    toast_tx: Sender<String>,
+}
+
+impl ErrorReporter {
+    /// Create a reporter that queues toasts on `toast_tx`.
+    #[must_use]
+    pub const fn new(toast_tx: Sender<String>) -> Self {
+        Self { toast_tx }
+    }
+
+    /// Log `message` and show it to the user.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Part of the application the error comes from
+    /// * `message` - Human-readable error description
+    pub fn report(&self, source: ErrorSource, message: &str) {
+        warn!(
+            source = source.label(),
+            error = message,
+            "Reporting error to user"
+        );
+        if let Err(e) = self
+            .toast_tx
+            .try_send(format!("{}: {message}", source.label()))
+        {
+            warn!(error = %e, "Failed to enqueue error toast");
+        }
+    }
+
+    /// Report `event` if it signals a playback failure.
+    fn report_playback_event(&self, event: &PlaybackEvent) {
+        if let Some(message) = playback_error_message(event) {
+            self.report(ErrorSource::Playback, &message);
+        }
+    }
+}
+
+/// Part of the application an error comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorSource {
+    /// Decoding or audio output.
+    Playback,
+    /// Scanning or watching the library directories.
+    Library,
+}
+
+impl ErrorSource {
+    /// Prefix shown before the error message.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Playback => "Playback error",
+            Self::Library => "Library error",
+        }
+    }
+}
+
+/// User-facing message for a playback event, if it signals a failure.
+#[must_use]
+pub fn playback_error_message(event: &PlaybackEvent) -> Option<String> {
+    match event {
+        Error { error } => Some(error.clone()),
+        DeviceLost { error } => Some(format!("Audio device lost \u{2014} {error}")),
+        _ => None,
+    }
+}
+
+/// Report playback failures from the engine as toasts.
+pub fn wire_error_reporting(state: &AppState) {
+    let rx = state.playback.subscribe();
+    let reporter = state.error_reporter.clone();
+    spawn_future_local(async move {
+        while let Ok(event) = rx.recv().await {
+            reporter.report_playback_event(&event);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        anyhow::{Result, ensure},
+        async_channel::unbounded,
+    };
+
+    use crate::{
+        playback::engine::PlaybackEvent::{DeviceLost, Error, Paused},
+        ui::errors::{
+            ErrorReporter,
+            ErrorSource::{Library, Playback},
+            playback_error_message,
+        },
+    };
+
+    #[test]
+    fn report_queues_prefixed_toast() -> Result<()> {
+        let (tx, rx) = unbounded();
+        let reporter = ErrorReporter::new(tx);
+        reporter.report(Library, "Permission denied");
+        reporter.report_playback_event(&Error {
+            error: "Corrupt frame".to_string(),
+        });
+        reporter.report_playback_event(&Paused);
+
+        ensure!(rx.try_recv()? == "Library error: Permission denied");
+        ensure!(rx.try_recv()? == format!("{}: Corrupt frame", Playback.label()));
+        ensure!(rx.is_empty(), "Non-error events must not be reported");
+        Ok(())
+    }
+
+    #[test]
+    fn device_loss_is_reported() {
+        let message = playback_error_message(&DeviceLost {
+            error: "unplugged".to_string(),
+        });
+        assert_eq!(
+            message.as_deref(),
+            Some("Audio device lost \u{2014} unplugged"),
+            "Device loss must name the cause"
+        );
+    }
+}
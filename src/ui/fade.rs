@@ -0,0 +1,46 @@
+//! Audio > Playback row setting the fade on play and pause.
+//!
+//! Playback fades in when it starts or resumes and fades out before it
+//! pauses or stops. Bit-perfect output is never faded.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{SpinRow, glib::spawn_future_local, gtk::Adjustment, prelude::ObjectExt},
+    num_traits::cast::cast,
+    tracing::{error, warn},
+};
+
+use crate::{
+    app::AppState, playback::control::PlaybackController, storage::database::SqliteStorage,
+};
+
+/// Build the play/pause fade length row (milliseconds, `0` disables).
+pub fn build_fade_row(state: &Arc<AppState>) -> SpinRow {
+    let initial_ms = f64::from(state.storage.get_fade_ms());
+    let adjustment = Adjustment::new(initial_ms, 0.0, 2000.0, 50.0, 200.0, 0.0);
+    let fade_row = SpinRow::builder()
+        .title("Fade on Play and Pause")
+        .subtitle("Fade length in milliseconds, skipped in bit-perfect mode (0 disables)")
+        .adjustment(&adjustment)
+        .digits(0)
+        .build();
+
+    let state_fade = Arc::clone(state);
+    fade_row.connect_notify_local(Some("value"), move |row, _| {
+        let fade_ms: u32 = cast(row.value()).unwrap_or(0);
+        if let Err(e) = state_fade.playback.set_fade_ms(fade_ms) {
+            warn!(error = %e, "Failed to set fade length from preferences");
+        }
+        spawn_future_local(save_fade_setting(Arc::clone(&state_fade.storage), fade_ms));
+    });
+
+    fade_row
+}
+
+/// Persist play/pause fade length, logging on failure.
+async fn save_fade_setting(storage: Arc<SqliteStorage>, fade_ms: u32) {
+    if let Err(e) = storage.set_fade_ms(fade_ms).await {
+        error!(error = %e, "Failed to save fade setting");
+    }
+}
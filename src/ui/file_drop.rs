@@ -0,0 +1,170 @@
+//! Drag-and-drop of folders and audio files onto the main window.
+//!
+//! Dropped folders become library directories, and dropped audio files add
+//! the folder they live in. Folders already inside a library directory are
+//! only rescanned. The window is outlined while a drag hovers over it.
+
+use std::{path::PathBuf, sync::Arc};
+
+use {
+    libadwaita::{
+        ApplicationWindow,
+        gdk::{DragAction, FileList},
+        glib::{spawn_future_local, types::StaticType},
+        gtk::DropTarget,
+        prelude::{FileExt, WidgetExt},
+    },
+    tokio::{spawn, sync::watch::Sender as TokioSender},
+    tracing::{info, warn},
+};
+
+use crate::{
+    app::AppState,
+    library::{
+        formats::is_supported_audio_format,
+        scanner::{FsScanner, LibraryScanner},
+    },
+    storage::{Storage, database::SqliteStorage},
+};
+
+/// CSS class that outlines the window while a drop is hovering.
+const DROP_CSS_CLASS: &str = "library-drop";
+
+/// Accept folders and audio files dropped onto `window`.
+pub fn install_file_drop(window: &ApplicationWindow, state: &Arc<AppState>) {
+    let target = DropTarget::new(FileList::static_type(), DragAction::COPY);
+    let state = Arc::clone(state);
+    target.connect_drop(move |_, value, _, _| {
+        let Ok(files) = value.get::<FileList>() else {
+            return false;
+        };
+        let paths: Vec<PathBuf> = files.files().iter().filter_map(FileExt::path).collect();
+        let folders = library_folders(&paths);
+        if folders.is_empty() {
+            info!(dropped = paths.len(), "Drop held no folders or audio files");
+            return false;
+        }
+        spawn_future_local(add_dropped_folders(Arc::clone(&state), folders));
+        true
+    });
+    window.add_css_class(DROP_CSS_CLASS);
+    window.add_controller(target);
+}
+
+/// Folders to add for the dropped `paths`.
+///
+/// Directories are taken as they are and supported audio files contribute
+/// their parent folder. Other files are ignored, duplicates are dropped,
+/// and folders nested in another chosen folder are left to their parent.
+fn library_folders(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut folders: Vec<PathBuf> = paths
+        .iter()
+        .filter_map(|path| {
+            if path.is_dir() {
+                Some(path.clone())
+            } else if is_supported_audio_format(path) {
+                path.parent().map(PathBuf::from)
+            } else {
+                None
+            }
+        })
+        .collect();
+    folders.sort();
+    folders.dedup();
+    let nested: Vec<bool> = folders
+        .iter()
+        .map(|f| {
+            folders
+                .iter()
+                .any(|other| other != f && f.starts_with(other))
+        })
+        .collect();
+    folders
+        .into_iter()
+        .zip(nested)
+        .filter_map(|(folder, nested)| (!nested).then_some(folder))
+        .collect()
+}
+
+/// Register the dropped folders as library directories and scan them.
+async fn add_dropped_folders(state: Arc<AppState>, folders: Vec<PathBuf>) {
+    let known: Vec<PathBuf> = match state.storage.list_library_directories().await {
+        Ok(dirs) => dirs.into_iter().map(|d| PathBuf::from(d.path)).collect(),
+        Err(e) => {
+            warn!(error = %e, "Failed to list library directories");
+            Vec::new()
+        }
+    };
+    for folder in &folders {
+        if known.iter().any(|dir| folder.starts_with(dir)) {
+            continue;
+        }
+        if let Err(e) = state.storage.add_library_directory(folder).await {
+            warn!(error = %e, path = %folder.display(), "Failed to add library directory");
+        }
+    }
+
+    let message = match folders.as_slice() {
+        [folder] => format!("Adding {} to the library", folder.display()),
+        _ => format!("Adding {} folders to the library", folders.len()),
+    };
+    state.send_toast(message).await;
+
+    info!(count = folders.len(), "Scanning dropped folders");
+    spawn(scan_folders(
+        Arc::clone(&state.scanner),
+        folders,
+        state.refresh_tx.clone(),
+    ));
+}
+
+/// Scan `folders` one after another, then refresh the library views.
+async fn scan_folders(
+    scanner: Arc<FsScanner<SqliteStorage>>,
+    folders: Vec<PathBuf>,
+    refresh_tx: TokioSender<()>,
+) {
+    for folder in &folders {
+        if let Err(e) = scanner.scan_directory(folder).await {
+            warn!(error = %e, path = %folder.display(), "Failed to scan dropped folder");
+        }
+    }
+    if let Err(e) = refresh_tx.send(()) {
+        warn!(error = %e, "Failed to send refresh signal");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir, write};
+
+    use {
+        anyhow::{Result, ensure},
+        tempfile::tempdir,
+    };
+
+    use crate::ui::file_drop::library_folders;
+
+    #[test]
+    fn dropped_files_add_their_folder_once() -> Result<()> {
+        let dir = tempdir()?;
+        let album = dir.path().join("Album");
+        let nested = album.join("CD1");
+        let loose = dir.path().join("Loose");
+        create_dir(&album)?;
+        create_dir(&nested)?;
+        create_dir(&loose)?;
+        let track = loose.join("01.flac");
+        let cover = loose.join("cover.jpg");
+        write(&track, b"")?;
+        write(&cover, b"")?;
+
+        let folders = library_folders(&[nested, album.clone(), track.clone(), track, cover]);
+
+        ensure!(
+            folders == vec![album, loose],
+            "nested folders and duplicates collapse, non-audio files are ignored"
+        );
+        Ok(())
+    }
+}
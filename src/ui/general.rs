@@ -0,0 +1,261 @@
+//! General page of the preferences dialog.
+//!
+//! Lists every keyboard shortcut with its current accelerator. Activating a
+//! row waits for the next key combination; Backspace disables the shortcut
+//! and Escape keeps the old one. Bindings shared by two actions are flagged
+//! on both rows. Whether the last session is resumed at launch, the share
+//! text template, settings import and export follow the shortcuts.
+
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use {
+    libadwaita::{
+        ActionRow, AlertDialog, PreferencesDialog, PreferencesGroup, PreferencesPage,
+        ShortcutLabel, SwitchRow,
+        gdk::{Key, ModifierType},
+        glib::{
+            Propagation::{Proceed, Stop},
+            spawn_future_local,
+        },
+        gtk::{
+            Align::Center, Button, EventControllerKey, PropagationPhase::Capture,
+            accelerator_get_default_mod_mask, accelerator_get_label, accelerator_name,
+            accelerator_parse, accelerator_valid,
+        },
+        prelude::{
+            ActionRowExt, AdwDialogExt, AlertDialogExt, ButtonExt, EventControllerExt,
+            PreferencesDialogExt, PreferencesGroupExt, PreferencesPageExt, PreferencesRowExt,
+            WidgetExt,
+        },
+    },
+    tracing::{error, info},
+};
+
+use crate::{
+    app::AppState,
+    config::shortcuts::{ShortcutAction, ShortcutSettings},
+    ui::{
+        diagnostics::build_diagnostics_group, player::share::build_share_group,
+        transfer::build_transfer_group,
+    },
+};
+
+/// Shortcut settings as edited on the page, shared by all rows.
+struct ShortcutsPageState {
+    /// Application state used to persist and broadcast bindings.
+    state: Arc<AppState>,
+    /// Bindings as currently shown on the page.
+    current: RefCell<ShortcutSettings>,
+    /// Row and accelerator label of every action.
+    rows: Vec<(ShortcutAction, ActionRow, ShortcutLabel)>,
+}
+
+impl ShortcutsPageState {
+    /// Update the bindings, refresh the rows, and apply and persist them.
+    fn commit(&self, update: impl FnOnce(&mut ShortcutSettings)) {
+        let mut current = self.current.borrow_mut();
+        update(&mut current);
+        let settings = current.clone();
+        drop(current);
+
+        self.refresh_rows(&settings);
+        self.state.shortcuts_tx.send_if_modified(|active| {
+            let changed = *active != settings;
+            active.clone_from(&settings);
+            changed
+        });
+        spawn_future_local(save_shortcuts(Arc::clone(&self.state), settings));
+    }
+
+    /// Bind `action` to a captured accelerator, warning about clashes.
+    fn rebind(&self, action: ShortcutAction, accelerator: &str) {
+        info!(action = ?action, accelerator, "Keyboard shortcut changed");
+        self.commit(|s| s.set(action, accelerator));
+        let Some(other) = self.current.borrow().conflict_for(action) else {
+            return;
+        };
+        let message = format!(
+            "{} is also used by {}",
+            accelerator_label(accelerator),
+            other.label()
+        );
+        let state = Arc::clone(&self.state);
+        spawn_future_local(async move { state.send_toast(message).await });
+    }
+
+    /// Show the current accelerators and conflicts on every row.
+    fn refresh_rows(&self, settings: &ShortcutSettings) {
+        for (action, row, label) in &self.rows {
+            label.set_accelerator(settings.accelerator(*action).unwrap_or_default());
+            let subtitle = settings
+                .conflict_for(*action)
+                .map(|other| format!("Conflicts with {}", other.label()))
+                .unwrap_or_default();
+            row.set_subtitle(&subtitle);
+        }
+    }
+}
+
+/// Build the General preferences page.
+pub fn build_general_page(dialog: &PreferencesDialog, state: &Arc<AppState>) {
+    let page = PreferencesPage::new();
+    page.set_title("General");
+    page.set_icon_name(Some("preferences-system-symbolic"));
+
+    let group = PreferencesGroup::new();
+    group.set_title("Keyboard Shortcuts");
+    group.set_description(Some(
+        "Select a shortcut to change it; shortcuts are ignored while typing",
+    ));
+
+    let rows = ShortcutAction::ALL
+        .into_iter()
+        .map(|action| {
+            let label = ShortcutLabel::new("");
+            label.set_disabled_text("Disabled");
+            label.set_valign(Center);
+            let row = ActionRow::builder()
+                .title(action.label())
+                .activatable(true)
+                .build();
+            row.add_suffix(&label);
+            group.add(&row);
+            (action, row, label)
+        })
+        .collect();
+
+    let settings = state.storage.get_shortcuts();
+    let shared = Rc::new(ShortcutsPageState {
+        state: Arc::clone(state),
+        current: RefCell::new(settings.clone()),
+        rows,
+    });
+    shared.refresh_rows(&settings);
+
+    for (action, row, _) in &shared.rows {
+        connect_capture(row, &shared, *action);
+    }
+
+    let restore_btn = Button::builder()
+        .label("Restore Defaults")
+        .css_classes(["flat"])
+        .valign(Center)
+        .build();
+    let restore_shared = Rc::clone(&shared);
+    restore_btn.connect_clicked(move |_| {
+        info!("Keyboard shortcuts restored to defaults");
+        restore_shared.commit(|s| *s = ShortcutSettings::default());
+    });
+    group.set_header_suffix(Some(&restore_btn));
+
+    page.add(&group);
+    build_startup_group(&page, state);
+    build_share_group(&page, state);
+    build_transfer_group(&page, state);
+    build_diagnostics_group(&page, state);
+    dialog.add(&page);
+}
+
+/// Open the capture dialog when the row of `action` is activated.
+fn connect_capture(row: &ActionRow, shared: &Rc<ShortcutsPageState>, action: ShortcutAction) {
+    let shared = Rc::downgrade(shared);
+    row.connect_activated(move |row| {
+        if let Some(shared) = shared.upgrade() {
+            present_capture_dialog(row, &shared, action);
+        }
+    });
+}
+
+/// Wait for the next key combination and bind it to `action`.
+fn present_capture_dialog(
+    row: &ActionRow,
+    shared: &Rc<ShortcutsPageState>,
+    action: ShortcutAction,
+) {
+    let dialog = AlertDialog::new(
+        Some("Set Shortcut"),
+        Some(&format!(
+            "Press a key combination for \u{201c}{}\u{201d}. Backspace disables the shortcut, \
+             Escape cancels.",
+            action.label()
+        )),
+    );
+    dialog.add_response("cancel", "Cancel");
+    dialog.set_close_response("cancel");
+
+    let controller = EventControllerKey::new();
+    controller.set_propagation_phase(Capture);
+    let capture_dialog = dialog.clone();
+    let shared = Rc::clone(shared);
+    controller.connect_key_pressed(move |_, key, _, modifiers| {
+        let Some(accelerator) = captured_accelerator(key, modifiers) else {
+            return Proceed;
+        };
+        shared.rebind(action, &accelerator);
+        capture_dialog.force_close();
+        Stop
+    });
+    dialog.add_controller(controller);
+
+    dialog.present(Some(row));
+}
+
+/// Accelerator for a captured key press.
+///
+/// Returns `None` for Escape and lone modifier keys, which leave the
+/// binding unchanged, and an empty string for Backspace, which disables it.
+fn captured_accelerator(key: Key, modifiers: ModifierType) -> Option<String> {
+    let modifiers = modifiers & accelerator_get_default_mod_mask();
+    if modifiers.is_empty() && key == Key::BackSpace {
+        return Some(String::new());
+    }
+    if key == Key::Escape || !accelerator_valid(key.to_lower(), modifiers) {
+        return None;
+    }
+    Some(accelerator_name(key.to_lower(), modifiers).to_string())
+}
+
+/// Build the group choosing whether the last session is resumed at launch.
+fn build_startup_group(page: &PreferencesPage, state: &Arc<AppState>) {
+    let group = PreferencesGroup::new();
+    group.set_title("Startup");
+
+    let row = SwitchRow::new();
+    row.set_title("Resume Last Session");
+    row.set_subtitle(
+        "Restore the playback queue and current track at launch; when off, the queue is not \
+         saved on exit",
+    );
+    row.set_active(state.storage.get_resume_on_startup());
+    let state = Arc::clone(state);
+    row.connect_active_notify(move |row| {
+        let resume = row.is_active();
+        info!(resume, "Session restore toggled");
+        spawn_future_local(save_resume_setting(Arc::clone(&state), resume));
+    });
+
+    group.add(&row);
+    page.add(&group);
+}
+
+/// Persist whether the last session is resumed, logging on failure.
+async fn save_resume_setting(state: Arc<AppState>, resume: bool) {
+    if let Err(e) = state.storage.set_resume_on_startup(resume).await {
+        error!(error = %e, "Failed to save session restore preference");
+    }
+}
+
+/// Persist shortcut bindings, logging on failure.
+async fn save_shortcuts(state: Arc<AppState>, shortcuts: ShortcutSettings) {
+    if let Err(e) = state.storage.set_shortcuts(shortcuts).await {
+        error!(error = %e, "Failed to save keyboard shortcuts");
+    }
+}
+
+/// Human-readable form of an accelerator, e.g. `Ctrl+Right`.
+fn accelerator_label(accelerator: &str) -> String {
+    accelerator_parse(accelerator).map_or_else(
+        || accelerator.to_string(),
+        |(key, modifiers)| accelerator_get_label(key, modifiers).to_string(),
+    )
+}
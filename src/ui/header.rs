@@ -3,24 +3,40 @@
 //! Uses `AdwViewSwitcher` for tab navigation per GNOME HIG. The switcher
 //! is placed in the title widget slot of `AdwHeaderBar`.
 //!
-//! Provides a toggle button to switch between grid and column layout views
-//! and a preferences button to open the settings dialog.
+//! Provides a toggle button to switch between grid and column layout views,
+//! album and artist sort selectors for the matching tab, buttons playing the whole library,
+//! mini player and now playing buttons, and a preferences button to open the settings dialog.
 
 use std::sync::Arc;
 
 use {
     libadwaita::{
-        glib::spawn_future_local,
-        gtk::{Box, Button, Orientation::Horizontal, ToggleButton, Window},
-        prelude::{BoxExt, ButtonExt, ToggleButtonExt, WidgetExt},
+        glib::{WeakRef, spawn_future_local},
+        gtk::{
+            Box, Button, DropDown, Orientation::Horizontal, Spinner, ToggleButton, Window,
+            accessible::Property::Label as PropertyLabel,
+        },
+        prelude::{AccessibleExtManual, BoxExt, ButtonExt, ObjectExt, ToggleButtonExt, WidgetExt},
     },
-    tracing::warn,
+    tokio::sync::watch::Receiver,
+    tracing::{info, warn},
 };
 
 use crate::{
     app::AppState,
-    storage::settings::ViewMode::{self, Column, Grid},
-    ui::settings::show_preferences_dialog,
+    library::scan_status::ScanStatus,
+    storage::{
+        settings::{
+            ActiveTab::{self, Artists},
+            ViewMode::{self, Column, Grid},
+        },
+        sort_order::{ArtistSortOrder, SortOrder},
+    },
+    ui::{
+        library::play_all::build_play_all_buttons,
+        player::{mini::toggle_mini_player, now_playing::toggle_now_playing},
+        settings::show_preferences_dialog,
+    },
 };
 
 /// Persist the view mode setting to storage, logging on failure.
@@ -30,6 +46,164 @@ async fn save_view_mode(state: Arc<AppState>, mode: ViewMode) {
     }
 }
 
+/// Persist the album sort order to storage, logging on failure.
+async fn save_album_sort(state: Arc<AppState>, sort: SortOrder) {
+    if let Err(err) = state.storage.set_album_sort(sort).await {
+        warn!(error = %err, "Failed to set album sort order");
+    }
+}
+
+/// Persist the artist sort order to storage, logging on failure.
+async fn save_artist_sort(state: Arc<AppState>, sort: ArtistSortOrder) {
+    if let Err(err) = state.storage.set_artist_sort(sort).await {
+        warn!(error = %err, "Failed to set artist sort order");
+    }
+}
+
+/// Build the album sort selector.
+///
+/// Choosing an order persists it and refreshes the library views; the
+/// "Recently Added" entry shows newly imported albums first.
+///
+/// # Arguments
+///
+/// * `state` - Application state holding the current sort order
+#[must_use]
+pub fn build_sort_dropdown(state: &Arc<AppState>) -> DropDown {
+    let labels: Vec<&str> = SortOrder::ALL.iter().map(|s| s.label()).collect();
+    let dropdown = DropDown::from_strings(&labels);
+    dropdown.set_tooltip_text(Some("Sort albums"));
+    dropdown.update_property(&[PropertyLabel("Sort albums")]);
+    let current = *state.album_sort_tx.borrow();
+    let position = SortOrder::ALL.iter().position(|s| *s == current);
+    dropdown.set_selected(position.map_or(0, |p| u32::try_from(p).unwrap_or(0)));
+
+    let state_clone = Arc::clone(state);
+    dropdown.connect_selected_notify(move |dd| {
+        let index = usize::try_from(dd.selected()).unwrap_or(usize::MAX);
+        let Some(sort) = SortOrder::ALL.get(index).copied() else {
+            return;
+        };
+        let changed = state_clone.album_sort_tx.send_if_modified(|current| {
+            let changed = *current != sort;
+            *current = sort;
+            changed
+        });
+        if !changed {
+            return;
+        }
+        info!(sort = ?sort, "Album sort order changed");
+        spawn_future_local(save_album_sort(Arc::clone(&state_clone), sort));
+        if let Err(e) = state_clone.refresh_tx.send(()) {
+            warn!(error = %e, "Failed to send refresh signal");
+        }
+    });
+
+    dropdown
+}
+
+/// Build the artist sort selector.
+///
+/// Choosing an order persists it and refreshes the library views, so the
+/// most represented artists can be listed first.
+///
+/// # Arguments
+///
+/// * `state` - Application state holding the current sort order
+#[must_use]
+pub fn build_artist_sort_dropdown(state: &Arc<AppState>) -> DropDown {
+    let labels: Vec<&str> = ArtistSortOrder::ALL.iter().map(|s| s.label()).collect();
+    let dropdown = DropDown::from_strings(&labels);
+    dropdown.set_tooltip_text(Some("Sort artists"));
+    dropdown.update_property(&[PropertyLabel("Sort artists")]);
+    let current = *state.artist_sort_tx.borrow();
+    let position = ArtistSortOrder::ALL.iter().position(|s| *s == current);
+    dropdown.set_selected(position.map_or(0, |p| u32::try_from(p).unwrap_or(0)));
+
+    let state_clone = Arc::clone(state);
+    dropdown.connect_selected_notify(move |dd| {
+        let index = usize::try_from(dd.selected()).unwrap_or(usize::MAX);
+        let Some(sort) = ArtistSortOrder::ALL.get(index).copied() else {
+            return;
+        };
+        let changed = state_clone.artist_sort_tx.send_if_modified(|current| {
+            let changed = *current != sort;
+            *current = sort;
+            changed
+        });
+        if !changed {
+            return;
+        }
+        info!(sort = ?sort, "Artist sort order changed");
+        spawn_future_local(save_artist_sort(Arc::clone(&state_clone), sort));
+        if let Err(e) = state_clone.refresh_tx.send(()) {
+            warn!(error = %e, "Failed to send refresh signal");
+        }
+    });
+
+    dropdown
+}
+
+/// Show the artist sort selector on the Artists tab and the album sort
+/// selector everywhere else.
+fn follow_active_tab(state: &AppState, album_sort: &DropDown, artist_sort: &DropDown) {
+    let mut tab_rx = state.active_tab_tx.subscribe();
+    show_sort_for_tab(*tab_rx.borrow_and_update(), album_sort, artist_sort);
+    spawn_future_local(follow_tab_changes(
+        tab_rx,
+        album_sort.downgrade(),
+        artist_sort.downgrade(),
+    ));
+}
+
+/// Show the sort selector matching `tab` and hide the other.
+fn show_sort_for_tab(tab: ActiveTab, album_sort: &DropDown, artist_sort: &DropDown) {
+    album_sort.set_visible(tab != Artists);
+    artist_sort.set_visible(tab == Artists);
+}
+
+/// Switch the sort selectors on every tab change until they are dropped.
+async fn follow_tab_changes(
+    mut tab_rx: Receiver<ActiveTab>,
+    weak_album: WeakRef<DropDown>,
+    weak_artist: WeakRef<DropDown>,
+) {
+    while tab_rx.changed().await.is_ok() {
+        let tab = *tab_rx.borrow_and_update();
+        let (Some(album_sort), Some(artist_sort)) = (weak_album.upgrade(), weak_artist.upgrade())
+        else {
+            break;
+        };
+        show_sort_for_tab(tab, &album_sort, &artist_sort);
+    }
+}
+
+/// Build a spinner shown while the library is being scanned.
+///
+/// Its tooltip tells how many files of the current folder are done.
+#[must_use]
+pub fn build_scan_spinner(state: &Arc<AppState>) -> Spinner {
+    let spinner = Spinner::builder().spinning(true).visible(false).build();
+    spinner.update_property(&[PropertyLabel("Scanning library")]);
+
+    let status_rx = state.scan_status_tx.subscribe();
+    spawn_future_local(follow_scan_status(status_rx, spinner.downgrade()));
+
+    spinner
+}
+
+/// Show the spinner while a scan runs until the spinner is dropped.
+async fn follow_scan_status(mut status_rx: Receiver<ScanStatus>, weak_spinner: WeakRef<Spinner>) {
+    while status_rx.changed().await.is_ok() {
+        let status = *status_rx.borrow_and_update();
+        let Some(spinner) = weak_spinner.upgrade() else {
+            break;
+        };
+        spinner.set_visible(status.active);
+        spinner.set_tooltip_text(Some(&status.label()));
+    }
+}
+
 /// Build the view mode toggle button.
 ///
 /// Creates a `ToggleButton` that switches between grid and column layout.
@@ -70,19 +244,65 @@ pub fn build_view_toggle(state: &Arc<AppState>, initial_mode: ViewMode) -> Toggl
     toggle
 }
 
-/// Build a header bar with view toggle and preferences button.
+/// Build a header bar with scan spinner, sort selector, view toggle, play
+/// all, mini player, now playing and preferences buttons.
 ///
-/// Creates a horizontal box containing the view toggle button and a
-/// gear icon button to open the preferences dialog.
+/// Creates a horizontal box containing a spinner shown while scanning, the
+/// album or artist sort selector depending on the tab, the view
+/// toggle button, buttons playing or shuffling the whole library, a button switching to the mini player, a button opening
+/// the fullscreen now playing view and a gear icon button to open the
+/// preferences dialog.
 #[must_use]
 pub fn build_header_controls(state: &Arc<AppState>, parent: &Window) -> Box {
     let controls = Box::builder().orientation(Horizontal).spacing(6).build();
 
     let initial_mode = state.storage.get_view_mode();
 
+    controls.append(&build_scan_spinner(state));
+
+    let album_sort = build_sort_dropdown(state);
+    let artist_sort = build_artist_sort_dropdown(state);
+    follow_active_tab(state, &album_sort, &artist_sort);
+    controls.append(&album_sort);
+    controls.append(&artist_sort);
+
     let toggle = build_view_toggle(state, initial_mode);
     controls.append(&toggle);
 
+    controls.append(&build_play_all_buttons(state));
+
+    let mini_btn = Button::builder()
+        .icon_name("view-restore-symbolic")
+        .tooltip_text("Mini player")
+        .css_classes(["flat"])
+        .can_focus(true)
+        .build();
+    mini_btn.update_property(&[PropertyLabel("Mini player")]);
+
+    let state_mini = Arc::clone(state);
+    let parent_mini = parent.clone();
+    mini_btn.connect_clicked(move |_| {
+        toggle_mini_player(&parent_mini, &state_mini);
+    });
+
+    controls.append(&mini_btn);
+
+    let now_playing_btn = Button::builder()
+        .icon_name("view-fullscreen-symbolic")
+        .tooltip_text("Now playing")
+        .css_classes(["flat"])
+        .can_focus(true)
+        .build();
+    now_playing_btn.update_property(&[PropertyLabel("Now playing")]);
+
+    let state_now_playing = Arc::clone(state);
+    let parent_now_playing = parent.clone();
+    now_playing_btn.connect_clicked(move |_| {
+        toggle_now_playing(&parent_now_playing, &state_now_playing);
+    });
+
+    controls.append(&now_playing_btn);
+
     let prefs_btn = Button::builder()
         .icon_name("open-menu-symbolic")
         .tooltip_text("Preferences")
@@ -0,0 +1,379 @@
+//! Virtualized album tile grid.
+//!
+//! Albums are held in a `gio::ListStore` and shown through a `GtkGridView`,
+//! so tile widgets only exist for rows near the visible viewport and are
+//! recycled as the user scrolls. Covers come from a [`CoverLoader`], which
+//! keeps memory flat regardless of library size.
+
+use std::{cell::Cell, collections::HashMap, rc::Rc, sync::Arc};
+
+use {
+    libadwaita::{
+        gio::ListStore,
+        glib::{BoxedAnyObject, Object, spawn_future_local},
+        gtk::{
+            Align::{End, Start},
+            Box as GtkBox, Button, EventControllerMotion, GestureClick, GridView, Label, ListItem,
+            NoSelection,
+            Orientation::{Horizontal, Vertical},
+            Overlay, SignalListItemFactory, Widget,
+            pango::EllipsizeMode::End as EllipsizeEnd,
+        },
+        prelude::{BoxExt, ButtonExt, Cast, ListItemExt, ObjectExt, WidgetExt},
+    },
+    tracing::{info, warn},
+};
+
+use crate::{
+    app::AppState,
+    library::dynamic_range::DrBadgeDisplayPolicy,
+    storage::{Album, FormatInfo, zoom::ZoomLevel},
+    ui::{
+        build_album_play_button,
+        library::{
+            albums::{activate_album, album_play_icon, toggle_or_play_album},
+            artist_link::link_album_artist,
+            cover_loader::{CoverLoader, build_placeholder, item_album_id},
+            dr_badge::{build_dr_overlay, show_dr_overlay},
+            models::AlbumData,
+            quality_badge::{build_quality_overlay, show_quality_overlay},
+        },
+    },
+};
+
+/// Minimum number of tiles per grid row.
+const MIN_COLUMNS: u32 = 2;
+
+/// Maximum number of tiles per grid row.
+const MAX_COLUMNS: u32 = 12;
+
+/// Which badges the tiles of a grid show on their covers.
+#[derive(Debug, Clone, Copy)]
+struct TileBadges {
+    /// Which albums get a DR badge.
+    dr_policy: DrBadgeDisplayPolicy,
+    /// Whether albums get a quality badge.
+    show_quality: bool,
+}
+
+/// Widgets of one recyclable album tile.
+#[derive(Clone)]
+struct TileWidgets {
+    /// Outer card container.
+    card: GtkBox,
+    /// Overlay holding the cover and the play button.
+    overlay: Overlay,
+    /// Hover play/pause button.
+    play_button: Button,
+    /// Album the tile shows, if bound.
+    album_id: Rc<Cell<Option<i64>>>,
+    /// DR badge in the corner of the cover.
+    dr_badge: Label,
+    /// Quality badge in the opposite corner of the cover.
+    quality_badge: Label,
+    /// Album title label.
+    title: Label,
+    /// Album artist label.
+    artist: Label,
+    /// Format summary label.
+    format: Label,
+    /// Release year label.
+    year: Label,
+}
+
+/// Build the virtualized album grid.
+///
+/// Albums keep the order they are given in and covers are sized by the
+/// current zoom level. Returns a `GridView`, which must be placed directly
+/// inside a `ScrolledWindow` for recycling to work.
+///
+/// # Arguments
+///
+/// * `state` - Application state
+/// * `albums` - Albums to display, already sorted
+/// * `artist_names` - Map of artist id → display name
+/// * `format_info` - Map of album id → distinct format info
+pub fn build_album_grid_view(
+    state: &Arc<AppState>,
+    albums: &[Album],
+    artist_names: &HashMap<i64, String>,
+    format_info: &HashMap<i64, FormatInfo>,
+) -> Widget {
+    let use_original_year = state.storage.get_use_original_year();
+    let items: Vec<BoxedAnyObject> = albums
+        .iter()
+        .map(|album| {
+            let artist_name = artist_names
+                .get(&album.artist_id)
+                .map_or("Unknown Artist", String::as_str);
+            let fi = format_info.get(&album.id).cloned().unwrap_or_default();
+            BoxedAnyObject::new(AlbumData::new(album, artist_name, &fi, use_original_year))
+        })
+        .collect();
+    let store = ListStore::new::<BoxedAnyObject>();
+    store.extend_from_slice(&items);
+
+    let cover_size = state.zoom_level_tx.borrow().cover_size();
+    let loader = CoverLoader::new_shared(&state.cover_art_cache, cover_size);
+    let badges = TileBadges {
+        dr_policy: state.storage.get_dr_badge_policy(),
+        show_quality: state.storage.get_show_quality_badges(),
+    };
+    let factory = SignalListItemFactory::new();
+    let state = Arc::clone(state);
+    factory.connect_setup(move |_, item: &Object| {
+        if let Some(list_item) = item.downcast_ref::<ListItem>() {
+            setup_tile(&state, &loader, badges, list_item);
+        }
+    });
+
+    GridView::builder()
+        .model(&NoSelection::new(Some(store)))
+        .factory(&factory)
+        .min_columns(MIN_COLUMNS)
+        .max_columns(MAX_COLUMNS)
+        .can_focus(true)
+        .tooltip_text("Album library grid \u{2014} click an album to play")
+        .build()
+        .upcast()
+}
+
+/// Change the album grid zoom level by one step.
+///
+/// Does nothing when the level is already at its limit.
+///
+/// # Arguments
+///
+/// * `state` - Application state holding the current zoom level
+/// * `step` - [`ZoomLevel::zoom_in`] or [`ZoomLevel::zoom_out`]
+pub fn step_zoom_level(state: &Arc<AppState>, step: fn(ZoomLevel) -> ZoomLevel) {
+    let level = step(*state.zoom_level_tx.borrow());
+    set_zoom_level(state, level);
+}
+
+/// Show album grid covers at `level`.
+///
+/// The new level is broadcast, persisted, and applied by refreshing the
+/// library views. Does nothing when `level` is already in use.
+///
+/// # Arguments
+///
+/// * `state` - Application state holding the current zoom level
+/// * `level` - Zoom level to use
+pub fn set_zoom_level(state: &Arc<AppState>, level: ZoomLevel) {
+    let changed = state.zoom_level_tx.send_if_modified(|current| {
+        let changed = *current != level;
+        *current = level;
+        changed
+    });
+    if !changed {
+        return;
+    }
+    info!(zoom = ?level, "Album grid zoom changed");
+    spawn_future_local(save_zoom_level(Arc::clone(state), level));
+    if let Err(e) = state.refresh_tx.send(()) {
+        warn!(error = %e, "Failed to send refresh signal");
+    }
+}
+
+/// Persist the album grid zoom level, logging on failure.
+async fn save_zoom_level(state: Arc<AppState>, level: ZoomLevel) {
+    if let Err(e) = state.storage.set_zoom_level(level).await {
+        warn!(error = %e, "Failed to save zoom level");
+    }
+}
+
+/// Return the album artist ID of the album `list_item` currently shows.
+fn item_artist_id(list_item: &ListItem) -> Option<i64> {
+    let item = list_item.item()?;
+    let boxed = item.downcast_ref::<BoxedAnyObject>()?;
+    Some(boxed.borrow::<AlbumData>().artist_id)
+}
+
+/// Build an empty tile for `list_item` and rebind it whenever the item
+/// changes.
+///
+/// Handlers resolve the album from the list item at event time, so a
+/// recycled tile always acts on the album it currently shows. Cover
+/// badges follow `badges`.
+fn setup_tile(
+    state: &Arc<AppState>,
+    loader: &Rc<CoverLoader>,
+    badges: TileBadges,
+    list_item: &ListItem,
+) {
+    let widgets = build_tile_widgets(loader.cover_size());
+    let weak_item = list_item.downgrade();
+
+    let motion_ctrl = EventControllerMotion::new();
+    let btn_show = widgets.play_button.clone();
+    let state_enter = Arc::clone(state);
+    let item_enter = weak_item.clone();
+    motion_ctrl.connect_enter(move |_, _, _| {
+        let Some(album_id) = item_enter.upgrade().and_then(|li| item_album_id(&li)) else {
+            return;
+        };
+        btn_show.set_icon_name(album_play_icon(&state_enter, album_id));
+        btn_show.set_visible(true);
+    });
+    let btn_hide = widgets.play_button.clone();
+    motion_ctrl.connect_leave(move |_| {
+        btn_hide.set_visible(false);
+    });
+    widgets.overlay.add_controller(motion_ctrl);
+
+    let state_play = Arc::clone(state);
+    let item_play = weak_item.clone();
+    widgets.play_button.connect_clicked(move |btn| {
+        let Some(album_id) = item_play.upgrade().and_then(|li| item_album_id(&li)) else {
+            return;
+        };
+        let icon = album_play_icon(&state_play, album_id);
+        btn.set_icon_name(if icon == "media-playback-pause-symbolic" {
+            "media-playback-start-symbolic"
+        } else {
+            "media-playback-pause-symbolic"
+        });
+
+        let state = Arc::clone(&state_play);
+        spawn_future_local(async move {
+            toggle_or_play_album(&state, album_id).await;
+        });
+    });
+
+    let item_artist = weak_item.clone();
+    link_album_artist(&widgets.artist, state, move || {
+        item_artist_id(&item_artist.upgrade()?)
+    });
+
+    let gesture = GestureClick::new();
+    let state_click = Arc::clone(state);
+    gesture.connect_released(move |_, _, _, _| {
+        let Some(album_id) = weak_item.upgrade().and_then(|li| item_album_id(&li)) else {
+            return;
+        };
+        let state = Arc::clone(&state_click);
+        spawn_future_local(async move {
+            activate_album(&state, album_id).await;
+        });
+    });
+    widgets.card.add_controller(gesture);
+
+    list_item.set_child(Some(&widgets.card));
+
+    let loader = Rc::clone(loader);
+    list_item.connect_notify_local(Some("item"), move |list_item, _| {
+        bind_tile(list_item, &widgets, &loader, badges);
+    });
+}
+
+/// Fill a tile's widgets from the album its list item now holds.
+fn bind_tile(
+    list_item: &ListItem,
+    widgets: &TileWidgets,
+    loader: &CoverLoader,
+    badges: TileBadges,
+) {
+    widgets.play_button.set_visible(false);
+    let album_id = item_album_id(list_item);
+    loader.track_shown(widgets.album_id.replace(album_id), album_id);
+    let Some(item) = list_item.item() else {
+        return;
+    };
+    let Some(boxed) = item.downcast_ref::<BoxedAnyObject>() else {
+        return;
+    };
+    let data = boxed.borrow::<AlbumData>();
+
+    widgets.card.set_tooltip_text(Some(&format!(
+        "Play \u{201c}{}\u{201d} by album artist",
+        data.title
+    )));
+    widgets.title.set_label(&data.title);
+    widgets.artist.set_label(&data.artist_name);
+    widgets.format.set_label(&data.format_summary);
+    widgets.year.set_label(&if data.year == 0 {
+        String::new()
+    } else {
+        data.year.to_string()
+    });
+    show_dr_overlay(&widgets.dr_badge, &data, badges.dr_policy);
+    show_quality_overlay(&widgets.quality_badge, &data, badges.show_quality);
+    loader.show(list_item, &widgets.overlay, &data);
+}
+
+/// Build the widget tree of an empty album tile with `cover_size` covers.
+///
+/// Uses `GestureClick` for click handling instead of `Button` to avoid
+/// theme-inflated natural sizing from the `card` CSS class.
+fn build_tile_widgets(cover_size: i32) -> TileWidgets {
+    let card = GtkBox::builder()
+        .orientation(Vertical)
+        .spacing(6)
+        .css_classes(["card"])
+        .can_focus(true)
+        .build();
+
+    let overlay = Overlay::new();
+    overlay.set_child(Some(&build_placeholder(cover_size)));
+    overlay.set_css_classes(&["cover-overlay"]);
+
+    let dr_badge = build_dr_overlay();
+    overlay.add_overlay(&dr_badge);
+    let quality_badge = build_quality_overlay();
+    overlay.add_overlay(&quality_badge);
+
+    let play_button = build_album_play_button();
+    play_button.set_visible(false);
+    overlay.add_overlay(&play_button);
+    card.append(&overlay);
+
+    let title = Label::builder()
+        .ellipsize(EllipsizeEnd)
+        .max_width_chars(20)
+        .css_classes(["heading", "title"])
+        .halign(Start)
+        .build();
+
+    let artist = Label::builder()
+        .ellipsize(EllipsizeEnd)
+        .max_width_chars(20)
+        .css_classes(["dim-label", "caption"])
+        .halign(Start)
+        .build();
+
+    let format_row = GtkBox::builder().orientation(Horizontal).spacing(6).build();
+
+    let format = Label::builder()
+        .ellipsize(EllipsizeEnd)
+        .max_width_chars(14)
+        .css_classes(["dim-label", "caption"])
+        .halign(Start)
+        .build();
+    format.set_hexpand(true);
+
+    let year = Label::builder()
+        .css_classes(["dim-label", "caption"])
+        .halign(End)
+        .build();
+
+    format_row.append(&format);
+    format_row.append(&year);
+
+    card.append(&title);
+    card.append(&artist);
+    card.append(&format_row);
+
+    TileWidgets {
+        card,
+        overlay,
+        play_button,
+        album_id: Rc::new(Cell::new(None)),
+        dr_badge,
+        quality_badge,
+        title,
+        artist,
+        format,
+        year,
+    }
+}
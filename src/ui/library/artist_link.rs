@@ -0,0 +1,53 @@
+//! Link from an album tile to the page of its artist.
+//!
+//! The artist line under a cover opens the artist's detail page, so an
+//! album found by a search also leads to everything else by its artist.
+//! The label claims its click, so the tile underneath does not also open
+//! or play the album.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        glib::spawn_future_local,
+        gtk::{EventSequenceState::Claimed, GestureClick, Label},
+        prelude::{GestureExt, GestureSingleExt, WidgetExt},
+    },
+    tracing::info,
+};
+
+use crate::app::{AppState, NavigationEvent::ArtistDetail};
+
+/// Open the artist page when `label` is clicked.
+///
+/// # Arguments
+///
+/// * `label` - Tile label showing the album artist
+/// * `state` - Application state
+/// * `artist_id` - Artist of the album the tile shows at click time
+pub fn link_album_artist(
+    label: &Label,
+    state: &Arc<AppState>,
+    artist_id: impl Fn() -> Option<i64> + 'static,
+) {
+    label.set_cursor_from_name(Some("pointer"));
+    label.set_tooltip_text(Some("Go to artist"));
+
+    let click = GestureClick::new();
+    click.set_button(1);
+    click.connect_pressed(|gesture, _, _, _| {
+        gesture.set_state(Claimed);
+    });
+    let state = Arc::clone(state);
+    click.connect_released(move |_, _, _, _| {
+        let Some(artist_id) = artist_id() else {
+            return;
+        };
+        info!(artist_id, "Going to album artist");
+        let state = Arc::clone(&state);
+        spawn_future_local(async move {
+            state.send_navigation_event(ArtistDetail(artist_id)).await;
+        });
+    });
+    label.add_controller(click);
+}
@@ -21,7 +21,7 @@ use {
         },
         prelude::{AccessibleExtManual, BoxExt, WidgetExt},
     },
-    tracing::warn,
+    tracing::{info, warn},
 };
 
 use crate::{
@@ -31,6 +31,7 @@ use crate::{
         settings::ViewMode::{self, Column, Grid},
     },
     ui::library::{
+        albums::play_tracks,
         column_view::{NarrowState, build_artist_column_view},
         common::build_grid,
         empty::{
@@ -129,18 +130,24 @@ pub async fn lazy_build_artist_mode(state: &Arc<AppState>, stack: &Stack, mode:
         return;
     }
 
-    let artists = match state.storage.get_all_artists().await {
-        Ok(a) => a
-            .into_iter()
-            .filter(|a| a.album_count > 0)
-            .collect::<Vec<_>>(),
+    let sort = *state.artist_sort_tx.borrow();
+    let artists = match state.storage.get_album_artists(sort).await {
+        Ok(a) => a,
         Err(e) => {
             warn!(error = %e, "Failed to load artists for lazy build");
             return;
         }
     };
 
+    // A refresh may have rebuilt this page while the data was loading.
+    if stack.child_by_name(child_name).is_some() {
+        return;
+    }
+
     if artists.is_empty() {
+        if stack.child_by_name("grid").is_some() {
+            return;
+        }
         let empty_widget = build_empty_state(
             state,
             &EmptyStateParams {
@@ -179,7 +186,7 @@ fn build_artist_avatar() -> Widget {
 /// Build a single artist card widget.
 ///
 /// Returns a `Box` containing a vertical layout with avatar,
-/// name, and album count labels. Matches the album card structural
+/// name, and album and track count labels. Matches the album card structural
 /// pattern (Overlay wrapper) for consistent card sizing.
 fn build_artist_card(state: &Arc<AppState>, artist: &Artist) -> Box {
     let card = Box::builder()
@@ -208,15 +215,18 @@ fn build_artist_card(state: &Arc<AppState>, artist: &Artist) -> Box {
     name_label.update_property(&[PropertyLabel(&format!("Artist: {}", artist.name))]);
 
     let album_count_label = Label::builder()
-        .label(format!("{} albums", artist.album_count))
+        .label(format!(
+            "{} albums \u{b7} {} tracks",
+            artist.album_count, artist.track_count
+        ))
         .ellipsize(End)
         .max_width_chars(20)
         .css_classes(["dim-label", "caption"])
         .halign(Start)
         .build();
     album_count_label.update_property(&[PropertyLabel(&format!(
-        "{} albums by {}",
-        artist.album_count, artist.name
+        "{} albums and {} tracks by {}",
+        artist.album_count, artist.track_count, artist.name
     ))]);
 
     card.append(&name_label);
@@ -235,3 +245,32 @@ fn build_artist_card(state: &Arc<AppState>, artist: &Artist) -> Box {
 
     card
 }
+
+/// Play every album of an artist in the order the artist page lists them,
+/// or all of their tracks in random order when `shuffled` is set.
+pub async fn play_artist(state: &AppState, artist_id: i64, shuffled: bool) {
+    let album_ids: Vec<i64> = match state.storage.get_albums_by_artist(artist_id).await {
+        Ok(albums) => albums.iter().map(|a| a.id).collect(),
+        Err(e) => {
+            warn!(error = %e, artist_id, "Failed to fetch artist albums");
+            return;
+        }
+    };
+    let mut tracks = match state.storage.get_tracks_by_albums(&album_ids).await {
+        Ok(tracks) => tracks,
+        Err(e) => {
+            warn!(error = %e, artist_id, "Failed to fetch artist tracks");
+            return;
+        }
+    };
+    if tracks.is_empty() {
+        info!(artist_id, "Artist has no tracks");
+        return;
+    }
+
+    tracks.sort_by_key(|t| {
+        let id = t.audio.album_id?;
+        album_ids.iter().position(|&a| a == id)
+    });
+    play_tracks(state, &tracks, shuffled).await;
+}
@@ -0,0 +1,193 @@
+//! Browse tab with genre and decade tiles.
+//!
+//! Each tile names a genre or release decade with its album count.
+//! Clicking a tile opens the matching albums in the album grid. The tiles
+//! are rebuilt whenever the library is refreshed.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        StatusPage,
+        glib::spawn_future_local,
+        gtk::{
+            Align::Start, Box, Button, Label, Orientation::Vertical, ScrolledWindow,
+            accessible::Property::Label as PropertyLabel, pango::EllipsizeMode::End,
+        },
+        prelude::{AccessibleExtManual, BoxExt, ButtonExt, WidgetExt},
+    },
+    tokio::join,
+    tracing::{info, warn},
+};
+
+use crate::{
+    app::{AppState, NavigationEvent::BrowseAlbums},
+    storage::browse::BrowseFilter::{self, Decade, Genre},
+    ui::library::{common::build_grid, empty::clear_container},
+};
+
+/// Build the browse tab.
+///
+/// # Arguments
+///
+/// * `state` - Application state
+#[must_use]
+pub fn build_browse_view(state: &Arc<AppState>) -> ScrolledWindow {
+    let content = Box::builder()
+        .orientation(Vertical)
+        .spacing(12)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(18)
+        .margin_end(18)
+        .build();
+    let scroll = ScrolledWindow::builder()
+        .vexpand(true)
+        .hexpand(true)
+        .child(&content)
+        .build();
+
+    spawn_future_local(populate_browse(Arc::clone(state), content.clone()));
+
+    let mut refresh_rx = state.refresh_tx.subscribe();
+    let refresh_state = Arc::clone(state);
+    spawn_future_local(async move {
+        while refresh_rx.changed().await.is_ok() {
+            populate_browse(Arc::clone(&refresh_state), content.clone()).await;
+        }
+    });
+
+    scroll
+}
+
+/// Load the genres and decades and rebuild the tiles in `content`.
+async fn populate_browse(state: Arc<AppState>, content: Box) {
+    let (genres, decades) = join!(state.storage.get_genres(), state.storage.get_decades());
+    let genres = genres.unwrap_or_else(|e| {
+        warn!(error = %e, "Failed to load genres");
+        Vec::new()
+    });
+    let decades = decades.unwrap_or_else(|e| {
+        warn!(error = %e, "Failed to load decades");
+        Vec::new()
+    });
+    info!(
+        genres = genres.len(),
+        decades = decades.len(),
+        "Browse view loaded"
+    );
+
+    clear_container(&content);
+    if genres.is_empty() && decades.is_empty() {
+        content.append(&build_nothing_to_browse());
+        return;
+    }
+    if !genres.is_empty() {
+        let tiles = genres
+            .into_iter()
+            .map(|g| (Genre(g.name), g.album_count))
+            .collect();
+        append_section(&state, &content, "Genres", tiles);
+    }
+    if !decades.is_empty() {
+        let tiles = decades
+            .into_iter()
+            .map(|d| (Decade(d.decade), d.album_count))
+            .collect();
+        append_section(&state, &content, "Decades", tiles);
+    }
+}
+
+/// Append a heading and a grid of `tiles` to `content`.
+fn append_section(
+    state: &Arc<AppState>,
+    content: &Box,
+    heading: &str,
+    tiles: Vec<(BrowseFilter, i64)>,
+) {
+    let label = Label::builder()
+        .label(heading)
+        .css_classes(["title-2"])
+        .halign(Start)
+        .build();
+    content.append(&label);
+
+    let grid = build_grid(&format!("{heading} \u{2014} click one to show its albums"));
+    grid.set_halign(Start);
+    for (filter, album_count) in tiles {
+        grid.append(&build_tile(state, filter, album_count));
+    }
+    content.append(&grid);
+}
+
+/// Build one tile that opens the albums matching `filter`.
+fn build_tile(state: &Arc<AppState>, filter: BrowseFilter, album_count: i64) -> Button {
+    let title = filter.title();
+    let card = Box::builder().orientation(Vertical).spacing(2).build();
+    card.append(
+        &Label::builder()
+            .label(&title)
+            .ellipsize(End)
+            .max_width_chars(18)
+            .css_classes(["heading"])
+            .halign(Start)
+            .build(),
+    );
+    card.append(
+        &Label::builder()
+            .label(album_count_label(album_count))
+            .css_classes(["dim-label", "caption"])
+            .halign(Start)
+            .build(),
+    );
+
+    let tile = Button::builder()
+        .child(&card)
+        .width_request(160)
+        .css_classes(["card"])
+        .build();
+    tile.update_property(&[PropertyLabel(&format!(
+        "{title}, {}",
+        album_count_label(album_count)
+    ))]);
+
+    let state = Arc::clone(state);
+    tile.connect_clicked(move |_| {
+        let state = Arc::clone(&state);
+        let filter = filter.clone();
+        spawn_future_local(async move {
+            state.send_navigation_event(BrowseAlbums(filter)).await;
+        });
+    });
+    tile
+}
+
+/// Album count text, e.g. `1 album` or `12 albums`.
+fn album_count_label(count: i64) -> String {
+    if count == 1 {
+        "1 album".to_string()
+    } else {
+        format!("{count} albums")
+    }
+}
+
+/// Placeholder shown when no album has a genre or release year.
+fn build_nothing_to_browse() -> StatusPage {
+    StatusPage::builder()
+        .icon_name("view-list-symbolic")
+        .title("Nothing to Browse")
+        .description("Albums with a genre or release year are grouped here.")
+        .vexpand(true)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::library::browse::album_count_label;
+
+    #[test]
+    fn album_count_is_pluralized() {
+        assert_eq!(album_count_label(1), "1 album", "Singular for one album");
+        assert_eq!(album_count_label(7), "7 albums", "Plural otherwise");
+    }
+}
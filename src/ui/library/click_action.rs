@@ -0,0 +1,97 @@
+//! View > Display rows choosing what clicks in the library views do.
+//!
+//! Albums open their detail page by default. Playing from a click replaces
+//! the queue; the play button on a cover always plays the album. Rows of
+//! the list view open with a double click unless set to a single click;
+//! changing this rebuilds the list view.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{ComboRow, glib::spawn_future_local, gtk::StringList, prelude::ComboRowExt},
+    tracing::{error, info, warn},
+};
+
+use crate::{
+    app::AppState,
+    storage::click_action::{AlbumClickAction, ListActivation},
+};
+
+/// Build the row choosing what clicking an album does.
+pub fn build_album_click_row(state: &Arc<AppState>) -> ComboRow {
+    let labels: Vec<&str> = AlbumClickAction::ALL.iter().map(|a| a.label()).collect();
+    let model = StringList::new(&labels);
+    let click_row = ComboRow::builder()
+        .title("Clicking an Album")
+        .subtitle(
+            "Playing replaces the queue, which can be undone from the notification; the play \
+             button on the cover always plays the album",
+        )
+        .model(&model)
+        .build();
+    let current = state.storage.get_album_click_action();
+    let position = AlbumClickAction::ALL.iter().position(|a| *a == current);
+    click_row.set_selected(position.and_then(|p| u32::try_from(p).ok()).unwrap_or(0));
+
+    let state_click = Arc::clone(state);
+    click_row.connect_selected_notify(move |row| {
+        let Some(action) = usize::try_from(row.selected())
+            .ok()
+            .and_then(|i| AlbumClickAction::ALL.get(i).copied())
+        else {
+            return;
+        };
+        info!(action = ?action, "Album click action changed");
+        spawn_future_local(save_album_click_setting(Arc::clone(&state_click), action));
+    });
+
+    click_row
+}
+
+/// Persist the album click action, logging on failure.
+async fn save_album_click_setting(state: Arc<AppState>, action: AlbumClickAction) {
+    if let Err(e) = state.storage.set_album_click_action(action).await {
+        error!(error = %e, "Failed to save album click action");
+    }
+}
+
+/// Build the row choosing how rows of the list view are opened.
+pub fn build_list_activation_row(state: &Arc<AppState>) -> ComboRow {
+    let labels: Vec<&str> = ListActivation::ALL.iter().map(|a| a.label()).collect();
+    let model = StringList::new(&labels);
+    let activation_row = ComboRow::builder()
+        .title("Open List Rows With")
+        .subtitle("With a double click, a single click selects rows instead")
+        .model(&model)
+        .build();
+    let current = state.storage.get_list_activation();
+    let position = ListActivation::ALL.iter().position(|a| *a == current);
+    activation_row.set_selected(position.and_then(|p| u32::try_from(p).ok()).unwrap_or(0));
+
+    let state_activation = Arc::clone(state);
+    activation_row.connect_selected_notify(move |row| {
+        let Some(activation) = usize::try_from(row.selected())
+            .ok()
+            .and_then(|i| ListActivation::ALL.get(i).copied())
+        else {
+            return;
+        };
+        info!(activation = ?activation, "List activation mode changed");
+        spawn_future_local(save_list_activation_setting(
+            Arc::clone(&state_activation),
+            activation,
+        ));
+    });
+
+    activation_row
+}
+
+/// Persist the list activation mode and reload the library views.
+async fn save_list_activation_setting(state: Arc<AppState>, activation: ListActivation) {
+    if let Err(e) = state.storage.set_list_activation(activation).await {
+        error!(error = %e, "Failed to save list activation mode");
+    }
+    if let Err(e) = state.refresh_tx.send(()) {
+        warn!(error = %e, "Failed to send refresh signal");
+    }
+}
@@ -0,0 +1,51 @@
+//! View > Display row choosing whether collaborations are split.
+//!
+//! Split, albums credited to several artists are listed under each of
+//! them instead of under one combined artist. The library views reload
+//! with the change.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        SwitchRow,
+        glib::spawn_future_local,
+        prelude::{ActionRowExt, PreferencesRowExt},
+    },
+    tracing::{error, info, warn},
+};
+
+use crate::app::AppState;
+
+/// Build the row listing albums of collaborations under each artist.
+pub fn build_split_collaborations_row(state: &Arc<AppState>) -> SwitchRow {
+    let split_row = SwitchRow::new();
+    split_row.set_title("Split Collaborations");
+    split_row.set_subtitle(
+        "List albums by \u{201c}A feat. B\u{201d} or \u{201c}A & B\u{201d} under each artist \
+         instead of as a separate artist",
+    );
+    split_row.set_active(state.storage.get_split_collaborations());
+
+    let state_split = Arc::clone(state);
+    split_row.connect_active_notify(move |row| {
+        let enabled = row.is_active();
+        info!(enabled, "Collaboration splitting changed");
+        spawn_future_local(save_split_collaborations_setting(
+            Arc::clone(&state_split),
+            enabled,
+        ));
+    });
+
+    split_row
+}
+
+/// Persist whether collaborations are split and reload the library views.
+async fn save_split_collaborations_setting(state: Arc<AppState>, enabled: bool) {
+    if let Err(e) = state.storage.set_split_collaborations(enabled).await {
+        error!(error = %e, "Failed to save collaboration setting");
+    }
+    if let Err(e) = state.refresh_tx.send(()) {
+        warn!(error = %e, "Failed to send refresh signal");
+    }
+}
@@ -3,6 +3,10 @@
 //! Provides `NarrowState` for adaptive column hiding and two builder
 //! functions that return a fully wired `GtkColumnView` with
 //! column-specific factories, sorters, and click‑to‑navigate handling.
+//!
+//! Rows open on a double click by default, so a single click only selects
+//! them and several rows can be selected with Ctrl and Shift. Users who
+//! prefer it can switch to opening rows on a single click instead.
 
 use std::{
     cmp::Ordering::{self, Equal},
@@ -26,8 +30,8 @@ use {
         },
         gtk::{
             Align::Start, ColumnView, ColumnViewColumn, ContentFit::Cover, CustomSorter, Image,
-            Label, ListItem, NoSelection, Picture, SignalListItemFactory, SortListModel, Widget,
-            pango::EllipsizeMode::End,
+            Label, ListItem, MultiSelection, NoSelection, Picture, SelectionModel,
+            SignalListItemFactory, SortListModel, Widget, pango::EllipsizeMode::End,
         },
         prelude::{Cast, ListItemExt, ListModelExt, ObjectExt},
     },
@@ -39,12 +43,19 @@ use {
 use crate::{
     app::{
         AppState,
-        NavigationEvent::{self, AlbumDetail, ArtistDetail},
+        NavigationEvent::{self, ArtistDetail},
+    },
+    storage::{
+        Album, Artist, FormatInfo,
+        click_action::ListActivation::{self, DoubleClick, SingleClick},
+        collation::TitleCollator,
     },
-    storage::{Album, Artist, FormatInfo},
     ui::{
         CoverArtCache, DecodedCover,
-        library::models::{AlbumData, ArtistData},
+        library::{
+            albums::activate_album,
+            models::{AlbumData, ArtistData},
+        },
         raw_to_texture,
     },
 };
@@ -119,14 +130,21 @@ impl NarrowState {
 /// Map of album ID to pending `Picture` weak references awaiting cover art.
 type PendingCovers = HashMap<i64, Vec<WeakRef<Picture>>>;
 
-/// Create a `ColumnView` with sort model and no selection from a store.
-fn setup_column_view(store: ListStore) -> ColumnView {
+/// Create a sortable `ColumnView` over a store.
+///
+/// With [`SingleClick`] activation rows cannot be selected, since every
+/// click opens one. With [`DoubleClick`] a click selects rows instead.
+fn setup_column_view(store: ListStore, activation: ListActivation) -> ColumnView {
     let model: ListModel = store.upcast();
     let sort_model = SortListModel::new(Some(model), None::<CustomSorter>);
-    let selection = NoSelection::new(Some(sort_model));
+    let selection: SelectionModel = match activation {
+        SingleClick => NoSelection::new(Some(sort_model)).upcast(),
+        DoubleClick => MultiSelection::new(Some(sort_model)).upcast(),
+    };
 
     ColumnView::builder()
         .model(&selection)
+        .single_click_activate(activation == SingleClick)
         .hexpand(true)
         .vexpand(true)
         .build()
@@ -184,19 +202,38 @@ pub fn build_album_column_view<S: BuildHasher>(
 ) -> Widget {
     let store = ListStore::new::<BoxedAnyObject>();
 
-    let column_view = setup_column_view(store.clone());
+    let column_view = setup_column_view(store.clone(), state.storage.get_list_activation());
 
     let pending_widgets = Arc::<Mutex<PendingCovers>>::default();
 
+    let collator = state.storage.title_collator();
     let cover_col = build_cover_column(&state.cover_art_cache, &pending_widgets);
-    let artist_col =
-        build_string_column("Artist Name", |d: &AlbumData| d.artist_name.clone(), true);
-    let album_col = build_string_column("Album Name", |d: &AlbumData| d.title.clone(), true);
-    let format_col = build_string_column("Format", |d: &AlbumData| d.format.clone(), false);
-    let bit_depth_col =
-        build_string_column("Bit Depth", |d: &AlbumData| d.bit_depth.clone(), false);
-    let sample_rate_col =
-        build_string_column("Sample Rate", |d: &AlbumData| d.sample_rate.clone(), false);
+    let artist_col = build_string_column(
+        "Artist Name",
+        |d: &AlbumData| d.artist_name.clone(),
+        &collator,
+        true,
+    );
+    let album_col = build_string_column(
+        "Album Name",
+        |d: &AlbumData| d.title.clone(),
+        &collator,
+        true,
+    );
+    let format_col =
+        build_string_column("Format", |d: &AlbumData| d.format.clone(), &collator, false);
+    let bit_depth_col = build_string_column(
+        "Bit Depth",
+        |d: &AlbumData| d.bit_depth.clone(),
+        &collator,
+        false,
+    );
+    let sample_rate_col = build_string_column(
+        "Sample Rate",
+        |d: &AlbumData| d.sample_rate.clone(),
+        &collator,
+        false,
+    );
     let year_col = build_int_column("Year", |d: &AlbumData| d.year, default_int_format, false);
 
     column_view.append_column(&cover_col);
@@ -207,11 +244,15 @@ pub fn build_album_column_view<S: BuildHasher>(
     column_view.append_column(&sample_rate_col);
     column_view.append_column(&year_col);
 
-    let nav_state = Arc::clone(state);
+    let activate_state = Arc::clone(state);
     column_view.connect_activate(move |cv, position| {
-        if let Some(album_id) = id_at_position::<AlbumData>(cv, position, |d| d.id) {
-            navigate_to_event(Arc::clone(&nav_state), AlbumDetail(album_id));
-        }
+        let Some(album_id) = id_at_position::<AlbumData>(cv, position, |d| d.id) else {
+            return;
+        };
+        let state = Arc::clone(&activate_state);
+        spawn_future_local(async move {
+            activate_album(&state, album_id).await;
+        });
     });
 
     setup_narrow_bindings(
@@ -219,6 +260,7 @@ pub fn build_album_column_view<S: BuildHasher>(
         &[&format_col, &bit_depth_col, &sample_rate_col],
     );
 
+    let use_original_year = state.storage.get_use_original_year();
     let mut items: Vec<BoxedAnyObject> = albums
         .iter()
         .map(|album| {
@@ -226,17 +268,7 @@ pub fn build_album_column_view<S: BuildHasher>(
                 .get(&album.artist_id)
                 .map_or("Unknown Artist", String::as_str);
             let fi = format_info.get(&album.id).cloned().unwrap_or_default();
-            let data = AlbumData {
-                id: album.id,
-                title: album.title.clone(),
-                artist_name: artist_name.to_string(),
-                year: album.year.unwrap_or(0),
-                format: fi.formats_display(),
-                bit_depth: fi.bit_depth_display(),
-                sample_rate: fi.sample_rate_display(),
-                artwork_path: album.artwork_path.clone().unwrap_or_default(),
-            };
-            BoxedAnyObject::new(data)
+            BoxedAnyObject::new(AlbumData::new(album, artist_name, &fi, use_original_year))
         })
         .collect();
     batched_fill_store(&store, &mut items);
@@ -259,24 +291,37 @@ pub fn build_album_column_view<S: BuildHasher>(
 
 /// Build a fully wired `ColumnView` for artists.
 ///
-/// Columns: Artist Icon, Artist Name, Number of Albums.
+/// Columns: Artist Icon, Artist Name, Number of Albums, Number of Tracks.
 pub fn build_artist_column_view(state: &Arc<AppState>, artists: &[Artist]) -> Widget {
     let store = ListStore::new::<BoxedAnyObject>();
 
-    let column_view = setup_column_view(store.clone());
+    let column_view = setup_column_view(store.clone(), state.storage.get_list_activation());
 
+    let collator = state.storage.title_collator();
     let icon_col = build_artist_icon_column();
-    let name_col = build_string_column("Artist Name", |d: &ArtistData| d.name.clone(), true);
+    let name_col = build_string_column(
+        "Artist Name",
+        |d: &ArtistData| d.name.clone(),
+        &collator,
+        true,
+    );
     let albums_col = build_int_column(
         "Albums",
         |d: &ArtistData| d.album_count,
         default_int_format,
         false,
     );
+    let tracks_col = build_int_column(
+        "Tracks",
+        |d: &ArtistData| d.track_count,
+        default_int_format,
+        false,
+    );
 
     column_view.append_column(&icon_col);
     column_view.append_column(&name_col);
     column_view.append_column(&albums_col);
+    column_view.append_column(&tracks_col);
 
     let nav_state = Arc::clone(state);
     column_view.connect_activate(move |cv, position| {
@@ -292,6 +337,7 @@ pub fn build_artist_column_view(state: &Arc<AppState>, artists: &[Artist]) -> Wi
                 id: artist.id,
                 name: artist.name.clone(),
                 album_count: artist.album_count,
+                track_count: artist.track_count,
             })
         })
         .collect();
@@ -403,6 +449,7 @@ fn start_cover_batch_decode(
             COVER_THUMB_SIZE,
             tx.clone(),
             "column view",
+            None,
         );
     }
     drop(tx);
@@ -493,16 +540,18 @@ fn build_label_column<T: Clone + Send + 'static>(
         .build()
 }
 
-/// Build a text column that sorts by the given extractor function.
+/// Build a text column that sorts the extracted text with `collator`.
 fn build_string_column<T: Clone + Send + 'static>(
     title: &str,
     extract: fn(&T) -> String,
+    collator: &TitleCollator,
     expand: bool,
 ) -> ColumnViewColumn {
+    let collator = collator.clone();
     build_label_column(
         title,
         extract,
-        move |a: &T, b: &T| extract(a).cmp(&extract(b)),
+        move |a: &T, b: &T| collator.compare(&extract(a), &extract(b)),
         expand,
     )
 }
@@ -553,16 +602,15 @@ fn navigate_to_event(state: Arc<AppState>, event: NavigationEvent) {
 }
 
 /// Extract the id at the given sort‑model position.
+///
+/// The selection model passes the sorted items through, so positions in
+/// it match the sort model.
 fn id_at_position<T: Clone + Send + 'static>(
     cv: &ColumnView,
     position: u32,
     get_id: fn(&T) -> i64,
 ) -> Option<i64> {
-    let selection = cv.model()?;
-    let selection = selection.downcast_ref::<NoSelection>()?;
-    let model = selection.model()?;
-    let sort_model = model.downcast_ref::<SortListModel>()?;
-    let item = sort_model.item(position)?;
+    let item = cv.model()?.item(position)?;
     let boxed = item.downcast_ref::<BoxedAnyObject>()?;
     Some(get_id(&boxed.borrow::<T>()))
 }
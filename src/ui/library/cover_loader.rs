@@ -0,0 +1,237 @@
+//! Cover loading for the virtualized album grid.
+//!
+//! Covers are requested when a tile is bound and applied only if the tile
+//! still shows the same album. Decodes still queued for albums that were
+//! scrolled out of view are skipped, so fast scrolling does not leave the
+//! decoder busy with covers nobody sees.
+
+use std::{
+    collections::{HashMap, HashSet},
+    rc::{Rc, Weak},
+    sync::Arc,
+};
+
+use {
+    async_channel::{Receiver, Sender, unbounded},
+    libadwaita::{
+        gdk::{MemoryTexture, prelude::TextureExt},
+        glib::{BoxedAnyObject, WeakRef, spawn_future_local},
+        gtk::{ContentFit::Cover, Image, ListItem, Overlay, Picture, Widget},
+        prelude::{Cast, ListItemExt, ObjectExt},
+    },
+    parking_lot::Mutex,
+};
+
+use crate::ui::{
+    CoverArtCache, DecodeWanted, DecodedCover, library::models::AlbumData, raw_to_texture,
+};
+
+/// Albums shown by the tiles of one grid.
+#[derive(Debug, Default)]
+struct CoverDemand {
+    /// Number of tiles showing each album.
+    shown: HashMap<i64, usize>,
+    /// Albums whose queued decode was skipped because no tile showed them.
+    dropped: HashSet<i64>,
+}
+
+impl CoverDemand {
+    /// Record that one tile no longer shows `album_id`.
+    fn release(&mut self, album_id: i64) {
+        let Some(count) = self.shown.get_mut(&album_id) else {
+            return;
+        };
+        *count = count.saturating_sub(1);
+        if *count == 0 {
+            self.shown.remove(&album_id);
+        }
+    }
+
+    /// Whether a tile shows `album_id`, marking it as dropped if none does.
+    fn check_wanted(&mut self, album_id: i64) -> bool {
+        let shown = self.shown.contains_key(&album_id);
+        if !shown {
+            self.dropped.insert(album_id);
+        }
+        shown
+    }
+}
+
+/// Cover loading shared by every tile of one grid.
+///
+/// Each album is decoded at most once while tiles wait for it; results are
+/// applied on the main thread to the tiles that still show that album.
+pub struct CoverLoader {
+    /// Shared decoded-texture cache.
+    cache: Arc<CoverArtCache>,
+    /// Cover edge length in pixels for the grid's zoom level.
+    cover_size: i32,
+    /// Tiles waiting for a decode, keyed by album ID.
+    pending: Mutex<PendingTiles>,
+    /// Albums shown by tiles, checked by the decoder before decoding.
+    demand: Arc<Mutex<CoverDemand>>,
+    /// Sender handed to the cover decoder for results.
+    tx: Sender<(i64, DecodedCover)>,
+}
+
+impl CoverLoader {
+    /// Create a loader and start its main-thread result loop.
+    ///
+    /// The loop exits once the grid, and with it the last strong reference
+    /// to the loader, is dropped.
+    pub fn new_shared(cache: &Arc<CoverArtCache>, cover_size: i32) -> Rc<Self> {
+        let (tx, rx) = unbounded::<(i64, DecodedCover)>();
+        let loader = Rc::new(Self {
+            cache: Arc::clone(cache),
+            cover_size,
+            pending: Mutex::new(HashMap::new()),
+            demand: Arc::new(Mutex::new(CoverDemand::default())),
+            tx,
+        });
+
+        spawn_future_local(receive_covers(Rc::downgrade(&loader), rx));
+
+        loader
+    }
+
+    /// Show the cover for `data` in `overlay`, requesting a decode if needed.
+    pub fn show(&self, list_item: &ListItem, overlay: &Overlay, data: &AlbumData) {
+        if let Some(texture) = self
+            .cache
+            .get(data.id)
+            .filter(|t| t.width() >= self.cover_size || t.height() >= self.cover_size)
+        {
+            apply_texture(overlay, &texture, self.cover_size);
+            return;
+        }
+
+        if overlay.child().is_none_or(|c| c.is::<Picture>()) {
+            overlay.set_child(Some(&build_placeholder(self.cover_size)));
+        }
+        if data.artwork_path.is_empty() {
+            return;
+        }
+
+        let mut pending = self.pending.lock();
+        let waiters = pending.entry(data.id).or_default();
+        let dropped = self.demand.lock().dropped.remove(&data.id);
+        let first = waiters.is_empty() || dropped;
+        waiters.push((list_item.downgrade(), overlay.downgrade()));
+        drop(pending);
+
+        if first {
+            self.cache.request_decode_to_channel(
+                data.id,
+                data.artwork_path.clone(),
+                self.cover_size,
+                self.tx.clone(),
+                "album grid",
+                Some(self.wanted(data.id)),
+            );
+        }
+    }
+
+    /// Cover edge length in pixels.
+    #[must_use]
+    pub const fn cover_size(&self) -> i32 {
+        self.cover_size
+    }
+
+    /// Record that a tile now shows `current` instead of `previous`.
+    pub fn track_shown(&self, previous: Option<i64>, current: Option<i64>) {
+        if previous == current {
+            return;
+        }
+        let mut demand = self.demand.lock();
+        if let Some(previous) = previous {
+            demand.release(previous);
+        }
+        if let Some(current) = current {
+            *demand.shown.entry(current).or_default() += 1;
+        }
+    }
+
+    /// Decoder check that the cover of `album_id` is still shown by a tile.
+    ///
+    /// A skipped album is marked as dropped, so the next tile showing it
+    /// requests it again. Once the grid is gone, nothing is wanted.
+    fn wanted(&self, album_id: i64) -> DecodeWanted {
+        let demand = Arc::downgrade(&self.demand);
+        Box::new(move || {
+            demand
+                .upgrade()
+                .is_some_and(|demand| demand.lock().check_wanted(album_id))
+        })
+    }
+
+    /// Cache a decoded cover and apply it to tiles still showing the album.
+    fn apply(&self, album_id: i64, decoded: &DecodedCover) {
+        let texture = raw_to_texture(decoded);
+        self.cache.insert(album_id, texture.clone());
+
+        let Some(waiters) = self.pending.lock().remove(&album_id) else {
+            return;
+        };
+        waiters
+            .iter()
+            .filter_map(|(list_item, overlay)| Some((list_item.upgrade()?, overlay.upgrade()?)))
+            .filter(|(list_item, _)| item_album_id(list_item) == Some(album_id))
+            .for_each(|(_, overlay)| apply_texture(&overlay, &texture, self.cover_size));
+    }
+}
+
+/// Map of album ID to bound tiles waiting for that album's cover.
+type PendingTiles = HashMap<i64, Vec<(WeakRef<ListItem>, WeakRef<Overlay>)>>;
+
+/// Apply decode results until the loader is dropped or the channel closes.
+async fn receive_covers(loader: Weak<CoverLoader>, rx: Receiver<(i64, DecodedCover)>) {
+    while let Ok((album_id, decoded)) = rx.recv().await {
+        let Some(loader) = loader.upgrade() else {
+            break;
+        };
+        loader.apply(album_id, &decoded);
+    }
+}
+
+/// Return the album ID currently shown by `list_item`.
+pub fn item_album_id(list_item: &ListItem) -> Option<i64> {
+    let item = list_item.item()?;
+    let boxed = item.downcast_ref::<BoxedAnyObject>()?;
+    Some(boxed.borrow::<AlbumData>().id)
+}
+
+/// Build a placeholder cover art widget of `size` pixels.
+///
+/// Returns an `Image` with a generic audio icon, shown until the cover
+/// has been decoded.
+pub fn build_placeholder(size: i32) -> Widget {
+    Image::builder()
+        .icon_name("audio-x-generic-symbolic")
+        .pixel_size(size / 2)
+        .width_request(size)
+        .height_request(size)
+        .css_classes(["album-cover", "dim-label"])
+        .build()
+        .upcast()
+}
+
+/// Apply a decoded texture to an overlay's child.
+///
+/// If the child is already a `Picture`, updates its paintable in place.
+/// Otherwise replaces the child with a new `Picture` of `size` pixels.
+fn apply_texture(overlay: &Overlay, texture: &MemoryTexture, size: i32) {
+    let updated = overlay.child().and_then(|c| {
+        c.downcast_ref::<Picture>()
+            .map(|p| p.set_paintable(Some(texture)))
+    });
+    if updated.is_none() {
+        let picture = Picture::builder()
+            .paintable(texture)
+            .content_fit(Cover)
+            .width_request(size)
+            .height_request(size)
+            .css_classes(["album-cover"])
+            .build();
+        overlay.set_child(Some(&picture));
+    }
+}
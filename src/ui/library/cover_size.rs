@@ -0,0 +1,43 @@
+//! View > Display row choosing the cover size of the album grid.
+//!
+//! The row and the zoom shortcuts share one zoom level, so the shortcuts
+//! step from the size picked here.
+
+use std::sync::Arc;
+
+use libadwaita::{ComboRow, gtk::StringList, prelude::ComboRowExt};
+
+use crate::{app::AppState, storage::zoom::ZoomLevel, ui::library::album_tiles::set_zoom_level};
+
+/// Build the row pinning the cover size of the album grid.
+pub fn build_cover_size_row(state: &Arc<AppState>) -> ComboRow {
+    let labels: Vec<String> = ZoomLevel::ALL
+        .iter()
+        .map(|z| format!("{} ({} px)", z.label(), z.cover_size()))
+        .collect();
+    let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+    let model = StringList::new(&labels);
+    let size_row = ComboRow::builder()
+        .title("Album Cover Size")
+        .subtitle(
+            "Size of the covers in the album grid, also changed with Ctrl+= and Ctrl+\u{2212}",
+        )
+        .model(&model)
+        .build();
+    let current = *state.zoom_level_tx.borrow();
+    let position = ZoomLevel::ALL.iter().position(|z| *z == current);
+    size_row.set_selected(position.and_then(|p| u32::try_from(p).ok()).unwrap_or(0));
+
+    let state_size = Arc::clone(state);
+    size_row.connect_selected_notify(move |row| {
+        let Some(level) = usize::try_from(row.selected())
+            .ok()
+            .and_then(|i| ZoomLevel::ALL.get(i).copied())
+        else {
+            return;
+        };
+        set_zoom_level(&state_size, level);
+    });
+
+    size_row
+}
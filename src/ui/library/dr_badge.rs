@@ -0,0 +1,72 @@
+//! DR badge shown on album grid covers.
+//!
+//! The badge reads `DR12` and is colored by the value: green for wide
+//! dynamics, amber for average ones and red for loudness-war masters.
+//! Which albums get one follows the user's [`DrBadgeDisplayPolicy`].
+
+use libadwaita::{
+    gtk::{
+        Align::{End, Start},
+        Label,
+    },
+    prelude::WidgetExt,
+};
+
+use crate::{library::dynamic_range::DrBadgeDisplayPolicy, ui::library::models::AlbumData};
+
+/// Lowest DR shown as wide dynamics.
+const WIDE_DR: i32 = 14;
+
+/// Lowest DR not flagged as a loud master.
+const AVERAGE_DR: i32 = 8;
+
+/// Color classes a badge may carry.
+const COLOR_CLASSES: [&str; 3] = ["success", "warning", "error"];
+
+/// Build the DR badge overlaid on a grid cover, hidden until bound.
+#[must_use]
+pub fn build_dr_overlay() -> Label {
+    Label::builder()
+        .css_classes(["osd", "caption", "numeric"])
+        .halign(Start)
+        .valign(End)
+        .margin_start(6)
+        .margin_bottom(6)
+        .visible(false)
+        .build()
+}
+
+/// Show the badge of `data`, or hide it if `policy` leaves it out.
+///
+/// # Arguments
+///
+/// * `badge` - Badge built by [`build_dr_overlay`]
+/// * `data` - Album the tile now shows
+/// * `policy` - Which albums get a badge
+pub fn show_dr_overlay(badge: &Label, data: &AlbumData, policy: DrBadgeDisplayPolicy) {
+    let Some(dr) = data
+        .dr_value
+        .filter(|_| policy.shows(data.dr_value, data.lossless))
+    else {
+        badge.set_visible(false);
+        return;
+    };
+    badge.set_label(&format!("DR{dr}"));
+    badge.set_tooltip_text(Some(&format!("Dynamic range {dr} dB")));
+    for class in COLOR_CLASSES {
+        badge.remove_css_class(class);
+    }
+    badge.add_css_class(dr_color_class(dr));
+    badge.set_visible(true);
+}
+
+/// Color class for a DR value.
+const fn dr_color_class(dr: i32) -> &'static str {
+    if dr >= WIDE_DR {
+        "success"
+    } else if dr >= AVERAGE_DR {
+        "warning"
+    } else {
+        "error"
+    }
+}
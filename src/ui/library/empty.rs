@@ -1,28 +1,29 @@
 //! Shared utilities for library views.
 //!
 //! Provides empty state components and the generic grid builder
-//! used by the album and artist grid views.
+//! used by the album and artist grid views. Empty states show the progress
+//! of a running scan.
 
 use std::sync::Arc;
 
 use {
     libadwaita::{
-        glib::{object::Cast, spawn_future_local},
+        glib::{WeakRef, object::Cast, spawn_future_local},
         gtk::{
             Align::Center, Box, Button, FileDialog, Image, Label, Orientation::Vertical,
             ScrolledWindow, Stack, Widget, Window, accessible::Property::Label as PropertyLabel,
             prelude::WidgetExt,
         },
-        prelude::{AccessibleExtManual, BoxExt, ButtonExt, FileExt, IsA},
+        prelude::{AccessibleExtManual, BoxExt, ButtonExt, FileExt, IsA, ObjectExt},
     },
     parking_lot::Mutex,
-    tokio::spawn,
+    tokio::{spawn, sync::watch::Receiver},
     tracing::{info, warn},
 };
 
 use crate::{
     app::AppState,
-    library::scanner::LibraryScanner,
+    library::{scan_status::ScanStatus, scanner::LibraryScanner},
     storage::{Storage, settings::ViewMode},
     ui::library::column_view::NarrowState,
 };
@@ -92,11 +93,42 @@ pub fn build_empty_state(state: &Arc<AppState>, params: &EmptyStateParams) -> Bo
     container.append(&icon);
     container.append(&heading);
     container.append(&description);
+    container.append(&build_scan_progress_label(state));
     container.append(&add_folder_button);
 
     container
 }
 
+/// Build a label showing the progress of a running scan, hidden when idle.
+///
+/// An empty library is usually empty because its first scan is still
+/// running; the grid fills in when the scan completes.
+fn build_scan_progress_label(state: &Arc<AppState>) -> Label {
+    let initial = *state.scan_status_tx.borrow();
+    let label = Label::builder()
+        .label(initial.label())
+        .visible(initial.active)
+        .css_classes(["dim-label", "caption"])
+        .build();
+
+    let status_rx = state.scan_status_tx.subscribe();
+    spawn_future_local(follow_scan_progress(status_rx, label.downgrade()));
+
+    label
+}
+
+/// Show the progress of each scan on the label until it is dropped.
+async fn follow_scan_progress(mut status_rx: Receiver<ScanStatus>, weak_label: WeakRef<Label>) {
+    while status_rx.changed().await.is_ok() {
+        let status = *status_rx.borrow_and_update();
+        let Some(label) = weak_label.upgrade() else {
+            break;
+        };
+        label.set_visible(status.active);
+        label.set_label(&status.label());
+    }
+}
+
 /// Build a library grid view that pre-builds both grid (`FlowBox`) and column
 /// (`ColumnView`) layouts inside a `Stack`.  The parent orchestrator toggles
 /// the stack's visible child on view‑mode change — no data re‑fetch or widget
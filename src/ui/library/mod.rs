@@ -1,9 +1,22 @@
-//! Library views: album grid/column, artist grid/column, empty state,
-//! `GObject` models, and `GtkColumnView` builders.
+//! Library views: virtualized album grid with links to album artists, album column, artist
+//! grid/column, genre and decade browsing, empty state, `GObject` models, `GtkColumnView`
+//! builders, and playing the whole library.
 
+pub mod album_tiles;
 pub mod albums;
+pub mod artist_link;
 pub mod artists;
+pub mod browse;
+pub mod click_action;
+pub mod collaborations;
 pub mod column_view;
 pub mod common;
+pub mod cover_loader;
+pub mod cover_size;
+pub mod dr_badge;
 pub mod empty;
 pub mod models;
+pub mod original_year;
+pub mod play_all;
+pub mod quality_badge;
+pub mod sort_articles;
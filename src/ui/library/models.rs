@@ -2,16 +2,23 @@
 //!
 //! Wrapped in `BoxedAnyObject` for use with `gio::ListStore`.
 
-/// Data for an album displayed in `GtkColumnView`.
+use crate::{
+    storage::{Album, FormatInfo},
+    ui::library::quality_badge::AudioQuality,
+};
+
+/// Data for an album displayed in `GtkColumnView` or the album grid.
 #[derive(Clone, Debug)]
 pub struct AlbumData {
     /// Unique album identifier.
     pub id: i64,
     /// Album title.
     pub title: String,
+    /// Album artist identifier.
+    pub artist_id: i64,
     /// Artist display name.
     pub artist_name: String,
-    /// Release year (0 = unknown).
+    /// Year shown for the album (0 = unknown).
     pub year: i32,
     /// Audio format display (e.g. "FLAC" or "FLAC, MP3").
     pub format: String,
@@ -19,8 +26,45 @@ pub struct AlbumData {
     pub bit_depth: String,
     /// Sample rate display (e.g. "96" or "44.1, 96").
     pub sample_rate: String,
+    /// Combined format summary shown on grid tiles (e.g. "FLAC 24/96").
+    pub format_summary: String,
     /// Path to album artwork (empty = no artwork).
     pub artwork_path: String,
+    /// Dynamic range in dB, once analyzed.
+    pub dr_value: Option<i32>,
+    /// Whether all tracks are lossless.
+    pub lossless: bool,
+    /// Quality class shown by the grid badge.
+    pub quality: AudioQuality,
+}
+
+impl AlbumData {
+    /// Build display data for `album`.
+    ///
+    /// `use_original_year` picks the year shown, see [`Album::display_year`].
+    #[must_use]
+    pub fn new(
+        album: &Album,
+        artist_name: &str,
+        format_info: &FormatInfo,
+        use_original_year: bool,
+    ) -> Self {
+        Self {
+            id: album.id,
+            title: album.title.clone(),
+            artist_id: album.artist_id,
+            artist_name: artist_name.to_string(),
+            year: album.display_year(use_original_year).unwrap_or(0),
+            format: format_info.formats_display(),
+            bit_depth: format_info.bit_depth_display(),
+            sample_rate: format_info.sample_rate_display(),
+            format_summary: format_info.summary(),
+            artwork_path: album.artwork_path.clone().unwrap_or_default(),
+            dr_value: album.dr_value,
+            lossless: album.lossless,
+            quality: AudioQuality::of(album),
+        }
+    }
 }
 
 /// Data for an artist displayed in `GtkColumnView`.
@@ -32,4 +76,6 @@ pub struct ArtistData {
     pub name: String,
     /// Number of albums by this artist.
     pub album_count: i32,
+    /// Number of tracks on this artist's albums.
+    pub track_count: i32,
 }
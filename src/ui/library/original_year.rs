@@ -0,0 +1,48 @@
+//! View > Display row choosing which release year albums show.
+//!
+//! Albums are shown and sorted by the year of their edition unless the
+//! original release year is preferred. The library views reload with the
+//! change.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        SwitchRow,
+        glib::spawn_future_local,
+        prelude::{ActionRowExt, PreferencesRowExt},
+    },
+    tracing::{error, info, warn},
+};
+
+use crate::app::AppState;
+
+/// Build the row choosing between original release and edition years.
+pub fn build_original_year_row(state: &Arc<AppState>) -> SwitchRow {
+    let year_row = SwitchRow::new();
+    year_row.set_title("Show Original Release Year");
+    year_row.set_subtitle(
+        "Show and sort albums by the year they were first released instead of the year \
+         of this edition",
+    );
+    year_row.set_active(state.storage.get_use_original_year());
+
+    let state_year = Arc::clone(state);
+    year_row.connect_active_notify(move |row| {
+        let enabled = row.is_active();
+        info!(enabled, "Original release year preference changed");
+        spawn_future_local(save_original_year_setting(Arc::clone(&state_year), enabled));
+    });
+
+    year_row
+}
+
+/// Persist the release-year preference and reload the library views.
+async fn save_original_year_setting(state: Arc<AppState>, enabled: bool) {
+    if let Err(e) = state.storage.set_use_original_year(enabled).await {
+        error!(error = %e, "Failed to save release-year preference");
+    }
+    if let Err(e) = state.refresh_tx.send(()) {
+        warn!(error = %e, "Failed to send refresh signal");
+    }
+}
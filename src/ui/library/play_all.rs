@@ -0,0 +1,157 @@
+//! Playing the whole library.
+//!
+//! The header has "Play all" and "Shuffle all" buttons that queue every
+//! track of the library, in library order or shuffled, and turn on repeat
+//! so the library plays through again after the last track. Only the track
+//! IDs are read up front. Playback starts once the first few tracks are
+//! loaded and the rest are appended in large chunks, so a library of tens
+//! of thousands of tracks starts right away without reading every track
+//! row at once.
+
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use {
+    libadwaita::{
+        glib::spawn_future_local,
+        gtk::{Box, Button, Orientation::Horizontal, accessible::Property::Label as PropertyLabel},
+        prelude::{AccessibleExtManual, BoxExt, ButtonExt},
+    },
+    tracing::{info, warn},
+};
+
+use crate::{
+    app::AppState,
+    playback::{
+        control::PlaybackController,
+        queue::{RepeatMode::All, shuffle_tracks},
+    },
+    storage::{Storage, StorageResult, Track},
+    ui::library::albums::play_tracks,
+};
+
+/// Number of tracks loaded before playback starts.
+const FIRST_CHUNK_LEN: usize = 50;
+
+/// Number of tracks loaded and appended to the queue at a time afterwards.
+const CHUNK_LEN: usize = 2000;
+
+/// Build the linked "Play all" and "Shuffle all" buttons.
+#[must_use]
+pub fn build_play_all_buttons(state: &Arc<AppState>) -> Box {
+    let buttons = Box::builder()
+        .orientation(Horizontal)
+        .css_classes(["linked"])
+        .build();
+    buttons.append(&build_play_all_button(
+        state,
+        "media-playback-start-symbolic",
+        "Play all",
+        false,
+    ));
+    buttons.append(&build_play_all_button(
+        state,
+        "media-playlist-shuffle-symbolic",
+        "Shuffle all",
+        true,
+    ));
+    buttons
+}
+
+/// Build one button playing the whole library, `shuffled` or in order.
+fn build_play_all_button(
+    state: &Arc<AppState>,
+    icon_name: &str,
+    label: &str,
+    shuffled: bool,
+) -> Button {
+    let button = Button::builder()
+        .icon_name(icon_name)
+        .tooltip_text(label)
+        .css_classes(["flat"])
+        .can_focus(true)
+        .build();
+    button.update_property(&[PropertyLabel(label)]);
+    let state = Arc::clone(state);
+    button.connect_clicked(move |_| {
+        spawn_future_local(play_all(Arc::clone(&state), shuffled));
+    });
+    button
+}
+
+/// Queue the whole library, in library order or `shuffled`, and play it
+/// with repeat on.
+///
+/// Appending stops early if the queue is changed meanwhile, e.g. because
+/// an album is played, so the library is not mixed into the new queue.
+pub async fn play_all(state: Arc<AppState>, shuffled: bool) {
+    let mut track_ids = match state.storage.get_library_track_ids().await {
+        Ok(ids) => ids,
+        Err(e) => {
+            warn!(error = %e, "Failed to read library tracks");
+            return;
+        }
+    };
+    if track_ids.is_empty() {
+        info!("Library has no tracks to play");
+        return;
+    }
+    if shuffled {
+        shuffle_tracks(&mut track_ids);
+    }
+
+    let (first, rest) = track_ids.split_at(track_ids.len().min(FIRST_CHUNK_LEN));
+    let tracks = match load_tracks(&state, first).await {
+        Ok(tracks) => tracks,
+        Err(e) => {
+            warn!(error = %e, "Failed to load library tracks");
+            return;
+        }
+    };
+    if let Err(e) = state.playback.set_repeat_mode(All) {
+        warn!(error = %e, "Failed to repeat the library");
+    }
+    play_tracks(&state, &tracks, false).await;
+
+    let mut queued = state.playback.queue().len();
+    for chunk in rest.chunks(CHUNK_LEN) {
+        let tracks = match load_tracks(&state, chunk).await {
+            Ok(tracks) => tracks,
+            Err(e) => {
+                warn!(error = %e, queued, "Failed to load library tracks");
+                return;
+            }
+        };
+        if state.playback.queue().len() != queued {
+            info!(queued, "Queue changed, no longer adding library tracks");
+            return;
+        }
+        let paths: HashMap<i64, PathBuf> = tracks
+            .iter()
+            .map(|t| (t.id, PathBuf::from(&t.audio.file_path)))
+            .collect();
+        state.playback.add_track_paths(paths);
+        queued += tracks.len();
+        if let Err(e) = state
+            .playback
+            .extend_queue(tracks.iter().map(|t| t.id).collect())
+        {
+            warn!(error = %e, "Failed to queue library tracks");
+            return;
+        }
+    }
+    info!(queued, shuffled, "Queued the whole library");
+}
+
+/// Fetch the tracks with `track_ids`, in the order given.
+///
+/// Tracks removed from the library since the IDs were read are skipped.
+async fn load_tracks(state: &AppState, track_ids: &[i64]) -> StorageResult<Vec<Track>> {
+    let mut by_id: HashMap<i64, Track> = state
+        .storage
+        .get_tracks_by_ids(track_ids)
+        .await?
+        .into_iter()
+        .map(|t| (t.id, t))
+        .collect();
+    Ok(track_ids.iter().filter_map(|id| by_id.remove(id)).collect())
+}
@@ -0,0 +1,192 @@
+//! Hi-Res, lossless, and lossy badges shown on album grid covers.
+//!
+//! The badge sits in the opposite corner from the DR badge. It is computed
+//! from the album's representative format, bit depth, and sample rate:
+//! DSD and lossless albums above CD resolution read "Hi-Res", other
+//! lossless albums "Lossless", and the rest "Lossy". The badges can be
+//! hidden in the Library preferences.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        SwitchRow,
+        glib::spawn_future_local,
+        gtk::{
+            Align::{End, Start},
+            Label,
+        },
+        prelude::{ActionRowExt, PreferencesRowExt, WidgetExt},
+    },
+    tracing::{error, info, warn},
+};
+
+use crate::{app::AppState, storage::Album, ui::library::models::AlbumData};
+
+/// Highest bit depth of CD-quality audio.
+const CD_BIT_DEPTH: i32 = 16;
+
+/// Highest sample rate not counted as high resolution, in Hz.
+const CD_SAMPLE_RATE: i32 = 48_000;
+
+/// Format names of DSD files, which are always high resolution.
+const DSD_FORMATS: [&str; 3] = ["DSD", "DSF", "DFF"];
+
+/// Color classes a badge may carry.
+const COLOR_CLASSES: [&str; 2] = ["accent", "dim-label"];
+
+/// Audio quality class of an album.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioQuality {
+    /// DSD, or lossless above 16-bit or 48 kHz.
+    HiRes,
+    /// Lossless at CD resolution or below.
+    Lossless,
+    /// At least one track is lossy.
+    Lossy,
+}
+
+impl AudioQuality {
+    /// Quality class of `album` from its representative format.
+    #[must_use]
+    pub fn of(album: &Album) -> Self {
+        let dsd = DSD_FORMATS
+            .iter()
+            .any(|f| album.format.eq_ignore_ascii_case(f));
+        let above_cd = album.bit_depth.is_some_and(|d| d > CD_BIT_DEPTH)
+            || album.sample_rate.is_some_and(|r| r > CD_SAMPLE_RATE);
+        if dsd || (album.lossless && above_cd) {
+            Self::HiRes
+        } else if album.lossless {
+            Self::Lossless
+        } else {
+            Self::Lossy
+        }
+    }
+
+    /// Text shown on the badge.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::HiRes => "Hi-Res",
+            Self::Lossless => "Lossless",
+            Self::Lossy => "Lossy",
+        }
+    }
+
+    /// Color class of the badge.
+    const fn color_class(self) -> &'static str {
+        match self {
+            Self::HiRes => "accent",
+            Self::Lossless | Self::Lossy => "dim-label",
+        }
+    }
+}
+
+/// Build the quality badge overlaid on a grid cover, hidden until bound.
+#[must_use]
+pub fn build_quality_overlay() -> Label {
+    Label::builder()
+        .css_classes(["osd", "caption"])
+        .halign(End)
+        .valign(Start)
+        .margin_end(6)
+        .margin_top(6)
+        .visible(false)
+        .build()
+}
+
+/// Show the quality badge of `data`, or hide it when badges are off.
+///
+/// # Arguments
+///
+/// * `badge` - Badge built by [`build_quality_overlay`]
+/// * `data` - Album the tile now shows
+/// * `enabled` - Whether quality badges are shown
+pub fn show_quality_overlay(badge: &Label, data: &AlbumData, enabled: bool) {
+    if !enabled {
+        badge.set_visible(false);
+        return;
+    }
+    badge.set_label(data.quality.label());
+    for class in COLOR_CLASSES {
+        badge.remove_css_class(class);
+    }
+    badge.add_css_class(data.quality.color_class());
+    badge.set_visible(true);
+}
+
+/// Build the Library preferences row showing or hiding quality badges.
+#[must_use]
+pub fn build_quality_badge_row(state: &Arc<AppState>) -> SwitchRow {
+    let row = SwitchRow::new();
+    row.set_title("Show Quality Badges");
+    row.set_subtitle("Mark albums as Hi-Res, Lossless, or Lossy on their covers");
+    row.set_active(state.storage.get_show_quality_badges());
+    let state = Arc::clone(state);
+    row.connect_active_notify(move |row| {
+        let enabled = row.is_active();
+        info!(enabled, "Quality badges toggled");
+        spawn_future_local(save_quality_badge_setting(Arc::clone(&state), enabled));
+    });
+    row
+}
+
+/// Persist whether quality badges are shown and reload the library views.
+async fn save_quality_badge_setting(state: Arc<AppState>, enabled: bool) {
+    if let Err(e) = state.storage.set_show_quality_badges(enabled).await {
+        error!(error = %e, "Failed to save quality badge setting");
+    }
+    if let Err(e) = state.refresh_tx.send(()) {
+        warn!(error = %e, "Failed to send refresh signal");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        storage::Album,
+        ui::library::quality_badge::AudioQuality::{self, HiRes, Lossless, Lossy},
+    };
+
+    /// Quality of an album in `format` with the given resolution.
+    fn quality(
+        format: &str,
+        lossless: bool,
+        bit_depth: Option<i32>,
+        sample_rate: Option<i32>,
+    ) -> AudioQuality {
+        AudioQuality::of(&Album {
+            id: 1,
+            title: "Album".to_string(),
+            artist_id: 1,
+            year: None,
+            original_year: None,
+            genre: None,
+            artwork_path: None,
+            cover_override: None,
+            track_count: 1,
+            total_duration: 0.0,
+            format_summary: String::new(),
+            lossless,
+            format: format.to_string(),
+            bit_depth,
+            sample_rate,
+            date_added: None,
+            dr_value: None,
+        })
+    }
+
+    #[test]
+    fn albums_above_cd_resolution_are_hi_res() {
+        assert_eq!(quality("FLAC", true, Some(24), Some(44_100)), HiRes);
+        assert_eq!(quality("FLAC", true, Some(16), Some(96_000)), HiRes);
+        assert_eq!(quality("DSF", true, None, Some(2_822_400)), HiRes);
+    }
+
+    #[test]
+    fn cd_and_lossy_albums_are_not_hi_res() {
+        assert_eq!(quality("FLAC", true, Some(16), Some(48_000)), Lossless);
+        assert_eq!(quality("MP3", false, None, Some(96_000)), Lossy);
+    }
+}
@@ -0,0 +1,101 @@
+//! Library preferences rows for ignoring leading articles when sorting.
+//!
+//! A switch decides whether "The Beatles" sorts under B, and an entry row
+//! lists the articles, separated by commas, so other languages can add
+//! theirs. The articles are only editable while the switch is on.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        EntryRow, PreferencesGroup, SwitchRow,
+        glib::spawn_future_local,
+        prelude::{
+            ActionRowExt, EditableExt, EntryRowExt, PreferencesGroupExt, PreferencesRowExt,
+            WidgetExt,
+        },
+    },
+    tracing::{error, info, warn},
+};
+
+use crate::app::AppState;
+
+/// Add the leading article switch and article list rows to `group`.
+pub fn add_sort_article_rows(group: &PreferencesGroup, state: &Arc<AppState>) {
+    let enabled = state.storage.get_ignore_sort_articles();
+    let switch_row = SwitchRow::new();
+    switch_row.set_title("Ignore Leading Articles");
+    switch_row.set_subtitle("Sort \u{201c}The Beatles\u{201d} under B");
+    switch_row.set_active(enabled);
+
+    let articles_row = EntryRow::builder()
+        .title("Leading Articles")
+        .text(state.storage.get_sort_articles().join(", "))
+        .show_apply_button(true)
+        .sensitive(enabled)
+        .build();
+
+    let (state_switch, articles_switch) = (Arc::clone(state), articles_row.clone());
+    switch_row.connect_active_notify(move |row| {
+        let enabled = row.is_active();
+        articles_switch.set_sensitive(enabled);
+        info!(enabled, "Leading article sorting toggled");
+        spawn_future_local(save_ignore_setting(Arc::clone(&state_switch), enabled));
+    });
+
+    let state_articles = Arc::clone(state);
+    articles_row.connect_apply(move |row| {
+        let articles = parse_articles(&row.text());
+        info!(?articles, "Sort articles changed");
+        spawn_future_local(save_articles(Arc::clone(&state_articles), articles));
+    });
+
+    group.add(&switch_row);
+    group.add(&articles_row);
+}
+
+/// Articles in a comma-separated list, without blanks.
+fn parse_articles(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Persist whether leading articles are ignored and re-sort the library.
+async fn save_ignore_setting(state: Arc<AppState>, enabled: bool) {
+    if let Err(e) = state.storage.set_ignore_sort_articles(enabled).await {
+        error!(error = %e, "Failed to save leading article setting");
+    }
+    send_refresh(&state);
+}
+
+/// Persist the sort articles and re-sort the library.
+async fn save_articles(state: Arc<AppState>, articles: Vec<String>) {
+    if let Err(e) = state.storage.set_sort_articles(articles).await {
+        error!(error = %e, "Failed to save sort articles");
+    }
+    send_refresh(&state);
+}
+
+/// Reload the library views in their new order.
+fn send_refresh(state: &AppState) {
+    if let Err(e) = state.refresh_tx.send(()) {
+        warn!(error = %e, "Failed to send refresh signal");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::library::sort_articles::parse_articles;
+
+    #[test]
+    fn articles_are_split_on_commas() {
+        assert_eq!(
+            parse_articles(" The, A ,,Die "),
+            ["The", "A", "Die"],
+            "Blanks are dropped"
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Multimedia key handling for the main window.
+//!
+//! Installs a capture-phase `EventControllerKey` on the window so the
+//! XF86 play/pause, next, previous, and stop keys drive the
+//! [`PlaybackController`] regardless of which widget has focus. The
+//! XF86 keysyms never insert text, so a focused text entry keeps
+//! receiving ordinary keystrokes while the user types.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        ApplicationWindow,
+        gdk::Key,
+        glib::Propagation::{Proceed, Stop},
+        gtk::{EventControllerKey, PropagationPhase::Capture},
+        prelude::{EventControllerExt, WidgetExt},
+    },
+    tracing::{error, info},
+};
+
+use crate::{
+    app::AppState,
+    playback::{PlaybackError, control::PlaybackController, engine::PlaybackEngine},
+};
+
+/// Playback action bound to a multimedia key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKeyAction {
+    /// Skip to the next track.
+    Next,
+    /// Toggle between playing and paused.
+    PlayPause,
+    /// Return to the previous track.
+    Previous,
+    /// Stop playback.
+    Stop,
+}
+
+impl MediaKeyAction {
+    /// Map a key to its media action, or `None` for ordinary keys.
+    #[must_use]
+    pub fn from_key(key: Key) -> Option<Self> {
+        match key {
+            Key::AudioPlay | Key::AudioPause => Some(Self::PlayPause),
+            Key::AudioNext => Some(Self::Next),
+            Key::AudioPrev => Some(Self::Previous),
+            Key::AudioStop => Some(Self::Stop),
+            _ => None,
+        }
+    }
+
+    /// Run the action against the playback engine.
+    ///
+    /// # Errors
+    ///
+    /// Returns the underlying [`PlaybackError`] if the engine rejects it.
+    pub fn apply(self, playback: &PlaybackEngine) -> Result<(), PlaybackError> {
+        match self {
+            Self::Next => playback.next_track(),
+            Self::PlayPause => playback.toggle_pause(),
+            Self::Previous => playback.previous_track(),
+            Self::Stop => playback.stop(),
+        }
+    }
+}
+
+/// Attach multimedia key handling to the main window.
+pub fn install_media_keys(window: &ApplicationWindow, state: &Arc<AppState>) {
+    let controller = EventControllerKey::new();
+    controller.set_propagation_phase(Capture);
+
+    let playback = Arc::clone(&state.playback);
+    controller.connect_key_pressed(move |_, key, _, _| {
+        let Some(action) = MediaKeyAction::from_key(key) else {
+            return Proceed;
+        };
+        info!(action = ?action, "Media key pressed");
+        if let Err(e) = action.apply(&playback) {
+            error!(error = %e, action = ?action, "Failed to handle media key");
+        }
+        Stop
+    });
+
+    window.add_controller(controller);
+}
+
+#[cfg(test)]
+mod tests {
+    use libadwaita::gdk::Key;
+
+    use crate::ui::media_keys::MediaKeyAction::{self, Next, PlayPause, Previous, Stop};
+
+    #[test]
+    fn media_keys_map_to_actions() {
+        assert_eq!(MediaKeyAction::from_key(Key::AudioPlay), Some(PlayPause));
+        assert_eq!(MediaKeyAction::from_key(Key::AudioPause), Some(PlayPause));
+        assert_eq!(MediaKeyAction::from_key(Key::AudioNext), Some(Next));
+        assert_eq!(MediaKeyAction::from_key(Key::AudioPrev), Some(Previous));
+        assert_eq!(MediaKeyAction::from_key(Key::AudioStop), Some(Stop));
+    }
+
+    #[test]
+    fn typing_keys_are_not_intercepted() {
+        assert_eq!(MediaKeyAction::from_key(Key::a), None);
+        assert_eq!(MediaKeyAction::from_key(Key::space), None);
+        assert_eq!(MediaKeyAction::from_key(Key::Return), None);
+    }
+}
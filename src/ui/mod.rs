@@ -1,16 +1,64 @@
 //! Libadwaita UI components: window, header, library views, detail pages, player panel.
 
+pub mod background_work;
+pub mod cache;
+pub mod catalog;
+pub mod change_detection;
+pub mod channels;
+pub mod cleanup;
+pub mod cover_art;
 pub mod detail;
+pub mod diagnostics;
+pub mod discord_ipc;
+pub mod dr_badge_policy;
+pub mod dr_batch;
+pub mod duplicates;
+pub mod equalizer;
+pub mod errors;
+pub mod fade;
+pub mod file_drop;
+pub mod general;
 pub mod header;
 pub mod library;
+pub mod media_keys;
+pub mod notifications;
+pub mod output_buffer;
 pub mod player;
+pub mod queue_undo;
+pub mod relocate;
+pub mod resample_quality;
+pub mod rich_presence;
+pub mod scrobbling;
+pub mod search;
 pub mod settings;
+pub mod shortcuts;
+pub mod signal_path;
+pub mod silence;
+pub mod source_rate;
+pub mod statistics;
 pub mod status;
+pub mod symlinks;
+pub mod transfer;
+pub mod transitions;
 pub mod window;
 
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    fs::rename,
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{
+            AtomicU64,
+            Ordering::{AcqRel, Acquire},
+        },
+    },
+};
 
-use crate::threading::ThreadManager;
+use crate::{
+    library::thumbnail::thumbnail_path,
+    threading::{ThreadManager, scheduler::BackgroundScheduler},
+};
 
 use {
     async_channel::{Receiver, Sender, unbounded},
@@ -23,7 +71,7 @@ use {
         gtk::{Align::Center, Button, gdk_pixbuf::Pixbuf},
     },
     parking_lot::Mutex,
-    tracing::error,
+    tracing::{debug, error, warn},
 };
 
 /// Request for the centralized cover decoder worker.
@@ -36,6 +84,9 @@ pub struct ArtworkDecodeRequest {
     pub size: i32,
     /// Callback invoked on the worker thread with the decode result.
     pub on_complete: Box<dyn FnOnce(i64, Option<DecodedCover>) + Send + 'static>,
+    /// Whether the cover is still needed, checked before decoding; `None`
+    /// always decodes.
+    pub wanted: Option<DecodeWanted>,
 }
 
 /// Thread-safe cache for decoded cover art textures.
@@ -63,12 +114,17 @@ impl CoverArtCache {
     /// Create a new `CoverArtCache` wrapped in [`Arc`].
     ///
     /// Spawns a single background thread (`"cover-decoder"`) via the
-    /// [`ThreadManager`] that processes decode requests sequentially.
-    pub fn new_shared(thread_manager: &ThreadManager) -> Arc<Self> {
+    /// [`ThreadManager`] that processes decode requests sequentially,
+    /// drawing one permit from `scheduler` per decode.
+    pub fn new_shared(
+        thread_manager: &ThreadManager,
+        scheduler: &Arc<BackgroundScheduler>,
+    ) -> Arc<Self> {
         let (request_tx, request_rx) = unbounded::<ArtworkDecodeRequest>();
 
+        let scheduler = Arc::clone(scheduler);
         thread_manager.spawn_named("cover-decoder", move || {
-            run_cover_decoder(&request_rx);
+            run_cover_decoder(&request_rx, &scheduler);
         });
 
         Arc::new(Self {
@@ -88,6 +144,9 @@ impl CoverArtCache {
     }
 
     /// Request decoding and send the result through a channel.
+    ///
+    /// The request is dropped without decoding if `wanted` says the cover
+    /// is no longer needed by the time the decoder gets to it.
     pub fn request_decode_to_channel(
         &self,
         album_id: i64,
@@ -95,6 +154,7 @@ impl CoverArtCache {
         size: i32,
         tx: Sender<(i64, DecodedCover)>,
         error_context: &'static str,
+        wanted: Option<DecodeWanted>,
     ) {
         self.request_decode(ArtworkDecodeRequest {
             album_id,
@@ -103,6 +163,7 @@ impl CoverArtCache {
             on_complete: Box::new(move |aid, decoded| {
                 send_channel_cover(&tx, aid, decoded, error_context);
             }),
+            wanted,
         });
     }
 
@@ -116,6 +177,12 @@ impl CoverArtCache {
         self.textures.lock().insert(album_id, Arc::new(texture));
     }
 
+    /// Forget the decoded cover of an album, so views decode its new
+    /// artwork the next time they show it.
+    pub fn remove(&self, album_id: i64) {
+        self.textures.lock().remove(&album_id);
+    }
+
     /// Record the album that a track belongs to, enabling cache lookups
     /// by track ID to resolve to the album-level cache entry.
     pub fn record_track_album(&self, track_id: i64, album_id: i64) {
@@ -147,6 +214,38 @@ impl CoverArtCache {
     }
 }
 
+/// Cancels the outdated cover decodes of a view showing one cover at a
+/// time, such as the player panel.
+///
+/// Every request takes a new ticket, which makes the requests before it
+/// unwanted, so skipping through tracks only decodes the cover that is
+/// still shown once the decoder gets to it.
+#[derive(Debug, Clone, Default)]
+pub struct CoverTicket {
+    /// Number of the latest ticket handed out.
+    latest: Arc<AtomicU64>,
+}
+
+impl CoverTicket {
+    /// Take a new ticket, cancelling the requests of earlier ones.
+    ///
+    /// # Returns
+    ///
+    /// The check to pass along with the request of this ticket.
+    #[must_use]
+    pub fn next(&self) -> DecodeWanted {
+        let ticket = self.latest.fetch_add(1, AcqRel) + 1;
+        let latest = Arc::clone(&self.latest);
+        Box::new(move || latest.load(Acquire) == ticket)
+    }
+}
+
+/// Check run on the cover decoder thread right before a decode.
+///
+/// Returning `false` drops the request unanswered: nothing is decoded and
+/// the completion callback is not called.
+pub type DecodeWanted = Box<dyn Fn() -> bool + Send + 'static>;
+
 /// Decoded cover art as raw pixel data (Send-safe).
 pub struct DecodedCover {
     /// Image width in pixels.
@@ -163,17 +262,17 @@ pub struct DecodedCover {
 
 /// Decode an image file at a given size into raw pixel data.
 ///
-/// Returns `None` if the file could not be loaded or decoded.
+/// A pre-scaled thumbnail from the disk cache is used when available;
+/// otherwise the original is decoded and its thumbnail stored for next
+/// time. Returns `None` if the file could not be loaded or decoded.
 /// The raw data can be sent across threads and converted to a
 /// `MemoryTexture` on the main thread via [`raw_to_texture`].
 pub fn decode_cover_raw(path: &str, size: i32) -> Option<DecodedCover> {
-    let pixbuf = match Pixbuf::from_file_at_scale(path, size, size, true) {
-        Ok(p) => p,
-        Err(e) => {
-            error!(error = %e, "Failed to decode cover art at {path}");
-            return None;
-        }
-    };
+    let thumbnail = thumbnail_path(Path::new(path), size);
+    let pixbuf = thumbnail
+        .as_deref()
+        .and_then(load_thumbnail)
+        .or_else(|| decode_and_store_thumbnail(path, size, thumbnail.as_deref()))?;
     let format = if pixbuf.has_alpha() { R8g8b8a8 } else { R8g8b8 };
     let bytes = pixbuf.read_pixel_bytes();
     Some(DecodedCover {
@@ -185,6 +284,47 @@ pub fn decode_cover_raw(path: &str, size: i32) -> Option<DecodedCover> {
     })
 }
 
+/// Load a cached thumbnail, returning `None` if it is missing or unreadable.
+fn load_thumbnail(thumbnail: &Path) -> Option<Pixbuf> {
+    if !thumbnail.exists() {
+        return None;
+    }
+    match Pixbuf::from_file(thumbnail) {
+        Ok(p) => Some(p),
+        Err(e) => {
+            warn!(error = %e, path = %thumbnail.display(), "Failed to load cached thumbnail");
+            None
+        }
+    }
+}
+
+/// Decode the original cover at `size` and write it to the thumbnail cache.
+fn decode_and_store_thumbnail(path: &str, size: i32, thumbnail: Option<&Path>) -> Option<Pixbuf> {
+    let pixbuf = match Pixbuf::from_file_at_scale(path, size, size, true) {
+        Ok(p) => p,
+        Err(e) => {
+            error!(error = %e, "Failed to decode cover art at {path}");
+            return None;
+        }
+    };
+    if let Some(thumbnail) = thumbnail {
+        store_thumbnail(&pixbuf, thumbnail);
+    }
+    Some(pixbuf)
+}
+
+/// Write a thumbnail atomically, logging on failure.
+fn store_thumbnail(pixbuf: &Pixbuf, thumbnail: &Path) {
+    let tmp = thumbnail.with_extension("png.tmp");
+    if let Err(e) = pixbuf.savev(&tmp, "png", &[]) {
+        warn!(error = %e, path = %tmp.display(), "Failed to write thumbnail");
+        return;
+    }
+    if let Err(e) = rename(&tmp, thumbnail) {
+        warn!(error = %e, path = %thumbnail.display(), "Failed to store thumbnail");
+    }
+}
+
 /// Convert raw decoded pixel data into a `MemoryTexture` for painting.
 ///
 /// Must be called on the main thread (creates a `GdkMemoryTexture`).
@@ -200,13 +340,6 @@ pub fn raw_to_texture(decoded: &DecodedCover) -> MemoryTexture {
     )
 }
 
-/// Decode an image file at a given size into a `MemoryTexture`.
-///
-/// Returns `None` if the file could not be loaded or decoded.
-pub fn decode_cover_at_size(path: &str, size: i32) -> Option<MemoryTexture> {
-    decode_cover_raw(path, size).as_ref().map(raw_to_texture)
-}
-
 /// Build a circular OSD play button for album overlays.
 #[must_use]
 pub fn build_album_play_button() -> Button {
@@ -219,9 +352,18 @@ pub fn build_album_play_button() -> Button {
 }
 
 /// Run the background cover decoder loop.
-fn run_cover_decoder(rx: &Receiver<ArtworkDecodeRequest>) {
+///
+/// Requests whose cover is no longer wanted are skipped. Each decode holds a [`BackgroundScheduler`] permit so cover decoding
+/// shares the background budget with scanning and analysis.
+fn run_cover_decoder(rx: &Receiver<ArtworkDecodeRequest>, scheduler: &BackgroundScheduler) {
     while let Ok(req) = rx.recv_blocking() {
+        if req.wanted.as_ref().is_some_and(|wanted| !wanted()) {
+            debug!(album_id = req.album_id, "Skipped cover no longer shown");
+            continue;
+        }
+        let permit = scheduler.acquire();
         let decoded = decode_cover_raw(&req.path, req.size);
+        drop(permit);
         (req.on_complete)(req.album_id, decoded);
     }
 }
@@ -238,3 +380,18 @@ fn send_channel_cover(
         error!(error = %e, "Failed to send decoded cover to {context}");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::CoverTicket;
+
+    #[test]
+    fn newer_ticket_cancels_older_requests() {
+        let ticket = CoverTicket::default();
+        let first = ticket.next();
+        assert!(first(), "Latest request is wanted");
+        let second = ticket.next();
+        assert!(!first(), "Superseded request is dropped");
+        assert!(second(), "Newest request is wanted");
+    }
+}
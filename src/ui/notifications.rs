@@ -0,0 +1,170 @@
+//! Desktop notification when a new track starts.
+//!
+//! Notifications are opt-in and only sent while the main window is not
+//! focused, since the player panel already shows the track then. Each one
+//! replaces the previous, so a listening session leaves a single entry in
+//! the notification list. When notifications are turned off for the
+//! application in the desktop settings, the desktop drops them silently.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use {
+    async_channel::Receiver,
+    libadwaita::{
+        ApplicationWindow, SwitchRow,
+        gio::{File, FileIcon, Notification, spawn_blocking},
+        glib::{WeakRef, spawn_future_local},
+        prelude::{ActionRowExt, ApplicationExt, GtkWindowExt, ObjectExt, PreferencesRowExt},
+    },
+    tracing::{debug, error, warn},
+};
+
+use crate::{
+    app::AppState,
+    library::thumbnail::thumbnail_path,
+    playback::{
+        control::PlaybackController,
+        engine::PlaybackEvent::{self, TrackStarted},
+    },
+    storage::database::SqliteStorage,
+};
+
+/// ID under which track notifications replace each other.
+const NOTIFICATION_ID: &str = "track-started";
+
+/// Send a notification for every track that starts while `window` is not
+/// focused and notifications are enabled.
+///
+/// # Arguments
+///
+/// * `window` - Main window, whose application sends the notifications
+/// * `state` - Application state with the playback events and settings
+pub fn install_track_notifications(window: &ApplicationWindow, state: &Arc<AppState>) {
+    let rx = state.playback.subscribe();
+    let weak_window = window.downgrade();
+    spawn_future_local(follow_track_starts(rx, weak_window, Arc::clone(state)));
+}
+
+/// Notify about each started track until the window is gone.
+async fn follow_track_starts(
+    rx: Receiver<PlaybackEvent>,
+    weak_window: WeakRef<ApplicationWindow>,
+    state: Arc<AppState>,
+) {
+    while let Ok(event) = rx.recv().await {
+        let TrackStarted { track_id } = event else {
+            continue;
+        };
+        let Some(window) = weak_window.upgrade() else {
+            break;
+        };
+        if window.is_active() || !state.storage.get_track_notifications() {
+            continue;
+        }
+        notify_track(&window, &state, track_id).await;
+    }
+}
+
+/// Send the notification for `track_id`.
+async fn notify_track(window: &ApplicationWindow, state: &AppState, track_id: i64) {
+    let Some(info) = state.playing_track_info(track_id).await else {
+        return;
+    };
+    let Some(app) = window.application() else {
+        debug!(track_id, "No application to send the track notification");
+        return;
+    };
+
+    let notification = Notification::new(&info.title);
+    notification.set_body(Some(&track_body(&info.artist, &info.album)));
+    if let Some(artwork_path) = info.artwork_path {
+        let size = state.zoom_level_tx.borrow().cover_size();
+        if let Some(icon) = cover_icon(artwork_path, size).await {
+            notification.set_icon(&icon);
+        }
+    }
+    app.send_notification(Some(NOTIFICATION_ID), &notification);
+    debug!(track_id, "Track notification sent");
+}
+
+/// Body text naming the artist and album, leaving out unknown ones.
+fn track_body(artist: &str, album: &str) -> String {
+    match (artist.is_empty(), album.is_empty()) {
+        (false, false) => format!("{artist} \u{2014} {album}"),
+        (false, true) => artist.to_string(),
+        (true, _) => album.to_string(),
+    }
+}
+
+/// Notification icon for `artwork_path` at `size`.
+///
+/// The thumbnail lookup reads file metadata and may create the cache
+/// directory, so it runs off the main thread.
+async fn cover_icon(artwork_path: String, size: i32) -> Option<FileIcon> {
+    match spawn_blocking(move || cover_icon_path(&artwork_path, size)).await {
+        Ok(path) => Some(FileIcon::new(&File::for_path(path))),
+        Err(e) => {
+            warn!(error = ?e, "Cover thumbnail lookup panicked");
+            None
+        }
+    }
+}
+
+/// Cached cover thumbnail of `artwork_path` at `size`, or the artwork
+/// itself when no thumbnail has been made yet.
+fn cover_icon_path(artwork_path: &str, size: i32) -> PathBuf {
+    thumbnail_path(Path::new(artwork_path), size)
+        .filter(|thumbnail| thumbnail.exists())
+        .unwrap_or_else(|| PathBuf::from(artwork_path))
+}
+
+/// Persist the track notification setting, logging on failure.
+async fn save_track_notifications(storage: Arc<SqliteStorage>, enabled: bool) {
+    if let Err(e) = storage.set_track_notifications(enabled).await {
+        error!(error = %e, "Failed to save track notification setting");
+    }
+}
+
+/// Build the switch turning track notifications on or off.
+#[must_use]
+pub fn build_track_notification_row(state: &Arc<AppState>) -> SwitchRow {
+    let row = SwitchRow::new();
+    row.set_title("Track Notifications");
+    row.set_subtitle(
+        "Show the new track in a desktop notification while the window is in the background",
+    );
+    row.set_active(state.storage.get_track_notifications());
+
+    let storage = Arc::clone(&state.storage);
+    row.connect_active_notify(move |row| {
+        spawn_future_local(save_track_notifications(
+            Arc::clone(&storage),
+            row.is_active(),
+        ));
+    });
+
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::notifications::track_body;
+
+    #[test]
+    fn body_skips_unknown_fields() {
+        assert_eq!(
+            track_body("Low", "Things We Lost"),
+            "Low \u{2014} Things We Lost"
+        );
+        assert_eq!(track_body("Low", ""), "Low", "No album");
+        assert_eq!(
+            track_body("", "Things We Lost"),
+            "Things We Lost",
+            "No artist"
+        );
+        assert_eq!(track_body("", ""), "", "Nothing known");
+    }
+}
@@ -0,0 +1,76 @@
+//! Audio > Output row choosing the size of the output buffer.
+//!
+//! Each choice shows how much audio the buffer holds at 48 kHz, so the
+//! trade-off between stability and responsiveness is visible without
+//! knowing what a frame is.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{ComboRow, glib::spawn_future_local, gtk::StringList, prelude::ComboRowExt},
+    tracing::{error, warn},
+};
+
+use crate::{
+    app::AppState,
+    playback::{
+        buffer::{buffer_frame_choices, buffer_latency},
+        control::PlaybackController,
+    },
+    storage::database::SqliteStorage,
+};
+
+/// Sample rate the latency of each choice is shown for.
+const LABEL_SAMPLE_RATE: u32 = 48_000;
+
+/// Build the row choosing the output buffer size.
+pub fn build_output_buffer_row(state: &Arc<AppState>) -> ComboRow {
+    let choices: Vec<u32> = buffer_frame_choices().collect();
+    let labels: Vec<String> = choices.iter().map(|f| buffer_label(*f)).collect();
+    let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+    let model = StringList::new(&labels);
+    let buffer_row = ComboRow::builder()
+        .title("Output Buffer")
+        .subtitle(
+            "Raise if playback drops out on a busy system; lower to hear equalizer and \
+             balance changes sooner. Applies from the next track",
+        )
+        .model(&model)
+        .build();
+    let current = state.storage.get_output_buffer_frames();
+    let position = choices.iter().position(|f| *f == current);
+    buffer_row.set_selected(position.and_then(|p| u32::try_from(p).ok()).unwrap_or(0));
+
+    let state_buffer = Arc::clone(state);
+    buffer_row.connect_selected_notify(move |row| {
+        let Some(frames) = usize::try_from(row.selected())
+            .ok()
+            .and_then(|i| choices.get(i).copied())
+        else {
+            return;
+        };
+        if let Err(e) = state_buffer.playback.set_buffer_frames(frames) {
+            warn!(error = %e, "Failed to set output buffer size");
+            return;
+        }
+        spawn_future_local(save_buffer_setting(
+            Arc::clone(&state_buffer.storage),
+            frames,
+        ));
+    });
+
+    buffer_row
+}
+
+/// Label of a buffer of `frames`, with the audio it holds at 48 kHz.
+fn buffer_label(frames: u32) -> String {
+    let latency = buffer_latency(frames, LABEL_SAMPLE_RATE);
+    format!("{frames} frames ({} ms)", latency.as_millis())
+}
+
+/// Persist the output buffer size, logging on failure.
+async fn save_buffer_setting(storage: Arc<SqliteStorage>, frames: u32) {
+    if let Err(e) = storage.set_output_buffer_frames(frames).await {
+        error!(error = %e, "Failed to save output buffer size");
+    }
+}
@@ -0,0 +1,215 @@
+//! A-B repeat buttons of the player panel.
+//!
+//! Clicking A marks the loop start at the current position and clicking B
+//! marks the end and starts looping. A third click on either button clears
+//! the loop and playback continues normally. The marks reset whenever
+//! another track starts or playback stops.
+
+use std::{cell::Cell, rc::Rc, sync::Arc};
+
+use {
+    libadwaita::{
+        glib::MainContext,
+        gtk::{
+            Align::Center, Box, Button, Orientation::Horizontal,
+            accessible::Property::Label as PropertyLabel,
+        },
+        prelude::{AccessibleExtManual, BoxExt, ButtonExt, WidgetExt},
+    },
+    tracing::{error, info},
+};
+
+use crate::{
+    app::AppState,
+    playback::{
+        control::PlaybackController,
+        engine::PlaybackEvent::{self, Stopped, TrackStarted},
+    },
+    ui::player::panel::format_time,
+};
+
+/// Widgets and marks of the A-B repeat controls.
+#[derive(Clone)]
+struct AbLoopView {
+    /// Button marking the loop start.
+    a_button: Button,
+    /// Button marking the loop end.
+    b_button: Button,
+    /// Points marked so far.
+    marks: Rc<Cell<AbMarks>>,
+}
+
+impl AbLoopView {
+    /// Show `marks` on the buttons.
+    fn show(&self, marks: AbMarks) {
+        self.marks.set(marks);
+        let (a, b) = match marks {
+            AbMarks::Unset => (None, None),
+            AbMarks::Start(start) => (Some(start), None),
+            AbMarks::Loop(start, end) => (Some(start), Some(end)),
+        };
+        style_point(&self.a_button, "start", a);
+        style_point(&self.b_button, "end", b);
+    }
+
+    /// Apply a button press to the marks and the playback engine.
+    fn press(&self, state: &AppState, update: fn(AbMarks, f64) -> AbMarks) {
+        let playback = state.playback.state();
+        if playback.current_track_id.is_none() {
+            return;
+        }
+        let before = self.marks.get();
+        let after = update(before, playback.elapsed_seconds);
+        if after == before {
+            return;
+        }
+        if after.range() != before.range()
+            && let Err(e) = state.playback.set_ab_loop(after.range())
+        {
+            error!(error = %e, "Failed to set A-B loop");
+            return;
+        }
+        info!(marks = ?after, "A-B repeat marks changed");
+        self.show(after);
+    }
+
+    /// Clear the marks once the track they belong to is no longer playing.
+    fn on_playback_event(&self, event: &PlaybackEvent) {
+        if matches!(event, TrackStarted { .. } | Stopped) {
+            self.show(AbMarks::Unset);
+        }
+    }
+}
+
+/// Loop points marked through the buttons.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AbMarks {
+    /// No point marked.
+    Unset,
+    /// Only the start point is marked.
+    Start(f64),
+    /// Both points are marked and the section loops.
+    Loop(f64, f64),
+}
+
+impl AbMarks {
+    /// Marks after clicking A at `position`.
+    const fn press_a(self, position: f64) -> Self {
+        match self {
+            Self::Unset => Self::Start(position),
+            Self::Start(_) | Self::Loop(..) => Self::Unset,
+        }
+    }
+
+    /// Marks after clicking B at `position`.
+    ///
+    /// An end point before the start point is ignored.
+    fn press_b(self, position: f64) -> Self {
+        match self {
+            Self::Start(start) if position > start => Self::Loop(start, position),
+            Self::Start(start) => Self::Start(start),
+            Self::Unset | Self::Loop(..) => Self::Unset,
+        }
+    }
+
+    /// Loop range to hand to the playback engine.
+    const fn range(self) -> Option<(f64, f64)> {
+        match self {
+            Self::Loop(start, end) => Some((start, end)),
+            Self::Unset | Self::Start(_) => None,
+        }
+    }
+}
+
+/// Build the A and B buttons for looping a section of the current track.
+#[must_use]
+pub fn build_ab_loop_controls(state: &Arc<AppState>) -> Box {
+    let controls = Box::builder()
+        .orientation(Horizontal)
+        .spacing(6)
+        .halign(Center)
+        .build();
+
+    let view = AbLoopView {
+        a_button: point_button("A"),
+        b_button: point_button("B"),
+        marks: Rc::new(Cell::new(AbMarks::Unset)),
+    };
+    view.show(AbMarks::Unset);
+    controls.append(&view.a_button);
+    controls.append(&view.b_button);
+
+    let a_view = view.clone();
+    let a_state = Arc::clone(state);
+    view.a_button.connect_clicked(move |_| {
+        a_view.press(&a_state, AbMarks::press_a);
+    });
+    let b_view = view.clone();
+    let b_state = Arc::clone(state);
+    view.b_button.connect_clicked(move |_| {
+        b_view.press(&b_state, AbMarks::press_b);
+    });
+
+    let rx = state.playback.subscribe();
+    MainContext::default().spawn_local(async move {
+        while let Ok(event) = rx.recv().await {
+            view.on_playback_event(&event);
+        }
+    });
+
+    controls
+}
+
+/// Build a small labelled button for one loop point.
+fn point_button(label: &str) -> Button {
+    Button::builder()
+        .label(label)
+        .css_classes(["flat", "circular", "caption"])
+        .build()
+}
+
+/// Highlight a loop point button and describe its mark.
+fn style_point(button: &Button, name: &str, position: Option<f64>) {
+    let tooltip = position.map_or_else(
+        || {
+            button.remove_css_class("suggested-action");
+            format!("Mark the loop {name} at the current position")
+        },
+        |position| {
+            button.add_css_class("suggested-action");
+            format!(
+                "Loop {name} at {} \u{2014} click to clear",
+                format_time(position)
+            )
+        },
+    );
+    button.set_tooltip_text(Some(&tooltip));
+    button.update_property(&[PropertyLabel(&tooltip)]);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::player::ab_loop::AbMarks::{Loop, Start, Unset};
+
+    #[test]
+    fn clicks_mark_loop_then_clear() {
+        let start = Unset.press_a(12.0);
+        assert_eq!(start, Start(12.0), "A marks the start");
+        let looping = start.press_b(20.0);
+        assert_eq!(looping, Loop(12.0, 20.0), "B marks the end");
+        assert_eq!(looping.range(), Some((12.0, 20.0)), "Loop is handed on");
+        assert_eq!(looping.press_a(25.0), Unset, "Third click on A clears");
+        assert_eq!(looping.press_b(25.0), Unset, "Third click on B clears");
+    }
+
+    #[test]
+    fn end_before_start_is_ignored() {
+        assert_eq!(
+            Start(30.0).press_b(10.0),
+            Start(30.0),
+            "B before A is ignored"
+        );
+        assert_eq!(Unset.press_b(10.0), Unset, "B needs a start point");
+        assert_eq!(Start(30.0).range(), None, "A alone does not loop");
+    }
+}
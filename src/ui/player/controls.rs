@@ -1,19 +1,32 @@
 //! Playback control widgets: transport buttons, seek slider, and volume control.
-
-use std::sync::{Arc, atomic::Ordering::Release};
+//!
+//! Every control carries an accessible name, and the sliders report their
+//! value as text, so screen readers announce them usefully. Previous and
+//! next are made insensitive when there is nowhere to skip to, which also
+//! marks them disabled for assistive technologies.
+
+use std::{
+    cell::Cell,
+    rc::Rc,
+    sync::{Arc, atomic::Ordering::Release},
+};
 
 use {
     libadwaita::{
-        glib::{Propagation::Proceed, spawn_future_local},
+        glib::{Propagation::Proceed, WeakRef, spawn_future_local},
         gtk::{
             Align::{Center, End, Start},
             Box, Button, GestureClick, Label,
             Orientation::{Horizontal, Vertical},
-            Scale,
-            accessible::Property::Label as PropertyLabel,
+            Revealer,
+            RevealerTransitionType::SlideDown,
+            Scale, ToggleButton,
+            accessible::Property::{Label as PropertyLabel, ValueText},
             prelude::{GestureSingleExt, RangeExt},
         },
-        prelude::{AccessibleExtManual, BoxExt, ButtonExt, ScaleExt, WidgetExt},
+        prelude::{
+            AccessibleExtManual, BoxExt, ButtonExt, ObjectExt, ScaleExt, ToggleButtonExt, WidgetExt,
+        },
     },
     tracing::{error, warn},
 };
@@ -22,14 +35,24 @@ use crate::{
     app::AppState,
     playback::{
         control::PlaybackController,
-        engine::{MuteState::Unmuted, PlaybackEngine},
+        engine::{
+            MuteState::{Muted, Unmuted},
+            PlaybackEngine,
+            PlaybackEvent::RepeatModeChanged,
+            PlaybackStatus::Playing,
+        },
         output::OutputMode::{self, BitPerfect, Resampled},
+        queue::RepeatMode::{self, All, Off},
+        signal_path::SignalPathReport,
     },
     storage::database::SqliteStorage,
-    ui::player::{panel::format_time, queue::build_queue_view},
+    ui::player::{
+        panel::{format_end_time, format_time},
+        queue::build_queue_view,
+    },
 };
 
-/// Build the playback control buttons (prev, play/pause, next).
+/// Build the playback control buttons (shuffle, prev, play/pause, next, repeat).
 ///
 /// Returns the button box and the play/pause button reference for event wiring.
 #[must_use]
@@ -40,6 +63,8 @@ pub fn build_playback_controls(state: &Arc<AppState>) -> (Box, Button) {
         .halign(Center)
         .build();
 
+    controls.append(&build_shuffle_button(state));
+
     let prev_button = Button::builder()
         .icon_name("media-skip-backward-symbolic")
         .css_classes(["flat"])
@@ -57,9 +82,8 @@ pub fn build_playback_controls(state: &Arc<AppState>) -> (Box, Button) {
     let play_button = Button::builder()
         .icon_name("media-playback-start-symbolic")
         .css_classes(["suggested-action", "circular"])
-        .tooltip_text("Play or pause")
         .build();
-    play_button.update_property(&[PropertyLabel("Play or pause")]);
+    show_play_state(&play_button, state.playback.state().status == Playing);
     let state_play = Arc::clone(state);
     play_button.connect_clicked(move |_| {
         if let Err(e) = state_play.playback.toggle_pause() {
@@ -82,9 +106,111 @@ pub fn build_playback_controls(state: &Arc<AppState>) -> (Box, Button) {
     });
     controls.append(&next_button);
 
+    controls.append(&build_repeat_button(state));
+
+    sync_navigation(&prev_button, &next_button, &state.playback);
+    spawn_future_local(follow_navigation(
+        prev_button.downgrade(),
+        next_button.downgrade(),
+        Arc::clone(state),
+    ));
+
     (controls, play_button)
 }
 
+/// Show `button` as pause while `playing`, and as play otherwise.
+pub fn show_play_state(button: &Button, playing: bool) {
+    let (icon, label) = if playing {
+        ("media-playback-pause-symbolic", "Pause")
+    } else {
+        ("media-playback-start-symbolic", "Play")
+    };
+    button.set_icon_name(icon);
+    button.set_tooltip_text(Some(label));
+    button.update_property(&[PropertyLabel(label)]);
+}
+
+/// Make previous and next sensitive only while the queue allows them.
+fn sync_navigation(prev: &Button, next: &Button, playback: &PlaybackEngine) {
+    let queue = playback.queue();
+    let has_previous = queue
+        .current_position()
+        .is_some_and(|position| position > 0 || queue.repeat_mode() == All);
+    prev.set_sensitive(has_previous);
+    next.set_sensitive(queue.peek_next().is_some());
+}
+
+/// Keep previous and next in step with the queue until they are gone.
+async fn follow_navigation(prev: WeakRef<Button>, next: WeakRef<Button>, state: Arc<AppState>) {
+    let events = state.playback.subscribe();
+    while events.recv().await.is_ok() {
+        let (Some(prev), Some(next)) = (prev.upgrade(), next.upgrade()) else {
+            break;
+        };
+        sync_navigation(&prev, &next, &state.playback);
+    }
+}
+
+/// Build the toggle turning shuffle on or off.
+fn build_shuffle_button(state: &Arc<AppState>) -> ToggleButton {
+    let button = ToggleButton::builder()
+        .icon_name("media-playlist-shuffle-symbolic")
+        .css_classes(["flat"])
+        .tooltip_text("Shuffle")
+        .active(state.playback.queue().is_shuffled())
+        .build();
+    button.update_property(&[PropertyLabel("Shuffle")]);
+    let state = Arc::clone(state);
+    button.connect_toggled(move |btn| {
+        if let Err(e) = state.playback.set_shuffle(btn.is_active()) {
+            error!(error = %e, "Failed to set shuffle");
+        }
+    });
+    button
+}
+
+/// Build the button cycling through the repeat modes.
+fn build_repeat_button(state: &Arc<AppState>) -> Button {
+    let button = Button::builder().css_classes(["flat"]).build();
+    show_repeat_mode(&button, state.playback.queue().repeat_mode());
+    let state_click = Arc::clone(state);
+    button.connect_clicked(move |btn| {
+        let mode = state_click.playback.queue().repeat_mode().cycle();
+        match state_click.playback.set_repeat_mode(mode) {
+            Ok(()) => show_repeat_mode(btn, mode),
+            Err(e) => error!(error = %e, "Failed to set repeat mode"),
+        }
+    });
+    spawn_future_local(follow_repeat_mode(button.downgrade(), Arc::clone(state)));
+    button
+}
+
+/// Keep the repeat button in step with modes set elsewhere, such as by
+/// playing the whole library, until it is gone.
+async fn follow_repeat_mode(button: WeakRef<Button>, state: Arc<AppState>) {
+    let events = state.playback.subscribe();
+    while let Ok(event) = events.recv().await {
+        let Some(button) = button.upgrade() else {
+            break;
+        };
+        if let RepeatModeChanged { mode } = event {
+            show_repeat_mode(&button, mode);
+        }
+    }
+}
+
+/// Update the repeat button icon, tooltip, and highlight for `mode`.
+fn show_repeat_mode(button: &Button, mode: RepeatMode) {
+    button.set_icon_name(mode.icon_name());
+    button.set_tooltip_text(Some(mode.label()));
+    button.update_property(&[PropertyLabel(mode.label())]);
+    if mode == Off {
+        button.remove_css_class("accent");
+    } else {
+        button.add_css_class("accent");
+    }
+}
+
 /// Seek to the current scale position, clamped to track duration.
 fn seek_to_scale_value(playback: &PlaybackEngine, scale: &Scale) {
     let s = playback.state();
@@ -128,6 +254,7 @@ pub fn build_seek_section(state: &Arc<AppState>) -> (Box, Scale, Label, Label) {
     seek_scale.set_hexpand(true);
     seek_scale.set_can_focus(true);
     seek_scale.set_tooltip_text(Some("Seek through the track"));
+    seek_scale.update_property(&[PropertyLabel("Playback position")]);
 
     let is_seeking = Arc::clone(&state.is_seeking);
     let gesture = GestureClick::new();
@@ -173,6 +300,62 @@ pub fn build_seek_section(state: &Arc<AppState>) -> (Box, Scale, Label, Label) {
     (seek_box, seek_scale, current_time, total_time)
 }
 
+/// Move `scale` to `elapsed` seconds of a `duration`-second track.
+///
+/// The position is also given as text, since the percentage the slider
+/// holds means little when read out.
+pub fn show_seek_position(scale: &Scale, elapsed: f64, duration: f64) {
+    let fraction = if duration > 0.0 {
+        elapsed / duration
+    } else {
+        0.0
+    };
+    scale.set_value(fraction * 100.0);
+    scale.update_property(&[ValueText(&format!(
+        "{} of {}",
+        format_time(elapsed),
+        format_time(duration)
+    ))]);
+}
+
+/// Toggle `label` between the track duration and the time remaining when
+/// clicked, remembering the choice in the settings.
+///
+/// Returns the shared flag telling position updates which one to show.
+#[must_use]
+pub fn connect_end_time_toggle(state: &Arc<AppState>, label: &Label) -> Rc<Cell<bool>> {
+    let show_remaining = Rc::new(Cell::new(state.storage.get_show_remaining_time()));
+    label.set_tooltip_text(Some("Click to switch between duration and time remaining"));
+
+    let gesture = GestureClick::new();
+    let state = Arc::clone(state);
+    let flag = Rc::clone(&show_remaining);
+    let label_click = label.clone();
+    gesture.connect_released(move |_, _, _, _| {
+        let remaining = !flag.get();
+        flag.set(remaining);
+        let s = state.playback.state();
+        label_click.set_label(&format_end_time(
+            s.elapsed_seconds,
+            s.duration_seconds,
+            remaining,
+        ));
+        spawn_future_local(persist_show_remaining(
+            Arc::clone(&state.storage),
+            remaining,
+        ));
+    });
+    label.add_controller(gesture);
+    show_remaining
+}
+
+/// Persist the duration/remaining choice to the storage backend.
+async fn persist_show_remaining(storage: Arc<SqliteStorage>, remaining: bool) {
+    if let Err(e) = storage.set_show_remaining_time(remaining).await {
+        warn!(error = %e, "Failed to persist time display preference");
+    }
+}
+
 /// Persist a volume change to the storage backend.
 async fn persist_volume(storage: Arc<SqliteStorage>, volume: f64) {
     if let Err(e) = storage.set_volume(volume).await {
@@ -188,26 +371,16 @@ async fn persist_volume(storage: Arc<SqliteStorage>, volume: f64) {
 pub fn build_volume_control(state: &Arc<AppState>) -> (Box, Button, Scale) {
     let vol_box = Box::builder().orientation(Horizontal).spacing(6).build();
 
-    let mute_button = Button::builder()
-        .icon_name("audio-volume-high-symbolic")
-        .css_classes(["flat"])
-        .tooltip_text("Mute or unmute")
-        .build();
-    mute_button.update_property(&[PropertyLabel("Mute or unmute")]);
+    let mute_button = Button::builder().css_classes(["flat"]).build();
+    show_muted(&mute_button, state.playback.state().muted == Muted);
     let state_mute = Arc::clone(state);
-    let mute_btn_ref = mute_button.clone();
-    mute_button.connect_clicked(move |_| {
+    mute_button.connect_clicked(move |btn| {
         let current = state_mute.playback.state();
         let new_muted = current.muted == Unmuted;
         if let Err(e) = state_mute.playback.set_muted(new_muted) {
             error!(error = %e, "Failed to set mute");
         }
-        let icon = if new_muted {
-            "audio-volume-muted-symbolic"
-        } else {
-            "audio-volume-high-symbolic"
-        };
-        mute_btn_ref.set_icon_name(icon);
+        show_muted(btn, new_muted);
     });
     vol_box.append(&mute_button);
 
@@ -217,10 +390,13 @@ pub fn build_volume_control(state: &Arc<AppState>) -> (Box, Button, Scale) {
     volume_scale.set_draw_value(false);
     volume_scale.set_hexpand(true);
     volume_scale.set_can_focus(true);
+    volume_scale.update_property(&[PropertyLabel("Volume")]);
+    show_volume_text(&volume_scale);
     let state_vol = Arc::clone(state);
     let vol_ref = volume_scale.clone();
     volume_scale.connect_value_changed(move |_| {
         let value = vol_ref.value();
+        show_volume_text(&vol_ref);
         if let Err(e) = state_vol.playback.set_volume(value) {
             error!(error = %e, "Failed to set volume");
         }
@@ -229,11 +405,14 @@ pub fn build_volume_control(state: &Arc<AppState>) -> (Box, Button, Scale) {
     vol_box.append(&volume_scale);
 
     let initial_mode = state.playback.state().output_mode;
-    let mode_button = Button::builder()
-        .icon_name(initial_mode.icon_name())
-        .css_classes(["flat", "caption"])
-        .tooltip_text(mode_button_tooltip(initial_mode))
-        .build();
+    let mode_button = Button::builder().css_classes(["flat", "caption"]).build();
+    show_output_mode(&mode_button, &state.playback.signal_path_report());
+    let state_tooltip = Arc::clone(state);
+    mode_button.connect_query_tooltip(move |_, _, _, _, tooltip| {
+        let report = state_tooltip.playback.signal_path_report();
+        tooltip.set_text(Some(&output_mode_tooltip(&report)));
+        true
+    });
     let state_mode = Arc::clone(state);
     let scale_for_click = volume_scale.clone();
     mode_button.connect_clicked(move |btn| {
@@ -245,8 +424,7 @@ pub fn build_volume_control(state: &Arc<AppState>) -> (Box, Button, Scale) {
         if let Err(e) = state_mode.playback.set_output_mode(new_mode) {
             error!(error = %e, "Failed to toggle output mode");
         }
-        btn.set_icon_name(new_mode.icon_name());
-        btn.set_tooltip_text(Some(mode_button_tooltip(new_mode)));
+        show_output_mode(btn, &state_mode.playback.signal_path_report());
         update_volume_scale_visual(&scale_for_click, new_mode);
     });
     vol_box.append(&mode_button);
@@ -256,6 +434,24 @@ pub fn build_volume_control(state: &Arc<AppState>) -> (Box, Button, Scale) {
     (vol_box, mode_button, volume_scale)
 }
 
+/// Show the mute button as unmute while `muted`, and as mute otherwise.
+fn show_muted(button: &Button, muted: bool) {
+    let (icon, label) = if muted {
+        ("audio-volume-muted-symbolic", "Unmute")
+    } else {
+        ("audio-volume-high-symbolic", "Mute")
+    };
+    button.set_icon_name(icon);
+    button.set_tooltip_text(Some(label));
+    button.update_property(&[PropertyLabel(label)]);
+}
+
+/// Give the volume of `scale` as a percentage for screen readers.
+fn show_volume_text(scale: &Scale) {
+    let percent = (scale.value() * 100.0).round();
+    scale.update_property(&[ValueText(&format!("{percent:.0}%"))]);
+}
+
 /// Update the volume scale's visual state based on the output mode.
 ///
 /// In bit-perfect mode the scale is greyed out and interaction is
@@ -276,33 +472,79 @@ pub fn update_volume_scale_visual(scale: &Scale, mode: OutputMode) {
     }
 }
 
-/// Tooltip text for the mode toggle button.
-#[must_use]
-pub fn mode_button_tooltip(mode: OutputMode) -> &'static str {
-    match mode {
+/// Show the output mode on the mode toggle button.
+///
+/// The bit-perfect icon is only lit when `report` finds nothing touching
+/// the samples; a resampled track or changed speed shows the resampled icon
+/// even in bit-perfect mode.
+pub fn show_output_mode(button: &Button, report: &SignalPathReport) {
+    let lit = if report.is_bit_perfect() {
+        BitPerfect
+    } else {
+        Resampled
+    };
+    button.set_icon_name(lit.icon_name());
+    button.set_tooltip_text(Some(&output_mode_tooltip(report)));
+    let mode = match report.output_mode {
+        BitPerfect => "Bit-perfect",
+        Resampled => "Resampled",
+    };
+    button.update_property(&[PropertyLabel(&format!("Output mode: {mode}"))]);
+}
+
+/// Tooltip text for the mode toggle button: the chosen mode followed by
+/// the active processing stages.
+fn output_mode_tooltip(report: &SignalPathReport) -> String {
+    let mode = match report.output_mode {
         BitPerfect => {
             "Bit-Perfect mode \u{2014} no software volume scaling, hardware volume via ALSA mixer"
         }
         Resampled => "Resampled mode \u{2014} software volume scaling, sample rate conversion",
+    };
+    let mut tooltip = format!("{mode}\n{}", report.summary());
+    if report.gapless_fell_back() {
+        tooltip.push_str("\nGapless fell back to a hard cut before this track");
     }
+    tooltip
 }
 
-/// Build the queue section with label and queue view.
+/// Build the queue section with a header toggle and a slide-out queue view.
 #[must_use]
 pub fn build_queue_section(state: &Arc<AppState>) -> Box {
     let section = Box::builder().orientation(Vertical).spacing(4).build();
+    let header = Box::builder().orientation(Horizontal).spacing(6).build();
 
     let queue_label = Label::builder()
         .label("Queue")
         .css_classes(["heading", "dim-label"])
         .halign(Start)
+        .hexpand(true)
         .build();
     queue_label.update_property(&[PropertyLabel("Playback queue section")]);
-    section.append(&queue_label);
+    header.append(&queue_label);
+
+    let toggle = ToggleButton::builder()
+        .icon_name("view-list-bullet-symbolic")
+        .css_classes(["flat"])
+        .tooltip_text("Show or hide the queue")
+        .active(true)
+        .build();
+    toggle.update_property(&[PropertyLabel("Show or hide the queue")]);
+    header.append(&toggle);
+    section.append(&header);
 
     let queue = state.playback.queue().clone();
-    let queue_view = build_queue_view(state, &queue);
-    section.append(&queue_view);
+    let revealer = Revealer::builder()
+        .transition_type(SlideDown)
+        .transition_duration(200)
+        .reveal_child(true)
+        .child(&build_queue_view(state, &queue))
+        .build();
+    let revealer_toggle = revealer.clone();
+    toggle.connect_toggled(move |btn| {
+        revealer_toggle.set_reveal_child(btn.is_active());
+    });
+    section.append(&revealer);
 
     section
 }
@@ -0,0 +1,114 @@
+//! Navigation from the player to the album or artist of the playing track.
+//!
+//! The album and artist labels of the player panel open their detail pages,
+//! and a shortcut opens the playing album, where the playing track is
+//! highlighted.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        glib::spawn_future_local,
+        gtk::{GestureClick, Label},
+        prelude::{GestureSingleExt, WidgetExt},
+    },
+    tracing::{info, warn},
+};
+
+use crate::{
+    app::{
+        AppState,
+        NavigationEvent::{self, AlbumDetail, ArtistDetail},
+    },
+    playback::control::PlaybackController,
+    storage::Storage,
+};
+
+/// Detail page opened for the playing track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayingTarget {
+    /// The album of the playing track.
+    Album,
+    /// The artist of the playing track, or its album artist.
+    Artist,
+}
+
+/// Open the detail page of `target` when `label` is clicked.
+///
+/// # Arguments
+///
+/// * `label` - Player label showing the album or artist
+/// * `state` - Application state
+/// * `target` - Detail page the label opens
+pub fn link_to_playing(label: &Label, state: &Arc<AppState>, target: PlayingTarget) {
+    label.set_cursor_from_name(Some("pointer"));
+    label.set_tooltip_text(Some(match target {
+        PlayingTarget::Album => "Go to album",
+        PlayingTarget::Artist => "Go to artist",
+    }));
+
+    let click = GestureClick::new();
+    click.set_button(1);
+    let state = Arc::clone(state);
+    click.connect_released(move |_, _, _, _| {
+        spawn_future_local(go_to_playing(Arc::clone(&state), target));
+    });
+    label.add_controller(click);
+}
+
+/// Navigate to the detail page of `target` for the playing track.
+///
+/// Shows a toast when nothing is playing or the track has no album or
+/// artist.
+pub async fn go_to_playing(state: Arc<AppState>, target: PlayingTarget) {
+    let Some(track_id) = state.playback.state().current_track_id else {
+        state.send_toast("Nothing is playing".to_string()).await;
+        return;
+    };
+    match playing_destination(&state, track_id, target).await {
+        Some(event) => {
+            info!(track_id, target = ?target, "Going to playing track");
+            state.send_navigation_event(event).await;
+        }
+        None => {
+            let message = match target {
+                PlayingTarget::Album => "The playing track has no album",
+                PlayingTarget::Artist => "The playing track has no artist",
+            };
+            state.send_toast(message.to_string()).await;
+        }
+    }
+}
+
+/// Navigation event opening `target` for a track, if it has one.
+///
+/// The artist falls back to the album artist when the track has none.
+async fn playing_destination(
+    state: &AppState,
+    track_id: i64,
+    target: PlayingTarget,
+) -> Option<NavigationEvent> {
+    let track = match state.storage.get_track(track_id).await {
+        Ok(track) => track?,
+        Err(e) => {
+            warn!(error = %e, track_id, "Failed to load playing track");
+            return None;
+        }
+    };
+    let album_id = track.audio.album_id;
+    match target {
+        PlayingTarget::Album => album_id.map(AlbumDetail),
+        PlayingTarget::Artist => {
+            if let Some(artist_id) = track.audio.artist_id {
+                return Some(ArtistDetail(artist_id));
+            }
+            match state.storage.get_album(album_id?).await {
+                Ok(album) => album.map(|a| ArtistDetail(a.artist_id)),
+                Err(e) => {
+                    warn!(error = %e, track_id, "Failed to load playing album");
+                    None
+                }
+            }
+        }
+    }
+}
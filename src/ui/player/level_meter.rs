@@ -0,0 +1,158 @@
+//! Peak and RMS level meters of the player panel.
+//!
+//! The meters sample the levels published by the audio callback about 30
+//! times a second. Each channel shows its RMS as a bar and its peak as a
+//! tick that falls back slowly. The meters are hidden while metering is
+//! off or the output is bit-perfect. The View > Display row turning
+//! metering on and off is built here as well.
+
+use std::{cell::RefCell, rc::Rc, sync::Arc, time::Duration};
+
+use {
+    libadwaita::{
+        SwitchRow,
+        glib::{ControlFlow, spawn_future_local, timeout_add_local},
+        gtk::{DrawingArea, accessible::Property::Label as PropertyLabel, cairo::Context},
+        prelude::{
+            AccessibleExtManual, ActionRowExt, DrawingAreaExtManual, ObjectExt, PreferencesRowExt,
+            WidgetExt,
+        },
+    },
+    tracing::{error, warn},
+};
+
+use crate::{
+    app::AppState,
+    playback::level::{Levels, meter_fraction},
+};
+
+/// Interval between two samples of the levels.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(33);
+
+/// Height of one channel's bar in pixels.
+const BAR_HEIGHT: i32 = 5;
+
+/// Space between the two bars in pixels.
+const BAR_GAP: i32 = 3;
+
+/// Meter fraction a held peak falls per sample.
+const PEAK_FALL: f64 = 0.01;
+
+/// Opacity of the unlit part of a bar.
+const TRACK_ALPHA: f64 = 0.15;
+
+/// Positions of the bars and peak ticks, as fractions of the meter width.
+#[derive(Debug, Default)]
+struct MeterState {
+    /// RMS of the left and right channels.
+    rms: [f64; 2],
+    /// Held peak of the left and right channels.
+    peak: [f64; 2],
+}
+
+impl MeterState {
+    /// Show freshly sampled levels, letting held peaks fall back.
+    fn update(&mut self, levels: &Levels) {
+        self.rms = levels.rms.map(meter_fraction);
+        for (held, peak) in self.peak.iter_mut().zip(levels.peak) {
+            *held = meter_fraction(peak).max(*held - PEAK_FALL);
+        }
+    }
+}
+
+/// Build the level meters, hidden while metering is off.
+#[must_use]
+pub fn build_level_meter(state: &Arc<AppState>) -> DrawingArea {
+    let area = DrawingArea::builder()
+        .content_height(2 * BAR_HEIGHT + BAR_GAP)
+        .hexpand(true)
+        .visible(false)
+        .tooltip_text(
+            "Peak and RMS levels after decoding and processing, before the device. \
+             Metering only reads the samples and is skipped for bit-perfect output.",
+        )
+        .build();
+    area.update_property(&[PropertyLabel("Audio level meter")]);
+
+    let meter = Rc::new(RefCell::new(MeterState::default()));
+    let draw_meter = Rc::clone(&meter);
+    area.set_draw_func(move |area, cr, width, _| {
+        draw_meter_bars(area, cr, f64::from(width), &draw_meter.borrow());
+    });
+
+    let playback = Arc::clone(&state.playback);
+    let weak_area = area.downgrade();
+    timeout_add_local(SAMPLE_INTERVAL, move || {
+        let Some(area) = weak_area.upgrade() else {
+            return ControlFlow::Break;
+        };
+        let levels = playback.take_levels();
+        area.set_visible(levels.is_some());
+        if let Some(levels) = levels {
+            meter.borrow_mut().update(&levels);
+            area.queue_draw();
+        }
+        ControlFlow::Continue
+    });
+    area
+}
+
+/// Build the row showing or hiding the level meters of the player.
+pub fn build_level_meter_row(state: &Arc<AppState>) -> SwitchRow {
+    let meter_row = SwitchRow::new();
+    meter_row.set_title("Level Meter");
+    meter_row.set_subtitle(
+        "Show peak and RMS levels in the player. Levels are read after decoding and \
+         processing, before the device, without changing the samples. Bit-perfect output \
+         is not metered.",
+    );
+    meter_row.set_active(state.storage.get_show_level_meter());
+
+    let state_meter = Arc::clone(state);
+    meter_row.connect_active_notify(move |row| {
+        let enabled = row.is_active();
+        state_meter.playback.set_level_meter_enabled(enabled);
+        spawn_future_local(save_level_meter_setting(Arc::clone(&state_meter), enabled));
+    });
+
+    meter_row
+}
+
+/// Persist the level meter preference, logging on failure.
+async fn save_level_meter_setting(state: Arc<AppState>, enabled: bool) {
+    if let Err(e) = state.storage.set_show_level_meter(enabled).await {
+        error!(error = %e, "Failed to save level meter preference");
+    }
+}
+
+/// Draw the bar and peak tick of both channels.
+fn draw_meter_bars(area: &DrawingArea, cr: &Context, width: f64, meter: &MeterState) {
+    let color = area.color();
+    let (red, green, blue) = (
+        f64::from(color.red()),
+        f64::from(color.green()),
+        f64::from(color.blue()),
+    );
+    let height = f64::from(BAR_HEIGHT);
+    for (y, (rms, peak)) in [0, BAR_HEIGHT + BAR_GAP]
+        .map(f64::from)
+        .into_iter()
+        .zip(meter.rms.iter().zip(meter.peak))
+    {
+        cr.set_source_rgba(red, green, blue, TRACK_ALPHA);
+        cr.rectangle(0.0, y, width, height);
+        fill(cr);
+
+        cr.set_source_rgba(red, green, blue, f64::from(color.alpha()));
+        cr.rectangle(0.0, y, rms * width, height);
+        cr.rectangle(peak.mul_add(width, -2.0).max(0.0), y, 2.0, height);
+        fill(cr);
+    }
+}
+
+/// Fill the current path, logging on failure.
+fn fill(cr: &Context) {
+    if let Err(e) = cr.fill() {
+        warn!(error = %e, "Failed to draw level meter");
+    }
+}
@@ -0,0 +1,312 @@
+//! Lyrics section of the player panel.
+//!
+//! Synced lyrics highlight the line being sung and keep it in view; plain
+//! lyrics scroll along with playback progress. The section hides itself
+//! for tracks without lyrics.
+
+use std::{cell::RefCell, path::Path, rc::Rc, sync::Arc};
+
+use {
+    async_channel::{Sender, unbounded},
+    libadwaita::{
+        glib::MainContext,
+        gtk::{
+            Align::Start,
+            Box, Label,
+            Orientation::{Horizontal, Vertical},
+            PolicyType::{Automatic, Never},
+            Revealer,
+            RevealerTransitionType::SlideDown,
+            ScrolledWindow, ToggleButton,
+            accessible::Property::Label as PropertyLabel,
+            graphene::Point,
+        },
+        prelude::{AccessibleExtManual, AdjustmentExt, BoxExt, ToggleButtonExt, WidgetExt},
+    },
+    tokio::{spawn, task::spawn_blocking},
+    tracing::{error, warn},
+};
+
+use crate::{
+    app::AppState,
+    library::{
+        cue::split_cue_path,
+        lyrics::{Lyrics, load_lyrics},
+    },
+    playback::{
+        control::PlaybackController,
+        engine::{
+            PlaybackEngine,
+            PlaybackEvent::{self, PositionTick, Seeked, Stopped, TrackStarted},
+        },
+    },
+    storage::{Storage, database::SqliteStorage},
+};
+
+/// Height of the lyrics viewport in pixels.
+const LYRICS_HEIGHT: i32 = 240;
+
+/// Lyrics of the current track and the labels showing them.
+#[derive(Default)]
+struct LyricsState {
+    /// Track the lyrics belong to.
+    track_id: Option<i64>,
+    /// Parsed lyrics, once loaded.
+    lyrics: Option<Lyrics>,
+    /// One label per lyrics line.
+    labels: Vec<Label>,
+    /// Index of the highlighted line.
+    current: Option<usize>,
+}
+
+/// Widgets of the lyrics section.
+#[derive(Clone)]
+struct LyricsView {
+    /// Whole section, hidden when there are no lyrics.
+    section: Box,
+    /// Viewport scrolled along with playback.
+    scroll: ScrolledWindow,
+    /// Container of the line labels.
+    lines: Box,
+    /// Lyrics currently shown.
+    state: Rc<RefCell<LyricsState>>,
+}
+
+impl LyricsView {
+    /// Forget the shown lyrics and hide the section.
+    fn clear(&self, track_id: Option<i64>) {
+        while let Some(child) = self.lines.first_child() {
+            self.lines.remove(&child);
+        }
+        *self.state.borrow_mut() = LyricsState {
+            track_id,
+            ..LyricsState::default()
+        };
+        self.section.set_visible(false);
+    }
+
+    /// Show lyrics loaded for `track_id` if it is still the current track.
+    fn show(&self, track_id: i64, lyrics: Option<Lyrics>) {
+        let mut state = self.state.borrow_mut();
+        if state.track_id != Some(track_id) {
+            return;
+        }
+        let Some(lyrics) = lyrics else {
+            return;
+        };
+        state.labels = lyrics
+            .texts()
+            .into_iter()
+            .map(|text| self.append_line(text))
+            .collect();
+        state.lyrics = Some(lyrics);
+        drop(state);
+        self.scroll.vadjustment().set_value(0.0);
+        self.section.set_visible(true);
+    }
+
+    /// Append a dimmed label for one line.
+    fn append_line(&self, text: &str) -> Label {
+        let label = Label::builder()
+            .label(text)
+            .wrap(true)
+            .xalign(0.0)
+            .css_classes(["dim-label"])
+            .build();
+        self.lines.append(&label);
+        label
+    }
+
+    /// Follow the playback position.
+    fn update_position(&self, elapsed: f64, duration: f64) {
+        let mut state = self.state.borrow_mut();
+        let Some(lyrics) = &state.lyrics else {
+            return;
+        };
+        let line = lyrics.line_at(elapsed);
+        if matches!(lyrics, Lyrics::Plain(_)) {
+            drop(state);
+            self.scroll_by_progress(elapsed, duration);
+            return;
+        }
+        if line == state.current {
+            return;
+        }
+        if let Some(previous) = state.current.and_then(|i| state.labels.get(i)) {
+            set_highlight(previous, false);
+        }
+        if let Some(label) = line.and_then(|i| state.labels.get(i)) {
+            set_highlight(label, true);
+            self.center_on(label);
+        }
+        state.current = line;
+    }
+
+    /// Scroll so that `label` sits in the middle of the viewport.
+    fn center_on(&self, label: &Label) {
+        let Some(point) = label.compute_point(&self.lines, &Point::new(0.0, 0.0)) else {
+            return;
+        };
+        let adjustment = self.scroll.vadjustment();
+        let label_center = f64::from(point.y()) + f64::from(label.height()) / 2.0;
+        adjustment.set_value(label_center - adjustment.page_size() / 2.0);
+    }
+
+    /// Scroll plain lyrics proportionally to the track progress.
+    fn scroll_by_progress(&self, elapsed: f64, duration: f64) {
+        if duration <= 0.0 {
+            return;
+        }
+        let adjustment = self.scroll.vadjustment();
+        let range = (adjustment.upper() - adjustment.page_size()).max(0.0);
+        adjustment.set_value(range * (elapsed / duration).clamp(0.0, 1.0));
+    }
+}
+
+/// Build the lyrics section with a header toggle and a scrolling viewport.
+///
+/// The section stays hidden until lyrics for the playing track are found.
+#[must_use]
+pub fn build_lyrics_section(state: &Arc<AppState>) -> Box {
+    let section = Box::builder()
+        .orientation(Vertical)
+        .spacing(4)
+        .visible(false)
+        .build();
+    let header = Box::builder().orientation(Horizontal).spacing(6).build();
+
+    let lyrics_label = Label::builder()
+        .label("Lyrics")
+        .css_classes(["heading", "dim-label"])
+        .halign(Start)
+        .hexpand(true)
+        .build();
+    lyrics_label.update_property(&[PropertyLabel("Lyrics section")]);
+    header.append(&lyrics_label);
+
+    let toggle = ToggleButton::builder()
+        .icon_name("format-justify-left-symbolic")
+        .css_classes(["flat"])
+        .tooltip_text("Show or hide the lyrics")
+        .active(true)
+        .build();
+    toggle.update_property(&[PropertyLabel("Show or hide the lyrics")]);
+    header.append(&toggle);
+    section.append(&header);
+
+    let lines = Box::builder().orientation(Vertical).spacing(6).build();
+    let scroll = ScrolledWindow::builder()
+        .hscrollbar_policy(Never)
+        .vscrollbar_policy(Automatic)
+        .min_content_height(LYRICS_HEIGHT)
+        .max_content_height(LYRICS_HEIGHT)
+        .child(&lines)
+        .build();
+
+    let revealer = Revealer::builder()
+        .transition_type(SlideDown)
+        .transition_duration(200)
+        .reveal_child(true)
+        .child(&scroll)
+        .build();
+    let revealer_toggle = revealer.clone();
+    toggle.connect_toggled(move |btn| {
+        revealer_toggle.set_reveal_child(btn.is_active());
+    });
+    section.append(&revealer);
+
+    let view = LyricsView {
+        section: section.clone(),
+        scroll,
+        lines,
+        state: Rc::new(RefCell::new(LyricsState::default())),
+    };
+    spawn_lyrics_listeners(state, view);
+
+    section
+}
+
+/// Listen for playback events and loaded lyrics.
+fn spawn_lyrics_listeners(state: &Arc<AppState>, view: LyricsView) {
+    let (lyrics_tx, lyrics_rx) = unbounded::<(i64, Option<Lyrics>)>();
+
+    let ev_rx = state.playback.subscribe();
+    let ev_view = view.clone();
+    let playback = Arc::clone(&state.playback);
+    let storage = Arc::clone(&state.storage);
+    MainContext::default().spawn_local(async move {
+        while let Ok(event) = ev_rx.recv().await {
+            on_lyrics_event(&event, &ev_view, &playback, &storage, &lyrics_tx);
+        }
+    });
+
+    MainContext::default().spawn_local(async move {
+        while let Ok((track_id, lyrics)) = lyrics_rx.recv().await {
+            view.show(track_id, lyrics);
+        }
+    });
+}
+
+/// Handle a single playback event for the lyrics section.
+fn on_lyrics_event(
+    event: &PlaybackEvent,
+    view: &LyricsView,
+    playback: &PlaybackEngine,
+    storage: &Arc<SqliteStorage>,
+    lyrics_tx: &Sender<(i64, Option<Lyrics>)>,
+) {
+    match event {
+        TrackStarted { track_id } => {
+            view.clear(Some(*track_id));
+            spawn_load_lyrics(Arc::clone(storage), *track_id, lyrics_tx.clone());
+        }
+        Stopped => view.clear(None),
+        PositionTick {
+            elapsed_seconds,
+            duration_seconds,
+        } => view.update_position(*elapsed_seconds, *duration_seconds),
+        Seeked { position_seconds } => {
+            view.update_position(*position_seconds, playback.state().duration_seconds);
+        }
+        _ => {}
+    }
+}
+
+/// Look up the lyrics of a track in the background and send them back.
+fn spawn_load_lyrics(
+    storage: Arc<SqliteStorage>,
+    track_id: i64,
+    tx: Sender<(i64, Option<Lyrics>)>,
+) {
+    spawn(async move {
+        let path = match storage.get_track(track_id).await {
+            Ok(Some(track)) => split_cue_path(Path::new(&track.audio.file_path)).0,
+            Ok(None) => return,
+            Err(e) => {
+                warn!(error = %e, track_id, "Failed to load track for lyrics");
+                return;
+            }
+        };
+        let lyrics = match spawn_blocking(move || load_lyrics(&path)).await {
+            Ok(lyrics) => lyrics,
+            Err(e) => {
+                warn!(error = %e, track_id, "Lyrics lookup panicked");
+                None
+            }
+        };
+        if let Err(e) = tx.send((track_id, lyrics)).await {
+            error!(error = %e, "Failed to send lyrics");
+        }
+    });
+}
+
+/// Emphasise or dim a lyrics line.
+fn set_highlight(label: &Label, active: bool) {
+    if active {
+        label.remove_css_class("dim-label");
+        label.add_css_class("heading");
+    } else {
+        label.remove_css_class("heading");
+        label.add_css_class("dim-label");
+    }
+}
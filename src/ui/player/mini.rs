@@ -0,0 +1,310 @@
+//! Compact player window shown in place of the main window.
+//!
+//! The mini player holds only the cover, title, artist and transport
+//! buttons of the playing track. It follows the same `PlaybackEvent` stream
+//! as the player panel, so both stay in step with the engine. Closing it, or
+//! pressing its expand button, brings the main window back as it was left,
+//! and its size is remembered for the next time it opens.
+//!
+//! GTK 4 leaves window placement and stacking to the compositor, so the
+//! position is not restored and the window cannot be kept above others.
+
+use std::sync::Arc;
+
+use {
+    async_channel::{Receiver, Sender, unbounded},
+    libadwaita::{
+        ApplicationWindow, HeaderBar, ToolbarView,
+        gdk::MemoryTexture,
+        glib::{Propagation::Proceed, spawn_future_local},
+        gtk::{
+            self,
+            Align::{Center, Start},
+            Box, Button,
+            ContentFit::Cover,
+            Label,
+            Orientation::{Horizontal, Vertical},
+            Picture,
+            accessible::Property::Label as PropertyLabel,
+            pango::EllipsizeMode::End,
+        },
+        prelude::{
+            AccessibleExtManual, AdwApplicationWindowExt, BoxExt, ButtonExt, GtkWindowExt,
+            ObjectExt, WidgetExt,
+        },
+    },
+    tracing::{info, warn},
+};
+
+use crate::{
+    app::AppState,
+    playback::{
+        control::PlaybackController,
+        engine::{
+            PlaybackEvent::{self, Paused, Resumed, Stopped, TrackStarted},
+            PlaybackStatus::Playing,
+        },
+    },
+    storage::database::SqliteStorage,
+    ui::{
+        CoverTicket, DecodedCover,
+        player::controls::{build_playback_controls, show_play_state},
+        raw_to_texture,
+        shortcuts::install_shortcuts,
+    },
+};
+
+/// Edge length of the cover in pixels.
+const COVER_SIZE: i32 = 72;
+
+/// Widget name marking the mini player window.
+const MINI_PLAYER_NAME: &str = "mini-player";
+
+/// Widgets refreshed as playback changes.
+#[derive(Clone)]
+struct MiniPlayerWidgets {
+    /// Album cover of the playing track.
+    cover: Picture,
+    /// Cancels cover decodes of tracks that are no longer playing.
+    cover_ticket: CoverTicket,
+    /// Track title.
+    title: Label,
+    /// Artist name.
+    artist: Label,
+    /// Play/pause transport button.
+    play_button: Button,
+}
+
+/// Switch between the main window and the mini player.
+///
+/// # Arguments
+///
+/// * `window` - Window the toggle came from; the mini player closes itself
+///   and hands back to the main window, any other window is replaced by it
+/// * `state` - Application state
+pub fn toggle_mini_player(window: &gtk::Window, state: &Arc<AppState>) {
+    if window.widget_name() == MINI_PLAYER_NAME {
+        window.close();
+    } else {
+        open_mini_player(window, state);
+    }
+}
+
+/// Hide `main` and show the mini player until it is closed again.
+fn open_mini_player(main: &gtk::Window, state: &Arc<AppState>) {
+    let (width, height) = state.storage.get_mini_player_size();
+    let window = ApplicationWindow::builder()
+        .title("Oxhidifi")
+        .name(MINI_PLAYER_NAME)
+        .default_width(width)
+        .default_height(height)
+        .build();
+    window.set_application(main.application().as_ref());
+
+    let expand_button = Button::builder()
+        .icon_name("view-restore-symbolic")
+        .tooltip_text("Show library")
+        .css_classes(["flat"])
+        .build();
+    expand_button.update_property(&[PropertyLabel("Show library")]);
+    let window_ref = window.downgrade();
+    expand_button.connect_clicked(move |_| {
+        if let Some(window) = window_ref.upgrade() {
+            window.close();
+        }
+    });
+    let header = HeaderBar::builder()
+        .show_title(false)
+        .css_classes(["flat"])
+        .build();
+    header.pack_start(&expand_button);
+
+    let (content, widgets) = build_mini_content(state);
+    let toolbar = ToolbarView::new();
+    toolbar.add_top_bar(&header);
+    toolbar.set_content(Some(&content));
+    window.set_content(Some(&toolbar));
+
+    let events = state.playback.subscribe();
+    spawn_future_local(follow_playback(events.clone(), Arc::clone(state), widgets));
+
+    let storage = Arc::clone(&state.storage);
+    let library_window = main.clone();
+    window.connect_close_request(move |window| {
+        events.close();
+        let (width, height) = window.default_size();
+        spawn_future_local(save_size(Arc::clone(&storage), width, height));
+        info!("Leaving mini player");
+        library_window.present();
+        Proceed
+    });
+
+    install_shortcuts(&window, state);
+    info!("Entering mini player");
+    main.set_visible(false);
+    window.present();
+}
+
+/// Build the cover, track labels and transport buttons.
+fn build_mini_content(state: &Arc<AppState>) -> (Box, MiniPlayerWidgets) {
+    let cover = Picture::builder()
+        .content_fit(Cover)
+        .can_shrink(true)
+        .valign(Center)
+        .width_request(COVER_SIZE)
+        .height_request(COVER_SIZE)
+        .css_classes(["album-cover"])
+        .build();
+    cover.update_property(&[PropertyLabel("Album artwork")]);
+
+    let title = Label::builder()
+        .label("No track playing")
+        .css_classes(["heading"])
+        .ellipsize(End)
+        .halign(Start)
+        .build();
+    title.update_property(&[PropertyLabel("Track title")]);
+    let artist = Label::builder()
+        .css_classes(["dim-label", "caption"])
+        .ellipsize(End)
+        .halign(Start)
+        .build();
+    artist.update_property(&[PropertyLabel("Artist name")]);
+
+    let (controls, play_button) = build_playback_controls(state);
+    let details = Box::builder()
+        .orientation(Vertical)
+        .spacing(6)
+        .hexpand(true)
+        .valign(Center)
+        .build();
+    details.append(&title);
+    details.append(&artist);
+    details.append(&controls);
+
+    let content = Box::builder()
+        .orientation(Horizontal)
+        .spacing(12)
+        .margin_start(12)
+        .margin_end(12)
+        .margin_bottom(12)
+        .build();
+    content.append(&cover);
+    content.append(&details);
+
+    let widgets = MiniPlayerWidgets {
+        cover,
+        cover_ticket: CoverTicket::default(),
+        title,
+        artist,
+        play_button,
+    };
+    (content, widgets)
+}
+
+/// Show the current track, then keep the widgets in step with `events`
+/// until the window closes the channel.
+async fn follow_playback(
+    events: Receiver<PlaybackEvent>,
+    state: Arc<AppState>,
+    widgets: MiniPlayerWidgets,
+) {
+    let (cover_tx, cover_rx) = unbounded::<(i64, DecodedCover)>();
+    spawn_future_local(receive_covers(
+        cover_rx,
+        Arc::clone(&state),
+        widgets.clone(),
+    ));
+
+    let current = state.playback.state();
+    show_playing(&widgets, current.status == Playing);
+    if let Some(track_id) = current.current_track_id {
+        show_track(&state, &widgets, track_id, &cover_tx).await;
+    }
+
+    while let Ok(event) = events.recv().await {
+        match event {
+            TrackStarted { track_id } => {
+                show_playing(&widgets, true);
+                show_track(&state, &widgets, track_id, &cover_tx).await;
+            }
+            Paused => show_playing(&widgets, false),
+            Resumed => show_playing(&widgets, true),
+            Stopped => show_stopped(&widgets),
+            _ => {}
+        }
+    }
+}
+
+/// Look up the title, artist and cover of `track_id` and show them.
+async fn show_track(
+    state: &AppState,
+    widgets: &MiniPlayerWidgets,
+    track_id: i64,
+    cover_tx: &Sender<(i64, DecodedCover)>,
+) {
+    let Some(info) = state.playing_track_info(track_id).await else {
+        return;
+    };
+    widgets.title.set_label(&info.title);
+    widgets.artist.set_label(&info.artist);
+    widgets.cover.set_paintable(None::<&MemoryTexture>);
+
+    let cover_cache = &state.cover_art_cache;
+    let album_id = info.album_id;
+    let wanted = widgets.cover_ticket.next();
+    if album_id >= 0 {
+        cover_cache.record_track_album(track_id, album_id);
+        if let Some(texture) = cover_cache.get(album_id) {
+            widgets.cover.set_paintable(Some(&*texture));
+            return;
+        }
+    }
+    if let Some(path) = info.artwork_path {
+        let key = if album_id >= 0 { album_id } else { track_id };
+        cover_cache.request_decode_to_channel(
+            key,
+            path,
+            COVER_SIZE,
+            cover_tx.clone(),
+            "mini player",
+            Some(wanted),
+        );
+    }
+}
+
+/// Paint decoded covers that still belong to the playing track.
+async fn receive_covers(
+    covers: Receiver<(i64, DecodedCover)>,
+    state: Arc<AppState>,
+    widgets: MiniPlayerWidgets,
+) {
+    while let Ok((key, cover)) = covers.recv().await {
+        let Some(track_id) = state.playback.state().current_track_id else {
+            continue;
+        };
+        if key == track_id || state.cover_art_cache.get_album_for_track(track_id) == Some(key) {
+            widgets.cover.set_paintable(Some(&raw_to_texture(&cover)));
+        }
+    }
+}
+
+/// Show the play/pause button in its playing or paused state.
+fn show_playing(widgets: &MiniPlayerWidgets, playing: bool) {
+    show_play_state(&widgets.play_button, playing);
+}
+
+/// Clear the track details once playback stops.
+fn show_stopped(widgets: &MiniPlayerWidgets) {
+    widgets.title.set_label("No track playing");
+    widgets.artist.set_label("");
+    widgets.cover.set_paintable(None::<&MemoryTexture>);
+    show_playing(widgets, false);
+}
+
+/// Remember the mini player size, logging on failure.
+async fn save_size(storage: Arc<SqliteStorage>, width: i32, height: i32) {
+    if let Err(e) = storage.set_mini_player_size(width, height).await {
+        warn!(error = %e, "Failed to save mini player size");
+    }
+}
@@ -4,9 +4,19 @@
 //! Handles auto-show on playback start and auto-hide on queue empty/stop.
 //! Implements responsive behavior for narrow windows.
 
+pub mod ab_loop;
 pub mod controls;
+pub mod go_to;
+pub mod level_meter;
+pub mod lyrics;
+pub mod mini;
+pub mod now_playing;
 pub mod panel;
 pub mod queue;
+pub mod share;
+pub mod speed;
+pub mod track_info;
+pub mod waveform;
 
 use std::sync::Arc;
 
@@ -0,0 +1,345 @@
+//! Fullscreen now playing view for listening sessions.
+//!
+//! A fullscreen window over the main window shows the cover of the playing
+//! track at full size, with its title, artist, album and format, a large
+//! progress bar, and whether the output is bit-perfect and gapless. It
+//! follows the same `PlaybackEvent` stream as the player panel and reuses
+//! its metadata lookup and the shared cover cache. Escape, or the shortcut
+//! that opened it, leaves the view.
+
+use std::sync::Arc;
+
+use {
+    async_channel::{Receiver, Sender, unbounded},
+    libadwaita::{
+        ApplicationWindow,
+        gdk::{Key, MemoryTexture},
+        glib::{
+            Propagation::{Proceed, Stop},
+            spawn_future_local,
+        },
+        gtk::{
+            self,
+            Align::Center,
+            Box, EventControllerKey, Justification, Label,
+            Orientation::{Horizontal, Vertical},
+            Picture, ProgressBar,
+            accessible::Property::Label as PropertyLabel,
+            pango::EllipsizeMode::End,
+        },
+        prelude::{
+            AccessibleExtManual, AdwApplicationWindowExt, BoxExt, GtkWindowExt, ObjectExt,
+            WidgetExt,
+        },
+    },
+    tracing::info,
+};
+
+use crate::{
+    app::AppState,
+    playback::{
+        control::PlaybackController,
+        engine::PlaybackEvent::{
+            self, GaplessEnabledChanged, OutputModeChanged, OutputOpened, PlaybackRateChanged,
+            PositionTick, Seeked, Stopped, TrackStarted, VolumeChanged,
+        },
+    },
+    ui::{
+        CoverTicket, DecodedCover,
+        player::panel::{format_time, with_stream_layout},
+        raw_to_texture,
+        shortcuts::install_shortcuts,
+    },
+};
+
+/// Edge length covers are decoded at for the fullscreen view.
+const COVER_SIZE: i32 = 1024;
+
+/// Widget name marking the now playing window.
+const NOW_PLAYING_NAME: &str = "now-playing";
+
+/// Widgets refreshed as playback changes.
+#[derive(Clone)]
+struct NowPlayingWidgets {
+    /// Album cover of the playing track.
+    cover: Picture,
+    /// Cancels cover decodes of tracks that are no longer playing.
+    cover_ticket: CoverTicket,
+    /// Track title.
+    title: Label,
+    /// Artist name.
+    artist: Label,
+    /// Album title and year.
+    album: Label,
+    /// Codec, bit depth, sample rate and channels.
+    format: Label,
+    /// Bit-perfect and gapless status.
+    status: Label,
+    /// Played fraction of the track.
+    progress: ProgressBar,
+    /// Elapsed time.
+    elapsed: Label,
+    /// Track duration.
+    duration: Label,
+}
+
+/// Switch the fullscreen now playing view on or off.
+///
+/// # Arguments
+///
+/// * `window` - Window the toggle came from; the now playing view closes
+///   itself, any other window gets the view on top of it
+/// * `state` - Application state
+pub fn toggle_now_playing(window: &gtk::Window, state: &Arc<AppState>) {
+    if window.widget_name() == NOW_PLAYING_NAME {
+        window.close();
+    } else {
+        open_now_playing(window, state);
+    }
+}
+
+/// Show the now playing view fullscreen over `parent`.
+fn open_now_playing(parent: &gtk::Window, state: &Arc<AppState>) {
+    let window = ApplicationWindow::builder()
+        .title("Now Playing")
+        .name(NOW_PLAYING_NAME)
+        .transient_for(parent)
+        .build();
+    window.set_application(parent.application().as_ref());
+
+    let (content, widgets) = build_now_playing_content();
+    window.set_content(Some(&content));
+
+    let escape = EventControllerKey::new();
+    let window_ref = window.downgrade();
+    escape.connect_key_pressed(move |_, key, _, _| {
+        if key != Key::Escape {
+            return Proceed;
+        }
+        if let Some(window) = window_ref.upgrade() {
+            window.close();
+        }
+        Stop
+    });
+    window.add_controller(escape);
+
+    let events = state.playback.subscribe();
+    spawn_future_local(follow_playback(events.clone(), Arc::clone(state), widgets));
+    window.connect_close_request(move |_| {
+        events.close();
+        info!("Leaving now playing view");
+        Proceed
+    });
+
+    install_shortcuts(&window, state);
+    info!("Entering now playing view");
+    window.fullscreen();
+    window.present();
+}
+
+/// Build the cover, track details and progress bar.
+fn build_now_playing_content() -> (Box, NowPlayingWidgets) {
+    let cover = Picture::builder()
+        .can_shrink(true)
+        .hexpand(true)
+        .vexpand(true)
+        .css_classes(["album-cover"])
+        .build();
+    cover.update_property(&[PropertyLabel("Album artwork")]);
+
+    let label = |css: &[&str], accessible: &str| {
+        let label = Label::builder()
+            .css_classes(css)
+            .ellipsize(End)
+            .justify(Justification::Center)
+            .build();
+        label.update_property(&[PropertyLabel(accessible)]);
+        label
+    };
+    let title = label(&["title-1"], "Track title");
+    title.set_label("No track playing");
+    let artist = label(&["title-2"], "Artist name");
+    let album = label(&["title-4", "dim-label"], "Album name");
+    let format = label(&["dim-label"], "Audio format information");
+    let status = label(&["dim-label", "caption"], "Signal path status");
+
+    let progress = ProgressBar::builder()
+        .hexpand(true)
+        .valign(Center)
+        .css_classes(["now-playing-progress"])
+        .build();
+    progress.update_property(&[PropertyLabel("Playback progress")]);
+    let elapsed = label(&["numeric"], "Elapsed time");
+    elapsed.set_label("00:00");
+    let duration = label(&["numeric"], "Track duration");
+    duration.set_label("00:00");
+    let progress_row = Box::builder().orientation(Horizontal).spacing(12).build();
+    progress_row.append(&elapsed);
+    progress_row.append(&progress);
+    progress_row.append(&duration);
+
+    let content = Box::builder()
+        .orientation(Vertical)
+        .spacing(12)
+        .margin_top(48)
+        .margin_bottom(48)
+        .margin_start(96)
+        .margin_end(96)
+        .build();
+    content.append(&cover);
+    content.append(&title);
+    content.append(&artist);
+    content.append(&album);
+    content.append(&format);
+    content.append(&progress_row);
+    content.append(&status);
+
+    let widgets = NowPlayingWidgets {
+        cover,
+        cover_ticket: CoverTicket::default(),
+        title,
+        artist,
+        album,
+        format,
+        status,
+        progress,
+        elapsed,
+        duration,
+    };
+    (content, widgets)
+}
+
+/// Show the current track, then keep the widgets in step with `events`
+/// until the window closes the channel.
+async fn follow_playback(
+    events: Receiver<PlaybackEvent>,
+    state: Arc<AppState>,
+    widgets: NowPlayingWidgets,
+) {
+    let (cover_tx, cover_rx) = unbounded::<(i64, DecodedCover)>();
+    spawn_future_local(receive_covers(
+        cover_rx,
+        Arc::clone(&state),
+        widgets.clone(),
+    ));
+
+    let current = state.playback.state();
+    show_status(&state, &widgets);
+    show_position(&widgets, current.elapsed_seconds, current.duration_seconds);
+    if let Some(track_id) = current.current_track_id {
+        show_track(&state, &widgets, track_id, &cover_tx).await;
+    }
+
+    while let Ok(event) = events.recv().await {
+        match event {
+            TrackStarted { track_id } => {
+                show_status(&state, &widgets);
+                show_track(&state, &widgets, track_id, &cover_tx).await;
+            }
+            Stopped => show_stopped(&widgets),
+            PositionTick {
+                elapsed_seconds,
+                duration_seconds,
+            } => show_position(&widgets, elapsed_seconds, duration_seconds),
+            Seeked { position_seconds } => {
+                let duration = state.playback.state().duration_seconds;
+                show_position(&widgets, position_seconds, duration);
+            }
+            GaplessEnabledChanged { .. }
+            | OutputModeChanged { .. }
+            | OutputOpened { .. }
+            | PlaybackRateChanged { .. }
+            | VolumeChanged { .. } => show_status(&state, &widgets),
+            _ => {}
+        }
+    }
+}
+
+/// Look up the details and cover of `track_id` and show them.
+async fn show_track(
+    state: &AppState,
+    widgets: &NowPlayingWidgets,
+    track_id: i64,
+    cover_tx: &Sender<(i64, DecodedCover)>,
+) {
+    let Some(info) = state.playing_track_info(track_id).await else {
+        return;
+    };
+    widgets.title.set_label(&info.title);
+    widgets.artist.set_label(&info.artist);
+    widgets.album.set_label(&info.album);
+    widgets.format.set_label(&with_stream_layout(
+        &info.format,
+        state.playback.state().channel_layout,
+    ));
+    widgets.cover.set_paintable(None::<&MemoryTexture>);
+
+    let cover_cache = &state.cover_art_cache;
+    let album_id = info.album_id;
+    let wanted = widgets.cover_ticket.next();
+    if album_id >= 0 {
+        cover_cache.record_track_album(track_id, album_id);
+        if let Some(texture) = cover_cache.get(album_id) {
+            widgets.cover.set_paintable(Some(&*texture));
+        }
+    }
+    if let Some(path) = info.artwork_path {
+        let key = if album_id >= 0 { album_id } else { track_id };
+        cover_cache.request_decode_to_channel(
+            key,
+            path,
+            COVER_SIZE,
+            cover_tx.clone(),
+            "now playing",
+            Some(wanted),
+        );
+    }
+}
+
+/// Paint full-size covers that still belong to the playing track.
+///
+/// The cached cover is shown first and replaced here; the full-size decode
+/// is kept out of the cache, which holds covers at list and panel sizes.
+async fn receive_covers(
+    covers: Receiver<(i64, DecodedCover)>,
+    state: Arc<AppState>,
+    widgets: NowPlayingWidgets,
+) {
+    while let Ok((key, cover)) = covers.recv().await {
+        let Some(track_id) = state.playback.state().current_track_id else {
+            continue;
+        };
+        if key == track_id || state.cover_art_cache.get_album_for_track(track_id) == Some(key) {
+            widgets.cover.set_paintable(Some(&raw_to_texture(&cover)));
+        }
+    }
+}
+
+/// Show the playback position and track duration.
+fn show_position(widgets: &NowPlayingWidgets, elapsed: f64, duration: f64) {
+    let fraction = if duration > 0.0 {
+        (elapsed / duration).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    widgets.progress.set_fraction(fraction);
+    widgets.elapsed.set_label(&format_time(elapsed));
+    widgets.duration.set_label(&format_time(duration));
+}
+
+/// Show whether the output is bit-perfect and gapless.
+fn show_status(state: &AppState, widgets: &NowPlayingWidgets) {
+    let report = state.playback.signal_path_report();
+    widgets.status.set_label(&report.status_line());
+    widgets.status.set_tooltip_text(Some(&report.summary()));
+}
+
+/// Clear the track details once playback stops.
+fn show_stopped(widgets: &NowPlayingWidgets) {
+    widgets.title.set_label("No track playing");
+    for label in [&widgets.artist, &widgets.album, &widgets.format] {
+        label.set_label("");
+    }
+    widgets.cover.set_paintable(None::<&MemoryTexture>);
+    show_position(widgets, 0.0, 0.0);
+}
@@ -1,12 +1,16 @@
 //! Player panel content with artwork, track info, and playback controls.
 //!
-//! Displays album artwork, track title, artist, seek slider, playback
-//! controls, and volume slider. Used as the content of the sidebar pane.
+//! Displays album artwork, track title, artist, waveform, level meter, seek
+//! slider, playback controls, volume slider, and lyrics. Used as the content of the sidebar pane.
 //! Subscribes to `PlaybackEvent` for fully event-driven updates.
 
-use std::sync::{
-    Arc,
-    atomic::{AtomicBool, Ordering::Acquire},
+use std::{
+    cell::Cell,
+    rc::Rc,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering::Acquire},
+    },
 };
 
 use {
@@ -23,7 +27,7 @@ use {
             pango::EllipsizeMode::End,
             prelude::RangeExt,
         },
-        prelude::{AccessibleExtManual, BoxExt, ButtonExt, TextureExt, WidgetExt},
+        prelude::{AccessibleExtManual, BoxExt, TextureExt},
     },
     tokio::spawn,
     tracing::error,
@@ -36,19 +40,30 @@ use crate::{
         engine::{
             PlaybackEngine,
             PlaybackEvent::{
-                self, OutputModeChanged, Paused, PositionTick, Resumed, Seeked, Stopped,
-                TrackStarted,
+                self, OutputModeChanged, OutputOpened, Paused, PlaybackRateChanged, PositionTick,
+                Resumed, Seeked, Stopped, TrackStarted, VolumeChanged,
             },
         },
         layout::{AudioLayout, format_channel_label},
     },
-    storage::{Storage, database::SqliteStorage},
+    storage::database::SqliteStorage,
     ui::{
-        CoverArtCache, DecodedCover,
+        CoverArtCache, CoverTicket, DecodedCover,
         detail::common::build_scroll_content,
-        player::controls::{
-            build_playback_controls, build_queue_section, build_seek_section, build_volume_control,
-            mode_button_tooltip, update_volume_scale_visual,
+        player::{
+            ab_loop::build_ab_loop_controls,
+            controls::{
+                build_playback_controls, build_queue_section, build_seek_section,
+                build_volume_control, connect_end_time_toggle, show_output_mode, show_play_state,
+                show_seek_position, update_volume_scale_visual,
+            },
+            go_to::{PlayingTarget, link_to_playing},
+            level_meter::build_level_meter,
+            lyrics::build_lyrics_section,
+            share::build_copy_now_playing_button,
+            speed::build_speed_control,
+            track_info::{TrackInfo, resolve_track_info},
+            waveform::build_waveform,
         },
         raw_to_texture,
     },
@@ -60,9 +75,6 @@ use crate::{
 /// decode via the async metadata path.
 const COVER_MIN_SIZE: i32 = 180;
 
-/// Tuple of resolved metadata: `(title, artist, album, artwork_path, format_info, album_id)`.
-type MetaResult = (String, String, String, Option<String>, String, i64);
-
 /// Widget references for playback control updates.
 #[derive(Clone)]
 struct PlaybackWidgets {
@@ -70,14 +82,18 @@ struct PlaybackWidgets {
     labels: TrackLabels,
     /// Album artwork display widget.
     artwork_image: Picture,
+    /// Cancels cover decodes of tracks that are no longer playing.
+    cover_ticket: CoverTicket,
     /// Play/pause transport button.
     play_button: Button,
     /// Seek slider for position control.
     seek_scale: Scale,
     /// Label showing current playback position.
     current_time: Label,
-    /// Label showing total track duration.
+    /// Label showing total track duration or the time remaining.
     total_time: Label,
+    /// Whether `total_time` shows the time remaining.
+    show_remaining: Rc<Cell<bool>>,
     /// Output-mode toggle button (BP / RM).
     output_mode_btn: Button,
     /// Volume scale slider, greyed out in bit-perfect mode.
@@ -106,13 +122,24 @@ pub fn format_time(seconds: f64) -> String {
     format!("{mins:02.0}:{secs:02.0}")
 }
 
+/// Format the time shown after the seek bar: the track duration, or the
+/// time remaining as `-MM:SS` when `remaining` is set.
+#[must_use]
+pub fn format_end_time(elapsed: f64, duration: f64, remaining: bool) -> String {
+    if remaining {
+        format!("-{}", format_time(duration - elapsed))
+    } else {
+        format_time(duration)
+    }
+}
+
 /// Update track labels or spawn metadata fetch when track or status changes.
 fn handle_status_change(
     is_stopped: bool,
     track_id: Option<i64>,
     labels: &TrackLabels,
     storage: &Arc<SqliteStorage>,
-    meta_tx: &Sender<(i64, MetaResult)>,
+    meta_tx: &Sender<(i64, TrackInfo)>,
 ) {
     if is_stopped {
         let t = labels.title.clone();
@@ -134,7 +161,7 @@ fn handle_status_change(
     let storage = Arc::clone(storage);
     let tx = meta_tx.clone();
     spawn(async move {
-        let result = resolve_track_metadata(&storage, track_id).await;
+        let result = resolve_track_info(&storage, track_id).await;
         if let Err(e) = tx.try_send((track_id, result)) {
             error!(error = %e, "Failed to send metadata");
         }
@@ -162,70 +189,19 @@ fn update_cover_from_cache(
     artwork.set_paintable(Some(&*texture));
 }
 
-/// Resolve track metadata from storage.
+/// Show the speaker layout the decoder reports in a format line.
 ///
-/// Returns `(title, artist_name, album_name, artwork_path, format_info, album_id)`.
-async fn resolve_track_metadata(
-    storage: &SqliteStorage,
-    track_id: i64,
-) -> (String, String, String, Option<String>, String, i64) {
-    let Ok(Some(track)) = storage.get_track(track_id).await else {
-        return (
-            format!("Track #{track_id}"),
-            String::new(),
-            String::new(),
-            None,
-            String::new(),
-            -1,
-        );
-    };
-
-    let title = track.title;
-    let album_id = track.audio.album_id.unwrap_or(-1);
-
-    let artist_name = match track.audio.artist_id {
-        Some(aid) => match storage.get_artist(aid).await {
-            Ok(Some(a)) => a.name,
-            _ => String::new(),
-        },
-        None => String::new(),
-    };
-
-    let (album_name, artwork_path) = match album_id {
-        aid if aid >= 0 => match storage.get_album(aid).await {
-            Ok(Some(album)) => (album.title, album.artwork_path),
-            _ => (String::new(), None),
-        },
-        _ => (String::new(), None),
-    };
-
-    let channel_label = format_channel_label(AudioLayout::from_count(
-        u32::try_from(track.audio.channels).unwrap_or(0),
-    ));
-    let sample_rate_khz = f64::from(track.audio.sample_rate) / 1000.0;
-    let format_info = match track.audio.bit_depth {
-        Some(depth) => {
-            format!(
-                "{} \u{2022} {depth}-bit / {sample_rate_khz:.1} kHz \u{2022} {channel_label}",
-                track.audio.format,
-            )
+/// The line built from the library ends with a label guessed from the
+/// channel count; the decoder knows the actual layout, such as 5.1.2
+/// rather than 8 channels. Lines without a channel label and unknown
+/// layouts are returned unchanged.
+pub fn with_stream_layout(format_info: &str, layout: Option<AudioLayout>) -> String {
+    match (format_info.rsplit_once(" \u{2022} "), layout) {
+        (Some((head, _)), Some(layout)) => {
+            format!("{head} \u{2022} {}", format_channel_label(layout))
         }
-        None => {
-            format!(
-                "{} \u{2022} {sample_rate_khz:.1} kHz \u{2022} {channel_label}",
-                track.audio.format,
-            )
-        }
-    };
-
-    (
-        title,
-        artist_name,
-        album_name,
-        artwork_path,
-        format_info,
-        album_id,
-    )
+        _ => format_info.to_string(),
+    }
 }
 
 /// Build the player panel content area.
@@ -246,13 +222,22 @@ pub fn build_player_content(state: &Arc<AppState>) -> ScrolledWindow {
     content.append(&artist_label);
     content.append(&album_label);
     content.append(&format_label);
+    content.append(&build_copy_now_playing_button(state));
+    link_to_playing(&artist_label, state, PlayingTarget::Artist);
+    link_to_playing(&album_label, state, PlayingTarget::Album);
 
+    content.append(&build_waveform(state));
+    content.append(&build_level_meter(state));
     let (seek_section, seek_scale, current_time, total_time) = build_seek_section(state);
+    let show_remaining = connect_end_time_toggle(state, &total_time);
     content.append(&seek_section);
     let (controls_section, play_button) = build_playback_controls(state);
     content.append(&controls_section);
+    content.append(&build_ab_loop_controls(state));
+    content.append(&build_speed_control(state));
     let (vol_section, mode_btn, vol_scale) = build_volume_control(state);
     content.append(&vol_section);
+    content.append(&build_lyrics_section(state));
     content.append(&build_queue_section(state));
 
     scroll.set_child(Some(&content));
@@ -265,30 +250,68 @@ pub fn build_player_content(state: &Arc<AppState>) -> ScrolledWindow {
             format: format_label,
         },
         artwork_image,
+        cover_ticket: CoverTicket::default(),
         play_button,
         seek_scale,
         current_time,
         total_time,
+        show_remaining,
         output_mode_btn: mode_btn,
         volume_scale: vol_scale,
     };
 
     spawn_async_listeners(state, widgets);
+    spawn_rate_change_notices(state);
     scroll
 }
 
-/// Apply metadata labels to the UI via idle callback.
-fn apply_meta_labels(labels: &TrackLabels, t: &str, ar: &str, al: &str, fmt: &str) {
-    labels.title.set_label(t);
-    labels.artist.set_label(ar);
-    labels.album.set_label(al);
-    labels.format.set_label(fmt);
+/// Announce output sample rate changes between tracks in a toast, when
+/// enabled in the preferences.
+fn spawn_rate_change_notices(state: &Arc<AppState>) {
+    let events = state.playback.subscribe();
+    let state = Arc::clone(state);
+    MainContext::default().spawn_local(async move {
+        while let Ok(event) = events.recv().await {
+            announce_rate_change(&state, &event).await;
+        }
+    });
+}
+
+/// Show a toast if `event` opened the output at a new sample rate.
+async fn announce_rate_change(state: &AppState, event: &PlaybackEvent) {
+    let &OutputOpened {
+        previous_device_rate,
+        device_sample_rate,
+    } = event
+    else {
+        return;
+    };
+    if previous_device_rate == 0
+        || previous_device_rate == device_sample_rate
+        || !state.storage.get_notify_rate_changes()
+    {
+        return;
+    }
+    let message = format!(
+        "Output switched from {:.1} to {:.1} kHz",
+        f64::from(previous_device_rate) / 1000.0,
+        f64::from(device_sample_rate) / 1000.0
+    );
+    state.send_toast(message).await;
+}
+
+/// Show the details of a track in the labels.
+fn apply_meta_labels(labels: &TrackLabels, info: &TrackInfo) {
+    labels.title.set_label(&info.title);
+    labels.artist.set_label(&info.artist);
+    labels.album.set_label(&info.album);
+    labels.format.set_label(&info.format);
 }
 
 /// Process one metadata update: check track ID match, update labels, request cover.
 fn process_metadata(
     tid: i64,
-    meta: MetaResult,
+    info: TrackInfo,
     widgets: &PlaybackWidgets,
     playback: &Arc<PlaybackEngine>,
     cover_cache: &Arc<CoverArtCache>,
@@ -298,8 +321,13 @@ fn process_metadata(
     if Some(tid) != playback.state().current_track_id {
         return;
     }
-    let (t, ar, al, art_path, fmt, album_id) = meta;
-    apply_meta_labels(&widgets.labels, &t, &ar, &al, &fmt);
+    let info = TrackInfo {
+        format: with_stream_layout(&info.format, playback.state().channel_layout),
+        ..info
+    };
+    apply_meta_labels(&widgets.labels, &info);
+    let album_id = info.album_id;
+    let wanted = widgets.cover_ticket.next();
 
     if album_id >= 0 {
         cover_cache.record_track_album(tid, album_id);
@@ -308,7 +336,7 @@ fn process_metadata(
             return;
         }
     }
-    if let Some(path) = art_path {
+    if let Some(path) = info.artwork_path {
         let key = if album_id >= 0 { album_id } else { tid };
         cover_cache.request_decode_to_channel(
             key,
@@ -316,6 +344,7 @@ fn process_metadata(
             cover_size,
             cover_tx.clone(),
             "main thread",
+            Some(wanted),
         );
     }
 }
@@ -352,7 +381,7 @@ fn process_cover_art(
 
 /// Set up async listeners for playback events, metadata, and cover art.
 fn spawn_async_listeners(state: &Arc<AppState>, widgets: PlaybackWidgets) {
-    let (meta_tx, meta_rx) = unbounded::<(i64, MetaResult)>();
+    let (meta_tx, meta_rx) = unbounded::<(i64, TrackInfo)>();
     let (cover_tx, cover_rx) = unbounded::<(i64, DecodedCover)>();
 
     let playback = Arc::clone(&state.playback);
@@ -377,8 +406,8 @@ fn spawn_async_listeners(state: &Arc<AppState>, widgets: PlaybackWidgets) {
     let mp = Arc::clone(&playback);
     let mc = Arc::clone(&cover_cache);
     MainContext::default().spawn_local(async move {
-        while let Ok((tid, meta)) = meta_rx.recv().await {
-            process_metadata(tid, meta, &mw, &mp, &mc, &cover_tx, 280);
+        while let Ok((tid, info)) = meta_rx.recv().await {
+            process_metadata(tid, info, &mw, &mp, &mc, &cover_tx, 280);
         }
     });
 
@@ -397,7 +426,7 @@ fn on_playback_event(
     is_seeking: &AtomicBool,
     cover_cache: &CoverArtCache,
     storage: &Arc<SqliteStorage>,
-    meta_tx: &Sender<(i64, MetaResult)>,
+    meta_tx: &Sender<(i64, TrackInfo)>,
 ) {
     match event {
         TrackStarted { track_id } => {
@@ -409,75 +438,74 @@ fn on_playback_event(
                 playback,
             );
             handle_status_change(false, Some(*track_id), &widgets.labels, storage, meta_tx);
-            widgets
-                .play_button
-                .set_icon_name("media-playback-pause-symbolic");
-        }
-        Paused => {
-            widgets
-                .play_button
-                .set_icon_name("media-playback-start-symbolic");
-        }
-        Resumed => {
-            widgets
-                .play_button
-                .set_icon_name("media-playback-pause-symbolic");
+            show_play_state(&widgets.play_button, true);
+            show_output_mode(&widgets.output_mode_btn, &playback.signal_path_report());
         }
+        Paused => show_play_state(&widgets.play_button, false),
+        Resumed => show_play_state(&widgets.play_button, true),
         Stopped => {
             widgets.labels.title.set_label("No track playing");
             widgets.labels.artist.set_label("");
             widgets.labels.album.set_label("");
             widgets.labels.format.set_label("");
-            widgets
-                .play_button
-                .set_icon_name("media-playback-start-symbolic");
+            show_play_state(&widgets.play_button, false);
             widgets.total_time.set_label("00:00");
             if !is_seeking.load(Acquire) {
-                widgets.seek_scale.set_value(0.0);
+                show_seek_position(&widgets.seek_scale, 0.0, 0.0);
                 widgets.current_time.set_label("00:00");
             }
         }
         Seeked { .. } => {
             let s = playback.state();
             if !is_seeking.load(Acquire) {
-                let fraction = match s.duration_seconds {
-                    d if d > 0.0 => s.elapsed_seconds / d,
-                    _ => 0.0,
-                };
-                widgets.seek_scale.set_value(fraction * 100.0);
+                show_seek_position(&widgets.seek_scale, s.elapsed_seconds, s.duration_seconds);
                 widgets
                     .current_time
                     .set_label(&format_time(s.elapsed_seconds));
             }
-            widgets
-                .total_time
-                .set_label(&format_time(s.duration_seconds));
+            widgets.total_time.set_label(&format_end_time(
+                s.elapsed_seconds,
+                s.duration_seconds,
+                widgets.show_remaining.get(),
+            ));
         }
         PositionTick {
             elapsed_seconds,
             duration_seconds,
         } => {
             if !is_seeking.load(Acquire) {
-                let fraction = match *duration_seconds {
-                    d if d > 0.0 => *elapsed_seconds / d,
-                    _ => 0.0,
-                };
-                widgets.seek_scale.set_value(fraction * 100.0);
+                show_seek_position(&widgets.seek_scale, *elapsed_seconds, *duration_seconds);
                 widgets
                     .current_time
                     .set_label(&format_time(*elapsed_seconds));
             }
-            widgets
-                .total_time
-                .set_label(&format_time(*duration_seconds));
+            widgets.total_time.set_label(&format_end_time(
+                *elapsed_seconds,
+                *duration_seconds,
+                widgets.show_remaining.get(),
+            ));
         }
         OutputModeChanged { mode } => {
-            widgets.output_mode_btn.set_icon_name(mode.icon_name());
-            widgets
-                .output_mode_btn
-                .set_tooltip_text(Some(mode_button_tooltip(*mode)));
+            show_output_mode(&widgets.output_mode_btn, &playback.signal_path_report());
             update_volume_scale_visual(&widgets.volume_scale, *mode);
         }
+        OutputOpened { .. } => {
+            show_output_mode(&widgets.output_mode_btn, &playback.signal_path_report());
+            let format = &widgets.labels.format;
+            format.set_label(&with_stream_layout(
+                &format.label(),
+                playback.state().channel_layout,
+            ));
+        }
+        PlaybackRateChanged { .. } => {
+            show_output_mode(&widgets.output_mode_btn, &playback.signal_path_report());
+        }
+        // Keyboard shortcuts change the volume without touching the slider.
+        VolumeChanged { volume }
+            if (widgets.volume_scale.value() - volume).abs() >= f64::EPSILON =>
+        {
+            widgets.volume_scale.set_value(*volume);
+        }
         _ => {}
     }
 }
@@ -541,7 +569,10 @@ fn build_track_info() -> (Label, Label, Label, Label) {
 
 #[cfg(test)]
 mod tests {
-    use crate::ui::player::panel::format_time;
+    use crate::{
+        playback::layout::AudioLayout::{Channels, Immersive},
+        ui::player::panel::{format_end_time, format_time, with_stream_layout},
+    };
 
     #[test]
     fn format_time_zero() {
@@ -557,4 +588,26 @@ mod tests {
     fn format_time_hours() {
         assert_eq!(format_time(3661.0), "61:01");
     }
+
+    #[test]
+    fn format_end_time_duration_or_remaining() {
+        assert_eq!(format_end_time(30.0, 150.0, false), "02:30");
+        assert_eq!(format_end_time(30.0, 150.0, true), "-02:00");
+    }
+
+    #[test]
+    fn stream_layout_replaces_channel_label() {
+        let line = "FLAC \u{2022} 24-bit / 48.0 kHz \u{2022} 8-channel";
+        let layout = Immersive {
+            surround: 5,
+            lfe: 1,
+            height: 2,
+        };
+        assert_eq!(
+            with_stream_layout(line, Some(layout)),
+            "FLAC \u{2022} 24-bit / 48.0 kHz \u{2022} 5.1.2 Immersive"
+        );
+        assert_eq!(with_stream_layout(line, None), line, "decoder not open yet");
+        assert_eq!(with_stream_layout("", Some(Channels(2))), "", "no track");
+    }
 }
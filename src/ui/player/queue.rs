@@ -1,7 +1,8 @@
 //! Visible queue view with track list, drag-and-drop reorder, and remove button.
 //!
-//! Displays the playback queue with current/upcoming sections.
-//! Uses `ListView` with compact rows. Each row has a drag handle to reorder.
+//! Displays the playback queue in play order with the current track
+//! highlighted. Uses `ListView` with compact rows. Each row has a drag
+//! handle to reorder and a remove button; clicking a row jumps to it.
 //! Subscribes to `PlaybackEvent` for fully event-driven updates.
 
 use std::{
@@ -20,7 +21,8 @@ use {
         },
         gtk::{
             Align::Start,
-            Box, Button, DragSource, DropTarget, Label, ListItem, ListView, NoSelection,
+            Box, Button, DragSource, DropTarget, GestureClick, Label, ListItem, ListView,
+            NoSelection,
             Orientation::{Horizontal, Vertical},
             SignalListItemFactory,
             accessible::Property::Label as PropertyLabel,
@@ -36,7 +38,10 @@ use crate::{
     app::AppState,
     playback::{
         control::PlaybackController,
-        engine::PlaybackEvent::{self, QueueChanged, TrackStarted},
+        engine::{
+            PlaybackEngine,
+            PlaybackEvent::{self, QueueChanged, TrackStarted},
+        },
         queue::PlaybackQueue,
     },
     storage::Storage,
@@ -51,28 +56,34 @@ struct QueueItemData {
     is_current: bool,
 }
 
-/// Reorder an item within both the queue model and the `ListStore`.
-fn reorder_entry(queue: &PlaybackQueue, store: &ListStore, from: usize, to: usize) {
+/// Reorder an item via the playback controller and mirror it in the `ListStore`.
+fn reorder_entry(playback: &PlaybackEngine, store: &ListStore, from: usize, to: usize) {
     if from == to {
         return;
     }
-    queue.move_track(from, to);
-    let Ok(from_u32) = u32::try_from(from) else {
+    if let Err(e) = playback.move_in_queue(from, to) {
+        warn!(error = %e, from, to, "Failed to reorder queue");
+        return;
+    }
+    let (Ok(from_u32), Ok(to_u32)) = (u32::try_from(from), u32::try_from(to)) else {
         return;
     };
     let Some(item) = store.item(from_u32) else {
         return;
     };
     store.remove(from_u32);
-    let adjusted_pos = if to > from { to - 1 } else { to };
-    let Ok(adjusted_u32) = u32::try_from(adjusted_pos) else {
-        return;
-    };
-    store.insert(adjusted_u32, &item);
+    store.insert(to_u32, &item);
+}
+
+/// Start playing the queue entry at `pos`, logging on failure.
+fn jump_to_entry(playback: &PlaybackEngine, pos: usize) {
+    if let Err(e) = playback.jump_to_queue_index(pos) {
+        error!(error = %e, pos, "Failed to jump to queue entry");
+    }
 }
 
 /// Process a drop value for reordering.
-fn handle_drop_value(value: &Value, queue: &PlaybackQueue, store: &ListStore, to_pos: usize) {
+fn handle_drop_value(value: &Value, playback: &PlaybackEngine, store: &ListStore, to_pos: usize) {
     let from = match value.get::<i32>() {
         Ok(v) => v,
         Err(e) => {
@@ -81,7 +92,7 @@ fn handle_drop_value(value: &Value, queue: &PlaybackQueue, store: &ListStore, to
         }
     };
     let from_u = usize::try_from(from).unwrap_or(0);
-    reorder_entry(queue, store, from_u, to_pos);
+    reorder_entry(playback, store, from_u, to_pos);
 }
 
 /// Remove a track from the queue at the given position, updating the store.
@@ -94,9 +105,9 @@ fn try_remove_entry(q: &PlaybackQueue, store: &ListStore, pos: usize) {
 }
 
 /// Create the `SignalListItemFactory` that builds and binds queue rows.
-fn build_row_factory(queue: &PlaybackQueue, store: &ListStore) -> SignalListItemFactory {
+fn build_row_factory(playback: &Arc<PlaybackEngine>, store: &ListStore) -> SignalListItemFactory {
     let factory = SignalListItemFactory::new();
-    let factory_queue = queue.clone();
+    let factory_playback = Arc::clone(playback);
     let factory_store = store.clone();
 
     factory.connect_setup(move |_, list_item| {
@@ -104,7 +115,7 @@ fn build_row_factory(queue: &PlaybackQueue, store: &ListStore) -> SignalListItem
             return;
         };
         let li = list_item_obj.clone();
-        let queue_li = factory_queue.clone();
+        let playback_li = Arc::clone(&factory_playback);
         let store_li = factory_store.clone();
 
         let container = Box::builder()
@@ -140,6 +151,15 @@ fn build_row_factory(queue: &PlaybackQueue, store: &ListStore) -> SignalListItem
             .hexpand(true)
             .build();
         label.update_property(&[PropertyLabel("Track name in queue")]);
+        label.set_tooltip_text(Some("Play this track"));
+
+        let click = GestureClick::new();
+        let li_click = li.clone();
+        let playback_click = Arc::clone(&playback_li);
+        click.connect_released(move |_, _, _, _| {
+            jump_to_entry(&playback_click, li_click.position() as usize);
+        });
+        label.add_controller(click);
 
         let remove = Button::builder()
             .icon_name("window-close-symbolic")
@@ -149,20 +169,20 @@ fn build_row_factory(queue: &PlaybackQueue, store: &ListStore) -> SignalListItem
         remove.update_property(&[PropertyLabel("Remove from queue")]);
 
         let li_remove = li.clone();
-        let queue_remove = queue_li.clone();
+        let playback_remove = Arc::clone(&playback_li);
         let store_remove = store_li.clone();
         remove.connect_clicked(move |_| {
             let pos = li_remove.position() as usize;
-            try_remove_entry(&queue_remove, &store_remove, pos);
+            try_remove_entry(playback_remove.queue(), &store_remove, pos);
         });
 
         let drop = DropTarget::new(Type::I32, DragAction::MOVE);
         let li_drop = li;
-        let queue_drop = queue_li;
+        let playback_drop = playback_li;
         let store_drop = store_li;
         drop.connect_drop(move |_, value, _, _| {
             let to_pos = li_drop.position() as usize;
-            handle_drop_value(value, &queue_drop, &store_drop, to_pos);
+            handle_drop_value(value, &playback_drop, &store_drop, to_pos);
             true
         });
 
@@ -250,6 +270,7 @@ fn update_name_cache(cache: &Arc<Mutex<Vec<(i64, String)>>>, names: &Vec<(i64, S
 /// Build the queue view using `ListView` with compact rows.
 ///
 /// Each row has a drag handle for reordering, track name, and remove button.
+/// Clicking a track name jumps playback to that entry.
 #[must_use]
 pub fn build_queue_view(state: &Arc<AppState>, queue: &PlaybackQueue) -> Box {
     let store = ListStore::builder()
@@ -259,7 +280,7 @@ pub fn build_queue_view(state: &Arc<AppState>, queue: &PlaybackQueue) -> Box {
     let queue = queue.clone();
     let rx = state.playback.subscribe();
 
-    let factory = build_row_factory(&queue, &store);
+    let factory = build_row_factory(&state.playback, &store);
 
     let model = NoSelection::new(Some(store.clone()));
     let list_view = ListView::builder()
@@ -281,6 +302,10 @@ pub fn build_queue_view(state: &Arc<AppState>, queue: &PlaybackQueue) -> Box {
     let (queue_tx, queue_rx) = unbounded::<Vec<(i64, String)>>();
     let cached_names = Arc::new(Mutex::new(Vec::<(i64, String)>::new()));
 
+    if !poll_queue.is_empty() {
+        spawn_fetch_queue_names(&poll_state, poll_queue.tracks(), queue_tx.clone());
+    }
+
     let ev_state = Arc::clone(&poll_state);
     let ev_store = poll_store.clone();
     let ev_queue = poll_queue.clone();
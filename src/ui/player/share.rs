@@ -0,0 +1,143 @@
+//! "Copy Now Playing" button of the player panel and the matching template
+//! setting on the General preferences page.
+//!
+//! The copied text is rendered from the template in the settings, so users
+//! can pick which details of the playing track end up in the clipboard.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        EntryRow, PreferencesGroup, PreferencesPage,
+        gdk::Clipboard,
+        glib::spawn_future_local,
+        gtk::{Align::Center, Button, accessible::Property::Label as PropertyLabel},
+        prelude::{
+            AccessibleExtManual, ButtonExt, EditableExt, EntryRowExt, PreferencesGroupExt,
+            PreferencesPageExt, PreferencesRowExt, WidgetExt,
+        },
+    },
+    tracing::{error, warn},
+};
+
+use crate::{
+    app::AppState,
+    library::share::{DEFAULT_SHARE_TEMPLATE, SHARE_TOKENS, ShareFields, render_share_text},
+    playback::control::PlaybackController,
+    storage::{Storage, database::SqliteStorage},
+};
+
+/// Build the button copying the playing track as text.
+#[must_use]
+pub fn build_copy_now_playing_button(state: &Arc<AppState>) -> Button {
+    let button = Button::builder()
+        .icon_name("edit-copy-symbolic")
+        .css_classes(["flat", "circular"])
+        .halign(Center)
+        .tooltip_text("Copy now playing")
+        .build();
+    button.update_property(&[PropertyLabel("Copy now playing")]);
+
+    let state = Arc::clone(state);
+    button.connect_clicked(move |btn| {
+        spawn_future_local(copy_now_playing(Arc::clone(&state), btn.clipboard()));
+    });
+    button
+}
+
+/// Render the playing track with the saved template into `clipboard`.
+async fn copy_now_playing(state: Arc<AppState>, clipboard: Clipboard) {
+    let Some(track_id) = state.playback.state().current_track_id else {
+        state.send_toast("Nothing is playing".to_string()).await;
+        return;
+    };
+    let Some(fields) = share_fields(&state.storage, track_id).await else {
+        state
+            .send_toast("Track details are unavailable".to_string())
+            .await;
+        return;
+    };
+    let text = render_share_text(&state.storage.get_share_template(), &fields);
+    clipboard.set_text(&text);
+    state.send_toast("Copied to clipboard".to_string()).await;
+}
+
+/// Look up the details of a track that a share template can refer to.
+///
+/// The artist falls back to the album artist when the track has none.
+async fn share_fields(storage: &SqliteStorage, track_id: i64) -> Option<ShareFields> {
+    let track = match storage.get_track(track_id).await {
+        Ok(track) => track?,
+        Err(e) => {
+            warn!(error = %e, track_id, "Failed to load track for sharing");
+            return None;
+        }
+    };
+    let album = match track.audio.album_id {
+        Some(id) => match storage.get_album(id).await {
+            Ok(album) => album,
+            Err(e) => {
+                warn!(error = %e, album_id = id, "Failed to load album for sharing");
+                None
+            }
+        },
+        None => None,
+    };
+    let artist_id = track
+        .audio
+        .artist_id
+        .or_else(|| album.as_ref().map(|a| a.artist_id));
+    let artist = match artist_id {
+        Some(id) => match storage.get_artist(id).await {
+            Ok(artist) => artist,
+            Err(e) => {
+                warn!(error = %e, artist_id = id, "Failed to load artist for sharing");
+                None
+            }
+        },
+        None => None,
+    };
+    Some(ShareFields {
+        artist: artist.map(|a| a.name).unwrap_or_default(),
+        album: album.map(|a| a.title).unwrap_or_default(),
+        title: track.title,
+        format: track.audio.format,
+        bit_depth: track.audio.bit_depth,
+        sample_rate: track.audio.sample_rate,
+    })
+}
+
+/// Build the General > Sharing group with the template of the copied text.
+pub fn build_share_group(page: &PreferencesPage, state: &Arc<AppState>) {
+    let group = PreferencesGroup::new();
+    group.set_title("Sharing");
+    let tokens: Vec<String> = SHARE_TOKENS.iter().map(|t| format!("{{{t}}}")).collect();
+    group.set_description(Some(&format!(
+        "Text copied by the player's copy button. Available: {}",
+        tokens.join(", ")
+    )));
+
+    let template_row = EntryRow::new();
+    template_row.set_title("Now Playing Template");
+    template_row.set_text(&state.storage.get_share_template());
+    template_row.set_show_apply_button(true);
+    let storage = Arc::clone(&state.storage);
+    template_row.connect_apply(move |row| {
+        let mut template = row.text().trim().to_string();
+        if template.is_empty() {
+            template = DEFAULT_SHARE_TEMPLATE.to_string();
+            row.set_text(&template);
+        }
+        spawn_future_local(save_template(Arc::clone(&storage), template));
+    });
+
+    group.add(&template_row);
+    page.add(&group);
+}
+
+/// Persist the share template, logging on failure.
+async fn save_template(storage: Arc<SqliteStorage>, template: String) {
+    if let Err(e) = storage.set_share_template(template).await {
+        error!(error = %e, "Failed to save share template");
+    }
+}
@@ -0,0 +1,140 @@
+//! Playback speed selector of the player panel.
+//!
+//! The speed is applied by resampling, so pitch follows speed. A chosen
+//! speed applies to the current track only unless "Keep speed" is checked,
+//! in which case it carries over to the following tracks.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        glib::{MainContext, spawn_future_local},
+        gtk::{
+            Align::Center, Box, CheckButton, DropDown, Orientation::Horizontal,
+            accessible::Property::Label as PropertyLabel,
+        },
+        prelude::{AccessibleExtManual, BoxExt, CheckButtonExt, WidgetExt},
+    },
+    tracing::{error, warn},
+};
+
+use crate::{
+    app::AppState,
+    playback::{
+        control::PlaybackController,
+        engine::{
+            PlaybackEngine,
+            PlaybackEvent::{self, TrackStarted},
+        },
+    },
+};
+
+/// Speeds offered by the selector.
+const SPEEDS: [f64; 6] = [0.5, 0.75, 1.0, 1.25, 1.5, 2.0];
+
+/// Index of the offered speed closest to `rate`.
+fn speed_index(rate: f64) -> u32 {
+    let index = SPEEDS
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| (*a - rate).abs().total_cmp(&(*b - rate).abs()))
+        .map_or(0, |(i, _)| i);
+    u32::try_from(index).unwrap_or(0)
+}
+
+/// Apply the speed picked in the selector to the current track.
+fn apply_selected_speed(playback: &PlaybackEngine, dropdown: &DropDown) {
+    let index = usize::try_from(dropdown.selected()).unwrap_or(usize::MAX);
+    let Some(rate) = SPEEDS.get(index).copied() else {
+        return;
+    };
+    if speed_index(playback.state().effective_playback_rate()) == dropdown.selected() {
+        return;
+    }
+    if let Err(e) = playback.set_playback_rate(rate) {
+        error!(error = %e, rate, "Failed to set playback speed");
+    }
+}
+
+/// Show the speed of a newly started track in the selector.
+fn on_playback_event(playback: &PlaybackEngine, dropdown: &DropDown, event: &PlaybackEvent) {
+    if matches!(event, TrackStarted { .. }) {
+        dropdown.set_selected(speed_index(playback.state().effective_playback_rate()));
+    }
+}
+
+/// Persist the "Keep speed" preference, logging on failure.
+async fn save_remember_playback_rate(state: Arc<AppState>, remember: bool) {
+    if let Err(e) = state.storage.set_remember_playback_rate(remember).await {
+        warn!(error = %e, "Failed to save playback speed preference");
+    }
+}
+
+/// Build the playback speed selector with its "Keep speed" toggle.
+#[must_use]
+pub fn build_speed_control(state: &Arc<AppState>) -> Box {
+    let controls = Box::builder()
+        .orientation(Horizontal)
+        .spacing(6)
+        .halign(Center)
+        .build();
+
+    let labels: Vec<String> = SPEEDS.iter().map(|s| format!("{s}\u{d7}")).collect();
+    let label_refs: Vec<&str> = labels.iter().map(String::as_str).collect();
+    let dropdown = DropDown::from_strings(&label_refs);
+    dropdown.set_tooltip_text(Some("Playback speed (changes pitch)"));
+    dropdown.update_property(&[PropertyLabel("Playback speed")]);
+    dropdown.set_selected(speed_index(
+        state.playback.state().effective_playback_rate(),
+    ));
+    let playback_select = Arc::clone(&state.playback);
+    dropdown.connect_selected_notify(move |dd| {
+        apply_selected_speed(&playback_select, dd);
+    });
+    controls.append(&dropdown);
+
+    let keep = CheckButton::builder()
+        .label("Keep speed")
+        .tooltip_text("Keep this speed for the following tracks")
+        .active(state.playback.state().remember_playback_rate)
+        .build();
+    let state_keep = Arc::clone(state);
+    keep.connect_toggled(move |btn| {
+        let remember = btn.is_active();
+        if let Err(e) = state_keep.playback.set_remember_playback_rate(remember) {
+            error!(error = %e, "Failed to set playback speed preference");
+        }
+        spawn_future_local(save_remember_playback_rate(
+            Arc::clone(&state_keep),
+            remember,
+        ));
+    });
+    controls.append(&keep);
+
+    let rx = state.playback.subscribe();
+    let playback = Arc::clone(&state.playback);
+    MainContext::default().spawn_local(async move {
+        while let Ok(event) = rx.recv().await {
+            on_playback_event(&playback, &dropdown, &event);
+        }
+    });
+
+    controls
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::player::speed::speed_index;
+
+    #[test]
+    fn speed_index_picks_closest_speed() {
+        assert_eq!(speed_index(1.0), 2, "Normal speed is the third entry");
+        assert_eq!(speed_index(2.0), 5, "Fastest speed is the last entry");
+        assert_eq!(speed_index(1.3), 3, "Rates snap to the closest entry");
+        assert_eq!(
+            speed_index(9.0),
+            5,
+            "Rates above the range pick the fastest"
+        );
+    }
+}
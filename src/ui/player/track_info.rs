@@ -0,0 +1,224 @@
+//! Details of the playing track shown by the player views.
+//!
+//! The panel, mini player, now-playing view, and track notifications all
+//! show the same details. They are looked up once into a [`TrackInfo`], so
+//! callers pick fields by name instead of by tuple position, where a title
+//! and an artist are easily swapped.
+//!
+//! [`follow_current_track`] looks up the details of every track that starts
+//! and keeps them in `AppState`, which answers "what is playing" with a
+//! [`PlaybackSnapshot`] instead of each view following the events itself.
+
+use std::sync::Arc;
+
+use libadwaita::glib::spawn_future_local;
+
+use crate::{
+    app::AppState,
+    playback::{
+        control::PlaybackController,
+        engine::{
+            PlaybackEvent::{self, Stopped, TrackStarted},
+            PlaybackStatus,
+        },
+        layout::{AudioLayout, format_channel_label},
+    },
+    storage::{Album, Storage, TrackAudio, database::SqliteStorage},
+};
+
+/// The playing track and its details.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurrentTrack {
+    /// Track ID.
+    pub track_id: i64,
+    /// Display details of the track.
+    pub info: TrackInfo,
+}
+
+/// What is playing, as returned by `AppState::current_playback`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaybackSnapshot {
+    /// Playing or paused track, `None` when stopped or while its details
+    /// are looked up.
+    pub track: Option<CurrentTrack>,
+    /// Whether playback is playing, paused, or stopped.
+    pub status: PlaybackStatus,
+    /// Elapsed time of the track in seconds.
+    pub position: f64,
+    /// Duration of the track in seconds, `0.0` if unknown.
+    pub duration: f64,
+}
+
+/// Display details of one track.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackInfo {
+    /// Track title.
+    pub title: String,
+    /// Track artist, empty if unknown.
+    pub artist: String,
+    /// Album title with its year, empty for tracks without an album.
+    pub album: String,
+    /// Album artwork file, if any.
+    pub artwork_path: Option<String>,
+    /// Format line, e.g. `FLAC • 24-bit / 96.0 kHz • Stereo`.
+    pub format: String,
+    /// Album ID, `-1` for tracks without an album.
+    pub album_id: i64,
+}
+
+impl TrackInfo {
+    /// Placeholder for a track that cannot be read from the library.
+    #[must_use]
+    pub fn unknown(track_id: i64) -> Self {
+        Self {
+            title: format!("Track #{track_id}"),
+            artist: String::new(),
+            album: String::new(),
+            artwork_path: None,
+            format: String::new(),
+            album_id: -1,
+        }
+    }
+}
+
+/// Album title followed by the year it is shown with, if known.
+fn album_label(album: &Album, use_original_year: bool) -> String {
+    album.display_year(use_original_year).map_or_else(
+        || album.title.clone(),
+        |year| format!("{} ({year})", album.title),
+    )
+}
+
+/// Format line of a track: format, bit depth if any, sample rate, and
+/// channels.
+fn format_line(audio: &TrackAudio) -> String {
+    let channel_label = format_channel_label(AudioLayout::from_count(
+        u32::try_from(audio.channels).unwrap_or(0),
+    ));
+    let sample_rate_khz = f64::from(audio.sample_rate) / 1000.0;
+    audio.bit_depth.map_or_else(
+        || {
+            format!(
+                "{} \u{2022} {sample_rate_khz:.1} kHz \u{2022} {channel_label}",
+                audio.format,
+            )
+        },
+        |depth| {
+            format!(
+                "{} \u{2022} {depth}-bit / {sample_rate_khz:.1} kHz \u{2022} {channel_label}",
+                audio.format,
+            )
+        },
+    )
+}
+
+/// Look up the display details of `track_id`.
+///
+/// # Returns
+///
+/// The details, or [`TrackInfo::unknown`] if the track cannot be read.
+/// Artist and album are left empty when they cannot be read.
+pub async fn resolve_track_info(storage: &SqliteStorage, track_id: i64) -> TrackInfo {
+    let Ok(Some(track)) = storage.get_track(track_id).await else {
+        return TrackInfo::unknown(track_id);
+    };
+    let album_id = track.audio.album_id.unwrap_or(-1);
+
+    let artist = match track.audio.artist_id {
+        Some(aid) => match storage.get_artist(aid).await {
+            Ok(Some(a)) => a.name,
+            _ => String::new(),
+        },
+        None => String::new(),
+    };
+
+    let (album, artwork_path) = match album_id {
+        aid if aid >= 0 => match storage.get_album(aid).await {
+            Ok(Some(album)) => (
+                album_label(&album, storage.get_use_original_year()),
+                album.artwork_path,
+            ),
+            _ => (String::new(), None),
+        },
+        _ => (String::new(), None),
+    };
+
+    TrackInfo {
+        format: format_line(&track.audio),
+        title: track.title,
+        artist,
+        album,
+        artwork_path,
+        album_id,
+    }
+}
+
+/// Keep the details of the playing track in `state` up to date.
+///
+/// The details of each track that starts are looked up once and kept
+/// until the next track starts or playback stops.
+pub fn follow_current_track(state: &Arc<AppState>) {
+    let rx = state.playback.subscribe();
+    let state = Arc::clone(state);
+    spawn_future_local(async move {
+        while let Ok(event) = rx.recv().await {
+            update_current_track(&state, &event).await;
+        }
+    });
+}
+
+/// Update the playing track after `event`.
+async fn update_current_track(state: &AppState, event: &PlaybackEvent) {
+    match *event {
+        TrackStarted { track_id } => {
+            let info = resolve_track_info(&state.storage, track_id).await;
+            if state.playback.state().current_track_id == Some(track_id) {
+                state
+                    .current_track_tx
+                    .send_replace(Some(CurrentTrack { track_id, info }));
+            }
+        }
+        Stopped => {
+            state.current_track_tx.send_replace(None);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{storage::TrackAudio, ui::player::track_info::format_line};
+
+    /// Audio details of a stereo track in `format`.
+    fn audio(format: &str, bit_depth: Option<i32>) -> TrackAudio {
+        TrackAudio {
+            file_path: "/music/a.flac".to_string(),
+            content_hash: None,
+            format: format.to_string(),
+            sample_rate: 96000,
+            bit_depth,
+            channels: 2,
+            codec: String::new(),
+            lossless: bit_depth.is_some(),
+            bitrate: None,
+            album_id: None,
+            artist_id: None,
+            file_size: 0,
+            last_modified: String::new(),
+        }
+    }
+
+    #[test]
+    fn format_line_shows_bit_depth_when_known() {
+        let lossless = format_line(&audio("FLAC", Some(24)));
+        assert!(
+            lossless.starts_with("FLAC \u{2022} 24-bit / 96.0 kHz \u{2022} "),
+            "Got {lossless}"
+        );
+        let lossy = format_line(&audio("MP3", None));
+        assert!(
+            lossy.starts_with("MP3 \u{2022} 96.0 kHz \u{2022} "),
+            "Got {lossy}"
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! Waveform overview shown above the seek slider of the player panel.
+//!
+//! The overview of the playing track is computed in the background and
+//! cached on disk, so the plain slider is all there is until it is ready.
+//! The played part is drawn in full colour, and clicking a point seeks
+//! there. The overview can be turned off with a View > Display row of the
+//! preferences, built here as well.
+
+use std::{cell::RefCell, path::Path, rc::Rc, sync::Arc};
+
+use {
+    async_channel::{Sender, unbounded},
+    libadwaita::{
+        SwitchRow,
+        glib::{MainContext, spawn_future_local},
+        gtk::{
+            DrawingArea, GestureClick, accessible::Property::Label as PropertyLabel,
+            cairo::Context, gdk::RGBA,
+        },
+        prelude::{
+            AccessibleExtManual, ActionRowExt, DrawingAreaExtManual, GestureSingleExt,
+            PreferencesRowExt, WidgetExt,
+        },
+    },
+    num_traits::cast::AsPrimitive,
+    tokio::{spawn, task::spawn_blocking},
+    tracing::{error, info, warn},
+};
+
+use crate::{
+    app::AppState,
+    library::waveform::load_waveform,
+    playback::{
+        control::PlaybackController,
+        engine::{
+            PlaybackEngine,
+            PlaybackEvent::{self, PositionTick, Seeked, Stopped, TrackStarted},
+        },
+    },
+    storage::{Storage, database::SqliteStorage},
+};
+
+/// Height of the waveform in pixels.
+const WAVEFORM_HEIGHT: i32 = 40;
+
+/// Opacity of the bars past the playback position.
+const UNPLAYED_ALPHA: f32 = 0.35;
+
+/// Waveform of the current track and the playback progress through it.
+#[derive(Default)]
+struct WaveformState {
+    /// Track the waveform belongs to.
+    track_id: Option<i64>,
+    /// Bar heights from `0` to `255`, empty until loaded.
+    bars: Vec<u8>,
+    /// Played fraction of the track from `0.0` to `1.0`.
+    progress: f64,
+}
+
+/// Drawing area of the waveform and the state it draws.
+#[derive(Clone)]
+struct WaveformView {
+    /// Area the bars are drawn in, hidden without a waveform.
+    area: DrawingArea,
+    /// Waveform currently shown.
+    state: Rc<RefCell<WaveformState>>,
+}
+
+impl WaveformView {
+    /// Forget the shown waveform and hide the area.
+    fn clear(&self, track_id: Option<i64>) {
+        *self.state.borrow_mut() = WaveformState {
+            track_id,
+            ..WaveformState::default()
+        };
+        self.area.set_visible(false);
+    }
+
+    /// Show bars loaded for `track_id` if it is still the current track.
+    fn show(&self, track_id: i64, bars: Option<Vec<u8>>) {
+        let mut state = self.state.borrow_mut();
+        if state.track_id != Some(track_id) {
+            return;
+        }
+        let Some(bars) = bars.filter(|b| !b.is_empty()) else {
+            return;
+        };
+        state.bars = bars;
+        drop(state);
+        self.area.set_visible(true);
+        self.area.queue_draw();
+    }
+
+    /// Follow the playback position.
+    fn update_position(&self, elapsed: f64, duration: f64) {
+        let progress = if duration > 0.0 {
+            (elapsed / duration).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let mut state = self.state.borrow_mut();
+        if state.bars.is_empty() || (state.progress - progress).abs() < f64::EPSILON {
+            return;
+        }
+        state.progress = progress;
+        drop(state);
+        self.area.queue_draw();
+    }
+}
+
+/// Build the waveform overview, seeking on click.
+///
+/// The area stays hidden until the waveform of the playing track is ready.
+#[must_use]
+pub fn build_waveform(state: &Arc<AppState>) -> DrawingArea {
+    let area = DrawingArea::builder()
+        .content_height(WAVEFORM_HEIGHT)
+        .hexpand(true)
+        .visible(false)
+        .tooltip_text("Click to seek")
+        .build();
+    area.update_property(&[PropertyLabel("Track waveform")]);
+
+    let view = WaveformView {
+        area: area.clone(),
+        state: Rc::new(RefCell::new(WaveformState::default())),
+    };
+    let draw_state = Rc::clone(&view.state);
+    area.set_draw_func(move |area, cr, width, height| {
+        draw_waveform(area, cr, width, height, &draw_state.borrow());
+    });
+
+    let gesture = GestureClick::new();
+    gesture.set_button(0);
+    let playback = Arc::clone(&state.playback);
+    let area_click = area.clone();
+    gesture.connect_pressed(move |_, _, x, _| {
+        seek_to_fraction(&playback, x / f64::from(area_click.width().max(1)));
+    });
+    area.add_controller(gesture);
+
+    spawn_waveform_listeners(state, view);
+    area
+}
+
+/// Build the row showing or hiding the waveform above the seek slider.
+pub fn build_waveform_row(state: &Arc<AppState>) -> SwitchRow {
+    let waveform_row = SwitchRow::new();
+    waveform_row.set_title("Waveform Seek Bar");
+    waveform_row.set_subtitle(
+        "Show an overview of the playing track above the seek slider, starting with the \
+         next track",
+    );
+    waveform_row.set_active(state.storage.get_show_waveform());
+
+    let state_waveform = Arc::clone(state);
+    waveform_row.connect_active_notify(move |row| {
+        let enabled = row.is_active();
+        info!(enabled, "Waveform preference changed");
+        spawn_future_local(save_waveform_setting(Arc::clone(&state_waveform), enabled));
+    });
+
+    waveform_row
+}
+
+/// Persist the waveform preference, logging on failure.
+async fn save_waveform_setting(state: Arc<AppState>, enabled: bool) {
+    if let Err(e) = state.storage.set_show_waveform(enabled).await {
+        error!(error = %e, "Failed to save waveform preference");
+    }
+}
+
+/// Seek to `fraction` of the current track.
+fn seek_to_fraction(playback: &PlaybackEngine, fraction: f64) {
+    let duration = playback.state().duration_seconds;
+    if duration <= 0.0 {
+        return;
+    }
+    if let Err(e) = playback.seek_to(fraction.clamp(0.0, 1.0) * duration) {
+        error!(error = %e, "Seek failed");
+    }
+}
+
+/// Listen for playback events and loaded waveforms.
+fn spawn_waveform_listeners(state: &Arc<AppState>, view: WaveformView) {
+    let (bars_tx, bars_rx) = unbounded::<(i64, Option<Vec<u8>>)>();
+
+    let ev_rx = state.playback.subscribe();
+    let ev_view = view.clone();
+    let playback = Arc::clone(&state.playback);
+    let storage = Arc::clone(&state.storage);
+    MainContext::default().spawn_local(async move {
+        while let Ok(event) = ev_rx.recv().await {
+            on_waveform_event(&event, &ev_view, &playback, &storage, &bars_tx);
+        }
+    });
+
+    MainContext::default().spawn_local(async move {
+        while let Ok((track_id, bars)) = bars_rx.recv().await {
+            view.show(track_id, bars);
+        }
+    });
+}
+
+/// Handle a single playback event for the waveform.
+fn on_waveform_event(
+    event: &PlaybackEvent,
+    view: &WaveformView,
+    playback: &PlaybackEngine,
+    storage: &Arc<SqliteStorage>,
+    bars_tx: &Sender<(i64, Option<Vec<u8>>)>,
+) {
+    match event {
+        TrackStarted { track_id } => {
+            view.clear(Some(*track_id));
+            if storage.get_show_waveform() {
+                spawn_load_waveform(Arc::clone(storage), *track_id, bars_tx.clone());
+            }
+        }
+        Stopped => view.clear(None),
+        PositionTick {
+            elapsed_seconds,
+            duration_seconds,
+        } => view.update_position(*elapsed_seconds, *duration_seconds),
+        Seeked { position_seconds } => {
+            view.update_position(*position_seconds, playback.state().duration_seconds);
+        }
+        _ => {}
+    }
+}
+
+/// Load or compute the waveform of a track in the background and send it back.
+fn spawn_load_waveform(
+    storage: Arc<SqliteStorage>,
+    track_id: i64,
+    tx: Sender<(i64, Option<Vec<u8>>)>,
+) {
+    spawn(async move {
+        let audio = match storage.get_track(track_id).await {
+            Ok(Some(track)) => track.audio,
+            Ok(None) => return,
+            Err(e) => {
+                warn!(error = %e, track_id, "Failed to load track for waveform");
+                return;
+            }
+        };
+        let bars = match spawn_blocking(move || {
+            load_waveform(Path::new(&audio.file_path), audio.content_hash.as_deref())
+        })
+        .await
+        {
+            Ok(bars) => bars,
+            Err(e) => {
+                warn!(error = %e, track_id, "Waveform computation panicked");
+                None
+            }
+        };
+        if let Err(e) = tx.send((track_id, bars)).await {
+            error!(error = %e, "Failed to send waveform");
+        }
+    });
+}
+
+/// Draw one centred bar per waveform value, dimming the unplayed part.
+fn draw_waveform(area: &DrawingArea, cr: &Context, width: i32, height: i32, state: &WaveformState) {
+    if state.bars.is_empty() {
+        return;
+    }
+    let width = f64::from(width);
+    let height = f64::from(height);
+    let count: f64 = state.bars.len().as_();
+    let slot = width / count;
+    let played = state.progress * width;
+    let bar = |(i, value): (usize, &u8)| {
+        let index: f64 = i.as_();
+        let bar_height = (f64::from(*value) / 255.0 * height).max(1.0);
+        (slot * index, (height - bar_height) / 2.0, bar_height)
+    };
+
+    let (played_bars, unplayed_bars): (Vec<_>, Vec<_>) = state
+        .bars
+        .iter()
+        .enumerate()
+        .map(bar)
+        .partition(|&(x, _, _)| x < played);
+
+    let color = area.color();
+    fill_bars(cr, &played_bars, slot, &color, color.alpha());
+    fill_bars(cr, &unplayed_bars, slot, &color, UNPLAYED_ALPHA);
+}
+
+/// Fill `bars` given as `(x, y, height)` in `color` at `alpha`.
+fn fill_bars(cr: &Context, bars: &[(f64, f64, f64)], slot: f64, color: &RGBA, alpha: f32) {
+    cr.set_source_rgba(
+        f64::from(color.red()),
+        f64::from(color.green()),
+        f64::from(color.blue()),
+        f64::from(alpha),
+    );
+    for &(x, y, height) in bars {
+        cr.rectangle(x, y, (slot * 0.7).max(1.0), height);
+    }
+    if let Err(e) = cr.fill() {
+        warn!(error = %e, "Failed to draw waveform");
+    }
+}
@@ -0,0 +1,70 @@
+//! "Queue replaced" toast with an "Undo" button.
+//!
+//! Playing an album or a list of tracks replaces the queue. When that drops
+//! tracks from it, a toast offers to put the previous queue back for a few
+//! seconds, so a carefully built queue is not lost to a stray click.
+
+use std::sync::Arc;
+
+use {
+    async_channel::Receiver,
+    libadwaita::{Toast, ToastOverlay, ToastPriority::High, glib::spawn_future_local},
+    tracing::{info, warn},
+};
+
+use crate::{
+    app::AppState,
+    playback::{
+        control::PlaybackController,
+        engine::PlaybackEvent::{self, QueueReplaced},
+    },
+};
+
+/// Seconds the "Undo" button stays offered.
+const UNDO_TIMEOUT_SECS: u32 = 8;
+
+/// Spawn a future offering to undo each replacement of the queue.
+///
+/// A newer toast dismisses the one before, since only the last replacement
+/// can be undone.
+pub fn listen_for_queue_replacements(state: &Arc<AppState>, toast_overlay: &ToastOverlay) {
+    let rx = state.playback.subscribe();
+    spawn_future_local(offer_undo(rx, Arc::clone(state), toast_overlay.clone()));
+}
+
+/// Show an undo toast for every queue replacement until the engine closes
+/// its event channel.
+async fn offer_undo(rx: Receiver<PlaybackEvent>, state: Arc<AppState>, overlay: ToastOverlay) {
+    let mut shown: Option<Toast> = None;
+    while let Ok(event) = rx.recv().await {
+        let QueueReplaced { previous_len } = event else {
+            continue;
+        };
+        if let Some(toast) = shown.take() {
+            toast.dismiss();
+        }
+        let toast = queue_replaced_toast(&state, previous_len);
+        overlay.add_toast(toast.clone());
+        shown = Some(toast);
+    }
+}
+
+/// Toast announcing that a queue of `previous_len` tracks was replaced.
+fn queue_replaced_toast(state: &Arc<AppState>, previous_len: usize) -> Toast {
+    let noun = if previous_len == 1 { "track" } else { "tracks" };
+    let toast = Toast::builder()
+        .title(format!("Queue of {previous_len} {noun} replaced"))
+        .button_label("Undo")
+        .priority(High)
+        .timeout(UNDO_TIMEOUT_SECS)
+        .build();
+    let state = Arc::clone(state);
+    toast.connect_button_clicked(move |_| {
+        info!("Undoing queue replacement");
+        if let Err(e) = state.playback.undo_queue_replace() {
+            warn!(error = %e, "Failed to restore the replaced queue");
+            state.show_toast(format!("Could not restore the queue: {e}"));
+        }
+    });
+    toast
+}
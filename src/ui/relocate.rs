@@ -0,0 +1,83 @@
+//! Moving a library directory to a new location.
+//!
+//! Each directory row of the Library preferences has a "Move…" button for
+//! when the music was moved to another disk or mount point. After the new
+//! folder is picked, every stored path under the old one is rewritten, so
+//! tracks keep their favorites and edited tags instead of being imported
+//! again as new files.
+
+use std::{path::PathBuf, sync::Arc};
+
+use {
+    libadwaita::{
+        ActionRow,
+        glib::{object::Cast, spawn_future_local},
+        gtk::{Button, FileDialog, Window},
+        prelude::{ButtonExt, FileExt, ObjectExt, PreferencesRowExt, WidgetExt},
+    },
+    tracing::{info, warn},
+};
+
+use crate::app::AppState;
+
+/// Build the button moving the directory shown in `row`.
+///
+/// The row title is the directory path and is updated after a move.
+#[must_use]
+pub fn build_move_button(state: &Arc<AppState>, row: &ActionRow) -> Button {
+    let button = Button::builder()
+        .label("Move…")
+        .tooltip_text("Point this directory and its tracks to a new location")
+        .css_classes(["flat"])
+        .build();
+    let state = Arc::clone(state);
+    let weak_row = row.downgrade();
+    button.connect_clicked(move |btn| {
+        let Some(row) = weak_row.upgrade() else {
+            return;
+        };
+        let parent = btn.root().and_then(|r| r.downcast::<Window>().ok());
+        spawn_future_local(move_directory(Arc::clone(&state), row, parent));
+    });
+    button
+}
+
+/// Ask for the new location of the directory in `row` and move it there.
+async fn move_directory(state: Arc<AppState>, row: ActionRow, parent: Option<Window>) {
+    let old_path = PathBuf::from(row.title().as_str());
+    let dialog = FileDialog::builder()
+        .title("Select New Location")
+        .accept_label("Move Here")
+        .build();
+    let folder = match dialog.select_folder_future(parent.as_ref()).await {
+        Ok(folder) => folder,
+        Err(e) => {
+            info!(error = %e, "Directory move cancelled");
+            return;
+        }
+    };
+    let Some(new_path) = folder.path() else {
+        warn!("Selected folder has no local path");
+        return;
+    };
+
+    let message = match state.storage.remap_directory(&old_path, &new_path).await {
+        Ok(report) => {
+            row.set_title(&new_path.display().to_string());
+            if let Err(e) = state.refresh_tx.send(()) {
+                warn!(error = %e, "Failed to refresh library after directory move");
+            }
+            format!("Moved {} tracks to {}", report.tracks, new_path.display())
+        }
+        Err(e) => {
+            warn!(
+                error = %e,
+                old_path = %old_path.display(),
+                new_path = %new_path.display(),
+                "Failed to move library directory"
+            );
+            format!("Could not move directory: {e}")
+        }
+    };
+    state.send_toast(message).await;
+}
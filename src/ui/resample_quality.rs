@@ -0,0 +1,57 @@
+//! Audio > Output row choosing the resampler quality.
+//!
+//! Higher quality costs more CPU. The engine picks up the new quality from
+//! the next track.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{ComboRow, glib::spawn_future_local, gtk::StringList, prelude::ComboRowExt},
+    tracing::{error, warn},
+};
+
+use crate::{
+    app::AppState,
+    playback::{control::PlaybackController, resampler::ResampleQuality},
+    storage::database::SqliteStorage,
+};
+
+/// Build the row choosing the resampler's quality/CPU trade-off.
+pub fn build_resample_quality_row(state: &Arc<AppState>) -> ComboRow {
+    let labels: Vec<&str> = ResampleQuality::ALL.iter().map(|q| q.label()).collect();
+    let model = StringList::new(&labels);
+    let quality_row = ComboRow::builder()
+        .title("Resampler Quality")
+        .subtitle("Higher quality uses more CPU; applies from the next track")
+        .model(&model)
+        .build();
+    let current = state.storage.get_resample_quality();
+    let position = ResampleQuality::ALL.iter().position(|q| *q == current);
+    quality_row.set_selected(position.and_then(|p| u32::try_from(p).ok()).unwrap_or(0));
+
+    let state_quality = Arc::clone(state);
+    quality_row.connect_selected_notify(move |row| {
+        let Some(quality) = usize::try_from(row.selected())
+            .ok()
+            .and_then(|i| ResampleQuality::ALL.get(i).copied())
+        else {
+            return;
+        };
+        if let Err(e) = state_quality.playback.set_resample_quality(quality) {
+            warn!(error = %e, "Failed to set resampler quality");
+        }
+        spawn_future_local(save_resample_quality_setting(
+            Arc::clone(&state_quality.storage),
+            quality,
+        ));
+    });
+
+    quality_row
+}
+
+/// Persist the resampler quality, logging on failure.
+async fn save_resample_quality_setting(storage: Arc<SqliteStorage>, quality: ResampleQuality) {
+    if let Err(e) = storage.set_resample_quality(quality).await {
+        error!(error = %e, "Failed to save resampler quality setting");
+    }
+}
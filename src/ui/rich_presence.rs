@@ -0,0 +1,392 @@
+//! Discord Rich Presence showing the playing track.
+//!
+//! When enabled, the title, artist, and album of the playing track are
+//! published to a Discord client running on this machine over its local
+//! IPC socket, together with the elapsed time. If Discord is not running,
+//! updates are dropped silently and connecting is retried while a track
+//! plays, so starting Discord later picks up the current track. Presence
+//! is cleared when playback stops. The feature is off by default and also
+//! needs the application ID of a Discord application to publish under.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use {
+    async_channel::Receiver,
+    libadwaita::{
+        EntryRow, PreferencesGroup, PreferencesPage, SwitchRow,
+        glib::spawn_future_local,
+        prelude::{
+            EditableExt, EntryRowExt, PreferencesGroupExt, PreferencesPageExt, PreferencesRowExt,
+        },
+    },
+    parking_lot::Mutex,
+    serde_json::{Map, Value},
+    tokio::{spawn, task::spawn_blocking},
+    tracing::{debug, error, info},
+};
+
+use crate::{
+    app::AppState,
+    playback::{
+        control::PlaybackController,
+        engine::{
+            PlaybackEngine,
+            PlaybackEvent::{self, Paused, PositionTick, Resumed, Seeked, Stopped, TrackStarted},
+        },
+    },
+    storage::{Storage, database::SqliteStorage},
+    ui::discord_ipc::DiscordIpc,
+};
+
+/// Activity type shown as "Listening to".
+const ACTIVITY_LISTENING: u8 = 2;
+
+/// Longest activity text Discord accepts, in characters.
+const MAX_FIELD_CHARS: usize = 128;
+
+/// Minimum delay between connection attempts while Discord is not running.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Track shown in the presence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Presence {
+    /// Track title.
+    pub title: String,
+    /// Track artist, if known.
+    pub artist: Option<String>,
+    /// Album title, if known.
+    pub album: Option<String>,
+    /// Playback position in seconds.
+    pub elapsed_seconds: f64,
+    /// Track duration in seconds.
+    pub duration_seconds: f64,
+    /// Whether playback is paused.
+    pub paused: bool,
+}
+
+impl Presence {
+    /// Discord activity for this presence at Unix time `now`.
+    ///
+    /// The elapsed time is only shown while playing.
+    #[must_use]
+    pub fn activity(&self, now: i64) -> Value {
+        let mut activity = Map::new();
+        activity.insert("type".to_string(), ACTIVITY_LISTENING.into());
+        activity.insert("details".to_string(), truncate(&self.title).into());
+        if let Some(state) = self.state_line() {
+            activity.insert("state".to_string(), truncate(&state).into());
+        }
+        if !self.paused {
+            activity.insert("timestamps".to_string(), self.timestamps(now));
+        }
+        Value::Object(activity)
+    }
+
+    /// Second line of the presence, e.g. `by Artist — Album`.
+    fn state_line(&self) -> Option<String> {
+        let line = match (&self.artist, &self.album) {
+            (Some(artist), Some(album)) => format!("by {artist} \u{2014} {album}"),
+            (Some(artist), None) => format!("by {artist}"),
+            (None, Some(album)) => album.clone(),
+            (None, None) => String::new(),
+        };
+        match (self.paused, line.is_empty()) {
+            (true, true) => Some("Paused".to_string()),
+            (true, false) => Some(format!("Paused \u{2014} {line}")),
+            (false, true) => None,
+            (false, false) => Some(line),
+        }
+    }
+
+    /// Start and end of the track as Unix times, given the time `now`.
+    fn timestamps(&self, now: i64) -> Value {
+        let start = now.saturating_sub(whole_seconds(self.elapsed_seconds));
+        let mut timestamps = Map::new();
+        timestamps.insert("start".to_string(), start.into());
+        if self.duration_seconds > 0.0 {
+            let end = start.saturating_add(whole_seconds(self.duration_seconds));
+            timestamps.insert("end".to_string(), end.into());
+        }
+        Value::Object(timestamps)
+    }
+}
+
+/// Connection state shared by the presence updates.
+#[derive(Default)]
+struct PresenceClient {
+    /// Open connection, if Discord is running.
+    ipc: Option<DiscordIpc>,
+    /// Application ID the connection was opened for.
+    client_id: String,
+    /// When connecting was last attempted.
+    last_attempt: Option<Instant>,
+}
+
+impl PresenceClient {
+    /// Show `activity`, or clear the presence when `None`.
+    ///
+    /// Connects first if needed. Failures are logged at debug level and
+    /// drop the connection, to be retried on a later update.
+    fn publish(&mut self, client_id: &str, activity: Option<&Value>) {
+        if self.client_id != client_id {
+            self.disconnect();
+            self.client_id = client_id.to_string();
+        }
+        if self.ipc.is_none() && self.should_retry() {
+            self.try_connect();
+        }
+        let Some(ipc) = self.ipc.as_mut() else {
+            return;
+        };
+        if let Err(e) = ipc.set_activity(activity) {
+            debug!(error = %e, "Lost connection to Discord");
+            self.ipc = None;
+        }
+    }
+
+    /// Whether no connection is open and the reconnect delay has passed.
+    fn should_retry(&self) -> bool {
+        self.ipc.is_none()
+            && self
+                .last_attempt
+                .is_none_or(|at| at.elapsed() >= RECONNECT_INTERVAL)
+    }
+
+    /// Attempt to connect to Discord.
+    fn try_connect(&mut self) {
+        self.last_attempt = Some(Instant::now());
+        match DiscordIpc::connect(&self.client_id) {
+            Ok(ipc) => {
+                info!("Connected to Discord for Rich Presence");
+                self.ipc = Some(ipc);
+            }
+            Err(e) => debug!(error = %e, "Discord is not available"),
+        }
+    }
+
+    /// Close the connection, which also clears the presence.
+    fn disconnect(&mut self) {
+        self.ipc = None;
+        self.last_attempt = None;
+    }
+}
+
+/// Build the Discord group on the Scrobbling page.
+pub fn build_rich_presence_group(page: &PreferencesPage, state: &Arc<AppState>) {
+    let group = PreferencesGroup::new();
+    group.set_title("Discord");
+    group.set_description(Some(
+        "Show the playing track on your Discord profile while Discord is running",
+    ));
+
+    let enable_row = SwitchRow::new();
+    enable_row.set_title("Share Listening Activity");
+    enable_row.set_active(state.storage.get_rich_presence_enabled());
+    let storage = Arc::clone(&state.storage);
+    enable_row.connect_active_notify(move |row| {
+        spawn_future_local(save_enabled(Arc::clone(&storage), row.is_active()));
+    });
+
+    let client_id_row = EntryRow::new();
+    client_id_row.set_title("Application ID");
+    client_id_row.set_text(&state.storage.get_discord_client_id());
+    client_id_row.set_show_apply_button(true);
+    let storage = Arc::clone(&state.storage);
+    client_id_row.connect_apply(move |row| {
+        let client_id = row.text().trim().to_string();
+        spawn_future_local(save_client_id(Arc::clone(&storage), client_id));
+    });
+
+    group.add(&enable_row);
+    group.add(&client_id_row);
+    page.add(&group);
+}
+
+/// Persist whether Rich Presence is enabled, logging on failure.
+async fn save_enabled(storage: Arc<SqliteStorage>, enabled: bool) {
+    if let Err(e) = storage.set_rich_presence_enabled(enabled).await {
+        error!(error = %e, "Failed to save Rich Presence setting");
+    }
+}
+
+/// Persist the Discord application ID, logging on failure.
+async fn save_client_id(storage: Arc<SqliteStorage>, client_id: String) {
+    if let Err(e) = storage.set_discord_client_id(client_id).await {
+        error!(error = %e, "Failed to save Discord application ID");
+    }
+}
+
+/// Publish the playing track to Discord while Rich Presence is enabled.
+///
+/// # Arguments
+///
+/// * `playback` - Engine whose events are followed
+/// * `storage` - Storage for settings and track metadata
+pub fn spawn_rich_presence(playback: &PlaybackEngine, storage: Arc<SqliteStorage>) {
+    spawn(run_rich_presence(playback.subscribe(), storage));
+}
+
+/// Handle playback events until the engine closes its event channel.
+async fn run_rich_presence(rx: Receiver<PlaybackEvent>, storage: Arc<SqliteStorage>) {
+    let client = Arc::new(Mutex::new(PresenceClient::default()));
+    let mut presence: Option<Presence> = None;
+
+    while let Ok(event) = rx.recv().await {
+        let client_id = storage.get_discord_client_id();
+        if !storage.get_rich_presence_enabled() || client_id.is_empty() {
+            clear_presence(&client, &mut presence);
+            continue;
+        }
+        let retry = client.try_lock().is_some_and(|c| c.should_retry());
+        if apply_event(&event, &storage, &mut presence, retry).await {
+            let activity = presence.as_ref().map(|p| p.activity(unix_now()));
+            with_client(&client, move |c| c.publish(&client_id, activity.as_ref()));
+        }
+    }
+}
+
+/// Update `presence` for `event`.
+///
+/// Returns whether the presence must be published again. Position ticks
+/// only republish when `retry` is set, so that a Discord client started
+/// during playback picks up the current track.
+async fn apply_event(
+    event: &PlaybackEvent,
+    storage: &SqliteStorage,
+    presence: &mut Option<Presence>,
+    retry: bool,
+) -> bool {
+    match *event {
+        TrackStarted { track_id } => {
+            *presence = resolve_presence(storage, track_id).await;
+            true
+        }
+        PositionTick {
+            elapsed_seconds,
+            duration_seconds,
+        } => presence.as_mut().is_some_and(|p| {
+            p.elapsed_seconds = elapsed_seconds;
+            p.duration_seconds = duration_seconds;
+            retry
+        }),
+        Seeked { position_seconds } => presence.as_mut().is_some_and(|p| {
+            p.elapsed_seconds = position_seconds;
+            true
+        }),
+        Paused => presence.as_mut().is_some_and(|p| {
+            p.paused = true;
+            true
+        }),
+        Resumed => presence.as_mut().is_some_and(|p| {
+            p.paused = false;
+            true
+        }),
+        Stopped => presence.take().is_some(),
+        _ => false,
+    }
+}
+
+/// Forget the shown track and disconnect, which clears the presence.
+fn clear_presence(client: &Arc<Mutex<PresenceClient>>, presence: &mut Option<Presence>) {
+    if presence.take().is_some() {
+        with_client(client, PresenceClient::disconnect);
+    }
+}
+
+/// Run `op` on the client without blocking the async runtime.
+fn with_client(
+    client: &Arc<Mutex<PresenceClient>>,
+    op: impl FnOnce(&mut PresenceClient) + Send + 'static,
+) {
+    let client = Arc::clone(client);
+    spawn_blocking(move || op(&mut client.lock()));
+}
+
+/// Build the presence of `track_id` from library metadata.
+async fn resolve_presence(storage: &SqliteStorage, track_id: i64) -> Option<Presence> {
+    let track = match storage.get_track(track_id).await {
+        Ok(track) => track?,
+        Err(e) => {
+            debug!(error = %e, track_id, "Failed to load track for Rich Presence");
+            return None;
+        }
+    };
+    let artist = match track.audio.artist_id {
+        Some(id) => storage.get_artist(id).await.unwrap_or_default(),
+        None => None,
+    };
+    let album = match track.audio.album_id {
+        Some(id) => storage.get_album(id).await.unwrap_or_default(),
+        None => None,
+    };
+    Some(Presence {
+        title: track.title,
+        artist: artist.map(|a| a.name),
+        album: album.map(|a| a.title),
+        elapsed_seconds: 0.0,
+        duration_seconds: track.duration,
+        paused: false,
+    })
+}
+
+/// Shorten `text` to the length Discord accepts.
+fn truncate(text: &str) -> String {
+    text.chars().take(MAX_FIELD_CHARS).collect()
+}
+
+/// Whole seconds of a non-negative duration given in seconds.
+fn whole_seconds(seconds: f64) -> i64 {
+    Duration::try_from_secs_f64(seconds)
+        .map_or(0, |d| i64::try_from(d.as_secs()).unwrap_or(i64::MAX))
+}
+
+/// Current Unix time in whole seconds.
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| i64::try_from(d.as_secs()).unwrap_or(i64::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::rich_presence::Presence;
+
+    fn presence() -> Presence {
+        Presence {
+            title: "So What".to_string(),
+            artist: Some("Miles Davis".to_string()),
+            album: Some("Kind of Blue".to_string()),
+            elapsed_seconds: 30.0,
+            duration_seconds: 545.0,
+            paused: false,
+        }
+    }
+
+    #[test]
+    fn playing_activity_shows_elapsed_time() {
+        let activity = presence().activity(1_000);
+        assert_eq!(activity["details"], "So What");
+        assert_eq!(activity["state"], "by Miles Davis \u{2014} Kind of Blue");
+        assert_eq!(activity["timestamps"]["start"], 970, "Started 30 s ago");
+        assert_eq!(activity["timestamps"]["end"], 1_515, "Ends after duration");
+    }
+
+    #[test]
+    fn paused_activity_has_no_timestamps() {
+        let paused = Presence {
+            artist: None,
+            album: None,
+            paused: true,
+            ..presence()
+        };
+        let activity = paused.activity(1_000);
+        assert_eq!(activity["state"], "Paused");
+        assert!(
+            activity.get("timestamps").is_none(),
+            "Paused presence must not count up"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Scrobbling page of the preferences dialog.
+//!
+//! Scrobbling is opt-in: nothing is sent until the switch is turned on and
+//! credentials for the selected service are entered. Text fields commit on
+//! apply so partially typed tokens are never saved. How much of a track
+//! counts as a play is set here too.
+
+use std::{cell::Cell, rc::Rc, sync::Arc};
+
+use {
+    libadwaita::{
+        ComboRow, EntryRow, PasswordEntryRow, PreferencesDialog, PreferencesGroup, PreferencesPage,
+        PreferencesRow, SpinRow, SwitchRow,
+        glib::spawn_future_local,
+        gtk::{Adjustment, Editable, StringList, Widget},
+        prelude::{
+            ActionRowExt, ComboRowExt, EditableExt, EntryRowExt, IsA, ObjectExt,
+            PreferencesDialogExt, PreferencesGroupExt, PreferencesPageExt, PreferencesRowExt,
+        },
+    },
+    num_traits::cast::cast,
+    parking_lot::Mutex,
+    tracing::{error, info},
+};
+
+use crate::{
+    app::AppState,
+    library::{
+        play_threshold::PlayThreshold,
+        scrobble::{ScrobbleService, ScrobbleSettings},
+    },
+    storage::database::SqliteStorage,
+    ui::rich_presence::build_rich_presence_group,
+};
+
+/// Scrobble settings as edited on the page, shared by all rows.
+struct ScrobblePageState {
+    /// Storage the settings are persisted to.
+    storage: Arc<SqliteStorage>,
+    /// Settings as currently shown on the page.
+    current: Mutex<ScrobbleSettings>,
+}
+
+impl ScrobblePageState {
+    /// Update the page settings and persist them in the background.
+    fn commit(&self, update: impl FnOnce(&mut ScrobbleSettings)) {
+        let mut current = self.current.lock();
+        update(&mut current);
+        let settings = current.clone();
+        drop(current);
+        spawn_future_local(save_scrobble_settings(Arc::clone(&self.storage), settings));
+    }
+}
+
+/// Persist scrobble settings, logging on failure.
+async fn save_scrobble_settings(storage: Arc<SqliteStorage>, settings: ScrobbleSettings) {
+    if let Err(e) = storage.set_scrobble_settings(settings).await {
+        error!(error = %e, "Failed to save scrobble settings");
+    }
+}
+
+/// Persist the play threshold, logging on failure.
+async fn save_play_threshold(storage: Arc<SqliteStorage>, threshold: PlayThreshold) {
+    if let Err(e) = storage.set_play_threshold(threshold).await {
+        error!(error = %e, "Failed to save play threshold");
+    }
+}
+
+/// Build the Scrobbling preferences page.
+pub fn build_scrobbling_page(dialog: &PreferencesDialog, state: &Arc<AppState>) {
+    let page = PreferencesPage::new();
+    page.set_title("Scrobbling");
+    page.set_icon_name(Some("network-transmit-symbolic"));
+
+    let settings = state.storage.get_scrobble_settings();
+    let shared = Arc::new(ScrobblePageState {
+        storage: Arc::clone(&state.storage),
+        current: Mutex::new(settings.clone()),
+    });
+
+    let general_group = PreferencesGroup::new();
+    general_group.set_title("Scrobbling");
+    general_group.set_description(Some(
+        "Submit listens once a track counts as played; offline plays are queued",
+    ));
+    general_group.add(&build_enable_row(&shared, settings.enabled));
+    general_group.add(&build_service_combo(&shared, settings.service));
+    page.add(&general_group);
+
+    build_play_threshold_group(&page, state);
+
+    let listenbrainz_group = PreferencesGroup::new();
+    listenbrainz_group.set_title("ListenBrainz");
+    listenbrainz_group.add(&text_row(
+        &shared,
+        EntryRow::new(),
+        "Server",
+        &settings.listenbrainz_url,
+        |s, v| s.listenbrainz_url = v,
+    ));
+    listenbrainz_group.add(&text_row(
+        &shared,
+        PasswordEntryRow::new(),
+        "User Token",
+        &settings.listenbrainz_token,
+        |s, v| s.listenbrainz_token = v,
+    ));
+    page.add(&listenbrainz_group);
+
+    let lastfm_group = PreferencesGroup::new();
+    lastfm_group.set_title("Last.fm");
+    lastfm_group.add(&text_row(
+        &shared,
+        EntryRow::new(),
+        "API Key",
+        &settings.lastfm_api_key,
+        |s, v| s.lastfm_api_key = v,
+    ));
+    lastfm_group.add(&text_row(
+        &shared,
+        PasswordEntryRow::new(),
+        "API Secret",
+        &settings.lastfm_api_secret,
+        |s, v| s.lastfm_api_secret = v,
+    ));
+    lastfm_group.add(&text_row(
+        &shared,
+        PasswordEntryRow::new(),
+        "Session Key",
+        &settings.lastfm_session_key,
+        |s, v| s.lastfm_session_key = v,
+    ));
+    page.add(&lastfm_group);
+
+    build_rich_presence_group(&page, state);
+
+    dialog.add(&page);
+}
+
+/// Add the group setting when a track counts as played.
+fn build_play_threshold_group(page: &PreferencesPage, state: &Arc<AppState>) {
+    let group = PreferencesGroup::new();
+    group.set_title("Counted Plays");
+    group.set_description(Some(
+        "Tracks shorter than 30 seconds are never counted; seeking ahead does not count",
+    ));
+    let threshold = Rc::new(Cell::new(state.storage.get_play_threshold()));
+
+    let adjustment = Adjustment::new(
+        f64::from(threshold.get().percent),
+        1.0,
+        100.0,
+        5.0,
+        10.0,
+        0.0,
+    );
+    let percent_row = SpinRow::builder()
+        .title("Minimum Play")
+        .subtitle("Percent of a track to hear before it counts as played")
+        .adjustment(&adjustment)
+        .digits(0)
+        .build();
+    let (state_percent, threshold_percent) = (Arc::clone(state), Rc::clone(&threshold));
+    percent_row.connect_notify_local(Some("value"), move |row, _| {
+        let percent: u32 = cast(row.value()).unwrap_or(50);
+        update_play_threshold(&state_percent, &threshold_percent, |t| {
+            t.percent = percent;
+        });
+    });
+
+    let cap_row = SwitchRow::new();
+    cap_row.set_title("Count After Four Minutes");
+    cap_row.set_subtitle("Long tracks count once four minutes have been heard");
+    cap_row.set_active(threshold.get().four_minute_cap);
+    let (state_cap, threshold_cap) = (Arc::clone(state), Rc::clone(&threshold));
+    cap_row.connect_active_notify(move |row| {
+        let four_minute_cap = row.is_active();
+        update_play_threshold(&state_cap, &threshold_cap, |t| {
+            t.four_minute_cap = four_minute_cap;
+        });
+    });
+
+    group.add(&percent_row);
+    group.add(&cap_row);
+    page.add(&group);
+}
+
+/// Apply `change` to the shared play threshold and save it.
+fn update_play_threshold(
+    state: &Arc<AppState>,
+    threshold: &Cell<PlayThreshold>,
+    change: impl FnOnce(&mut PlayThreshold),
+) {
+    let mut updated = threshold.get();
+    change(&mut updated);
+    threshold.set(updated);
+    info!(threshold = ?updated, "Play threshold changed");
+    spawn_future_local(save_play_threshold(Arc::clone(&state.storage), updated));
+}
+
+/// Build the switch turning scrobbling on or off.
+fn build_enable_row(shared: &Arc<ScrobblePageState>, enabled: bool) -> SwitchRow {
+    let enable_row = SwitchRow::new();
+    enable_row.set_title("Enable Scrobbling");
+    enable_row.set_active(enabled);
+
+    let shared = Arc::clone(shared);
+    enable_row.connect_active_notify(move |row| {
+        let enabled = row.is_active();
+        shared.commit(|s| s.enabled = enabled);
+    });
+
+    enable_row
+}
+
+/// Build the selector for the receiving service.
+fn build_service_combo(shared: &Arc<ScrobblePageState>, selected: ScrobbleService) -> ComboRow {
+    let labels: Vec<&str> = ScrobbleService::ALL.iter().map(|s| s.label()).collect();
+    let service_combo = ComboRow::builder()
+        .title("Service")
+        .model(&StringList::new(&labels))
+        .build();
+    let position = ScrobbleService::ALL
+        .iter()
+        .position(|s| *s == selected)
+        .map_or(0, |p| u32::try_from(p).unwrap_or(0));
+    service_combo.set_selected(position);
+
+    let shared = Arc::clone(shared);
+    service_combo.connect_selected_notify(move |combo| {
+        if let Some(service) = ScrobbleService::ALL.get(combo.selected() as usize).copied() {
+            shared.commit(|s| s.service = service);
+        }
+    });
+
+    service_combo
+}
+
+/// Configure an entry row that commits its text on apply.
+fn text_row<R: IsA<EntryRow> + IsA<Editable> + IsA<PreferencesRow> + IsA<Widget>>(
+    shared: &Arc<ScrobblePageState>,
+    row: R,
+    title: &str,
+    value: &str,
+    apply: impl Fn(&mut ScrobbleSettings, String) + 'static,
+) -> R {
+    row.set_title(title);
+    row.set_text(value);
+    row.set_show_apply_button(true);
+
+    let shared = Arc::clone(shared);
+    row.connect_apply(move |row| {
+        let text = row.text().trim().to_string();
+        shared.commit(|s| apply(s, text));
+    });
+
+    row
+}
@@ -0,0 +1,251 @@
+//! Album search bar with format, quality, dynamic range, and decade filters.
+//!
+//! The text entry and the filter dropdowns together form one
+//! [`AlbumSearch`], published on [`AppState::album_search_tx`]. Every change
+//! triggers a library refresh; the album views then query storage with the
+//! search so filtering happens in SQL rather than on the loaded widgets.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        gtk::{
+            Box, DropDown, Orientation::Horizontal, SearchBar, SearchEntry, ToggleButton,
+            accessible::Property::Label as PropertyLabel,
+        },
+        prelude::{AccessibleExtManual, BoxExt, EditableExt, ToggleButtonExt, WidgetExt},
+    },
+    tracing::{info, warn},
+};
+
+use crate::{app::AppState, storage::AlbumSearch};
+
+/// Format filter choices; the first entry accepts every format.
+const FORMATS: [&str; 9] = [
+    "Any Format",
+    "FLAC",
+    "ALAC",
+    "WAV",
+    "AIFF",
+    "DSF",
+    "MP3",
+    "AAC",
+    "OPUS",
+];
+
+/// Quality filter choices as `(label, min bit depth, min sample rate)`.
+const QUALITIES: [(&str, Option<i32>, Option<i32>); 4] = [
+    ("Any Quality", None, None),
+    ("CD Quality or Better", Some(16), Some(44_100)),
+    ("Hi-Res (24/88.2+)", Some(24), Some(88_200)),
+    ("Hi-Res (24/176.4+)", Some(24), Some(176_400)),
+];
+
+/// Dynamic range filter choices as `(label, min DR, max DR)`, following the
+/// bands of the DR badges.
+const DYNAMIC_RANGES: [(&str, Option<i32>, Option<i32>); 4] = [
+    ("Any DR", None, None),
+    ("Wide (DR14+)", Some(14), None),
+    ("Average (DR8\u{2013}13)", Some(8), Some(13)),
+    ("Loud (DR7 or Lower)", None, Some(7)),
+];
+
+/// First decade offered by the decade filter.
+const FIRST_DECADE: i32 = 1950;
+
+/// Last decade offered by the decade filter.
+const LAST_DECADE: i32 = 2020;
+
+/// Widgets of the search bar whose values make up the search.
+#[derive(Clone)]
+struct SearchControls {
+    /// Free-text entry.
+    entry: SearchEntry,
+    /// Format filter.
+    format: DropDown,
+    /// Bit depth and sample rate filter.
+    quality: DropDown,
+    /// Measured dynamic range filter.
+    dynamic_range: DropDown,
+    /// Release decade filter.
+    decade: DropDown,
+}
+
+impl SearchControls {
+    /// Build the search described by the current widget values.
+    fn search(&self) -> AlbumSearch {
+        let format = usize::try_from(self.format.selected()).unwrap_or(0);
+        let quality = usize::try_from(self.quality.selected()).unwrap_or(0);
+        let (_, min_bit_depth, min_sample_rate) =
+            QUALITIES.get(quality).copied().unwrap_or(("", None, None));
+        let dynamic_range = usize::try_from(self.dynamic_range.selected()).unwrap_or(0);
+        let (_, min_dr, max_dr) = DYNAMIC_RANGES
+            .get(dynamic_range)
+            .copied()
+            .unwrap_or(("", None, None));
+        let decade = decade_start(self.decade.selected());
+        AlbumSearch {
+            query: self.entry.text().trim().to_string(),
+            formats: FORMATS
+                .get(format)
+                .filter(|_| format > 0)
+                .map(|f| vec![(*f).to_string()])
+                .unwrap_or_default(),
+            min_year: decade,
+            max_year: decade.map(|d| d + 9),
+            min_bit_depth,
+            min_sample_rate,
+            genre: None,
+            min_dr,
+            max_dr,
+        }
+    }
+}
+
+/// Build the album search bar and the header button revealing it.
+///
+/// Closing the bar clears the text and every filter.
+#[must_use]
+pub fn build_search_bar(state: &Arc<AppState>) -> (SearchBar, ToggleButton) {
+    let entry = SearchEntry::builder()
+        .placeholder_text("Search albums, artists, and tracks")
+        .hexpand(true)
+        .build();
+    entry.update_property(&[PropertyLabel("Search the library")]);
+
+    let decades: Vec<String> = ["Any Year".to_string()]
+        .into_iter()
+        .chain(
+            (FIRST_DECADE..=LAST_DECADE)
+                .step_by(10)
+                .map(|d| format!("{d}s")),
+        )
+        .collect();
+    let decade_labels: Vec<&str> = decades.iter().map(String::as_str).collect();
+    let quality_labels: Vec<&str> = QUALITIES.iter().map(|(label, ..)| *label).collect();
+    let dynamic_range_labels: Vec<&str> = DYNAMIC_RANGES.iter().map(|(label, ..)| *label).collect();
+    let controls = SearchControls {
+        entry,
+        format: filter_dropdown(&FORMATS, "Filter by format"),
+        quality: filter_dropdown(&quality_labels, "Filter by bit depth and sample rate"),
+        dynamic_range: filter_dropdown(&dynamic_range_labels, "Filter by dynamic range"),
+        decade: filter_dropdown(&decade_labels, "Filter by release decade"),
+    };
+
+    let content = Box::builder()
+        .orientation(Horizontal)
+        .spacing(6)
+        .width_request(360)
+        .build();
+    content.append(&controls.entry);
+    content.append(&controls.format);
+    content.append(&controls.quality);
+    content.append(&controls.dynamic_range);
+    content.append(&controls.decade);
+
+    let search_bar = SearchBar::builder()
+        .child(&content)
+        .show_close_button(true)
+        .build();
+    search_bar.connect_entry(&controls.entry);
+
+    let toggle = ToggleButton::builder()
+        .icon_name("system-search-symbolic")
+        .tooltip_text("Search")
+        .css_classes(["flat"])
+        .can_focus(true)
+        .build();
+    let toggle_bar = search_bar.clone();
+    toggle.connect_toggled(move |btn| toggle_bar.set_search_mode(btn.is_active()));
+
+    let entry_controls = controls.clone();
+    let entry_state = Arc::clone(state);
+    controls.entry.connect_search_changed(move |_| {
+        publish_search(&entry_state, entry_controls.search());
+    });
+    for dropdown in [
+        &controls.format,
+        &controls.quality,
+        &controls.dynamic_range,
+        &controls.decade,
+    ] {
+        let dropdown_controls = controls.clone();
+        let dropdown_state = Arc::clone(state);
+        dropdown.connect_selected_notify(move |_| {
+            publish_search(&dropdown_state, dropdown_controls.search());
+        });
+    }
+
+    let bar_toggle = toggle.clone();
+    search_bar.connect_search_mode_enabled_notify(move |bar| {
+        bar_toggle.set_active(bar.is_search_mode());
+        if !bar.is_search_mode() {
+            reset_controls(&controls);
+        }
+    });
+
+    (search_bar, toggle)
+}
+
+/// Build a compact dropdown for one filter.
+fn filter_dropdown(labels: &[&str], accessible_label: &str) -> DropDown {
+    let dropdown = DropDown::from_strings(labels);
+    dropdown.set_tooltip_text(Some(accessible_label));
+    dropdown.update_property(&[PropertyLabel(accessible_label)]);
+    dropdown
+}
+
+/// Clear the text and every filter.
+fn reset_controls(controls: &SearchControls) {
+    controls.entry.set_text("");
+    controls.format.set_selected(0);
+    controls.quality.set_selected(0);
+    controls.dynamic_range.set_selected(0);
+    controls.decade.set_selected(0);
+}
+
+/// First year of the decade at dropdown position `selected`.
+fn decade_start(selected: u32) -> Option<i32> {
+    let Ok(index) = i32::try_from(selected) else {
+        return None;
+    };
+    if index == 0 {
+        return None;
+    }
+    let start = FIRST_DECADE + (index - 1) * 10;
+    (start <= LAST_DECADE).then_some(start)
+}
+
+/// Publish a changed search and refresh the library views.
+fn publish_search(state: &AppState, search: AlbumSearch) {
+    let changed = state.album_search_tx.send_if_modified(|current| {
+        let changed = *current != search;
+        current.clone_from(&search);
+        changed
+    });
+    if !changed {
+        return;
+    }
+    info!(
+        query = %search.query,
+        facets = search.has_facets(),
+        "Album search changed"
+    );
+    if let Err(e) = state.refresh_tx.send(()) {
+        warn!(error = %e, "Failed to send refresh signal");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::search::decade_start;
+
+    #[test]
+    fn decade_positions_map_to_years() {
+        assert_eq!(decade_start(0), None, "First entry accepts any year");
+        assert_eq!(decade_start(1), Some(1950), "Second entry is the 1950s");
+        assert_eq!(decade_start(3), Some(1970), "Fourth entry is the 1970s");
+        assert_eq!(decade_start(8), Some(2020), "Last entry is the 2020s");
+        assert_eq!(decade_start(9), None, "Positions past the list are ignored");
+    }
+}
@@ -1,5 +1,5 @@
-//! `PreferencesDialog` for library directories, audio device selection,
-//! view preferences, and gapless playback toggle per FR-033.
+//! `PreferencesDialog` for general options, library directories, audio device selection,
+//! view preferences, gapless/crossfade playback options per FR-033, and library statistics.
 
 use std::{path::PathBuf, sync::Arc};
 
@@ -33,20 +33,65 @@ use crate::{
         LibraryDirectory, Storage,
         database::SqliteStorage,
         settings::{
-            ActiveTab::{self, Albums, Artists},
+            ActiveTab::{self, Albums, Artists, Browse},
             ViewMode::{self, Column, Grid},
         },
     },
+    ui::{
+        background_work::build_background_group,
+        cache::build_cache_group,
+        catalog::build_catalog_group,
+        change_detection::build_change_detection_group,
+        channels::build_channels_group,
+        cleanup::build_cleanup_group,
+        cover_art::build_artwork_group,
+        dr_badge_policy::build_dr_badge_group,
+        equalizer::build_equalizer_page,
+        fade::build_fade_row,
+        general::build_general_page,
+        library::{
+            click_action::{build_album_click_row, build_list_activation_row},
+            collaborations::build_split_collaborations_row,
+            cover_size::build_cover_size_row,
+            original_year::build_original_year_row,
+            quality_badge::build_quality_badge_row,
+            sort_articles::add_sort_article_rows,
+        },
+        notifications::build_track_notification_row,
+        output_buffer::build_output_buffer_row,
+        player::{level_meter::build_level_meter_row, waveform::build_waveform_row},
+        relocate::build_move_button,
+        resample_quality::build_resample_quality_row,
+        scrobbling::build_scrobbling_page,
+        signal_path::build_signal_path_group,
+        silence::add_leading_silence_rows,
+        source_rate::{build_follow_rate_row, build_rate_notice_row},
+        statistics::build_statistics_page,
+        symlinks::build_symlinks_row,
+        transitions::{build_crossfade_row, build_gap_row, sync_gap_row},
+    },
 };
 
-/// Remove a library directory by ID in a background task.
-fn spawn_remove_directory(storage: &Arc<SqliteStorage>, dir_id: i64) {
+/// Remove a library directory by ID in a background task and offer to
+/// undo the removal.
+fn spawn_remove_directory(state: &Arc<AppState>, dir_id: i64, path: String) {
     info!(dir_id, "Library directory removed",);
-    let storage = Arc::clone(storage);
+    let state = Arc::clone(state);
     spawn_future_local(async move {
-        if let Err(e) = storage.remove_library_directory(dir_id).await {
+        let snapshot = match state.storage.snapshot_directory(dir_id).await {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                error!(error = %e, "Failed to snapshot library directory");
+                return;
+            }
+        };
+        if let Err(e) = state.storage.remove_library_directory(dir_id).await {
             error!(error = %e, "Failed to remove library directory");
+            return;
         }
+        state
+            .undo
+            .push(format!("Removed library folder {path}"), snapshot);
     });
 }
 
@@ -78,14 +123,18 @@ async fn set_audio_device(state: &Arc<AppState>, idx: u32) {
     if let Err(e) = state.storage.set_audio_device(name).await {
         error!(error = %e, "Failed to save audio device selection");
     }
+    // The device may have its own remembered volume.
+    if let Err(e) = state
+        .playback
+        .set_volume(state.storage.get_settings_volume())
+    {
+        warn!(error = %e, "Failed to restore the device volume");
+    }
 }
 
-/// Build a directory row with a remove button and add it to the group.
-fn add_directory_row(
-    group: &PreferencesGroup,
-    storage: &Arc<SqliteStorage>,
-    dir: &LibraryDirectory,
-) {
+/// Build a directory row with move and remove buttons and add it to the
+/// group.
+fn add_directory_row(group: &PreferencesGroup, state: &Arc<AppState>, dir: &LibraryDirectory) {
     let row = ActionRow::builder()
         .title(&dir.path)
         .activatable_widget(group)
@@ -94,14 +143,16 @@ fn add_directory_row(
         .label("Remove")
         .css_classes(["destructive-action", "flat"])
         .build();
+    row.add_suffix(&build_move_button(state, &row));
     row.add_suffix(&remove_btn);
     row.set_activatable_widget(Some(&remove_btn));
 
-    let storage = Arc::clone(storage);
+    let state = Arc::clone(state);
     let dir_id = dir.id;
+    let path = dir.path.clone();
     let row_clone = row.clone();
     remove_btn.connect_clicked(move |_| {
-        spawn_remove_directory(&storage, dir_id);
+        spawn_remove_directory(&state, dir_id, path.clone());
         row_clone.set_visible(false);
     });
 
@@ -132,9 +183,13 @@ pub fn show_preferences_dialog(state: &Arc<AppState>, parent: &Window) {
     let dialog = PreferencesDialog::new();
     dialog.set_search_enabled(false);
 
+    build_general_page(&dialog, state);
     build_library_page(&dialog, state, parent);
     build_audio_page(&dialog, state);
+    build_equalizer_page(&dialog, state);
+    build_scrobbling_page(&dialog, state);
     build_view_page(&dialog, state);
+    build_statistics_page(&dialog, state);
 
     dialog.present(Some(parent));
 }
@@ -190,7 +245,7 @@ fn build_library_page(dialog: &PreferencesDialog, state: &Arc<AppState>, parent:
         };
 
         for dir in &dirs {
-            add_directory_row(&group_clone, &state_clone.storage, dir);
+            add_directory_row(&group_clone, &state_clone, dir);
         }
     });
 
@@ -199,6 +254,7 @@ fn build_library_page(dialog: &PreferencesDialog, state: &Arc<AppState>, parent:
         .css_classes(["suggested-action"])
         .build();
     group.add(&add_btn);
+    group.add(&build_symlinks_row(state));
 
     let state_clone = Arc::clone(state);
     let parent_clone = parent.clone();
@@ -214,6 +270,12 @@ fn build_library_page(dialog: &PreferencesDialog, state: &Arc<AppState>, parent:
     });
 
     page.add(&group);
+    build_background_group(&page, state);
+    build_change_detection_group(&page, state);
+    build_artwork_group(&page, state);
+    build_cleanup_group(&page, state);
+    build_cache_group(&page, state);
+    build_catalog_group(&page, state);
     dialog.add(&page);
 }
 
@@ -268,8 +330,8 @@ fn build_audio_page(dialog: &PreferencesDialog, state: &Arc<AppState>) {
     let mode_combo = ComboRow::builder()
         .title("Output Mode")
         .subtitle(
-            "Resampled: software volume, sample rate conversion; BitPerfect: no resampling, \
-             hardware volume via ALSA mixer",
+            "Resampled: software volume, sample rate conversion; BitPerfect: no software \
+             processing, hardware volume via ALSA mixer",
         )
         .model(&mode_model)
         .build();
@@ -296,9 +358,15 @@ fn build_audio_page(dialog: &PreferencesDialog, state: &Arc<AppState>) {
     });
 
     output_group.add(&mode_combo);
+    output_group.add(&build_follow_rate_row(state));
+    output_group.add(&build_rate_notice_row(state));
+    output_group.add(&build_resample_quality_row(state));
+    output_group.add(&build_output_buffer_row(state));
     page.add(&output_group);
 
     build_playback_group(&page, state);
+    build_channels_group(&page, state);
+    build_signal_path_group(&page, state);
 
     dialog.add(&page);
 }
@@ -328,21 +396,28 @@ fn build_playback_group(page: &PreferencesPage, state: &Arc<AppState>) {
 
     playback_group.add(&volume_row);
 
+    let gap_row = build_gap_row(state);
     let gapless_row = SwitchRow::new();
     gapless_row.set_title("Gapless Playback");
     gapless_row.set_subtitle("Seamless transitions between tracks");
     gapless_row.set_active(state.storage.get_gapless_enabled());
 
     let state_gapless = Arc::clone(state);
+    let gap_row_gapless = gap_row.clone();
     gapless_row.connect_active_notify(move |row| {
         let enabled = row.is_active();
         if let Err(e) = state_gapless.playback.set_gapless_enabled(enabled) {
             warn!(error = %e, "Failed to toggle gapless playback");
         }
+        sync_gap_row(&gap_row_gapless, &state_gapless);
         spawn_future_local(save_gapless_setting(Arc::clone(&state_gapless), enabled));
     });
 
     playback_group.add(&gapless_row);
+    playback_group.add(&build_crossfade_row(state, &gap_row));
+    playback_group.add(&gap_row);
+    playback_group.add(&build_fade_row(state));
+    add_leading_silence_rows(&playback_group, state);
     page.add(&playback_group);
 }
 
@@ -382,7 +457,7 @@ fn build_view_page(dialog: &PreferencesDialog, state: &Arc<AppState>) {
 
     display_group.add(&view_combo);
 
-    let tab_model = StringList::new(&["Albums", "Artists"]);
+    let tab_model = StringList::new(&["Albums", "Artists", "Browse"]);
     let tab_combo = ComboRow::builder()
         .title("Default Tab")
         .model(&tab_model)
@@ -390,23 +465,17 @@ fn build_view_page(dialog: &PreferencesDialog, state: &Arc<AppState>) {
     tab_combo.set_selected(match state.storage.get_active_tab() {
         Albums => 0,
         Artists => 1,
+        Browse => 2,
     });
 
     let state_tab = Arc::clone(state);
     tab_combo.connect_selected_notify(move |combo| {
-        let tab = if combo.selected() == 0 {
-            Albums
-        } else {
-            Artists
+        let tab = match combo.selected() {
+            1 => Artists,
+            2 => Browse,
+            _ => Albums,
         };
-        info!(
-            active_tab = if matches!(tab, Albums) {
-                "albums"
-            } else {
-                "artists"
-            },
-            "Default tab changed",
-        );
+        info!(active_tab = ?tab, "Default tab changed");
         spawn_future_local(save_tab_setting(Arc::clone(&state_tab), tab));
         state_tab.active_tab_tx.send_if_modified(|current| {
             let changed = *current != tab;
@@ -416,6 +485,17 @@ fn build_view_page(dialog: &PreferencesDialog, state: &Arc<AppState>) {
     });
 
     display_group.add(&tab_combo);
+    display_group.add(&build_cover_size_row(state));
+    display_group.add(&build_quality_badge_row(state));
+    display_group.add(&build_album_click_row(state));
+    display_group.add(&build_list_activation_row(state));
+    display_group.add(&build_original_year_row(state));
+    add_sort_article_rows(&display_group, state);
+    display_group.add(&build_split_collaborations_row(state));
+    display_group.add(&build_waveform_row(state));
+    display_group.add(&build_level_meter_row(state));
+    display_group.add(&build_track_notification_row(state));
     page.add(&display_group);
+    build_dr_badge_group(&page, state);
     dialog.add(&page);
 }
@@ -0,0 +1,303 @@
+//! Configurable keyboard shortcuts for the main window, the mini player and
+//! the now playing view.
+//!
+//! Bindings come from [`ShortcutSettings`] and are rebuilt whenever the
+//! settings change. The handler runs in the capture phase so list and grid
+//! views cannot swallow arrow keys, but steps aside while a text field has
+//! focus so typing in the search bar is never hijacked.
+
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use {
+    libadwaita::{
+        ApplicationWindow,
+        gdk::{Key, ModifierType},
+        glib::{
+            Propagation::{Proceed, Stop},
+            WeakRef,
+            object::Cast,
+            spawn_future_local,
+        },
+        gtk::{
+            Editable, EventControllerKey, PropagationPhase::Capture,
+            accelerator_get_default_mod_mask, accelerator_parse,
+        },
+        prelude::{EventControllerExt, GtkWindowExt, ObjectExt, WidgetExt},
+    },
+    tracing::{error, info, warn},
+};
+
+use crate::{
+    app::AppState,
+    config::shortcuts::{
+        ShortcutAction::{
+            self, GoToPlaying, MiniPlayer, NextTrack, NowPlaying, PlayPause, PreviousTrack,
+            SeekBackward, SeekBackwardLong, SeekForward, SeekForwardLong, VolumeDown, VolumeUp,
+            ZoomIn, ZoomOut,
+        },
+        ShortcutSettings,
+    },
+    playback::{PlaybackError, control::PlaybackController, engine::PlaybackEngine},
+    storage::zoom::ZoomLevel,
+    ui::{
+        library::album_tiles::step_zoom_level,
+        player::{
+            go_to::{PlayingTarget, go_to_playing},
+            mini::toggle_mini_player,
+            now_playing::toggle_now_playing,
+        },
+    },
+};
+
+/// Seek distance of the seek shortcuts in seconds.
+const SEEK_STEP_SECONDS: f64 = 5.0;
+
+/// Seek distance of the longer (Shift) seek shortcuts in seconds.
+const LONG_SEEK_STEP_SECONDS: f64 = 30.0;
+
+/// Volume change of the volume shortcuts, as a fraction of full scale.
+const VOLUME_STEP: f64 = 0.05;
+
+/// Parsed shortcut bindings, ready to match key presses.
+#[derive(Debug, Default)]
+pub struct KeyBindings {
+    /// Key, modifiers and action of every bound shortcut.
+    entries: Vec<(Key, ModifierType, ShortcutAction)>,
+}
+
+impl KeyBindings {
+    /// Parse the accelerators of `settings`, skipping invalid ones.
+    ///
+    /// Conflicting bindings are logged; the action listed first wins.
+    #[must_use]
+    pub fn from_settings(settings: &ShortcutSettings) -> Self {
+        for (action, other) in settings.conflicts() {
+            warn!(action = ?action, other = ?other, "Shortcut bound to two actions");
+        }
+        let entries = ShortcutAction::ALL
+            .into_iter()
+            .filter_map(|action| parse_binding(action, settings.accelerator(action)?))
+            .collect();
+        Self { entries }
+    }
+
+    /// Action bound to a key press, if any.
+    #[must_use]
+    pub fn action_for(&self, key: Key, modifiers: ModifierType) -> Option<ShortcutAction> {
+        let key = key.to_lower();
+        let modifiers = modifiers & accelerator_get_default_mod_mask();
+        self.entries
+            .iter()
+            .find(|(k, m, _)| *k == key && *m == modifiers)
+            .map(|(_, _, action)| *action)
+    }
+}
+
+/// Attach the configurable shortcut handler to a window.
+pub fn install_shortcuts(window: &ApplicationWindow, state: &Arc<AppState>) {
+    let bindings = Rc::new(RefCell::new(KeyBindings::from_settings(
+        &state.shortcuts_tx.borrow(),
+    )));
+
+    let controller = EventControllerKey::new();
+    controller.set_propagation_phase(Capture);
+
+    let key_state = Arc::clone(state);
+    let key_bindings = Rc::clone(&bindings);
+    let window_ref = window.downgrade();
+    controller.connect_key_pressed(move |_, key, _, modifiers| {
+        if is_typing(&window_ref) {
+            return Proceed;
+        }
+        let Some(action) = key_bindings.borrow().action_for(key, modifiers) else {
+            return Proceed;
+        };
+        info!(action = ?action, "Keyboard shortcut pressed");
+        if let Err(e) = run_action(action, &key_state, &window_ref) {
+            error!(error = %e, action = ?action, "Failed to handle keyboard shortcut");
+        }
+        Stop
+    });
+    window.add_controller(controller);
+
+    let mut rx = state.shortcuts_tx.subscribe();
+    spawn_future_local(async move {
+        while rx.changed().await.is_ok() {
+            let updated = KeyBindings::from_settings(&rx.borrow_and_update());
+            *bindings.borrow_mut() = updated;
+            info!("Keyboard shortcuts reloaded");
+        }
+    });
+}
+
+/// Parse one accelerator, logging and skipping it if GTK rejects it.
+fn parse_binding(
+    action: ShortcutAction,
+    accelerator: &str,
+) -> Option<(Key, ModifierType, ShortcutAction)> {
+    let Some((key, modifiers)) = accelerator_parse(accelerator) else {
+        warn!(accelerator, action = ?action, "Ignoring invalid shortcut");
+        return None;
+    };
+    Some((key.to_lower(), modifiers, action))
+}
+
+/// Whether a text field of the window has keyboard focus.
+fn is_typing(window: &WeakRef<ApplicationWindow>) -> bool {
+    window
+        .upgrade()
+        .and_then(|w| w.focus())
+        .is_some_and(|focus| focus.is::<Editable>())
+}
+
+/// Run a shortcut action against the playback engine, the library view or
+/// the window it was pressed in.
+///
+/// # Errors
+///
+/// Returns the underlying [`PlaybackError`] if the engine rejects it.
+fn run_action(
+    action: ShortcutAction,
+    state: &Arc<AppState>,
+    window: &WeakRef<ApplicationWindow>,
+) -> Result<(), PlaybackError> {
+    let playback = &state.playback;
+    match action {
+        GoToPlaying => {
+            spawn_future_local(go_to_playing(Arc::clone(state), PlayingTarget::Album));
+            Ok(())
+        }
+        MiniPlayer => {
+            if let Some(window) = window.upgrade() {
+                toggle_mini_player(window.upcast_ref(), state);
+            }
+            Ok(())
+        }
+        NextTrack => playback.next_track(),
+        NowPlaying => {
+            if let Some(window) = window.upgrade() {
+                toggle_now_playing(window.upcast_ref(), state);
+            }
+            Ok(())
+        }
+        PlayPause => playback.toggle_pause(),
+        PreviousTrack => playback.previous_track(),
+        SeekBackward => seek_by(playback, -SEEK_STEP_SECONDS),
+        SeekBackwardLong => seek_by(playback, -LONG_SEEK_STEP_SECONDS),
+        SeekForward => seek_by(playback, SEEK_STEP_SECONDS),
+        SeekForwardLong => seek_by(playback, LONG_SEEK_STEP_SECONDS),
+        VolumeDown => step_volume(playback, -VOLUME_STEP),
+        VolumeUp => step_volume(playback, VOLUME_STEP),
+        ZoomIn => {
+            step_zoom_level(state, ZoomLevel::zoom_in);
+            Ok(())
+        }
+        ZoomOut => {
+            step_zoom_level(state, ZoomLevel::zoom_out);
+            Ok(())
+        }
+    }
+}
+
+/// Seek relative to the current position; does nothing when idle.
+///
+/// # Errors
+///
+/// Returns the underlying [`PlaybackError`] if the seek is rejected.
+fn seek_by(playback: &PlaybackEngine, delta_seconds: f64) -> Result<(), PlaybackError> {
+    let state = playback.state();
+    if state.current_track_id.is_none() {
+        return Ok(());
+    }
+    playback.seek_to((state.elapsed_seconds + delta_seconds).max(0.0))
+}
+
+/// Change the volume by `delta`, staying within full scale.
+///
+/// # Errors
+///
+/// Returns the underlying [`PlaybackError`] if the volume is rejected.
+fn step_volume(playback: &PlaybackEngine, delta: f64) -> Result<(), PlaybackError> {
+    let volume = playback.state().volume;
+    playback.set_volume((volume + delta).clamp(0.0, 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use libadwaita::gdk::{Key, ModifierType};
+
+    use crate::{
+        config::shortcuts::{
+            ShortcutAction::{
+                GoToPlaying, NextTrack, PlayPause, SeekForward, SeekForwardLong, VolumeUp,
+            },
+            ShortcutSettings,
+        },
+        ui::shortcuts::KeyBindings,
+    };
+
+    #[test]
+    #[ignore = "Requires GTK initialization (display server)"]
+    fn default_bindings_match_key_presses() {
+        let bindings = KeyBindings::from_settings(&ShortcutSettings::default());
+        assert_eq!(
+            bindings.action_for(Key::space, ModifierType::empty()),
+            Some(PlayPause),
+            "Space toggles playback"
+        );
+        assert_eq!(
+            bindings.action_for(Key::Right, ModifierType::empty()),
+            Some(SeekForward),
+            "Right seeks forward"
+        );
+        assert_eq!(
+            bindings.action_for(Key::Right, ModifierType::CONTROL_MASK),
+            Some(NextTrack),
+            "Control+Right skips"
+        );
+        assert_eq!(
+            bindings.action_for(Key::Right, ModifierType::SHIFT_MASK),
+            Some(SeekForwardLong),
+            "Shift+Right seeks further"
+        );
+        assert_eq!(
+            bindings.action_for(Key::Up, ModifierType::empty()),
+            Some(VolumeUp),
+            "Up raises the volume"
+        );
+        assert_eq!(
+            bindings.action_for(Key::j, ModifierType::CONTROL_MASK),
+            Some(GoToPlaying),
+            "Control+J opens the playing album"
+        );
+        assert_eq!(
+            bindings.action_for(Key::a, ModifierType::empty()),
+            None,
+            "Unbound keys pass through"
+        );
+    }
+
+    #[test]
+    #[ignore = "Requires GTK initialization (display server)"]
+    fn rebound_and_invalid_shortcuts() {
+        let mut settings = ShortcutSettings::default();
+        settings.set(PlayPause, "<Control>p");
+        settings.set(NextTrack, "not a key");
+        let bindings = KeyBindings::from_settings(&settings);
+        assert_eq!(
+            bindings.action_for(Key::P, ModifierType::CONTROL_MASK),
+            Some(PlayPause),
+            "Letter case does not matter"
+        );
+        assert_eq!(
+            bindings.action_for(Key::space, ModifierType::empty()),
+            None,
+            "Old binding is released"
+        );
+        assert_eq!(
+            bindings.action_for(Key::Right, ModifierType::CONTROL_MASK),
+            None,
+            "Invalid accelerators are skipped"
+        );
+    }
+}
@@ -0,0 +1,51 @@
+//! Audio > Signal Path group of the preferences dialog.
+//!
+//! Reports what touches the current track on its way to the device. The
+//! report follows playback events for as long as the dialog is open.
+
+use std::sync::Arc;
+
+use libadwaita::{
+    ActionRow, PreferencesGroup, PreferencesPage,
+    glib::{WeakRef, spawn_future_local},
+    prelude::{ActionRowExt, ObjectExt, PreferencesGroupExt, PreferencesPageExt, WidgetExt},
+};
+
+use crate::{app::AppState, playback::control::PlaybackController};
+
+/// Build the Signal Path group reporting what touches the current track.
+pub fn build_signal_path_group(page: &PreferencesPage, state: &Arc<AppState>) {
+    let signal_group = PreferencesGroup::new();
+    signal_group.set_title("Signal Path");
+    signal_group.set_description(Some(
+        "Bit-perfect output needs bit-perfect mode and the track's native sample rate",
+    ));
+
+    let report_row = ActionRow::builder()
+        .title("Current Track")
+        .subtitle(state.playback.signal_path_report().summary())
+        .build();
+    let state_map = Arc::clone(state);
+    report_row.connect_map(move |row| {
+        row.set_subtitle(&state_map.playback.signal_path_report().summary());
+    });
+    spawn_future_local(follow_signal_path(
+        report_row.downgrade(),
+        Arc::clone(state),
+    ));
+
+    signal_group.add(&report_row);
+    page.add(&signal_group);
+}
+
+/// Refresh the signal path report on every playback event until the row
+/// is gone.
+async fn follow_signal_path(row: WeakRef<ActionRow>, state: Arc<AppState>) {
+    let events = state.playback.subscribe();
+    while events.recv().await.is_ok() {
+        let Some(row) = row.upgrade() else {
+            break;
+        };
+        row.set_subtitle(&state.playback.signal_path_report().summary());
+    }
+}
@@ -0,0 +1,110 @@
+//! Audio > Playback rows for skipping leading silence.
+//!
+//! A switch turns the skip on and a spin row sets the level below which the
+//! opening of a track counts as silence. The threshold is only editable
+//! while the skip is on.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        PreferencesGroup, SpinRow, SwitchRow,
+        glib::spawn_future_local,
+        gtk::Adjustment,
+        prelude::{ActionRowExt, ObjectExt, PreferencesGroupExt, PreferencesRowExt, WidgetExt},
+    },
+    num_traits::cast::cast,
+    tracing::{error, warn},
+};
+
+use crate::{
+    app::AppState,
+    playback::{control::PlaybackController, silence::SILENCE_THRESHOLD_DB_RANGE},
+    storage::database::SqliteStorage,
+};
+
+/// Add the leading silence switch and threshold rows to `group`.
+pub fn add_leading_silence_rows(group: &PreferencesGroup, state: &Arc<AppState>) {
+    let enabled = state.storage.get_skip_leading_silence();
+    let switch_row = SwitchRow::new();
+    switch_row.set_title("Skip Leading Silence");
+    switch_row.set_subtitle(
+        "Start tracks at their first audible sound, except within gapless albums and in \
+         bit-perfect mode",
+    );
+    switch_row.set_active(enabled);
+
+    let threshold_row = build_threshold_row(state);
+    threshold_row.set_sensitive(enabled);
+
+    let state_switch = Arc::clone(state);
+    let threshold_switch = threshold_row.clone();
+    switch_row.connect_active_notify(move |row| {
+        let enabled = row.is_active();
+        threshold_switch.set_sensitive(enabled);
+        apply_leading_silence(&state_switch, enabled, threshold_db(&threshold_switch));
+        spawn_future_local(save_skip_setting(
+            Arc::clone(&state_switch.storage),
+            enabled,
+        ));
+    });
+
+    group.add(&switch_row);
+    group.add(&threshold_row);
+}
+
+/// Build the silence threshold row in dBFS.
+fn build_threshold_row(state: &Arc<AppState>) -> SpinRow {
+    let start = f64::from(*SILENCE_THRESHOLD_DB_RANGE.start());
+    let end = f64::from(*SILENCE_THRESHOLD_DB_RANGE.end());
+    let initial = f64::from(state.storage.get_leading_silence_db());
+    let adjustment = Adjustment::new(initial, start, end, 1.0, 10.0, 0.0);
+    let row = SpinRow::builder()
+        .title("Silence Threshold")
+        .subtitle("Level in dBFS below which the opening of a track is skipped")
+        .adjustment(&adjustment)
+        .digits(0)
+        .build();
+
+    let state_row = Arc::clone(state);
+    row.connect_notify_local(Some("value"), move |row, _| {
+        let threshold_db = threshold_db(row);
+        let enabled = state_row.playback.state().leading_silence_db.is_some();
+        apply_leading_silence(&state_row, enabled, threshold_db);
+        spawn_future_local(save_threshold_setting(
+            Arc::clone(&state_row.storage),
+            threshold_db,
+        ));
+    });
+
+    row
+}
+
+/// Hand the skip setting to the playback engine.
+fn apply_leading_silence(state: &AppState, enabled: bool, threshold_db: i32) {
+    if let Err(e) = state
+        .playback
+        .set_leading_silence_db(enabled.then_some(threshold_db))
+    {
+        warn!(error = %e, "Failed to set leading silence skip from preferences");
+    }
+}
+
+/// Threshold chosen in `row`, in dBFS.
+fn threshold_db(row: &SpinRow) -> i32 {
+    cast(row.value()).unwrap_or(*SILENCE_THRESHOLD_DB_RANGE.start())
+}
+
+/// Persist whether leading silence is skipped, logging on failure.
+async fn save_skip_setting(storage: Arc<SqliteStorage>, enabled: bool) {
+    if let Err(e) = storage.set_skip_leading_silence(enabled).await {
+        error!(error = %e, "Failed to save leading silence skip setting");
+    }
+}
+
+/// Persist the silence threshold, logging on failure.
+async fn save_threshold_setting(storage: Arc<SqliteStorage>, threshold_db: i32) {
+    if let Err(e) = storage.set_leading_silence_db(threshold_db).await {
+        error!(error = %e, "Failed to save silence threshold");
+    }
+}
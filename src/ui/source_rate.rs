@@ -0,0 +1,77 @@
+//! Audio > Output rows for running the device at each track's sample rate.
+//!
+//! When the device supports the rate of the track, it is switched to it
+//! and only the other tracks are resampled. A second switch announces
+//! each switch of the device rate in a toast.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        SwitchRow,
+        glib::spawn_future_local,
+        prelude::{ActionRowExt, PreferencesRowExt},
+    },
+    tracing::{error, warn},
+};
+
+use crate::{
+    app::AppState, playback::control::PlaybackController, storage::database::SqliteStorage,
+};
+
+/// Build the row switching the device to each track's sample rate.
+pub fn build_follow_rate_row(state: &Arc<AppState>) -> SwitchRow {
+    let follow_row = SwitchRow::new();
+    follow_row.set_title("Match Source Sample Rate");
+    follow_row.set_subtitle(
+        "Switch the device to each track's sample rate when supported, resampling only \
+         the rest",
+    );
+    follow_row.set_active(state.storage.get_follow_source_rate());
+
+    let state_follow = Arc::clone(state);
+    follow_row.connect_active_notify(move |row| {
+        let enabled = row.is_active();
+        if let Err(e) = state_follow.playback.set_follow_source_rate(enabled) {
+            warn!(error = %e, "Failed to toggle sample-rate follow");
+        }
+        spawn_future_local(save_follow_rate_setting(
+            Arc::clone(&state_follow.storage),
+            enabled,
+        ));
+    });
+
+    follow_row
+}
+
+/// Persist sample-rate follow setting, logging on failure.
+async fn save_follow_rate_setting(storage: Arc<SqliteStorage>, enabled: bool) {
+    if let Err(e) = storage.set_follow_source_rate(enabled).await {
+        error!(error = %e, "Failed to save sample-rate follow setting");
+    }
+}
+
+/// Build the switch announcing output sample rate changes in a toast.
+pub fn build_rate_notice_row(state: &Arc<AppState>) -> SwitchRow {
+    let notice_row = SwitchRow::new();
+    notice_row.set_title("Announce Sample Rate Changes");
+    notice_row.set_subtitle("Show a notification when the device switches to another rate");
+    notice_row.set_active(state.storage.get_notify_rate_changes());
+
+    let storage = Arc::clone(&state.storage);
+    notice_row.connect_active_notify(move |row| {
+        spawn_future_local(save_rate_notice_setting(
+            Arc::clone(&storage),
+            row.is_active(),
+        ));
+    });
+
+    notice_row
+}
+
+/// Persist the rate change notice setting, logging on failure.
+async fn save_rate_notice_setting(storage: Arc<SqliteStorage>, enabled: bool) {
+    if let Err(e) = storage.set_notify_rate_changes(enabled).await {
+        error!(error = %e, "Failed to save rate change notice setting");
+    }
+}
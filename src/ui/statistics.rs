@@ -0,0 +1,174 @@
+//! Library statistics page for the preferences dialog.
+//!
+//! Shows album, artist, and track counts, total playtime and size on disk,
+//! and a per-format breakdown. The numbers come from the cached
+//! [`SqliteStorage::stats`](crate::storage::database::SqliteStorage::stats),
+//! so opening the page does not re-aggregate an unchanged library.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        ActionRow, PreferencesDialog, PreferencesGroup, PreferencesPage,
+        glib::{format_size, spawn_future_local},
+        prelude::{
+            ActionRowExt, PreferencesDialogExt, PreferencesGroupExt, PreferencesPageExt, WidgetExt,
+        },
+    },
+    num_traits::NumCast,
+    tracing::error,
+};
+
+use crate::{
+    app::AppState,
+    storage::stats::{FormatStats, LibraryStats},
+};
+
+/// Overview rows filled in once the statistics have loaded.
+struct OverviewRows {
+    /// Number of albums.
+    albums: ActionRow,
+    /// Number of artists.
+    artists: ActionRow,
+    /// Number of tracks and the lossless share.
+    tracks: ActionRow,
+    /// Total playtime.
+    playtime: ActionRow,
+    /// Total size on disk.
+    size: ActionRow,
+}
+
+impl OverviewRows {
+    /// Create the rows with a loading placeholder and add them to `group`.
+    fn new(group: &PreferencesGroup) -> Self {
+        let rows = Self {
+            albums: stat_row("Albums"),
+            artists: stat_row("Artists"),
+            tracks: stat_row("Tracks"),
+            playtime: stat_row("Total Playtime"),
+            size: stat_row("Size on Disk"),
+        };
+        for row in [
+            &rows.albums,
+            &rows.artists,
+            &rows.tracks,
+            &rows.playtime,
+            &rows.size,
+        ] {
+            group.add(row);
+        }
+        rows
+    }
+
+    /// Show `stats` in the rows.
+    fn show(&self, stats: &LibraryStats) {
+        self.albums.set_subtitle(&stats.album_count.to_string());
+        self.artists.set_subtitle(&stats.artist_count.to_string());
+        self.tracks.set_subtitle(&format!(
+            "{} ({} lossless)",
+            stats.track_count, stats.lossless_count
+        ));
+        self.playtime
+            .set_subtitle(&format_playtime(stats.total_duration));
+        self.size.set_subtitle(&format_bytes(stats.total_size));
+    }
+}
+
+/// Build the Statistics page.
+pub fn build_statistics_page(dialog: &PreferencesDialog, state: &Arc<AppState>) {
+    let page = PreferencesPage::new();
+    page.set_title("Statistics");
+    page.set_icon_name(Some("document-properties-symbolic"));
+
+    let overview_group = PreferencesGroup::new();
+    overview_group.set_title("Library");
+    let overview = OverviewRows::new(&overview_group);
+
+    let formats_group = PreferencesGroup::new();
+    formats_group.set_title("Formats");
+    formats_group.set_description(Some("Tracks per audio format, most common first"));
+
+    page.add(&overview_group);
+    page.add(&formats_group);
+    dialog.add(&page);
+
+    spawn_future_local(show_stats(Arc::clone(state), overview, formats_group));
+}
+
+/// Load the statistics into the overview rows and the formats group.
+async fn show_stats(state: Arc<AppState>, overview: OverviewRows, formats_group: PreferencesGroup) {
+    match state.storage.stats().await {
+        Ok(stats) => {
+            overview.show(&stats);
+            for format in &stats.formats {
+                formats_group.add(&format_row(format));
+            }
+        }
+        Err(e) => error!(error = %e, "Failed to load library statistics"),
+    }
+}
+
+/// Row showing a statistic below its title.
+fn stat_row(title: &str) -> ActionRow {
+    let row = ActionRow::builder().title(title).subtitle("…").build();
+    row.add_css_class("property");
+    row
+}
+
+/// Row summarising the tracks of one format.
+fn format_row(format: &FormatStats) -> ActionRow {
+    let row = ActionRow::builder()
+        .title(&format.format)
+        .subtitle(format!(
+            "{} tracks · {} · {}",
+            format.track_count,
+            format_playtime(format.total_duration),
+            format_bytes(format.total_size),
+        ))
+        .build();
+    row.add_css_class("property");
+    row
+}
+
+/// Format a byte count for display, e.g. `1.2 TB`.
+fn format_bytes(bytes: i64) -> String {
+    format_size(u64::try_from(bytes).unwrap_or(0)).to_string()
+}
+
+/// Format seconds as days, hours, and minutes, e.g. `3 d 4 h 12 min`.
+fn format_playtime(seconds: f64) -> String {
+    let minutes: u64 = NumCast::from((seconds.max(0.0) / 60.0).round()).unwrap_or(0);
+    let (days, hours, mins) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{days} d {hours} h {mins} min")
+    } else if hours > 0 {
+        format!("{hours} h {mins} min")
+    } else {
+        format!("{mins} min")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::statistics::format_playtime;
+
+    #[test]
+    fn format_playtime_minutes() {
+        assert_eq!(format_playtime(0.0), "0 min");
+        assert_eq!(format_playtime(125.0), "2 min");
+    }
+
+    #[test]
+    fn format_playtime_hours_and_days() {
+        assert_eq!(format_playtime(3.0_f64.mul_add(3600.0, 60.0)), "3 h 1 min");
+        assert_eq!(
+            format_playtime(2.0_f64.mul_add(86_400.0, 3600.0)),
+            "2 d 1 h 0 min"
+        );
+    }
+
+    #[test]
+    fn format_playtime_negative_treated_as_zero() {
+        assert_eq!(format_playtime(-5.0), "0 min");
+    }
+}
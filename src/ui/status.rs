@@ -1,6 +1,8 @@
 //! Status bar with scanning progress indicator.
 //!
 //! Displays scanning progress and status information at the bottom of the window.
+//! The progress bar follows [`AppState::scan_status_tx`]; scan events set
+//! the messages at the start and end of a scan.
 
 use std::sync::Arc;
 
@@ -17,14 +19,16 @@ use {
         },
         prelude::{AccessibleExtManual, BoxExt, WidgetExt},
     },
+    tokio::sync::watch::Receiver as StatusReceiver,
 };
 
 use crate::{
     app::AppState,
-    library::scanner::{
-        ScanEvent,
-        ScanEvent::{ScanCompleted, ScanError, ScanProgress, ScanStarted},
+    library::{
+        scan_status::ScanStatus,
+        scanner::ScanEvent::{self, ScanCompleted, ScanError, ScanStarted},
     },
+    ui::errors::{ErrorReporter, ErrorSource::Library},
 };
 
 /// Status bar showing scanning progress and library information.
@@ -122,34 +126,65 @@ impl StatusBar {
         self.hide_progress();
     }
 
-    /// Subscribe to the scan event channel and update the status bar.
+    /// Subscribe to the scan events and status and update the status bar.
     ///
-    /// Uses `async_channel` which integrates with `GLib`'s main context via
-    /// `spawn_future_local`, unlike `tokio::sync::broadcast` whose wakers
-    /// don't wake the `GLib` main loop.
+    /// The progress bar follows the scan status; the events set the
+    /// messages at the start and end of a scan. Uses `async_channel` which
+    /// integrates with `GLib`'s main context via `spawn_future_local`,
+    /// unlike `tokio::sync::broadcast` whose wakers don't wake the `GLib`
+    /// main loop.
     fn subscribe_to_scan_events(&self, state: &Arc<AppState>) {
         let rx = state.scan_event_rx.clone();
         let status_label = self.status_label.clone();
-        let progress_bar = self.progress_bar.clone();
+        let reporter = state.error_reporter.clone();
 
         spawn_future_local(async move {
-            Self::run_scan_event_loop(rx, &status_label, &progress_bar).await;
+            Self::run_scan_event_loop(rx, &status_label, &reporter).await;
         });
+
+        spawn_future_local(Self::follow_scan_status(
+            state.scan_status_tx.subscribe(),
+            self.status_label.clone(),
+            self.progress_bar.clone(),
+        ));
+    }
+
+    /// Show every change of the scan status until the sender is dropped.
+    async fn follow_scan_status(
+        mut status_rx: StatusReceiver<ScanStatus>,
+        status_label: Label,
+        progress_bar: ProgressBar,
+    ) {
+        while status_rx.changed().await.is_ok() {
+            let status = *status_rx.borrow_and_update();
+            Self::show_scan_status(&status_label, &progress_bar, status);
+        }
+    }
+
+    /// Show the progress of the running scan, hiding the bar when idle.
+    fn show_scan_status(status_label: &Label, progress_bar: &ProgressBar, status: ScanStatus) {
+        progress_bar.set_visible(status.active);
+        progress_bar.set_fraction(status.fraction());
+        if status.active && status.total > 0 {
+            status_label.set_label(&status.label());
+        }
     }
 
     /// Run the scan event loop, processing events until the channel closes.
     async fn run_scan_event_loop(
         rx: Receiver<ScanEvent>,
         status_label: &Label,
-        progress_bar: &ProgressBar,
+        reporter: &ErrorReporter,
     ) {
         while let Ok(event) = rx.recv().await {
-            Self::handle_scan_event(status_label, progress_bar, event);
+            Self::handle_scan_event(status_label, reporter, event);
         }
     }
 
-    /// Apply a single scan event to the status bar widgets.
-    fn handle_scan_event(status_label: &Label, progress_bar: &ProgressBar, event: ScanEvent) {
+    /// Apply a single scan event to the status label.
+    ///
+    /// Scan errors are also shown to the user through `reporter`.
+    fn handle_scan_event(status_label: &Label, reporter: &ErrorReporter, event: ScanEvent) {
         match event {
             ScanStarted { directory } => {
                 let name = directory.file_name().map_or_else(
@@ -157,19 +192,6 @@ impl StatusBar {
                     |n| n.to_string_lossy().to_string(),
                 );
                 status_label.set_label(&format!("Scanning \u{201c}{name}\u{201d}..."));
-                progress_bar.set_visible(true);
-                progress_bar.set_fraction(0.0);
-            }
-            ScanProgress {
-                files_found,
-                files_processed,
-                ..
-            } => {
-                let fraction = f64::from(files_processed) / f64::from(files_found.max(1));
-                progress_bar.set_fraction(fraction);
-                status_label.set_label(&format!(
-                    "Scanning... {files_processed}/{files_found} files"
-                ));
             }
             ScanCompleted {
                 tracks_added,
@@ -179,12 +201,10 @@ impl StatusBar {
                 status_label.set_label(&format!(
                     "Scan complete: {tracks_added} tracks added, {tracks_skipped} skipped"
                 ));
-                progress_bar.set_fraction(1.0);
-                progress_bar.set_visible(false);
             }
             ScanError { error, .. } => {
                 status_label.set_label(&format!("Scan error: {error}"));
-                progress_bar.set_visible(false);
+                reporter.report(Library, &error);
             }
             _ => {}
         }
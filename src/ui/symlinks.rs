@@ -0,0 +1,44 @@
+//! Library > Directories row choosing whether scans follow symbolic links.
+//!
+//! Linked folders are scanned like real ones, and each folder is still
+//! added only once, so links pointing back into the library do not loop.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        SwitchRow,
+        glib::spawn_future_local,
+        prelude::{ActionRowExt, PreferencesRowExt},
+    },
+    tracing::{error, info},
+};
+
+use crate::{app::AppState, storage::database::SqliteStorage};
+
+/// Build the switch choosing whether scans follow symbolic links.
+pub fn build_symlinks_row(state: &Arc<AppState>) -> SwitchRow {
+    let row = SwitchRow::new();
+    row.set_title("Follow Symbolic Links");
+    row.set_subtitle("Scan linked folders and files; each folder is still added only once");
+    row.set_active(state.storage.get_follow_symlinks());
+
+    let state_links = Arc::clone(state);
+    row.connect_active_notify(move |row| {
+        let follow = row.is_active();
+        info!(follow, "Symlink preference changed");
+        state_links.scanner.set_follow_symlinks(follow);
+        spawn_future_local(save_symlinks_setting(
+            Arc::clone(&state_links.storage),
+            follow,
+        ));
+    });
+    row
+}
+
+/// Persist the symlink preference, logging on failure.
+async fn save_symlinks_setting(storage: Arc<SqliteStorage>, follow: bool) {
+    if let Err(e) = storage.set_follow_symlinks(follow).await {
+        error!(error = %e, "Failed to save symlink preference");
+    }
+}
@@ -0,0 +1,204 @@
+//! General > Backup group of the preferences dialog.
+//!
+//! Exports the settings and library folders to a portable JSON file and
+//! imports such a file, e.g. to copy a configuration to another machine.
+//! Folders of an imported file that exist here are added and scanned;
+//! missing ones are listed in a toast instead of failing the import.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use {
+    libadwaita::{
+        ActionRow, PreferencesGroup, PreferencesPage,
+        gio::ListStore,
+        glib::{object::Cast, spawn_future_local},
+        gtk::{Align::Center, Button, FileDialog, FileFilter, Window},
+        prelude::{
+            ActionRowExt, ButtonExt, FileExt, PreferencesGroupExt, PreferencesPageExt, WidgetExt,
+        },
+    },
+    tokio::spawn,
+    tracing::{info, warn},
+};
+
+use crate::{
+    app::AppState,
+    library::scanner::{FsScanner, LibraryScanner},
+    storage::{database::SqliteStorage, transfer::ImportReport},
+};
+
+/// Suggested name of an exported settings file.
+const EXPORT_FILE_NAME: &str = "oxhidifi-settings.json";
+
+/// Build the General > Backup group.
+pub fn build_transfer_group(page: &PreferencesPage, state: &Arc<AppState>) {
+    let group = PreferencesGroup::new();
+    group.set_title("Backup");
+    group.set_description(Some(
+        "Copy settings, library folders, and shortcuts to another machine",
+    ));
+
+    let export_btn = Button::builder().label("Export…").valign(Center).build();
+    let export_row = ActionRow::builder()
+        .title("Export Settings")
+        .subtitle("Save the current configuration to a file")
+        .build();
+    export_row.add_suffix(&export_btn);
+    export_row.set_activatable_widget(Some(&export_btn));
+    let state_export = Arc::clone(state);
+    export_btn.connect_clicked(move |btn| {
+        let parent = btn.root().and_then(|r| r.downcast::<Window>().ok());
+        spawn_future_local(export_settings(Arc::clone(&state_export), parent));
+    });
+
+    let import_btn = Button::builder().label("Import…").valign(Center).build();
+    let import_row = ActionRow::builder()
+        .title("Import Settings")
+        .subtitle("Replace the current configuration with an exported file")
+        .build();
+    import_row.add_suffix(&import_btn);
+    import_row.set_activatable_widget(Some(&import_btn));
+    let state_import = Arc::clone(state);
+    import_btn.connect_clicked(move |btn| {
+        let parent = btn.root().and_then(|r| r.downcast::<Window>().ok());
+        spawn_future_local(import_settings(Arc::clone(&state_import), parent));
+    });
+
+    group.add(&export_row);
+    group.add(&import_row);
+    page.add(&group);
+}
+
+/// Ask for a destination and export the settings there.
+async fn export_settings(state: Arc<AppState>, parent: Option<Window>) {
+    let dialog = settings_file_dialog("Export Settings", "Export");
+    dialog.set_initial_name(Some(EXPORT_FILE_NAME));
+    let file = match dialog.save_future(parent.as_ref()).await {
+        Ok(file) => file,
+        Err(e) => {
+            info!(error = %e, "Settings export cancelled");
+            return;
+        }
+    };
+    let Some(path) = file.path() else {
+        warn!("Selected settings file has no local path");
+        return;
+    };
+    let message = match state.storage.export_settings(&path).await {
+        Ok(()) => {
+            info!(path = %path.display(), "Settings exported");
+            format!("Settings exported to {}", path.display())
+        }
+        Err(e) => {
+            warn!(error = %e, path = %path.display(), "Failed to export settings");
+            format!("Could not export settings: {e}")
+        }
+    };
+    state.send_toast(message).await;
+}
+
+/// Ask for an exported file and import it.
+async fn import_settings(state: Arc<AppState>, parent: Option<Window>) {
+    let dialog = settings_file_dialog("Import Settings", "Import");
+    let file = match dialog.open_future(parent.as_ref()).await {
+        Ok(file) => file,
+        Err(e) => {
+            info!(error = %e, "Settings import cancelled");
+            return;
+        }
+    };
+    let Some(path) = file.path() else {
+        warn!("Selected settings file has no local path");
+        return;
+    };
+    let message = match state.storage.import_settings(&path).await {
+        Ok(report) => {
+            info!(
+                path = %path.display(),
+                added = report.directories_added.len(),
+                missing = report.missing_directories.len(),
+                "Settings imported"
+            );
+            let summary = import_summary(&report);
+            scan_directories(&state, report.directories_added);
+            summary
+        }
+        Err(e) => {
+            warn!(error = %e, path = %path.display(), "Failed to import settings");
+            format!("Could not import settings: {e}")
+        }
+    };
+    state.send_toast(message).await;
+}
+
+/// File dialog filtered to JSON settings files.
+fn settings_file_dialog(title: &str, accept_label: &str) -> FileDialog {
+    let filter = FileFilter::new();
+    filter.set_name(Some("Settings files"));
+    filter.add_mime_type("application/json");
+    filter.add_suffix("json");
+    let filters = ListStore::new::<FileFilter>();
+    filters.append(&filter);
+    FileDialog::builder()
+        .title(title)
+        .accept_label(accept_label)
+        .filters(&filters)
+        .default_filter(&filter)
+        .build()
+}
+
+/// Scan the imported library `directories` and refresh the views.
+fn scan_directories(state: &AppState, directories: Vec<PathBuf>) {
+    if directories.is_empty() {
+        return;
+    }
+    let scanner = Arc::clone(&state.scanner);
+    let refresh_tx = state.refresh_tx.clone();
+    spawn(async move {
+        for dir in directories {
+            scan_directory(&scanner, &dir).await;
+        }
+        if let Err(e) = refresh_tx.send(()) {
+            warn!(error = %e, "Failed to send refresh signal");
+        }
+    });
+}
+
+/// Scan one imported library directory, logging on failure.
+async fn scan_directory(scanner: &FsScanner<SqliteStorage>, dir: &Path) {
+    if let Err(e) = scanner.scan_directory(dir).await {
+        warn!(error = %e, path = %dir.display(), "Failed to scan imported directory");
+    }
+}
+
+/// Toast text describing an import.
+fn import_summary(report: &ImportReport) -> String {
+    let message = "Settings imported; restart Oxhidifi to apply all of them";
+    if report.missing_directories.is_empty() {
+        return message.to_string();
+    }
+    format!(
+        "{message}. Folders not found on this machine: {}",
+        report.missing_directories.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{storage::transfer::ImportReport, ui::transfer::import_summary};
+
+    #[test]
+    fn summary_lists_missing_folders() {
+        let report = ImportReport {
+            directories_added: Vec::new(),
+            missing_directories: vec!["/mnt/music".to_string()],
+        };
+        assert!(
+            import_summary(&report).ends_with("Folders not found on this machine: /mnt/music"),
+            "Missing folders must be named"
+        );
+    }
+}
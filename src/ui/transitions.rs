@@ -0,0 +1,102 @@
+//! Audio > Playback rows for transitions between tracks.
+//!
+//! Crossfade overlaps the end of a track with the start of the next one.
+//! The silence between tracks only applies when gapless playback and
+//! crossfade are off, so its row is insensitive otherwise.
+
+use std::sync::Arc;
+
+use {
+    libadwaita::{
+        SpinRow,
+        glib::spawn_future_local,
+        gtk::Adjustment,
+        prelude::{ObjectExt, WidgetExt},
+    },
+    num_traits::cast::cast,
+    tracing::{error, warn},
+};
+
+use crate::{
+    app::AppState,
+    playback::{control::PlaybackController, gapless::GaplessMode::Disabled},
+    storage::database::SqliteStorage,
+};
+
+/// Build the crossfade window row (milliseconds, `0` disables).
+///
+/// `gap_row` is updated when crossfading is turned on or off.
+pub fn build_crossfade_row(state: &Arc<AppState>, gap_row: &SpinRow) -> SpinRow {
+    let initial_ms = f64::from(state.storage.get_crossfade_ms());
+    let adjustment = Adjustment::new(initial_ms, 0.0, 10_000.0, 100.0, 1000.0, 0.0);
+    let crossfade_row = SpinRow::builder()
+        .title("Crossfade")
+        .subtitle("Overlap between tracks in milliseconds (0 disables)")
+        .adjustment(&adjustment)
+        .digits(0)
+        .build();
+
+    let state_crossfade = Arc::clone(state);
+    let gap_row = gap_row.clone();
+    crossfade_row.connect_notify_local(Some("value"), move |row, _| {
+        let crossfade_ms: u32 = cast(row.value()).unwrap_or(0);
+        if let Err(e) = state_crossfade.playback.set_crossfade_ms(crossfade_ms) {
+            warn!(error = %e, "Failed to set crossfade from preferences");
+        }
+        sync_gap_row(&gap_row, &state_crossfade);
+        spawn_future_local(save_crossfade_setting(
+            Arc::clone(&state_crossfade.storage),
+            crossfade_ms,
+        ));
+    });
+
+    crossfade_row
+}
+
+/// Persist crossfade window, logging on failure.
+async fn save_crossfade_setting(storage: Arc<SqliteStorage>, crossfade_ms: u32) {
+    if let Err(e) = storage.set_crossfade_ms(crossfade_ms).await {
+        error!(error = %e, "Failed to save crossfade setting");
+    }
+}
+
+/// Build the inter-track gap row (milliseconds, `0` disables).
+pub fn build_gap_row(state: &Arc<AppState>) -> SpinRow {
+    let initial_ms = f64::from(state.storage.get_inter_track_gap_ms());
+    let adjustment = Adjustment::new(initial_ms, 0.0, 10_000.0, 250.0, 1000.0, 0.0);
+    let gap_row = SpinRow::builder()
+        .title("Silence Between Tracks")
+        .subtitle(
+            "Pause in milliseconds before the next track, used when gapless playback and \
+             crossfade are off (0 disables)",
+        )
+        .adjustment(&adjustment)
+        .digits(0)
+        .build();
+    sync_gap_row(&gap_row, state);
+
+    let state_gap = Arc::clone(state);
+    gap_row.connect_notify_local(Some("value"), move |row, _| {
+        let gap_ms: u32 = cast(row.value()).unwrap_or(0);
+        if let Err(e) = state_gap.playback.set_inter_track_gap_ms(gap_ms) {
+            warn!(error = %e, "Failed to set inter-track gap from preferences");
+        }
+        spawn_future_local(save_gap_setting(Arc::clone(&state_gap.storage), gap_ms));
+    });
+
+    gap_row
+}
+
+/// Enable the inter-track gap row only while gapless playback and
+/// crossfade are off, as the gap is not used otherwise.
+pub fn sync_gap_row(gap_row: &SpinRow, state: &AppState) {
+    let engine = state.playback.state();
+    gap_row.set_sensitive(engine.gapless_mode == Disabled && engine.crossfade_ms == 0);
+}
+
+/// Persist the inter-track gap, logging on failure.
+async fn save_gap_setting(storage: Arc<SqliteStorage>, gap_ms: u32) {
+    if let Err(e) = storage.set_inter_track_gap_ms(gap_ms).await {
+        error!(error = %e, "Failed to save inter-track gap");
+    }
+}
@@ -42,26 +42,38 @@ use {
 use crate::{
     app::{
         AppState,
-        NavigationEvent::{self, AlbumDetail, ArtistDetail, Back},
+        NavigationEvent::{self, AlbumDetail, ArtistDetail, Back, BrowseAlbums},
     },
     playback::control::PlaybackController,
     storage::{
         database::SqliteStorage,
         settings::{
             ActiveTab,
-            ActiveTab::{Albums, Artists},
+            ActiveTab::{Albums, Artists, Browse},
             ViewMode::{self, Column, Grid},
         },
     },
     ui::{
-        detail::{album::build_album_detail, artist::build_artist_detail},
+        detail::{
+            album::build_album_detail, artist::build_artist_detail, browse::build_browse_detail,
+        },
+        errors::wire_error_reporting,
+        file_drop::install_file_drop,
         header::build_header_controls,
         library::{
             albums::{build_album_grid, lazy_build_album_mode},
             artists::{build_artist_grid, lazy_build_artist_mode},
+            browse::build_browse_view,
             column_view::NarrowState,
         },
-        player::{panel::build_player_content, wire_panel_events},
+        media_keys::install_media_keys,
+        notifications::install_track_notifications,
+        player::{
+            panel::build_player_content, track_info::follow_current_track, wire_panel_events,
+        },
+        queue_undo::listen_for_queue_replacements,
+        search::build_search_bar,
+        shortcuts::install_shortcuts,
         status::StatusBar,
     },
 };
@@ -90,6 +102,14 @@ pub fn build_window(app: &Application, state: &Arc<AppState>) -> ApplicationWind
     window.set_content(Some(&toast_overlay));
 
     listen_for_toasts(state, &toast_overlay);
+    listen_for_undo_offers(state, &toast_overlay);
+    listen_for_queue_replacements(state, &toast_overlay);
+    wire_error_reporting(state);
+    install_media_keys(&window, state);
+    follow_current_track(state);
+    install_track_notifications(&window, state);
+    install_shortcuts(&window, state);
+    install_file_drop(&window, state);
 
     add_responsive_breakpoints(&window, &split_view, &narrow_state);
 
@@ -132,6 +152,14 @@ fn load_hig_css() {
         headerbar {
             transition: background 200ms ease;
         }
+        window.library-drop:drop(active) {
+            outline: 3px solid @accent_color;
+            outline-offset: -3px;
+        }
+        .now-playing-progress trough,
+        .now-playing-progress progress {
+            min-height: 8px;
+        }
         ",
     );
     style_context_add_provider_for_display(
@@ -153,6 +181,51 @@ fn listen_for_toasts(state: &Arc<AppState>, toast_overlay: &ToastOverlay) {
     });
 }
 
+/// Spawn a future showing undoable library actions as toasts with an
+/// "Undo" button.
+fn listen_for_undo_offers(state: &Arc<AppState>, toast_overlay: &ToastOverlay) {
+    let rx = state.undo.offered();
+    let overlay = toast_overlay.clone();
+    let state = Arc::clone(state);
+    spawn_future_local(async move {
+        while let Ok(description) = rx.recv().await {
+            overlay.add_toast(undo_toast(&state, description));
+        }
+    });
+}
+
+/// Toast describing a library action, with an "Undo" button while there is
+/// something to undo.
+fn undo_toast(state: &Arc<AppState>, description: String) -> Toast {
+    let toast = Toast::builder().title(description).priority(Normal).build();
+    if !state.undo.is_empty() {
+        toast.set_button_label(Some("Undo"));
+        let state = Arc::clone(state);
+        toast.connect_button_clicked(move |_| {
+            spawn_future_local(undo_latest(Arc::clone(&state)));
+        });
+    }
+    toast
+}
+
+/// Undo the most recent library action and refresh the library views.
+async fn undo_latest(state: Arc<AppState>) {
+    let message = match state.undo.undo_latest(&state.storage).await {
+        Ok(Some(entry)) => {
+            if let Err(e) = state.refresh_tx.send(()) {
+                warn!(error = %e, "Failed to send refresh signal");
+            }
+            format!("Undone: {}", entry.description)
+        }
+        Ok(None) => "Nothing to undo".to_string(),
+        Err(e) => {
+            warn!(error = %e, "Failed to undo library action");
+            format!("Could not undo: {e}")
+        }
+    };
+    state.send_toast(message).await;
+}
+
 /// Add responsive breakpoints for narrow windows.
 ///
 /// Collapses the `OverlaySplitView` sidebar below 800 px width and
@@ -230,8 +303,14 @@ fn build_content_pane(
     );
     artists_child.set_icon_name(Some("avatar-default-symbolic"));
 
+    let browse_view = build_browse_view(state);
+    let browse_child =
+        stack.add_titled_with_icon(&browse_view, Some("browse"), "Browse", "view-list-symbolic");
+    browse_child.set_icon_name(Some("view-list-symbolic"));
+
     match state.storage.get_active_tab() {
         Artists => stack.set_visible_child_name("artists"),
+        Browse => stack.set_visible_child_name("browse"),
         Albums => {}
     }
 
@@ -243,6 +322,7 @@ fn build_content_pane(
             active_tab_stack.set_visible_child_name(match tab {
                 Albums => "albums",
                 Artists => "artists",
+                Browse => "browse",
             });
         }
     });
@@ -264,15 +344,18 @@ fn build_content_pane(
         .policy(Wide)
         .stack(&stack)
         .can_focus(true)
-        .tooltip_text("Switch between Albums and Artists views")
+        .tooltip_text("Switch between Albums, Artists, and Browse views")
         .build();
     content_header.set_title_widget(Some(&switcher));
 
     let controls = build_header_controls(state, parent);
     content_header.pack_end(&controls);
+    let (search_bar, search_toggle) = build_search_bar(state);
+    content_header.pack_end(&search_toggle);
     content_header.pack_start(toggle_button);
 
     content_toolbar.add_top_bar(&content_header);
+    content_toolbar.add_top_bar(&search_bar);
 
     let content_area = Stack::new();
     content_area.set_vexpand(true);
@@ -286,7 +369,7 @@ fn build_content_pane(
     let switcher_bar = ViewSwitcherBar::builder()
         .stack(&stack)
         .can_focus(true)
-        .tooltip_text("Switch between Albums and Artists views")
+        .tooltip_text("Switch between Albums, Artists, and Browse views")
         .build();
     content_toolbar.add_bottom_bar(&switcher_bar);
 
@@ -419,7 +502,11 @@ fn persist_active_tab(
     active_tab_tx: &TokioSender<ActiveTab>,
     name: &str,
 ) {
-    let tab = if name == "artists" { Artists } else { Albums };
+    let tab = match name {
+        "artists" => Artists,
+        "browse" => Browse,
+        _ => Albums,
+    };
     let s = Arc::clone(storage);
     spawn_future_local(async move {
         if let Err(e) = s.set_active_tab(tab).await {
@@ -460,6 +547,15 @@ fn handle_navigation_event(
             nav_content_area.add_named(&detail, Some("detail"));
             nav_content_area.set_visible_child(&detail);
         }
+        BrowseAlbums(filter) => {
+            info!(filter = %filter.title(), "Navigating to browsed albums");
+            if let Some(prev_detail) = nav_content_area.child_by_name("detail") {
+                nav_content_area.remove(&prev_detail);
+            }
+            let detail = build_browse_detail(nav_state, &filter, nav_tx);
+            nav_content_area.add_named(&detail, Some("detail"));
+            nav_content_area.set_visible_child(&detail);
+        }
         Back => {
             info!("Navigating back to library view");
             nav_content_area.set_visible_child(orig_stack);
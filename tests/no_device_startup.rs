@@ -20,6 +20,7 @@ mod tests {
         library::scanner::{FsScanner, LibraryScanner},
         playback::{engine::PlaybackEngine, output::startup_device_check},
         storage::{Storage, database::SqliteStorage},
+        threading::scheduler::BackgroundScheduler,
     };
 
     fn log_device_check() {
@@ -56,7 +57,12 @@ mod tests {
             );
 
             let (scan_event_tx, _) = unbounded();
-            let scanner = Arc::new(FsScanner::new(Arc::clone(&storage), scan_event_tx, 4));
+            let scheduler = Arc::new(BackgroundScheduler::default());
+            let scanner = Arc::new(FsScanner::new(
+                Arc::clone(&storage),
+                scan_event_tx,
+                scheduler,
+            ));
 
             let dirs = storage
                 .list_library_directories()
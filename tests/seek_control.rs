@@ -6,6 +6,8 @@
 
 #[cfg(test)]
 mod tests {
+    use std::{collections::HashMap, path::PathBuf, thread::sleep};
+
     use anyhow::{Result, bail};
 
     use oxhidifi::playback::{
@@ -14,9 +16,11 @@ mod tests {
         engine::{
             MuteState::{Muted, Unmuted},
             PlaybackEngine,
+            PlaybackEvent::TrackStarted,
             PlaybackStatus::Stopped,
         },
         queue::PlaybackQueue,
+        skip::SKIP_SETTLE,
     };
 
     use oxhidifi::ui::player::panel::format_time;
@@ -178,4 +182,36 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn rapid_next_presses_load_only_the_final_track() -> Result<()> {
+        let engine = PlaybackEngine::new();
+        engine.set_track_paths(
+            (1..=5)
+                .map(|id| (id, PathBuf::from(format!("/nonexistent/{id}.flac"))))
+                .collect::<HashMap<_, _>>(),
+        );
+        engine.play_queue(vec![1, 2, 3, 4, 5])?;
+        let events = engine.subscribe();
+
+        for _ in 0..3 {
+            engine.next_track()?;
+        }
+        if engine.queue().current() != Some(4) {
+            bail!("every press must move the queue at once");
+        }
+        sleep(SKIP_SETTLE * 3);
+
+        let mut started = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            started.extend(match event {
+                TrackStarted { track_id } => Some(track_id),
+                _ => None,
+            });
+        }
+        if started != [4] {
+            bail!("only the final track should load, got {started:?}");
+        }
+        Ok(())
+    }
 }
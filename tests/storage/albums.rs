@@ -0,0 +1,276 @@
+//! Artist and album records: inserting, editing, artwork and years.
+
+use std::path::Path;
+
+use {
+    anyhow::{Context, Result, ensure},
+    tokio::test,
+};
+
+use oxhidifi::storage::{AlbumUpdate, FieldUpdate, NewAlbum, NewArtist, Storage};
+
+use crate::{make_album, make_track, test_storage};
+
+#[test]
+async fn insert_and_get_artist() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let artist_id = storage
+        .insert_artist(NewArtist {
+            name: "Test Artist".to_string(),
+        })
+        .await?;
+    let artist = storage
+        .get_artist(artist_id)
+        .await?
+        .context("artist not found")?;
+
+    ensure!(
+        artist.name == "Test Artist",
+        "unexpected artist name: {}",
+        artist.name
+    );
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn get_all_artists() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+
+    storage
+        .insert_artist(NewArtist {
+            name: "Artist A".to_string(),
+        })
+        .await?;
+    storage
+        .insert_artist(NewArtist {
+            name: "Artist B".to_string(),
+        })
+        .await?;
+
+    let all = storage.get_all_artists().await?;
+    ensure!(all.len() == 2, "expected 2 artists, got {}", all.len());
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn insert_and_get_album() -> anyhow::Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let artist_id = storage
+        .insert_artist(NewArtist {
+            name: "Album Artist".to_string(),
+        })
+        .await?;
+    let album_id = storage
+        .insert_album(NewAlbum {
+            year: Some(2024),
+            genre: Some("Rock".to_string()),
+            ..make_album("Test Album", artist_id)
+        })
+        .await?;
+    let album = storage
+        .get_album(album_id)
+        .await?
+        .context("album not found")?;
+
+    ensure!(
+        album.title == "Test Album",
+        "unexpected album title: {}",
+        album.title
+    );
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn update_album_fields() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let artist_id = storage
+        .insert_artist(NewArtist {
+            name: "Wrong Artist".to_string(),
+        })
+        .await?;
+    let fixed_artist_id = storage
+        .insert_artist(NewArtist {
+            name: "Right Artist".to_string(),
+        })
+        .await?;
+    let album_id = storage
+        .insert_album(NewAlbum {
+            year: Some(1999),
+            genre: Some("Jazz".to_string()),
+            ..make_album("Edited Album", artist_id)
+        })
+        .await?;
+
+    storage
+        .update_album(
+            album_id,
+            AlbumUpdate {
+                artist_id: Some(fixed_artist_id),
+                year: FieldUpdate::Set(2001),
+                genre: FieldUpdate::SetNull,
+                ..AlbumUpdate::default()
+            },
+        )
+        .await?;
+
+    let album = storage
+        .get_album(album_id)
+        .await?
+        .context("album not found after update")?;
+    ensure!(
+        album.artist_id == fixed_artist_id,
+        "unexpected artist id: {}",
+        album.artist_id
+    );
+    ensure!(
+        album.year == Some(2001),
+        "unexpected year: {:?}",
+        album.year
+    );
+    ensure!(album.genre.is_none(), "genre should be cleared");
+    ensure!(
+        album.title == "Edited Album",
+        "title must be untouched: {}",
+        album.title
+    );
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn cover_override_replaces_scanned_artwork_until_cleared() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let artist_id = storage
+        .insert_artist(NewArtist {
+            name: "Cover Artist".to_string(),
+        })
+        .await?;
+    let album_id = storage
+        .insert_album(NewAlbum {
+            artwork_path: Some("/cache/scanned.jpg".to_string()),
+            ..make_album("Covered", artist_id)
+        })
+        .await?;
+
+    storage
+        .set_album_cover(album_id, Some("/cache/chosen.png"))
+        .await?;
+    let album = storage
+        .get_album(album_id)
+        .await?
+        .context("album not found")?;
+    ensure!(
+        album.artwork_path.as_deref() == Some("/cache/chosen.png"),
+        "chosen cover must be shown, got {:?}",
+        album.artwork_path
+    );
+    ensure!(album.cover_override.is_some(), "override must be reported");
+
+    storage.set_album_cover(album_id, None).await?;
+    let album = storage
+        .get_album(album_id)
+        .await?
+        .context("album not found")?;
+    ensure!(
+        album.artwork_path.as_deref() == Some("/cache/scanned.jpg"),
+        "scanned artwork must come back, got {:?}",
+        album.artwork_path
+    );
+    ensure!(album.cover_override.is_none(), "override must be cleared");
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn display_year_falls_back_to_the_tagged_date() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let artist_id = storage
+        .insert_artist(NewArtist {
+            name: "Dated Artist".to_string(),
+        })
+        .await?;
+    // (title, edition year, original year, shown by edition, shown by original)
+    let cases = [
+        ("Reissue", Some(2015), Some(1971), Some(2015), Some(1971)),
+        ("Edition Only", Some(2000), None, Some(2000), Some(2000)),
+        ("Original Only", None, Some(1965), Some(1965), Some(1965)),
+        ("Undated", None, None, None, None),
+    ];
+    for (title, year, original_year, by_edition, by_original) in cases {
+        let album_id = storage
+            .insert_album(NewAlbum {
+                year,
+                original_year,
+                ..make_album(title, artist_id)
+            })
+            .await?;
+        let album = storage
+            .get_album(album_id)
+            .await?
+            .context("album not found")?;
+        ensure!(
+            album.original_year == original_year,
+            "{title}: original year must round-trip"
+        );
+        ensure!(
+            album.display_year(false) == by_edition,
+            "{title}: edition year shown as {:?}",
+            album.display_year(false)
+        );
+        ensure!(
+            album.display_year(true) == by_original,
+            "{title}: original year shown as {:?}",
+            album.display_year(true)
+        );
+    }
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn album_track_relationships() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+
+    let artist_id = storage
+        .insert_artist(NewArtist {
+            name: "Rel Artist".to_string(),
+        })
+        .await?;
+
+    let album_id = storage
+        .insert_album(NewAlbum {
+            year: Some(2024),
+            genre: Some("Jazz".to_string()),
+            format_summary: "FLAC 24-bit/96kHz".to_string(),
+            bit_depth: Some(24),
+            sample_rate: Some(96000),
+            ..make_album("Rel Album", artist_id)
+        })
+        .await?;
+
+    storage
+        .insert_track(make_track(
+            "Track 1",
+            Path::new("/music/r1.flac"),
+            Some(album_id),
+        ))
+        .await?;
+    storage
+        .insert_track(make_track(
+            "Track 2",
+            Path::new("/music/r2.flac"),
+            Some(album_id),
+        ))
+        .await?;
+
+    let tracks = storage.get_tracks_by_album(album_id).await?;
+    ensure!(tracks.len() == 2, "expected 2 tracks, got {}", tracks.len());
+
+    let albums = storage.get_albums_by_artist(artist_id).await?;
+    ensure!(albums.len() == 1, "expected 1 album, got {}", albums.len());
+    drop(dir);
+    Ok(())
+}
@@ -0,0 +1,79 @@
+//! Integration tests for the storage layer (`SqliteStorage` + `Storage` trait).
+
+#[cfg(test)]
+mod albums;
+#[cfg(test)]
+mod maintenance;
+#[cfg(test)]
+mod relocation;
+#[cfg(test)]
+mod search;
+#[cfg(test)]
+mod sorting;
+#[cfg(test)]
+mod tracks;
+
+use std::path::Path;
+
+use {
+    anyhow::{Context, Result},
+    tempfile::{TempDir, tempdir},
+};
+
+use oxhidifi::storage::{NewAlbum, NewTrack, TrackAudio, database::SqliteStorage};
+
+/// Create a temporary `SqliteStorage` instance for testing.
+///
+/// # Errors
+///
+/// Returns an error if the temp directory or database connection cannot be created.
+async fn test_storage() -> Result<(SqliteStorage, TempDir)> {
+    let dir = tempdir().context("failed to create temp dir")?;
+    let db_path = dir.path().join("test.db");
+    let storage = SqliteStorage::connect(&db_path)
+        .await
+        .context("failed to connect to storage")?;
+    Ok((storage, dir))
+}
+
+fn make_track(title: &str, path: &Path, album_id: Option<i64>) -> NewTrack {
+    NewTrack {
+        title: title.to_string(),
+        track_number: Some(1),
+        disc_number: Some(1),
+        disc_total: None,
+        duration: 180.0,
+        audio: TrackAudio {
+            file_path: path.to_string_lossy().to_string(),
+            content_hash: None,
+            format: "FLAC".to_string(),
+            sample_rate: 44100,
+            bit_depth: Some(16),
+            channels: 2,
+            codec: "flac".to_string(),
+            lossless: true,
+            bitrate: None,
+            album_id,
+            artist_id: None,
+            file_size: 1024,
+            last_modified: "2024-01-01T00:00:00Z".to_string(),
+        },
+    }
+}
+
+/// A 16-bit/44.1 kHz FLAC album; tests override the fields they check.
+fn make_album(title: &str, artist_id: i64) -> NewAlbum {
+    NewAlbum {
+        title: title.to_string(),
+        artist_id,
+        year: None,
+        original_year: None,
+        genre: None,
+        artwork_path: None,
+        format_summary: "FLAC 16-bit/44.1kHz".to_string(),
+        lossless: true,
+        format: "FLAC".to_string(),
+        bit_depth: Some(16),
+        sample_rate: Some(44100),
+    }
+}
@@ -0,0 +1,247 @@
+//! Pruning missing files, undoing removals, DR gaps and catalog export.
+
+use std::{
+    fs::{create_dir, read_to_string, write},
+    path::Path,
+};
+
+use {
+    anyhow::{Context, Result, ensure},
+    tokio::test,
+};
+
+use oxhidifi::{
+    library::undo::UndoStack,
+    storage::{NewAlbum, NewArtist, QueueContext, Storage, catalog::CatalogFormat::Csv},
+};
+
+use crate::{make_album, make_track, test_storage};
+
+#[test]
+async fn prune_missing_removes_dead_tracks_and_orphans() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let kept_path = dir.path().join("kept.flac");
+    write(&kept_path, b"fLaC")?;
+
+    let artist_id = storage
+        .insert_artist(NewArtist {
+            name: "Pruned Artist".to_string(),
+        })
+        .await?;
+    let mut album_ids = Vec::new();
+    for title in ["Kept Album", "Gone Album"] {
+        album_ids.push(
+            storage
+                .insert_album(NewAlbum {
+                    bit_depth: None,
+                    sample_rate: None,
+                    ..make_album(title, artist_id)
+                })
+                .await?,
+        );
+    }
+    let kept_id = storage
+        .insert_track(make_track("Kept", &kept_path, Some(album_ids[0])))
+        .await?;
+    let gone_id = storage
+        .insert_track(make_track(
+            "Gone",
+            &dir.path().join("gone.flac"),
+            Some(album_ids[1]),
+        ))
+        .await?;
+    storage
+        .append_queue(gone_id, Some(QueueContext::Manual))
+        .await?;
+
+    ensure!(
+        storage.find_missing_tracks().await? == vec![gone_id],
+        "only the track without a file should be missing"
+    );
+    let report = storage.prune_missing().await?;
+    ensure!(
+        report.tracks_removed == 1 && report.albums_removed == 1,
+        "unexpected prune report: {report:?}"
+    );
+    ensure!(
+        report.artists_removed == 0,
+        "artist with a remaining album must be kept"
+    );
+    ensure!(
+        storage.get_track(kept_id).await?.is_some(),
+        "kept track removed"
+    );
+    ensure!(
+        storage.get_track(gone_id).await?.is_none(),
+        "missing track kept"
+    );
+    ensure!(
+        storage.get_album(album_ids[1]).await?.is_none(),
+        "orphaned album kept"
+    );
+    ensure!(storage.get_queue().await?.is_empty(), "queue entry kept");
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn prune_under_roots_keeps_unreachable_tracks() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let library = dir.path().join("library");
+    create_dir(&library)?;
+    let local_id = storage
+        .insert_track(make_track("Local", &library.join("gone.flac"), None))
+        .await?;
+    let share_id = storage
+        .insert_track(make_track(
+            "Share",
+            &dir.path().join("share/song.flac"),
+            None,
+        ))
+        .await?;
+
+    let report = storage.prune_missing_under(&[library]).await?;
+    ensure!(report.tracks_removed == 1, "unexpected report: {report:?}");
+    ensure!(
+        storage.get_track(local_id).await?.is_none(),
+        "missing track under the root kept"
+    );
+    ensure!(
+        storage.get_track(share_id).await?.is_some(),
+        "track outside the roots removed"
+    );
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn undo_restores_removed_tracks_and_albums() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let artist_id = storage
+        .insert_artist(NewArtist {
+            name: "Gone Artist".to_string(),
+        })
+        .await?;
+    let album_id = storage
+        .insert_album(NewAlbum {
+            year: Some(2001),
+            ..make_album("Gone Album", artist_id)
+        })
+        .await?;
+    let track_id = storage
+        .insert_track(make_track(
+            "Gone",
+            &dir.path().join("gone.flac"),
+            Some(album_id),
+        ))
+        .await?;
+
+    let undo = UndoStack::new();
+    let snapshot = storage.snapshot_tracks(&[track_id]).await?;
+    let report = storage.remove_tracks(&[track_id]).await?;
+    ensure!(report.albums_removed == 1, "unexpected report: {report:?}");
+    undo.push("Removed 1 track".to_string(), snapshot);
+
+    let undone = undo.undo_latest(&storage).await?;
+    ensure!(undone.is_some(), "nothing was undone");
+    let track = storage
+        .get_track(track_id)
+        .await?
+        .context("track not restored")?;
+    ensure!(
+        track.title == "Gone" && track.audio.album_id == Some(album_id),
+        "restored track differs: {track:?}"
+    );
+    let album = storage
+        .get_album(album_id)
+        .await?
+        .context("album not restored")?;
+    ensure!(album.artist_id == artist_id, "album artist not restored");
+    ensure!(undo.is_empty(), "undone action left on the stack");
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn undo_restores_removed_directory() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    storage.add_library_directory(Path::new("/music")).await?;
+    let before = storage.list_library_directories().await?;
+    let id = before.first().context("directory not added")?.id;
+
+    let snapshot = storage.snapshot_directory(id).await?;
+    storage.remove_library_directory(id).await?;
+    let restored = storage.restore_snapshot(&snapshot).await?;
+    ensure!(restored == 1, "expected 1 restored row, got {restored}");
+
+    let after = storage.list_library_directories().await?;
+    ensure!(
+        after.len() == 1 && after[0].id == id && after[0].path == before[0].path,
+        "directory not restored: {after:?}"
+    );
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn albums_without_dr_skip_measured_and_empty_albums() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let artist_id = storage
+        .insert_artist(NewArtist {
+            name: "Artist".to_string(),
+        })
+        .await?;
+    let mut albums = Vec::new();
+    for title in ["Measured", "Unmeasured", "Empty"] {
+        let album_id = storage.insert_album(make_album(title, artist_id)).await?;
+        albums.push(album_id);
+    }
+    for (name, album_id) in [("m.flac", albums[0]), ("u.flac", albums[1])] {
+        storage
+            .insert_track(make_track(name, &dir.path().join(name), Some(album_id)))
+            .await?;
+    }
+    storage.set_album_dr(albums[0], Some(12)).await?;
+
+    let ids = storage.get_album_ids_without_dr().await?;
+    ensure!(ids == [albums[1]], "unexpected albums without DR: {ids:?}");
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn export_catalog_lists_every_track() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let artist_id = storage
+        .insert_artist(NewArtist {
+            name: "Nina Simone".to_string(),
+        })
+        .await?;
+    let album_id = storage
+        .insert_album(NewAlbum {
+            year: Some(1965),
+            ..make_album("Pastel Blues", artist_id)
+        })
+        .await?;
+    for title in ["Be My Husband", "Sinnerman, Live"] {
+        let path = dir.path().join(format!("{title}.flac"));
+        storage
+            .insert_track(make_track(title, &path, Some(album_id)))
+            .await?;
+    }
+
+    let path = dir.path().join("catalog.csv");
+    let written = storage.export_catalog(Csv, &path).await?;
+    ensure!(written == 2, "expected 2 tracks, got {written}");
+    let catalog = read_to_string(&path)?;
+    ensure!(
+        catalog.lines().count() == 3,
+        "expected a header and 2 rows: {catalog}"
+    );
+    ensure!(
+        catalog.contains(",Nina Simone,Pastel Blues,\"Sinnerman, Live\",1,1,1965,FLAC,16,44100,"),
+        "tracks without an artist must use the album artist: {catalog}"
+    );
+    drop(dir);
+    Ok(())
+}
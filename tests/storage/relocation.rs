@@ -0,0 +1,75 @@
+//! Library directories and moving them to a new location.
+
+use std::{fs::create_dir, path::Path};
+
+use {
+    anyhow::{Context, Result, ensure},
+    tokio::test,
+};
+
+use oxhidifi::storage::Storage;
+
+use crate::{make_track, test_storage};
+
+#[test]
+async fn library_directories() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+
+    storage.add_library_directory(Path::new("/music")).await?;
+    storage.add_library_directory(Path::new("/audio")).await?;
+
+    let dirs = storage.list_library_directories().await?;
+    ensure!(dirs.len() == 2, "expected 2 dirs, got {}", dirs.len());
+
+    storage.remove_library_directory(dirs[0].id).await?;
+
+    let dirs = storage.list_library_directories().await?;
+    ensure!(dirs.len() == 1, "expected 1 dir, got {}", dirs.len());
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn remap_directory_keeps_tracks() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let old = dir.path().join("old");
+    let new = dir.path().join("new");
+    create_dir(&new)?;
+    let moved = storage
+        .insert_track(make_track("Moved", &old.join("a.flac"), None))
+        .await?;
+    let sibling_path = dir.path().join("old2").join("b.flac");
+    storage
+        .insert_track(make_track("Sibling", &sibling_path, None))
+        .await?;
+    storage.add_library_directory(&old).await?;
+
+    ensure!(
+        storage
+            .remap_directory(&old, &dir.path().join("missing"))
+            .await
+            .is_err(),
+        "a missing target must be rejected"
+    );
+
+    let report = storage.remap_directory(&old, &new).await?;
+    ensure!(report.tracks == 1, "only the moved track: {report:?}");
+    let track = storage
+        .find_by_path(&new.join("a.flac"))
+        .await?
+        .context("moved track not found")?;
+    ensure!(track.id == moved, "the track must keep its ID");
+    ensure!(
+        storage.find_by_path(&sibling_path).await?.is_some(),
+        "a sibling with the same name prefix must not move"
+    );
+    let dirs = storage.list_library_directories().await?;
+    ensure!(
+        dirs.iter()
+            .map(|d| d.path.as_str())
+            .eq([new.to_string_lossy().as_ref()]),
+        "the library directory must move: {dirs:?}"
+    );
+    drop(dir);
+    Ok(())
+}
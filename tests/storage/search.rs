@@ -0,0 +1,314 @@
+//! Track and album search, facets, genres and decades.
+
+use std::path::Path;
+
+use {
+    anyhow::{Result, ensure},
+    tokio::test,
+};
+
+use oxhidifi::storage::{
+    Album, AlbumSearch, NewAlbum, NewArtist, Storage,
+    sort_order::SortOrder::{DateAdded, DrValue, FolderPath, Title, Year},
+};
+
+use crate::{make_album, make_track, test_storage};
+
+#[test]
+async fn track_search() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+
+    storage
+        .insert_track(make_track(
+            "Bohemian Rhapsody",
+            Path::new("/music/queen.flac"),
+            None,
+        ))
+        .await?;
+    storage
+        .insert_track(make_track(
+            "Stairway to Heaven",
+            Path::new("/music/ledzep.flac"),
+            None,
+        ))
+        .await?;
+
+    let results = storage.search_tracks("Bohemian").await?;
+    ensure!(
+        results.len() == 1,
+        "expected 1 result, got {}",
+        results.len()
+    );
+    ensure!(
+        results[0].title == "Bohemian Rhapsody",
+        "unexpected title: {}",
+        results[0].title
+    );
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn search_albums_by_text_and_facets() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let artist_id = storage
+        .insert_artist(NewArtist {
+            name: "Fleetwood Mac".to_string(),
+        })
+        .await?;
+    let mut album_ids = Vec::new();
+    for (title, year) in [("Rumours", 1977), ("Tango in the Night", 1987)] {
+        album_ids.push(
+            storage
+                .insert_album(NewAlbum {
+                    year: Some(year),
+                    ..make_album(title, artist_id)
+                })
+                .await?,
+        );
+    }
+    let mut hires = make_track(
+        "Dreams",
+        Path::new("/music/dreams.flac"),
+        Some(album_ids[0]),
+    );
+    hires.audio.bit_depth = Some(24);
+    hires.audio.sample_rate = 96000;
+    storage.insert_track(hires).await?;
+    let mut lossy = make_track("Big Love", Path::new("/music/big.mp3"), Some(album_ids[1]));
+    lossy.audio.format = "MP3".to_string();
+    lossy.audio.bit_depth = None;
+    storage.insert_track(lossy).await?;
+
+    let titles =
+        |albums: Vec<Album>| -> Vec<String> { albums.into_iter().map(|a| a.title).collect() };
+    let by_artist = storage
+        .search_albums(
+            &AlbumSearch {
+                query: "fleetwood".to_string(),
+                ..AlbumSearch::default()
+            },
+            Title,
+        )
+        .await?;
+    ensure!(by_artist.len() == 2, "artist name must match both albums");
+
+    let seventies_flac = storage
+        .search_albums(
+            &AlbumSearch {
+                formats: vec!["flac".to_string()],
+                min_year: Some(1970),
+                max_year: Some(1979),
+                ..AlbumSearch::default()
+            },
+            Title,
+        )
+        .await?;
+    ensure!(
+        titles(seventies_flac) == ["Rumours"],
+        "format and year facets must narrow to Rumours"
+    );
+
+    let hires_by_track = storage
+        .search_albums(
+            &AlbumSearch {
+                query: "Dreams".to_string(),
+                min_bit_depth: Some(24),
+                min_sample_rate: Some(88200),
+                ..AlbumSearch::default()
+            },
+            Title,
+        )
+        .await?;
+    ensure!(
+        titles(hires_by_track) == ["Rumours"],
+        "track title and resolution facets must match"
+    );
+
+    let none = storage
+        .search_albums(
+            &AlbumSearch {
+                formats: vec!["MP3".to_string()],
+                min_bit_depth: Some(16),
+                ..AlbumSearch::default()
+            },
+            Title,
+        )
+        .await?;
+    ensure!(none.is_empty(), "facets apply to the same track");
+
+    let recent = storage
+        .search_albums(&AlbumSearch::default(), DateAdded)
+        .await?;
+    ensure!(
+        recent.iter().all(|a| a.date_added.is_some()),
+        "inserted albums must record when they were added"
+    );
+    ensure!(
+        titles(recent) == ["Tango in the Night", "Rumours"],
+        "recently added must list the newest album first"
+    );
+    let by_year = storage.search_albums(&AlbumSearch::default(), Year).await?;
+    ensure!(
+        titles(by_year) == ["Rumours", "Tango in the Night"],
+        "year order must list the oldest album first"
+    );
+    let by_folder = storage
+        .search_albums(&AlbumSearch::default(), FolderPath)
+        .await?;
+    ensure!(
+        titles(by_folder) == ["Tango in the Night", "Rumours"],
+        "folder order must follow the file paths"
+    );
+    storage.set_album_dr(album_ids[1], Some(11)).await?;
+    let by_dr = storage
+        .search_albums(&AlbumSearch::default(), DrValue)
+        .await?;
+    ensure!(
+        titles(by_dr) == ["Tango in the Night", "Rumours"],
+        "dynamic range order must list unmeasured albums last"
+    );
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn search_albums_by_dynamic_range_and_format() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let artist_id = storage
+        .insert_artist(NewArtist {
+            name: "Mastering".to_string(),
+        })
+        .await?;
+    // Title, format of its track, measured DR.
+    let albums = [
+        ("Loud", "FLAC", Some(6)),
+        ("Wide", "FLAC", Some(14)),
+        ("Wide Lossy", "MP3", Some(15)),
+        ("Unmeasured", "FLAC", None),
+    ];
+    for (title, format, dr) in albums {
+        let album_id = storage.insert_album(make_album(title, artist_id)).await?;
+        let path = dir.path().join(format!("{title}.{format}"));
+        let mut track = make_track(title, &path, Some(album_id));
+        track.audio.format = format.to_string();
+        storage.insert_track(track).await?;
+        storage.set_album_dr(album_id, dr).await?;
+    }
+
+    let titles =
+        |albums: Vec<Album>| -> Vec<String> { albums.into_iter().map(|a| a.title).collect() };
+    let wide_flac = storage
+        .search_albums(
+            &AlbumSearch {
+                formats: vec!["FLAC".to_string()],
+                min_dr: Some(12),
+                ..AlbumSearch::default()
+            },
+            Title,
+        )
+        .await?;
+    ensure!(
+        titles(wide_flac) == ["Wide"],
+        "DR and format facets must both apply"
+    );
+
+    let loud_flac = storage
+        .search_albums(
+            &AlbumSearch {
+                formats: vec!["FLAC".to_string()],
+                max_dr: Some(7),
+                ..AlbumSearch::default()
+            },
+            Title,
+        )
+        .await?;
+    ensure!(
+        titles(loud_flac) == ["Loud"],
+        "unmeasured albums must not pass a DR bound"
+    );
+
+    let wide = storage
+        .search_albums(
+            &AlbumSearch {
+                min_dr: Some(12),
+                max_dr: Some(20),
+                ..AlbumSearch::default()
+            },
+            Title,
+        )
+        .await?;
+    ensure!(
+        titles(wide) == ["Wide", "Wide Lossy"],
+        "a DR range alone must match every format"
+    );
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn browse_by_genre_and_decade() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let artist_id = storage
+        .insert_artist(NewArtist {
+            name: "Various".to_string(),
+        })
+        .await?;
+    for (title, year, genre) in [
+        ("Kind of Blue", 1959, "Jazz"),
+        ("Thriller", 1982, "Rock; Pop"),
+        ("Nevermind", 1991, "rock"),
+    ] {
+        storage
+            .insert_album(NewAlbum {
+                year: Some(year),
+                genre: Some(genre.to_string()),
+                ..make_album(title, artist_id)
+            })
+            .await?;
+    }
+
+    let genres: Vec<(String, i64)> = storage
+        .get_genres()
+        .await?
+        .into_iter()
+        .map(|g| (g.name, g.album_count))
+        .collect();
+    ensure!(
+        genres
+            == [
+                ("Jazz".to_string(), 1),
+                ("Pop".to_string(), 1),
+                ("Rock".to_string(), 2)
+            ],
+        "unexpected genres: {genres:?}"
+    );
+
+    let rock: Vec<String> = storage
+        .get_albums_by_genre("ROCK", Title)
+        .await?
+        .into_iter()
+        .map(|a| a.title)
+        .collect();
+    ensure!(
+        rock == ["Nevermind", "Thriller"],
+        "genre must match one value of a multi-genre album: {rock:?}"
+    );
+    ensure!(
+        storage.get_albums_by_genre("Roc", Title).await?.is_empty(),
+        "genre must match whole values only"
+    );
+
+    let decades: Vec<i32> = storage
+        .get_decades()
+        .await?
+        .into_iter()
+        .map(|d| d.decade)
+        .collect();
+    ensure!(
+        decades == [1950, 1980, 1990],
+        "unexpected decades: {decades:?}"
+    );
+    drop(dir);
+    Ok(())
+}
@@ -0,0 +1,145 @@
+//! Ordering of artists, albums and library tracks.
+
+use std::path::Path;
+
+use {
+    anyhow::{Result, ensure},
+    tokio::test,
+};
+
+use oxhidifi::storage::{
+    Artist, NewAlbum, NewArtist, Storage,
+    sort_order::ArtistSortOrder::{AlbumCount, Name, TrackCount},
+};
+
+use crate::{make_album, make_track, test_storage};
+
+#[test]
+async fn album_artists_sort_by_counts() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let mut artist_ids = Vec::new();
+    for name in ["Autechre", "Boards of Canada", "Caribou"] {
+        artist_ids.push(
+            storage
+                .insert_artist(NewArtist {
+                    name: name.to_string(),
+                })
+                .await?,
+        );
+    }
+    // Artist, album title, tracks on the album; Caribou has no albums.
+    let albums = [
+        (artist_ids[0], "Amber", 3),
+        (artist_ids[1], "Geogaddi", 1),
+        (artist_ids[1], "Tomorrow's Harvest", 1),
+    ];
+    for (artist_id, title, tracks) in albums {
+        let album_id = storage.insert_album(make_album(title, artist_id)).await?;
+        let album_tracks = (0..tracks)
+            .map(|number| format!("/music/{title}/{number}.flac"))
+            .map(|path| make_track("Track", Path::new(&path), Some(album_id)))
+            .collect();
+        storage.insert_tracks_batch(album_tracks).await?;
+    }
+
+    let names =
+        |artists: &[Artist]| -> Vec<String> { artists.iter().map(|a| a.name.clone()).collect() };
+    let by_name = storage.get_album_artists(Name).await?;
+    ensure!(
+        names(&by_name) == ["Autechre", "Boards of Canada"],
+        "artists without albums are left out"
+    );
+    ensure!(
+        by_name
+            .iter()
+            .map(|a| (a.album_count, a.track_count))
+            .eq([(1, 3), (2, 2)]),
+        "album and track counts must be returned"
+    );
+    let by_albums = storage.get_album_artists(AlbumCount).await?;
+    ensure!(
+        names(&by_albums) == ["Boards of Canada", "Autechre"],
+        "most albums first"
+    );
+    let by_tracks = storage.get_album_artists(TrackCount).await?;
+    ensure!(
+        names(&by_tracks) == ["Autechre", "Boards of Canada"],
+        "most tracks first"
+    );
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn library_track_ids_follow_artist_album_and_number() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let mut albums = Vec::new();
+    for name in ["beta", "Alpha"] {
+        let artist_id = storage
+            .insert_artist(NewArtist {
+                name: name.to_string(),
+            })
+            .await?;
+        let album_id = storage
+            .insert_album(NewAlbum {
+                year: Some(2024),
+                ..make_album(&format!("{name} Album"), artist_id)
+            })
+            .await?;
+        albums.push(album_id);
+    }
+    let loose = storage
+        .insert_track(make_track("Loose", &dir.path().join("loose.flac"), None))
+        .await?;
+    let beta = storage
+        .insert_track(make_track(
+            "B1",
+            &dir.path().join("b1.flac"),
+            Some(albums[0]),
+        ))
+        .await?;
+    let mut second = make_track("A2", &dir.path().join("a2.flac"), Some(albums[1]));
+    second.track_number = Some(2);
+    let alpha_second = storage.insert_track(second).await?;
+    let alpha_first = storage
+        .insert_track(make_track(
+            "A1",
+            &dir.path().join("a1.flac"),
+            Some(albums[1]),
+        ))
+        .await?;
+
+    let ids = storage.get_library_track_ids().await?;
+    ensure!(
+        ids == [alpha_first, alpha_second, beta, loose],
+        "unexpected library order: {ids:?}"
+    );
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn album_titles_sort_naturally_and_ignore_accents() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let artist_id = storage
+        .insert_artist(NewArtist {
+            name: "Artist".to_string(),
+        })
+        .await?;
+    for title in ["Volume 10", "Volume 2", "\u{c9}cho", "Fable"] {
+        storage.insert_album(make_album(title, artist_id)).await?;
+    }
+
+    let titles: Vec<String> = storage
+        .get_all_albums()
+        .await?
+        .into_iter()
+        .map(|a| a.title)
+        .collect();
+    ensure!(
+        titles == ["\u{c9}cho", "Fable", "Volume 2", "Volume 10"],
+        "unexpected album order: {titles:?}"
+    );
+    drop(dir);
+    Ok(())
+}
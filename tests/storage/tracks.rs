@@ -0,0 +1,232 @@
+//! Track records, change detection, duplicates and the play queue.
+
+use std::path::Path;
+
+use {
+    anyhow::{Context, Result, ensure},
+    tokio::test,
+};
+
+use oxhidifi::storage::{NewQueueEntry, QueueContext, Storage, TrackUpdate};
+
+use crate::{make_track, test_storage};
+
+#[test]
+async fn insert_and_get_track() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let track = make_track("Test Track", Path::new("/music/test.flac"), None);
+    let track_id = storage.insert_track(track).await?;
+    let fetched = storage
+        .get_track(track_id)
+        .await?
+        .context("track not found")?;
+
+    ensure!(
+        fetched.title == "Test Track",
+        "unexpected track title: {}",
+        fetched.title
+    );
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn track_crud() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let track = make_track("CRUD Track", Path::new("/music/crud.flac"), None);
+    let track_id = storage.insert_track(track).await?;
+    let fetched = storage
+        .get_track(track_id)
+        .await?
+        .context("track not found")?;
+    ensure!(
+        fetched.title == "CRUD Track",
+        "unexpected title: {}",
+        fetched.title
+    );
+
+    storage
+        .update_track(
+            track_id,
+            TrackUpdate {
+                title: Some("Updated Track".to_string()),
+                ..TrackUpdate::default()
+            },
+        )
+        .await?;
+
+    let updated = storage
+        .get_track(track_id)
+        .await?
+        .context("track not found after update")?;
+    ensure!(
+        updated.title == "Updated Track",
+        "unexpected title after update: {}",
+        updated.title
+    );
+
+    storage.delete_track(track_id).await?;
+
+    ensure!(
+        matches!(storage.get_track(track_id).await, Ok(None)),
+        "track should have been deleted"
+    );
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn unchanged_files_skip_update() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let path = Path::new("/music/a.flac");
+    let modified = "2024-01-01T00:00:00Z";
+    let track_id = storage.insert_track(make_track("A", path, None)).await?;
+
+    ensure!(
+        !storage.needs_update(path, 1024, modified).await?,
+        "same size and mtime should be skipped"
+    );
+    ensure!(
+        storage.needs_update(path, 2048, modified).await?,
+        "a new size should be re-read"
+    );
+    ensure!(
+        storage
+            .needs_update(path, 1024, "2024-02-01T00:00:00Z")
+            .await?,
+        "a new mtime should be re-read"
+    );
+    ensure!(
+        storage
+            .needs_update(Path::new("/music/new.flac"), 1024, modified)
+            .await?,
+        "unknown files should be read"
+    );
+
+    let mut changed = make_track("A (Remastered)", path, None);
+    changed.audio.file_size = 2048;
+    changed.audio.last_modified = "2024-02-01T00:00:00Z".to_string();
+    storage.update_scanned_track(track_id, changed).await?;
+
+    let track = storage
+        .get_track(track_id)
+        .await?
+        .context("track should keep its id")?;
+    ensure!(
+        track.title == "A (Remastered)",
+        "title should be updated, got {}",
+        track.title
+    );
+    ensure!(
+        !storage
+            .needs_update(path, 2048, "2024-02-01T00:00:00Z")
+            .await?,
+        "updated row should record the new size and mtime"
+    );
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn duplicate_detection_by_path() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let path = Path::new("/music/unique.flac");
+    let track = make_track("Unique Path", path, None);
+    storage.insert_track(track).await?;
+    let found = storage
+        .find_by_path(path)
+        .await?
+        .context("track not found by path")?;
+    ensure!(
+        found.audio.file_path == "/music/unique.flac",
+        "unexpected file path: {}",
+        found.audio.file_path
+    );
+
+    ensure!(
+        matches!(
+            storage.find_by_path(Path::new("/nonexistent.flac")).await,
+            Ok(None)
+        ),
+        "nonexistent path should not be found"
+    );
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn duplicate_detection_by_hash() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+    let hash = "abcdef1234567890";
+
+    let mut track1 = make_track("Track 1", Path::new("/music/track1.flac"), None);
+    track1.audio.content_hash = Some(hash.to_string());
+    storage.insert_track(track1).await?;
+
+    let mut track2 = make_track("Track 2", Path::new("/music/track2.flac"), None);
+    track2.audio.content_hash = Some(hash.to_string());
+    storage.insert_track(track2).await?;
+
+    let found = storage.find_by_hash(hash).await?;
+    ensure!(found.len() == 2, "expected 2 tracks, got {}", found.len());
+    drop(dir);
+    Ok(())
+}
+
+#[test]
+async fn queue_operations() -> Result<()> {
+    let (storage, dir) = test_storage().await?;
+
+    let track1_id = storage
+        .insert_track(make_track("Q1", Path::new("/music/q1.flac"), None))
+        .await?;
+    let track2_id = storage
+        .insert_track(make_track("Q2", Path::new("/music/q2.flac"), None))
+        .await?;
+
+    let queue = vec![
+        NewQueueEntry {
+            track_id: track1_id,
+            position: 0,
+            context_type: Some("manual".to_string()),
+            context_id: None,
+        },
+        NewQueueEntry {
+            track_id: track2_id,
+            position: 1,
+            context_type: Some("manual".to_string()),
+            context_id: None,
+        },
+    ];
+
+    storage.set_queue(&queue).await?;
+
+    let entries = storage.get_queue().await?;
+    ensure!(
+        entries.len() == 2,
+        "expected 2 queue entries, got {}",
+        entries.len()
+    );
+    ensure!(
+        entries[0].track_id == track1_id,
+        "expected track_id {track1_id} in queue, got {}",
+        entries[0].track_id
+    );
+
+    storage
+        .append_queue(track1_id, Some(QueueContext::Manual))
+        .await?;
+
+    let entries = storage.get_queue().await?;
+    ensure!(
+        entries.len() == 3,
+        "expected 3 queue entries, got {}",
+        entries.len()
+    );
+
+    storage.clear_queue().await?;
+    let entries = storage.get_queue().await?;
+    ensure!(entries.is_empty(), "queue should be empty");
+    drop(dir);
+    Ok(())
+}